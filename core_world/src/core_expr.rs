@@ -182,6 +182,44 @@ impl fmt::Display for CoreExpr {
     }
 }
 
+/// Number of constructor nodes in a term.
+/// Uses an explicit stack, so arbitrarily deep terms are safe to measure.
+pub fn node_count(expr: &CoreExpr) -> usize {
+    let mut count = 0;
+    let mut pending = vec![expr];
+    while let Some(node) = pending.pop() {
+        count += 1;
+        match node {
+            CoreExpr::Var(_) | CoreExpr::Nat(_) => {}
+            CoreExpr::Lam(body) => pending.push(body),
+            CoreExpr::App(left, right) | CoreExpr::Pair(left, right) => {
+                pending.push(left);
+                pending.push(right);
+            }
+        }
+    }
+    count
+}
+
+/// Number of nodes on the longest root-to-leaf path; a lone leaf has depth 1.
+/// Uses an explicit stack, so arbitrarily deep terms are safe to measure.
+pub fn depth(expr: &CoreExpr) -> usize {
+    let mut deepest = 0;
+    let mut pending = vec![(expr, 1)];
+    while let Some((node, level)) = pending.pop() {
+        deepest = deepest.max(level);
+        match node {
+            CoreExpr::Var(_) | CoreExpr::Nat(_) => {}
+            CoreExpr::Lam(body) => pending.push((body, level + 1)),
+            CoreExpr::App(left, right) | CoreExpr::Pair(left, right) => {
+                pending.push((left, level + 1));
+                pending.push((right, level + 1));
+            }
+        }
+    }
+    deepest
+}
+
 /// Helper function to create a variable expression
 pub fn var(index: usize) -> CoreExpr {
     CoreExpr::Var(index)
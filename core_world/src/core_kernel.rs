@@ -2,6 +2,7 @@
 /// This module contains β-reduction, α-equivalence, and normalization algorithms
 /// Updated to follow formal De Bruijn index rules from corrected documentation
 use crate::core_expr::CoreExpr;
+use std::collections::HashMap;
 
 /// Perform β-reduction on a CoreExpr
 /// Formal β-reduction: (λM) N →β [N/0]M
@@ -481,6 +482,72 @@ pub fn normalize_with_limit(
     Ok(result)
 }
 
+/// Normalize independent subterms on multiple threads
+///
+/// The two components of a `Pair`, and the head and argument of an `App` whose
+/// head is not a lambda, are normalized separately and then recombined. The
+/// decomposition does not depend on `threads`, so the normal form is the same
+/// for any thread count; `threads` only bounds how many run concurrently.
+pub fn normalize_parallel(
+    expr: CoreExpr,
+    step_limit: usize,
+    threads: usize,
+) -> Result<CoreExpr, crate::NormalizationError> {
+    normalize_parallel_inner(expr, step_limit, threads.max(1))
+}
+
+/// Parallel normalization worker with a remaining thread budget
+fn normalize_parallel_inner(
+    expr: CoreExpr,
+    step_limit: usize,
+    threads: usize,
+) -> Result<CoreExpr, crate::NormalizationError> {
+    match expr {
+        CoreExpr::Pair(first, second) => {
+            let (first, second) = normalize_pair_of(*first, *second, step_limit, threads);
+            Ok(CoreExpr::Pair(Box::new(first?), Box::new(second?)))
+        }
+        CoreExpr::App(func, arg) if !matches!(*func, CoreExpr::Lam(_)) => {
+            let (func, arg) = normalize_pair_of(*func, *arg, step_limit, threads);
+            let (func, arg) = (func?, arg?);
+            if matches!(func, CoreExpr::Lam(_)) {
+                // The head normalized to a lambda, so the application is now a redex
+                normalize_with_limit(CoreExpr::App(Box::new(func), Box::new(arg)), step_limit)
+            } else {
+                Ok(CoreExpr::App(Box::new(func), Box::new(arg)))
+            }
+        }
+        other => normalize_with_limit(other, step_limit),
+    }
+}
+
+/// Normalize two independent subterms, splitting the thread budget between them
+fn normalize_pair_of(
+    left: CoreExpr,
+    right: CoreExpr,
+    step_limit: usize,
+    threads: usize,
+) -> (
+    Result<CoreExpr, crate::NormalizationError>,
+    Result<CoreExpr, crate::NormalizationError>,
+) {
+    if threads <= 1 {
+        return (
+            normalize_parallel_inner(left, step_limit, 1),
+            normalize_parallel_inner(right, step_limit, 1),
+        );
+    }
+
+    let left_threads = threads / 2;
+    let right_threads = threads - left_threads;
+    std::thread::scope(|scope| {
+        let handle = scope.spawn(move || normalize_parallel_inner(left, step_limit, left_threads));
+        let right = normalize_parallel_inner(right, step_limit, right_threads);
+        let left = handle.join().expect("normalization thread panicked");
+        (left, right)
+    })
+}
+
 /// Perform η-reduction on a CoreExpr
 /// η-reduction: λx.(f x) →η f (when x is not free in f)
 pub fn eta_reduce(expr: CoreExpr) -> CoreExpr {
@@ -557,6 +624,195 @@ fn contains_free_var(expr: &CoreExpr, target_index: usize) -> bool {
     }
 }
 
+/// η-long normal form, for comparing functions extensionally
+///
+/// The term is β-normalized and η-contracted to its η-short form, then every
+/// variable occurrence is η-expanded until it is applied to as many arguments
+/// as the variable ever receives in the term. The whole term is compared as a
+/// function, so a neutral term at the top is expanded at least once. Terms
+/// that are βη-equal therefore share the same η-long form.
+pub fn eta_long_normal_form(
+    expr: CoreExpr,
+    step_limit: usize,
+) -> Result<CoreExpr, crate::NormalizationError> {
+    let short = eta_short(&normalize_stack_based(expr, step_limit)?);
+
+    let mut arities = HashMap::new();
+    collect_arities(&short, &mut Vec::new(), &mut 0, &mut arities);
+
+    let mut next_binder = 0;
+    let mut binders = Vec::new();
+    let top_expansions = usize::from(!matches!(short, CoreExpr::Lam(_)));
+    Ok(eta_expand(
+        &short,
+        &mut binders,
+        &mut next_binder,
+        &arities,
+        top_expansions,
+    ))
+}
+
+/// A variable identified independently of how many binders enclose it
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum VarKey {
+    /// Bound by the n-th lambda of the term, in pre-order
+    Bound(usize),
+    /// Free, with its De Bruijn index outside the term
+    Free(usize),
+}
+
+/// Resolve a De Bruijn index against the enclosing binders (innermost last)
+fn var_key(index: usize, binders: &[Option<usize>]) -> Option<VarKey> {
+    if index < binders.len() {
+        binders[binders.len() - 1 - index].map(VarKey::Bound)
+    } else {
+        Some(VarKey::Free(index - binders.len()))
+    }
+}
+
+/// η-contract bottom-up: λ.(f 0) becomes f when 0 is not free in f
+fn eta_short(expr: &CoreExpr) -> CoreExpr {
+    match expr {
+        CoreExpr::Lam(body) => match eta_short(body) {
+            CoreExpr::App(func, arg)
+                if *arg == CoreExpr::Var(0) && !contains_free_var(&func, 0) =>
+            {
+                lower_free_vars(*func, 0)
+            }
+            body => CoreExpr::Lam(Box::new(body)),
+        },
+        CoreExpr::App(func, arg) => {
+            CoreExpr::App(Box::new(eta_short(func)), Box::new(eta_short(arg)))
+        }
+        CoreExpr::Pair(first, second) => {
+            CoreExpr::Pair(Box::new(eta_short(first)), Box::new(eta_short(second)))
+        }
+        CoreExpr::Var(_) | CoreExpr::Nat(_) => expr.clone(),
+    }
+}
+
+/// Subtract one from every free variable above `cutoff`
+fn lower_free_vars(expr: CoreExpr, cutoff: usize) -> CoreExpr {
+    match expr {
+        CoreExpr::Var(index) if index > cutoff => CoreExpr::Var(index - 1),
+        CoreExpr::Var(_) | CoreExpr::Nat(_) => expr,
+        CoreExpr::Lam(body) => CoreExpr::Lam(Box::new(lower_free_vars(*body, cutoff + 1))),
+        CoreExpr::App(func, arg) => CoreExpr::App(
+            Box::new(lower_free_vars(*func, cutoff)),
+            Box::new(lower_free_vars(*arg, cutoff)),
+        ),
+        CoreExpr::Pair(first, second) => CoreExpr::Pair(
+            Box::new(lower_free_vars(*first, cutoff)),
+            Box::new(lower_free_vars(*second, cutoff)),
+        ),
+    }
+}
+
+/// Split an application spine into its head and arguments
+fn spine(expr: &CoreExpr) -> (&CoreExpr, Vec<&CoreExpr>) {
+    let mut head = expr;
+    let mut args = Vec::new();
+    while let CoreExpr::App(func, arg) = head {
+        args.push(&**arg);
+        head = func;
+    }
+    args.reverse();
+    (head, args)
+}
+
+/// Record the largest number of arguments each variable is applied to
+fn collect_arities(
+    expr: &CoreExpr,
+    binders: &mut Vec<Option<usize>>,
+    next_binder: &mut usize,
+    arities: &mut HashMap<VarKey, usize>,
+) {
+    match expr {
+        CoreExpr::Lam(body) => {
+            binders.push(Some(*next_binder));
+            *next_binder += 1;
+            collect_arities(body, binders, next_binder, arities);
+            binders.pop();
+        }
+        CoreExpr::Pair(first, second) => {
+            collect_arities(first, binders, next_binder, arities);
+            collect_arities(second, binders, next_binder, arities);
+        }
+        CoreExpr::Nat(_) => {}
+        CoreExpr::Var(_) | CoreExpr::App(_, _) => {
+            let (head, args) = spine(expr);
+            match head {
+                CoreExpr::Var(index) => {
+                    if let Some(key) = var_key(*index, binders) {
+                        let arity = arities.entry(key).or_insert(0);
+                        *arity = (*arity).max(args.len());
+                    }
+                }
+                other => collect_arities(other, binders, next_binder, arities),
+            }
+            for arg in args {
+                collect_arities(arg, binders, next_binder, arities);
+            }
+        }
+    }
+}
+
+/// Expand each variable occurrence to its recorded arity, and a neutral
+/// `expr` by at least `min_expansions` lambdas
+///
+/// Binders are numbered in the same pre-order as [`collect_arities`]; the
+/// lambdas introduced by expansion carry no number.
+fn eta_expand(
+    expr: &CoreExpr,
+    binders: &mut Vec<Option<usize>>,
+    next_binder: &mut usize,
+    arities: &HashMap<VarKey, usize>,
+    min_expansions: usize,
+) -> CoreExpr {
+    match expr {
+        CoreExpr::Lam(body) => {
+            binders.push(Some(*next_binder));
+            *next_binder += 1;
+            let body = eta_expand(body, binders, next_binder, arities, 0);
+            binders.pop();
+            CoreExpr::Lam(Box::new(body))
+        }
+        CoreExpr::Pair(first, second) => CoreExpr::Pair(
+            Box::new(eta_expand(first, binders, next_binder, arities, 0)),
+            Box::new(eta_expand(second, binders, next_binder, arities, 0)),
+        ),
+        CoreExpr::Nat(_) => expr.clone(),
+        CoreExpr::Var(_) | CoreExpr::App(_, _) => {
+            let (head, args) = spine(expr);
+            let arity = match head {
+                CoreExpr::Var(index) => var_key(*index, binders)
+                    .and_then(|key| arities.get(&key).copied())
+                    .unwrap_or(0),
+                _ => 0,
+            };
+            let head = match head {
+                CoreExpr::Var(_) => head.clone(),
+                other => eta_expand(other, binders, next_binder, arities, 0),
+            };
+            let args: Vec<CoreExpr> = args
+                .into_iter()
+                .map(|arg| eta_expand(arg, binders, next_binder, arities, 0))
+                .collect();
+
+            let missing = arity.saturating_sub(args.len()).max(min_expansions);
+            let mut applied = lift_with_amount(head, missing);
+            for arg in args {
+                applied =
+                    CoreExpr::App(Box::new(applied), Box::new(lift_with_amount(arg, missing)));
+            }
+            for index in (0..missing).rev() {
+                applied = CoreExpr::App(Box::new(applied), Box::new(CoreExpr::Var(index)));
+            }
+            (0..missing).fold(applied, |body, _| CoreExpr::Lam(Box::new(body)))
+        }
+    }
+}
+
 /// Normalize with recursion depth tracking
 fn normalize_with_depth(expr: CoreExpr, current_depth: usize, max_depth: usize) -> CoreExpr {
     if current_depth >= max_depth {
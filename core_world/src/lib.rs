@@ -54,11 +54,14 @@ use crate::{core_expr::CoreExpr, proof_checker::Proof};
 pub mod core_expr;
 pub mod core_kernel;
 pub mod proof_checker;
+pub mod shared_expr;
 
 // Re-export helper functions for convenience
 pub use core_expr::{app, lam, nat, pair, var};
+pub use core_expr::{depth, node_count};
 pub use core_kernel::alpha_equiv;
 pub use proof_checker::prove_beta;
+pub use shared_expr::SharedExpr;
 
 /// The primary export: verifies that a proof correctly establishes term equivalence.
 pub fn verify_equivalence(proof: Proof) -> Result<(CoreExpr, CoreExpr), VerifyError> {
@@ -120,6 +123,28 @@ pub fn normalize_stack_based(
     core_kernel::normalize_stack_based(term, step_limit)
 }
 
+/// V2 Parallel normalization: Returns the normal form, normalizing independent
+/// subterms (pair components, stuck application heads and arguments) on up to
+/// `threads` threads. The result does not depend on the thread count.
+pub fn normalize_parallel(
+    term: CoreExpr,
+    step_limit: usize,
+    threads: usize,
+) -> Result<CoreExpr, NormalizationError> {
+    core_kernel::normalize_parallel(term, step_limit, threads)
+}
+
+/// Sharing-preserving normalization: Returns the normal form as a graph in
+/// which every use of a substituted argument points at the same node, so
+/// copying combinators do not duplicate their arguments. Call `to_tree()` on
+/// the result to get a plain `CoreExpr`.
+pub fn normalize_shared(
+    term: &CoreExpr,
+    step_limit: usize,
+) -> Result<SharedExpr, NormalizationError> {
+    shared_expr::normalize_shared(term, step_limit)
+}
+
 /// Public error types.
 #[derive(Debug)]
 pub enum VerifyError {
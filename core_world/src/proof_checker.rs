@@ -5,6 +5,16 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use thiserror::Error;
 
+/// Default bound on proof nesting for `verify` and `deserialize_proof`.
+///
+/// Both walk the proof with an explicit stack, so the bound limits the work
+/// an untrusted proof can demand rather than protecting the native stack.
+/// `prove_normalization` nests one `Trans` per reduction step, so this
+/// admits normalization proofs of up to ten thousand steps; callers can pass
+/// their own bound to `verify_with_max_depth` and
+/// `deserialize_proof_with_max_depth`.
+pub const DEFAULT_MAX_PROOF_DEPTH: usize = 10_000;
+
 /// Proof of equivalence between λ-calculus terms according to CoreSpec v1.0
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Proof {
@@ -53,6 +63,15 @@ pub enum ProofError {
     InvalidCongApp(String),
     #[error("Invalid congruence for abstraction: {0}")]
     InvalidCongLam(String),
+    /// A step of a larger proof failed. `path` gives the child index taken at
+    /// each level from the root to the failing subproof: 0 for the only or
+    /// first child (`proof_a`, `proof_f`), 1 for the second (`proof_b` of
+    /// `Trans`, `proof_a` of `CongApp`). An empty path is the root itself.
+    #[error("Proof step {path:?} failed: {reason}")]
+    StepFailed { path: Vec<usize>, reason: String },
+    /// The proof nests more than the given number of steps deep
+    #[error("Proof exceeds the maximum depth of {0}")]
+    DepthLimitExceeded(usize),
 }
 
 /// Error type for proof serialization/deserialization failures.
@@ -63,6 +82,8 @@ pub enum ProofParseError {
     InvalidTag(u8),
     InvalidLengthPrefix,
     CoreExprParseError(String),
+    /// The encoded proof nests more than the given number of steps deep
+    DepthLimitExceeded(usize),
 }
 
 impl fmt::Display for ProofParseError {
@@ -73,13 +94,134 @@ impl fmt::Display for ProofParseError {
             ProofParseError::InvalidTag(tag) => write!(f, "Invalid tag: {}", tag),
             ProofParseError::InvalidLengthPrefix => write!(f, "Invalid length prefix"),
             ProofParseError::CoreExprParseError(msg) => write!(f, "CoreExpr parse error: {}", msg),
+            ProofParseError::DepthLimitExceeded(max) => {
+                write!(f, "Proof exceeds the maximum depth of {}", max)
+            }
         }
     }
 }
 
 /// Verify a proof and return the pair of equivalent terms it proves.
 /// Signature: `verify(proof: &Proof) -> Result<(CoreExpr, CoreExpr), ProofError>`
+///
+/// On failure the error is `ProofError::StepFailed`, locating the subproof
+/// that broke and carrying the rule violation it reported. Proofs nested
+/// deeper than `DEFAULT_MAX_PROOF_DEPTH` fail with
+/// `ProofError::DepthLimitExceeded`.
 pub fn verify(proof: &Proof) -> Result<(CoreExpr, CoreExpr), ProofError> {
+    verify_with_max_depth(proof, DEFAULT_MAX_PROOF_DEPTH)
+}
+
+/// Verify a proof whose steps nest at most `max_depth` deep.
+///
+/// The root proof is at depth 1. Verification stops with
+/// `ProofError::DepthLimitExceeded` on reaching a subproof below that,
+/// before descending into it.
+pub fn verify_with_max_depth(
+    proof: &Proof,
+    max_depth: usize,
+) -> Result<(CoreExpr, CoreExpr), ProofError> {
+    verify_at(proof, max_depth)
+}
+
+/// Verify `proof` bottom-up, keeping the steps still being verified on an
+/// explicit stack so that deep proofs cannot overflow the native one
+fn verify_at(proof: &Proof, max_depth: usize) -> Result<(CoreExpr, CoreExpr), ProofError> {
+    if max_depth == 0 {
+        return Err(ProofError::DepthLimitExceeded(max_depth));
+    }
+    // Each open step with the number of its subproofs already verified, and
+    // the index of every open step but the root within its parent
+    let mut open: Vec<(&Proof, usize)> = vec![(proof, 0)];
+    let mut path = Vec::new();
+    let mut results = Vec::new();
+
+    while let Some((proof, verified)) = open.last_mut() {
+        let proof: &Proof = proof;
+        let subproofs = subproofs(proof);
+        if let Some(&child) = subproofs.get(*verified) {
+            path.push(*verified);
+            *verified += 1;
+            if path.len() >= max_depth {
+                return Err(ProofError::DepthLimitExceeded(max_depth));
+            }
+            open.push((child, 0));
+            continue;
+        }
+
+        open.pop();
+        let proved = results.split_off(results.len() - subproofs.len());
+        let result = conclude(proof, proved).map_err(|error| ProofError::StepFailed {
+            path: path.clone(),
+            reason: error.to_string(),
+        })?;
+        results.push(result);
+        path.pop();
+    }
+    Ok(results.pop().expect("the root proof was verified"))
+}
+
+/// Direct subproofs of `proof`, in the order their indices in a path count
+fn subproofs(proof: &Proof) -> Vec<&Proof> {
+    match proof {
+        Proof::BetaStep { .. } | Proof::EtaStep { .. } | Proof::Refl(_) => Vec::new(),
+        Proof::Sym(subproof) => vec![subproof],
+        Proof::Trans { proof_a, proof_b } => vec![proof_a, proof_b],
+        Proof::CongApp { proof_f, proof_a } => vec![proof_f, proof_a],
+        Proof::CongLam { proof_b } => vec![proof_b],
+    }
+}
+
+/// The equivalence `proof` establishes, given what its subproofs proved
+fn conclude(
+    proof: &Proof,
+    proved: Vec<(CoreExpr, CoreExpr)>,
+) -> Result<(CoreExpr, CoreExpr), ProofError> {
+    let mut proved = proved.into_iter();
+    let mut next = || proved.next().expect("every subproof was verified");
+    match proof {
+        Proof::BetaStep { .. } | Proof::EtaStep { .. } | Proof::Refl(_) => verify_leaf(proof),
+
+        Proof::Sym(_) => {
+            // Symmetry: if subproof proves A ≡ B, then Sym(subproof) proves B ≡ A
+            let (a, b) = next();
+            Ok((b, a))
+        }
+
+        Proof::Trans { .. } => {
+            // Transitivity: if proof_a proves A ≡ B and proof_b proves B ≡ C, then Trans proves A ≡ C
+            let (a, b) = next();
+            let (c, d) = next();
+            check_middle_terms(&b, &c)?;
+            Ok((a, d))
+        }
+
+        Proof::CongApp { .. } => {
+            // Congruence for application: if proof_f proves F ≡ G and proof_a proves A ≡ B,
+            // then CongApp proves (F A) ≡ (G B)
+            let (f, g) = next();
+            let (a, b) = next();
+
+            let app1 = CoreExpr::App(Box::new(f), Box::new(a));
+            let app2 = CoreExpr::App(Box::new(g), Box::new(b));
+
+            Ok((app1, app2))
+        }
+
+        Proof::CongLam { .. } => {
+            // Congruence for abstraction: if proof_b proves M ≡ N, then CongLam proves (λ.M) ≡ (λ.N)
+            let (m, n) = next();
+
+            let lam1 = CoreExpr::Lam(Box::new(m));
+            let lam2 = CoreExpr::Lam(Box::new(n));
+
+            Ok((lam1, lam2))
+        }
+    }
+}
+
+/// Check a proof step with no subproofs
+fn verify_leaf(proof: &Proof) -> Result<(CoreExpr, CoreExpr), ProofError> {
     match proof {
         Proof::BetaStep { redex, contractum } => {
             // Verify that one β-reduction step transforms redex to contractum
@@ -112,48 +254,19 @@ pub fn verify(proof: &Proof) -> Result<(CoreExpr, CoreExpr), ProofError> {
             Ok((expr.clone(), expr.clone()))
         }
 
-        Proof::Sym(subproof) => {
-            // Symmetry: if subproof proves A ≡ B, then Sym(subproof) proves B ≡ A
-            let (a, b) = verify(subproof)?;
-            Ok((b, a))
-        }
-
-        Proof::Trans { proof_a, proof_b } => {
-            // Transitivity: if proof_a proves A ≡ B and proof_b proves B ≡ C, then Trans proves A ≡ C
-            let (a, b) = verify(proof_a)?;
-            let (c, d) = verify(proof_b)?;
-
-            if alpha_equiv(b.clone(), c.clone()) {
-                Ok((a, d))
-            } else {
-                Err(ProofError::InvalidTransitivity(format!(
-                    "Middle terms don't match: {:?} ≠ {:?}",
-                    b, c
-                )))
-            }
-        }
-
-        Proof::CongApp { proof_f, proof_a } => {
-            // Congruence for application: if proof_f proves F ≡ G and proof_a proves A ≡ B,
-            // then CongApp proves (F A) ≡ (G B)
-            let (f, g) = verify(proof_f)?;
-            let (a, b) = verify(proof_a)?;
-
-            let app1 = CoreExpr::App(Box::new(f.clone()), Box::new(a.clone()));
-            let app2 = CoreExpr::App(Box::new(g.clone()), Box::new(b.clone()));
-
-            Ok((app1, app2))
-        }
-
-        Proof::CongLam { proof_b } => {
-            // Congruence for abstraction: if proof_b proves M ≡ N, then CongLam proves (λ.M) ≡ (λ.N)
-            let (m, n) = verify(proof_b)?;
-
-            let lam1 = CoreExpr::Lam(Box::new(m.clone()));
-            let lam2 = CoreExpr::Lam(Box::new(n.clone()));
+        _ => unreachable!("verify_leaf called on a proof with subproofs"),
+    }
+}
 
-            Ok((lam1, lam2))
-        }
+/// Check that the middle terms of a transitivity step agree
+fn check_middle_terms(b: &CoreExpr, c: &CoreExpr) -> Result<(), ProofError> {
+    if alpha_equiv(b.clone(), c.clone()) {
+        Ok(())
+    } else {
+        Err(ProofError::InvalidTransitivity(format!(
+            "Middle terms don't match: {:?} ≠ {:?}",
+            b, c
+        )))
     }
 }
 
@@ -250,129 +363,136 @@ pub fn prove_normalization(term: CoreExpr, step_limit: usize) -> Result<Proof, P
 /// - CongLam: [0x07, b_bytes...]
 pub fn serialize_proof(proof: &Proof) -> Vec<u8> {
     let mut bytes = Vec::new();
-    match proof {
-        Proof::BetaStep { redex, contractum } => {
-            bytes.push(0x01);
-            bytes.extend_from_slice(&serialize_core_expr(redex));
-            bytes.extend_from_slice(&serialize_core_expr(contractum));
-        }
-        Proof::EtaStep { redex, contractum } => {
-            bytes.push(0x02);
-            bytes.extend_from_slice(&serialize_core_expr(redex));
-            bytes.extend_from_slice(&serialize_core_expr(contractum));
-        }
-        Proof::Refl(expr) => {
-            bytes.push(0x03);
-            bytes.extend_from_slice(&serialize_core_expr(expr));
-        }
-        Proof::Sym(subproof) => {
-            bytes.push(0x04);
-            bytes.extend_from_slice(&serialize_proof(subproof));
-        }
-        Proof::Trans { proof_a, proof_b } => {
-            bytes.push(0x05);
-            bytes.extend_from_slice(&serialize_proof(proof_a));
-            bytes.extend_from_slice(&serialize_proof(proof_b));
-        }
-        Proof::CongApp { proof_f, proof_a } => {
-            bytes.push(0x06);
-            bytes.extend_from_slice(&serialize_proof(proof_f));
-            bytes.extend_from_slice(&serialize_proof(proof_a));
-        }
-        Proof::CongLam { proof_b } => {
-            bytes.push(0x07);
-            bytes.extend_from_slice(&serialize_proof(proof_b));
+    // Steps still to encode, next on top, so deep proofs do not recurse
+    let mut pending = vec![proof];
+    while let Some(proof) = pending.pop() {
+        match proof {
+            Proof::BetaStep { redex, contractum } => {
+                bytes.push(0x01);
+                bytes.extend_from_slice(&serialize_core_expr(redex));
+                bytes.extend_from_slice(&serialize_core_expr(contractum));
+            }
+            Proof::EtaStep { redex, contractum } => {
+                bytes.push(0x02);
+                bytes.extend_from_slice(&serialize_core_expr(redex));
+                bytes.extend_from_slice(&serialize_core_expr(contractum));
+            }
+            Proof::Refl(expr) => {
+                bytes.push(0x03);
+                bytes.extend_from_slice(&serialize_core_expr(expr));
+            }
+            Proof::Sym(_) => bytes.push(0x04),
+            Proof::Trans { .. } => bytes.push(0x05),
+            Proof::CongApp { .. } => bytes.push(0x06),
+            Proof::CongLam { .. } => bytes.push(0x07),
         }
+        pending.extend(subproofs(proof).into_iter().rev());
     }
     bytes
 }
 
 /// Deserialize a proof from binary format
+///
+/// Inputs encoding a proof deeper than `DEFAULT_MAX_PROOF_DEPTH` are
+/// rejected with `ProofParseError::DepthLimitExceeded`.
 pub fn deserialize_proof(bytes: &[u8]) -> Result<Proof, ProofParseError> {
-    if bytes.is_empty() {
-        return Err(ProofParseError::EmptyInput);
-    }
+    deserialize_proof_with_max_depth(bytes, DEFAULT_MAX_PROOF_DEPTH)
+}
 
+/// Deserialize a proof whose steps nest at most `max_depth` deep
+///
+/// The limit is checked on reading each step's tag, so nothing is
+/// allocated for the steps below it.
+pub fn deserialize_proof_with_max_depth(
+    bytes: &[u8],
+    max_depth: usize,
+) -> Result<Proof, ProofParseError> {
+    deserialize_at(bytes, max_depth)
+}
+
+/// Deserialize a proof of at most `max_depth` nested steps, keeping the steps
+/// still waiting for subproofs on an explicit stack rather than recursing
+fn deserialize_at(bytes: &[u8], max_depth: usize) -> Result<Proof, ProofParseError> {
+    // Each open step's tag with the subproofs read for it so far
+    let mut open: Vec<(u8, Vec<Proof>)> = Vec::new();
     let mut cursor = 0;
-    let tag = bytes[cursor];
-    cursor += 1;
 
-    match tag {
-        0x01 => {
-            // BetaStep
-            let redex = deserialize_core_expr(&bytes[cursor..])
-                .map_err(|e| ProofParseError::CoreExprParseError(format!("{:?}", e)))?;
-            let redex_len = serialize_core_expr(&redex).len();
-            let remaining = &bytes[cursor + redex_len..];
-            if remaining.is_empty() {
-                return Err(ProofParseError::IncompleteData);
-            }
-            let contractum = deserialize_core_expr(remaining)
-                .map_err(|e| ProofParseError::CoreExprParseError(format!("{:?}", e)))?;
-            Ok(Proof::BetaStep { redex, contractum })
+    loop {
+        let Some(&tag) = bytes.get(cursor) else {
+            return Err(match open.last() {
+                Some((_, read)) if !read.is_empty() => ProofParseError::IncompleteData,
+                _ => ProofParseError::EmptyInput,
+            });
+        };
+        if open.len() >= max_depth {
+            return Err(ProofParseError::DepthLimitExceeded(max_depth));
         }
-        0x02 => {
-            // EtaStep
-            let redex = deserialize_core_expr(&bytes[cursor..])
-                .map_err(|e| ProofParseError::CoreExprParseError(format!("{:?}", e)))?;
-            let redex_len = serialize_core_expr(&redex).len();
-            let remaining = &bytes[cursor + redex_len..];
-            if remaining.is_empty() {
-                return Err(ProofParseError::IncompleteData);
+        cursor += 1;
+
+        let mut proof = match tag {
+            0x01 | 0x02 => {
+                // BetaStep or EtaStep
+                let redex = read_core_expr(bytes, &mut cursor)?;
+                if cursor >= bytes.len() {
+                    return Err(ProofParseError::IncompleteData);
+                }
+                let contractum = read_core_expr(bytes, &mut cursor)?;
+                if tag == 0x01 {
+                    Proof::BetaStep { redex, contractum }
+                } else {
+                    Proof::EtaStep { redex, contractum }
+                }
             }
-            let contractum = deserialize_core_expr(remaining)
-                .map_err(|e| ProofParseError::CoreExprParseError(format!("{:?}", e)))?;
-            Ok(Proof::EtaStep { redex, contractum })
-        }
-        0x03 => {
             // Refl
-            let expr = deserialize_core_expr(&bytes[cursor..])
-                .map_err(|e| ProofParseError::CoreExprParseError(format!("{:?}", e)))?;
-            Ok(Proof::Refl(expr))
-        }
-        0x04 => {
-            // Sym
-            let subproof = deserialize_proof(&bytes[cursor..])?;
-            Ok(Proof::Sym(Box::new(subproof)))
-        }
-        0x05 => {
-            // Trans
-            let proof_a = deserialize_proof(&bytes[cursor..])?;
-            let proof_a_serialized = serialize_proof(&proof_a);
-            let proof_a_len = proof_a_serialized.len();
-            let remaining = &bytes[cursor + proof_a_len..];
-            if remaining.is_empty() {
-                return Err(ProofParseError::IncompleteData);
+            0x03 => Proof::Refl(read_core_expr(bytes, &mut cursor)?),
+            // Sym, Trans, CongApp and CongLam: their subproofs follow
+            0x04..=0x07 => {
+                open.push((tag, Vec::new()));
+                continue;
             }
-            let proof_b = deserialize_proof(remaining)?;
-            Ok(Proof::Trans {
-                proof_a: Box::new(proof_a),
-                proof_b: Box::new(proof_b),
-            })
-        }
-        0x06 => {
-            // CongApp
-            let proof_f = deserialize_proof(&bytes[cursor..])?;
-            let proof_f_serialized = serialize_proof(&proof_f);
-            let proof_f_len = proof_f_serialized.len();
-            let remaining = &bytes[cursor + proof_f_len..];
-            if remaining.is_empty() {
-                return Err(ProofParseError::IncompleteData);
+            _ => return Err(ProofParseError::InvalidTag(tag)),
+        };
+
+        // Hand the finished step to its parent, completing every open step
+        // this was the last subproof of
+        loop {
+            let Some((tag, read)) = open.last_mut() else {
+                return Ok(proof);
+            };
+            read.push(proof);
+            let arity = if matches!(tag, 0x04 | 0x07) { 1 } else { 2 };
+            if read.len() < arity {
+                break;
             }
-            let proof_a = deserialize_proof(remaining)?;
-            Ok(Proof::CongApp {
-                proof_f: Box::new(proof_f),
-                proof_a: Box::new(proof_a),
-            })
-        }
-        0x07 => {
-            // CongLam
-            let proof_b = deserialize_proof(&bytes[cursor..])?;
-            Ok(Proof::CongLam {
-                proof_b: Box::new(proof_b),
-            })
+            let (tag, read) = open.pop().expect("the step is open");
+            proof = assemble(tag, read);
         }
-        _ => Err(ProofParseError::InvalidTag(tag)),
+    }
+}
+
+/// Read the `CoreExpr` encoded at `cursor` and move past it
+fn read_core_expr(bytes: &[u8], cursor: &mut usize) -> Result<CoreExpr, ProofParseError> {
+    let expr = deserialize_core_expr(&bytes[*cursor..])
+        .map_err(|e| ProofParseError::CoreExprParseError(format!("{:?}", e)))?;
+    *cursor += serialize_core_expr(&expr).len();
+    Ok(expr)
+}
+
+/// Build the step with tag `tag` from its subproofs, in encoding order
+fn assemble(tag: u8, subproofs: Vec<Proof>) -> Proof {
+    let mut subproofs = subproofs.into_iter().map(Box::new);
+    let mut next = || subproofs.next().expect("every subproof was read");
+    match tag {
+        0x04 => Proof::Sym(next()),
+        0x05 => Proof::Trans {
+            proof_a: next(),
+            proof_b: next(),
+        },
+        0x06 => Proof::CongApp {
+            proof_f: next(),
+            proof_a: next(),
+        },
+        _ => Proof::CongLam { proof_b: next() },
     }
 }
 
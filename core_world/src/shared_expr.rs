@@ -0,0 +1,323 @@
+/// Sharing-preserving λ-calculus terms
+/// `SharedExpr` mirrors `CoreExpr` but links children through `Rc`, so a term
+/// can be a DAG: substitution hands out the same replacement node for every
+/// occurrence of the bound variable instead of copying it.
+use crate::core_expr::CoreExpr;
+use crate::NormalizationError;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// λ-calculus term whose subterms may be shared
+#[derive(Debug, PartialEq, Eq)]
+pub enum SharedExpr {
+    /// Variable expression with De Bruijn index
+    Var(usize),
+    /// Lambda abstraction (function)
+    Lam(Rc<SharedExpr>),
+    /// Function application
+    App(Rc<SharedExpr>, Rc<SharedExpr>),
+    Nat(u64),
+    Pair(Rc<SharedExpr>, Rc<SharedExpr>),
+}
+
+impl SharedExpr {
+    /// Materialize the graph as a `CoreExpr` tree, copying shared subterms
+    pub fn to_tree(&self) -> CoreExpr {
+        match self {
+            SharedExpr::Var(index) => CoreExpr::Var(*index),
+            SharedExpr::Lam(body) => CoreExpr::Lam(Box::new(body.to_tree())),
+            SharedExpr::App(func, arg) => {
+                CoreExpr::App(Box::new(func.to_tree()), Box::new(arg.to_tree()))
+            }
+            SharedExpr::Nat(n) => CoreExpr::Nat(*n),
+            SharedExpr::Pair(first, second) => {
+                CoreExpr::Pair(Box::new(first.to_tree()), Box::new(second.to_tree()))
+            }
+        }
+    }
+
+    /// Number of distinct nodes in the graph (shared nodes count once)
+    pub fn distinct_nodes(&self) -> usize {
+        fn visit(node: &Rc<SharedExpr>, seen: &mut HashSet<*const SharedExpr>) {
+            if !seen.insert(Rc::as_ptr(node)) {
+                return;
+            }
+            for child in node.children() {
+                visit(child, seen);
+            }
+        }
+
+        let mut seen = HashSet::new();
+        for child in self.children() {
+            visit(child, &mut seen);
+        }
+        seen.len() + 1
+    }
+
+    fn children(&self) -> Vec<&Rc<SharedExpr>> {
+        match self {
+            SharedExpr::Var(_) | SharedExpr::Nat(_) => vec![],
+            SharedExpr::Lam(body) => vec![body],
+            SharedExpr::App(a, b) | SharedExpr::Pair(a, b) => vec![a, b],
+        }
+    }
+
+    /// Copy the top node, sharing its children
+    fn shallow_clone(&self) -> SharedExpr {
+        match self {
+            SharedExpr::Var(index) => SharedExpr::Var(*index),
+            SharedExpr::Lam(body) => SharedExpr::Lam(body.clone()),
+            SharedExpr::App(func, arg) => SharedExpr::App(func.clone(), arg.clone()),
+            SharedExpr::Nat(n) => SharedExpr::Nat(*n),
+            SharedExpr::Pair(first, second) => SharedExpr::Pair(first.clone(), second.clone()),
+        }
+    }
+}
+
+impl From<&CoreExpr> for SharedExpr {
+    fn from(expr: &CoreExpr) -> Self {
+        match expr {
+            CoreExpr::Var(index) => SharedExpr::Var(*index),
+            CoreExpr::Lam(body) => SharedExpr::Lam(Rc::new(body.as_ref().into())),
+            CoreExpr::App(func, arg) => {
+                SharedExpr::App(Rc::new(func.as_ref().into()), Rc::new(arg.as_ref().into()))
+            }
+            CoreExpr::Nat(n) => SharedExpr::Nat(*n),
+            CoreExpr::Pair(first, second) => SharedExpr::Pair(
+                Rc::new(first.as_ref().into()),
+                Rc::new(second.as_ref().into()),
+            ),
+        }
+    }
+}
+
+/// Normalize to βη-normal form without duplicating substituted arguments
+///
+/// Reduction is normal-order, so any term that has a normal form reaches it.
+/// Each β-step counts against `step_limit`.
+pub fn normalize_shared(
+    expr: &CoreExpr,
+    step_limit: usize,
+) -> Result<SharedExpr, NormalizationError> {
+    let mut normalizer = SharedNormalizer {
+        steps: 0,
+        step_limit,
+        normal_forms: HashMap::new(),
+        head_normal_forms: HashMap::new(),
+    };
+    let root = Rc::new(SharedExpr::from(expr));
+    let normal = normalizer.normalize(&root)?;
+    drop(normalizer);
+    drop(root);
+    Ok(Rc::try_unwrap(normal).unwrap_or_else(|shared| shared.shallow_clone()))
+}
+
+struct SharedNormalizer {
+    steps: usize,
+    step_limit: usize,
+    /// Normal forms already computed, keyed by node address. The key node is
+    /// kept alive alongside so its address cannot be reused.
+    normal_forms: HashMap<*const SharedExpr, (Rc<SharedExpr>, Rc<SharedExpr>)>,
+    /// Weak head normal forms, memoized the same way
+    head_normal_forms: HashMap<*const SharedExpr, (Rc<SharedExpr>, Rc<SharedExpr>)>,
+}
+
+impl SharedNormalizer {
+    fn normalize(&mut self, node: &Rc<SharedExpr>) -> Result<Rc<SharedExpr>, NormalizationError> {
+        if let Some((_, normal)) = self.normal_forms.get(&Rc::as_ptr(node)) {
+            return Ok(normal.clone());
+        }
+
+        let normal = match &**node {
+            SharedExpr::Var(_) | SharedExpr::Nat(_) => node.clone(),
+            SharedExpr::Lam(body) => {
+                let body_nf = self.normalize(body)?;
+                eta_contract(&body_nf).unwrap_or_else(|| rebuild_lam(node, body, body_nf))
+            }
+            SharedExpr::Pair(first, second) => {
+                let first_nf = self.normalize(first)?;
+                let second_nf = self.normalize(second)?;
+                rebuild_binary(node, first, second, first_nf, second_nf, SharedExpr::Pair)
+            }
+            SharedExpr::App(_, _) => {
+                let head_normal = self.weak_head_normalize(node)?;
+                match &*head_normal {
+                    SharedExpr::App(func, arg) => {
+                        // Stuck application: the head is not a lambda
+                        let func_nf = self.normalize(func)?;
+                        let arg_nf = self.normalize(arg)?;
+                        rebuild_binary(&head_normal, func, arg, func_nf, arg_nf, SharedExpr::App)
+                    }
+                    _ => self.normalize(&head_normal)?,
+                }
+            }
+        };
+
+        self.normal_forms
+            .insert(Rc::as_ptr(node), (node.clone(), normal.clone()));
+        Ok(normal)
+    }
+
+    /// Reduce the head of an application spine until it is not a redex
+    fn weak_head_normalize(
+        &mut self,
+        node: &Rc<SharedExpr>,
+    ) -> Result<Rc<SharedExpr>, NormalizationError> {
+        let SharedExpr::App(func, arg) = &**node else {
+            return Ok(node.clone());
+        };
+        if let Some((_, head_normal)) = self.head_normal_forms.get(&Rc::as_ptr(node)) {
+            return Ok(head_normal.clone());
+        }
+
+        let head = self.weak_head_normalize(func)?;
+        let head_normal = if let SharedExpr::Lam(body) = &*head {
+            self.tick()?;
+            let reduced = substitute(body, arg);
+            self.weak_head_normalize(&reduced)?
+        } else if Rc::ptr_eq(&head, func) {
+            node.clone()
+        } else {
+            Rc::new(SharedExpr::App(head, arg.clone()))
+        };
+
+        self.head_normal_forms
+            .insert(Rc::as_ptr(node), (node.clone(), head_normal.clone()));
+        Ok(head_normal)
+    }
+
+    fn tick(&mut self) -> Result<(), NormalizationError> {
+        if self.steps >= self.step_limit {
+            return Err(NormalizationError::StepLimitExceeded(self.steps));
+        }
+        self.steps += 1;
+        Ok(())
+    }
+}
+
+/// `[arg/0]body`, sharing `arg` (lifted once per binder depth) at every use
+fn substitute(body: &Rc<SharedExpr>, arg: &Rc<SharedExpr>) -> Rc<SharedExpr> {
+    let mut lifted = vec![arg.clone()];
+    substitute_at(body, 0, &mut lifted)
+}
+
+/// Substitute `lifted[0]` for index `target`; `lifted[d]` is the replacement
+/// lifted under `d` binders, built on first use and reused afterwards
+fn substitute_at(
+    node: &Rc<SharedExpr>,
+    target: usize,
+    lifted: &mut Vec<Rc<SharedExpr>>,
+) -> Rc<SharedExpr> {
+    match &**node {
+        SharedExpr::Var(index) if *index == target => {
+            while lifted.len() <= target {
+                let next = shift(&lifted[lifted.len() - 1], 1, 0);
+                lifted.push(next);
+            }
+            lifted[target].clone()
+        }
+        SharedExpr::Var(index) if *index > target => Rc::new(SharedExpr::Var(index - 1)),
+        SharedExpr::Var(_) | SharedExpr::Nat(_) => node.clone(),
+        SharedExpr::Lam(body) => {
+            let new_body = substitute_at(body, target + 1, lifted);
+            rebuild_lam(node, body, new_body)
+        }
+        SharedExpr::App(func, arg) => {
+            let new_func = substitute_at(func, target, lifted);
+            let new_arg = substitute_at(arg, target, lifted);
+            rebuild_binary(node, func, arg, new_func, new_arg, SharedExpr::App)
+        }
+        SharedExpr::Pair(first, second) => {
+            let new_first = substitute_at(first, target, lifted);
+            let new_second = substitute_at(second, target, lifted);
+            rebuild_binary(node, first, second, new_first, new_second, SharedExpr::Pair)
+        }
+    }
+}
+
+/// Add `amount` to free variables ≥ `cutoff`, reusing unchanged subterms
+fn shift(node: &Rc<SharedExpr>, amount: usize, cutoff: usize) -> Rc<SharedExpr> {
+    match &**node {
+        SharedExpr::Var(index) if *index >= cutoff => Rc::new(SharedExpr::Var(index + amount)),
+        SharedExpr::Var(_) | SharedExpr::Nat(_) => node.clone(),
+        SharedExpr::Lam(body) => rebuild_lam(node, body, shift(body, amount, cutoff + 1)),
+        SharedExpr::App(func, arg) => {
+            let new_func = shift(func, amount, cutoff);
+            let new_arg = shift(arg, amount, cutoff);
+            rebuild_binary(node, func, arg, new_func, new_arg, SharedExpr::App)
+        }
+        SharedExpr::Pair(first, second) => {
+            let new_first = shift(first, amount, cutoff);
+            let new_second = shift(second, amount, cutoff);
+            rebuild_binary(node, first, second, new_first, new_second, SharedExpr::Pair)
+        }
+    }
+}
+
+/// η-contract `λ.(f 0)` to `f` lowered by one, when 0 is not free in `f`
+fn eta_contract(body: &Rc<SharedExpr>) -> Option<Rc<SharedExpr>> {
+    match &**body {
+        SharedExpr::App(func, arg) if **arg == SharedExpr::Var(0) && !occurs(func, 0) => {
+            Some(lower(func, 0))
+        }
+        _ => None,
+    }
+}
+
+/// Whether index `target` occurs free
+fn occurs(node: &SharedExpr, target: usize) -> bool {
+    match node {
+        SharedExpr::Var(index) => *index == target,
+        SharedExpr::Nat(_) => false,
+        SharedExpr::Lam(body) => occurs(body, target + 1),
+        SharedExpr::App(a, b) | SharedExpr::Pair(a, b) => occurs(a, target) || occurs(b, target),
+    }
+}
+
+/// Subtract one from free variables > `cutoff`
+fn lower(node: &Rc<SharedExpr>, cutoff: usize) -> Rc<SharedExpr> {
+    match &**node {
+        SharedExpr::Var(index) if *index > cutoff => Rc::new(SharedExpr::Var(index - 1)),
+        SharedExpr::Var(_) | SharedExpr::Nat(_) => node.clone(),
+        SharedExpr::Lam(body) => rebuild_lam(node, body, lower(body, cutoff + 1)),
+        SharedExpr::App(func, arg) => {
+            let new_func = lower(func, cutoff);
+            let new_arg = lower(arg, cutoff);
+            rebuild_binary(node, func, arg, new_func, new_arg, SharedExpr::App)
+        }
+        SharedExpr::Pair(first, second) => {
+            let new_first = lower(first, cutoff);
+            let new_second = lower(second, cutoff);
+            rebuild_binary(node, first, second, new_first, new_second, SharedExpr::Pair)
+        }
+    }
+}
+
+/// Reuse `node` when its body came back unchanged
+fn rebuild_lam(
+    node: &Rc<SharedExpr>,
+    old_body: &Rc<SharedExpr>,
+    new_body: Rc<SharedExpr>,
+) -> Rc<SharedExpr> {
+    if Rc::ptr_eq(old_body, &new_body) {
+        node.clone()
+    } else {
+        Rc::new(SharedExpr::Lam(new_body))
+    }
+}
+
+/// Reuse `node` when both children came back unchanged
+fn rebuild_binary(
+    node: &Rc<SharedExpr>,
+    old_left: &Rc<SharedExpr>,
+    old_right: &Rc<SharedExpr>,
+    new_left: Rc<SharedExpr>,
+    new_right: Rc<SharedExpr>,
+    make: fn(Rc<SharedExpr>, Rc<SharedExpr>) -> SharedExpr,
+) -> Rc<SharedExpr> {
+    if Rc::ptr_eq(old_left, &new_left) && Rc::ptr_eq(old_right, &new_right) {
+        node.clone()
+    } else {
+        Rc::new(make(new_left, new_right))
+    }
+}
@@ -151,7 +151,8 @@ fn test_invalid_beta_step() {
     assert!(result.is_err());
     assert!(matches!(
         result.unwrap_err(),
-        ProofError::InvalidBetaStep(_)
+        ProofError::StepFailed { path, reason }
+            if path.is_empty() && reason.starts_with("Invalid beta step")
     ));
 }
 
@@ -164,5 +165,52 @@ fn test_invalid_eta_step() {
 
     let result = verify(&invalid_proof);
     assert!(result.is_err());
-    assert!(matches!(result.unwrap_err(), ProofError::InvalidEtaStep(_)));
+    assert!(matches!(
+        result.unwrap_err(),
+        ProofError::StepFailed { path, reason }
+            if path.is_empty() && reason.starts_with("Invalid eta step")
+    ));
+}
+
+#[test]
+fn test_broken_trans_chain_reports_failing_junction() {
+    // Trans(Refl(0), Trans(Trans(Refl(0), Refl(0)), Refl(1))): the innermost
+    // junctions line up, but the one at [1] joins 0 ≡ 0 with 1 ≡ 1
+    let proof = Proof::Trans {
+        proof_a: Box::new(Proof::Refl(var(0))),
+        proof_b: Box::new(Proof::Trans {
+            proof_a: Box::new(Proof::Trans {
+                proof_a: Box::new(Proof::Refl(var(0))),
+                proof_b: Box::new(Proof::Refl(var(0))),
+            }),
+            proof_b: Box::new(Proof::Refl(var(1))),
+        }),
+    };
+
+    match verify(&proof).unwrap_err() {
+        ProofError::StepFailed { path, reason } => {
+            assert_eq!(path, vec![1]);
+            assert!(reason.starts_with("Invalid transitivity"), "{}", reason);
+        }
+        other => panic!("expected StepFailed, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_failing_leaf_path_within_congruence() {
+    // CongLam(CongApp(Refl(0), <bad beta step>)) fails at [0, 1]
+    let proof = Proof::CongLam {
+        proof_b: Box::new(Proof::CongApp {
+            proof_f: Box::new(Proof::Refl(var(0))),
+            proof_a: Box::new(Proof::BetaStep {
+                redex: app(lam(var(0)), var(1)),
+                contractum: var(2),
+            }),
+        }),
+    };
+
+    assert!(matches!(
+        verify(&proof).unwrap_err(),
+        ProofError::StepFailed { path, .. } if path == vec![0, 1]
+    ));
 }
@@ -0,0 +1,35 @@
+/// Test η-long normal forms for extensional comparison
+use core_world::core_expr::{app, lam, nat, pair, var};
+use core_world::core_kernel::eta_long_normal_form;
+
+#[test]
+fn test_function_and_its_eta_expansion_agree() {
+    let f = var(0);
+    let expanded = lam(app(var(1), var(0)));
+
+    let expected = lam(app(var(1), var(0)));
+    assert_eq!(eta_long_normal_form(f, 100).unwrap(), expected);
+    assert_eq!(eta_long_normal_form(expanded, 100).unwrap(), expected);
+}
+
+#[test]
+fn test_partial_application_is_expanded_to_full_arity() {
+    // λg. (g 1 2, g): the bare g is expanded to take two arguments
+    let term = lam(pair(app(app(var(0), nat(1)), nat(2)), var(0)));
+
+    let expected = lam(pair(
+        app(app(var(0), nat(1)), nat(2)),
+        lam(lam(app(app(var(2), var(1)), var(0)))),
+    ));
+    assert_eq!(eta_long_normal_form(term, 100).unwrap(), expected);
+}
+
+#[test]
+fn test_beta_redexes_are_reduced_first() {
+    // (λh. h) f normalizes to f before expansion
+    let term = app(lam(var(0)), var(0));
+    assert_eq!(
+        eta_long_normal_form(term, 100).unwrap(),
+        lam(app(var(1), var(0)))
+    );
+}
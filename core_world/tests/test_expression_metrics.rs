@@ -0,0 +1,36 @@
+/// Test term size and depth metrics
+use core_world::core_expr::{app, lam, nat, pair, var};
+use core_world::{depth, node_count};
+
+#[test]
+fn test_metrics_of_known_terms() {
+    // λ.λ.(1 0): Lam, Lam, App, Var, Var
+    let expr = lam(lam(app(var(1), var(0))));
+    assert_eq!(node_count(&expr), 5);
+    assert_eq!(depth(&expr), 4);
+
+    let leaf = nat(7);
+    assert_eq!(node_count(&leaf), 1);
+    assert_eq!(depth(&leaf), 1);
+
+    let lopsided = pair(lam(lam(var(0))), nat(1));
+    assert_eq!(node_count(&lopsided), 5);
+    assert_eq!(depth(&lopsided), 4);
+}
+
+#[test]
+fn test_metrics_handle_very_deep_terms() {
+    const SPINE: usize = 100_000;
+
+    // Right spine: f (f (f ... x))
+    let mut expr = var(0);
+    for _ in 0..SPINE {
+        expr = app(var(1), expr);
+    }
+
+    assert_eq!(node_count(&expr), 2 * SPINE + 1);
+    assert_eq!(depth(&expr), SPINE + 1);
+
+    // Dropping the term is recursive, so leak it rather than overflow here
+    std::mem::forget(expr);
+}
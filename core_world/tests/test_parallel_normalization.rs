@@ -0,0 +1,62 @@
+use core_world::core_expr::{app, lam, nat, pair, var, CoreExpr};
+use core_world::core_kernel::{normalize, normalize_parallel};
+use std::time::Instant;
+
+/// Build a balanced tree of pairs whose leaves are identity redexes `(λx.x) n`
+fn wide_pair_tree(depth: u32, next_leaf: &mut u64) -> CoreExpr {
+    if depth == 0 {
+        *next_leaf += 1;
+        app(lam(var(0)), nat(*next_leaf))
+    } else {
+        let first = wide_pair_tree(depth - 1, next_leaf);
+        let second = wide_pair_tree(depth - 1, next_leaf);
+        pair(first, second)
+    }
+}
+
+#[test]
+fn test_parallel_matches_sequential_on_wide_pair_tree() {
+    let expr = wide_pair_tree(6, &mut 0);
+
+    let sequential = normalize(expr.clone());
+    let parallel = normalize_parallel(expr, 1000, 8).unwrap();
+
+    assert_eq!(parallel, sequential);
+}
+
+#[test]
+fn test_parallel_result_is_independent_of_thread_count() {
+    // Stuck application head with a reducible argument, nested inside a pair
+    let expr = pair(
+        app(var(3), app(lam(var(0)), nat(7))),
+        app(app(lam(lam(var(1))), nat(1)), nat(2)),
+    );
+
+    let single = normalize_parallel(expr.clone(), 1000, 1).unwrap();
+    for threads in [2, 3, 4, 16] {
+        assert_eq!(
+            normalize_parallel(expr.clone(), 1000, threads).unwrap(),
+            single
+        );
+    }
+    assert_eq!(single, pair(app(var(3), nat(7)), nat(1)));
+}
+
+#[test]
+fn test_parallel_normalization_benchmark() {
+    let expr = wide_pair_tree(10, &mut 0);
+
+    let start_time = Instant::now();
+    let sequential = normalize_parallel(expr.clone(), 1000, 1).unwrap();
+    let sequential_duration = start_time.elapsed();
+
+    let start_time = Instant::now();
+    let parallel = normalize_parallel(expr, 1000, 4).unwrap();
+    let parallel_duration = start_time.elapsed();
+
+    assert_eq!(parallel, sequential);
+    println!(
+        "Normalized 1024-leaf pair tree: 1 thread {:?}, 4 threads {:?}",
+        sequential_duration, parallel_duration
+    );
+}
@@ -0,0 +1,71 @@
+use core_world::core_expr::{nat, serialize_core_expr};
+use core_world::proof_checker::{
+    deserialize_proof, deserialize_proof_with_max_depth, serialize_proof, verify,
+    verify_with_max_depth, Proof, ProofError, ProofParseError, DEFAULT_MAX_PROOF_DEPTH,
+};
+
+/// `depth` nested proofs: `Sym(Sym(...(Refl 0)))`
+fn sym_chain(depth: usize) -> Proof {
+    (1..depth).fold(Proof::Refl(nat(0)), |proof, _| Proof::Sym(Box::new(proof)))
+}
+
+/// Encoding of `sym_chain(depth)`, built without building the proof
+fn sym_chain_bytes(depth: usize) -> Vec<u8> {
+    let mut bytes = vec![0x04; depth - 1];
+    bytes.push(0x03);
+    bytes.extend_from_slice(&serialize_core_expr(&nat(0)));
+    bytes
+}
+
+#[test]
+fn test_proof_at_max_depth_verifies() {
+    let proof = sym_chain(50);
+    assert_eq!(verify_with_max_depth(&proof, 50), Ok((nat(0), nat(0))));
+    assert_eq!(
+        verify_with_max_depth(&proof, 49),
+        Err(ProofError::DepthLimitExceeded(49))
+    );
+}
+
+#[test]
+fn test_deep_sym_chain_is_rejected_by_verify() {
+    let proof = sym_chain(DEFAULT_MAX_PROOF_DEPTH + 1);
+    assert_eq!(
+        verify(&proof),
+        Err(ProofError::DepthLimitExceeded(DEFAULT_MAX_PROOF_DEPTH))
+    );
+}
+
+#[test]
+fn test_pathologically_deep_encoding_is_rejected_by_deserialize() {
+    let bytes = sym_chain_bytes(1_000_000);
+    assert_eq!(
+        deserialize_proof(&bytes).unwrap_err(),
+        ProofParseError::DepthLimitExceeded(DEFAULT_MAX_PROOF_DEPTH)
+    );
+}
+
+#[test]
+fn test_deserialize_depth_bound_matches_verify() {
+    let bytes = sym_chain_bytes(20);
+    assert_eq!(bytes, serialize_proof(&sym_chain(20)));
+    assert!(deserialize_proof_with_max_depth(&bytes, 20).is_ok());
+    assert_eq!(
+        deserialize_proof_with_max_depth(&bytes, 19).unwrap_err(),
+        ProofParseError::DepthLimitExceeded(19)
+    );
+}
+
+#[test]
+fn test_long_normalization_chain_verifies_by_default() {
+    // `prove_normalization` nests one Trans per step, left to right
+    let steps = 5_000;
+    let proof = (1..steps).fold(Proof::Refl(nat(0)), |proof, _| Proof::Trans {
+        proof_a: Box::new(proof),
+        proof_b: Box::new(Proof::Refl(nat(0))),
+    });
+
+    assert_eq!(verify(&proof), Ok((nat(0), nat(0))));
+    let decoded = deserialize_proof(&serialize_proof(&proof)).unwrap();
+    assert_eq!(verify(&decoded), Ok((nat(0), nat(0))));
+}
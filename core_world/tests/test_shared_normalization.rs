@@ -0,0 +1,58 @@
+/// Test sharing-preserving normalization
+use core_world::core_expr::{app, lam, nat, pair, var, CoreExpr};
+use core_world::core_kernel::is_normal_form;
+use core_world::{normalize_shared, SharedExpr};
+use std::rc::Rc;
+
+/// A reducible argument large enough that copying it would be noticeable
+fn big() -> CoreExpr {
+    app(
+        lam(pair(var(0), pair(nat(1), nat(2)))),
+        pair(nat(3), lam(var(0))),
+    )
+}
+
+fn big_normal() -> CoreExpr {
+    pair(pair(nat(3), lam(var(0))), pair(nat(1), nat(2)))
+}
+
+#[test]
+fn test_duplicated_argument_is_a_single_node() {
+    let duplicate = lam(app(var(0), var(0)));
+
+    let result = normalize_shared(&app(duplicate, big()), 100).unwrap();
+
+    match &result {
+        SharedExpr::App(func, arg) => assert!(Rc::ptr_eq(func, arg)),
+        other => panic!("Expected App, got {:?}", other),
+    }
+    // The tree would contain two full copies of `big`'s normal form
+    let tree = result.to_tree();
+    assert!(result.distinct_nodes() < core_world::node_count(&tree));
+}
+
+#[test]
+fn test_to_tree_gives_the_normal_form() {
+    let cases = vec![
+        (
+            app(lam(app(var(0), var(0))), big()),
+            app(big_normal(), big_normal()),
+        ),
+        (app(lam(lam(app(var(1), var(0)))), lam(var(0))), lam(var(0))),
+        (lam(app(var(3), var(0))), var(2)),
+        (pair(app(lam(var(0)), nat(7)), var(2)), pair(nat(7), var(2))),
+    ];
+
+    for (term, expected) in cases {
+        let tree = normalize_shared(&term, 100).unwrap().to_tree();
+        assert_eq!(tree, expected);
+        assert!(is_normal_form(&tree));
+    }
+}
+
+#[test]
+fn test_step_limit_is_enforced() {
+    let omega = lam(app(var(0), var(0)));
+    let result = normalize_shared(&app(omega.clone(), omega), 50);
+    assert!(result.is_err());
+}
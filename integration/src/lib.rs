@@ -1,15 +1,21 @@
 /// Integration bridge module
 /// Provides conversion functions between Core-World and Physics-World types
-use core_world::core_expr::CoreExpr;
-use physics_world::types::Value;
+use core_world::core_expr::{lam, var, CoreExpr};
+use core_world::core_kernel::alpha_equiv;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::ClosureEquivalence;
 
 /// Convert a Physics-World Value to a Core-World CoreExpr
 /// This function provides the bridge between the two layers
+///
+/// Integers are stored as their two's-complement bits in a `Nat`. Booleans
+/// use the Church encodings `λt.λf.t` and `λt.λf.f`, and nil is `λx.x`, so
+/// none of them collides with an integer.
 pub fn core_expr_from_value(value: &Value) -> CoreExpr {
     match value {
-        Value::Nil => CoreExpr::Nat(0), // Represent nil as zero for simplicity
-        Value::Bool(true) => CoreExpr::Nat(1),
-        Value::Bool(false) => CoreExpr::Nat(0),
+        Value::Nil => lam(var(0)),
+        Value::Bool(true) => lam(lam(var(1))),
+        Value::Bool(false) => lam(lam(var(0))),
         Value::Int(n) => CoreExpr::Nat(*n as u64),
         Value::Float(f) => CoreExpr::Nat(*f as u64), // Convert float to u64 for CoreExpr
         Value::Symbol(usize) => CoreExpr::Var(*usize), // Use symbol index as variable index
@@ -43,6 +49,103 @@ pub fn core_expr_from_value(value: &Value) -> CoreExpr {
             // For error values, we'll create a placeholder representation
             CoreExpr::Nat(47) // Placeholder for error representation
         }
+        Value::Vector(_) => {
+            // For vectors, we'll create a placeholder representation
+            CoreExpr::Nat(48) // Placeholder for vector representation
+        }
+        Value::BigInt(_) => {
+            // For big integers, we'll create a placeholder representation
+            CoreExpr::Nat(49) // Placeholder for big integer representation
+        }
+        Value::Bytes(_) => {
+            // For byte strings, we'll create a placeholder representation
+            CoreExpr::Nat(50) // Placeholder for byte string representation
+        }
+        Value::Thunk(_) => {
+            // For thunks, we'll create a placeholder representation
+            CoreExpr::Nat(51) // Placeholder for thunk representation
+        }
+        Value::ErrorRecord { .. } => {
+            // For error records, we'll create a placeholder representation
+            CoreExpr::Nat(52) // Placeholder for error record representation
+        }
+    }
+}
+
+/// Convert a CoreExpr produced by `core_expr_from_value` back to a Value
+///
+/// Returns `None` for expressions that are not the encoding of nil, a
+/// boolean, an integer or a symbol.
+pub fn value_from_core_expr(expr: &CoreExpr) -> Option<Value> {
+    match expr {
+        CoreExpr::Nat(n) => Some(Value::Int(*n as i64)),
+        CoreExpr::Var(index) => Some(Value::Symbol(*index)),
+        _ if *expr == core_expr_from_value(&Value::Nil) => Some(Value::Nil),
+        _ if *expr == core_expr_from_value(&Value::Bool(true)) => Some(Value::Bool(true)),
+        _ if *expr == core_expr_from_value(&Value::Bool(false)) => Some(Value::Bool(false)),
+        _ => None,
+    }
+}
+
+/// Decompile a closure body to the CoreExpr it returns
+///
+/// Bodies are evaluated symbolically: local slot `i` becomes `Var(i)`,
+/// literals go through `core_expr_from_value`, `Cons` builds a `Pair` and
+/// `Call(n)` applies the callee on top of the stack to its `n` arguments in
+/// order. Returns `None` for bodies using any other instruction, since
+/// their result has no CoreExpr counterpart here.
+pub fn core_expr_from_body(body: &[OpCode]) -> Option<CoreExpr> {
+    let mut stack: Vec<CoreExpr> = Vec::new();
+    for op in body {
+        match op {
+            OpCode::Nil => stack.push(core_expr_from_value(&Value::Nil)),
+            OpCode::Bool(b) => stack.push(core_expr_from_value(&Value::Bool(*b))),
+            OpCode::Int(n) if *n >= 0 => stack.push(core_expr_from_value(&Value::Int(*n))),
+            OpCode::GetLocal(slot) => stack.push(CoreExpr::Var(*slot as usize)),
+            OpCode::Dup => stack.push(stack.last()?.clone()),
+            OpCode::Pop => {
+                stack.pop()?;
+            }
+            OpCode::Swap => {
+                let len = stack.len();
+                if len < 2 {
+                    return None;
+                }
+                stack.swap(len - 1, len - 2);
+            }
+            OpCode::Cons => {
+                let cdr = stack.pop()?;
+                let car = stack.pop()?;
+                stack.push(CoreExpr::Pair(Box::new(car), Box::new(cdr)));
+            }
+            OpCode::Call(arg_count) => {
+                let function = stack.pop()?;
+                let args = stack.split_off(stack.len().checked_sub(*arg_count as usize)?);
+                let applied = args.into_iter().fold(function, |function, arg| {
+                    CoreExpr::App(Box::new(function), Box::new(arg))
+                });
+                stack.push(applied);
+            }
+            OpCode::Ret => return stack.pop(),
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Closure comparison for `ClosureAlphaEq` by α-equivalence of the
+/// decompiled bodies
+///
+/// Bodies that do not decompile are compared as bytecode.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlphaEquivalence;
+
+impl ClosureEquivalence for AlphaEquivalence {
+    fn equivalent(&self, a: &[OpCode], b: &[OpCode]) -> bool {
+        match (core_expr_from_body(a), core_expr_from_body(b)) {
+            (Some(a), Some(b)) => alpha_equiv(a, b),
+            _ => a == b,
+        }
     }
 }
 
@@ -55,9 +158,9 @@ mod tests {
     #[test]
     fn test_core_expr_from_value_conversions() {
         // Test basic value conversions
-        assert_eq!(core_expr_from_value(&Value::Nil), CoreExpr::Nat(0));
-        assert_eq!(core_expr_from_value(&Value::Bool(true)), CoreExpr::Nat(1));
-        assert_eq!(core_expr_from_value(&Value::Bool(false)), CoreExpr::Nat(0));
+        assert_eq!(core_expr_from_value(&Value::Nil), lam(var(0)));
+        assert_eq!(core_expr_from_value(&Value::Bool(true)), lam(lam(var(1))));
+        assert_eq!(core_expr_from_value(&Value::Bool(false)), lam(lam(var(0))));
         assert_eq!(core_expr_from_value(&Value::Int(42)), CoreExpr::Nat(42));
         assert_eq!(core_expr_from_value(&Value::Symbol(5)), CoreExpr::Var(5));
         assert_eq!(
@@ -243,8 +346,8 @@ mod tests {
         // Test size calculations for capability opcodes in integration context
         assert_eq!(OpCode::HasCap(0).size_bytes(), 5);
         assert_eq!(OpCode::RequestCap(0, 0).size_bytes(), 9);
-        assert_eq!(OpCode::GrantCap(0, 0).size_bytes(), 6);
-        assert_eq!(OpCode::RevokeCap(0, 0).size_bytes(), 6);
+        assert_eq!(OpCode::GrantCap(0, 0).size_bytes(), 9);
+        assert_eq!(OpCode::RevokeCap(0, 0).size_bytes(), 9);
 
         assert_eq!(
             OpCode::HostCall {
@@ -253,7 +356,7 @@ mod tests {
                 args: 0
             }
             .size_bytes(),
-            7
+            8
         );
 
         // Test with maximum values
@@ -264,11 +367,11 @@ mod tests {
         );
         assert_eq!(
             OpCode::GrantCap(u32::MAX, u32::MAX as usize).size_bytes(),
-            6
+            9
         );
         assert_eq!(
             OpCode::RevokeCap(u32::MAX, u32::MAX as usize).size_bytes(),
-            6
+            9
         );
     }
 
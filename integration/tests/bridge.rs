@@ -5,7 +5,7 @@
 mod tests {
     use core_world::core_expr::{app, lam, nat, var, CoreExpr};
     use core_world::core_kernel::{alpha_equiv, normalize};
-    use integration::core_expr_from_value;
+    use integration::{core_expr_from_value, value_from_core_expr};
     use physics_world::types::{OpCode, Value};
     use physics_world::vm::state::VmState;
 
@@ -34,9 +34,9 @@ mod tests {
 
         // Test conversion from Physics World Value to CoreExpr
         let test_values = vec![
-            (Value::Nil, CoreExpr::Nat(0)),
-            (Value::Bool(true), CoreExpr::Nat(1)),
-            (Value::Bool(false), CoreExpr::Nat(0)),
+            (Value::Nil, lam(var(0))),
+            (Value::Bool(true), lam(lam(var(1)))),
+            (Value::Bool(false), lam(lam(var(0)))),
             (Value::Int(42), CoreExpr::Nat(42)),
             (Value::Symbol(5), CoreExpr::Var(5)),
             (Value::ActorId(123), CoreExpr::Nat(123)),
@@ -48,6 +48,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_value_round_trip() {
+        // Nil, false and zero must not share an encoding
+        let values = vec![
+            Value::Nil,
+            Value::Bool(false),
+            Value::Bool(true),
+            Value::Int(0),
+            Value::Int(1),
+            Value::Int(-7),
+            Value::Int(i64::MAX),
+            Value::Symbol(3),
+        ];
+
+        for value in &values {
+            let expr = core_expr_from_value(value);
+            assert_eq!(
+                value_from_core_expr(&expr).as_ref(),
+                Some(value),
+                "Failed to round-trip value {:?}",
+                value
+            );
+        }
+    }
+
     #[test]
     fn test_complex_expression_reduction() {
         // Test reduction of a more complex expression: (λx.x) (λy.y)
@@ -0,0 +1,120 @@
+/// Test ClosureAlphaEq comparing closures through the Core-World bridge
+use std::sync::Arc;
+
+use core_world::core_expr::{app, var};
+use integration::{core_expr_from_body, AlphaEquivalence};
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::opcodes::closure::create_closure_body;
+use physics_world::vm::VmState;
+
+/// Compare a closure over `1` with body `lhs` to a closure over `2` with body `rhs`
+fn compare(lhs: Vec<OpCode>, rhs: Vec<OpCode>, alpha: bool) -> Value {
+    let main = vec![
+        OpCode::Int(1),
+        OpCode::MakeClosure(0, 1),
+        OpCode::Int(2),
+        OpCode::MakeClosure(1, 1),
+        OpCode::ClosureAlphaEq,
+    ];
+    let mut vm = VmState::new(main, vec![], 100, 8192, 1, 100);
+    for body in [lhs, rhs] {
+        let ptr = create_closure_body(&mut vm, body).unwrap();
+        vm.constant_pool.push(Value::Closure(ptr));
+    }
+    if alpha {
+        vm.set_closure_equivalence(Arc::new(AlphaEquivalence));
+    }
+    vm.run().unwrap()
+}
+
+/// `f x`, reading `x` from slot 0 and the captured `f` from slot 1
+fn apply_capture() -> Vec<OpCode> {
+    vec![
+        OpCode::GetLocal(0),
+        OpCode::GetLocal(1),
+        OpCode::Call(1),
+        OpCode::Ret,
+    ]
+}
+
+/// The same application, reaching the same stack by a different route
+fn apply_capture_swapped() -> Vec<OpCode> {
+    vec![
+        OpCode::GetLocal(1),
+        OpCode::GetLocal(0),
+        OpCode::Swap,
+        OpCode::Call(1),
+        OpCode::Ret,
+    ]
+}
+
+#[test]
+fn test_bodies_decompile_to_core_expr() {
+    let expected = app(var(1), var(0));
+    assert_eq!(
+        core_expr_from_body(&apply_capture()),
+        Some(expected.clone())
+    );
+    assert_eq!(
+        core_expr_from_body(&apply_capture_swapped()),
+        Some(expected)
+    );
+    assert_eq!(
+        core_expr_from_body(&[OpCode::GetLocal(0), OpCode::Add, OpCode::Ret]),
+        None
+    );
+}
+
+#[test]
+fn test_same_body_with_different_captures_is_equal() {
+    assert_eq!(
+        compare(apply_capture(), apply_capture(), false),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        compare(apply_capture(), apply_capture(), true),
+        Value::Bool(true)
+    );
+}
+
+#[test]
+fn test_equivalent_bodies_are_equal_only_up_to_alpha() {
+    // The bytecode differs, so only the decompiled comparison sees through it
+    assert_eq!(
+        compare(apply_capture(), apply_capture_swapped(), false),
+        Value::Bool(false)
+    );
+    assert_eq!(
+        compare(apply_capture(), apply_capture_swapped(), true),
+        Value::Bool(true)
+    );
+}
+
+#[test]
+fn test_structurally_different_bodies_are_unequal() {
+    let argument_first = vec![
+        OpCode::GetLocal(1),
+        OpCode::GetLocal(0),
+        OpCode::Call(1),
+        OpCode::Ret,
+    ];
+    assert_eq!(
+        compare(apply_capture(), argument_first, true),
+        Value::Bool(false)
+    );
+}
+
+#[test]
+fn test_bodies_returning_nil_false_and_zero_are_unequal() {
+    let returning = |literal| vec![literal, OpCode::Ret];
+    let literals = [OpCode::Nil, OpCode::Bool(false), OpCode::Int(0)];
+    for (i, lhs) in literals.iter().enumerate() {
+        for rhs in &literals[i + 1..] {
+            assert_eq!(
+                compare(returning(*lhs), returning(*rhs), true),
+                Value::Bool(false),
+                "{lhs:?} and {rhs:?} bodies compared equal"
+            );
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Compiled artifacts cached on disk
+//!
+//! An artifact is a whole [`CompilationResult`] in bincode, behind a short
+//! header naming the format version. The result records a hash of every
+//! input it was compiled from and of the compiler version, so a build cache
+//! can tell whether an artifact is still current with
+//! [`load_artifact_for_source`] instead of recompiling every run.
+
+use crate::compiler::stable_hash;
+use crate::core_compilation::capability_analysis::CapabilityCheckMode;
+use crate::core_compiler::CompilationResult;
+use crate::error::CompilationError;
+use crate::trust_tier::TrustTier;
+use std::path::Path;
+
+/// Bytes every artifact starts with
+const MAGIC: &[u8; 4] = b"JUEA";
+
+/// Version of the artifact encoding; artifacts of other versions are rejected
+const FORMAT_VERSION: u32 = 5;
+
+/// Version of the compiler, whose output may change between releases
+const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Hash identifying a compilation of `source` at `tier` with the given
+/// default step and memory limits, capability check mode and harness
+/// `inputs`, by this compiler and artifact format, as stored in
+/// [`CompilationResult::source_hash`].
+///
+/// The hash is the same in every build and on every platform, so an
+/// artifact stays current across rebuilds of the same compiler version.
+#[must_use]
+pub fn source_hash(
+    source: &str,
+    tier: TrustTier,
+    step_limit: u64,
+    memory_limit: usize,
+    mode: CapabilityCheckMode,
+    inputs: &[String],
+) -> u64 {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    push_length_prefixed(&mut bytes, COMPILER_VERSION.as_bytes());
+    bytes.push(tier as u8);
+    bytes.extend_from_slice(&step_limit.to_le_bytes());
+    bytes.extend_from_slice(&(memory_limit as u64).to_le_bytes());
+    bytes.push(mode as u8);
+    bytes.extend_from_slice(&(inputs.len() as u64).to_le_bytes());
+    for input in inputs {
+        push_length_prefixed(&mut bytes, input.as_bytes());
+    }
+    // Every other field is fixed-width or prefixed, so the source needs no prefix
+    bytes.extend_from_slice(source.as_bytes());
+    stable_hash(&bytes)
+}
+
+/// Append `field` to `bytes` after its length
+fn push_length_prefixed(bytes: &mut Vec<u8>, field: &[u8]) {
+    bytes.extend_from_slice(&(field.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(field);
+}
+
+/// Write `result` to `path` as an artifact, replacing any existing file.
+///
+/// # Errors
+///
+/// Returns [`CompilationError::ArtifactError`] if the result cannot be
+/// encoded or the file cannot be written.
+pub fn save_artifact(
+    result: &CompilationResult,
+    path: impl AsRef<Path>,
+) -> Result<(), CompilationError> {
+    let encoded = bincode::serialize(result)
+        .map_err(|e| CompilationError::ArtifactError(format!("cannot encode artifact: {e}")))?;
+
+    let mut bytes = Vec::with_capacity(MAGIC.len() + 4 + encoded.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&encoded);
+
+    let path = path.as_ref();
+    std::fs::write(path, bytes).map_err(|e| {
+        CompilationError::ArtifactError(format!("cannot write {}: {e}", path.display()))
+    })
+}
+
+/// Read the artifact at `path`.
+///
+/// # Errors
+///
+/// Returns [`CompilationError::ArtifactError`] if the file cannot be read,
+/// is not an artifact, was written by another format version, or does not
+/// decode.
+pub fn load_artifact(path: impl AsRef<Path>) -> Result<CompilationResult, CompilationError> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| {
+        CompilationError::ArtifactError(format!("cannot read {}: {e}", path.display()))
+    })?;
+
+    let payload = bytes.strip_prefix(MAGIC).ok_or_else(|| {
+        CompilationError::ArtifactError(format!("{} is not an artifact", path.display()))
+    })?;
+    let (version, encoded) = payload.split_first_chunk().ok_or_else(|| {
+        CompilationError::ArtifactError(format!("{} is truncated", path.display()))
+    })?;
+    let version = u32::from_le_bytes(*version);
+    if version != FORMAT_VERSION {
+        return Err(CompilationError::ArtifactError(format!(
+            "{} has format version {version}, expected {FORMAT_VERSION}",
+            path.display()
+        )));
+    }
+
+    bincode::deserialize(encoded).map_err(|e| {
+        CompilationError::ArtifactError(format!("cannot decode {}: {e}", path.display()))
+    })
+}
+
+/// Read the artifact at `path`, checking it was compiled by this compiler
+/// from `source` with the inputs [`source_hash`] covers.
+///
+/// # Errors
+///
+/// Returns the errors of [`load_artifact`], and
+/// [`CompilationError::ArtifactError`] if the artifact records no source
+/// hash or the hash of a different compilation.
+pub fn load_artifact_for_source(
+    path: impl AsRef<Path>,
+    source: &str,
+    tier: TrustTier,
+    step_limit: u64,
+    memory_limit: usize,
+    mode: CapabilityCheckMode,
+    inputs: &[String],
+) -> Result<CompilationResult, CompilationError> {
+    let path = path.as_ref();
+    let result = load_artifact(path)?;
+    let expected = source_hash(source, tier, step_limit, memory_limit, mode, inputs);
+    if result.source_hash != Some(expected) {
+        return Err(CompilationError::ArtifactError(format!(
+            "{} was not compiled by this compiler from this source and these settings",
+            path.display()
+        )));
+    }
+    Ok(result)
+}
@@ -1,3 +1,4 @@
+use crate::core_compilation::capability_analyzer::get_child_nodes;
 use crate::error::SourceLocation;
 use crate::trust_tier::TrustTier;
 use physics_world::types::{Capability, OpCode};
@@ -23,7 +24,8 @@ impl CapabilityCheck {
     /// * `check_type` - Type of check performed
     ///
     /// # Returns
-    /// * `Self` - New CapabilityCheck instance
+    /// * `Self` - New `CapabilityCheck` instance
+    #[must_use]
     pub fn new(location: SourceLocation, capability: Capability, check_type: CheckType) -> Self {
         Self {
             location,
@@ -44,7 +46,20 @@ pub enum CheckType {
     Proof,
 }
 
+/// Build audit entries recording how each required capability is enforced
+#[must_use]
+pub fn audit_capabilities(
+    required_caps: &[Capability],
+    check_type: &CheckType,
+) -> Vec<CapabilityCheck> {
+    required_caps
+        .iter()
+        .map(|cap| CapabilityCheck::new(SourceLocation::default(), cap.clone(), check_type.clone()))
+        .collect()
+}
+
 /// Insert runtime capability checks into bytecode
+#[must_use]
 pub fn insert_capability_checks(
     bytecode: Vec<OpCode>,
     ast: &crate::ast::AstNode,
@@ -84,13 +99,8 @@ fn analyze_required_checks(ast: &crate::ast::AstNode) -> Vec<(Capability, Source
         crate::ast::AstNode::RequireCapability {
             capability,
             location,
-        } => {
-            // Parse capability from string
-            if let Ok(cap) = parse_capability(capability) {
-                required_checks.push((cap, location.clone()));
-            }
         }
-        crate::ast::AstNode::HasCapability {
+        | crate::ast::AstNode::HasCapability {
             capability,
             location,
         } => {
@@ -104,7 +114,7 @@ fn analyze_required_checks(ast: &crate::ast::AstNode) -> Vec<(Capability, Source
         } => {
             // FFI calls require specific capabilities based on function name
             if let Some(required_cap) =
-                crate::compiler::capability_analysis::get_ffi_function_capability(function)
+                crate::core_compilation::capability_analyzer::get_ffi_function_capability(function)
             {
                 required_checks.push((required_cap, location.clone()));
             } else {
@@ -142,71 +152,7 @@ fn parse_capability(cap_str: &str) -> Result<Capability, ()> {
         "sys-create-actor" => Ok(Capability::SysCreateActor),
         "sys-terminate-actor" => Ok(Capability::SysTerminateActor),
         "sys-clock" => Ok(Capability::SysClock),
+        "sys-gc" => Ok(Capability::SysGc),
         _ => Err(()),
     }
 }
-
-/// Helper function to get child nodes from AST
-fn get_child_nodes(ast: &crate::ast::AstNode) -> Vec<&crate::ast::AstNode> {
-    let mut children = Vec::new();
-
-    match ast {
-        crate::ast::AstNode::Call {
-            function,
-            arguments,
-            ..
-        } => {
-            children.push(&**function);
-            for arg in arguments {
-                children.push(arg);
-            }
-        }
-        crate::ast::AstNode::Lambda { body, .. } => {
-            children.push(&**body);
-        }
-        crate::ast::AstNode::Let { bindings, body, .. } => {
-            for (_, value) in bindings {
-                children.push(value);
-            }
-            children.push(&**body);
-        }
-        crate::ast::AstNode::If {
-            condition,
-            then_branch,
-            else_branch,
-            ..
-        } => {
-            children.push(&**condition);
-            children.push(&**then_branch);
-            children.push(&**else_branch);
-        }
-        crate::ast::AstNode::TrustTier { expression, .. } => {
-            children.push(&**expression);
-        }
-        crate::ast::AstNode::MacroDefinition { body, .. } => {
-            children.push(&**body);
-        }
-        crate::ast::AstNode::MacroExpansion { arguments, .. } => {
-            for arg in arguments {
-                children.push(arg);
-            }
-        }
-        crate::ast::AstNode::FfiCall { arguments, .. } => {
-            for arg in arguments {
-                children.push(arg);
-            }
-        }
-        crate::ast::AstNode::List { elements, .. } => {
-            for elem in elements {
-                children.push(elem);
-            }
-        }
-        crate::ast::AstNode::Cons { car, cdr, .. } => {
-            children.push(&**car);
-            children.push(&**cdr);
-        }
-        _ => {} // No children for other node types
-    }
-
-    children
-}
@@ -1,3 +1,4 @@
+use crate::core_compilation::proof_generator::NormalizationCache;
 use crate::error::{CompilationError, SourceMap};
 use crate::trust_tier::TrustTier;
 use core_world::core_expr::CoreExpr;
@@ -661,11 +662,14 @@ fn compile_to_core_and_verify(
     let (core_expr, core_proof) =
         super::core_compilation::compile_ast_to_core_expr_with_proofs(&ast)?;
 
-    // 2. Generate comprehensive proof if we don't already have one
+    // 2. Generate comprehensive proof if we don't already have one, sharing
+    // normal forms with the obligations of earlier compilations
     let final_proof = if core_proof.is_some() {
         core_proof
     } else {
-        super::core_compilation::generate_comprehensive_proof(&core_expr)
+        NormalizationCache::with_shared(|cache| {
+            super::core_compilation::generate_comprehensive_proof(&core_expr, cache)
+        })
     };
 
     // 3. Generate verified bytecode from CoreExpr
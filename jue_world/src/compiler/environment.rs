@@ -91,6 +91,18 @@ impl CompilationEnvironment {
         self.lookup_variable(name)
     }
 
+    /// Allocate a frame slot without binding a name to it yet
+    pub fn reserve_slot(&mut self) -> usize {
+        let index = self.frame_size;
+        self.frame_size += 1;
+        index
+    }
+
+    /// Bind `name` in the current scope to a slot from [`Self::reserve_slot`]
+    pub fn bind_slot(&mut self, name: String, index: usize) {
+        self.current_scope.bindings.insert(name, index);
+    }
+
     /// Add a variable to the current scope
     pub fn add_variable(&mut self, name: String, _offset: usize) -> usize {
         // Use current frame_size as the index
@@ -0,0 +1,189 @@
+//! Proof-carrying module format
+//!
+//! A [`LoadedModule`] is what a loader receives: the bytecode and constants
+//! a [`CompilationResult`] produced, plus, for formally verified code, the
+//! Core expression the bytecode was compiled from and a proof about it, both
+//! in Core-World's binary serialization. The proof is re-checked with
+//! [`verify_equivalence`] before a VM is built, and a hash of the bytecode
+//! and constants taken when the proof was attached is compared against the
+//! module's code, so a proof-carrying module whose proof or code was
+//! corrupted or mismatched in transit never runs.
+//!
+//! This is an integrity check only, not tamper resistance: the hash is
+//! unkeyed, so whoever alters the code can recompute it, and modules
+//! without a proof load without any check at all.
+
+use super::stable_hash;
+use crate::core_compiler::CompilationResult;
+use core_world::core_expr::CoreExpr;
+use core_world::proof_checker::Proof;
+use core_world::{
+    deserialize_core_expr, deserialize_proof, serialize_core_expr, serialize_proof,
+    verify_equivalence, VerifyError,
+};
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::{FunctionInfo, PairConstant, VmError};
+use physics_world::vm::{FunctionNames, SymbolTable, VmState};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Recursion depth given to VMs built from a module
+const MAX_RECURSION_DEPTH: u32 = 100;
+
+/// Bytecode module, optionally carrying the proof that justifies it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedModule {
+    /// Instructions to execute
+    pub bytecode: Vec<OpCode>,
+    /// Constants referenced by the bytecode
+    pub constants: Vec<Value>,
+    /// Symbols referenced by `Symbol` opcodes
+    #[serde(default)]
+    pub symbol_table: SymbolTable,
+    /// Compiler metadata for each function id
+    #[serde(default)]
+    pub function_table: HashMap<u16, FunctionInfo>,
+    /// Source-level names of the functions, for stack traces
+    #[serde(default)]
+    pub function_names: FunctionNames,
+    /// Literal pairs pooled in `constants`, built when the module is instantiated
+    #[serde(default)]
+    pub pair_constants: Vec<PairConstant>,
+    /// Maximum execution steps allowed
+    pub step_limit: u64,
+    /// Maximum memory usage allowed
+    pub memory_limit: usize,
+    /// Serialized Core expression the bytecode was compiled from
+    #[serde(default)]
+    pub core_expr: Option<Vec<u8>>,
+    /// Serialized proof whose left-hand side is `core_expr`
+    #[serde(default)]
+    pub core_proof: Option<Vec<u8>>,
+    /// Hash of the bytecode and constants the proof was attached to, see
+    /// [`Self::code_hash`]
+    #[serde(default)]
+    pub proof_code_hash: Option<u64>,
+}
+
+impl LoadedModule {
+    /// Package a compilation result without a proof
+    #[must_use]
+    pub fn from_compilation(result: &CompilationResult) -> Self {
+        Self {
+            bytecode: result.bytecode.clone(),
+            constants: result.constants.clone(),
+            symbol_table: result.symbol_table.clone(),
+            function_table: result.function_table.clone(),
+            function_names: result.function_names.clone(),
+            pair_constants: result.pair_constants.clone(),
+            step_limit: result.step_limit,
+            memory_limit: result.memory_limit,
+            core_expr: None,
+            core_proof: None,
+            proof_code_hash: None,
+        }
+    }
+
+    /// Embed the Core expression the bytecode implements and a proof about it
+    #[must_use]
+    pub fn with_proof(mut self, core_expr: &CoreExpr, proof: &Proof) -> Self {
+        self.core_expr = Some(serialize_core_expr(core_expr));
+        self.core_proof = Some(serialize_proof(proof));
+        self.proof_code_hash = Some(self.code_hash());
+        self
+    }
+
+    /// Stable hash of the bytecode, constants and literal pairs, which
+    /// [`Self::with_proof`] binds to the proof
+    #[must_use]
+    pub fn code_hash(&self) -> u64 {
+        // Serializing plain vectors of opcodes and values cannot fail
+        let code = bincode::serialize(&(&self.bytecode, &self.constants, &self.pair_constants))
+            .unwrap_or_default();
+        stable_hash(&code)
+    }
+
+    /// Whether the module carries a proof
+    #[must_use]
+    pub fn is_proof_carrying(&self) -> bool {
+        self.core_proof.is_some()
+    }
+
+    /// Re-check the embedded proof against the embedded Core expression,
+    /// and the module's code against the code the proof was attached to.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`VerifyError::InvalidProofStructure`] if the module carries no
+    /// proof or code hash or either payload fails to decode, and
+    /// [`VerifyError::ProofRuleViolation`] if the proof does not check, is
+    /// about a different expression or was attached to different code.
+    pub fn verify_proof(&self) -> Result<(), VerifyError> {
+        let (Some(expr_bytes), Some(proof_bytes), Some(code_hash)) =
+            (&self.core_expr, &self.core_proof, self.proof_code_hash)
+        else {
+            return Err(VerifyError::InvalidProofStructure);
+        };
+        if code_hash != self.code_hash() {
+            return Err(VerifyError::ProofRuleViolation(
+                "module code differs from the code the proof was attached to".to_string(),
+            ));
+        }
+        let expr =
+            deserialize_core_expr(expr_bytes).map_err(|_| VerifyError::InvalidProofStructure)?;
+        let proof =
+            deserialize_proof(proof_bytes).map_err(|_| VerifyError::InvalidProofStructure)?;
+
+        let (lhs, _) = verify_equivalence(proof)?;
+        if lhs != expr {
+            return Err(VerifyError::ProofRuleViolation(
+                "proof does not concern the module's Core expression".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Build a VM ready to run the module as actor `actor_id`.
+    ///
+    /// Proof-carrying modules must pass [`Self::verify_proof`] first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InstantiateError::Proof`] with the [`Self::verify_proof`]
+    /// error of a proof-carrying module whose proof does not check, and
+    /// [`InstantiateError::Constants`] if the literal pairs cannot be built.
+    pub fn instantiate(&self, actor_id: u32) -> Result<VmState, InstantiateError> {
+        if self.is_proof_carrying() {
+            self.verify_proof()?;
+        }
+        let mut vm = VmState::new(
+            self.bytecode.clone(),
+            self.constants.clone(),
+            self.step_limit,
+            self.memory_limit,
+            actor_id,
+            MAX_RECURSION_DEPTH,
+        );
+        vm.attach_symbol_table(self.symbol_table.clone());
+        vm.attach_function_table(self.function_table.clone());
+        vm.attach_function_names(self.function_names.clone());
+        vm.link_pair_constants(&self.pair_constants)
+            .map_err(InstantiateError::Constants)?;
+        Ok(vm)
+    }
+}
+
+/// Why [`LoadedModule::instantiate`] refused to build a VM
+#[derive(Debug)]
+pub enum InstantiateError {
+    /// The module's proof did not check
+    Proof(VerifyError),
+    /// The module's literal pairs could not be allocated
+    Constants(VmError),
+}
+
+impl From<VerifyError> for InstantiateError {
+    fn from(error: VerifyError) -> Self {
+        InstantiateError::Proof(error)
+    }
+}
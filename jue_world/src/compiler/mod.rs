@@ -1,5 +1,24 @@
-/// Compiler module for Jue-World V2.0
-///
-/// This module contains compiler infrastructure and utilities.
+//! Compiler module for Jue-World V2.0
+//!
+//! This module contains compiler infrastructure and utilities.
 
+/// Compiled artifacts cached on disk
+pub mod artifact;
+/// Capability check audit trail
+pub mod capability_checking;
+/// Compilation environment and variable scopes
 pub mod environment;
+/// Proof-carrying module format
+pub mod loaded_module;
+
+pub use artifact::{load_artifact, load_artifact_for_source, save_artifact, source_hash};
+
+/// 64-bit FNV-1a hash of `bytes`.
+///
+/// Unlike `DefaultHasher` the result is the same in every build and on
+/// every platform, so it can be stored and compared later.
+pub(crate) fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -1,6 +1,7 @@
 
 use crate::parser::parse;
 use crate::compiler::core_compilation::{compile_ast_to_core_expr, generate_simple_proof, generate_comprehensive_proof};
+use crate::core_compilation::proof_generator::NormalizationCache;
 use core_world::proof_checker::{verify, Proof};
 
 #[test]
@@ -36,7 +37,7 @@ fn test_complex_lambda_proof_generation() {
     let core_expr = compile_ast_to_core_expr(&ast).unwrap();
 
     // Generate comprehensive proof
-    let proof = generate_comprehensive_proof(&core_expr);
+    let proof = generate_comprehensive_proof(&core_expr, &mut NormalizationCache::new());
 
     assert!(
         proof.is_some(),
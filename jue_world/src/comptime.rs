@@ -1,8 +1,307 @@
+use crate::ast::{AstNode, Literal};
 use crate::error::{CompilationError, SourceLocation};
+use crate::ffi_system::global_ffi_registry::FfiRegistry;
+use crate::ffi_system::standard_functions::create_standard_ffi_registry;
+use crate::optimize::child_nodes_mut;
 use crate::trust_tier::TrustTier;
 use physics_world::types::{Capability, OpCode, Value};
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
 /// Compile-time execution with restricted capabilities
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write as _};
+use std::hash::{Hash, Hasher};
+
+/// Memoized comptime macro results, keyed by macro name, argument ASTs and
+/// the rest of the evaluation's inputs.
+///
+/// Entries are bucketed by a hash of the macro name, arguments and compiled
+/// bytecode, and a hit must also match the constants pool, stack,
+/// capabilities and limits in its [`EvaluationInputs`], so a hash collision
+/// or a change to any input is a miss rather than a wrong result. Callers
+/// must only store results of pure evaluations (see [`is_pure_comptime`]).
+#[derive(Debug, Clone)]
+pub struct ComptimeCache<R> {
+    entries: HashMap<u64, Vec<CacheEntry<R>>>,
+}
+
+/// A cached result and everything it was computed from
+#[derive(Debug, Clone)]
+struct CacheEntry<R> {
+    macro_name: String,
+    arguments: Vec<AstNode>,
+    inputs: StoredInputs,
+    result: R,
+}
+
+impl<R> Default for ComptimeCache<R> {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+}
+
+impl<R> ComptimeCache<R> {
+    /// Create an empty cache
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Previously stored result of `macro_name` applied to `arguments` with `inputs`
+    #[must_use]
+    pub fn get(
+        &self,
+        macro_name: &str,
+        arguments: &[AstNode],
+        inputs: &EvaluationInputs<'_>,
+    ) -> Option<&R> {
+        self.entries
+            .get(&hash_key(macro_name, arguments, inputs.bytecode))?
+            .iter()
+            .find(|entry| {
+                entry.macro_name == macro_name
+                    && entry.arguments == arguments
+                    && entry.inputs.matches(inputs)
+            })
+            .map(|entry| &entry.result)
+    }
+
+    /// Store the result of `macro_name` applied to `arguments` with `inputs`
+    pub fn insert(
+        &mut self,
+        macro_name: &str,
+        arguments: &[AstNode],
+        inputs: &EvaluationInputs<'_>,
+        result: R,
+    ) {
+        self.insert_stored(macro_name, arguments, StoredInputs::new(inputs), result);
+    }
+
+    /// Store `result`, replacing an entry for the same call and inputs
+    fn insert_stored(
+        &mut self,
+        macro_name: &str,
+        arguments: &[AstNode],
+        inputs: StoredInputs,
+        result: R,
+    ) {
+        let bucket = self
+            .entries
+            .entry(hash_key(macro_name, arguments, &inputs.bytecode))
+            .or_default();
+        bucket.retain(|entry| {
+            entry.macro_name != macro_name || entry.arguments != arguments || entry.inputs != inputs
+        });
+        bucket.push(CacheEntry {
+            macro_name: macro_name.to_string(),
+            arguments: arguments.to_vec(),
+            inputs,
+            result,
+        });
+    }
+
+    /// Number of cached results
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    /// Whether nothing has been cached
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop every cached result
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Formats straight into a hasher, so hashing a Debug form allocates nothing
+struct HashWriter<'h>(&'h mut DefaultHasher);
+
+impl fmt::Write for HashWriter<'_> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        text.hash(self.0);
+        Ok(())
+    }
+}
+
+/// Hash of a macro call: its name, argument ASTs and compiled body
+fn hash_key(macro_name: &str, arguments: &[AstNode], bytecode: &[OpCode]) -> u64 {
+    // AstNode and OpCode hold floats and so cannot derive Hash; their Debug
+    // forms are structural
+    let mut hasher = DefaultHasher::new();
+    macro_name.hash(&mut hasher);
+    let _ = write!(HashWriter(&mut hasher), "{arguments:?}{bytecode:?}");
+    hasher.finish()
+}
+
+/// Everything besides the arguments that a comptime evaluation reads.
+///
+/// Covers the compiled macro body, the constants pool and stack it starts
+/// from, the capabilities it runs with and the step and memory limits that
+/// decide whether it completes.
+#[derive(Debug, Clone, Copy)]
+pub struct EvaluationInputs<'a> {
+    /// Compiled macro body
+    pub bytecode: &'a [OpCode],
+    /// Constants pool the body reads
+    pub constants: &'a [Value],
+    /// Stack the body starts from
+    pub stack: &'a [Value],
+    /// Capabilities the body runs with
+    pub capabilities: &'a HashSet<Capability>,
+    /// Step limit of the environment
+    pub max_steps: u64,
+    /// Memory limit of the environment
+    pub memory_limit: usize,
+}
+
+/// Owned copy of the [`EvaluationInputs`] a cached result was computed from
+#[derive(Debug, Clone, PartialEq)]
+struct StoredInputs {
+    bytecode: Vec<OpCode>,
+    constants: Vec<Value>,
+    stack: Vec<Value>,
+    capabilities: HashSet<Capability>,
+    max_steps: u64,
+    memory_limit: usize,
+}
+
+impl StoredInputs {
+    fn new(inputs: &EvaluationInputs<'_>) -> Self {
+        Self {
+            bytecode: inputs.bytecode.to_vec(),
+            constants: inputs.constants.to_vec(),
+            stack: inputs.stack.to_vec(),
+            capabilities: inputs.capabilities.clone(),
+            max_steps: inputs.max_steps,
+            memory_limit: inputs.memory_limit,
+        }
+    }
+
+    fn matches(&self, inputs: &EvaluationInputs<'_>) -> bool {
+        self.bytecode == inputs.bytecode
+            && self.constants == inputs.constants
+            && self.stack == inputs.stack
+            && &self.capabilities == inputs.capabilities
+            && self.max_steps == inputs.max_steps
+            && self.memory_limit == inputs.memory_limit
+    }
+}
+
+/// A comptime executor whose macro evaluations can be memoized; see
+/// [`execute_memoized`]
+pub(crate) trait MemoizedComptime {
+    /// Result of one evaluation
+    type Output: Clone;
+
+    /// Cache of earlier pure evaluations
+    fn cache(&self) -> &ComptimeCache<Self::Output>;
+
+    /// Mutable access to the cache
+    fn cache_mut(&mut self) -> &mut ComptimeCache<Self::Output>;
+
+    /// What evaluating `bytecode` now would read
+    fn inputs<'a>(&'a self, bytecode: &'a [OpCode]) -> EvaluationInputs<'a>;
+
+    /// Whether the budget left covers the steps and memory `result` used
+    fn fits(&self, result: &Self::Output) -> bool;
+
+    /// Take the steps and memory `result` used from the budget
+    fn charge(&mut self, result: &Self::Output);
+
+    /// Execute `bytecode` without consulting the cache
+    fn evaluate(&mut self, bytecode: Vec<OpCode>) -> Result<Self::Output, CompilationError>;
+}
+
+/// Evaluate a comptime macro, reusing the result of an earlier identical call.
+///
+/// `compile` produces the macro body's bytecode for `arguments`. Results of
+/// pure evaluations are cached, so a later call with the same name, equal
+/// arguments and the same [`EvaluationInputs`] returns the stored result
+/// without executing anything. Its recorded steps and memory are still taken
+/// from the budget, as re-executing it would; a stored result that no longer
+/// fits the budget is re-executed so the limit is reported.
+pub(crate) fn execute_memoized<E: MemoizedComptime>(
+    executor: &mut E,
+    macro_name: &str,
+    arguments: &[AstNode],
+    compile: impl FnOnce(&[AstNode]) -> Result<Vec<OpCode>, CompilationError>,
+) -> Result<E::Output, CompilationError> {
+    let bytecode = compile(arguments)?;
+    let inputs = executor.inputs(&bytecode);
+    let hit = executor
+        .cache()
+        .get(macro_name, arguments, &inputs)
+        .filter(|result| executor.fits(result))
+        .cloned();
+    if let Some(result) = hit {
+        executor.charge(&result);
+        return Ok(result);
+    }
+    if !is_pure_comptime(&bytecode) {
+        return executor.evaluate(bytecode);
+    }
+
+    // Executing changes the stack, so the inputs are copied beforehand
+    let stored = StoredInputs::new(&inputs);
+    let result = executor.evaluate(bytecode)?;
+    executor
+        .cache_mut()
+        .insert_stored(macro_name, arguments, stored, result.clone());
+    Ok(result)
+}
+
+/// Whether comptime bytecode is free of capability effects.
+///
+/// Capability checks, requests, grants, revocations, host calls and the
+/// capability-gated GC opcodes make a result depend on (or change) the
+/// capability environment, so evaluations containing them must not be memoized.
+#[must_use]
+pub fn is_pure_comptime(bytecode: &[OpCode]) -> bool {
+    !bytecode.iter().any(|opcode| {
+        matches!(
+            opcode,
+            OpCode::HasCap(_)
+                | OpCode::RequestCap(_, _)
+                | OpCode::GrantCap(_, _)
+                | OpCode::RevokeCap(_, _)
+                | OpCode::HostCall { .. }
+                | OpCode::GcCollect
+                | OpCode::GcStats
+        )
+    })
+}
+
+/// Result of a type test opcode applied to `value`, as the VM computes it
+pub(crate) fn type_test(opcode: &OpCode, value: &Value) -> Value {
+    use physics_world::vm::opcodes::type_ops;
+    match opcode {
+        OpCode::IsInt => Value::Bool(type_ops::is_int(value)),
+        OpCode::IsFloat => Value::Bool(type_ops::is_float(value)),
+        OpCode::IsPair => Value::Bool(type_ops::is_pair(value)),
+        OpCode::IsClosure => Value::Bool(type_ops::is_closure(value)),
+        _ => Value::Int(i64::from(value.type_tag())),
+    }
+}
+
+/// Result of a bitwise opcode applied to `lhs` and `rhs`, as the VM
+/// computes it; `None` for a shift amount outside `0..64`
+pub(crate) fn bitwise(opcode: &OpCode, lhs: i64, rhs: i64) -> Option<i64> {
+    use physics_world::vm::opcodes::arithmetic;
+    match opcode {
+        OpCode::BitAnd => Some(lhs & rhs),
+        OpCode::BitOr => Some(lhs | rhs),
+        OpCode::BitXor => Some(lhs ^ rhs),
+        OpCode::Shl => arithmetic::shift_left(lhs, rhs),
+        _ => arithmetic::shift_right(lhs, rhs),
+    }
+}
 
 /// Comptime environment with restricted capabilities
 #[derive(Debug, Clone)]
@@ -24,6 +323,9 @@ pub struct ComptimeEnv {
 
     /// Source location
     pub location: SourceLocation,
+
+    /// Results of pure comptime macro evaluations
+    pub cache: ComptimeCache<ComptimeResult>,
 }
 
 impl ComptimeEnv {
@@ -36,6 +338,7 @@ impl ComptimeEnv {
             step_count: 0,
             memory_usage: 0,
             location: SourceLocation::default(),
+            cache: ComptimeCache::new(),
         }
     }
 
@@ -142,6 +445,22 @@ impl ComptimeExecutor {
         Ok(result)
     }
 
+    /// Evaluate a comptime macro, reusing the result of an earlier identical call.
+    ///
+    /// See [`execute_memoized`] for when a cached result is used; a hit is
+    /// charged the steps and memory it recorded.
+    ///
+    /// # Errors
+    /// Returns the error from `compile`, or from executing the compiled bytecode.
+    pub fn execute_macro(
+        &mut self,
+        macro_name: &str,
+        arguments: &[AstNode],
+        compile: impl FnOnce(&[AstNode]) -> Result<Vec<OpCode>, CompilationError>,
+    ) -> Result<ComptimeResult, CompilationError> {
+        execute_memoized(self, macro_name, arguments, compile)
+    }
+
     /// Execute a single opcode
     fn execute_opcode(&mut self, opcode: OpCode) -> Result<(), CompilationError> {
         match opcode {
@@ -156,16 +475,8 @@ impl ComptimeExecutor {
             OpCode::Bool(b) => self.stack.push(Value::Bool(b)),
             OpCode::Int(i) => self.stack.push(Value::Int(i)),
             OpCode::Float(f) => self.stack.push(Value::Float(f)),
-            OpCode::Symbol(idx) => {
-                if idx < self.constants.len() {
-                    self.stack.push(self.constants[idx].clone());
-                } else {
-                    return Err(CompilationError::ComptimeError(format!(
-                        "Invalid constant index: {}",
-                        idx
-                    )));
-                }
-            }
+            // Symbol operands are symbol table ids, not constant indices
+            OpCode::Symbol(id) => self.stack.push(Value::Symbol(id)),
             OpCode::LoadString(idx) => {
                 // Load string from constant pool
                 if idx < self.constants.len() {
@@ -236,6 +547,28 @@ impl ComptimeExecutor {
                     ));
                 }
             }
+            OpCode::BytesToStr => {
+                // Decode bytes, leaving an Error value for invalid UTF-8
+                if let Some(Value::Bytes(bytes)) = self.stack.pop() {
+                    self.stack.push(match String::from_utf8(bytes) {
+                        Ok(s) => Value::String(s),
+                        Err(e) => Value::Error(format!("Invalid UTF-8: {}", e.utf8_error())),
+                    });
+                } else {
+                    return Err(CompilationError::ComptimeError(
+                        "Stack underflow or type mismatch for BytesToStr".to_string(),
+                    ));
+                }
+            }
+            OpCode::StrToBytes => {
+                if let Some(Value::String(s)) = self.stack.pop() {
+                    self.stack.push(Value::Bytes(s.into_bytes()));
+                } else {
+                    return Err(CompilationError::ComptimeError(
+                        "Stack underflow or type mismatch for StrToBytes".to_string(),
+                    ));
+                }
+            }
             OpCode::Dup => {
                 if let Some(val) = self.stack.last().cloned() {
                     self.stack.push(val);
@@ -315,6 +648,40 @@ impl ComptimeExecutor {
                 }
                 self.stack.push(Value::Nil); // Placeholder
             }
+            OpCode::ListLen => {
+                // Pairs are placeholders at comptime, so their length is unknown
+                return Err(CompilationError::ComptimeError(
+                    "List length not supported in comptime execution".to_string(),
+                ));
+            }
+            OpCode::DeepClone => {
+                // Comptime values live on the stack, so they are already unshared
+                if self.stack.is_empty() {
+                    return Err(CompilationError::ComptimeError(
+                        "Stack underflow".to_string(),
+                    ));
+                }
+            }
+            OpCode::ClosureAlphaEq => {
+                // Closures are placeholders at comptime, so only identical ones compare equal
+                if self.stack.len() < 2 {
+                    return Err(CompilationError::ComptimeError(
+                        "Stack underflow".to_string(),
+                    ));
+                }
+                let rhs = self.stack.pop().unwrap();
+                let lhs = self.stack.pop().unwrap();
+
+                self.stack.push(Value::Bool(lhs == rhs));
+            }
+            OpCode::MakeThunk | OpCode::Force => {
+                // Closures are placeholders at comptime, and so are their thunks
+                if self.stack.is_empty() {
+                    return Err(CompilationError::ComptimeError(
+                        "Stack underflow".to_string(),
+                    ));
+                }
+            }
             OpCode::Call(arg_count) => {
                 if self.stack.len() < arg_count as usize + 1 {
                     return Err(CompilationError::ComptimeError(
@@ -351,6 +718,26 @@ impl ComptimeExecutor {
                 // TODO: Implement return
                 self.stack.push(Value::Nil);
             }
+            OpCode::WithCaps { .. } => {
+                return Err(CompilationError::ComptimeError(
+                    "Capability regions not supported in comptime execution".to_string(),
+                ));
+            }
+            OpCode::CallN(_, _) | OpCode::RetN(_) => {
+                return Err(CompilationError::ComptimeError(
+                    "Multi-value calls not supported in comptime execution".to_string(),
+                ));
+            }
+            OpCode::CollectRest(_) => {
+                return Err(CompilationError::ComptimeError(
+                    "Rest parameters not supported in comptime execution".to_string(),
+                ));
+            }
+            OpCode::Spawn { .. } => {
+                return Err(CompilationError::ComptimeError(
+                    "Actor spawning not supported in comptime execution".to_string(),
+                ));
+            }
             OpCode::Jmp(_) => {
                 // TODO: Implement jump
                 return Err(CompilationError::ComptimeError(
@@ -465,6 +852,60 @@ impl ComptimeExecutor {
                     }
                 }
             }
+            OpCode::BitAnd | OpCode::BitOr | OpCode::BitXor | OpCode::Shl | OpCode::Shr => {
+                if self.stack.len() < 2 {
+                    return Err(CompilationError::ComptimeError(
+                        "Stack underflow".to_string(),
+                    ));
+                }
+                let rhs = self.stack.pop().unwrap();
+                let lhs = self.stack.pop().unwrap();
+
+                match (lhs, rhs) {
+                    (Value::Int(a), Value::Int(b)) => match bitwise(&opcode, a, b) {
+                        Some(result) => self.stack.push(Value::Int(result)),
+                        None => {
+                            return Err(CompilationError::ComptimeError(
+                                "Shift amount out of range".to_string(),
+                            ))
+                        }
+                    },
+                    _ => {
+                        return Err(CompilationError::ComptimeError(
+                            "Type mismatch for bitwise operation".to_string(),
+                        ))
+                    }
+                }
+            }
+            // GC operations - not supported in comptime (no heap)
+            OpCode::GcCollect | OpCode::GcStats => {
+                return Err(CompilationError::ComptimeError(
+                    "GC operations not supported in comptime execution".to_string(),
+                ));
+            }
+            // Budget introspection - results would depend on the comptime budget
+            OpCode::StepsRemaining | OpCode::MemoryRemaining => {
+                return Err(CompilationError::ComptimeError(
+                    "Resource introspection not supported in comptime execution".to_string(),
+                ));
+            }
+            // Type tests inspect the value only
+            OpCode::TypeOf
+            | OpCode::IsInt
+            | OpCode::IsFloat
+            | OpCode::IsPair
+            | OpCode::IsClosure => {
+                let value = self.stack.pop().ok_or_else(|| {
+                    CompilationError::ComptimeError("Stack underflow".to_string())
+                })?;
+                self.stack.push(type_test(&opcode, &value));
+            }
+            // Vector operations - not supported in comptime (no heap)
+            OpCode::MakeVector(_) | OpCode::VecGet | OpCode::VecSet | OpCode::VecLen => {
+                return Err(CompilationError::ComptimeError(
+                    "Vector operations not supported in comptime execution".to_string(),
+                ));
+            }
             // Float arithmetic operations - not supported in comptime
             OpCode::FAdd | OpCode::FSub | OpCode::FMul | OpCode::FDiv => {
                 return Err(CompilationError::ComptimeError(
@@ -571,6 +1012,45 @@ impl ComptimeExecutor {
                     "HostCall not supported in comptime execution".to_string(),
                 ));
             }
+            OpCode::MakeError => {
+                if self.stack.len() < 3 {
+                    return Err(CompilationError::ComptimeError(
+                        "Stack underflow".to_string(),
+                    ));
+                }
+                let payload = self.stack.pop().unwrap();
+                let (Some(Value::String(message)), Some(Value::Int(code))) =
+                    (self.stack.pop(), self.stack.pop())
+                else {
+                    return Err(CompilationError::ComptimeError(
+                        "MakeError expects an integer code and a string message".to_string(),
+                    ));
+                };
+                self.stack.push(Value::ErrorRecord {
+                    code,
+                    message,
+                    payload: Box::new(payload),
+                });
+            }
+            OpCode::ErrorPayload => match self.stack.pop() {
+                Some(Value::ErrorRecord { payload, .. }) => self.stack.push(*payload),
+                Some(_) => {
+                    return Err(CompilationError::ComptimeError(
+                        "ErrorPayload expects an error record".to_string(),
+                    ));
+                }
+                None => {
+                    return Err(CompilationError::ComptimeError(
+                        "Stack underflow".to_string(),
+                    ));
+                }
+            },
+            // Error handlers - not supported in comptime
+            OpCode::Throw | OpCode::PopErrorHandler => {
+                return Err(CompilationError::ComptimeError(
+                    "Error handlers not supported in comptime execution".to_string(),
+                ));
+            }
             // Sandbox instructions - not supported in comptime
             OpCode::InitSandbox
             | OpCode::IsolateCapabilities
@@ -587,6 +1067,44 @@ impl ComptimeExecutor {
     }
 }
 
+impl MemoizedComptime for ComptimeExecutor {
+    type Output = ComptimeResult;
+
+    fn cache(&self) -> &ComptimeCache<ComptimeResult> {
+        &self.env.cache
+    }
+
+    fn cache_mut(&mut self) -> &mut ComptimeCache<ComptimeResult> {
+        &mut self.env.cache
+    }
+
+    fn inputs<'a>(&'a self, bytecode: &'a [OpCode]) -> EvaluationInputs<'a> {
+        EvaluationInputs {
+            bytecode,
+            constants: &self.constants,
+            stack: &self.stack,
+            capabilities: &self.env.capabilities,
+            max_steps: self.env.max_steps,
+            memory_limit: self.env.memory_limit,
+        }
+    }
+
+    fn fits(&self, result: &ComptimeResult) -> bool {
+        let steps_left = self.env.max_steps.saturating_sub(self.env.step_count);
+        let memory_left = self.env.memory_limit.saturating_sub(self.env.memory_usage);
+        result.steps_used <= steps_left && result.memory_used <= memory_left
+    }
+
+    fn charge(&mut self, result: &ComptimeResult) {
+        self.env.step_count += result.steps_used;
+        self.env.memory_usage += result.memory_used;
+    }
+
+    fn evaluate(&mut self, bytecode: Vec<OpCode>) -> Result<ComptimeResult, CompilationError> {
+        self.execute(bytecode)
+    }
+}
+
 /// Execute comptime code with restricted capabilities
 pub fn execute_comptime(
     bytecode: Vec<OpCode>,
@@ -598,6 +1116,196 @@ pub fn execute_comptime(
     executor.execute(bytecode)
 }
 
+/// Fold pure compile-time-constant subexpressions into literals.
+///
+/// Calls to the built-in arithmetic, comparison and string operators whose
+/// arguments are all literals are evaluated, innermost first, and replaced
+/// by their result. Each operator is evaluated as the VM opcode of the same
+/// name computes it, and only for operands that opcode accepts. This is
+/// comptime evaluation, so the pipeline only runs it for tiers granted
+/// `ComptimeEval`.
+///
+/// Only deterministic, effect-free code is folded: FFI calls, capability
+/// forms and calls to host functions are left untouched, arguments and all.
+/// Operators a program rebinds anywhere are never folded, and neither is
+/// anything the VM would reject at runtime, such as overflowing arithmetic
+/// or division by zero, so the error is still raised there.
+#[must_use]
+pub fn fold_constants(ast: &AstNode) -> AstNode {
+    fold_constants_with_registry(ast, &create_standard_ffi_registry())
+}
+
+/// Like [`fold_constants`], treating the functions in `registry` as the
+/// host functions that are never folded
+#[must_use]
+pub fn fold_constants_with_registry(ast: &AstNode, registry: &FfiRegistry) -> AstNode {
+    let mut rebound = HashSet::new();
+    collect_bound_names(ast, &mut rebound);
+    let mut ast = ast.clone();
+    fold_in(&mut ast, &rebound, registry);
+    ast
+}
+
+fn fold_in(node: &mut AstNode, rebound: &HashSet<String>, registry: &FfiRegistry) {
+    match node {
+        AstNode::FfiCall { .. }
+        | AstNode::RequireCapability { .. }
+        | AstNode::HasCapability { .. }
+        | AstNode::MacroDefinition { .. } => return,
+        AstNode::Call { function, .. }
+            if operator_name(function)
+                .is_some_and(|name| registry.find_function(name).is_some()) =>
+        {
+            return
+        }
+        _ => {}
+    }
+
+    for child in child_nodes_mut(node) {
+        fold_in(child, rebound, registry);
+    }
+
+    let AstNode::Call {
+        function,
+        arguments,
+        ..
+    } = node
+    else {
+        return;
+    };
+    let Some(name) = operator_name(function).filter(|name| !rebound.contains(*name)) else {
+        return;
+    };
+    let literals: Option<Vec<&Literal>> = arguments
+        .iter()
+        .map(|argument| match argument {
+            AstNode::Literal(literal) => Some(literal),
+            _ => None,
+        })
+        .collect();
+    if let Some(folded) = literals.and_then(|literals| apply_operator(name, &literals)) {
+        *node = AstNode::Literal(folded);
+    }
+}
+
+/// Name a call's function position refers to, if it is a plain name
+fn operator_name(function: &AstNode) -> Option<&str> {
+    match function {
+        AstNode::Symbol(name) | AstNode::Variable(name) => Some(name),
+        _ => None,
+    }
+}
+
+/// Every name `node` binds with a lambda, `let` form or `define`
+fn collect_bound_names(node: &AstNode, names: &mut HashSet<String>) {
+    match node {
+        AstNode::Lambda {
+            parameters, rest, ..
+        } => names.extend(parameters.iter().chain(rest).cloned()),
+        AstNode::Let { bindings, .. }
+        | AstNode::LetStar { bindings, .. }
+        | AstNode::Letrec { bindings, .. } => {
+            names.extend(bindings.iter().map(|(name, _)| name.clone()));
+        }
+        AstNode::Define { name, .. } => {
+            names.insert(name.clone());
+        }
+        _ => {}
+    }
+    for child in crate::analysis::child_nodes(node) {
+        collect_bound_names(child, names);
+    }
+}
+
+/// Opcode whose semantics folding the built-in operator `name` follows.
+///
+/// The arithmetic operators fold left over two or more operands; the others
+/// take exactly two.
+fn operator_opcode(name: &str) -> Option<OpCode> {
+    let opcode = match name {
+        "+" => OpCode::Add,
+        "-" => OpCode::Sub,
+        "*" => OpCode::Mul,
+        "/" => OpCode::Div,
+        "%" => OpCode::Mod,
+        "=" => OpCode::Eq,
+        "!=" => OpCode::Ne,
+        "<" => OpCode::Lt,
+        ">" => OpCode::Gt,
+        "<=" => OpCode::Lte,
+        ">=" => OpCode::Gte,
+        "str-concat" => OpCode::StrConcat,
+        _ => return None,
+    };
+    Some(opcode)
+}
+
+/// Whether `opcode` is an arithmetic operator that folds over any number of
+/// operands from two up
+fn is_variadic_operator(opcode: &OpCode) -> bool {
+    matches!(
+        opcode,
+        OpCode::Add | OpCode::Sub | OpCode::Mul | OpCode::Div | OpCode::Mod
+    )
+}
+
+/// Result of the built-in operator `name` on literal `arguments`, or `None`
+/// if it has no opcode or the VM would reject the operands
+fn apply_operator(name: &str, arguments: &[&Literal]) -> Option<Literal> {
+    let opcode = operator_opcode(name)?;
+    match (&opcode, arguments) {
+        (OpCode::StrConcat, [Literal::String(lhs), Literal::String(rhs)]) => {
+            Some(Literal::String(format!("{lhs}{rhs}")))
+        }
+        (_, [first, rest @ ..]) if is_variadic_operator(&opcode) && !rest.is_empty() => {
+            rest.iter().try_fold((*first).clone(), |acc, operand| {
+                arithmetic(&opcode, &acc, operand)
+            })
+        }
+        (_, [lhs, rhs]) => comparison(&opcode, lhs, rhs).map(Literal::Bool),
+        _ => None,
+    }
+}
+
+/// `lhs` and `rhs` combined by the arithmetic `opcode`; the VM's integer
+/// opcodes take integers only
+fn arithmetic(opcode: &OpCode, lhs: &Literal, rhs: &Literal) -> Option<Literal> {
+    let (Literal::Int(lhs), Literal::Int(rhs)) = (lhs, rhs) else {
+        return None;
+    };
+    match opcode {
+        OpCode::Add => lhs.checked_add(*rhs),
+        OpCode::Sub => lhs.checked_sub(*rhs),
+        OpCode::Mul => lhs.checked_mul(*rhs),
+        OpCode::Div => lhs.checked_div(*rhs),
+        OpCode::Mod => lhs.checked_rem(*rhs),
+        _ => None,
+    }
+    .map(Literal::Int)
+}
+
+/// `lhs` compared with `rhs` by the comparison `opcode`: ordering between
+/// two integers, equality between non-float literals of the same type.
+/// Floats are left to the VM, whose float comparison policy is only known
+/// at runtime.
+fn comparison(opcode: &OpCode, lhs: &Literal, rhs: &Literal) -> Option<bool> {
+    let ordering = match (lhs, rhs) {
+        (Literal::Int(lhs), Literal::Int(rhs)) => Some(lhs.cmp(rhs)),
+        _ => None,
+    };
+    let same_type = std::mem::discriminant(lhs) == std::mem::discriminant(rhs)
+        && !matches!(lhs, Literal::Float(_));
+    match opcode {
+        OpCode::Eq if same_type => Some(lhs == rhs),
+        OpCode::Ne if same_type => Some(lhs != rhs),
+        OpCode::Lt => ordering.map(Ordering::is_lt),
+        OpCode::Gt => ordering.map(Ordering::is_gt),
+        OpCode::Lte => ordering.map(Ordering::is_le),
+        OpCode::Gte => ordering.map(Ordering::is_ge),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 #[path = "test/comptime.rs"]
 mod tests;
@@ -0,0 +1,339 @@
+//! Reusable queries over the AST.
+//!
+//! Analyses here are computed once per AST and consulted by the compilers,
+//! so that rules such as "which expressions are in tail position" live in a
+//! single place instead of being re-derived by each code generator.
+
+use crate::ast::AstNode;
+use crate::error::CompilationError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Identity of an AST node within a borrowed tree.
+///
+/// Identities are derived from node addresses, so they are only meaningful
+/// while the tree they were computed from is alive and unmoved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    /// Identity of `node`
+    #[must_use]
+    pub fn of(node: &AstNode) -> Self {
+        NodeId(std::ptr::from_ref(node) as usize)
+    }
+}
+
+/// Find every node whose value is returned directly by its enclosing lambda.
+///
+/// The rules are:
+/// - a lambda body is in tail position
+/// - let, let* and letrec bodies inherit the position of the binding form
+/// - both if branches inherit the position of the if; the condition never does
+/// - a trust-tier annotation passes its position to the wrapped expression
+/// - call arguments, callees, binding values and everything else are not in tail position
+///
+/// The root itself is not in tail position, since top-level code has no caller to return to.
+#[must_use]
+pub fn tail_positions(ast: &AstNode) -> HashSet<NodeId> {
+    let mut tails = HashSet::new();
+    mark_tail_positions(ast, false, &mut tails);
+    tails
+}
+
+/// Find every reference to a variable that is not in scope.
+///
+/// `forms` are top-level forms in program order; a top-level `define` is
+/// visible to the forms after it. Scoping follows the Physics-World compiler:
+/// let bindings see only the enclosing scope, let* bindings see the bindings
+/// before them, letrec bindings see each other.
+/// Returns one `VariableNotFound` per unbound reference, in source order.
+#[must_use]
+pub fn unbound_variables(forms: &[AstNode]) -> Vec<CompilationError> {
+    let mut scopes = vec![HashSet::new()];
+    let mut errors = Vec::new();
+    for form in forms {
+        collect_unbound(form, &mut scopes, &mut errors);
+    }
+    errors
+}
+
+/// Resolve the variables of `node` against `scopes`, innermost last
+fn collect_unbound(
+    node: &AstNode,
+    scopes: &mut Vec<HashSet<String>>,
+    errors: &mut Vec<CompilationError>,
+) {
+    match node {
+        AstNode::Variable(name) if !scopes.iter().any(|scope| scope.contains(name)) => {
+            errors.push(CompilationError::VariableNotFound(name.clone()));
+        }
+        AstNode::Lambda {
+            parameters,
+            rest,
+            body,
+            ..
+        } => {
+            scopes.push(parameters.iter().chain(rest).cloned().collect());
+            collect_unbound(body, scopes, errors);
+            scopes.pop();
+        }
+        AstNode::Let { bindings, body, .. } => {
+            for (_, value) in bindings {
+                collect_unbound(value, scopes, errors);
+            }
+            scopes.push(bindings.iter().map(|(name, _)| name.clone()).collect());
+            collect_unbound(body, scopes, errors);
+            scopes.pop();
+        }
+        AstNode::LetStar { bindings, body, .. } => {
+            scopes.push(HashSet::new());
+            for (name, value) in bindings {
+                collect_unbound(value, scopes, errors);
+                if let Some(scope) = scopes.last_mut() {
+                    scope.insert(name.clone());
+                }
+            }
+            collect_unbound(body, scopes, errors);
+            scopes.pop();
+        }
+        AstNode::Letrec { bindings, body, .. } => {
+            scopes.push(bindings.iter().map(|(name, _)| name.clone()).collect());
+            for (_, value) in bindings {
+                collect_unbound(value, scopes, errors);
+            }
+            collect_unbound(body, scopes, errors);
+            scopes.pop();
+        }
+        AstNode::Define { name, value, .. } => {
+            collect_unbound(value, scopes, errors);
+            if let Some(scope) = scopes.last_mut() {
+                scope.insert(name.clone());
+            }
+        }
+        AstNode::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            collect_unbound(condition, scopes, errors);
+            collect_unbound(then_branch, scopes, errors);
+            collect_unbound(else_branch, scopes, errors);
+        }
+        AstNode::TrustTier { expression, .. } => collect_unbound(expression, scopes, errors),
+        AstNode::Call {
+            function,
+            arguments,
+            ..
+        } => {
+            collect_unbound(function, scopes, errors);
+            for arg in arguments {
+                collect_unbound(arg, scopes, errors);
+            }
+        }
+        AstNode::FfiCall { arguments, .. } => {
+            for arg in arguments {
+                collect_unbound(arg, scopes, errors);
+            }
+        }
+        // Not compiled to bytecode, so there is nothing to resolve
+        _ => {}
+    }
+}
+
+/// A self-recursive function that makes no progress between calls
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TerminationWarning {
+    /// Name the function is bound to
+    pub function: String,
+    /// Parameters of the function, all passed through unchanged by the recursive call
+    pub parameters: Vec<String>,
+}
+
+impl fmt::Display for TerminationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Function {} may not terminate: it calls itself with unchanged arguments ({})",
+            self.function,
+            self.parameters.join(" ")
+        )
+    }
+}
+
+/// Flag self-recursive functions whose recursive call cannot make progress.
+///
+/// A function bound by `define` or `letrec` is flagged when its body calls it
+/// with every parameter passed through unchanged, e.g. `(f n)` inside
+/// `(lambda (n) ...)`. This is a cheap syntactic heuristic: a call whose
+/// arguments differ from the entry in any way, such as `(f (- n 1))`, is
+/// assumed to make progress, and calls inside nested lambdas are ignored.
+#[must_use]
+pub fn check_termination(ast: &AstNode) -> Vec<TerminationWarning> {
+    let mut warnings = Vec::new();
+    find_recursive_functions(ast, &mut warnings);
+    warnings
+}
+
+/// Check every `define` and `letrec` binding of a lambda in `node`
+fn find_recursive_functions(node: &AstNode, warnings: &mut Vec<TerminationWarning>) {
+    let check_binding = |name: &str, value: &AstNode, warnings: &mut Vec<TerminationWarning>| {
+        if let AstNode::Lambda {
+            parameters, body, ..
+        } = value
+        {
+            let entry: Vec<Option<&str>> = parameters.iter().map(|p| Some(p.as_str())).collect();
+            if calls_with_entry_arguments(body, name, &entry) {
+                warnings.push(TerminationWarning {
+                    function: name.to_string(),
+                    parameters: parameters.clone(),
+                });
+            }
+        }
+    };
+
+    match node {
+        AstNode::Define { name, value, .. } => check_binding(name, value, warnings),
+        AstNode::Letrec { bindings, .. } => {
+            for (name, value) in bindings {
+                check_binding(name, value, warnings);
+            }
+        }
+        _ => {}
+    }
+
+    for child in child_nodes(node) {
+        find_recursive_functions(child, warnings);
+    }
+}
+
+/// Whether `node` calls `function` with exactly the entry parameters.
+///
+/// `entry[i]` is the name still holding the function's i-th argument, or `None`
+/// once that parameter has been rebound.
+fn calls_with_entry_arguments(node: &AstNode, function: &str, entry: &[Option<&str>]) -> bool {
+    match node {
+        AstNode::Call {
+            function: callee,
+            arguments,
+            ..
+        } => {
+            let is_self_call =
+                matches!(callee.as_ref(), AstNode::Variable(name) if name == function);
+            let unchanged = arguments.len() == entry.len()
+                && arguments.iter().zip(entry).all(|(arg, param)| {
+                    matches!((arg, param), (AstNode::Variable(name), Some(param)) if name == param)
+                });
+            (is_self_call && unchanged)
+                || calls_with_entry_arguments(callee, function, entry)
+                || arguments
+                    .iter()
+                    .any(|arg| calls_with_entry_arguments(arg, function, entry))
+        }
+        // A nested lambda's body does not run as part of this call
+        AstNode::Lambda { .. } => false,
+        AstNode::Let { bindings, body, .. }
+        | AstNode::LetStar { bindings, body, .. }
+        | AstNode::Letrec { bindings, body, .. } => {
+            if bindings
+                .iter()
+                .any(|(_, value)| calls_with_entry_arguments(value, function, entry))
+            {
+                return true;
+            }
+            if bindings.iter().any(|(name, _)| name == function) {
+                // The function name is shadowed in the body
+                return false;
+            }
+            let entry: Vec<Option<&str>> = entry
+                .iter()
+                .map(|param| param.filter(|p| !bindings.iter().any(|(name, _)| name == p)))
+                .collect();
+            calls_with_entry_arguments(body, function, &entry)
+        }
+        other => child_nodes(other)
+            .into_iter()
+            .any(|child| calls_with_entry_arguments(child, function, entry)),
+    }
+}
+
+/// Direct children of `node`, including binding values and define values
+pub(crate) fn child_nodes(node: &AstNode) -> Vec<&AstNode> {
+    match node {
+        AstNode::Define { value, .. } => vec![value.as_ref()],
+        AstNode::LetStar { bindings, body, .. } | AstNode::Letrec { bindings, body, .. } => {
+            bindings
+                .iter()
+                .map(|(_, value)| value)
+                .chain(std::iter::once(body.as_ref()))
+                .collect()
+        }
+        other => crate::capability_analyzer::get_child_nodes(other),
+    }
+}
+
+/// Record `node` if it is in tail position and descend into its children
+fn mark_tail_positions(node: &AstNode, in_tail_position: bool, tails: &mut HashSet<NodeId>) {
+    if in_tail_position {
+        tails.insert(NodeId::of(node));
+    }
+
+    match node {
+        AstNode::Lambda { body, .. } => mark_tail_positions(body, true, tails),
+        AstNode::Let { bindings, body, .. }
+        | AstNode::LetStar { bindings, body, .. }
+        | AstNode::Letrec { bindings, body, .. } => {
+            for (_, value) in bindings {
+                mark_tail_positions(value, false, tails);
+            }
+            mark_tail_positions(body, in_tail_position, tails);
+        }
+        AstNode::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            mark_tail_positions(condition, false, tails);
+            mark_tail_positions(then_branch, in_tail_position, tails);
+            mark_tail_positions(else_branch, in_tail_position, tails);
+        }
+        AstNode::TrustTier { expression, .. } => {
+            mark_tail_positions(expression, in_tail_position, tails);
+        }
+        AstNode::Call {
+            function,
+            arguments,
+            ..
+        } => {
+            mark_tail_positions(function, false, tails);
+            for arg in arguments {
+                mark_tail_positions(arg, false, tails);
+            }
+        }
+        AstNode::Define { value, .. } => mark_tail_positions(value, false, tails),
+        AstNode::MacroDefinition { body, .. } => mark_tail_positions(body, false, tails),
+        AstNode::MacroExpansion { arguments, .. } | AstNode::FfiCall { arguments, .. } => {
+            for arg in arguments {
+                mark_tail_positions(arg, false, tails);
+            }
+        }
+        AstNode::List { elements, .. } => {
+            for element in elements {
+                mark_tail_positions(element, false, tails);
+            }
+        }
+        AstNode::Cons { car, cdr, .. } => {
+            mark_tail_positions(car, false, tails);
+            mark_tail_positions(cdr, false, tails);
+        }
+        AstNode::Literal(_)
+        | AstNode::Symbol(_)
+        | AstNode::Variable(_)
+        | AstNode::RequireCapability { .. }
+        | AstNode::HasCapability { .. }
+        | AstNode::TypeSignature { .. } => {}
+    }
+}
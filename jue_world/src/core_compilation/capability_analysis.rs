@@ -1,9 +1,12 @@
-use crate::error::{CapabilityViolation, CompilationError};
+use crate::error::{CapabilityViolation, CompilationError, CompilationWarning, SourceLocation};
 /// Capability analysis for Jue-World V2.0
 ///
 /// This module analyzes AST expressions to determine required capabilities
 /// and validates that the trust tier provides sufficient capabilities.
+use crate::ffi_system::global_ffi_registry::FfiRegistry;
+use crate::ffi_system::standard_functions::create_standard_ffi_registry;
 use crate::shared::ast::AstNode;
+use crate::shared::capability_set::CapabilitySet;
 use crate::shared::trust_tier::TrustTier;
 use physics_world::types::Capability;
 use std::collections::HashSet;
@@ -23,79 +26,293 @@ fn string_to_capability(cap_str: &str) -> Option<Capability> {
         "SysCreateActor" => Some(Capability::SysCreateActor),
         "SysTerminateActor" => Some(Capability::SysTerminateActor),
         "SysClock" => Some(Capability::SysClock),
+        "SysGc" => Some(Capability::SysGc),
         _ => None,
     }
 }
 
 /// Analyze capabilities required by an AST expression
-pub fn analyze_capabilities(ast: &AstNode) -> Result<HashSet<Capability>, CompilationError> {
+///
+/// Both explicit capability forms and calls to capability-gated FFI functions
+/// contribute to the required set. FFI functions are looked up in the
+/// standard registry.
+pub fn analyze_capabilities(ast: &AstNode) -> Result<CapabilitySet, CompilationError> {
+    analyze_capabilities_with_registry(ast, &create_standard_ffi_registry())
+}
+
+/// Analyze capabilities required by an AST expression against `registry`
+///
+/// Each FFI call requires whatever capability its registry entry declares,
+/// so registering a function is enough for the analysis to account for it.
+///
+/// # Errors
+///
+/// Currently infallible; the `Result` matches [`analyze_capabilities`].
+pub fn analyze_capabilities_with_registry(
+    ast: &AstNode,
+    registry: &FfiRegistry,
+) -> Result<CapabilitySet, CompilationError> {
+    let mut required_caps = HashSet::new();
+    analyze_expression(ast, registry, &mut required_caps);
+    Ok(required_caps.into_iter().collect())
+}
+
+/// Like [`analyze_capabilities`], but spreads the walk across up to `threads`
+/// worker threads.
+///
+/// A node's requirements are its own plus those of its subexpressions, in
+/// any order, so the tree is split into independent subtrees from the root
+/// down until there are a few per thread. Each worker analyzes a share of
+/// them and the resulting sets are unioned. The result is always the same
+/// as the sequential analysis.
+///
+/// # Errors
+///
+/// Currently infallible; the `Result` matches [`analyze_capabilities`].
+pub fn analyze_capabilities_parallel(
+    ast: &AstNode,
+    threads: usize,
+) -> Result<CapabilitySet, CompilationError> {
+    let registry = create_standard_ffi_registry();
+    if threads <= 1 {
+        return analyze_capabilities_with_registry(ast, &registry);
+    }
+
+    // Peel off levels of the tree, keeping the capabilities of the nodes
+    // peeled, until the remaining subtrees can be shared out evenly
     let mut required_caps = HashSet::new();
-    analyze_expression(ast, &mut required_caps);
-    Ok(required_caps)
+    let mut subtrees = vec![ast];
+    while subtrees.len() < threads * 4 {
+        let mut next = Vec::new();
+        for node in subtrees {
+            if let Some(cap) = own_capability(node, &registry) {
+                required_caps.insert(cap);
+            }
+            for_each_analyzed_child(node, |child| next.push(child));
+        }
+        if next.is_empty() {
+            return Ok(required_caps.into_iter().collect());
+        }
+        subtrees = next;
+    }
+
+    let chunk_size = subtrees.len().div_ceil(threads);
+    let registry = &registry;
+    let partial_sets: Vec<HashSet<Capability>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = subtrees
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut caps = HashSet::new();
+                    for subtree in chunk {
+                        analyze_expression(subtree, registry, &mut caps);
+                    }
+                    caps
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    for caps in partial_sets {
+        required_caps.extend(caps);
+    }
+    Ok(required_caps.into_iter().collect())
+}
+
+/// Capabilities gained and lost between two versions of a program
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapabilityDiff {
+    /// Required by the new version but not the old one
+    pub added: CapabilitySet,
+    /// Required by the old version but no longer by the new one
+    pub removed: CapabilitySet,
+}
+
+impl CapabilityDiff {
+    /// Whether both versions require exactly the same capabilities
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compare the capability requirements of two compilations
+#[must_use]
+pub fn diff(old: &CapabilitySet, new: &CapabilitySet) -> CapabilityDiff {
+    CapabilityDiff {
+        added: new.difference(old),
+        removed: old.difference(new),
+    }
 }
 
 /// Validate that the trust tier provides required capabilities
 pub fn validate_tier_capabilities(
     tier: TrustTier,
-    required_caps: &HashSet<Capability>,
+    required_caps: &CapabilitySet,
 ) -> Result<(), CompilationError> {
-    let granted_caps = tier.granted_capabilities();
-
-    for cap in required_caps {
-        if !granted_caps.contains(cap) {
-            return Err(CompilationError::CapabilityError(CapabilityViolation {
-                required: cap.clone(),
-                tier,
-                location: Default::default(),
-                suggestion: format!(
-                    "Trust tier {:?} does not grant required capability {:?}",
-                    tier, cap
-                ),
-            }));
-        }
+    let granted_caps = tier.capability_set();
+    if required_caps.is_subset_of(&granted_caps) {
+        return Ok(());
     }
 
-    Ok(())
+    // Report the first missing capability in sorted order
+    let missing = required_caps.difference(&granted_caps);
+    let cap = &missing.as_slice()[0];
+    Err(CompilationError::CapabilityError(CapabilityViolation {
+        required: cap.clone(),
+        tier,
+        location: Default::default(),
+        suggestion: format!(
+            "Trust tier {:?} does not grant required capability {:?}",
+            tier, cap
+        ),
+    }))
 }
 
-/// Recursively analyze expressions for capability requirements
-fn analyze_expression(ast: &AstNode, required_caps: &mut HashSet<Capability>) {
+/// How the compiler treats FFI calls whose capability was never declared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CapabilityCheckMode {
+    /// Leave undeclared capabilities to the runtime `HasCap` checks
+    #[default]
+    Permissive,
+    /// Reject programs that use a capability they never declare
+    Strict,
+}
+
+/// Detect capabilities that are declared with `require-capability` but never
+/// exercised by an FFI call
+///
+/// Over-broad capability requests are reported as warnings rather than errors,
+/// in the order the declarations appear in the source.
+#[must_use]
+pub fn detect_unused_capabilities(ast: &AstNode) -> Vec<CompilationWarning> {
+    detect_unused_capabilities_with_registry(ast, &create_standard_ffi_registry())
+}
+
+/// Like [`detect_unused_capabilities`], with the capabilities FFI calls use
+/// taken from `registry`
+#[must_use]
+pub fn detect_unused_capabilities_with_registry(
+    ast: &AstNode,
+    registry: &FfiRegistry,
+) -> Vec<CompilationWarning> {
+    let mut declared = Vec::new();
+    let mut used = Vec::new();
+    collect_capability_usage(ast, registry, &mut declared, &mut used);
+
+    declared
+        .into_iter()
+        .filter(|cap| !used.iter().any(|(used_cap, _)| used_cap == cap))
+        .map(CompilationWarning::UnusedCapability)
+        .collect()
+}
+
+/// Check that every capability an FFI call uses is declared somewhere in
+/// the program with `require-capability`
+///
+/// The check is the counterpart of [`detect_unused_capabilities`] and only
+/// applies in [`CapabilityCheckMode::Strict`]; permissive compilation
+/// leaves undeclared uses to the runtime checks.
+///
+/// # Errors
+///
+/// Returns [`CompilationError::UndeclaredCapability`] for the first
+/// undeclared capability the program uses.
+pub fn check_declared_capabilities(
+    ast: &AstNode,
+    mode: CapabilityCheckMode,
+) -> Result<(), CompilationError> {
+    check_declared_capabilities_with_registry(ast, mode, &create_standard_ffi_registry())
+}
+
+/// Like [`check_declared_capabilities`], with the capabilities FFI calls
+/// use taken from `registry`
+///
+/// # Errors
+///
+/// Fails like [`check_declared_capabilities`].
+pub fn check_declared_capabilities_with_registry(
+    ast: &AstNode,
+    mode: CapabilityCheckMode,
+    registry: &FfiRegistry,
+) -> Result<(), CompilationError> {
+    if mode == CapabilityCheckMode::Permissive {
+        return Ok(());
+    }
+
+    let mut declared = Vec::new();
+    let mut used = Vec::new();
+    collect_capability_usage(ast, registry, &mut declared, &mut used);
+
+    match used.into_iter().find(|(cap, _)| !declared.contains(cap)) {
+        Some((cap, location)) => Err(CompilationError::UndeclaredCapability { cap, location }),
+        None => Ok(()),
+    }
+}
+
+/// Record the capabilities an expression declares and the ones its FFI calls
+/// use, each with the location of its first use
+fn collect_capability_usage(
+    ast: &AstNode,
+    registry: &FfiRegistry,
+    declared: &mut Vec<Capability>,
+    used: &mut Vec<(Capability, SourceLocation)>,
+) {
     match ast {
-        AstNode::FfiCall { arguments, .. } => {
-            // FFI calls may require capabilities based on the function being called
-            // For now, we'll analyze the arguments
-            for arg in arguments {
-                analyze_expression(arg, required_caps);
-            }
-        }
         AstNode::RequireCapability { capability, .. } => {
             if let Some(cap) = string_to_capability(capability) {
-                required_caps.insert(cap);
+                if !declared.contains(&cap) {
+                    declared.push(cap);
+                }
             }
         }
-        AstNode::HasCapability { capability, .. } => {
-            if let Some(cap) = string_to_capability(capability) {
-                required_caps.insert(cap);
+        AstNode::FfiCall {
+            function,
+            arguments,
+            location,
+        } => {
+            if let Some(cap) = ffi_capability(registry, function) {
+                record_use(used, cap, location);
+            }
+            for arg in arguments {
+                collect_capability_usage(arg, registry, declared, used);
             }
         }
         AstNode::Call {
             function,
             arguments,
-            ..
+            location,
         } => {
-            analyze_expression(function, required_caps);
+            // Calls by a name that resolves to an FFI function compile to
+            // HostCall, whether the parser read it as a symbol or a variable
+            if let AstNode::Symbol(name) | AstNode::Variable(name) = function.as_ref() {
+                if let Some(cap) = ffi_capability(registry, name) {
+                    record_use(used, cap, location);
+                }
+            }
+            collect_capability_usage(function, registry, declared, used);
             for arg in arguments {
-                analyze_expression(arg, required_caps);
+                collect_capability_usage(arg, registry, declared, used);
             }
         }
         AstNode::Lambda { body, .. } => {
-            analyze_expression(body, required_caps);
+            collect_capability_usage(body, registry, declared, used);
         }
-        AstNode::Let { bindings, body, .. } => {
+        AstNode::Let { bindings, body, .. }
+        | AstNode::LetStar { bindings, body, .. }
+        | AstNode::Letrec { bindings, body, .. } => {
             for (_, expr) in bindings {
-                analyze_expression(expr, required_caps);
+                collect_capability_usage(expr, registry, declared, used);
             }
-            analyze_expression(body, required_caps);
+            collect_capability_usage(body, registry, declared, used);
         }
         AstNode::If {
             condition,
@@ -103,26 +320,120 @@ fn analyze_expression(ast: &AstNode, required_caps: &mut HashSet<Capability>) {
             else_branch,
             ..
         } => {
-            analyze_expression(condition, required_caps);
-            analyze_expression(then_branch, required_caps);
-            analyze_expression(else_branch, required_caps);
+            collect_capability_usage(condition, registry, declared, used);
+            collect_capability_usage(then_branch, registry, declared, used);
+            collect_capability_usage(else_branch, registry, declared, used);
+        }
+        AstNode::TrustTier { expression, .. } => {
+            collect_capability_usage(expression, registry, declared, used);
+        }
+        AstNode::Define { value, .. } => {
+            collect_capability_usage(value, registry, declared, used);
         }
         AstNode::List { elements, .. } => {
             for elem in elements {
-                analyze_expression(elem, required_caps);
+                collect_capability_usage(elem, registry, declared, used);
             }
         }
         AstNode::Cons { car, cdr, .. } => {
-            analyze_expression(car, required_caps);
-            analyze_expression(cdr, required_caps);
+            collect_capability_usage(car, registry, declared, used);
+            collect_capability_usage(cdr, registry, declared, used);
         }
         AstNode::MacroExpansion { arguments, .. } => {
             for arg in arguments {
-                analyze_expression(arg, required_caps);
+                collect_capability_usage(arg, registry, declared, used);
             }
         }
-        _ => {
-            // Other expression types don't require special capabilities
+        _ => {}
+    }
+}
+
+/// Record a use of `cap` unless an earlier one was already recorded
+fn record_use(
+    used: &mut Vec<(Capability, SourceLocation)>,
+    cap: Capability,
+    location: &SourceLocation,
+) {
+    if !used.iter().any(|(used_cap, _)| *used_cap == cap) {
+        used.push((cap, location.clone()));
+    }
+}
+
+/// Recursively analyze expressions for capability requirements
+fn analyze_expression(
+    ast: &AstNode,
+    registry: &FfiRegistry,
+    required_caps: &mut HashSet<Capability>,
+) {
+    if let Some(cap) = own_capability(ast, registry) {
+        required_caps.insert(cap);
+    }
+    for_each_analyzed_child(ast, |child| {
+        analyze_expression(child, registry, required_caps);
+    });
+}
+
+/// The capability `ast` requires by itself, not counting its subexpressions
+fn own_capability(ast: &AstNode, registry: &FfiRegistry) -> Option<Capability> {
+    match ast {
+        AstNode::FfiCall { function, .. } => ffi_capability(registry, function),
+        AstNode::RequireCapability { capability, .. }
+        | AstNode::HasCapability { capability, .. } => string_to_capability(capability),
+        AstNode::Call { function, .. } => match function.as_ref() {
+            // Calls by name that resolve to FFI functions compile to HostCall
+            AstNode::Symbol(name) | AstNode::Variable(name) => ffi_capability(registry, name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Visit the subexpressions of `ast` whose requirements count towards its own
+fn for_each_analyzed_child<'a>(ast: &'a AstNode, mut visit: impl FnMut(&'a AstNode)) {
+    match ast {
+        AstNode::FfiCall { arguments, .. }
+        | AstNode::List {
+            elements: arguments,
+            ..
+        }
+        | AstNode::MacroExpansion { arguments, .. } => arguments.iter().for_each(visit),
+        AstNode::Call {
+            function,
+            arguments,
+            ..
+        } => {
+            visit(function);
+            arguments.iter().for_each(visit);
+        }
+        AstNode::Lambda { body, .. } => visit(body),
+        AstNode::Let { bindings, body, .. } | AstNode::LetStar { bindings, body, .. } => {
+            for (_, expr) in bindings {
+                visit(expr);
+            }
+            visit(body);
         }
+        AstNode::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            visit(condition);
+            visit(then_branch);
+            visit(else_branch);
+        }
+        AstNode::Cons { car, cdr, .. } => {
+            visit(car);
+            visit(cdr);
+        }
+        // Other expression types don't require special capabilities
+        _ => {}
     }
 }
+
+/// The capability an FFI function requires, if it is registered and gated
+fn ffi_capability(registry: &FfiRegistry, name: &str) -> Option<Capability> {
+    registry
+        .find_function(name)
+        .and_then(|func| func.required_capability.clone())
+}
@@ -1,4 +1,6 @@
 use crate::error::{CapabilityViolation, CompilationError, SourceLocation};
+use crate::ffi_system::standard_functions::create_standard_ffi_registry;
+use crate::shared::capability_set::CapabilitySet;
 use crate::trust_tier::TrustTier;
 use physics_world::types::Capability;
 use std::collections::HashSet;
@@ -59,30 +61,12 @@ pub fn analyze_capabilities(
 }
 
 /// Get the capability required for a specific FFI function
+///
+/// The standard FFI registry is the single source of truth for this mapping.
 pub fn get_ffi_function_capability(function_name: &str) -> Option<Capability> {
-    // Use the standard capability mapping from FFI generator
-    let capability_mapping = create_standard_ffi_capability_mapping();
-    capability_mapping.get(function_name).cloned()
-}
-
-/// Create standard capability mapping for FFI functions
-fn create_standard_ffi_capability_mapping() -> std::collections::HashMap<String, Capability> {
-    let mut mapping = std::collections::HashMap::new();
-
-    // I/O capabilities
-    mapping.insert("read-sensor".to_string(), Capability::IoReadSensor);
-    mapping.insert("write-actuator".to_string(), Capability::IoWriteActuator);
-    mapping.insert("network-send".to_string(), Capability::IoNetwork);
-    mapping.insert("network-receive".to_string(), Capability::IoNetwork);
-    mapping.insert("persist-write".to_string(), Capability::IoPersist);
-    mapping.insert("persist-read".to_string(), Capability::IoPersist);
-
-    // System capabilities
-    mapping.insert("get-wall-clock".to_string(), Capability::SysClock);
-    mapping.insert("spawn-actor".to_string(), Capability::SysCreateActor);
-    mapping.insert("terminate-actor".to_string(), Capability::SysTerminateActor);
-
-    mapping
+    create_standard_ffi_registry()
+        .find_function(function_name)
+        .and_then(|func| func.required_capability.clone())
 }
 
 /// Helper function to parse capability from string
@@ -100,6 +84,7 @@ pub fn parse_capability(cap_str: &str) -> Result<Capability, CompilationError> {
         "sys-create-actor" => Ok(Capability::SysCreateActor),
         "sys-terminate-actor" => Ok(Capability::SysTerminateActor),
         "sys-clock" => Ok(Capability::SysClock),
+        "sys-gc" => Ok(Capability::SysGc),
         _ => Err(CompilationError::ParseError {
             message: format!("Unknown capability: {}", cap_str),
             location: SourceLocation::default(),
@@ -177,23 +162,23 @@ pub fn validate_tier_capabilities(
     tier: TrustTier,
     required_caps: &[Capability],
 ) -> Result<(), CompilationError> {
-    let granted_caps = tier.granted_capabilities();
-
-    for cap in required_caps {
-        if !granted_caps.contains(cap) {
-            return Err(CompilationError::CapabilityError(CapabilityViolation {
-                required: cap.clone(),
-                tier,
-                location: SourceLocation::default(), // TODO: Get actual location
-                suggestion: format!(
-                    "This capability is not available in {:?} tier. Consider using a higher trust tier.",
-                    tier
-                ),
-            }));
-        }
+    let required: CapabilitySet = required_caps.iter().cloned().collect();
+    let granted_caps = tier.capability_set();
+    if required.is_subset_of(&granted_caps) {
+        return Ok(());
     }
 
-    Ok(())
+    // Report the first missing capability in sorted order
+    let missing = required.difference(&granted_caps);
+    Err(CompilationError::CapabilityError(CapabilityViolation {
+        required: missing.as_slice()[0].clone(),
+        tier,
+        location: SourceLocation::default(), // TODO: Get actual location
+        suggestion: format!(
+            "This capability is not available in {:?} tier. Consider using a higher trust tier.",
+            tier
+        ),
+    }))
 }
 
 /// Validate FFI call against trust tier capabilities
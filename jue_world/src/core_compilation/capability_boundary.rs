@@ -0,0 +1,129 @@
+/// Proofs across the boundary between pure code and capability operations
+///
+/// A capability operation, such as reading a sensor, has no λ-calculus
+/// meaning: its result is only known at run time. What can be proven is that
+/// everything around it is pure. Each capability operation is replaced by a
+/// fresh free variable, and the remaining term is proven to reduce to its
+/// normal form. β- and η-reduction are closed under substitution, so the same
+/// steps are valid for whatever value the operation produces: the proof
+/// covers the original program with every capability result held opaque.
+use core_world::core_expr::CoreExpr;
+use core_world::proof_checker::{prove_normalization, Proof, ProofError};
+use physics_world::types::Capability;
+
+/// Reduction steps allowed when proving the pure fragment
+const BOUNDARY_STEP_LIMIT: usize = 1000;
+
+/// A capability operation inside a Core term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapabilityPoint {
+    /// Child index taken at each level from the root to the operation:
+    /// 0 for a lambda body, an application's function or a pair's first
+    /// element, 1 for an application's argument or a pair's second element
+    pub path: Vec<usize>,
+    /// Capability the operation exercises
+    pub capability: Capability,
+}
+
+impl CapabilityPoint {
+    /// Mark the subterm at `path` as an operation exercising `capability`
+    #[must_use]
+    pub fn new(path: Vec<usize>, capability: Capability) -> Self {
+        Self { path, capability }
+    }
+}
+
+/// Replace each capability operation in `expr` with a fresh free variable.
+///
+/// With `n` the number of free variables `expr` already refers to, the
+/// operation at `capability_points[k]` becomes free variable `n + k`, i.e.
+/// `Var(n + k)` at the top level and `Var(d + n + k)` under `d` binders.
+///
+/// # Errors
+///
+/// Returns `ProofError::StepFailed` with the point's path if it does not
+/// lead to a subterm or lies inside another capability point.
+pub fn abstract_capability_points(
+    expr: &CoreExpr,
+    capability_points: &[CapabilityPoint],
+) -> Result<CoreExpr, ProofError> {
+    for (index, point) in capability_points.iter().enumerate() {
+        let nested = capability_points
+            .iter()
+            .enumerate()
+            .any(|(other, outer)| other != index && point.path.starts_with(&outer.path));
+        if nested {
+            return Err(boundary_error(
+                point,
+                "lies inside another capability point",
+            ));
+        }
+    }
+
+    let first_fresh = free_variable_bound(expr, 0);
+    let mut abstracted = expr.clone();
+    for (index, point) in capability_points.iter().enumerate() {
+        let (subterm, depth) = subterm_at(&mut abstracted, &point.path)
+            .ok_or_else(|| boundary_error(point, "does not lead to a subterm"))?;
+        *subterm = CoreExpr::Var(depth + first_fresh + index);
+    }
+    Ok(abstracted)
+}
+
+/// Prove the pure part of `core_expr`, holding its capability operations opaque.
+///
+/// The returned proof shows that `core_expr`, with the operations at
+/// `capability_points` abstracted as described in
+/// [`abstract_capability_points`], is equivalent to its normal form. The
+/// abstracted term is the proof's left-hand side, so the boundary can be
+/// read back from the proof itself.
+///
+/// # Errors
+///
+/// Returns an error if a capability point is invalid, or if the pure
+/// fragment does not normalize within the step limit.
+pub fn prove_capability_boundary(
+    core_expr: &CoreExpr,
+    capability_points: &[CapabilityPoint],
+) -> Result<Proof, ProofError> {
+    let abstracted = abstract_capability_points(core_expr, capability_points)?;
+    prove_normalization(abstracted, BOUNDARY_STEP_LIMIT)
+}
+
+fn boundary_error(point: &CapabilityPoint, problem: &str) -> ProofError {
+    ProofError::StepFailed {
+        path: point.path.clone(),
+        reason: format!("{} capability point {problem}", point.capability),
+    }
+}
+
+/// Smallest `n` such that every free variable of `expr` has index below `n`,
+/// for `expr` found under `depth` binders
+fn free_variable_bound(expr: &CoreExpr, depth: usize) -> usize {
+    match expr {
+        CoreExpr::Var(index) if *index >= depth => index - depth + 1,
+        CoreExpr::Var(_) | CoreExpr::Nat(_) => 0,
+        CoreExpr::Lam(body) => free_variable_bound(body, depth + 1),
+        CoreExpr::App(first, second) | CoreExpr::Pair(first, second) => {
+            free_variable_bound(first, depth).max(free_variable_bound(second, depth))
+        }
+    }
+}
+
+/// The subterm at `path` and the number of binders above it
+fn subterm_at<'a>(expr: &'a mut CoreExpr, path: &[usize]) -> Option<(&'a mut CoreExpr, usize)> {
+    let mut current = expr;
+    let mut depth = 0;
+    for &step in path {
+        current = match (current, step) {
+            (CoreExpr::Lam(body), 0) => {
+                depth += 1;
+                body
+            }
+            (CoreExpr::App(first, _) | CoreExpr::Pair(first, _), 0) => first,
+            (CoreExpr::App(_, second) | CoreExpr::Pair(_, second), 1) => second,
+            _ => return None,
+        };
+    }
+    Some((current, depth))
+}
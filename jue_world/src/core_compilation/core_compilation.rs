@@ -1,3 +1,4 @@
+use super::proof_generator::NormalizationCache;
 use crate::error::{CompilationError, SourceLocation};
 use core_world::core_expr::{app, lam, nat, var, CoreExpr};
 use core_world::proof_checker::{prove_beta, prove_eta, prove_normalization, verify, Proof};
@@ -158,6 +159,11 @@ pub fn compile_ast_to_core_expr_with_proofs(
 
             Ok((result, combined_proof))
         }
+        crate::ast::AstNode::Lambda { rest: Some(_), .. } => {
+            Err(CompilationError::ProofGenerationFailed(
+                "Rest parameters have no Core-World translation".to_string(),
+            ))
+        }
         crate::ast::AstNode::Lambda {
             parameters, body, ..
         } => {
@@ -394,9 +400,17 @@ pub fn generate_reduction_proof(expr: &CoreExpr) -> Option<Proof> {
 }
 
 /// Generate a comprehensive proof for lambda calculus operations
-pub fn generate_comprehensive_proof(expr: &CoreExpr) -> Option<Proof> {
+///
+/// Normalization proofs come from `cache`, so an obligation α-equivalent to
+/// one already proven with the same cache is not normalized again; pass
+/// [`NormalizationCache::with_shared`]'s cache to share them across
+/// compilations.
+pub fn generate_comprehensive_proof(
+    expr: &CoreExpr,
+    cache: &mut NormalizationCache,
+) -> Option<Proof> {
     // First, try to generate a normalization proof
-    if let Some(normalization_proof) = generate_simple_proof(expr) {
+    if let Some(normalization_proof) = cache.normalization_proof(expr) {
         return Some(normalization_proof);
     }
 
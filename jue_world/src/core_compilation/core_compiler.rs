@@ -1,51 +1,361 @@
-use crate::error::CompilationError;
-use crate::macro_system::macro_expander::{expand_macros, MacroExpansionContext};
+use super::capability_analysis::CapabilityCheckMode;
+use super::dead_ffi_elimination::eliminate_dead_ffi_calls;
+use super::empirical_validation::{run_harness, TestHarness};
+use crate::ast::AstNode;
+use crate::capability_set::CapabilitySet;
+use crate::compiler::capability_checking::{audit_capabilities, CapabilityCheck, CheckType};
+use crate::comptime::fold_constants_with_registry;
+use crate::error::{CompilationError, CompilationWarning, SourceMap};
+use crate::escape_analysis::AnalysisContext;
+use crate::ffi_system::global_ffi_registry::FfiRegistry;
+use crate::ffi_system::standard_functions::create_standard_ffi_registry;
+use crate::macro_system::macro_expander::{create_macro_expansion_context, expand_macros};
 use crate::trust_tier::TrustTier;
 use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::state::{FunctionInfo, PairConstant};
+use physics_world::vm::{FunctionNames, SymbolTable};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Main compilation pipeline for Jue-World V2.0
 ///
 /// This is the primary entry point for compiling Jue source code into executable bytecode.
+/// Capabilities used without a `require-capability` declaration are left to
+/// the runtime checks; see [`compile_with_capability_mode`].
 pub fn compile(
     source: &str,
     tier: TrustTier,
     default_step_limit: u64,
     default_mem_limit: usize,
+) -> Result<CompilationResult, CompilationError> {
+    compile_with_capability_mode(
+        source,
+        tier,
+        default_step_limit,
+        default_mem_limit,
+        CapabilityCheckMode::Permissive,
+    )
+}
+
+/// Compile source, choosing how capabilities used without a
+/// `require-capability` declaration are treated.
+///
+/// In [`CapabilityCheckMode::Strict`] such a program is rejected before code
+/// generation; in [`CapabilityCheckMode::Permissive`] it compiles exactly as
+/// by [`compile`].
+///
+/// # Errors
+///
+/// Returns the same errors as [`compile`], plus
+/// [`CompilationError::UndeclaredCapability`] in strict mode.
+pub fn compile_with_capability_mode(
+    source: &str,
+    tier: TrustTier,
+    default_step_limit: u64,
+    default_mem_limit: usize,
+    mode: CapabilityCheckMode,
+) -> Result<CompilationResult, CompilationError> {
+    compile_with_registry(
+        source,
+        tier,
+        default_step_limit,
+        default_mem_limit,
+        mode,
+        &create_standard_ffi_registry(),
+    )
+}
+
+/// Compile source against a caller-supplied FFI registry.
+///
+/// Every stage that resolves FFI calls (dead-call elimination, constant
+/// folding, capability analysis and checking, and code generation) uses
+/// `registry`, so functions registered there compile like the standard
+/// ones. The [`CompilationResult::source_hash`] does not cover the registry.
+///
+/// # Errors
+///
+/// Returns the same errors as [`compile_with_capability_mode`].
+pub fn compile_with_registry(
+    source: &str,
+    tier: TrustTier,
+    default_step_limit: u64,
+    default_mem_limit: usize,
+    mode: CapabilityCheckMode,
+    registry: &FfiRegistry,
+) -> Result<CompilationResult, CompilationError> {
+    compile_reading_inputs(
+        source,
+        tier,
+        default_step_limit,
+        default_mem_limit,
+        mode,
+        &[],
+        registry,
+    )
+}
+
+/// Compile source and run it against `harness`, recording the outcome in
+/// [`CompilationResult::empirical_check`].
+///
+/// The program reads the harness inputs as variables. Only Empirical and
+/// Experimental code is validated this way; Formal and Verified results
+/// stay [`EmpiricalResult::NotApplicable`].
+///
+/// # Errors
+///
+/// Returns the same errors as [`compile`]. Failing cases are not errors;
+/// they are reported as [`EmpiricalResult::Failed`].
+pub fn compile_with_harness(
+    source: &str,
+    tier: TrustTier,
+    default_step_limit: u64,
+    default_mem_limit: usize,
+    harness: &TestHarness,
+) -> Result<CompilationResult, CompilationError> {
+    let mut result = compile_reading_inputs(
+        source,
+        tier,
+        default_step_limit,
+        default_mem_limit,
+        CapabilityCheckMode::Permissive,
+        &harness.inputs,
+        &create_standard_ffi_registry(),
+    )?;
+    if matches!(tier, TrustTier::Empirical | TrustTier::Experimental) {
+        result.empirical_check = run_harness(&result, harness);
+    }
+    Ok(result)
+}
+
+/// `ast` with its constant subexpressions folded, for tiers allowed
+/// compile-time evaluation
+fn fold_constants_if_allowed(ast: &AstNode, tier: TrustTier, registry: &FfiRegistry) -> AstNode {
+    if tier.allows_capability(&Capability::ComptimeEval) {
+        fold_constants_with_registry(ast, registry)
+    } else {
+        ast.clone()
+    }
+}
+
+/// The compilation pipeline against `registry`, for a program that may
+/// read `inputs` as variables held in the first top-level local slots
+fn compile_reading_inputs(
+    source: &str,
+    tier: TrustTier,
+    default_step_limit: u64,
+    default_mem_limit: usize,
+    mode: CapabilityCheckMode,
+    inputs: &[String],
+    registry: &FfiRegistry,
 ) -> Result<CompilationResult, CompilationError> {
     // 1. Parse source to AST
     let ast = crate::parser::parse(source)?;
 
     // 2. Expand macros (with capability checking)
-    let ctx = MacroExpansionContext {
-        macros: HashMap::new(),
-        trust_tier: tier,
-    };
-    let expanded_ast = expand_macros(&ast, &ctx)?;
+    let mut ctx = create_macro_expansion_context(tier);
+    let expanded_ast = expand_macros(&ast, &mut ctx)?;
+
+    // Drop unused pure FFI calls before their capabilities are counted
+    let expanded_ast = eliminate_dead_ffi_calls(&expanded_ast, registry);
+    let expanded_ast = fold_constants_if_allowed(&expanded_ast, tier, registry);
 
     // 3. Analyze capability requirements
-    let required_caps = super::capability_analysis::analyze_capabilities(&expanded_ast)?;
+    let required_caps =
+        super::capability_analysis::analyze_capabilities_with_registry(&expanded_ast, registry)?;
 
     // 4. Verify tier allows required capabilities
     super::capability_analysis::validate_tier_capabilities(tier, &required_caps)?;
 
+    // Every capability used must be declared, when the mode asks for it
+    super::capability_analysis::check_declared_capabilities_with_registry(
+        &expanded_ast,
+        mode,
+        registry,
+    )?;
+
     // 5. Compile based on tier
-    let result = match tier {
-        TrustTier::Formal | TrustTier::Verified => {
-            compile_to_core_and_verify(expanded_ast, tier, default_step_limit, default_mem_limit)
-        }
+    let mut result = match tier {
+        TrustTier::Formal | TrustTier::Verified => compile_to_core_and_verify(
+            expanded_ast,
+            tier,
+            default_step_limit,
+            default_mem_limit,
+            inputs,
+            registry,
+        ),
         TrustTier::Empirical | TrustTier::Experimental => compile_to_physics_with_checks(
             expanded_ast,
             tier,
             default_step_limit,
             default_mem_limit,
+            inputs,
+            registry,
         ),
     }?;
+    result.source_hash = Some(crate::compiler::source_hash(
+        source,
+        tier,
+        default_step_limit,
+        default_mem_limit,
+        mode,
+        inputs,
+    ));
 
     Ok(result)
 }
 
+/// Compile source with the step and memory limits that `tier` defaults to.
+///
+/// See [`TrustTier::default_limits`] for the per-tier budgets.
+///
+/// # Errors
+///
+/// Returns the same errors as [`compile`].
+pub fn compile_with_tier_defaults(
+    source: &str,
+    tier: TrustTier,
+) -> Result<CompilationResult, CompilationError> {
+    let (step_limit, mem_limit) = tier.default_limits();
+    compile(source, tier, step_limit, mem_limit)
+}
+
+/// Compile source, reporting every diagnostic instead of stopping at the first.
+///
+/// Parsing recovers at top-level form boundaries, and each parsed form is
+/// run through macro expansion, capability analysis and variable resolution
+/// before any error is returned. When no diagnostics are found the source is
+/// compiled exactly as by [`compile`].
+///
+/// # Errors
+///
+/// Returns all collected errors, grouped by pass, if any pass fails.
+pub fn compile_collecting(
+    source: &str,
+    tier: TrustTier,
+    default_step_limit: u64,
+    default_mem_limit: usize,
+) -> Result<CompilationResult, Vec<CompilationError>> {
+    let mut context = AnalysisContext::new();
+
+    let (forms, parse_errors) = crate::parser::parse_collecting(source);
+    for error in parse_errors {
+        context.report_error(error);
+    }
+
+    let mut ctx = create_macro_expansion_context(tier);
+    let registry = create_standard_ffi_registry();
+    let mut expanded_forms = Vec::new();
+    for form in &forms {
+        match expand_macros(form, &mut ctx) {
+            Ok(expanded) => {
+                let expanded = eliminate_dead_ffi_calls(&expanded, &registry);
+                expanded_forms.push(fold_constants_if_allowed(&expanded, tier, &registry));
+            }
+            Err(error) => context.report_error(error),
+        }
+    }
+
+    for form in &expanded_forms {
+        let checked =
+            super::capability_analysis::analyze_capabilities_with_registry(form, &registry)
+                .and_then(|caps| {
+                    super::capability_analysis::validate_tier_capabilities(tier, &caps)
+                });
+        if let Err(error) = checked {
+            context.report_error(error);
+        }
+    }
+
+    for error in crate::analysis::unbound_variables(&expanded_forms) {
+        context.report_error(error);
+    }
+
+    if !context.errors.is_empty() {
+        return Err(context.errors);
+    }
+    compile(source, tier, default_step_limit, default_mem_limit).map_err(|error| vec![error])
+}
+
+/// Compile many independent units, returning one result per unit in input order.
+///
+/// A unit that fails to compile, or panics inside the compiler, only affects its
+/// own entry; the rest of the batch still compiles.
+#[must_use]
+pub fn compile_batch(
+    units: &[(String, TrustTier)],
+    default_step_limit: u64,
+    default_mem_limit: usize,
+) -> Vec<Result<CompilationResult, CompilationError>> {
+    compile_batch_parallel(units, default_step_limit, default_mem_limit, 1)
+}
+
+/// Like [`compile_batch`], but spreads the units across up to `threads` worker threads.
+///
+/// Results are returned in input order regardless of the thread count.
+#[must_use]
+pub fn compile_batch_parallel(
+    units: &[(String, TrustTier)],
+    default_step_limit: u64,
+    default_mem_limit: usize,
+    threads: usize,
+) -> Vec<Result<CompilationResult, CompilationError>> {
+    let compile_chunk = |chunk: &[(String, TrustTier)]| {
+        chunk
+            .iter()
+            .map(|(source, tier)| {
+                compile_isolated(source, *tier, default_step_limit, default_mem_limit)
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let threads = threads.clamp(1, units.len().max(1));
+    if threads == 1 {
+        return compile_chunk(units);
+    }
+
+    let chunk_size = units.len().div_ceil(threads);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = units
+            .chunks(chunk_size)
+            .map(|chunk| (chunk.len(), scope.spawn(move || compile_chunk(chunk))))
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|(len, worker)| {
+                worker.join().unwrap_or_else(|_| {
+                    (0..len)
+                        .map(|_| {
+                            Err(CompilationError::InternalError(
+                                "Batch worker thread panicked".to_string(),
+                            ))
+                        })
+                        .collect()
+                })
+            })
+            .collect()
+    })
+}
+
+/// Compile a single unit, turning a compiler panic into an internal error
+fn compile_isolated(
+    source: &str,
+    tier: TrustTier,
+    default_step_limit: u64,
+    default_mem_limit: usize,
+) -> Result<CompilationResult, CompilationError> {
+    std::panic::catch_unwind(|| compile(source, tier, default_step_limit, default_mem_limit))
+        .unwrap_or_else(|panic| {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| (*s).to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            Err(CompilationError::InternalError(format!(
+                "Compiler panicked: {message}"
+            )))
+        })
+}
+
 /// Compilation result containing all outputs from the compilation process
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompilationResult {
@@ -62,20 +372,151 @@ pub struct CompilationResult {
     pub memory_limit: usize,
 
     /// Capabilities required by the compiled code
-    pub required_capabilities: Vec<Capability>,
+    pub required_capabilities: CapabilitySet,
 
     /// Capabilities granted by the trust tier
-    pub granted_capabilities: Vec<Capability>,
+    pub granted_capabilities: CapabilitySet,
 
     /// Whether execution is sandboxed
     pub sandboxed: bool,
 
-    /// Source mapping for debugging
-    pub source_map: Vec<(usize, usize, String)>,
+    /// Source location of the bytecode, for debugging and runtime errors
+    pub source_map: SourceMap,
+
+    /// Non-fatal diagnostics such as unused capability requests
+    #[serde(default)]
+    pub warnings: Vec<CompilationWarning>,
+
+    /// Audit trail of how each required capability is enforced
+    #[serde(default)]
+    pub capability_audit: Vec<CapabilityCheck>,
+
+    /// Symbols referenced by `Symbol` opcodes; attach to the VM before running
+    #[serde(default)]
+    pub symbol_table: SymbolTable,
+
+    /// Metadata for each compiled function, see
+    /// [`PhysicsWorldCompiler::function_table`](crate::physics_compiler::PhysicsWorldCompiler::function_table);
+    /// attach to the VM before running
+    #[serde(default)]
+    pub function_table: HashMap<u16, FunctionInfo>,
+
+    /// Names of the compiled functions by function id; attach to the VM so
+    /// stack traces show them
+    #[serde(default)]
+    pub function_names: FunctionNames,
+
+    /// Literal pairs pooled in `constants`; link them into the VM before running
+    #[serde(default)]
+    pub pair_constants: Vec<PairConstant>,
+
+    /// Hash of the source this was compiled from, see
+    /// [`source_hash`](crate::compiler::source_hash)
+    #[serde(default)]
+    pub source_hash: Option<u64>,
+
+    /// Outcome of running the code against a test harness, see
+    /// [`compile_with_harness`]
+    #[serde(default)]
+    pub empirical_check: EmpiricalResult,
+}
+
+impl CompilationResult {
+    /// Version of the schema produced by [`Self::to_json_pretty`]
+    pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+    /// Render the result as human-readable JSON for external tools.
+    ///
+    /// Unlike the serde representation, indices are decoded. The schema is:
+    ///
+    /// ```text
+    /// {
+    ///   "schema_version": 1,
+    ///   "tier_sandboxed": bool,
+    ///   "step_limit": u64,
+    ///   "memory_limit": usize,
+    ///   "required_capabilities": [capability name, sorted],
+    ///   "granted_capabilities": [capability name, sorted],
+    ///   "bytecode": [{ "offset": n, "op": mnemonic, "instruction": full text,
+    ///                  "resolved": constant or symbol text (only for indexed operands) }],
+    ///   "constants": [{ "index": n, "value": text }],
+    ///   "symbols": [name, by id],
+    ///   "warnings": [message]
+    /// }
+    /// ```
+    #[must_use]
+    pub fn to_json_pretty(&self) -> String {
+        let capability_names = |caps: &CapabilitySet| {
+            let mut names: Vec<String> = caps.iter().map(ToString::to_string).collect();
+            names.sort();
+            names
+        };
+
+        let bytecode: Vec<serde_json::Value> = self
+            .bytecode
+            .iter()
+            .enumerate()
+            .map(|(offset, op)| {
+                let instruction = format!("{op:?}");
+                let op_name: String = instruction
+                    .chars()
+                    .take_while(char::is_ascii_alphanumeric)
+                    .collect();
+                let mut entry = serde_json::json!({
+                    "offset": offset,
+                    "op": op_name,
+                    "instruction": instruction,
+                });
+                if let Some(resolved) = self.resolve_operand(op) {
+                    entry["resolved"] = serde_json::Value::String(resolved);
+                }
+                entry
+            })
+            .collect();
+
+        let constants: Vec<serde_json::Value> = self
+            .constants
+            .iter()
+            .enumerate()
+            .map(|(index, value)| serde_json::json!({ "index": index, "value": value.to_string() }))
+            .collect();
+
+        let symbols: Vec<&str> = (0..self.symbol_table.len())
+            .filter_map(|id| self.symbol_table.resolve(id))
+            .collect();
+
+        let document = serde_json::json!({
+            "schema_version": Self::JSON_SCHEMA_VERSION,
+            "tier_sandboxed": self.sandboxed,
+            "step_limit": self.step_limit,
+            "memory_limit": self.memory_limit,
+            "required_capabilities": capability_names(&self.required_capabilities),
+            "granted_capabilities": capability_names(&self.granted_capabilities),
+            "bytecode": bytecode,
+            "constants": constants,
+            "symbols": symbols,
+            "warnings": self.warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        });
+        serde_json::to_string_pretty(&document).unwrap_or_default()
+    }
+
+    /// Text of the constant or symbol an instruction's index operand refers to
+    fn resolve_operand(&self, op: &OpCode) -> Option<String> {
+        match op {
+            OpCode::LoadString(index) | OpCode::GetConst(index) => {
+                self.constants.get(*index).map(ToString::to_string)
+            }
+            OpCode::Symbol(id) => self
+                .symbol_table
+                .resolve(*id)
+                .map(|name| format!("'{name}")),
+            _ => None,
+        }
+    }
 }
 
 /// Empirical validation result
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub enum EmpiricalResult {
     Passed {
         tests_run: usize,
@@ -85,6 +526,7 @@ pub enum EmpiricalResult {
         reason: String,
         failing_case: String,
     },
+    #[default]
     NotApplicable,
 }
 
@@ -108,10 +550,18 @@ fn compile_to_core_and_verify(
     tier: TrustTier,
     step_limit: u64,
     mem_limit: usize,
+    inputs: &[String],
+    registry: &FfiRegistry,
 ) -> Result<CompilationResult, CompilationError> {
     // Placeholder: Core-World compilation not yet implemented
     // For now, fall back to physics compilation
-    compile_to_physics_with_checks(ast, tier, step_limit, mem_limit)
+    let mut result =
+        compile_to_physics_with_checks(ast, tier, step_limit, mem_limit, inputs, registry)?;
+
+    // Formal code carries no runtime checks; its capabilities are covered statically
+    result.capability_audit =
+        audit_capabilities(result.required_capabilities.as_slice(), &CheckType::Proof);
+    Ok(result)
 }
 
 /// Compile to Physics-World for Empirical/Experimental tiers
@@ -120,16 +570,38 @@ fn compile_to_physics_with_checks(
     tier: TrustTier,
     step_limit: u64,
     mem_limit: usize,
+    inputs: &[String],
+    registry: &FfiRegistry,
 ) -> Result<CompilationResult, CompilationError> {
     // Use the physics_compiler for all compilation for now
-    let (bytecode, constants) =
-        crate::physics_integration::physics_compiler::compile_to_physics_world(&ast, tier)?;
+    let (
+        bytecode,
+        constants,
+        symbol_table,
+        source_map,
+        function_table,
+        function_names,
+        pair_constants,
+    ) = crate::physics_integration::physics_compiler::compile_to_physics_world_with_registry(
+        &ast, tier, inputs, registry,
+    )?;
 
     // Analyze required capabilities for audit trail
-    let required_capabilities: Vec<Capability> =
-        super::capability_analysis::analyze_capabilities(&ast)?
+    let required_capabilities =
+        super::capability_analysis::analyze_capabilities_with_registry(&ast, registry)?;
+
+    // Flag capability requests that no FFI call actually exercises
+    let mut warnings =
+        super::capability_analysis::detect_unused_capabilities_with_registry(&ast, registry);
+    warnings.extend(
+        crate::analysis::check_termination(&ast)
             .into_iter()
-            .collect();
+            .map(CompilationWarning::PossiblyNonTerminating),
+    );
+
+    // Physics-path capabilities are enforced by inserted runtime checks
+    let capability_audit =
+        audit_capabilities(required_capabilities.as_slice(), &CheckType::Runtime);
 
     Ok(CompilationResult {
         bytecode,
@@ -137,8 +609,16 @@ fn compile_to_physics_with_checks(
         step_limit,
         memory_limit: mem_limit,
         required_capabilities,
-        granted_capabilities: tier.granted_capabilities().into_iter().collect(),
+        granted_capabilities: tier.capability_set(),
         sandboxed: tier == TrustTier::Experimental,
-        source_map: Vec::new(),
+        source_map,
+        warnings,
+        capability_audit,
+        symbol_table,
+        function_table,
+        function_names,
+        pair_constants,
+        source_hash: None,
+        empirical_check: EmpiricalResult::NotApplicable,
     })
 }
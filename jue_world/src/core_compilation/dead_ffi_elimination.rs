@@ -0,0 +1,190 @@
+//! Removal of FFI calls whose results are never used.
+//!
+//! A `let` binding whose value is a call to a function the registry marks
+//! as pure, and whose name is never referenced, computes a value nobody
+//! reads and has no other effect. Dropping the binding removes its
+//! `HostCall` from the bytecode and, since capability analysis runs on the
+//! rewritten tree, the capability the call would have required. Calls to
+//! impure functions such as `write-actuator` are always kept.
+
+use crate::ast::{AstNode, Literal};
+use crate::ffi_system::global_ffi_registry::FfiRegistry;
+
+/// Rewrite `ast` without the let bindings that hold unused pure FFI calls.
+///
+/// A binding is removed when its value is a pure FFI call whose arguments
+/// are literals, variables or further pure FFI calls, and its name appears
+/// neither in the body nor in another binding of the same form. A call
+/// through a name a lambda, binding form or `define` binds in scope is a
+/// closure call rather than an FFI call, as it is for the compiler, and is
+/// kept. A binding form left with no bindings is replaced by its body.
+/// `letrec` bindings are kept, since their values may refer to one another.
+#[must_use]
+pub fn eliminate_dead_ffi_calls(ast: &AstNode, registry: &FfiRegistry) -> AstNode {
+    let mut ast = ast.clone();
+    let mut scope = Vec::new();
+    collect_defines(&ast, &mut scope);
+    rewrite(&mut ast, registry, &mut scope);
+    ast
+}
+
+/// Rewrite `node`, where `scope` holds the names bound around it
+fn rewrite(node: &mut AstNode, registry: &FfiRegistry, scope: &mut Vec<String>) {
+    let outer = scope.len();
+    // Each let* value sees the bindings before it; let values see none
+    let sequential = matches!(node, AstNode::LetStar { .. });
+    match node {
+        AstNode::Let { bindings, body, .. } | AstNode::LetStar { bindings, body, .. } => {
+            for (name, value) in bindings.iter_mut() {
+                rewrite(value, registry, scope);
+                if sequential {
+                    scope.push(name.clone());
+                }
+            }
+            scope.truncate(outer);
+            scope.extend(bindings.iter().map(|(name, _)| name.clone()));
+            rewrite(body, registry, scope);
+            scope.truncate(outer);
+            remove_dead_bindings(bindings, body, registry, scope, sequential);
+            if bindings.is_empty() {
+                let body = std::mem::replace(body.as_mut(), AstNode::Literal(Literal::Nil));
+                *node = body;
+            }
+        }
+        AstNode::Letrec { bindings, body, .. } => {
+            scope.extend(bindings.iter().map(|(name, _)| name.clone()));
+            rewrite(body, registry, scope);
+            for (_, value) in bindings {
+                rewrite(value, registry, scope);
+            }
+        }
+        AstNode::Lambda {
+            parameters,
+            rest,
+            body,
+            ..
+        } => {
+            scope.extend(parameters.iter().chain(rest.iter()).cloned());
+            rewrite(body, registry, scope);
+        }
+        AstNode::Call {
+            function,
+            arguments,
+            ..
+        } => {
+            rewrite(function, registry, scope);
+            for arg in arguments {
+                rewrite(arg, registry, scope);
+            }
+        }
+        AstNode::FfiCall { arguments, .. } => {
+            for arg in arguments {
+                rewrite(arg, registry, scope);
+            }
+        }
+        AstNode::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            rewrite(condition, registry, scope);
+            rewrite(then_branch, registry, scope);
+            rewrite(else_branch, registry, scope);
+        }
+        AstNode::TrustTier { expression, .. } => rewrite(expression, registry, scope),
+        AstNode::Define { value, .. } => rewrite(value, registry, scope),
+        AstNode::List { elements, .. } => {
+            for element in elements {
+                rewrite(element, registry, scope);
+            }
+        }
+        AstNode::Cons { car, cdr, .. } => {
+            rewrite(car, registry, scope);
+            rewrite(cdr, registry, scope);
+        }
+        _ => {}
+    }
+    scope.truncate(outer);
+}
+
+/// Every name a `define` anywhere in `node` binds
+fn collect_defines(node: &AstNode, names: &mut Vec<String>) {
+    if let AstNode::Define { name, .. } = node {
+        names.push(name.clone());
+    }
+    for child in crate::analysis::child_nodes(node) {
+        collect_defines(child, names);
+    }
+}
+
+/// Drop the bindings that hold pure FFI calls nothing refers to, given the
+/// names `scope` binds around the binding form
+fn remove_dead_bindings(
+    bindings: &mut Vec<(String, AstNode)>,
+    body: &AstNode,
+    registry: &FfiRegistry,
+    scope: &[String],
+    sequential: bool,
+) {
+    let dead: Vec<bool> = bindings
+        .iter()
+        .enumerate()
+        .map(|(index, (name, value))| {
+            let mut visible = scope.to_vec();
+            if sequential {
+                visible.extend(bindings[..index].iter().map(|(name, _)| name.clone()));
+            }
+            is_pure_ffi_call(value, registry, &visible)
+                && !mentions(body, name)
+                && !bindings
+                    .iter()
+                    .enumerate()
+                    .any(|(other, (_, value))| other != index && mentions(value, name))
+        })
+        .collect();
+    let mut dead = dead.into_iter();
+    bindings.retain(|_| !dead.next().unwrap_or(false));
+}
+
+/// Whether evaluating `node` calls only pure FFI functions on effect-free
+/// arguments, where the names in `scope` are local variables
+fn is_pure_ffi_call(node: &AstNode, registry: &FfiRegistry, scope: &[String]) -> bool {
+    let (name, arguments) = match node {
+        AstNode::FfiCall {
+            function,
+            arguments,
+            ..
+        } => (function, arguments),
+        AstNode::Call {
+            function,
+            arguments,
+            ..
+        } => match function.as_ref() {
+            // A local variable shadows the FFI function of the same name
+            AstNode::Symbol(name) | AstNode::Variable(name) if !scope.contains(name) => {
+                (name, arguments)
+            }
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    registry.is_pure(name)
+        && arguments.iter().all(|arg| {
+            matches!(
+                arg,
+                AstNode::Literal(_) | AstNode::Symbol(_) | AstNode::Variable(_)
+            ) || is_pure_ffi_call(arg, registry, scope)
+        })
+}
+
+/// Whether `name` occurs anywhere in `node`, ignoring shadowing
+fn mentions(node: &AstNode, name: &str) -> bool {
+    match node {
+        AstNode::Symbol(symbol) | AstNode::Variable(symbol) => symbol == name,
+        _ => crate::analysis::child_nodes(node)
+            .into_iter()
+            .any(|child| mentions(child, name)),
+    }
+}
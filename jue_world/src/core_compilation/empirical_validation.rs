@@ -0,0 +1,141 @@
+//! Empirical validation of compiled programs
+//!
+//! Empirical and Experimental code is not proven correct, so the compiler can
+//! instead run it against a [`TestHarness`]: input/expected-output pairs for
+//! a program that reads its inputs through named variables. Each case runs
+//! the generated bytecode on a fresh VM with the inputs preloaded into the
+//! top-level locals, and instruction coverage is collected across all cases.
+
+use crate::core_compiler::{CompilationResult, EmpiricalResult};
+use physics_world::types::Value;
+use physics_world::vm::state::VmError;
+use physics_world::vm::VmState;
+use serde::{Deserialize, Serialize};
+
+/// Recursion depth given to the VMs running test cases
+const MAX_RECURSION_DEPTH: u32 = 100;
+
+/// Input/expected-output cases to run a compiled program against
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TestHarness {
+    /// Variables the program reads its inputs through; the compiler binds
+    /// them, in order, to the first top-level local slots
+    pub inputs: Vec<String>,
+    /// Cases to run, in order
+    pub cases: Vec<TestCase>,
+}
+
+/// One run of a program: the value of each input and the expected result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestCase {
+    /// Values of the harness inputs, in the same order
+    pub inputs: Vec<Value>,
+    /// Value the program must evaluate to
+    pub expected: Value,
+}
+
+impl TestHarness {
+    /// Harness for a program reading the variables `inputs`
+    #[must_use]
+    pub fn new(inputs: &[&str]) -> Self {
+        Self {
+            inputs: inputs.iter().map(ToString::to_string).collect(),
+            cases: Vec::new(),
+        }
+    }
+
+    /// Add a case expecting `expected` from `inputs`
+    #[must_use]
+    pub fn case(mut self, inputs: Vec<Value>, expected: Value) -> Self {
+        self.cases.push(TestCase { inputs, expected });
+        self
+    }
+}
+
+/// Run every case of `harness` against `result`'s bytecode.
+///
+/// Stops at the first case that fails to run or evaluates to something
+/// other than its expected value, and reports it. Coverage is the fraction
+/// of instructions executed by at least one case. A harness without cases
+/// is [`EmpiricalResult::NotApplicable`].
+#[must_use]
+pub fn run_harness(result: &CompilationResult, harness: &TestHarness) -> EmpiricalResult {
+    if harness.cases.is_empty() {
+        return EmpiricalResult::NotApplicable;
+    }
+
+    let mut covered = vec![false; result.bytecode.len()];
+    for (index, case) in harness.cases.iter().enumerate() {
+        let failed = |reason: String| EmpiricalResult::Failed {
+            reason,
+            failing_case: describe_case(index, case),
+        };
+        if case.inputs.len() != harness.inputs.len() {
+            return failed(format!(
+                "case supplies {} inputs, harness declares {}",
+                case.inputs.len(),
+                harness.inputs.len()
+            ));
+        }
+
+        let mut vm = match case_vm(result, case) {
+            Ok(vm) => vm,
+            Err(error) => return failed(format!("constants not loaded: {error:?}")),
+        };
+        let outcome = vm.run();
+        if let Some(collector) = &vm.coverage {
+            for (ip, hit) in covered.iter_mut().enumerate() {
+                *hit |= collector.is_covered(ip);
+            }
+        }
+
+        match outcome {
+            Ok(actual) if actual == case.expected => {}
+            Ok(actual) => return failed(format!("expected {}, got {actual}", case.expected)),
+            Err(error) => return failed(format!("runtime error: {error}")),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    let coverage = if covered.is_empty() {
+        1.0
+    } else {
+        covered.iter().filter(|&&hit| hit).count() as f64 / covered.len() as f64
+    };
+    EmpiricalResult::Passed {
+        tests_run: harness.cases.len(),
+        coverage,
+    }
+}
+
+/// A VM running `result` with `case`'s inputs in the top-level locals
+fn case_vm(result: &CompilationResult, case: &TestCase) -> Result<VmState, VmError> {
+    let mut vm = VmState::new(
+        result.bytecode.clone(),
+        result.constants.clone(),
+        result.step_limit,
+        result.memory_limit,
+        1,
+        MAX_RECURSION_DEPTH,
+    );
+    vm.attach_symbol_table(result.symbol_table.clone());
+    vm.attach_function_table(result.function_table.clone());
+    vm.attach_function_names(result.function_names.clone());
+    for capability in &result.granted_capabilities {
+        vm.grant_capability(capability.clone());
+    }
+    vm.top_level_locals.clone_from(&case.inputs);
+    vm.install_coverage_collector();
+    vm.link_pair_constants(&result.pair_constants)?;
+    Ok(vm)
+}
+
+/// How a failing case is identified in [`EmpiricalResult::Failed`]
+fn describe_case(index: usize, case: &TestCase) -> String {
+    let inputs: Vec<String> = case.inputs.iter().map(ToString::to_string).collect();
+    format!(
+        "case {index}: inputs [{}], expected {}",
+        inputs.join(", "),
+        case.expected
+    )
+}
@@ -1,8 +1,6 @@
 use crate::error::{CompilationError, SourceMap};
 use serde::{Deserialize, Serialize};
-use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
-use std::hash::{Hash, Hasher};
 
 /// Escape analysis information for functions
 ///
@@ -24,15 +22,87 @@ pub struct EscapeAnalysis {
     /// Used to track which function context we're analyzing
     pub current_function: Option<FunctionId>,
 
-    /// Stack of variable environments representing lexical scoping
-    /// Each element represents variables available in a specific scope
-    pub variable_environments: Vec<HashSet<usize>>,
+    /// Variables in scope at the current point of the analysis
+    /// Assigns each binding its own index and resolves names to them
+    pub symbols: ScopedSymbols,
 
     /// Pre-computed escape information for all analyzed functions
     /// Maps function IDs to their detailed escape analysis results
     pub function_info: HashMap<FunctionId, FunctionInfo>,
 }
 
+/// Scoped symbol table for escape analysis
+///
+/// Every binding gets the next dense index, and indices are never reused,
+/// so two bindings of the same name get distinct indices whether they are
+/// nested or siblings. A name resolves to its innermost visible binding.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScopedSymbols {
+    /// Name to index of the bindings in each open scope, innermost last
+    scopes: Vec<HashMap<String, usize>>,
+    /// Name bound at each index
+    names: Vec<String>,
+}
+
+impl ScopedSymbols {
+    /// Creates a symbol table with no scopes and no bindings
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a scope for the bindings that follow
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Closes the innermost scope; its bindings keep their indices
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Binds `name` in the innermost scope, opening one if there is none,
+    /// and returns its new index
+    pub fn bind(&mut self, name: &str) -> usize {
+        let index = self.names.len();
+        self.names.push(name.to_string());
+        if self.scopes.is_empty() {
+            self.push_scope();
+        }
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), index);
+        }
+        index
+    }
+
+    /// Index of the innermost visible binding of `name`
+    #[must_use]
+    pub fn resolve(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    /// Name bound at `index`
+    #[must_use]
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.names.get(index).map(String::as_str)
+    }
+
+    /// Number of indices handed out so far
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether nothing has been bound yet
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
 /// Function identifier for escape analysis
 ///
 /// A simple wrapper around a usize that uniquely identifies functions
@@ -151,19 +221,11 @@ impl EscapeAnalysis {
         Self {
             escaping_vars: HashSet::new(),
             current_function: None,
-            variable_environments: Vec::new(),
+            symbols: ScopedSymbols::new(),
             function_info: HashMap::new(),
         }
     }
 
-    fn get_variable_index(&self, var_name: &str) -> usize {
-        // Simple hash-based indexing for demonstration
-        // In a real implementation, this would use a proper symbol table
-        let mut hasher = DefaultHasher::new();
-        var_name.hash(&mut hasher);
-        hasher.finish() as usize
-    }
-
     /// Analyzes an AST expression for escape analysis
     ///
     /// This method performs escape analysis on the given AST node, tracking
@@ -175,9 +237,9 @@ impl EscapeAnalysis {
     /// * `context`: The analysis context maintaining current scope and state
     ///
     /// # Behavior
-    /// - **Variable**: Converts variable name to index and checks if it escapes
-    /// - **Lambda**: Analyzes function parameters and body, tracking free variables
-    /// - **Let**: Analyzes binding expressions and body with proper scoping
+    /// - **Variable**: Resolves the name to its binding's index and checks if it escapes
+    /// - **Lambda**: Binds the parameters, analyzes the body, and marks free variables escaping
+    /// - **Let**: Analyzes binding expressions, then the body with the bindings in scope
     /// - **Other**: Falls back to default analysis for unhandled expression types
     pub fn analyze_expression(
         &mut self,
@@ -185,28 +247,24 @@ impl EscapeAnalysis {
         context: &mut AnalysisContext,
     ) {
         match expr {
-            crate::ast::AstNode::Variable(var) => {
-                // Convert variable name to index (simplified for now)
-                let var_index = self.get_variable_index(var);
-                self.analyze_variable(var_index, context);
-            }
+            crate::ast::AstNode::Variable(var) => match self.symbols.resolve(var) {
+                Some(var_index) => self.analyze_variable(var_index, context),
+                None => context.report_error(CompilationError::ParseError {
+                    message: format!("Variable not found: {}", var),
+                    location: Default::default(),
+                }),
+            },
             crate::ast::AstNode::Lambda {
-                parameters, body, ..
+                parameters,
+                rest,
+                body,
+                ..
             } => {
-                // Convert parameter names to indices
-                let param_indices: Vec<usize> = parameters
-                    .iter()
-                    .map(|param| self.get_variable_index(param))
-                    .collect();
-                self.analyze_lambda(&param_indices, body, context);
+                let params: Vec<String> = parameters.iter().chain(rest).cloned().collect();
+                self.analyze_lambda(&params, body, context);
             }
             crate::ast::AstNode::Let { bindings, body, .. } => {
-                // Convert binding names to indices
-                let binding_indices: Vec<(usize, crate::ast::AstNode)> = bindings
-                    .iter()
-                    .map(|(name, expr)| (self.get_variable_index(name), expr.clone()))
-                    .collect();
-                self.analyze_let(&binding_indices, body, context);
+                self.analyze_let(bindings, body, context);
             }
             crate::ast::AstNode::Call {
                 function,
@@ -239,42 +297,39 @@ impl EscapeAnalysis {
     }
 
     fn analyze_variable(&mut self, var: usize, context: &mut AnalysisContext) {
-        // Check if this variable is defined in current or outer scopes
-        for vars in self.variable_environments.iter().rev() {
-            if vars.contains(&var) {
-                // Variable is in scope - check if it's used in a way that requires escaping
-                if context.is_captured() {
-                    self.escaping_vars.insert(var);
-                }
-                return;
-            }
+        // Variable is in scope - check if it's used in a way that requires escaping
+        if context.is_captured() {
+            self.escaping_vars.insert(var);
         }
+    }
 
-        // Variable not found - error condition
-        context.report_error(CompilationError::ParseError {
-            message: format!("Variable not found: {}", var),
-            location: Default::default(),
-        });
+    /// Bind `name` in the innermost scope, keeping `max_variable` up to date
+    fn bind_variable(&mut self, name: &str, context: &mut AnalysisContext) -> usize {
+        let index = self.symbols.bind(name);
+        context.max_variable = context.max_variable.max(index + 1);
+        index
     }
 
     fn analyze_lambda(
         &mut self,
-        params: &[usize],
+        params: &[String],
         body: &crate::ast::AstNode,
         context: &mut AnalysisContext,
     ) {
         // Push new scope for lambda parameters
-        let param_vars: HashSet<usize> = params.iter().cloned().collect();
-        self.variable_environments.push(param_vars);
+        self.symbols.push_scope();
+        for param in params {
+            self.bind_variable(param, context);
+        }
 
         // Analyze lambda body
         self.analyze_expression(body, context);
 
         // Pop parameter scope
-        self.variable_environments.pop();
+        self.symbols.pop_scope();
 
         // All free variables in this lambda escape
-        let free_vars = self.find_free_variables(body);
+        let free_vars = self.find_free_variables(params, body);
         for var in &free_vars {
             self.escaping_vars.insert(*var);
         }
@@ -303,22 +358,24 @@ impl EscapeAnalysis {
 
     fn analyze_let(
         &mut self,
-        bindings: &[(usize, crate::ast::AstNode)],
+        bindings: &[(String, crate::ast::AstNode)],
         body: &crate::ast::AstNode,
         context: &mut AnalysisContext,
     ) {
-        // Push new scope for let bindings
-        let binding_vars: HashSet<usize> = bindings.iter().map(|(var, _)| *var).collect();
-        self.variable_environments.push(binding_vars);
-
-        // Analyze bindings and body
+        // Binding values are evaluated before any of the names are visible
         for (_, expr) in bindings {
             self.analyze_expression(expr, context);
         }
+
+        // Push new scope for let bindings and analyze the body
+        self.symbols.push_scope();
+        for (name, _) in bindings {
+            self.bind_variable(name, context);
+        }
         self.analyze_expression(body, context);
 
         // Pop binding scope
-        self.variable_environments.pop();
+        self.symbols.pop_scope();
     }
 
     fn analyze_default(&mut self, expr: &crate::ast::AstNode, context: &mut AnalysisContext) {
@@ -383,88 +440,77 @@ impl EscapeAnalysis {
         }
     }
 
-    fn find_free_variables(&self, expr: &crate::ast::AstNode) -> Vec<usize> {
+    /// Indices of the variables a lambda with `params` and `body` refers to
+    /// from enclosing scopes, in order of first reference.
+    ///
+    /// Called with the lambda's own scope closed, so names are resolved to
+    /// their bindings outside it; names bound within the lambda, including
+    /// ones shadowing an outer binding, are not free. Unresolved names such
+    /// as globals have no index and are skipped.
+    fn find_free_variables(&self, params: &[String], body: &crate::ast::AstNode) -> Vec<usize> {
+        let mut bound = vec![params.iter().map(String::as_str).collect()];
         let mut free_vars = Vec::new();
-        self.find_free_variables_recursive(expr, &mut free_vars);
+        self.find_free_variables_recursive(body, &mut bound, &mut free_vars);
         free_vars
     }
 
-    fn find_free_variables_recursive(
+    fn find_free_variables_recursive<'a>(
         &self,
-        expr: &crate::ast::AstNode,
+        expr: &'a crate::ast::AstNode,
+        bound: &mut Vec<HashSet<&'a str>>,
         free_vars: &mut Vec<usize>,
     ) {
         match expr {
             crate::ast::AstNode::Variable(var) => {
-                // Convert variable name to index
-                let var_index = self.get_variable_index(var);
-                // Check if variable is bound in current scope
-                let mut is_bound = false;
-                for scope in &self.variable_environments {
-                    if scope.contains(&var_index) {
-                        is_bound = true;
-                        break;
-                    }
+                if bound.iter().any(|scope| scope.contains(var.as_str())) {
+                    return;
                 }
-                if !is_bound {
-                    free_vars.push(var_index);
+                if let Some(var_index) = self.symbols.resolve(var) {
+                    if !free_vars.contains(&var_index) {
+                        free_vars.push(var_index);
+                    }
                 }
             }
             crate::ast::AstNode::Lambda {
-                parameters, body, ..
+                parameters,
+                rest,
+                body,
+                ..
             } => {
-                // Add parameters to scope
-                let param_vars: HashSet<usize> = parameters
-                    .iter()
-                    .map(|param| self.get_variable_index(param))
-                    .collect();
-
-                // Create new environment with parameters added
-                let mut new_environments = self.variable_environments.clone();
-                new_environments.push(param_vars);
-
-                // Create a temporary analysis with the new environment
-                let temp_analysis = EscapeAnalysis {
-                    variable_environments: new_environments,
-                    ..Default::default()
-                };
-
                 // Analyze body with parameters in scope
-                temp_analysis.find_free_variables_recursive(body, free_vars);
+                bound.push(parameters.iter().chain(rest).map(String::as_str).collect());
+                self.find_free_variables_recursive(body, bound, free_vars);
+                bound.pop();
             }
             crate::ast::AstNode::Let { bindings, body, .. } => {
-                // Add let bindings to scope
-                let binding_vars: HashSet<usize> = bindings
-                    .iter()
-                    .map(|(name, _)| self.get_variable_index(name))
-                    .collect();
-
-                // Create new environment with bindings added
-                let mut new_environments = self.variable_environments.clone();
-                new_environments.push(binding_vars);
-
-                // Create a temporary analysis with the new environment
-                let temp_analysis = EscapeAnalysis {
-                    variable_environments: new_environments,
-                    ..Default::default()
-                };
-
-                // Analyze bindings and body with bindings in scope
+                // Binding values see the enclosing scope, the body the bindings
                 for (_, expr) in bindings {
-                    temp_analysis.find_free_variables_recursive(expr, free_vars);
+                    self.find_free_variables_recursive(expr, bound, free_vars);
                 }
-                temp_analysis.find_free_variables_recursive(body, free_vars);
+                bound.push(bindings.iter().map(|(name, _)| name.as_str()).collect());
+                self.find_free_variables_recursive(body, bound, free_vars);
+                bound.pop();
             }
             crate::ast::AstNode::Call {
                 function,
                 arguments,
                 ..
             } => {
-                self.find_free_variables_recursive(function, free_vars);
+                self.find_free_variables_recursive(function, bound, free_vars);
                 for arg in arguments {
-                    self.find_free_variables_recursive(arg, free_vars);
+                    self.find_free_variables_recursive(arg, bound, free_vars);
                 }
             }
+            crate::ast::AstNode::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.find_free_variables_recursive(condition, bound, free_vars);
+                self.find_free_variables_recursive(then_branch, bound, free_vars);
+                self.find_free_variables_recursive(else_branch, bound, free_vars);
+            }
             // Other expression types would be handled here
             _ => {}
         }
@@ -476,7 +522,7 @@ impl Default for EscapeAnalysis {
         Self {
             escaping_vars: HashSet::new(),
             current_function: None,
-            variable_environments: Vec::new(),
+            symbols: ScopedSymbols::new(),
             function_info: HashMap::new(),
         }
     }
@@ -1,9 +1,17 @@
+pub mod analysis;
 pub mod capability_analysis;
 pub mod capability_analyzer;
+/// Proofs of the pure code around capability operations
+pub mod capability_boundary;
 pub mod core_compiler;
+pub mod dead_ffi_elimination;
+pub mod empirical_validation;
 pub mod escape_analysis;
+pub mod optimize;
 pub mod proof_generator;
 
+pub use capability_boundary::{prove_capability_boundary, CapabilityPoint};
+
 // Note: proof_verifier and trust_tier_handler modules don't exist yet
 // pub mod proof_verifier;
 // pub mod trust_tier_handler;
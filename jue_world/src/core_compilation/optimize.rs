@@ -0,0 +1,454 @@
+//! AST-level optimization passes.
+//!
+//! Passes here rewrite the tree before code generation and leave its
+//! meaning unchanged; each is opt-in and documents when it applies.
+
+use crate::ast::{AstNode, Literal};
+use std::collections::{HashMap, HashSet};
+
+/// Inline small closures at their call sites.
+///
+/// A lambda bound by `let` or `let*` is inlined when its body has at most
+/// `max_size` AST nodes, its body does not refer to its own name, and the
+/// name is only ever called directly with the lambda's arity, so the
+/// closure never escapes. Each call `(f a b)` becomes
+/// `(let ((x' a) (y' b)) body')`, where the parameters are renamed to fresh
+/// names that cannot collide with the call site's variables. A call in tail
+/// position therefore leaves the inlined body in tail position. The binding
+/// is dropped once every call has been inlined.
+///
+/// Recursive closures, `letrec` bindings and closures whose free variables
+/// are rebound between definition and call are left alone.
+#[must_use]
+pub fn inline_small_closures(ast: &AstNode, max_size: usize) -> AstNode {
+    let mut ast = ast.clone();
+    let mut fresh = 0;
+    inline_in(&mut ast, max_size, &mut fresh);
+    ast
+}
+
+fn inline_in(node: &mut AstNode, max_size: usize, fresh: &mut usize) {
+    for child in child_nodes_mut(node) {
+        inline_in(child, max_size, fresh);
+    }
+
+    let (AstNode::Let { bindings, body, .. } | AstNode::LetStar { bindings, body, .. }) = node
+    else {
+        return;
+    };
+
+    let mut index = 0;
+    while index < bindings.len() {
+        let inlinable = {
+            let (name, value) = &bindings[index];
+            let siblings: Vec<&AstNode> = bindings
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != index)
+                .map(|(_, (_, value))| value)
+                .collect();
+            match value {
+                // A rest parameter has no single argument to substitute
+                AstNode::Lambda {
+                    parameters,
+                    rest: None,
+                    body: lambda_body,
+                    ..
+                } => {
+                    node_count(lambda_body) <= max_size
+                        && !mentions(lambda_body, name)
+                        && !siblings.iter().any(|value| mentions(value, name))
+                        && !binds(body, name)
+                        && only_called(body, name, parameters.len())
+                        && free_names(lambda_body, parameters).iter().all(|free| {
+                            !binds(body, free) && bindings.iter().all(|(other, _)| other != free)
+                        })
+                }
+                _ => false,
+            }
+        };
+        if !inlinable {
+            index += 1;
+            continue;
+        }
+
+        let (name, lambda) = bindings.remove(index);
+        if let AstNode::Lambda {
+            parameters,
+            body: lambda_body,
+            ..
+        } = lambda
+        {
+            substitute_calls(body, &name, &parameters, &lambda_body, fresh);
+        }
+    }
+
+    if bindings.is_empty() {
+        let body = std::mem::replace(body.as_mut(), AstNode::Literal(Literal::Nil));
+        *node = body;
+    }
+}
+
+/// Substitute literal-bound variables into their uses.
+///
+/// A `let` or `let*` binding whose value is a literal is dropped and each
+/// reference to its name in scope is replaced by the literal, so that later
+/// passes see `(+ 5 5)` where the source had `(let ((x 5)) (+ x x))`. Inner
+/// bindings of the same name shadow the literal as usual. A binding that is
+/// the target of a `set!`, or that a `define` in its scope rebinds, is left
+/// alone. A binding form left with no bindings is replaced by its body.
+/// `letrec` bindings are not propagated.
+#[must_use]
+pub fn propagate_constants(ast: &AstNode) -> AstNode {
+    let mut ast = ast.clone();
+    propagate_in(&mut ast);
+    ast
+}
+
+fn propagate_in(node: &mut AstNode) {
+    if let AstNode::Let { bindings, body, .. } = node {
+        let mut constants = HashMap::new();
+        bindings.retain(|(name, value)| {
+            let constant = matches!(value, AstNode::Literal(_)) && !reassigned(body, name);
+            if constant {
+                constants.insert(name.clone(), value.clone());
+            }
+            !constant
+        });
+        substitute(body, &constants);
+    } else if let AstNode::LetStar { bindings, body, .. } = node {
+        // Each binding is in scope for the bindings after it
+        let mut index = 0;
+        while index < bindings.len() {
+            let (name, value) = &bindings[index];
+            let constant = matches!(value, AstNode::Literal(_))
+                && !reassigned(body, name)
+                && !bindings[index + 1..]
+                    .iter()
+                    .any(|(_, later)| reassigned(later, name));
+            if !constant {
+                index += 1;
+                continue;
+            }
+            let (name, value) = bindings.remove(index);
+            let constants = HashMap::from([(name.clone(), value)]);
+            for (later, later_value) in &mut bindings[index..] {
+                substitute(later_value, &constants);
+                if *later == name {
+                    // Shadowed from here on
+                    break;
+                }
+            }
+            if !bindings[index..].iter().any(|(later, _)| *later == name) {
+                substitute(body, &constants);
+            }
+        }
+    }
+
+    if let AstNode::Let { bindings, body, .. } | AstNode::LetStar { bindings, body, .. } = node {
+        if bindings.is_empty() {
+            let body = std::mem::replace(body.as_mut(), AstNode::Literal(Literal::Nil));
+            *node = body;
+            propagate_in(node);
+            return;
+        }
+    }
+
+    for child in child_nodes_mut(node) {
+        propagate_in(child);
+    }
+}
+
+/// Replace free occurrences of the keys of `constants`, respecting shadowing
+fn substitute(node: &mut AstNode, constants: &HashMap<String, AstNode>) {
+    if constants.is_empty() {
+        return;
+    }
+    let without = |bound: &mut dyn Iterator<Item = &String>| {
+        let bound: HashSet<&String> = bound.collect();
+        constants
+            .iter()
+            .filter(|(name, _)| !bound.contains(name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<HashMap<_, _>>()
+    };
+
+    match node {
+        AstNode::Symbol(name) | AstNode::Variable(name) => {
+            if let Some(value) = constants.get(name) {
+                *node = value.clone();
+            }
+        }
+        AstNode::Lambda {
+            parameters,
+            rest,
+            body,
+            ..
+        } => substitute(body, &without(&mut parameters.iter().chain(rest.iter()))),
+        AstNode::Let { bindings, body, .. } => {
+            for (_, value) in bindings.iter_mut() {
+                substitute(value, constants);
+            }
+            substitute(body, &without(&mut bindings.iter().map(|(name, _)| name)));
+        }
+        AstNode::LetStar { bindings, body, .. } => {
+            let mut visible = constants.clone();
+            for (name, value) in bindings.iter_mut() {
+                substitute(value, &visible);
+                visible.remove(name.as_str());
+            }
+            substitute(body, &visible);
+        }
+        AstNode::Letrec { bindings, body, .. } => {
+            let inner = without(&mut bindings.iter().map(|(name, _)| name));
+            for (_, value) in bindings.iter_mut() {
+                substitute(value, &inner);
+            }
+            substitute(body, &inner);
+        }
+        _ => {
+            for child in child_nodes_mut(node) {
+                substitute(child, constants);
+            }
+        }
+    }
+}
+
+/// Whether `node` may change what `name` refers to, through `set!` or `define`
+fn reassigned(node: &AstNode, name: &str) -> bool {
+    match node {
+        AstNode::Call {
+            function,
+            arguments,
+            ..
+        } if matches!(function.as_ref(), AstNode::Symbol(callee) | AstNode::Variable(callee) if callee == "set!")
+            && matches!(arguments.first(), Some(AstNode::Symbol(target) | AstNode::Variable(target)) if target == name) =>
+        {
+            true
+        }
+        AstNode::Define { name: bound, .. } if bound == name => true,
+        _ => crate::analysis::child_nodes(node)
+            .into_iter()
+            .any(|child| reassigned(child, name)),
+    }
+}
+
+/// Replace every call of `name` in `node` with the renamed lambda body
+fn substitute_calls(
+    node: &mut AstNode,
+    name: &str,
+    parameters: &[String],
+    lambda_body: &AstNode,
+    fresh: &mut usize,
+) {
+    for child in child_nodes_mut(node) {
+        substitute_calls(child, name, parameters, lambda_body, fresh);
+    }
+
+    let AstNode::Call {
+        function,
+        arguments,
+        location,
+    } = node
+    else {
+        return;
+    };
+    if !matches!(function.as_ref(), AstNode::Symbol(callee) | AstNode::Variable(callee) if callee == name)
+    {
+        return;
+    }
+
+    *fresh += 1;
+    let renamed: HashMap<String, String> = parameters
+        .iter()
+        .map(|param| (param.clone(), format!("{param}%inline{fresh}")))
+        .collect();
+    let mut body = lambda_body.clone();
+    rename(&mut body, &renamed);
+
+    *node = if parameters.is_empty() {
+        body
+    } else {
+        AstNode::Let {
+            bindings: parameters
+                .iter()
+                .map(|param| renamed[param].clone())
+                .zip(std::mem::take(arguments))
+                .collect(),
+            body: Box::new(body),
+            location: location.clone(),
+        }
+    };
+}
+
+/// Rename free occurrences of the keys of `names`, respecting shadowing
+fn rename(node: &mut AstNode, names: &HashMap<String, String>) {
+    if names.is_empty() {
+        return;
+    }
+    let without = |bound: &mut dyn Iterator<Item = &String>| {
+        let bound: HashSet<&String> = bound.collect();
+        names
+            .iter()
+            .filter(|(name, _)| !bound.contains(name))
+            .map(|(name, new)| (name.clone(), new.clone()))
+            .collect::<HashMap<_, _>>()
+    };
+
+    match node {
+        AstNode::Symbol(name) | AstNode::Variable(name) => {
+            if let Some(new) = names.get(name) {
+                name.clone_from(new);
+            }
+        }
+        AstNode::Lambda {
+            parameters,
+            rest,
+            body,
+            ..
+        } => rename(body, &without(&mut parameters.iter().chain(rest.iter()))),
+        AstNode::Let { bindings, body, .. } => {
+            for (_, value) in bindings.iter_mut() {
+                rename(value, names);
+            }
+            rename(body, &without(&mut bindings.iter().map(|(name, _)| name)));
+        }
+        AstNode::LetStar { bindings, body, .. } => {
+            let mut visible = names.clone();
+            for (name, value) in bindings.iter_mut() {
+                rename(value, &visible);
+                visible.remove(name.as_str());
+            }
+            rename(body, &visible);
+        }
+        AstNode::Letrec { bindings, body, .. } => {
+            let inner = without(&mut bindings.iter().map(|(name, _)| name));
+            for (_, value) in bindings.iter_mut() {
+                rename(value, &inner);
+            }
+            rename(body, &inner);
+        }
+        _ => {
+            for child in child_nodes_mut(node) {
+                rename(child, names);
+            }
+        }
+    }
+}
+
+/// Whether every occurrence of `name` in `node` is the callee of a call with `arity` arguments
+fn only_called(node: &AstNode, name: &str, arity: usize) -> bool {
+    match node {
+        AstNode::Symbol(other) | AstNode::Variable(other) => other != name,
+        AstNode::Call {
+            function,
+            arguments,
+            ..
+        } if matches!(function.as_ref(), AstNode::Symbol(callee) | AstNode::Variable(callee) if callee == name) => {
+            arguments.len() == arity && arguments.iter().all(|arg| only_called(arg, name, arity))
+        }
+        _ => crate::analysis::child_nodes(node)
+            .into_iter()
+            .all(|child| only_called(child, name, arity)),
+    }
+}
+
+/// Names `body` refers to other than `parameters`, ignoring shadowing
+fn free_names(body: &AstNode, parameters: &[String]) -> HashSet<String> {
+    fn collect(node: &AstNode, names: &mut HashSet<String>) {
+        if let AstNode::Symbol(name) | AstNode::Variable(name) = node {
+            names.insert(name.clone());
+        }
+        for child in crate::analysis::child_nodes(node) {
+            collect(child, names);
+        }
+    }
+    let mut names = HashSet::new();
+    collect(body, &mut names);
+    for param in parameters {
+        names.remove(param);
+    }
+    names
+}
+
+/// Whether `node` introduces a binding named `name` anywhere
+fn binds(node: &AstNode, name: &str) -> bool {
+    let here = match node {
+        AstNode::Lambda { parameters, .. } => parameters.iter().any(|param| param == name),
+        AstNode::Let { bindings, .. }
+        | AstNode::LetStar { bindings, .. }
+        | AstNode::Letrec { bindings, .. } => bindings.iter().any(|(bound, _)| bound == name),
+        AstNode::Define { name: bound, .. } => bound == name,
+        _ => false,
+    };
+    here || crate::analysis::child_nodes(node)
+        .into_iter()
+        .any(|child| binds(child, name))
+}
+
+/// Whether `name` occurs anywhere in `node`
+fn mentions(node: &AstNode, name: &str) -> bool {
+    match node {
+        AstNode::Symbol(symbol) | AstNode::Variable(symbol) => symbol == name,
+        _ => crate::analysis::child_nodes(node)
+            .into_iter()
+            .any(|child| mentions(child, name)),
+    }
+}
+
+/// Number of nodes in the tree rooted at `node`
+fn node_count(node: &AstNode) -> usize {
+    1 + crate::analysis::child_nodes(node)
+        .into_iter()
+        .map(node_count)
+        .sum::<usize>()
+}
+
+/// Mutable direct children of `node`, in the same order as `child_nodes`
+pub(crate) fn child_nodes_mut(node: &mut AstNode) -> Vec<&mut AstNode> {
+    match node {
+        AstNode::Call {
+            function,
+            arguments,
+            ..
+        } => std::iter::once(function.as_mut())
+            .chain(arguments.iter_mut())
+            .collect(),
+        AstNode::Lambda { body, .. }
+        | AstNode::MacroDefinition { body, .. }
+        | AstNode::TrustTier {
+            expression: body, ..
+        }
+        | AstNode::Define { value: body, .. } => vec![body.as_mut()],
+        AstNode::Let { bindings, body, .. }
+        | AstNode::LetStar { bindings, body, .. }
+        | AstNode::Letrec { bindings, body, .. } => bindings
+            .iter_mut()
+            .map(|(_, value)| value)
+            .chain(std::iter::once(body.as_mut()))
+            .collect(),
+        AstNode::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => vec![
+            condition.as_mut(),
+            then_branch.as_mut(),
+            else_branch.as_mut(),
+        ],
+        AstNode::MacroExpansion { arguments, .. }
+        | AstNode::FfiCall { arguments, .. }
+        | AstNode::List {
+            elements: arguments,
+            ..
+        } => arguments.iter_mut().collect(),
+        AstNode::Cons { car, cdr, .. } => vec![car.as_mut(), cdr.as_mut()],
+        AstNode::Literal(_)
+        | AstNode::Symbol(_)
+        | AstNode::Variable(_)
+        | AstNode::RequireCapability { .. }
+        | AstNode::HasCapability { .. }
+        | AstNode::TypeSignature { .. } => Vec::new(),
+    }
+}
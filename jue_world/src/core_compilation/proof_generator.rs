@@ -1,6 +1,14 @@
 use crate::error::CompilationError;
 use core_world::core_expr::CoreExpr;
-use core_world::proof_checker::Proof;
+use core_world::core_kernel::{alpha_equiv, is_normal_form, normalize};
+use core_world::proof_checker::{prove_normalization, Proof};
+use std::cell::{OnceCell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// Reduction steps allowed when proving that a term normalizes
+const NORMALIZATION_STEP_LIMIT: usize = 100;
 
 /// Proof generator for Core-World compilation
 pub struct ProofGenerator;
@@ -12,3 +20,159 @@ impl ProofGenerator {
         None
     }
 }
+
+/// Normal form of a term and the proof that it reduces to it, each computed
+/// the first time it is asked for
+#[derive(Debug, Clone)]
+struct CachedNormalization {
+    /// Representative of the α-equivalence class
+    expr: CoreExpr,
+    normal_form: OnceCell<CoreExpr>,
+    /// `None` when the term did not normalize within the step limit
+    proof: OnceCell<Option<Proof>>,
+}
+
+/// Memoized normal forms, keyed by α-equivalence class.
+///
+/// Terms use de Bruijn indices, so α-equivalent terms share their structure
+/// and hence their structural hash. Entries within a hash bucket are still
+/// compared with `alpha_equiv`, so a hash collision can never hand back the
+/// normal form of a different term.
+///
+/// Normal forms and proofs are computed separately, so asking for one does
+/// not pay for the other. [`NormalizationCache::with_shared`] gives access to
+/// a cache that lives for the whole thread, so obligations repeated across
+/// compilations are only proven once.
+#[derive(Debug, Default)]
+pub struct NormalizationCache {
+    entries: HashMap<u64, Vec<CachedNormalization>>,
+    normalizations: usize,
+    hits: usize,
+}
+
+thread_local! {
+    static SHARED_CACHE: RefCell<NormalizationCache> = RefCell::new(NormalizationCache::new());
+}
+
+impl NormalizationCache {
+    /// Create an empty cache
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `f` with this thread's cache, which is kept between calls
+    pub fn with_shared<R>(f: impl FnOnce(&mut NormalizationCache) -> R) -> R {
+        SHARED_CACHE.with(|cache| f(&mut cache.borrow_mut()))
+    }
+
+    /// Normal form of `expr`, normalizing it only if no α-equivalent term
+    /// has been normalized before
+    pub fn normal_form(&mut self, expr: &CoreExpr) -> CoreExpr {
+        let (entry, normalizations, hits) = self.entry(expr);
+        if let Some(normal_form) = entry.normal_form.get() {
+            *hits += 1;
+            return normal_form.clone();
+        }
+        *normalizations += 1;
+        entry
+            .normal_form
+            .get_or_init(|| normalize(expr.clone()))
+            .clone()
+    }
+
+    /// Proof that `expr` reduces to its normal form, if one was found within
+    /// the step limit
+    pub fn normalization_proof(&mut self, expr: &CoreExpr) -> Option<Proof> {
+        let (entry, normalizations, hits) = self.entry(expr);
+        if let Some(proof) = entry.proof.get() {
+            *hits += 1;
+            return proof.clone();
+        }
+        *normalizations += 1;
+        entry.proof.get_or_init(|| prove(expr)).clone()
+    }
+
+    /// Number of normal forms and proofs actually computed, i.e. cache misses
+    #[must_use]
+    pub fn normalizations(&self) -> usize {
+        self.normalizations
+    }
+
+    /// Number of lookups answered from the cache
+    #[must_use]
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of α-equivalence classes stored
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    /// Whether nothing has been normalized yet
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop every cached normalization and reset the counters
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Entry of the α-equivalence class of `expr`, added empty if new, with
+    /// the miss and hit counters
+    fn entry(&mut self, expr: &CoreExpr) -> (&mut CachedNormalization, &mut usize, &mut usize) {
+        let bucket = self.entries.entry(structural_hash(expr)).or_default();
+        let found = bucket
+            .iter()
+            .position(|cached| alpha_equiv(cached.expr.clone(), expr.clone()));
+        let index = found.unwrap_or_else(|| {
+            bucket.push(CachedNormalization {
+                expr: expr.clone(),
+                normal_form: OnceCell::new(),
+                proof: OnceCell::new(),
+            });
+            bucket.len() - 1
+        });
+        (&mut bucket[index], &mut self.normalizations, &mut self.hits)
+    }
+}
+
+/// Proof that `expr` normalizes, or reflexivity if it is already normal
+fn prove(expr: &CoreExpr) -> Option<Proof> {
+    match prove_normalization(expr.clone(), NORMALIZATION_STEP_LIMIT) {
+        Ok(proof) => Some(proof),
+        Err(_) if is_normal_form(expr) => Some(Proof::Refl(expr.clone())),
+        Err(_) => None,
+    }
+}
+
+/// Hash of the shape of `expr`, identical for α-equivalent terms
+fn structural_hash(expr: &CoreExpr) -> u64 {
+    fn feed(expr: &CoreExpr, hasher: &mut DefaultHasher) {
+        match expr {
+            CoreExpr::Var(index) => (0u8, index).hash(hasher),
+            CoreExpr::Lam(body) => {
+                1u8.hash(hasher);
+                feed(body, hasher);
+            }
+            CoreExpr::App(func, arg) => {
+                2u8.hash(hasher);
+                feed(func, hasher);
+                feed(arg, hasher);
+            }
+            CoreExpr::Nat(n) => (3u8, n).hash(hasher),
+            CoreExpr::Pair(first, second) => {
+                4u8.hash(hasher);
+                feed(first, hasher);
+                feed(second, hasher);
+            }
+        }
+    }
+    let mut hasher = DefaultHasher::new();
+    feed(expr, &mut hasher);
+    hasher.finish()
+}
@@ -129,6 +129,10 @@ impl FfiCallGenerator {
                 let ptr_value = ptr.get() as u32;
                 bytecode.push(OpCode::Int(ptr_value as i64));
             }
+            Value::Vector(ptr) | Value::BigInt(ptr) => {
+                // Convert heap pointer to bytecode representation
+                bytecode.push(OpCode::Int(i64::from(ptr.get())));
+            }
             Value::ActorId(id) => {
                 // Convert actor ID to bytecode representation
                 bytecode.push(OpCode::Int(id as i64));
@@ -147,6 +151,18 @@ impl FfiCallGenerator {
                 // Error values - push nil as placeholder
                 bytecode.push(OpCode::Nil);
             }
+            Value::Bytes(_bytes) => {
+                // Byte strings - push nil as placeholder, like strings
+                bytecode.push(OpCode::Nil);
+            }
+            Value::ErrorRecord { .. } => {
+                // Error records - push nil as placeholder, like plain errors
+                bytecode.push(OpCode::Nil);
+            }
+            Value::Thunk(_ptr) => {
+                // Thunks are VM-local heap objects - push nil as placeholder
+                bytecode.push(OpCode::Nil);
+            }
         }
 
         Ok(())
@@ -1,5 +1,6 @@
 use crate::error::{CompilationError, SourceLocation};
 use physics_world::types::{Capability, HostFunction, OpCode, Value};
+use physics_world::vm::opcodes::capability::get_required_capability_for_host_function;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -26,6 +27,10 @@ pub struct FfiFunction {
     /// Required capability for this function (None means no capability required)
     pub required_capability: Option<Capability>,
 
+    /// Whether the function has no side effects, so an unused call may be removed
+    #[serde(default)]
+    pub pure: bool,
+
     /// Parameter types
     pub parameter_types: Vec<String>,
 
@@ -68,11 +73,54 @@ impl FfiRegistry {
         }
     }
 
+    /// Expose the host function `func_id` to programs as `name`.
+    ///
+    /// The compiler resolves calls to `name` through this registry, rejects
+    /// calls with other than `arity` arguments and guards each one with a
+    /// check for `required_cap`. The function is assumed to have side
+    /// effects, so unused calls are never removed.
+    ///
+    /// # Errors
+    /// Returns `CompilationError::FfiError` if the VM only runs `func_id`
+    /// under a capability other than `required_cap`.
+    pub fn register(
+        &mut self,
+        name: &str,
+        func_id: HostFunction,
+        required_cap: Capability,
+        arity: usize,
+    ) -> Result<(), CompilationError> {
+        if let Some(vm_cap) = get_required_capability_for_host_function(func_id as u16) {
+            if vm_cap != required_cap {
+                return Err(CompilationError::FfiError(format!(
+                    "{name}: host function {func_id:?} runs under {vm_cap:?}, not {required_cap:?}"
+                )));
+            }
+        }
+        self.register_function(FfiFunction {
+            name: name.to_string(),
+            host_function: func_id,
+            required_capability: Some(required_cap),
+            pure: false,
+            parameter_types: vec!["Any".to_string(); arity],
+            return_type: "Any".to_string(),
+            documentation: format!("Host function {func_id:?} registered by the embedder"),
+            location: SourceLocation::default(),
+        });
+        Ok(())
+    }
+
     /// Find FFI function by name
     pub fn find_function(&self, name: &str) -> Option<&FfiFunction> {
         self.functions.get(name)
     }
 
+    /// Whether `name` is a registered function marked as pure
+    #[must_use]
+    pub fn is_pure(&self, name: &str) -> bool {
+        self.functions.get(name).is_some_and(|func| func.pure)
+    }
+
     /// Get capability index for a given capability
     pub fn get_capability_index(&self, capability: &Capability) -> Option<usize> {
         self.capability_indices.get(capability).copied()
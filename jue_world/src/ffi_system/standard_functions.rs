@@ -10,6 +10,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "read-sensor".to_string(),
         host_function: HostFunction::ReadSensor,
         required_capability: Some(Capability::IoReadSensor),
+        pure: true,
         parameter_types: vec![],
         return_type: "Float".to_string(),
         documentation: "Read from virtual sensor".to_string(),
@@ -20,6 +21,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "write-actuator".to_string(),
         host_function: HostFunction::WriteActuator,
         required_capability: Some(Capability::IoWriteActuator),
+        pure: false,
         parameter_types: vec!["Float".to_string()],
         return_type: "Bool".to_string(),
         documentation: "Write to virtual actuator".to_string(),
@@ -30,18 +32,86 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "get-wall-clock".to_string(),
         host_function: HostFunction::GetWallClockNs,
         required_capability: Some(Capability::SysClock),
+        pure: true,
         parameter_types: vec![],
         return_type: "Int".to_string(),
         documentation: "Get current wall clock time in nanoseconds".to_string(),
         location: SourceLocation::default(),
     });
 
+    registry.register_function(super::global_ffi_registry::FfiFunction {
+        name: "network-send".to_string(),
+        host_function: HostFunction::NetworkSend,
+        required_capability: Some(Capability::IoNetwork),
+        pure: false,
+        parameter_types: vec!["String".to_string()],
+        return_type: "Nil".to_string(),
+        documentation: "Send a message over the virtual network".to_string(),
+        location: SourceLocation::default(),
+    });
+
+    registry.register_function(super::global_ffi_registry::FfiFunction {
+        name: "network-receive".to_string(),
+        host_function: HostFunction::NetworkReceive,
+        required_capability: Some(Capability::IoNetwork),
+        pure: false,
+        parameter_types: vec![],
+        return_type: "String".to_string(),
+        documentation: "Receive a message from the virtual network".to_string(),
+        location: SourceLocation::default(),
+    });
+
+    registry.register_function(super::global_ffi_registry::FfiFunction {
+        name: "persist-write".to_string(),
+        host_function: HostFunction::PersistWrite,
+        required_capability: Some(Capability::IoPersist),
+        pure: false,
+        parameter_types: vec!["String".to_string(), "String".to_string()],
+        return_type: "Nil".to_string(),
+        documentation: "Write a value to persistent storage".to_string(),
+        location: SourceLocation::default(),
+    });
+
+    registry.register_function(super::global_ffi_registry::FfiFunction {
+        name: "persist-read".to_string(),
+        host_function: HostFunction::PersistRead,
+        required_capability: Some(Capability::IoPersist),
+        pure: true,
+        parameter_types: vec!["String".to_string()],
+        return_type: "String".to_string(),
+        documentation: "Read a value from persistent storage".to_string(),
+        location: SourceLocation::default(),
+    });
+
+    registry.register_function(super::global_ffi_registry::FfiFunction {
+        name: "spawn-actor".to_string(),
+        host_function: HostFunction::SpawnActor,
+        required_capability: Some(Capability::SysCreateActor),
+        pure: false,
+        parameter_types: vec![],
+        return_type: "ActorId".to_string(),
+        documentation: "Spawn a new actor".to_string(),
+        location: SourceLocation::default(),
+    });
+
+    registry.register_function(super::global_ffi_registry::FfiFunction {
+        name: "terminate-actor".to_string(),
+        host_function: HostFunction::TerminateActor,
+        required_capability: Some(Capability::SysTerminateActor),
+        pure: false,
+        parameter_types: vec!["ActorId".to_string()],
+        return_type: "Nil".to_string(),
+        documentation: "Terminate an actor".to_string(),
+        location: SourceLocation::default(),
+    });
+
     // ========== INTEGER ARITHMETIC (no capability required) ==========
 
     registry.register_function(super::global_ffi_registry::FfiFunction {
         name: "add".to_string(),
         host_function: HostFunction::IntAdd,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Int".to_string(), "Int".to_string()],
         return_type: "Int".to_string(),
         documentation: "Add two integers".to_string(),
@@ -52,6 +122,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "sub".to_string(),
         host_function: HostFunction::IntSub,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Int".to_string(), "Int".to_string()],
         return_type: "Int".to_string(),
         documentation: "Subtract second integer from first".to_string(),
@@ -62,6 +133,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "mul".to_string(),
         host_function: HostFunction::IntMul,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Int".to_string(), "Int".to_string()],
         return_type: "Int".to_string(),
         documentation: "Multiply two integers".to_string(),
@@ -72,6 +144,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "div".to_string(),
         host_function: HostFunction::IntDiv,
         required_capability: None,
+        pure: false,
         parameter_types: vec!["Int".to_string(), "Int".to_string()],
         return_type: "Int".to_string(),
         documentation: "Divide first integer by second (integer division)".to_string(),
@@ -82,6 +155,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "mod".to_string(),
         host_function: HostFunction::IntMod,
         required_capability: None,
+        pure: false,
         parameter_types: vec!["Int".to_string(), "Int".to_string()],
         return_type: "Int".to_string(),
         documentation: "Modulo: remainder of first integer divided by second".to_string(),
@@ -94,6 +168,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "fadd".to_string(),
         host_function: HostFunction::FloatAdd,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Float".to_string(), "Float".to_string()],
         return_type: "Float".to_string(),
         documentation: "Add two floats".to_string(),
@@ -104,6 +179,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "fsub".to_string(),
         host_function: HostFunction::FloatSub,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Float".to_string(), "Float".to_string()],
         return_type: "Float".to_string(),
         documentation: "Subtract second float from first".to_string(),
@@ -114,6 +190,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "fmul".to_string(),
         host_function: HostFunction::FloatMul,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Float".to_string(), "Float".to_string()],
         return_type: "Float".to_string(),
         documentation: "Multiply two floats".to_string(),
@@ -124,6 +201,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "fdiv".to_string(),
         host_function: HostFunction::FloatDiv,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Float".to_string(), "Float".to_string()],
         return_type: "Float".to_string(),
         documentation: "Divide first float by second".to_string(),
@@ -136,6 +214,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "int-to-float".to_string(),
         host_function: HostFunction::IntToFloat,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Int".to_string()],
         return_type: "Float".to_string(),
         documentation: "Convert integer to float".to_string(),
@@ -146,6 +225,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "float-to-int".to_string(),
         host_function: HostFunction::FloatToInt,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Float".to_string()],
         return_type: "Int".to_string(),
         documentation: "Convert float to integer (truncates decimal)".to_string(),
@@ -158,6 +238,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "eq".to_string(),
         host_function: HostFunction::IntEq,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Int".to_string(), "Int".to_string()],
         return_type: "Int".to_string(),
         documentation: "Check if two integers are equal (returns 1 if true, 0 if false)"
@@ -169,6 +250,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "lt".to_string(),
         host_function: HostFunction::IntLt,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Int".to_string(), "Int".to_string()],
         return_type: "Int".to_string(),
         documentation: "Check if first integer is less than second (returns 1 if true, 0 if false)"
@@ -180,6 +262,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "gt".to_string(),
         host_function: HostFunction::IntGt,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Int".to_string(), "Int".to_string()],
         return_type: "Int".to_string(),
         documentation:
@@ -194,6 +277,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "feq".to_string(),
         host_function: HostFunction::FloatEq,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Float".to_string(), "Float".to_string()],
         return_type: "Int".to_string(),
         documentation: "Check if two floats are equal (returns 1 if true, 0 if false)".to_string(),
@@ -204,6 +288,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "flt".to_string(),
         host_function: HostFunction::FloatLt,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Float".to_string(), "Float".to_string()],
         return_type: "Int".to_string(),
         documentation: "Check if first float is less than second (returns 1 if true, 0 if false)"
@@ -215,6 +300,7 @@ pub fn create_standard_ffi_registry() -> super::global_ffi_registry::FfiRegistry
         name: "fgt".to_string(),
         host_function: HostFunction::FloatGt,
         required_capability: None,
+        pure: true,
         parameter_types: vec!["Float".to_string(), "Float".to_string()],
         return_type: "Int".to_string(),
         documentation:
@@ -28,8 +28,12 @@ impl CoreWorldCompiler {
                 arguments,
                 ..
             } => self.compile_call(function, arguments),
+            // Rest parameters have no Core-World translation
             AstNode::Lambda {
-                parameters, body, ..
+                parameters,
+                rest: None,
+                body,
+                ..
             } => self.compile_lambda(parameters, body),
             // Handle other AST nodes...
             _ => Err(CompilationError::InternalError(format!(
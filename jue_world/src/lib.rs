@@ -49,15 +49,19 @@ pub mod sandboxed_comptime;
 pub mod test_timeout;
 
 pub use crate::shared::ast;
+pub use crate::shared::capability_set;
 pub use crate::shared::error;
 pub use crate::shared::resource_limits;
 pub use crate::shared::structured_error;
 pub use crate::shared::trust_tier;
 pub use crate::shared::type_system;
 
+pub use crate::core_compilation::analysis;
 pub use crate::core_compilation::capability_analyzer;
 pub use crate::core_compilation::core_compiler;
+pub use crate::core_compilation::empirical_validation;
 pub use crate::core_compilation::escape_analysis;
+pub use crate::core_compilation::optimize;
 
 pub use crate::physics_integration::bytecode_generator;
 pub use crate::physics_integration::physics_compiler;
@@ -77,7 +81,7 @@ pub use crate::parsing::parser;
 pub use crate::parsing::tokenizer;
 
 pub use crate::comptime::{
-    ComptimeEnv, ComptimeExecutor, ComptimeResult,
+    ComptimeCache, ComptimeEnv, ComptimeExecutor, ComptimeResult, EvaluationInputs,
 };
 
 // Note: recursion_analysis.rs contains test modules, not exportable types
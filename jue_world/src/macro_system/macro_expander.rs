@@ -1,11 +1,15 @@
 /// Macro expander for Jue-World V2.0
 ///
 /// This module handles hygienic macro expansion with explicit capture escapes.
-use crate::error::{CapabilityViolation, CompilationError};
-use crate::shared::ast::AstNode;
+use crate::comptime::fold_constants;
+use crate::error::{CapabilityViolation, CompilationError, MacroExpansionSite, SourceLocation};
+use crate::physics_compiler::compile_to_physics_world;
+use crate::resource_limits::ResourceLimits;
+use crate::sandboxed_comptime::SandboxedComptimeExecutor;
+use crate::shared::ast::{AstNode, Literal};
 use crate::shared::trust_tier::TrustTier;
-use physics_world::types::Capability;
-use std::collections::HashMap;
+use physics_world::types::{Capability, Value};
+use std::collections::{HashMap, HashSet};
 /// Macro definition
 #[derive(Debug, Clone)]
 pub struct MacroDefinition {
@@ -25,13 +29,29 @@ pub struct MacroExpansionContext {
     pub macros: HashMap<String, MacroDefinition>,
     /// Current trust tier
     pub trust_tier: TrustTier,
+    /// Number of macro-introduced bindings renamed so far; each rename takes
+    /// the next value as its suffix, so no two are ever the same
+    pub gensym_counter: usize,
+    /// Names of the macros whose expansion is evaluated at compile time
+    pub comptime_macros: HashSet<String>,
+    /// Evaluates comptime macro expansions, memoizing pure ones across the
+    /// expansions of one context
+    pub comptime: SandboxedComptimeExecutor,
 }
 
 /// Create a new macro expansion context
 pub fn create_macro_expansion_context(trust_tier: TrustTier) -> MacroExpansionContext {
+    let limits = ResourceLimits::default();
     MacroExpansionContext {
         macros: HashMap::new(),
         trust_tier,
+        gensym_counter: 0,
+        comptime_macros: HashSet::new(),
+        comptime: SandboxedComptimeExecutor::new(
+            trust_tier,
+            limits.step_limit,
+            limits.memory_limit,
+        ),
     }
 }
 
@@ -56,6 +76,7 @@ pub fn define_macro(
         }));
     }
 
+    context.comptime_macros.remove(&name);
     context.macros.insert(
         name.clone(),
         MacroDefinition {
@@ -69,11 +90,75 @@ pub fn define_macro(
     Ok(())
 }
 
+/// Define a macro whose expansion is evaluated at compile time.
+///
+/// Each call expands like a [`define_macro`] macro and is then replaced by
+/// the literal its expansion evaluates to in the context's sandboxed comptime
+/// executor. A repeated call with equal arguments reuses the cached result of
+/// a pure evaluation.
+///
+/// # Errors
+///
+/// Fails like [`define_macro`], or with a capability error if the context's
+/// tier does not allow compile-time evaluation.
+pub fn define_comptime_macro(
+    context: &mut MacroExpansionContext,
+    name: String,
+    parameters: Vec<String>,
+    body: AstNode,
+    trust_tier: TrustTier,
+) -> Result<(), CompilationError> {
+    if !context
+        .trust_tier
+        .allows_capability(&Capability::ComptimeEval)
+    {
+        return Err(CompilationError::CapabilityError(CapabilityViolation {
+            required: Capability::ComptimeEval,
+            tier: context.trust_tier,
+            location: Default::default(),
+            suggestion: format!(
+                "Comptime macro {name} requires compile-time evaluation, which tier {:?} does not allow",
+                context.trust_tier
+            ),
+        }));
+    }
+    define_macro(context, name.clone(), parameters, body, trust_tier)?;
+    context.comptime_macros.insert(name);
+    Ok(())
+}
+
 /// Expand a macro call
 pub fn expand_macro(
-    context: &MacroExpansionContext,
+    context: &mut MacroExpansionContext,
+    macro_name: &str,
+    arguments: Vec<AstNode>,
+) -> Result<AstNode, CompilationError> {
+    expand_macro_at(context, macro_name, arguments, &SourceLocation::default())
+}
+
+/// Expand a macro call, reporting errors at `location`
+///
+/// Nodes copied from the macro body keep their locations in the definition
+/// and record `location` as the call they were expanded from; arguments
+/// substituted into the body are left untouched.
+///
+/// Expansion is hygienic: every variable the macro body binds is renamed
+/// with a fresh gensym suffix, so it can neither capture nor shadow a
+/// variable of the same name in the arguments or around the call.
+///
+/// A macro defined with [`define_comptime_macro`] is then evaluated, and the
+/// call replaced by the literal it evaluates to.
+///
+/// # Errors
+///
+/// Returns `MacroArityMismatch` if the number of arguments differs from the
+/// number of parameters in the definition, or the compilation or comptime
+/// error of a comptime macro whose expansion cannot be evaluated to a literal.
+pub fn expand_macro_at(
+    context: &mut MacroExpansionContext,
     macro_name: &str,
     arguments: Vec<AstNode>,
+    location: &SourceLocation,
 ) -> Result<AstNode, CompilationError> {
     // Find the macro definition
     let macro_def = context
@@ -81,19 +166,16 @@ pub fn expand_macro(
         .get(macro_name)
         .ok_or_else(|| CompilationError::ParseError {
             message: format!("Macro {} not found", macro_name),
-            location: Default::default(),
+            location: location.clone(),
         })?;
 
     // Check parameter count
     if macro_def.parameters.len() != arguments.len() {
-        return Err(CompilationError::ParseError {
-            message: format!(
-                "Macro {} expects {} arguments but got {}",
-                macro_name,
-                macro_def.parameters.len(),
-                arguments.len()
-            ),
-            location: Default::default(),
+        return Err(CompilationError::MacroArityMismatch {
+            macro_name: macro_name.to_string(),
+            expected: macro_def.parameters.len(),
+            got: arguments.len(),
+            location: location.clone(),
         });
     }
 
@@ -104,81 +186,323 @@ pub fn expand_macro(
     }
 
     // Perform substitution in the macro body
-    substitute_variables(&macro_def.body, &substitutions)
+    let mut expansion = Expansion {
+        substitutions,
+        site: MacroExpansionSite {
+            macro_name: macro_name.to_string(),
+            call_site: location.clone(),
+        },
+        gensym_counter: &mut context.gensym_counter,
+    };
+    let expanded = expansion.substitute(&macro_def.body, &HashMap::new())?;
+    if !context.comptime_macros.contains(macro_name) {
+        return Ok(expanded);
+    }
+
+    // Built-in operators are calls to the physics compiler, so constant ones
+    // are folded first. The body is compiled before the cache lookup, since
+    // the cache is keyed on it.
+    let expanded = fold_constants(&expanded);
+    let (bytecode, constants) = compile_to_physics_world(&expanded, context.trust_tier)?;
+    context.comptime.constants = constants;
+    let result = context
+        .comptime
+        .execute_macro(macro_name, &arguments, |_| Ok(bytecode))?;
+    let literal = match result.value {
+        Value::Nil => Literal::Nil,
+        Value::Bool(value) => Literal::Bool(value),
+        Value::Int(value) => Literal::Int(value),
+        Value::Float(value) => Literal::Float(value),
+        Value::String(value) => Literal::String(value),
+        other => {
+            return Err(CompilationError::ComptimeError(format!(
+                "Comptime macro {macro_name} evaluated to {other:?}, which is not a literal"
+            )))
+        }
+    };
+    Ok(AstNode::Literal(literal))
 }
 
-/// Substitute variables in an AST node
-fn substitute_variables(
-    node: &AstNode,
-    substitutions: &HashMap<String, AstNode>,
-) -> Result<AstNode, CompilationError> {
-    match node {
-        AstNode::Variable(name) => {
-            if let Some(replacement) = substitutions.get(name) {
-                Ok(replacement.clone())
-            } else {
-                Ok(node.clone())
+/// `location` of a macro body node, marked as expanded at `site`
+fn expanded_location(location: &SourceLocation, site: &MacroExpansionSite) -> SourceLocation {
+    SourceLocation {
+        expanded_from: Some(Box::new(site.clone())),
+        ..location.clone()
+    }
+}
+
+/// A macro body being copied out for one call
+struct Expansion<'a> {
+    /// Call-site arguments, by parameter name
+    substitutions: HashMap<String, AstNode>,
+    /// Call the body is expanded at
+    site: MacroExpansionSite,
+    /// The context's counter, advanced by each rename
+    gensym_counter: &'a mut usize,
+}
+
+impl Expansion<'_> {
+    /// Fresh name for a variable bound by the macro body.
+    ///
+    /// `#` cannot appear in a parsed identifier, so the result never
+    /// collides with a user's variable.
+    fn gensym(&mut self, name: &str) -> String {
+        let fresh = format!("{}#{}", name, self.gensym_counter);
+        *self.gensym_counter += 1;
+        fresh
+    }
+
+    /// Rename `names`, bound by the macro body, in a scope inside `renames`
+    fn bind(
+        &mut self,
+        names: &[String],
+        renames: &HashMap<String, String>,
+    ) -> (Vec<String>, HashMap<String, String>) {
+        let mut scope = renames.clone();
+        let fresh = names
+            .iter()
+            .map(|name| {
+                let fresh = self.gensym(name);
+                scope.insert(name.clone(), fresh.clone());
+                fresh
+            })
+            .collect();
+        (fresh, scope)
+    }
+
+    /// Copy a body node, substituting parameters and renaming the body's own
+    /// bindings in scope (`renames`)
+    fn substitute(
+        &mut self,
+        node: &AstNode,
+        renames: &HashMap<String, String>,
+    ) -> Result<AstNode, CompilationError> {
+        match node {
+            AstNode::Variable(name) => Ok(self.substitute_variable(name, renames)),
+            AstNode::Lambda {
+                parameters,
+                rest,
+                body,
+                location,
+            } => {
+                let bound: Vec<String> = parameters.iter().chain(rest).cloned().collect();
+                let (mut parameters, scope) = self.bind(&bound, renames);
+                Ok(AstNode::Lambda {
+                    rest: rest.as_ref().and_then(|_| parameters.pop()),
+                    parameters,
+                    body: Box::new(self.substitute(body, &scope)?),
+                    location: expanded_location(location, &self.site),
+                })
+            }
+            AstNode::Let {
+                bindings,
+                body,
+                location,
+            } => {
+                // Values are evaluated outside the new bindings
+                let (bindings, body) = self.substitute_bindings(bindings, body, renames, false)?;
+                Ok(AstNode::Let {
+                    bindings,
+                    body: Box::new(body),
+                    location: expanded_location(location, &self.site),
+                })
             }
+            AstNode::LetStar {
+                bindings,
+                body,
+                location,
+            } => {
+                let (bindings, body) = self.substitute_let_star(bindings, body, renames)?;
+                Ok(AstNode::LetStar {
+                    bindings,
+                    body: Box::new(body),
+                    location: expanded_location(location, &self.site),
+                })
+            }
+            AstNode::Letrec {
+                bindings,
+                body,
+                location,
+            } => {
+                // Every value sees every binding
+                let (bindings, body) = self.substitute_bindings(bindings, body, renames, true)?;
+                Ok(AstNode::Letrec {
+                    bindings,
+                    body: Box::new(body),
+                    location: expanded_location(location, &self.site),
+                })
+            }
+            AstNode::If {
+                condition,
+                then_branch,
+                else_branch,
+                location,
+            } => Ok(AstNode::If {
+                condition: Box::new(self.substitute(condition, renames)?),
+                then_branch: Box::new(self.substitute(then_branch, renames)?),
+                else_branch: Box::new(self.substitute(else_branch, renames)?),
+                location: expanded_location(location, &self.site),
+            }),
+            AstNode::Call {
+                function,
+                arguments,
+                location,
+            } => Ok(AstNode::Call {
+                function: Box::new(self.substitute(function, renames)?),
+                arguments: self.substitute_all(arguments, renames)?,
+                location: expanded_location(location, &self.site),
+            }),
+            AstNode::FfiCall {
+                function,
+                arguments,
+                location,
+            } => Ok(AstNode::FfiCall {
+                function: function.clone(),
+                arguments: self.substitute_all(arguments, renames)?,
+                location: expanded_location(location, &self.site),
+            }),
+            AstNode::MacroExpansion {
+                name,
+                arguments,
+                location,
+            } => Ok(AstNode::MacroExpansion {
+                name: name.clone(),
+                arguments: self.substitute_all(arguments, renames)?,
+                location: expanded_location(location, &self.site),
+            }),
+            // Handle other AST node types
+            _ => Ok(node.clone()),
         }
-        AstNode::Lambda {
-            parameters,
-            body,
-            location,
-        } => {
-            let new_body = substitute_variables(body, substitutions)?;
-            Ok(AstNode::Lambda {
-                parameters: parameters.clone(),
-                body: Box::new(new_body),
-                location: location.clone(),
-            })
+    }
+
+    /// The node a reference to `name` in the body becomes
+    fn substitute_variable(&self, name: &str, renames: &HashMap<String, String>) -> AstNode {
+        // A body binding shadows a parameter of the same name
+        if let Some(fresh) = renames.get(name) {
+            AstNode::Variable(fresh.clone())
+        } else if let Some(replacement) = self.substitutions.get(name) {
+            // Call-site code is spliced in as written
+            replacement.clone()
+        } else {
+            AstNode::Variable(name.to_string())
         }
-        AstNode::Call {
-            function,
-            arguments,
-            location,
-        } => {
-            let new_function = substitute_variables(function, substitutions)?;
-            let new_arguments = arguments
-                .iter()
-                .map(|arg| substitute_variables(arg, substitutions))
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(AstNode::Call {
-                function: Box::new(new_function),
-                arguments: new_arguments,
-                location: location.clone(),
-            })
+    }
+
+    /// Copy a `let` or `letrec`'s bindings and body; `recursive` values
+    /// are evaluated inside the new bindings
+    fn substitute_bindings(
+        &mut self,
+        bindings: &[(String, AstNode)],
+        body: &AstNode,
+        renames: &HashMap<String, String>,
+        recursive: bool,
+    ) -> Result<(Vec<(String, AstNode)>, AstNode), CompilationError> {
+        let names: Vec<String> = bindings.iter().map(|(name, _)| name.clone()).collect();
+        let (names, scope) = self.bind(&names, renames);
+        let value_scope = if recursive { &scope } else { renames };
+        let values = self.substitute_all(bindings.iter().map(|(_, value)| value), value_scope)?;
+        let body = self.substitute(body, &scope)?;
+        Ok((names.into_iter().zip(values).collect(), body))
+    }
+
+    /// Copy a `let*`'s bindings and body, each value seeing the bindings
+    /// before it
+    fn substitute_let_star(
+        &mut self,
+        bindings: &[(String, AstNode)],
+        body: &AstNode,
+        renames: &HashMap<String, String>,
+    ) -> Result<(Vec<(String, AstNode)>, AstNode), CompilationError> {
+        let mut scope = renames.clone();
+        let mut new_bindings = Vec::with_capacity(bindings.len());
+        for (name, value) in bindings {
+            let value = self.substitute(value, &scope)?;
+            let fresh = self.gensym(name);
+            scope.insert(name.clone(), fresh.clone());
+            new_bindings.push((fresh, value));
         }
-        // Handle other AST node types
-        _ => Ok(node.clone()),
+        Ok((new_bindings, self.substitute(body, &scope)?))
+    }
+
+    /// Copy each of `nodes` in the same scope
+    fn substitute_all<'n>(
+        &mut self,
+        nodes: impl IntoIterator<Item = &'n AstNode>,
+        renames: &HashMap<String, String>,
+    ) -> Result<Vec<AstNode>, CompilationError> {
+        nodes
+            .into_iter()
+            .map(|node| self.substitute(node, renames))
+            .collect()
     }
 }
 
 /// Expand all macros in an AST node
 pub fn expand_macros(
     node: &AstNode,
-    context: &MacroExpansionContext,
+    context: &mut MacroExpansionContext,
 ) -> Result<AstNode, CompilationError> {
     match node {
         AstNode::MacroExpansion {
             name: macro_name,
             arguments,
-            location: _,
+            location,
         } => {
-            let expanded = expand_macro(context, &macro_name, arguments.clone())?;
+            let expanded = expand_macro_at(context, macro_name, arguments.clone(), location)?;
             expand_macros(&expanded, context)
         }
         AstNode::Lambda {
             parameters,
+            rest,
             body,
             location,
         } => {
             let new_body = expand_macros(body, context)?;
             Ok(AstNode::Lambda {
                 parameters: parameters.clone(),
+                rest: rest.clone(),
                 body: Box::new(new_body),
                 location: location.clone(),
             })
         }
+        AstNode::Let {
+            bindings,
+            body,
+            location,
+        } => Ok(AstNode::Let {
+            bindings: expand_bindings(bindings, context)?,
+            body: Box::new(expand_macros(body, context)?),
+            location: location.clone(),
+        }),
+        AstNode::LetStar {
+            bindings,
+            body,
+            location,
+        } => Ok(AstNode::LetStar {
+            bindings: expand_bindings(bindings, context)?,
+            body: Box::new(expand_macros(body, context)?),
+            location: location.clone(),
+        }),
+        AstNode::Letrec {
+            bindings,
+            body,
+            location,
+        } => Ok(AstNode::Letrec {
+            bindings: expand_bindings(bindings, context)?,
+            body: Box::new(expand_macros(body, context)?),
+            location: location.clone(),
+        }),
+        AstNode::If {
+            condition,
+            then_branch,
+            else_branch,
+            location,
+        } => Ok(AstNode::If {
+            condition: Box::new(expand_macros(condition, context)?),
+            then_branch: Box::new(expand_macros(then_branch, context)?),
+            else_branch: Box::new(expand_macros(else_branch, context)?),
+            location: location.clone(),
+        }),
         AstNode::Call {
             function,
             arguments,
@@ -195,7 +519,33 @@ pub fn expand_macros(
                 location: location.clone(),
             })
         }
+        AstNode::FfiCall {
+            function,
+            arguments,
+            location,
+        } => {
+            let new_arguments = arguments
+                .iter()
+                .map(|arg| expand_macros(arg, context))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(AstNode::FfiCall {
+                function: function.clone(),
+                arguments: new_arguments,
+                location: location.clone(),
+            })
+        }
         // Handle other AST node types
         _ => Ok(node.clone()),
     }
 }
+
+/// Expand all macros in the values of `bindings`
+fn expand_bindings(
+    bindings: &[(String, AstNode)],
+    context: &mut MacroExpansionContext,
+) -> Result<Vec<(String, AstNode)>, CompilationError> {
+    bindings
+        .iter()
+        .map(|(name, value)| Ok((name.clone(), expand_macros(value, context)?)))
+        .collect()
+}
@@ -6,18 +6,49 @@ use crate::token::Token;
 /// Expression parser
 pub struct ExpressionParser<'a> {
     tokens: &'a [Token],
+    /// Where each token starts in the source; empty when unknown
+    locations: &'a [SourceLocation],
+    /// Opening parenthesis of every form being parsed, innermost last
+    form_locations: Vec<SourceLocation>,
     position: usize,
 }
 
 impl<'a> ExpressionParser<'a> {
     /// Create a new expression parser
+    ///
+    /// Nodes get default locations; use [`Self::with_locations`] to locate
+    /// them in the source.
     pub fn new(tokens: &'a [Token]) -> Self {
+        Self::with_locations(tokens, &[])
+    }
+
+    /// Create an expression parser that locates every form at its opening
+    /// parenthesis, `locations[i]` being where `tokens[i]` starts
+    #[must_use]
+    pub fn with_locations(tokens: &'a [Token], locations: &'a [SourceLocation]) -> Self {
         Self {
             tokens,
+            locations,
+            form_locations: Vec::new(),
             position: 0,
         }
     }
 
+    /// Location of the current token
+    fn token_location(&self) -> SourceLocation {
+        self.location_at(self.position)
+    }
+
+    /// Location of the token at `index`
+    fn location_at(&self, index: usize) -> SourceLocation {
+        self.locations.get(index).cloned().unwrap_or_default()
+    }
+
+    /// Location of the innermost form being parsed
+    fn form_location(&self) -> SourceLocation {
+        self.form_locations.last().cloned().unwrap_or_default()
+    }
+
     /// Safely get current token
     fn current_token(&self) -> Option<&Token> {
         self.tokens.get(self.position)
@@ -59,6 +90,11 @@ impl<'a> ExpressionParser<'a> {
                 self.advance();
                 result
             }
+            Some(Token::Float(n)) => {
+                let result = Ok(AstNode::Literal(Literal::Float(*n)));
+                self.advance();
+                result
+            }
             Some(Token::Boolean(b)) => {
                 let result = self.parse_boolean(*b);
                 self.advance();
@@ -81,6 +117,14 @@ impl<'a> ExpressionParser<'a> {
     }
 
     fn parse_list(&mut self) -> Result<AstNode, CompilationError> {
+        self.form_locations.push(self.token_location());
+        let form = self.parse_form();
+        self.form_locations.pop();
+        form
+    }
+
+    /// Parse the form whose opening parenthesis is the current token
+    fn parse_form(&mut self) -> Result<AstNode, CompilationError> {
         self.advance(); // Skip '('
 
         if self.is_at_end() {
@@ -94,6 +138,7 @@ impl<'a> ExpressionParser<'a> {
             Some(Token::Symbol(s)) if s.starts_with(':') => self.parse_trust_tier(),
             Some(Token::Symbol(s)) if s == "lambda" => self.parse_lambda(),
             Some(Token::Symbol(s)) if s == "let" => self.parse_let(),
+            Some(Token::Symbol(s)) if s == "let*" => self.parse_let_star(),
             Some(Token::Symbol(s)) if s == "letrec" => self.parse_letrec(),
             Some(Token::Symbol(s)) if s == "if" => self.parse_if(),
             Some(Token::Symbol(s)) if s == "require-capability" => self.parse_require_capability(),
@@ -135,14 +180,14 @@ impl<'a> ExpressionParser<'a> {
         Ok(AstNode::TrustTier {
             tier,
             expression: Box::new(expression),
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
     fn parse_lambda(&mut self) -> Result<AstNode, CompilationError> {
         self.advance(); // Skip 'lambda'
 
-        let parameters = self.parse_parameter_list()?;
+        let (parameters, rest) = self.parse_parameter_list()?;
         let body = self.parse()?;
 
         // Skip closing paren
@@ -157,12 +202,15 @@ impl<'a> ExpressionParser<'a> {
 
         Ok(AstNode::Lambda {
             parameters,
+            rest,
             body: Box::new(body),
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
-    fn parse_parameter_list(&mut self) -> Result<Vec<String>, CompilationError> {
+    /// Parse `(a b)`, `(a b . rest)` or a lone `a` into the fixed parameters
+    /// and the rest parameter, if any
+    fn parse_parameter_list(&mut self) -> Result<(Vec<String>, Option<String>), CompilationError> {
         if self.is_at_end() {
             return Err(CompilationError::ParseError {
                 message: "Expected parameter list".to_string(),
@@ -174,6 +222,7 @@ impl<'a> ExpressionParser<'a> {
             Some(Token::OpenParen) => {
                 self.advance();
                 let mut parameters = Vec::new();
+                let mut rest = None;
 
                 while !self.is_at_end() {
                     match self.current_token() {
@@ -185,21 +234,40 @@ impl<'a> ExpressionParser<'a> {
                             parameters.push(s.clone());
                             self.advance();
                         }
+                        Some(Token::Dot) => {
+                            let dot = self.token_location();
+                            self.advance();
+                            let Some(Token::Symbol(s)) = self.current_token() else {
+                                return Err(CompilationError::ParseError {
+                                    message: "Expected rest parameter name after '.'".to_string(),
+                                    location: dot,
+                                });
+                            };
+                            rest = Some(s.clone());
+                            let rest_location = self.token_location();
+                            self.advance();
+                            if !matches!(self.current_token(), Some(Token::CloseParen)) {
+                                return Err(CompilationError::ParseError {
+                                    message: "Rest parameter must be last".to_string(),
+                                    location: rest_location,
+                                });
+                            }
+                        }
                         _ => {
                             return Err(CompilationError::ParseError {
                                 message: "Expected parameter name".to_string(),
-                                location: SourceLocation::default(),
+                                location: self.token_location(),
                             })
                         }
                     }
                 }
 
-                Ok(parameters)
+                Ok((parameters, rest))
             }
             Some(Token::Symbol(s)) => {
                 let param = s.clone();
                 self.advance();
-                Ok(vec![param])
+                Ok((vec![param], None))
             }
             _ => Err(CompilationError::ParseError {
                 message: "Expected parameter list".to_string(),
@@ -227,7 +295,30 @@ impl<'a> ExpressionParser<'a> {
         Ok(AstNode::Let {
             bindings,
             body: Box::new(body),
-            location: SourceLocation::default(),
+            location: self.form_location(),
+        })
+    }
+
+    fn parse_let_star(&mut self) -> Result<AstNode, CompilationError> {
+        self.advance(); // Skip 'let*'
+
+        let bindings = self.parse_bindings()?;
+        let body = self.parse()?;
+
+        // Skip closing paren
+        if let Some(Token::CloseParen) = self.current_token() {
+            self.advance();
+        } else {
+            return Err(CompilationError::ParseError {
+                message: "Expected closing parenthesis".to_string(),
+                location: SourceLocation::default(),
+            });
+        }
+
+        Ok(AstNode::LetStar {
+            bindings,
+            body: Box::new(body),
+            location: self.form_location(),
         })
     }
 
@@ -250,7 +341,7 @@ impl<'a> ExpressionParser<'a> {
         Ok(AstNode::Letrec {
             bindings,
             body: Box::new(body),
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
@@ -284,7 +375,7 @@ impl<'a> ExpressionParser<'a> {
         Ok(AstNode::Define {
             name,
             value: Box::new(value),
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
@@ -373,7 +464,7 @@ impl<'a> ExpressionParser<'a> {
             condition: Box::new(condition),
             then_branch: Box::new(then_branch),
             else_branch: Box::new(else_branch),
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
@@ -405,7 +496,7 @@ impl<'a> ExpressionParser<'a> {
 
         Ok(AstNode::RequireCapability {
             capability,
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
@@ -437,7 +528,7 @@ impl<'a> ExpressionParser<'a> {
 
         Ok(AstNode::HasCapability {
             capability,
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
@@ -456,7 +547,17 @@ impl<'a> ExpressionParser<'a> {
 
         self.advance();
 
-        let parameters = self.parse_parameter_list()?;
+        let parameters_start = self.position;
+        let (parameters, rest) = self.parse_parameter_list()?;
+        if rest.is_some() {
+            let dot = (parameters_start..self.position)
+                .find(|&index| matches!(self.tokens.get(index), Some(Token::Dot)))
+                .unwrap_or(parameters_start);
+            return Err(CompilationError::ParseError {
+                message: "Macros cannot take a rest parameter".to_string(),
+                location: self.location_at(dot),
+            });
+        }
         let body = self.parse()?;
 
         // Skip closing paren
@@ -476,7 +577,7 @@ impl<'a> ExpressionParser<'a> {
             body: Box::new(body),
             capabilities: vec![],       // TODO: Parse capabilities
             tier: "formal".to_string(), // TODO: Parse tier
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
@@ -534,7 +635,7 @@ impl<'a> ExpressionParser<'a> {
         Ok(AstNode::FfiCall {
             function,
             arguments,
-            location: SourceLocation::default(),
+            location: self.form_location(),
         })
     }
 
@@ -567,7 +668,7 @@ impl<'a> ExpressionParser<'a> {
                         return Ok(AstNode::Call {
                             function: Box::new(AstNode::Symbol(function_name)),
                             arguments,
-                            location: SourceLocation::default(),
+                            location: self.form_location(),
                         });
                     } else if function_name.chars().all(|c| {
                         c.is_alphanumeric()
@@ -581,14 +682,14 @@ impl<'a> ExpressionParser<'a> {
                         return Ok(AstNode::Call {
                             function: Box::new(AstNode::Variable(function_name)),
                             arguments,
-                            location: SourceLocation::default(),
+                            location: self.form_location(),
                         });
                     } else {
                         // Everything else is treated as a symbol (built-in operator)
                         return Ok(AstNode::Call {
                             function: Box::new(AstNode::Symbol(function_name)),
                             arguments,
-                            location: SourceLocation::default(),
+                            location: self.form_location(),
                         });
                     }
                 }
@@ -619,7 +720,7 @@ impl<'a> ExpressionParser<'a> {
                     return Ok(AstNode::Call {
                         function: Box::new(function),
                         arguments,
-                        location: SourceLocation::default(),
+                        location: self.form_location(),
                     });
                 }
                 _ => {
@@ -673,6 +774,46 @@ impl<'a> ExpressionParser<'a> {
         Ok(AstNode::Literal(Literal::Nil))
     }
 
+    /// Parse every top-level form, recovering from errors.
+    ///
+    /// When a form fails to parse, its error is recorded and parsing resumes at
+    /// the next top-level form, so each malformed form reports one error.
+    pub fn parse_all_collecting(&mut self) -> (Vec<AstNode>, Vec<CompilationError>) {
+        let mut forms = Vec::new();
+        let mut errors = Vec::new();
+
+        while !self.is_at_end() {
+            let form_start = self.position;
+            match self.parse() {
+                Ok(form) => forms.push(form),
+                Err(error) => {
+                    errors.push(error);
+                    // Synchronize on the boundary of the form that failed
+                    self.position = form_start;
+                    self.skip_form();
+                }
+            }
+        }
+
+        (forms, errors)
+    }
+
+    /// Skip one complete datum starting at the current token
+    fn skip_form(&mut self) {
+        let mut depth = 0usize;
+        while let Some(token) = self.current_token() {
+            match token {
+                Token::OpenParen => depth += 1,
+                Token::CloseParen => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            self.advance();
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+
     /// Advance to next token safely
     fn advance(&mut self) {
         self.position += 1;
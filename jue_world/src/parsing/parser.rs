@@ -5,6 +5,36 @@ use crate::expression_parser::ExpressionParser;
 use crate::test_timeout::ParserGuard;
 use crate::token::Token;
 
+/// Parser dialect options
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParserConfig {
+    /// Accept `[` `]` as list delimiters equivalent to `(` `)`
+    pub square_brackets: bool,
+
+    /// Require each list to be closed by the same bracket type that opened it
+    pub require_matching_brackets: bool,
+}
+
+impl ParserConfig {
+    /// Square brackets allowed, and either bracket type may close either
+    #[must_use]
+    pub fn permissive() -> Self {
+        Self {
+            square_brackets: true,
+            require_matching_brackets: false,
+        }
+    }
+
+    /// Square brackets allowed, but `[` must be closed by `]` and `(` by `)`
+    #[must_use]
+    pub fn strict() -> Self {
+        Self {
+            square_brackets: true,
+            require_matching_brackets: true,
+        }
+    }
+}
+
 /// Jue parser
 pub struct Parser {
     /// Source code
@@ -19,19 +49,33 @@ pub struct Parser {
     /// Current column
     pub column: usize,
 
+    /// Dialect options
+    pub config: ParserConfig,
+
     /// Resource guard for preventing OOM issues
     resource_guard: ParserGuard,
+
+    /// Where each token produced by the last tokenization starts
+    token_locations: Vec<SourceLocation>,
 }
 
 impl Parser {
     /// Create a new parser
     pub fn new(source: String) -> Self {
+        Self::with_config(source, ParserConfig::default())
+    }
+
+    /// Create a new parser for the given dialect
+    #[must_use]
+    pub fn with_config(source: String, config: ParserConfig) -> Self {
         Self {
             source,
             position: 0,
             line: 1,
             column: 1,
+            config,
             resource_guard: ParserGuard::new(1000, 10000), // Max depth 1000, max tokens 10000
+            token_locations: Vec::new(),
         }
     }
 
@@ -41,6 +85,7 @@ impl Parser {
             line: self.line,
             column: self.column,
             offset: self.position,
+            expanded_from: None,
         }
     }
 
@@ -56,6 +101,17 @@ impl Parser {
         self.parse_expression(&tokens)
     }
 
+    /// Parse every top-level form in the source, collecting all parse errors.
+    ///
+    /// Tokenization errors are not recoverable and are returned alone.
+    pub fn parse_collecting(&mut self) -> (Vec<AstNode>, Vec<CompilationError>) {
+        match self.tokenize() {
+            Ok(tokens) => ExpressionParser::with_locations(&tokens, &self.token_locations)
+                .parse_all_collecting(),
+            Err(error) => (Vec::new(), vec![error]),
+        }
+    }
+
     /// Get current character safely
     fn current_char(&self) -> Option<char> {
         self.source.chars().nth(self.position)
@@ -64,20 +120,36 @@ impl Parser {
     /// Tokenize source code
     fn tokenize(&mut self) -> Result<Vec<Token>, CompilationError> {
         let mut tokens = Vec::new();
+        // Opening bracket of every list not yet closed
+        let mut open_brackets = Vec::new();
+        self.token_locations.clear();
 
         while let Some(c) = self.current_char() {
+            let start = self.current_location();
+            let token_count = tokens.len();
             match c {
-                '(' => {
+                '(' | '[' if c == '(' || self.config.square_brackets => {
                     self.resource_guard.enter_scope().map_err(|e| {
                         CompilationError::ParserResourceLimit(crate::error::ParserError {
                             message: format!("Max depth exceeded: {}", e),
                             location: self.current_location(),
                         })
                     })?;
+                    open_brackets.push(c);
                     tokens.push(Token::OpenParen);
                     self.advance();
                 }
-                ')' => {
+                ')' | ']' if c == ')' || self.config.square_brackets => {
+                    let opener = open_brackets.pop();
+                    if self.config.require_matching_brackets {
+                        let expected = if c == ')' { '(' } else { '[' };
+                        if let Some(opener) = opener.filter(|&opener| opener != expected) {
+                            return Err(CompilationError::ParseError {
+                                message: format!("Mismatched brackets: '{opener}' closed by '{c}'"),
+                                location: self.current_location(),
+                            });
+                        }
+                    }
                     self.resource_guard.exit_scope();
                     tokens.push(Token::CloseParen);
                     self.advance();
@@ -88,6 +160,11 @@ impl Parser {
                 ';' => {
                     self.skip_comment();
                 }
+                '.' => {
+                    // Only a lone dot, as before a rest parameter, gets here
+                    tokens.push(Token::Dot);
+                    self.advance();
+                }
                 '\'' => {
                     self.advance();
                     let symbol = self.read_symbol()?;
@@ -100,9 +177,13 @@ impl Parser {
                 }
                 _ if c.is_digit(10) => {
                     let number = self.read_number()?;
-                    // Parse as f64 for Token::Number
+                    // Parse as f64; a decimal point makes the literal a float
                     if let Ok(num) = number.parse::<f64>() {
-                        tokens.push(Token::Number(num));
+                        if number.contains('.') {
+                            tokens.push(Token::Float(num));
+                        } else {
+                            tokens.push(Token::Number(num));
+                        }
                     } else {
                         return Err(CompilationError::ParseError {
                             message: format!("Invalid number: {}", number),
@@ -136,6 +217,9 @@ impl Parser {
                     });
                 }
             }
+            if tokens.len() > token_count {
+                self.token_locations.push(start);
+            }
         }
 
         Ok(tokens)
@@ -279,7 +363,7 @@ impl Parser {
 
     /// Parse expression from tokens
     fn parse_expression(&self, tokens: &[Token]) -> Result<AstNode, CompilationError> {
-        let mut parser = ExpressionParser::new(tokens);
+        let mut parser = ExpressionParser::with_locations(tokens, &self.token_locations);
         parser.parse()
     }
 }
@@ -289,3 +373,21 @@ pub fn parse(source: &str) -> Result<AstNode, CompilationError> {
     let mut parser = Parser::new(source.to_string());
     parser.parse()
 }
+
+/// Parse Jue source code written in the given dialect
+///
+/// # Errors
+///
+/// Returns a parse error if the source is malformed, including mismatched
+/// brackets when the dialect requires matching ones.
+pub fn parse_with_config(source: &str, config: ParserConfig) -> Result<AstNode, CompilationError> {
+    let mut parser = Parser::with_config(source.to_string(), config);
+    parser.parse()
+}
+
+/// Parse all top-level forms of Jue source code, collecting every parse error
+#[must_use]
+pub fn parse_collecting(source: &str) -> (Vec<AstNode>, Vec<CompilationError>) {
+    let mut parser = Parser::new(source.to_string());
+    parser.parse_collecting()
+}
@@ -2,11 +2,22 @@
 ///
 /// This module converts source code into tokens for parsing.
 use crate::token::{SourceLocation, Token};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Token paired with the byte span it was read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    /// The token
+    pub token: Token,
+    /// Byte range of the token in the source
+    pub span: Range<usize>,
+}
 
 /// Tokenizer state
-pub struct Tokenizer {
-    /// Input source code
-    input: String,
+pub struct Tokenizer<'a> {
+    /// Input source code, owned or borrowed
+    input: Cow<'a, str>,
     /// Current position in input
     position: usize,
     /// Current line number
@@ -15,23 +26,34 @@ pub struct Tokenizer {
     column: usize,
     /// Current file name
     file: &'static str,
+    /// Whether comments are emitted as tokens instead of skipped
+    preserve_comments: bool,
 }
 
-impl Tokenizer {
+impl<'a> Tokenizer<'a> {
     /// Create a new tokenizer
-    pub fn new(input: String, file: &'static str) -> Self {
+    pub fn new(input: impl Into<Cow<'a, str>>, file: &'static str) -> Self {
         Tokenizer {
-            input,
+            input: input.into(),
             position: 0,
             line: 1,
             column: 1,
             file,
+            preserve_comments: false,
         }
     }
 
+    /// Emit comments as `Token::Comment` instead of skipping them, for
+    /// tooling such as documentation extraction
+    #[must_use]
+    pub fn preserving_comments(mut self) -> Self {
+        self.preserve_comments = true;
+        self
+    }
+
     /// Peek at the next character without consuming it
     fn peek(&self) -> Option<char> {
-        self.input.chars().nth(self.position)
+        self.input[self.position..].chars().next()
     }
 
     /// Consume the next character
@@ -58,117 +80,229 @@ impl Tokenizer {
         }
     }
 
+    /// Skip a comment starting at the current position, returning its span.
+    ///
+    /// Line comments run from `;` to the end of the line; block comments run
+    /// from `#|` to the matching `|#` and may nest. An unterminated block
+    /// comment extends to the end of the input.
+    fn skip_comment(&mut self) -> Option<Range<usize>> {
+        let start = self.position;
+        let rest = &self.input[self.position..];
+        if rest.starts_with(';') {
+            while self.peek().is_some_and(|ch| ch != '\n') {
+                self.consume();
+            }
+        } else if rest.starts_with("#|") {
+            let mut depth = 0;
+            while self.position < self.input.len() {
+                let rest = &self.input[self.position..];
+                if rest.starts_with("#|") {
+                    depth += 1;
+                } else if rest.starts_with("|#") {
+                    depth -= 1;
+                } else {
+                    self.consume();
+                    continue;
+                }
+                self.consume();
+                self.consume();
+                if depth == 0 {
+                    break;
+                }
+            }
+        } else {
+            return None;
+        }
+        Some(start..self.position)
+    }
+
     /// Tokenize the input
     pub fn tokenize(&mut self) -> Vec<Token> {
+        let mut tokens: Vec<Token> = self
+            .tokenize_spanned()
+            .into_iter()
+            .map(|spanned| spanned.token)
+            .collect();
+        tokens.push(Token::Eof);
+        tokens
+    }
+
+    /// Tokenize the input, recording the byte span of every token
+    pub fn tokenize_spanned(&mut self) -> Vec<SpannedToken> {
         let mut tokens = Vec::new();
+        while let Some(token) = self.next_token() {
+            tokens.push(token);
+        }
+        tokens
+    }
 
-        while let Some(ch) = self.peek() {
-            match ch {
-                // Skip whitespace
-                ch if ch.is_whitespace() => {
-                    self.skip_whitespace();
-                    continue;
-                }
+    /// Tokenize only the tokens overlapping `byte_range` of `source`.
+    ///
+    /// Tokenization restarts from the beginning of the line containing the
+    /// range start, or from the start of the token or comment spanning that
+    /// line start, so a range starting mid-string, mid-comment or mid-token is
+    /// handled the same way as in a full tokenization. Range ends falling
+    /// inside a multibyte character are moved back to its start. Spans are
+    /// relative to `source`.
+    #[must_use]
+    pub fn tokenize_range(source: &str, byte_range: Range<usize>) -> Vec<SpannedToken> {
+        let end = Self::char_boundary_at_or_before(source, byte_range.end);
+        let start = Self::char_boundary_at_or_before(source, byte_range.start.min(end));
+        let boundary = Self::safe_boundary(source, start);
 
-                // Single character tokens
-                '(' => tokens.push(Token::OpenParen),
-                ')' => tokens.push(Token::CloseParen),
-                '[' => tokens.push(Token::LeftBracket),
-                ']' => tokens.push(Token::RightBracket),
-                '{' => tokens.push(Token::LeftBrace),
-                '}' => tokens.push(Token::RightBrace),
-                '.' => tokens.push(Token::Dot),
-                ',' => tokens.push(Token::Comma),
-                ';' => tokens.push(Token::Semicolon),
-                ':' => tokens.push(Token::Colon),
-                '\'' => tokens.push(Token::Quote),
-                '`' => tokens.push(Token::Backtick),
-                '+' => tokens.push(Token::Plus),
-                '-' => tokens.push(Token::Minus),
-                '*' => tokens.push(Token::Star),
-                '/' => tokens.push(Token::Slash),
-                '%' => tokens.push(Token::Percent),
-                '=' => tokens.push(Token::Equals),
-                '!' => tokens.push(Token::Bang),
-                '<' => tokens.push(Token::Less),
-                '>' => tokens.push(Token::Greater),
-                '&' => tokens.push(Token::Ampersand),
-                '|' => tokens.push(Token::Pipe),
-                '^' => tokens.push(Token::Caret),
-                '?' => tokens.push(Token::Question),
-
-                // Identifiers and keywords
-                ch if ch.is_alphabetic() || ch == '_' => {
-                    let start = self.position;
-                    while let Some(ch) = self.peek() {
-                        if ch.is_alphanumeric() || ch == '_' || ch == '-' || ch == '?' || ch == '!'
-                        {
-                            self.consume();
-                        } else {
-                            break;
-                        }
-                    }
-                    let end = self.position;
-                    let ident = self.input[start..end].to_string();
-                    tokens.push(Token::Symbol(ident));
+        let mut tokenizer = Tokenizer::new(&source[boundary..], "range");
+        let mut tokens = Vec::new();
+        while let Some(mut spanned) = tokenizer.next_token() {
+            spanned.span = spanned.span.start + boundary..spanned.span.end + boundary;
+            if spanned.span.start >= end {
+                break;
+            }
+            if spanned.span.end > start {
+                tokens.push(spanned);
+            }
+        }
+        tokens
+    }
+
+    /// Clamp `offset` to `source` and move it back to a character boundary
+    fn char_boundary_at_or_before(source: &str, offset: usize) -> usize {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+
+    /// Find a position at or before `offset` where the lexer is between tokens
+    ///
+    /// The source before the line containing `offset` is lexed in place, with
+    /// comments kept as tokens, so quotes inside comments and semicolons
+    /// inside strings are seen exactly as a full tokenization sees them. Only
+    /// a string or block comment still open at the end of that prefix reaches
+    /// its last byte, the newline, and then the line starts inside it.
+    fn safe_boundary(source: &str, offset: usize) -> usize {
+        let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let mut lexer = Tokenizer::new(&source[..line_start], "range").preserving_comments();
+        let mut boundary = line_start;
+        while let Some(spanned) = lexer.next_token() {
+            if spanned.span.end >= line_start {
+                boundary = spanned.span.start;
+            }
+        }
+        boundary
+    }
+
+    /// Read the next token, skipping leading whitespace and, unless they are
+    /// preserved, comments
+    fn next_token(&mut self) -> Option<SpannedToken> {
+        self.skip_whitespace();
+        while let Some(span) = self.skip_comment() {
+            if self.preserve_comments {
+                return Some(SpannedToken {
+                    token: Token::Comment { span: span.clone() },
+                    span,
+                });
+            }
+            self.skip_whitespace();
+        }
+        let start = self.position;
+        let ch = self.peek()?;
+
+        let token = match ch {
+            // Single character tokens
+            '(' | ')' | '[' | ']' | '{' | '}' | '.' | ',' | ':' | '\'' | '`' | '+' | '-' | '*'
+            | '/' | '%' | '=' | '!' | '<' | '>' | '&' | '|' | '^' | '?' => {
+                self.consume();
+                match ch {
+                    '(' => Token::OpenParen,
+                    ')' => Token::CloseParen,
+                    '[' => Token::LeftBracket,
+                    ']' => Token::RightBracket,
+                    '{' => Token::LeftBrace,
+                    '}' => Token::RightBrace,
+                    '.' => Token::Dot,
+                    ',' => Token::Comma,
+                    ':' => Token::Colon,
+                    '\'' => Token::Quote,
+                    '`' => Token::Backtick,
+                    '+' => Token::Plus,
+                    '-' => Token::Minus,
+                    '*' => Token::Star,
+                    '/' => Token::Slash,
+                    '%' => Token::Percent,
+                    '=' => Token::Equals,
+                    '!' => Token::Bang,
+                    '<' => Token::Less,
+                    '>' => Token::Greater,
+                    '&' => Token::Ampersand,
+                    '|' => Token::Pipe,
+                    '^' => Token::Caret,
+                    _ => Token::Question,
                 }
+            }
 
-                // Numbers
-                ch if ch.is_ascii_digit() => {
-                    let start = self.position;
-                    let mut is_float = false;
-                    while let Some(ch) = self.peek() {
-                        if ch.is_ascii_digit() {
-                            self.consume();
-                        } else if ch == '.' && !is_float {
-                            self.consume();
-                            is_float = true;
-                        } else {
-                            break;
-                        }
-                    }
-                    let end = self.position;
-                    let num_str = &self.input[start..end];
-                    if is_float {
-                        if let Ok(num) = num_str.parse::<f64>() {
-                            tokens.push(Token::Number(num));
-                        } else {
-                            tokens.push(Token::Unknown(ch));
-                        }
+            // Identifiers and keywords
+            ch if ch.is_alphabetic() || ch == '_' => {
+                while let Some(ch) = self.peek() {
+                    if ch.is_alphanumeric() || ch == '_' || ch == '-' || ch == '?' || ch == '!' {
+                        self.consume();
                     } else {
-                        if let Ok(num) = num_str.parse::<i64>() {
-                            tokens.push(Token::Number(num as f64));
-                        } else {
-                            tokens.push(Token::Unknown(ch));
-                        }
+                        break;
                     }
                 }
+                Token::Symbol(self.input[start..self.position].to_string())
+            }
 
-                // Strings
-                '"' => {
-                    self.consume(); // Consume opening quote
-                    let start = self.position;
-                    while let Some(ch) = self.peek() {
-                        if ch == '"' {
-                            break;
-                        }
+            // Numbers
+            ch if ch.is_ascii_digit() => {
+                let mut is_float = false;
+                while let Some(ch) = self.peek() {
+                    if ch.is_ascii_digit() {
                         self.consume();
+                    } else if ch == '.' && !is_float {
+                        self.consume();
+                        is_float = true;
+                    } else {
+                        break;
                     }
-                    let end = self.position;
-                    let str_content = self.input[start..end].to_string();
-                    self.consume(); // Consume closing quote
-                    tokens.push(Token::String(str_content));
                 }
+                let num_str = &self.input[start..self.position];
+                if is_float {
+                    num_str
+                        .parse::<f64>()
+                        .map_or(Token::Unknown(ch), Token::Float)
+                } else {
+                    num_str
+                        .parse::<i64>()
+                        .map_or(Token::Unknown(ch), |num| Token::Number(num as f64))
+                }
+            }
 
-                // Unknown characters
-                _ => {
-                    let ch = self.consume().unwrap();
-                    tokens.push(Token::Unknown(ch));
+            // Strings
+            '"' => {
+                self.consume(); // Consume opening quote
+                let content_start = self.position;
+                while let Some(ch) = self.peek() {
+                    if ch == '"' {
+                        break;
+                    }
+                    self.consume();
                 }
+                let str_content = self.input[content_start..self.position].to_string();
+                self.consume(); // Consume closing quote
+                Token::String(str_content)
             }
-        }
 
-        tokens.push(Token::Eof);
-        tokens
+            // Unknown characters
+            _ => {
+                self.consume();
+                Token::Unknown(ch)
+            }
+        };
+
+        Some(SpannedToken {
+            token,
+            span: start..self.position,
+        })
     }
 }
@@ -52,14 +52,17 @@ impl PhysicsWorldCompiler {
 
                 let value_bytecode = match value_expr {
                     AstNode::Lambda {
-                        parameters, body, ..
+                        parameters,
+                        rest,
+                        body,
+                        ..
                     } => {
                         // For recursive lambdas, we need to:
                         // 1. First, push the placeholder value (already done above)
                         // 2. Compile the lambda body
                         // 3. The recursive calls will look up 'name' at offset and find this placeholder
 
-                        self.compile_lambda(parameters, body)?
+                        self.compile_lambda(parameters, rest.as_deref(), body)?
                     }
                     _ => self.compile_to_physics(value_expr)?,
                 };
@@ -35,6 +35,12 @@ impl PhysicsWorldCompiler {
             "*" => OpCode::Mul,
             "/" => OpCode::Div,
             "%" => OpCode::Mod,
+            // Bitwise operations
+            "bit-and" => OpCode::BitAnd,
+            "bit-or" => OpCode::BitOr,
+            "bit-xor" => OpCode::BitXor,
+            "shl" => OpCode::Shl,
+            "shr" => OpCode::Shr,
             // String operations
             "str-concat" => OpCode::StrConcat,
             "str-len" => OpCode::StrLen,
@@ -58,6 +64,7 @@ impl PhysicsWorldCompiler {
             "cons" => OpCode::Cons,
             "car" => OpCode::Car,
             "cdr" => OpCode::Cdr,
+            "length" => OpCode::ListLen,
             // Resource management
             "check-step-limit" => OpCode::CheckStepLimit,
             // Sandbox operations
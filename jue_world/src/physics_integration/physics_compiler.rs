@@ -1,10 +1,19 @@
+use crate::analysis::{child_nodes, tail_positions, NodeId};
 use crate::ast::AstNode;
 use crate::compiler::environment::CompilationEnvironment;
-use crate::error::{CompilationError, SourceLocation};
+use crate::error::{CompilationError, SourceLocation, SourceMap};
 use crate::ffi_system::ffi_call_generator::FfiCallGenerator;
+use crate::ffi_system::global_ffi_registry::FfiRegistry;
 use crate::ffi_system::standard_functions::create_standard_ffi_registry;
 use crate::trust_tier::TrustTier;
 use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::opcodes::closure_text::CLOSURE_BODY_PREFIX;
+use physics_world::vm::state::{EscapeStatus, FunctionInfo, PairConstant};
+use physics_world::vm::{FunctionNames, SymbolTable};
+use std::collections::{HashMap, HashSet};
+
+/// Error code of the record thrown when a guarded FFI call lacks its capability
+pub const CAPABILITY_DENIED_CODE: i64 = 403;
 
 /// Convert a string capability name to a Capability enum
 /// Maps string names to their corresponding Capability variants
@@ -22,6 +31,7 @@ pub fn string_to_capability(name: &str) -> Option<Capability> {
         "SysCreateActor" => Some(Capability::SysCreateActor),
         "SysTerminateActor" => Some(Capability::SysTerminateActor),
         "SysClock" => Some(Capability::SysClock),
+        "SysGc" => Some(Capability::SysGc),
         _ => None,
     }
 }
@@ -34,8 +44,14 @@ pub struct PhysicsWorldCompiler {
     pub location: SourceLocation,
     /// Capability indices
     pub capability_indices: Vec<Capability>,
-    /// String constant pool
-    pub string_pool: Vec<String>,
+    /// String constant pool, also holding the other pooled constants;
+    /// structurally equal constants share one slot
+    pub string_pool: Vec<Value>,
+    /// Literal pairs pooled in [`Self::string_pool`], built when the program
+    /// is loaded; a slot is reused for every structurally equal literal
+    pub pair_constants: Vec<PairConstant>,
+    /// Interned symbols, shipped with the bytecode so ids match at runtime
+    pub symbol_table: SymbolTable,
     /// FFI registry
     pub ffi_registry: FfiCallGenerator,
     /// Compilation environment
@@ -44,6 +60,27 @@ pub struct PhysicsWorldCompiler {
     pub is_compiling_recursive_lambda: bool,
     /// Debug flag to disable TCO
     pub disable_tco: bool,
+    /// Nodes of the AST being compiled that are in tail position
+    pub tail_positions: HashSet<NodeId>,
+    /// Source location of each instruction of the last compiled program
+    pub source_locations: Vec<SourceLocation>,
+    /// Metadata for every lambda compiled, keyed by function id: lambdas
+    /// are numbered in the order compilation reaches them, or by the
+    /// constant slot of their body when [`Self::hoist_lambdas`] is set
+    pub function_table: HashMap<u16, FunctionInfo>,
+    /// Names of the lambdas bound by `define`, `let`, `let*` or `letrec`,
+    /// keyed by the same function ids as [`Self::function_table`]
+    pub function_names: FunctionNames,
+    /// Store each lambda's body in the constant pool and emit
+    /// `MakeClosure(slot, captures)` for it, the form the VM runs, instead
+    /// of `MakeClosure(arity, body length)` followed by the body inline
+    pub hoist_lambdas: bool,
+    /// Constant slot and code of every hoisted lambda body; the slot holds
+    /// a placeholder until [`Self::store_hoisted_bodies`]
+    hoisted_bodies: Vec<(usize, Vec<OpCode>)>,
+    /// For every node being compiled, innermost last, where the code of
+    /// each child compiled so far starts and the locations of that code
+    child_locations: Vec<Vec<(usize, Vec<SourceLocation>)>>,
 }
 
 impl PhysicsWorldCompiler {
@@ -54,6 +91,8 @@ impl PhysicsWorldCompiler {
             location: SourceLocation::default(),
             capability_indices: Vec::new(),
             string_pool: Vec::new(),
+            pair_constants: Vec::new(),
+            symbol_table: SymbolTable::new(),
             ffi_registry: FfiCallGenerator {
                 registry: create_standard_ffi_registry(),
                 location: SourceLocation::default(),
@@ -61,6 +100,13 @@ impl PhysicsWorldCompiler {
             environment: CompilationEnvironment::new(),
             is_compiling_recursive_lambda: false,
             disable_tco: false, // Default: TCO enabled
+            tail_positions: HashSet::new(),
+            source_locations: Vec::new(),
+            function_table: HashMap::new(),
+            function_names: FunctionNames::new(),
+            hoist_lambdas: false,
+            hoisted_bodies: Vec::new(),
+            child_locations: Vec::new(),
         }
     }
 
@@ -76,24 +122,220 @@ impl PhysicsWorldCompiler {
 
     /// Get or create string index in constant pool
     pub fn get_string_index(&mut self, string: &str) -> usize {
-        if let Some(index) = self.string_pool.iter().position(|s| s == string) {
+        self.get_constant_index(Value::String(string.to_string()))
+    }
+
+    /// Get or create the index of a constant in the constant pool
+    ///
+    /// Constants are compared structurally, so a repeated compound constant
+    /// reuses the slot of the first one instead of taking a new slot.
+    pub fn get_constant_index(&mut self, value: Value) -> usize {
+        // Slots reserved for literal pairs hold a placeholder until load time,
+        // and those reserved for lambda bodies until compilation ends
+        let is_reserved = |index: usize| {
+            self.pair_constants.iter().any(|pair| pair.slot == index)
+                || self.hoisted_bodies.iter().any(|(slot, _)| *slot == index)
+        };
+        if let Some(index) = self
+            .string_pool
+            .iter()
+            .enumerate()
+            .position(|(index, existing)| !is_reserved(index) && constants_equal(existing, &value))
+        {
             index
         } else {
-            self.string_pool.push(string.to_string());
+            self.string_pool.push(value);
             self.string_pool.len() - 1
         }
     }
 
-    /// Compile AST to Physics-World bytecode with tail context tracking
+    /// Get or create the constant slot of the literal pair whose car and cdr
+    /// are the constants at `car` and `cdr`
     ///
-    /// # Arguments
-    /// * `ast` - The AST node to compile
-    /// * `in_tail_position` - Whether this expression is in tail position (last expr that determines return value)
-    pub fn compile_to_physics_with_tail_context(
+    /// Parts are pooled before the pair, so equal literal pairs have equal
+    /// part slots and share one pair slot.
+    pub fn get_pair_index(&mut self, car: usize, cdr: usize) -> usize {
+        if let Some(pair) = self
+            .pair_constants
+            .iter()
+            .find(|pair| pair.car == car && pair.cdr == cdr)
+        {
+            return pair.slot;
+        }
+        let slot = self.string_pool.len();
+        self.string_pool.push(Value::Nil);
+        self.pair_constants.push(PairConstant { slot, car, cdr });
+        slot
+    }
+
+    /// Constant slot of `node`, which must satisfy [`is_literal_data`],
+    /// pooling it and its parts
+    fn pool_literal_data(&mut self, node: &AstNode) -> usize {
+        match node {
+            AstNode::Cons { car, cdr, .. } => {
+                let (car, cdr) = (self.pool_literal_data(car), self.pool_literal_data(cdr));
+                self.get_pair_index(car, cdr)
+            }
+            AstNode::List { elements, .. } => {
+                let parts: Vec<usize> = elements
+                    .iter()
+                    .map(|element| self.pool_literal_data(element))
+                    .collect();
+                let nil = self.get_constant_index(Value::Nil);
+                parts
+                    .into_iter()
+                    .rev()
+                    .fold(nil, |tail, head| self.get_pair_index(head, tail))
+            }
+            AstNode::Literal(literal) => self.get_constant_index(match literal {
+                crate::ast::Literal::Nil => Value::Nil,
+                crate::ast::Literal::Bool(value) => Value::Bool(*value),
+                crate::ast::Literal::Int(value) => Value::Int(*value),
+                crate::ast::Literal::Float(value) => Value::Float(*value),
+                crate::ast::Literal::String(value) => Value::String(value.clone()),
+            }),
+            _ => unreachable!("pool_literal_data called on {node:?}"),
+        }
+    }
+
+    /// Compile a pair or list construction
+    ///
+    /// One built only from literals loads its pooled constant; otherwise the
+    /// parts are evaluated and joined with `Cons`, a list ending in nil.
+    fn compile_compound(&mut self, node: &AstNode) -> Result<Vec<OpCode>, CompilationError> {
+        let empty = matches!(node, AstNode::List { elements, .. } if elements.is_empty());
+        if is_literal_data(node) && !empty {
+            return Ok(vec![OpCode::GetConst(self.pool_literal_data(node))]);
+        }
+        let mut bytecode = Vec::new();
+        match node {
+            AstNode::Cons { car, cdr, .. } => {
+                self.emit_node(&mut bytecode, car)?;
+                self.emit_node(&mut bytecode, cdr)?;
+                bytecode.push(OpCode::Cons);
+            }
+            AstNode::List { elements, .. } => {
+                for element in elements {
+                    self.emit_node(&mut bytecode, element)?;
+                }
+                bytecode.push(OpCode::Nil);
+                bytecode.extend(std::iter::repeat_n(OpCode::Cons, elements.len()));
+            }
+            _ => {}
+        }
+        Ok(bytecode)
+    }
+
+    /// Compile AST to Physics-World bytecode
+    ///
+    /// Tail positions are computed once for the whole tree and consulted
+    /// when emitting calls. The location of every emitted instruction is
+    /// left in [`Self::source_locations`].
+    pub fn compile_to_physics(&mut self, ast: &AstNode) -> Result<Vec<OpCode>, CompilationError> {
+        self.tail_positions = tail_positions(ast);
+        let (bytecode, locations) = self.compile_located(ast)?;
+        self.source_locations = locations;
+        Ok(bytecode)
+    }
+
+    /// Source map of the last compiled program, with an entry wherever the
+    /// location changes from the instruction before
+    #[must_use]
+    pub fn source_map(&self) -> SourceMap {
+        let mut source_map = SourceMap::new();
+        for (offset, location) in self.source_locations.iter().enumerate() {
+            if offset == 0 || self.source_locations[offset - 1] != *location {
+                source_map.add_mapping(offset, location.clone());
+            }
+        }
+        source_map
+    }
+
+    /// Bind `names` to the first top-level local slots, in order, so the
+    /// program can read values the host stores there before running it
+    pub fn declare_inputs(&mut self, names: &[String]) {
+        for name in names {
+            self.environment.define_variable(name);
+        }
+    }
+
+    /// Whether `node` is in tail position in the tree being compiled
+    #[must_use]
+    pub fn is_tail_position(&self, node: &AstNode) -> bool {
+        self.tail_positions.contains(&NodeId::of(node))
+    }
+
+    /// Compile `ast`, returning its code and the location of each instruction
+    ///
+    /// Instructions emitted by a child keep the child's location; the rest
+    /// are located at `ast`, or at the nearest enclosing node with a location.
+    fn compile_located(
         &mut self,
         ast: &AstNode,
-        in_tail_position: bool,
-    ) -> Result<Vec<OpCode>, CompilationError> {
+    ) -> Result<(Vec<OpCode>, Vec<SourceLocation>), CompilationError> {
+        let enclosing = self.location.clone();
+        if let Some(location) = ast.location() {
+            self.location = location.clone();
+        }
+        self.child_locations.push(Vec::new());
+        let bytecode = self.compile_node(ast);
+        let children = self.child_locations.pop().unwrap_or_default();
+        let own = std::mem::replace(&mut self.location, enclosing);
+        let bytecode = bytecode?;
+
+        let mut locations = vec![own; bytecode.len()];
+        for (start, child) in children {
+            if let Some(slots) = locations.get_mut(start..start + child.len()) {
+                slots.clone_from_slice(&child);
+            }
+        }
+        Ok((bytecode, locations))
+    }
+
+    /// Compile `node` onto the end of `bytecode`, keeping its locations
+    fn emit_node(
+        &mut self,
+        bytecode: &mut Vec<OpCode>,
+        node: &AstNode,
+    ) -> Result<(), CompilationError> {
+        let (code, locations) = self.compile_located(node)?;
+        if let Some(children) = self.child_locations.last_mut() {
+            children.push((bytecode.len(), locations));
+        }
+        bytecode.extend(code);
+        Ok(())
+    }
+
+    /// Compile the value bound to `name`, naming it in
+    /// [`Self::function_names`] if it is a lambda
+    fn emit_binding(
+        &mut self,
+        bytecode: &mut Vec<OpCode>,
+        name: &str,
+        value: &AstNode,
+    ) -> Result<(), CompilationError> {
+        if let AstNode::Lambda { .. } = value {
+            // The lambda takes the next function id when compiled
+            self.function_names.insert(self.next_function_id(), name);
+        }
+        self.emit_node(bytecode, value)
+    }
+
+    /// Locate `count` generated instructions inserted at `at` with the
+    /// instruction they precede, or the last one when appended
+    fn locate_inserted(&mut self, at: usize, count: usize) {
+        let nearest = self
+            .source_locations
+            .get(at)
+            .or_else(|| self.source_locations.last())
+            .cloned()
+            .unwrap_or_default();
+        self.source_locations
+            .splice(at..at, std::iter::repeat_n(nearest, count));
+    }
+
+    /// Compile a single node of the tree passed to [`Self::compile_to_physics`]
+    fn compile_node(&mut self, ast: &AstNode) -> Result<Vec<OpCode>, CompilationError> {
         match ast {
             AstNode::Literal(lit) => self.compile_literal(lit),
             AstNode::Variable(name) => self.compile_variable(name),
@@ -102,15 +344,19 @@ impl PhysicsWorldCompiler {
                 function,
                 arguments,
                 ..
-            } => self.compile_call(function, arguments, in_tail_position),
+            } => self.compile_call(function, arguments, self.is_tail_position(ast)),
             AstNode::Lambda {
-                parameters, body, ..
-            } => self.compile_lambda(parameters, body),
-            AstNode::Let { bindings, body, .. } => {
-                self.compile_let(bindings, body, in_tail_position)
-            }
+                parameters,
+                rest,
+                body,
+                ..
+            } => self.compile_lambda(parameters, rest.as_deref(), body),
+            AstNode::Let { bindings, body, .. } => self.compile_let(bindings, body),
+            AstNode::LetStar { bindings, body, .. } => self.compile_let_star(bindings, body),
             AstNode::TrustTier { expression, .. } => {
-                self.compile_to_physics_with_tail_context(expression, in_tail_position)
+                let mut bytecode = Vec::new();
+                self.emit_node(&mut bytecode, expression)?;
+                Ok(bytecode)
             }
             AstNode::RequireCapability { capability, .. } => {
                 self.compile_require_capability_string(capability)
@@ -123,16 +369,15 @@ impl PhysicsWorldCompiler {
                 then_branch,
                 else_branch,
                 ..
-            } => self.compile_if(condition, then_branch, else_branch, in_tail_position),
+            } => self.compile_if(condition, then_branch, else_branch),
             AstNode::FfiCall {
                 function,
                 arguments,
                 location,
             } => self.compile_ffi_call(function, arguments, location),
             AstNode::Define { name, value, .. } => self.compile_define(name.clone(), value),
-            AstNode::Letrec { bindings, body, .. } => {
-                self.compile_letrec(bindings, body, in_tail_position)
-            }
+            AstNode::Letrec { bindings, body, .. } => self.compile_letrec(bindings, body),
+            AstNode::Cons { .. } | AstNode::List { .. } => self.compile_compound(ast),
             // Handle other AST nodes...
             _ => Err(CompilationError::InternalError(format!(
                 "Unsupported AST node for Physics-World compilation: {:?}",
@@ -141,11 +386,6 @@ impl PhysicsWorldCompiler {
         }
     }
 
-    /// Compile AST to Physics-World bytecode (backward-compatible wrapper)
-    pub fn compile_to_physics(&mut self, ast: &AstNode) -> Result<Vec<OpCode>, CompilationError> {
-        self.compile_to_physics_with_tail_context(ast, false)
-    }
-
     /// Compile a literal value
     pub fn compile_literal(
         &mut self,
@@ -174,14 +414,14 @@ impl PhysicsWorldCompiler {
 
     /// Compile a symbol
     pub fn compile_symbol(&mut self, name: &str) -> Result<Vec<OpCode>, CompilationError> {
-        let symbol_index = self.get_string_index(name);
-        Ok(vec![OpCode::Symbol(symbol_index)])
+        let symbol_id = self.symbol_table.intern(name);
+        Ok(vec![OpCode::Symbol(symbol_id)])
     }
 
     /// Compile a function call
     ///
-    /// Auto-detects FFI function calls when the function is a Symbol
-    /// that matches a registered FFI function.
+    /// Auto-detects FFI function calls when the function names a function
+    /// in the FFI registry, including ones an embedder registered.
     ///
     /// # Arguments
     /// * `function` - The function to call
@@ -193,11 +433,23 @@ impl PhysicsWorldCompiler {
         arguments: &[AstNode],
         in_tail_position: bool,
     ) -> Result<Vec<OpCode>, CompilationError> {
-        // Check if this is a symbol-based call that might be an FFI function
-        if let AstNode::Symbol(name) = function {
+        // Type predicates lower to type test opcodes
+        if let [argument] = arguments {
+            if let Some(test) = self.type_predicate(function) {
+                let mut bytecode = Vec::new();
+                self.emit_node(&mut bytecode, argument)?;
+                bytecode.extend(test);
+                return Ok(bytecode);
+            }
+        }
+
+        // Check if this call names a registered FFI function that no local
+        // variable shadows
+        if let AstNode::Symbol(name) | AstNode::Variable(name) = function {
             // Check FFI registry first - FFI functions take priority
             // We need to avoid borrow conflict, so we check existence first
-            let is_ffi_function = self.ffi_registry.registry.find_function(name).is_some();
+            let is_ffi_function = self.ffi_registry.registry.find_function(name).is_some()
+                && self.environment.get_variable_index(name).is_none();
             if is_ffi_function {
                 // Clone location to avoid borrow conflict with mutable self
                 let location = self.location.clone();
@@ -208,13 +460,13 @@ impl PhysicsWorldCompiler {
         // Regular function call - compile as closure call
         let mut bytecode = Vec::new();
 
-        // Compile arguments in reverse order (NOT in tail position)
+        // Compile arguments in reverse order
         for arg in arguments.iter().rev() {
-            bytecode.extend(self.compile_to_physics_with_tail_context(arg, false)?);
+            self.emit_node(&mut bytecode, arg)?;
         }
 
-        // Compile function (NOT in tail position)
-        bytecode.extend(self.compile_to_physics_with_tail_context(function, false)?);
+        // Compile function
+        self.emit_node(&mut bytecode, function)?;
 
         // Emit Call or TailCall based on position
         if in_tail_position && !self.disable_tco {
@@ -226,15 +478,70 @@ impl PhysicsWorldCompiler {
         Ok(bytecode)
     }
 
+    /// Instructions testing the value on top of the stack, if `function`
+    /// names a built-in type predicate that no local variable shadows
+    fn type_predicate(&self, function: &AstNode) -> Option<Vec<OpCode>> {
+        let (AstNode::Variable(name) | AstNode::Symbol(name)) = function else {
+            return None;
+        };
+        if self.environment.get_variable_index(name).is_some() {
+            return None;
+        }
+        let test = match name.as_str() {
+            "integer?" => vec![OpCode::IsInt],
+            "float?" => vec![OpCode::IsFloat],
+            "pair?" => vec![OpCode::IsPair],
+            "procedure?" => vec![OpCode::IsClosure],
+            "type-of" => vec![OpCode::TypeOf],
+            // A float is replaced by an integer, so IsInt answers for both
+            "number?" => vec![
+                OpCode::Dup,
+                OpCode::IsFloat,
+                OpCode::JmpIfFalse(2),
+                OpCode::Pop,
+                OpCode::Int(0),
+                OpCode::IsInt,
+            ],
+            _ => return None,
+        };
+        Some(test)
+    }
+
     /// Compile a lambda function
-    /// Lambda body is ALWAYS compiled in tail position (per Scheme semantics)
+    ///
+    /// A `rest` parameter takes the local slot after the fixed parameters;
+    /// the body starts with `CollectRest`, which gathers any further
+    /// arguments into a list there.
+    ///
+    /// The lambda's local count and free variables are recorded in
+    /// [`Self::function_table`] under the next function id. When
+    /// [`Self::hoist_lambdas`] is set, the body goes to the constant slot
+    /// that id names; see [`Self::compile_hoisted_lambda`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if the body fails to compile, or if there are more parameters
+    /// or lambdas than `CollectRest` and function ids can address.
     pub fn compile_lambda(
         &mut self,
         parameters: &[String],
+        rest: Option<&str>,
         body: &AstNode,
     ) -> Result<Vec<OpCode>, CompilationError> {
         let mut bytecode = Vec::new();
 
+        // Number the lambda before the lambdas in its body
+        let function = u16::try_from(self.next_function_id()).map_err(|_| {
+            CompilationError::InternalError("Too many lambdas for 16-bit function ids".to_string())
+        })?;
+        let info = self.function_info(parameters, rest, body);
+        if self.hoist_lambdas {
+            let captured = info.captured_locals();
+            self.function_table.insert(function, info);
+            return self.compile_hoisted_lambda(function, &captured, parameters, rest, body);
+        }
+        self.function_table.insert(function, info);
+
         // Create new environment for lambda
         self.environment.push_scope();
 
@@ -243,53 +550,245 @@ impl PhysicsWorldCompiler {
             self.environment.add_variable(param.clone(), i);
         }
 
-        // Compile lambda body - ALWAYS in tail position (per expert guidance)
-        let body_bytecode = self.compile_to_physics_with_tail_context(body, true)?;
+        // Create closure, patching in the body length once it is compiled
+        bytecode.push(OpCode::MakeClosure(parameters.len(), 0));
+        if let Some(rest) = rest {
+            let fixed = u16::try_from(parameters.len()).map_err(|_| {
+                CompilationError::InternalError(format!(
+                    "Too many parameters before rest parameter `{rest}`"
+                ))
+            })?;
+            self.environment
+                .add_variable(rest.to_string(), parameters.len());
+            bytecode.push(OpCode::CollectRest(fixed));
+        }
+        self.emit_node(&mut bytecode, body)?;
+        bytecode[0] = OpCode::MakeClosure(parameters.len(), bytecode.len() - 1);
 
         // Pop environment scope
         self.environment.pop_scope();
 
-        // Create closure
-        bytecode.push(OpCode::MakeClosure(parameters.len(), body_bytecode.len()));
-        bytecode.extend(body_bytecode);
+        Ok(bytecode)
+    }
+
+    /// Id the next lambda compiled will take: its ordinal, or with
+    /// [`Self::hoist_lambdas`] the constant slot its body will take
+    fn next_function_id(&self) -> usize {
+        if self.hoist_lambdas {
+            self.string_pool.len()
+        } else {
+            self.function_table.len()
+        }
+    }
+
+    /// Compile a lambda whose body is stored in constant slot `function`
+    ///
+    /// The body is compiled with slots numbered on from the enclosing
+    /// scope's, like an inline body, so free variables keep distinct slots;
+    /// its own locals are then renumbered from zero, starting with the
+    /// parameters, and it ends with `Ret`. The emitted code pushes the
+    /// `captured` locals and makes a closure of the body capturing them, so
+    /// the `MakeClosure` operand is the function id [`Self::function_table`]
+    /// is keyed by.
+    fn compile_hoisted_lambda(
+        &mut self,
+        function: u16,
+        captured: &[usize],
+        parameters: &[String],
+        rest: Option<&str>,
+        body: &AstNode,
+    ) -> Result<Vec<OpCode>, CompilationError> {
+        let slot = usize::from(function);
+        self.string_pool.push(Value::Nil);
+        self.hoisted_bodies.push((slot, Vec::new()));
+
+        self.environment.push_scope();
+        let base = self.environment.frame_size;
+        for param in parameters {
+            self.environment.define_variable(param);
+        }
+        let mut code = Vec::new();
+        if let Some(rest) = rest {
+            let fixed = u16::try_from(parameters.len()).map_err(|_| {
+                CompilationError::InternalError(format!(
+                    "Too many parameters before rest parameter `{rest}`"
+                ))
+            })?;
+            self.environment.define_variable(rest);
+            code.push(OpCode::CollectRest(fixed));
+        }
+        // The body's locations are not part of the enclosing code
+        self.child_locations.push(Vec::new());
+        let compiled = self.emit_node(&mut code, body);
+        self.child_locations.pop();
+        self.environment.pop_scope();
+        compiled?;
+        code.push(OpCode::Ret);
+        // Slots past 16 bits cannot be addressed, so no local is above one
+        if let Ok(base) = u16::try_from(base) {
+            for op in &mut code {
+                if let OpCode::GetLocal(slot) | OpCode::SetLocal(slot) = op {
+                    if *slot >= base {
+                        *slot -= base;
+                    }
+                }
+            }
+        }
 
+        if let Some(entry) = self.hoisted_bodies.iter_mut().find(|(at, _)| *at == slot) {
+            entry.1 = code;
+        }
+        let mut bytecode = captured
+            .iter()
+            .map(|&local| {
+                u16::try_from(local).map(OpCode::GetLocal).map_err(|_| {
+                    CompilationError::InternalError(format!(
+                        "Captured local {local} is past 16-bit slots"
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        bytecode.push(OpCode::MakeClosure(slot, captured.len()));
         Ok(bytecode)
     }
 
-    /// Compile a let binding
+    /// Write every hoisted lambda body into its constant slot, as the
+    /// `closure_body:` text `MakeClosure` builds the body from
+    pub fn store_hoisted_bodies(&mut self) {
+        for (slot, body) in std::mem::take(&mut self.hoisted_bodies) {
+            let opcodes: Vec<String> = body.iter().map(|op| format!("{op:?}")).collect();
+            self.string_pool[slot] =
+                Value::String(format!("{CLOSURE_BODY_PREFIX}[{}]", opcodes.join(", ")));
+        }
+    }
+
+    /// Escape information for a lambda, computed in the enclosing scope
+    ///
+    /// Every enclosing local the body refers to is a free variable, and all
+    /// of them escape into the closure. Shadowing inside the body is
+    /// ignored, so a free variable may be reported that is never read.
+    fn function_info(
+        &self,
+        parameters: &[String],
+        rest: Option<&str>,
+        body: &AstNode,
+    ) -> FunctionInfo {
+        fn collect<'a>(node: &'a AstNode, names: &mut Vec<&'a str>) {
+            if let AstNode::Variable(name) = node {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+            for child in child_nodes(node) {
+                collect(child, names);
+            }
+        }
+        let mut names = Vec::new();
+        collect(body, &mut names);
+
+        let mut free_variables = Vec::new();
+        for name in names {
+            if parameters.iter().any(|param| param == name) || rest == Some(name) {
+                continue;
+            }
+            if let Some(slot) = self.environment.get_variable_index(name) {
+                if !free_variables.contains(&slot) {
+                    free_variables.push(slot);
+                }
+            }
+        }
+        FunctionInfo {
+            local_count: parameters.len() + usize::from(rest.is_some()),
+            escape_info: (0..free_variables.len())
+                .map(|index| (index, EscapeStatus::Escaping))
+                .collect(),
+            free_variables,
+        }
+    }
+
+    /// Compile a let binding (parallel - no binding sees another)
     ///
     /// # Arguments
     /// * `bindings` - Variable bindings
     /// * `body` - Body expression
-    /// * `in_tail_position` - Whether the body is in tail position
     pub fn compile_let(
         &mut self,
         bindings: &[(String, AstNode)],
         body: &AstNode,
-        in_tail_position: bool,
     ) -> Result<Vec<OpCode>, CompilationError> {
         let mut bytecode = Vec::new();
 
         // Create new environment scope
         self.environment.push_scope();
 
-        // Compile each binding (values NOT in tail position)
+        // Compile each binding, reserving its slot so later values can't reuse it
+        let mut slots = Vec::with_capacity(bindings.len());
         for (name, value) in bindings {
-            let value_bytecode = self.compile_to_physics_with_tail_context(value, false)?;
-            bytecode.extend(value_bytecode);
+            self.emit_binding(&mut bytecode, name, value)?;
 
-            // Add variable to environment
-            let index = self.environment.add_variable(name.clone(), 0);
+            let index = self.environment.reserve_slot();
             bytecode.push(OpCode::SetLocal(index as u16));
+            slots.push((name, index));
+        }
+
+        // Names only become visible in the body
+        for (name, index) in slots {
+            self.environment.bind_slot(name.clone(), index);
         }
 
-        // Compile body - propagate tail context
-        let body_bytecode = self.compile_to_physics_with_tail_context(body, in_tail_position)?;
+        // Compile body
+        let body_start = bytecode.len();
+        self.emit_node(&mut bytecode, body)?;
 
         // Pop environment scope
         self.environment.pop_scope();
 
-        bytecode.extend(body_bytecode);
+        let (binding_bytecode, body_bytecode) = bytecode.split_at_mut(body_start);
+        fuse_store_reload(binding_bytecode, body_bytecode);
+        Ok(bytecode)
+    }
+
+    /// Compile a let* binding (sequential - each value sees the names before it)
+    ///
+    /// Every binding opens its own scope, so a name is visible to the values
+    /// after it and to the body, but never to its own value.
+    ///
+    /// # Arguments
+    /// * `bindings` - Variable bindings
+    /// * `body` - Body expression
+    ///
+    /// # Errors
+    ///
+    /// Fails if a value or the body fails to compile, or if the bindings need
+    /// more local slots than `SetLocal` can address.
+    pub fn compile_let_star(
+        &mut self,
+        bindings: &[(String, AstNode)],
+        body: &AstNode,
+    ) -> Result<Vec<OpCode>, CompilationError> {
+        let mut bytecode = Vec::new();
+
+        for (name, value) in bindings {
+            self.emit_binding(&mut bytecode, name, value)?;
+
+            self.environment.push_scope();
+            let index = self.environment.add_variable(name.clone(), 0);
+            let index = u16::try_from(index).map_err(|_| {
+                CompilationError::InternalError(format!("Local slot {index} out of range"))
+            })?;
+            bytecode.push(OpCode::SetLocal(index));
+        }
+
+        // Compile body
+        let body_start = bytecode.len();
+        self.emit_node(&mut bytecode, body)?;
+
+        for _ in bindings {
+            self.environment.pop_scope();
+        }
+
+        let (binding_bytecode, body_bytecode) = bytecode.split_at_mut(body_start);
+        fuse_store_reload(binding_bytecode, body_bytecode);
         Ok(bytecode)
     }
 
@@ -298,12 +797,10 @@ impl PhysicsWorldCompiler {
     /// # Arguments
     /// * `bindings` - Variable bindings
     /// * `body` - Body expression
-    /// * `in_tail_position` - Whether the body is in tail position
     pub fn compile_letrec(
         &mut self,
         bindings: &[(String, AstNode)],
         body: &AstNode,
-        in_tail_position: bool,
     ) -> Result<Vec<OpCode>, CompilationError> {
         let mut bytecode = Vec::new();
 
@@ -317,11 +814,9 @@ impl PhysicsWorldCompiler {
         }
 
         // Now compile each binding (they can reference each other via the environment)
-        // Binding values are NOT in tail position
         for (name, value) in bindings {
             // Compile the value expression
-            let value_bytecode = self.compile_to_physics_with_tail_context(value, false)?;
-            bytecode.extend(value_bytecode);
+            self.emit_binding(&mut bytecode, name, value)?;
 
             // Store the compiled value in the variable slot
             if let Some(index) = self.environment.get_variable_index(name) {
@@ -329,13 +824,12 @@ impl PhysicsWorldCompiler {
             }
         }
 
-        // Compile body - propagate tail context
-        let body_bytecode = self.compile_to_physics_with_tail_context(body, in_tail_position)?;
+        // Compile body
+        self.emit_node(&mut bytecode, body)?;
 
         // Pop environment scope
         self.environment.pop_scope();
 
-        bytecode.extend(body_bytecode);
         Ok(bytecode)
     }
 
@@ -347,9 +841,8 @@ impl PhysicsWorldCompiler {
     ) -> Result<Vec<OpCode>, CompilationError> {
         let mut bytecode = Vec::new();
 
-        // Compile the value (NOT in tail position for defines)
-        let value_bytecode = self.compile_to_physics_with_tail_context(value, false)?;
-        bytecode.extend(value_bytecode);
+        // Compile the value
+        self.emit_binding(&mut bytecode, &name, value)?;
 
         // Add variable to environment and store
         let index = self.environment.add_variable(name, 0);
@@ -388,25 +881,23 @@ impl PhysicsWorldCompiler {
     /// * `condition` - The condition expression
     /// * `then_branch` - The then branch
     /// * `else_branch` - The else branch
-    /// * `in_tail_position` - Whether the if expression is in tail position
     pub fn compile_if(
         &mut self,
         condition: &AstNode,
         then_branch: &AstNode,
         else_branch: &AstNode,
-        in_tail_position: bool,
     ) -> Result<Vec<OpCode>, CompilationError> {
         let mut bytecode = Vec::new();
 
-        // Compile condition (never in tail position)
-        bytecode.extend(self.compile_to_physics_with_tail_context(condition, false)?);
+        // Compile condition
+        self.emit_node(&mut bytecode, condition)?;
 
         // Reserve space for conditional jump
         bytecode.push(OpCode::JmpIfFalse(0));
         let cond_jump_idx = bytecode.len() - 1;
 
-        // Compile then branch - propagate tail context
-        bytecode.extend(self.compile_to_physics_with_tail_context(then_branch, in_tail_position)?);
+        // Compile then branch
+        self.emit_node(&mut bytecode, then_branch)?;
 
         // Reserve space for jump over else branch
         bytecode.push(OpCode::Jmp(0));
@@ -414,7 +905,7 @@ impl PhysicsWorldCompiler {
 
         // Compile else branch
         let else_start_idx = bytecode.len();
-        bytecode.extend(self.compile_to_physics_with_tail_context(else_branch, in_tail_position)?);
+        self.emit_node(&mut bytecode, else_branch)?;
 
         // Patch conditional jump: jump to else_start_idx if condition is false
         // offset = else_start_idx - cond_jump_idx - 1 (per expert guidance)
@@ -433,25 +924,69 @@ impl PhysicsWorldCompiler {
     }
 
     /// Compile an FFI call
+    ///
+    /// A call to a function that requires a capability is guarded: when
+    /// the capability is missing, the program throws an error record
+    /// instead of making the call. Calls must pass as many arguments as
+    /// the function was registered with, except for the associative
+    /// arithmetic functions, which fold any number of arguments.
     pub fn compile_ffi_call(
         &mut self,
         function: &str,
         arguments: &[AstNode],
-        _location: &SourceLocation,
+        location: &SourceLocation,
     ) -> Result<Vec<OpCode>, CompilationError> {
+        let (required_capability, arity) = self
+            .ffi_registry
+            .registry
+            .find_function(function)
+            .map(|func| (func.required_capability.clone(), func.parameter_types.len()))
+            .ok_or_else(|| CompilationError::FfiFunctionNotFound(function.to_string()))?;
+
         // Check if this is an associative operation that can be folded
         let is_associative = matches!(function, "add" | "fadd" | "mul" | "fmul");
 
+        // Everything else takes exactly the arguments it was registered with
+        if !is_associative && arguments.len() != arity {
+            return Err(CompilationError::FfiArityMismatch {
+                function: function.to_string(),
+                expected: arity,
+                got: arguments.len(),
+                location: location.clone(),
+            });
+        }
+
+        let mut bytecode = match required_capability {
+            Some(capability) => self.capability_guard(function, capability),
+            None => Vec::new(),
+        };
+
         if is_associative && arguments.len() > 2 {
             // Use n-ary associative folding
-            self.compile_nary_associative(function, arguments)
-        } else if arguments.len() > 2 {
-            // Non-associative with too many arguments - use binary folding as fallback
-            self.compile_nary_associative(function, arguments)
+            bytecode.extend(self.compile_nary_associative(function, arguments)?);
         } else {
             // Binary or unary - compile normally
-            self.compile_binary_ffi_call(function, arguments)
+            bytecode.extend(self.compile_binary_ffi_call(function, arguments)?);
         }
+        Ok(bytecode)
+    }
+
+    /// Instructions that throw unless the running actor holds `capability`
+    fn capability_guard(&mut self, function: &str, capability: Capability) -> Vec<OpCode> {
+        let denied = self.get_constant_index(Value::ErrorRecord {
+            code: CAPABILITY_DENIED_CODE,
+            message: format!("{function} requires capability {capability:?}"),
+            payload: Box::new(Value::Nil),
+        });
+        let cap_idx = self.get_constant_index(Value::Capability(capability));
+        vec![
+            OpCode::HasCap(cap_idx),
+            // Missing: skip the jump over the throw
+            OpCode::JmpIfFalse(1),
+            OpCode::Jmp(2),
+            OpCode::GetConst(denied),
+            OpCode::Throw,
+        ]
     }
 
     /// Compile n-ary associative operations using left-fold
@@ -486,25 +1021,23 @@ impl PhysicsWorldCompiler {
             .ok_or_else(|| CompilationError::FfiFunctionNotFound(function.to_string()))?;
 
         // Get capability index (None means no capability required)
-        let cap_idx = match &func.required_capability {
-            Some(capability) => self
-                .ffi_registry
-                .registry
-                .get_capability_index(capability)
-                .unwrap_or(0),
-            None => 0,
-        };
+        let required_capability = func.required_capability.clone();
 
         // Store host_function for later use (avoid borrow in loop)
         let host_function = func.host_function as u16;
 
+        // The VM reads the capability from the constant pool (0 when none is required)
+        let cap_idx = required_capability.map_or(0, |capability| {
+            self.get_constant_index(Value::Capability(capability))
+        });
+
         // Compile first argument
-        bytecode.extend(self.compile_to_physics_with_tail_context(&arguments[0], false)?);
+        self.emit_node(&mut bytecode, &arguments[0])?;
 
         // For each subsequent argument: compile arg, then binary HostCall
         for arg in &arguments[1..] {
             // Compile the next argument
-            bytecode.extend(self.compile_to_physics_with_tail_context(arg, false)?);
+            self.emit_node(&mut bytecode, arg)?;
 
             // Emit binary HostCall (2 arguments)
             bytecode.push(OpCode::HostCall {
@@ -527,7 +1060,7 @@ impl PhysicsWorldCompiler {
 
         // Compile arguments in reverse order (NOT in tail position)
         for arg in arguments.iter().rev() {
-            bytecode.extend(self.compile_to_physics_with_tail_context(arg, false)?);
+            self.emit_node(&mut bytecode, arg)?;
         }
 
         // Look up FFI function
@@ -537,23 +1070,18 @@ impl PhysicsWorldCompiler {
             .find_function(function)
             .ok_or_else(|| CompilationError::FfiFunctionNotFound(function.to_string()))?;
 
-        // Get capability index for this function (None means no capability required)
-        let cap_idx = match &func.required_capability {
-            Some(capability) => self
-                .ffi_registry
-                .registry
-                .get_capability_index(capability)
-                .unwrap_or(0),
-            None => {
-                // No capability required - use a placeholder that will be ignored
-                0
-            }
-        };
+        let required_capability = func.required_capability.clone();
+        let host_function = func.host_function as u16;
+
+        // The VM reads the capability from the constant pool (0 when none is required)
+        let cap_idx = required_capability.map_or(0, |capability| {
+            self.get_constant_index(Value::Capability(capability))
+        });
 
         // Add FFI call instruction (HostCall with capability info)
         bytecode.push(OpCode::HostCall {
             cap_idx,
-            func_id: func.host_function as u16,
+            func_id: host_function,
             args: arguments.len() as u8,
         });
 
@@ -565,8 +1093,15 @@ impl PhysicsWorldCompiler {
         &mut self,
         bytecode: Vec<OpCode>,
     ) -> Result<Vec<OpCode>, CompilationError> {
-        // Analyze the bytecode to find required capabilities
-        let required_caps = self.analyze_capabilities_from_bytecode(&bytecode);
+        // Analyze the bytecode, hoisted lambda bodies included, to find
+        // required capabilities
+        let hoisted: Vec<OpCode> = self
+            .hoisted_bodies
+            .iter()
+            .flat_map(|(_, body)| body.iter().copied())
+            .collect();
+        let mut required_caps = self.analyze_capabilities_from_bytecode(&bytecode);
+        required_caps.extend(self.analyze_capabilities_from_bytecode(&hoisted));
 
         if required_caps.is_empty() {
             // No capabilities required, return original bytecode
@@ -586,6 +1121,9 @@ impl PhysicsWorldCompiler {
         }
 
         // Prepend capability checks to the main bytecode
+        if self.source_locations.len() == bytecode.len() {
+            self.locate_inserted(0, check_bytecode.len());
+        }
         check_bytecode.extend(bytecode);
         Ok(check_bytecode)
     }
@@ -596,6 +1134,7 @@ impl PhysicsWorldCompiler {
         mut bytecode: Vec<OpCode>,
     ) -> Result<Vec<OpCode>, CompilationError> {
         let mut wrapper = Vec::new();
+        let located = self.source_locations.len() == bytecode.len();
 
         // 1. Add resource monitoring initialization
         wrapper.push(OpCode::InitSandbox);
@@ -604,7 +1143,8 @@ impl PhysicsWorldCompiler {
         wrapper.push(OpCode::IsolateCapabilities);
 
         // 3. Add error boundary setup
-        let error_handler_offset = bytecode.len() + 2; // After wrapper setup
+        // Skip the main bytecode and the cleanup to reach the handler
+        let error_handler_offset = bytecode.len() + 1;
         wrapper.push(OpCode::SetErrorHandler(error_handler_offset as i16));
 
         // 4. Add main bytecode
@@ -618,6 +1158,10 @@ impl PhysicsWorldCompiler {
         wrapper.push(OpCode::LogSandboxViolation);
         // Don't add Ret here either - let error handler complete naturally
 
+        if located {
+            self.locate_inserted(0, 3);
+            self.locate_inserted(self.source_locations.len(), 2);
+        }
         Ok(wrapper)
     }
 
@@ -642,7 +1186,82 @@ pub fn compile_to_physics_world(
     ast: &AstNode,
     tier: TrustTier,
 ) -> Result<(Vec<OpCode>, Vec<Value>), CompilationError> {
+    let (bytecode, constants, _) = compile_to_physics_world_with_symbols(ast, tier)?;
+    Ok((bytecode, constants))
+}
+
+/// Compile to Physics-World, also returning the symbol table the bytecode's
+/// `Symbol` opcodes refer to
+pub fn compile_to_physics_world_with_symbols(
+    ast: &AstNode,
+    tier: TrustTier,
+) -> Result<(Vec<OpCode>, Vec<Value>, SymbolTable), CompilationError> {
+    let (bytecode, constants, symbol_table, _, _, _, _) =
+        compile_program(ast, tier, &[], false, &create_standard_ffi_registry())?;
+    Ok((bytecode, constants, symbol_table))
+}
+
+/// Bytecode, constants, symbol table, source map, function table, function
+/// names and literal pairs of a compiled program
+pub type PhysicsProgram = (
+    Vec<OpCode>,
+    Vec<Value>,
+    SymbolTable,
+    SourceMap,
+    HashMap<u16, FunctionInfo>,
+    FunctionNames,
+    Vec<PairConstant>,
+);
+
+/// Compile to Physics-World, also returning the symbol table, a source map
+/// locating every instruction in the source `ast` was parsed from, and the
+/// function table and names described at
+/// [`PhysicsWorldCompiler::function_table`]
+///
+/// The program may read the variables `inputs` without binding them; see
+/// [`PhysicsWorldCompiler::declare_inputs`]. Lambda bodies are hoisted into
+/// the constant pool (see [`PhysicsWorldCompiler::hoist_lambdas`]), so the
+/// bytecode runs as it is and the function ids are constant slots.
+///
+/// # Errors
+///
+/// Fails like [`compile_to_physics_world`].
+pub fn compile_to_physics_world_with_source_map(
+    ast: &AstNode,
+    tier: TrustTier,
+    inputs: &[String],
+) -> Result<PhysicsProgram, CompilationError> {
+    compile_to_physics_world_with_registry(ast, tier, inputs, &create_standard_ffi_registry())
+}
+
+/// Like [`compile_to_physics_world_with_source_map`], resolving FFI calls
+/// against `registry` instead of the standard registry
+///
+/// # Errors
+///
+/// Fails like [`compile_to_physics_world`].
+pub fn compile_to_physics_world_with_registry(
+    ast: &AstNode,
+    tier: TrustTier,
+    inputs: &[String],
+    registry: &FfiRegistry,
+) -> Result<PhysicsProgram, CompilationError> {
+    compile_program(ast, tier, inputs, true, registry)
+}
+
+/// Compile `ast` as a whole program against `registry`, hoisting lambda
+/// bodies into the constant pool if `hoist_lambdas` is set
+fn compile_program(
+    ast: &AstNode,
+    tier: TrustTier,
+    inputs: &[String],
+    hoist_lambdas: bool,
+    registry: &FfiRegistry,
+) -> Result<PhysicsProgram, CompilationError> {
     let mut compiler = PhysicsWorldCompiler::new(tier);
+    compiler.ffi_registry.registry = registry.clone();
+    compiler.hoist_lambdas = hoist_lambdas;
+    compiler.declare_inputs(inputs);
     let mut bytecode = compiler.compile_to_physics(ast)?;
 
     // Add tier-specific processing
@@ -655,12 +1274,51 @@ pub fn compile_to_physics_world(
         }
         _ => {} // Formal/Verified tiers handled by Core-World
     }
+    compiler.store_hoisted_bodies();
+
+    let source_map = compiler.source_map();
+    Ok((
+        bytecode,
+        compiler.string_pool,
+        compiler.symbol_table,
+        source_map,
+        compiler.function_table,
+        compiler.function_names,
+        compiler.pair_constants,
+    ))
+}
+
+/// Replace a store immediately followed by a reload of the same slot with
+/// `Dup` + `SetLocal`, keeping the instruction count (and jump offsets) unchanged
+fn fuse_store_reload(bindings: &mut [OpCode], body: &mut [OpCode]) {
+    if let (Some(&OpCode::SetLocal(stored)), Some(&OpCode::GetLocal(loaded))) =
+        (bindings.last(), body.first())
+    {
+        if stored == loaded {
+            let last = bindings.len() - 1;
+            bindings[last] = OpCode::Dup;
+            body[0] = OpCode::SetLocal(stored);
+        }
+    }
+}
+
+/// Whether `node` is a literal, or a pair or list built only from literals
+fn is_literal_data(node: &AstNode) -> bool {
+    match node {
+        AstNode::Literal(_) => true,
+        AstNode::Cons { car, cdr, .. } => is_literal_data(car) && is_literal_data(cdr),
+        AstNode::List { elements, .. } => elements.iter().all(is_literal_data),
+        _ => false,
+    }
+}
 
-    // Extract string constants from compiler's string pool
-    let string_constants: Vec<Value> = compiler
-        .string_pool
-        .into_iter()
-        .map(|s| Value::String(s))
-        .collect();
-    Ok((bytecode, string_constants))
+/// Structural equality of constants
+///
+/// Like `==`, except floats compare by bit pattern: a NaN constant can share
+/// a slot with an identical NaN, while `0.0` and `-0.0` stay distinct.
+fn constants_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Float(x), Value::Float(y)) => x.to_bits() == y.to_bits(),
+        _ => a == b,
+    }
 }
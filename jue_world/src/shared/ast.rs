@@ -29,6 +29,8 @@ pub enum AstNode {
     Lambda {
         /// Parameter names
         parameters: Vec<String>,
+        /// Rest parameter, bound to a list of any arguments past `parameters`
+        rest: Option<String>,
         /// Function body
         body: Box<AstNode>,
         /// Source location for error reporting
@@ -161,6 +163,16 @@ pub enum AstNode {
         location: SourceLocation,
     },
 
+    /// Sequential let binding (let* ((name value) ...)) where each value sees the names before it
+    LetStar {
+        /// Variable bindings (name, value pairs) - each name is visible in later values
+        bindings: Vec<(String, AstNode)>,
+        /// Body expression
+        body: Box<AstNode>,
+        /// Source location for error reporting
+        location: SourceLocation,
+    },
+
     /// Recursive let binding (letrec ((name value) ...)) where names are visible in values
     Letrec {
         /// Variable bindings (name, value pairs) - names are visible in all values
@@ -217,6 +229,33 @@ pub enum Type {
     Variable(String),
 }
 
+impl AstNode {
+    /// Source location of this node; literals, symbols and variables carry
+    /// none and are located by the form containing them
+    #[must_use]
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            AstNode::Literal(_) | AstNode::Symbol(_) | AstNode::Variable(_) => None,
+            AstNode::Call { location, .. }
+            | AstNode::Lambda { location, .. }
+            | AstNode::Let { location, .. }
+            | AstNode::If { location, .. }
+            | AstNode::TrustTier { location, .. }
+            | AstNode::RequireCapability { location, .. }
+            | AstNode::HasCapability { location, .. }
+            | AstNode::TypeSignature { location, .. }
+            | AstNode::MacroDefinition { location, .. }
+            | AstNode::MacroExpansion { location, .. }
+            | AstNode::FfiCall { location, .. }
+            | AstNode::List { location, .. }
+            | AstNode::Cons { location, .. }
+            | AstNode::Define { location, .. }
+            | AstNode::LetStar { location, .. }
+            | AstNode::Letrec { location, .. } => Some(location),
+        }
+    }
+}
+
 impl fmt::Display for AstNode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -235,7 +274,10 @@ impl fmt::Display for AstNode {
                 write!(f, ")")
             }
             AstNode::Lambda {
-                parameters, body, ..
+                parameters,
+                rest,
+                body,
+                ..
             } => {
                 write!(f, "(lambda (")?;
                 for (i, param) in parameters.iter().enumerate() {
@@ -244,6 +286,12 @@ impl fmt::Display for AstNode {
                     }
                     write!(f, "{}", param)?;
                 }
+                if let Some(rest) = rest {
+                    if !parameters.is_empty() {
+                        write!(f, " ")?;
+                    }
+                    write!(f, ". {rest}")?;
+                }
                 write!(f, ") {})", body)
             }
             AstNode::Let { bindings, body, .. } => {
@@ -332,6 +380,16 @@ impl fmt::Display for AstNode {
             AstNode::Define { name, value, .. } => {
                 write!(f, "(define {} {})", name, value)
             }
+            AstNode::LetStar { bindings, body, .. } => {
+                write!(f, "(let* (")?;
+                for (i, (name, value)) in bindings.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "({name} {value})")?;
+                }
+                write!(f, ") {body})")
+            }
             AstNode::Letrec { bindings, body, .. } => {
                 write!(f, "(letrec (")?;
                 for (i, (name, value)) in bindings.iter().enumerate() {
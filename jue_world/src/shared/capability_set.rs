@@ -0,0 +1,163 @@
+use physics_world::types::Capability;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Set of capabilities for Jue-World V2.0
+///
+/// Capabilities are kept sorted and deduplicated, so membership is a binary
+/// search and subset/union/difference are linear merges. The set serializes
+/// as a sorted list, which is the same shape as the `Vec<Capability>` it
+/// replaces; duplicates in serialized input collapse on deserialization.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "Vec<Capability>", into = "Vec<Capability>")]
+pub struct CapabilitySet {
+    capabilities: Vec<Capability>,
+}
+
+impl CapabilitySet {
+    /// Create an empty capability set
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a capability; returns whether it was newly added
+    pub fn insert(&mut self, capability: Capability) -> bool {
+        match self.capabilities.binary_search(&capability) {
+            Ok(_) => false,
+            Err(index) => {
+                self.capabilities.insert(index, capability);
+                true
+            }
+        }
+    }
+
+    /// Remove a capability; returns whether it was present
+    pub fn remove(&mut self, capability: &Capability) -> bool {
+        match self.capabilities.binary_search(capability) {
+            Ok(index) => {
+                self.capabilities.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Check whether the set holds `capability`
+    #[must_use]
+    pub fn contains(&self, capability: &Capability) -> bool {
+        self.capabilities.binary_search(capability).is_ok()
+    }
+
+    /// Check whether every capability in this set is also in `other`
+    #[must_use]
+    pub fn is_subset_of(&self, other: &CapabilitySet) -> bool {
+        let mut theirs = other.capabilities.iter().peekable();
+        self.capabilities.iter().all(|cap| {
+            while theirs.next_if(|other_cap| *other_cap < cap).is_some() {}
+            theirs.next_if_eq(&cap).is_some()
+        })
+    }
+
+    /// Check whether this set holds every capability in `other`
+    #[must_use]
+    pub fn is_superset_of(&self, other: &CapabilitySet) -> bool {
+        other.is_subset_of(self)
+    }
+
+    /// Capabilities in either set
+    #[must_use]
+    pub fn union(&self, other: &CapabilitySet) -> CapabilitySet {
+        self.iter().chain(other.iter()).cloned().collect()
+    }
+
+    /// Capabilities in this set but not in `other`, in sorted order
+    #[must_use]
+    pub fn difference(&self, other: &CapabilitySet) -> CapabilitySet {
+        CapabilitySet {
+            capabilities: self
+                .capabilities
+                .iter()
+                .filter(|cap| !other.contains(cap))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Iterate over the capabilities in sorted order
+    pub fn iter(&self) -> std::slice::Iter<'_, Capability> {
+        self.capabilities.iter()
+    }
+
+    /// The capabilities as a sorted slice
+    #[must_use]
+    pub fn as_slice(&self) -> &[Capability] {
+        &self.capabilities
+    }
+
+    /// Number of capabilities in the set
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.capabilities.len()
+    }
+
+    /// Check whether the set is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.capabilities.is_empty()
+    }
+}
+
+impl FromIterator<Capability> for CapabilitySet {
+    fn from_iter<I: IntoIterator<Item = Capability>>(iter: I) -> Self {
+        let mut capabilities: Vec<Capability> = iter.into_iter().collect();
+        capabilities.sort();
+        capabilities.dedup();
+        Self { capabilities }
+    }
+}
+
+impl Extend<Capability> for CapabilitySet {
+    fn extend<I: IntoIterator<Item = Capability>>(&mut self, iter: I) {
+        self.capabilities.extend(iter);
+        self.capabilities.sort();
+        self.capabilities.dedup();
+    }
+}
+
+impl From<Vec<Capability>> for CapabilitySet {
+    fn from(capabilities: Vec<Capability>) -> Self {
+        capabilities.into_iter().collect()
+    }
+}
+
+impl From<CapabilitySet> for Vec<Capability> {
+    fn from(set: CapabilitySet) -> Self {
+        set.capabilities
+    }
+}
+
+impl IntoIterator for CapabilitySet {
+    type Item = Capability;
+    type IntoIter = std::vec::IntoIter<Capability>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.capabilities.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a CapabilitySet {
+    type Item = &'a Capability;
+    type IntoIter = std::slice::Iter<'a, Capability>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.capabilities.iter()
+    }
+}
+
+impl fmt::Display for CapabilitySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self.iter().map(ToString::to_string).collect();
+        write!(f, "{{{}}}", names.join(", "))
+    }
+}
@@ -1,3 +1,4 @@
+use crate::resource_limits::Resource;
 use crate::trust_tier::TrustTier;
 use physics_world::types::Capability;
 use serde::{Deserialize, Serialize};
@@ -13,6 +14,9 @@ pub struct SourceLocation {
     pub column: usize,
     /// Character offset in source
     pub offset: usize,
+    /// Macro call this node was produced by, when it comes from a macro body
+    #[serde(default)]
+    pub expanded_from: Option<Box<MacroExpansionSite>>,
 }
 
 impl Default for SourceLocation {
@@ -21,10 +25,37 @@ impl Default for SourceLocation {
             line: 0,
             column: 0,
             offset: 0,
+            expanded_from: None,
         }
     }
 }
 
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.expanded_from {
+            Some(site) => write!(
+                f,
+                "in macro `{}` defined at {}:{}, expanded at {}",
+                site.macro_name, self.line, self.column, site.call_site
+            ),
+            None => write!(f, "{}:{}", self.line, self.column),
+        }
+    }
+}
+
+/// The macro call a node was expanded from
+///
+/// A node copied out of a macro body keeps its own location, which points
+/// into the macro definition, and records the call here, so errors can tell
+/// a bug in the macro from a bad call.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MacroExpansionSite {
+    /// Name of the expanded macro
+    pub macro_name: String,
+    /// Location of the macro call
+    pub call_site: SourceLocation,
+}
+
 /// Capability violation error
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CapabilityViolation {
@@ -102,7 +133,7 @@ impl fmt::Display for ParserError {
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum CompilationError {
     /// Parse error with message and location
-    #[error("Parse error at {location:?}: {message}")]
+    #[error("Parse error at {location}: {message}")]
     ParseError {
         /// Error message
         message: String,
@@ -134,10 +165,54 @@ pub enum CompilationError {
     #[error("Macro expansion error: {0}")]
     MacroExpansionError(String),
 
+    /// Macro called with the wrong number of arguments
+    #[error("Macro {macro_name} at {location} expects {expected} arguments but got {got}")]
+    MacroArityMismatch {
+        /// Name of the macro being expanded
+        macro_name: String,
+        /// Number of parameters in the macro definition
+        expected: usize,
+        /// Number of arguments at the call site
+        got: usize,
+        /// Source location of the call
+        location: SourceLocation,
+    },
+
+    /// FFI function called with other than its declared number of arguments
+    #[error("FFI function {function} at {location} expects {expected} arguments but got {got}")]
+    FfiArityMismatch {
+        /// Name of the FFI function being called
+        function: String,
+        /// Number of parameters the function was registered with
+        expected: usize,
+        /// Number of arguments at the call site
+        got: usize,
+        /// Source location of the call
+        location: SourceLocation,
+    },
+
+    /// A capability is used by an FFI call but never declared with `require-capability`
+    #[error("Capability {cap:?} used at {location:?} without a require-capability declaration")]
+    UndeclaredCapability {
+        /// The capability the call needs
+        cap: Capability,
+        /// Source location of the first call that needs it
+        location: SourceLocation,
+    },
+
     /// Comptime execution error
     #[error("Comptime execution error: {0}")]
     ComptimeError(String),
 
+    /// Comptime execution ran out of its step or memory budget
+    #[error("Comptime execution exhausted its {resource} budget of {limit}")]
+    ResourceExhausted {
+        /// The resource that ran out
+        resource: Resource,
+        /// The budget that was exceeded
+        limit: u64,
+    },
+
     /// FFI error
     #[error("FFI error: {0}")]
     FfiError(String),
@@ -153,6 +228,33 @@ pub enum CompilationError {
     /// FFI function not found error
     #[error("FFI function not found: {0}")]
     FfiFunctionNotFound(String),
+
+    /// A compiled artifact could not be written, read, or used for the source
+    #[error("Artifact error: {0}")]
+    ArtifactError(String),
+}
+
+/// Non-fatal diagnostics produced during compilation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CompilationWarning {
+    /// A capability was requested with `require-capability` but no FFI call uses it
+    UnusedCapability(Capability),
+    /// A self-recursive function calls itself without changing any argument
+    PossiblyNonTerminating(crate::analysis::TerminationWarning),
+}
+
+impl fmt::Display for CompilationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompilationWarning::UnusedCapability(cap) => {
+                write!(
+                    f,
+                    "Capability {cap:?} is required but never used by any FFI call"
+                )
+            }
+            CompilationWarning::PossiblyNonTerminating(warning) => write!(f, "{warning}"),
+        }
+    }
 }
 
 /// Source map for debugging information
@@ -186,6 +288,18 @@ impl SourceMap {
             .map(|(_, location)| location)
     }
 
+    /// Location of the instruction at `ip`: the mapping at the greatest
+    /// offset not past `ip`, since a mapping covers the instructions up to
+    /// the next one
+    #[must_use]
+    pub fn location_for(&self, ip: usize) -> Option<SourceLocation> {
+        self.bytecode_to_source
+            .iter()
+            .filter(|(offset, _)| *offset <= ip)
+            .max_by_key(|(offset, _)| *offset)
+            .map(|(_, location)| location.clone())
+    }
+
     /// Find bytecode offset for a source location
     pub fn find_bytecode_offset(&self, source_location: &SourceLocation) -> Option<&usize> {
         self.source_to_bytecode
@@ -193,4 +307,17 @@ impl SourceMap {
             .find(|(location, _)| *location == *source_location)
             .map(|(_, offset)| offset)
     }
+
+    /// Convert into the Physics-World source map a VM uses to locate runtime errors
+    #[must_use]
+    pub fn to_vm_source_map(&self) -> physics_world::vm::SourceMap {
+        let mut vm_map = physics_world::vm::SourceMap::new();
+        for (offset, location) in &self.bytecode_to_source {
+            vm_map.add_mapping(
+                *offset,
+                physics_world::vm::SourceLocation::new(location.line, location.column),
+            );
+        }
+        vm_map
+    }
 }
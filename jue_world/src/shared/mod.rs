@@ -1,4 +1,6 @@
 pub mod ast;
+/// Sorted, deduplicated capability sets
+pub mod capability_set;
 pub mod error;
 pub mod resource_limits;
 pub mod source_location;
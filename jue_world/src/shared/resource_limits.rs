@@ -1,6 +1,7 @@
 use crate::error::CompilationError;
 use physics_world::types::{OpCode, Value};
 use physics_world::vm::state::{VmError, VmState};
+use std::fmt;
 
 /// Resource limit configuration for Jue-World execution
 #[derive(Debug, Clone)]
@@ -15,6 +16,35 @@ pub struct ResourceLimits {
     pub heap_allocation_limit: usize,
 }
 
+impl Default for ResourceLimits {
+    fn default() -> Self {
+        Self {
+            step_limit: 1000,
+            memory_limit: 1024 * 1024, // 1MB
+            call_stack_limit: 100,
+            heap_allocation_limit: 1000,
+        }
+    }
+}
+
+/// A resource whose budget can run out during execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// Execution steps
+    Steps,
+    /// Memory in bytes
+    Memory,
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Resource::Steps => write!(f, "step"),
+            Resource::Memory => write!(f, "memory"),
+        }
+    }
+}
+
 /// Resource limit enforcer that works with Physics-World VM
 pub struct ResourceLimitEnforcer {
     limits: ResourceLimits,
@@ -96,10 +126,15 @@ impl ResourceLimitEnforcer {
                 Value::Symbol(_) => 4,
                 Value::Pair(_) => 8,
                 Value::Closure(_) => 16,
+                Value::Vector(_) => 8,
+                Value::BigInt(_) => 8,
                 Value::ActorId(_) => 4,
                 Value::Capability(_) => 8,
                 &Value::GcPtr(_) => 4,
                 &Value::Error(_) => 8, // Error values stored as strings
+                Value::Bytes(_) => 8,  // Byte storage (pointer + length)
+                Value::Thunk(_) => 4,
+                Value::ErrorRecord { .. } => 24, // Code, message and boxed payload
             };
         }
 
@@ -143,12 +178,7 @@ impl ResourceLimitBuilder {
     /// Create a new resource limit builder with default settings
     pub fn new() -> Self {
         Self {
-            limits: ResourceLimits {
-                step_limit: 1000,
-                memory_limit: 1024 * 1024, // 1MB
-                call_stack_limit: 100,
-                heap_allocation_limit: 1000,
-            },
+            limits: ResourceLimits::default(),
         }
     }
 
@@ -16,6 +16,9 @@ pub struct StructuredError {
     pub message: String,
     /// Source location where error occurred
     pub location: SourceLocation,
+    /// Number of characters covered starting at `location` (0 marks a single point)
+    #[serde(default)]
+    pub span_length: usize,
     /// Context information about what was being processed
     pub context: ErrorContext,
     /// Suggested recovery actions
@@ -132,6 +135,7 @@ impl StructuredErrorBuilder {
                 severity: ErrorSeverity::Error,
                 message,
                 location: SourceLocation::default(),
+                span_length: 0,
                 context: ErrorContext {
                     phase: CompilationPhase::Parsing,
                     trust_tier: TrustTier::Formal,
@@ -160,6 +164,13 @@ impl StructuredErrorBuilder {
         self
     }
 
+    /// Set the number of characters the error covers
+    #[must_use]
+    pub fn with_span_length(mut self, span_length: usize) -> Self {
+        self.error.span_length = span_length;
+        self
+    }
+
     /// Set compilation phase
     pub fn with_phase(mut self, phase: CompilationPhase) -> Self {
         self.error.context.phase = phase;
@@ -370,6 +381,48 @@ impl StructuredErrorHandler {
     }
 }
 
+impl StructuredError {
+    /// Render a rustc-style report pointing at the error's span in `source`.
+    ///
+    /// The span is underlined with `^` on the line below the offending
+    /// source line; it is clipped to the end of that line and always covers
+    /// at least one character. The snippet is omitted when the location does
+    /// not fall inside `source`.
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        let severity = self.severity.to_string().to_lowercase();
+        let line = self.location.line;
+        let column = self.location.column;
+
+        let mut report = vec![format!("{severity}[{}]: {}", self.error_code, self.message)];
+        let Some(text) = line.checked_sub(1).and_then(|i| source.lines().nth(i)) else {
+            report.push(format!(" --> {line}:{column}"));
+            return report.join("\n") + "\n";
+        };
+
+        let gutter = " ".repeat(line.to_string().len());
+        report.push(format!("{gutter}--> {line}:{column}"));
+        report.push(format!("{gutter} |"));
+        report.push(format!("{line} | {text}"));
+
+        // Keep tabs in the padding so the carets line up with the source
+        let start = column.saturating_sub(1);
+        let padding: String = text
+            .chars()
+            .take(start)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let remaining = text.chars().count().saturating_sub(start);
+        let width = self.span_length.min(remaining).max(1);
+        report.push(format!("{gutter} | {padding}{}", "^".repeat(width)));
+
+        for suggestion in &self.recovery_suggestions {
+            report.push(format!("{gutter} = help: {suggestion}"));
+        }
+        report.join("\n") + "\n"
+    }
+}
+
 impl Error for StructuredError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         None
@@ -1,3 +1,4 @@
+use crate::shared::capability_set::CapabilitySet;
 use physics_world::types::Capability;
 use std::collections::HashSet;
 
@@ -64,12 +65,33 @@ impl TrustTier {
                 caps.insert(Capability::IoPersist);
                 caps.insert(Capability::SysCreateActor);
                 caps.insert(Capability::SysClock);
+                caps.insert(Capability::SysGc);
                 // Note: MetaGrant and SysTerminateActor are NOT granted
                 caps
             }
         }
     }
 
+    /// Get the capabilities granted for this trust tier as a sorted set
+    #[must_use]
+    pub fn capability_set(&self) -> CapabilitySet {
+        self.granted_capabilities().into_iter().collect()
+    }
+
+    /// Default `(step_limit, memory_limit)` for code compiled at this tier
+    ///
+    /// Less-trusted tiers get tighter budgets: `Experimental` is the
+    /// strictest and `Formal` the most generous.
+    #[must_use]
+    pub fn default_limits(&self) -> (u64, usize) {
+        match self {
+            TrustTier::Formal => (10_000_000, 64 * 1024 * 1024),
+            TrustTier::Verified => (1_000_000, 16 * 1024 * 1024),
+            TrustTier::Empirical => (100_000, 4 * 1024 * 1024),
+            TrustTier::Experimental => (10_000, 1024 * 1024),
+        }
+    }
+
     /// Check if this tier allows the given capability
     pub fn allows_capability(&self, capability: &Capability) -> bool {
         let granted = self.granted_capabilities();
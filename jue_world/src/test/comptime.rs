@@ -190,4 +190,99 @@ mod tests {
         assert!(matches!(result.value, Value::Int(8)));
         assert_eq!(result.steps_used, 3);
     }
+
+    #[test]
+    fn test_pure_macro_evaluated_once_for_equal_arguments() {
+        use crate::ast::{AstNode, Literal};
+        use std::cell::Cell;
+
+        let mut executor = ComptimeExecutor::new(TrustTier::Empirical, 1000, 1024);
+        let evaluations = Cell::new(0);
+        let double = |args: &[AstNode]| {
+            evaluations.set(evaluations.get() + 1);
+            match args {
+                [AstNode::Literal(Literal::Int(n))] => {
+                    Ok(vec![OpCode::Int(*n), OpCode::Int(2), OpCode::Mul])
+                }
+                _ => Err(CompilationError::ComptimeError("bad argument".to_string())),
+            }
+        };
+        let args = vec![AstNode::Literal(Literal::Int(21))];
+
+        let first = executor.execute_macro("double", &args, double).unwrap();
+        let steps = executor.env.step_count;
+        let second = executor.execute_macro("double", &args, double).unwrap();
+        assert_eq!(first.value, Value::Int(42));
+        assert_eq!(second.value, Value::Int(42));
+        // The second call compiled the body but did not execute it, and was
+        // still charged the steps the first one took
+        assert_eq!(evaluations.get(), 2);
+        assert_eq!(executor.env.step_count, 2 * steps);
+
+        // Different arguments or a different macro name are separate entries
+        let other = vec![AstNode::Literal(Literal::Int(5))];
+        let third = executor.execute_macro("double", &other, double).unwrap();
+        assert_eq!(third.value, Value::Int(10));
+        executor.execute_macro("twice", &args, double).unwrap();
+        assert_eq!(executor.env.cache.len(), 3);
+    }
+
+    #[test]
+    fn test_macro_cache_misses_when_other_inputs_change() {
+        use crate::ast::{AstNode, Literal};
+
+        let args = vec![AstNode::Literal(Literal::Int(21))];
+        let double = |_: &[AstNode]| Ok(vec![OpCode::Int(21), OpCode::Int(2), OpCode::Mul]);
+        let triple = |_: &[AstNode]| Ok(vec![OpCode::Int(21), OpCode::Int(3), OpCode::Mul]);
+
+        // A redefined macro body under the same name is not served from the cache
+        let mut executor = ComptimeExecutor::new(TrustTier::Empirical, 1000, 1024);
+        executor.execute_macro("scale", &args, double).unwrap();
+        let result = executor.execute_macro("scale", &args, triple).unwrap();
+        assert_eq!(result.value, Value::Int(63));
+        assert_eq!(executor.env.cache.len(), 2);
+
+        // Neither is a body reading a changed constants pool
+        let load = |_: &[AstNode]| Ok(vec![OpCode::GetConst(0)]);
+        executor.constants = vec![Value::Int(1)];
+        executor.execute_macro("load", &args, load).unwrap();
+        executor.constants = vec![Value::Int(2)];
+        let result = executor.execute_macro("load", &args, load).unwrap();
+        assert_eq!(result.value, Value::Int(2));
+
+        // Changed capabilities or limits are a miss
+        executor.env.capabilities.clear();
+        executor.execute_macro("scale", &args, double).unwrap();
+        executor.env.max_steps += 1;
+        executor.execute_macro("scale", &args, double).unwrap();
+        assert_eq!(executor.env.cache.len(), 6);
+
+        // A cached result that no longer fits the budget reports the limit
+        executor.env.step_count = executor.env.max_steps - 1;
+        assert!(executor.execute_macro("scale", &args, double).is_err());
+    }
+
+    #[test]
+    fn test_macro_with_capability_effects_is_not_cached() {
+        use crate::comptime::is_pure_comptime;
+        use crate::sandboxed_comptime::SandboxedComptimeExecutor;
+        use std::cell::Cell;
+
+        let check = vec![OpCode::HasCap(0)];
+        assert!(!is_pure_comptime(&check));
+        assert!(is_pure_comptime(&[OpCode::Int(1)]));
+
+        let mut executor = SandboxedComptimeExecutor::new(TrustTier::Empirical, 1000, 1024);
+        executor.constants = vec![Value::Capability(Capability::MacroHygienic)];
+        let evaluations = Cell::new(0);
+        let has_cap = |_: &[crate::ast::AstNode]| {
+            evaluations.set(evaluations.get() + 1);
+            Ok(check.clone())
+        };
+
+        executor.execute_macro("has-cap", &[], has_cap).unwrap();
+        executor.execute_macro("has-cap", &[], has_cap).unwrap();
+        assert_eq!(evaluations.get(), 2);
+        assert!(executor.env.cache.is_empty());
+    }
 }
@@ -12,6 +12,7 @@ mod tests {
             line: 42,
             column: 8,
             offset: 337,
+            expanded_from: None,
         };
 
         assert_eq!(loc.line, 42);
@@ -28,6 +29,7 @@ mod tests {
                 line: 10,
                 column: 5,
                 offset: 100,
+                expanded_from: None,
             },
             suggestion: "Consider using :empirical tier".to_string(),
         };
@@ -49,6 +51,7 @@ mod tests {
                 line: 20,
                 column: 15,
                 offset: 200,
+                expanded_from: None,
             },
         };
 
@@ -67,12 +70,14 @@ mod tests {
             line: 1,
             column: 1,
             offset: 0,
+            expanded_from: None,
         };
 
         let loc2 = SourceLocation {
             line: 2,
             column: 5,
             offset: 10,
+            expanded_from: None,
         };
 
         source_map.add_mapping(0, loc1.clone());
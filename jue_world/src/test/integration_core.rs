@@ -68,6 +68,7 @@ mod tests {
 
         let lambda = AstNode::Lambda {
             parameters: vec!["x".to_string()],
+            rest: None,
             body: Box::new(AstNode::Variable("x".to_string())),
             location: SourceLocation::default(),
         };
@@ -87,6 +88,7 @@ mod tests {
 
         let lambda = AstNode::Lambda {
             parameters: vec!["x".to_string(), "y".to_string()],
+            rest: None,
             body: Box::new(AstNode::Call {
                 function: Box::new(AstNode::Variable("+".to_string())),
                 arguments: vec![
@@ -7,6 +7,7 @@ fn test_structured_error_creation() {
         line: 10,
         column: 5,
         offset: 50,
+        expanded_from: None,
     };
 
     let error = StructuredErrorBuilder::new(
@@ -65,6 +66,7 @@ fn test_error_reporting() {
             line: 42,
             column: 10,
             offset: 420,
+            expanded_from: None,
         },
         "Upgrade trust tier",
     );
@@ -111,6 +113,7 @@ fn test_error_display() {
             line: 1,
             column: 1,
             offset: 0,
+            expanded_from: None,
         })
         .with_recovery_suggestion("Check your types")
         .build();
@@ -1,6 +1,7 @@
 /// Token definitions for Jue-World V2.0
 ///
 /// This module defines the token types used by the lexer and parser.
+use std::ops::Range;
 
 /// Source location for tokens
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -84,6 +85,8 @@ pub enum Token {
     String(String),
     /// Number literal
     Number(f64),
+    /// Number literal written with a decimal point, e.g. `5.0`
+    Float(f64),
     /// Boolean literal
     Boolean(bool),
     /// Nil literal
@@ -92,6 +95,11 @@ pub enum Token {
     Eof,
     /// Unknown token
     Unknown(char),
+    /// Comment, only emitted when the tokenizer preserves comments
+    Comment {
+        /// Byte range of the comment in the source, delimiters included
+        span: Range<usize>,
+    },
 }
 
 impl Token {
@@ -39,9 +39,9 @@ fn test_physics_world_todo_features_implemented() {
     let mut compiler = PhysicsWorldCompiler::new(TrustTier::Formal);
     let bytecode = compiler.compile_to_physics(&ast).unwrap();
 
-    // Should contain SetLocal and GetLocal operations
+    // Should store the binding, duplicating it instead of reloading it
     assert!(bytecode.contains(&OpCode::SetLocal(0)));
-    assert!(bytecode.contains(&OpCode::GetLocal(0)));
+    assert!(bytecode.contains(&OpCode::Dup));
 
     // Test 4: VM handles new OpCodes
     let bytecode = vec![OpCode::Float(2.5), OpCode::Float(1.5), OpCode::FAdd];
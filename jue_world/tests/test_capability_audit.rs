@@ -0,0 +1,27 @@
+/// Test that capability audit entries are recorded for every trust tier
+use jue_world::compiler::capability_checking::CheckType;
+use jue_world::core_compiler::compile;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::Capability;
+
+#[test]
+fn test_formal_program_audits_capability_with_proof_check() {
+    let source = "(has-capability? MacroHygienic)";
+
+    let result = compile(source, TrustTier::Formal, 1000, 1024).unwrap();
+
+    assert!(!result.capability_audit.is_empty());
+    assert!(result.capability_audit.iter().all(|check| {
+        check.capability == Capability::MacroHygienic && check.check_type == CheckType::Proof
+    }));
+}
+
+#[test]
+fn test_empirical_program_audits_capability_with_runtime_check() {
+    let source = "(has-capability? IoReadSensor)";
+
+    let result = compile(source, TrustTier::Empirical, 1000, 1024).unwrap();
+
+    assert_eq!(result.capability_audit.len(), 1);
+    assert_eq!(result.capability_audit[0].check_type, CheckType::Runtime);
+}
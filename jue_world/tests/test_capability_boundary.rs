@@ -0,0 +1,61 @@
+/// Test proofs of the pure fragment around capability operations
+use core_world::core_expr::{app, lam, nat, var, CoreExpr};
+use core_world::core_kernel::alpha_equiv;
+use core_world::proof_checker::{verify, ProofError};
+use jue_world::core_compilation::{prove_capability_boundary, CapabilityPoint};
+use physics_world::types::Capability;
+
+/// Church addition: λm.λn.λf.λz. m f (n f z)
+fn plus() -> CoreExpr {
+    lam(lam(lam(lam(app(
+        app(var(3), var(1)),
+        app(app(var(2), var(1)), var(0)),
+    )))))
+}
+
+/// `(double (read-sensor 0))`, with `read-sensor` the free variable 0
+fn read_then_double() -> CoreExpr {
+    let double = lam(app(app(plus(), var(0)), var(0)));
+    let read_sensor = app(var(0), nat(0));
+    app(double, read_sensor)
+}
+
+fn sensor_point() -> CapabilityPoint {
+    CapabilityPoint::new(vec![1], Capability::IoReadSensor)
+}
+
+#[test]
+fn test_sensor_read_is_opaque_and_doubling_is_verified() {
+    let proof = prove_capability_boundary(&read_then_double(), &[sensor_point()]).unwrap();
+    let (lhs, rhs) = verify(&proof).unwrap();
+
+    // The read became free variable 1, the first index not already free
+    let double = lam(app(app(plus(), var(0)), var(0)));
+    assert!(alpha_equiv(lhs, app(double, var(1))));
+
+    // x + x for an unknown x: λf.λz. x f (x f z)
+    let doubled = lam(lam(app(
+        app(var(3), var(1)),
+        app(app(var(3), var(1)), var(0)),
+    )));
+    assert!(alpha_equiv(rhs, doubled));
+}
+
+#[test]
+fn test_without_capability_points_proves_the_whole_term() {
+    let term = app(lam(var(0)), nat(7));
+    let proof = prove_capability_boundary(&term, &[]).unwrap();
+    assert_eq!(verify(&proof).unwrap().1, nat(7));
+}
+
+#[test]
+fn test_invalid_capability_point_is_rejected() {
+    let point = CapabilityPoint::new(vec![1, 1, 0], Capability::IoReadSensor);
+    assert!(matches!(
+        prove_capability_boundary(&read_then_double(), &[point]),
+        Err(ProofError::StepFailed { path, .. }) if path == vec![1, 1, 0]
+    ));
+
+    let nested = CapabilityPoint::new(vec![1, 0], Capability::IoReadSensor);
+    assert!(prove_capability_boundary(&read_then_double(), &[sensor_point(), nested]).is_err());
+}
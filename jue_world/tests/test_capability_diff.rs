@@ -0,0 +1,27 @@
+/// Test capability requirement diffing between two versions of a program
+use jue_world::core_compilation::capability_analysis::{analyze_capabilities, diff};
+use jue_world::parser::parse;
+use physics_world::types::Capability;
+
+fn required(source: &str) -> jue_world::capability_set::CapabilitySet {
+    analyze_capabilities(&parse(source).unwrap()).unwrap()
+}
+
+#[test]
+fn test_edit_reports_added_and_removed_capabilities() {
+    let old = required("(let ((reading (read-sensor))) reading)");
+    let new = required("(let ((reading 42)) (network-send \"hello\"))");
+
+    let changes = diff(&old, &new);
+
+    assert_eq!(changes.added.as_slice(), &[Capability::IoNetwork]);
+    assert_eq!(changes.removed.as_slice(), &[Capability::IoReadSensor]);
+}
+
+#[test]
+fn test_unchanged_requirements_produce_empty_diff() {
+    let old = required("(read-sensor)");
+    let new = required("(let ((x (read-sensor))) x)");
+
+    assert!(diff(&old, &new).is_empty());
+}
@@ -0,0 +1,84 @@
+/// Test the sorted, deduplicated capability set
+use jue_world::capability_set::CapabilitySet;
+use jue_world::core_compiler::compile;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::Capability;
+
+fn set(caps: &[Capability]) -> CapabilitySet {
+    caps.iter().cloned().collect()
+}
+
+#[test]
+fn test_duplicates_collapse() {
+    let caps = set(&[
+        Capability::IoReadSensor,
+        Capability::MacroHygienic,
+        Capability::IoReadSensor,
+        Capability::MacroHygienic,
+    ]);
+    assert_eq!(caps.len(), 2);
+    assert_eq!(
+        caps.as_slice(),
+        &[Capability::MacroHygienic, Capability::IoReadSensor]
+    );
+
+    let mut caps = caps;
+    assert!(!caps.insert(Capability::IoReadSensor));
+    assert!(caps.insert(Capability::SysClock));
+    assert_eq!(caps.len(), 3);
+}
+
+#[test]
+fn test_subset_and_superset() {
+    let empirical = TrustTier::Empirical.capability_set();
+    let formal = TrustTier::Formal.capability_set();
+    let network = set(&[Capability::IoNetwork]);
+
+    assert!(formal.is_subset_of(&empirical));
+    assert!(empirical.is_superset_of(&formal));
+    assert!(!empirical.is_subset_of(&formal));
+    assert!(!network.is_subset_of(&empirical));
+    assert!(CapabilitySet::new().is_subset_of(&formal));
+    assert!(empirical.is_subset_of(&empirical));
+}
+
+#[test]
+fn test_union_and_difference() {
+    let a = set(&[Capability::IoReadSensor, Capability::SysClock]);
+    let b = set(&[Capability::SysClock, Capability::IoNetwork]);
+
+    assert_eq!(
+        a.union(&b),
+        set(&[
+            Capability::IoReadSensor,
+            Capability::IoNetwork,
+            Capability::SysClock
+        ])
+    );
+    assert_eq!(a.difference(&b), set(&[Capability::IoReadSensor]));
+    assert!(a.difference(&a).is_empty());
+}
+
+#[test]
+fn test_serializes_as_sorted_list() {
+    let caps = set(&[Capability::SysClock, Capability::MacroHygienic]);
+    let json = serde_json::to_string(&caps).unwrap();
+    assert_eq!(json, r#"["MacroHygienic","SysClock"]"#);
+
+    // Lists written before the set existed may be unsorted or repeat entries
+    let old: CapabilitySet =
+        serde_json::from_str(r#"["SysClock","MacroHygienic","SysClock"]"#).unwrap();
+    assert_eq!(old, caps);
+}
+
+#[test]
+fn test_tier_check_uses_capability_set() {
+    let result = compile("(if true 1 2)", TrustTier::Empirical, 1000, 1024).unwrap();
+    assert_eq!(
+        result.granted_capabilities,
+        TrustTier::Empirical.capability_set()
+    );
+    assert!(result
+        .required_capabilities
+        .is_subset_of(&result.granted_capabilities));
+}
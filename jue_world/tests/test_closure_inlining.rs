@@ -0,0 +1,65 @@
+/// Test inline expansion of small closures at their call sites
+use jue_world::ast::AstNode;
+use jue_world::optimize::inline_small_closures;
+use jue_world::parser::parse;
+use jue_world::physics_compiler::compile_to_physics_world;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::VmState;
+
+fn run(ast: &AstNode) -> (Vec<OpCode>, Value) {
+    let (bytecode, constants) = compile_to_physics_world(ast, TrustTier::Formal).unwrap();
+    let mut vm = VmState::new(bytecode.clone(), constants, 1000, 4096, 1, 100);
+    (bytecode, vm.run().unwrap())
+}
+
+#[test]
+fn test_one_node_closure_called_once_is_inlined() {
+    let ast = parse("(let ((id (lambda (x) x))) (id 5))").unwrap();
+    let inlined = inline_small_closures(&ast, 1);
+
+    let (bytecode, result) = run(&inlined);
+    assert!(!bytecode
+        .iter()
+        .any(|op| matches!(op, OpCode::MakeClosure(..) | OpCode::Call(_))));
+    // Same result as binding the argument to the parameter by hand
+    assert_eq!(result, run(&parse("(let ((x 5)) x)").unwrap()).1);
+    assert_eq!(result, Value::Int(5));
+}
+
+#[test]
+fn test_parameters_are_renamed_away_from_call_site_names() {
+    // The argument mentions the caller's `x`, which the parameter must not capture
+    let ast = parse("(let ((x 7)) (let ((id (lambda (x) x))) (id x)))").unwrap();
+    let inlined = inline_small_closures(&ast, 1);
+
+    let AstNode::Let { body, .. } = &inlined else {
+        panic!("Expected the outer let to remain, got {inlined:?}");
+    };
+    let AstNode::Let { bindings, .. } = body.as_ref() else {
+        panic!("Expected the call to become a let, got {body:?}");
+    };
+    assert_ne!(bindings[0].0, "x");
+    assert_eq!(run(&inlined).1, Value::Int(7));
+}
+
+#[test]
+fn test_recursive_closure_is_not_inlined() {
+    let ast = parse("(let ((loop (lambda (n) (loop n)))) (loop 1))").unwrap();
+    assert_eq!(inline_small_closures(&ast, 100), ast);
+}
+
+#[test]
+fn test_escaping_or_large_closure_is_not_inlined() {
+    let escaping = parse("(let ((id (lambda (x) x))) id)").unwrap();
+    assert_eq!(inline_small_closures(&escaping, 100), escaping);
+
+    let large = parse("(let ((f (lambda (x) (if x 1 2)))) (f true))").unwrap();
+    assert_eq!(inline_small_closures(&large, 1), large);
+}
+
+#[test]
+fn test_free_variable_rebound_at_call_site_is_not_inlined() {
+    let ast = parse("(let ((y 1)) (let ((f (lambda () y))) (let ((y 2)) (f))))").unwrap();
+    assert_eq!(inline_small_closures(&ast, 10), ast);
+}
@@ -0,0 +1,143 @@
+/// Test saving compiled artifacts to disk and loading them back
+use jue_world::compiler::{load_artifact, load_artifact_for_source, save_artifact, source_hash};
+use jue_world::core_compilation::capability_analysis::CapabilityCheckMode;
+use jue_world::core_compiler::{compile, compile_with_harness};
+use jue_world::empirical_validation::TestHarness;
+use jue_world::error::CompilationError;
+use jue_world::trust_tier::TrustTier;
+use std::path::PathBuf;
+
+const SOURCE: &str = "(let ((greeting \"hello\") (n 41)) (if (> n 40) greeting 'small))";
+
+/// A file in the temp directory unique to this test
+fn artifact_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("jue-artifact-{}-{name}.bin", std::process::id()))
+}
+
+#[test]
+fn test_round_trip_preserves_compilation() {
+    let result = compile(SOURCE, TrustTier::Empirical, 1000, 1024).unwrap();
+    let path = artifact_path("round-trip");
+
+    save_artifact(&result, &path).unwrap();
+    let loaded = load_artifact(&path).unwrap();
+    let current = load_artifact_for_source(
+        &path,
+        SOURCE,
+        TrustTier::Empirical,
+        1000,
+        1024,
+        CapabilityCheckMode::Permissive,
+        &[],
+    )
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    for loaded in [loaded, current] {
+        assert_eq!(loaded.bytecode, result.bytecode);
+        assert_eq!(loaded.constants, result.constants);
+        assert_eq!(loaded.step_limit, result.step_limit);
+        assert_eq!(loaded.memory_limit, result.memory_limit);
+        assert_eq!(loaded.source_hash, result.source_hash);
+        assert_eq!(loaded.to_json_pretty(), result.to_json_pretty());
+    }
+}
+
+#[test]
+fn test_stale_or_foreign_artifacts_are_rejected() {
+    let result = compile(SOURCE, TrustTier::Empirical, 1000, 1024).unwrap();
+    let path = artifact_path("stale");
+    save_artifact(&result, &path).unwrap();
+
+    let edited = SOURCE.replace("41", "39");
+    let stale = load_artifact_for_source(
+        &path,
+        &edited,
+        TrustTier::Empirical,
+        1000,
+        1024,
+        CapabilityCheckMode::Permissive,
+        &[],
+    );
+    assert!(matches!(stale, Err(CompilationError::ArtifactError(_))));
+    for (tier, steps, memory) in [
+        (TrustTier::Experimental, 1000, 1024),
+        (TrustTier::Empirical, 2000, 1024),
+        (TrustTier::Empirical, 1000, 2048),
+    ] {
+        assert!(matches!(
+            load_artifact_for_source(
+                &path,
+                SOURCE,
+                tier,
+                steps,
+                memory,
+                CapabilityCheckMode::Permissive,
+                &[]
+            ),
+            Err(CompilationError::ArtifactError(_))
+        ));
+    }
+
+    std::fs::write(&path, b"not an artifact").unwrap();
+    assert!(matches!(
+        load_artifact(&path),
+        Err(CompilationError::ArtifactError(_))
+    ));
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(matches!(
+        load_artifact(&path),
+        Err(CompilationError::ArtifactError(_))
+    ));
+}
+
+#[test]
+fn test_source_hash_is_stable() {
+    // Pinned so that a hash change, which invalidates every cached
+    // artifact, cannot go unnoticed
+    assert_eq!(
+        source_hash(
+            "",
+            TrustTier::Formal,
+            0,
+            0,
+            CapabilityCheckMode::Permissive,
+            &[]
+        ),
+        0xcd28_197e_4cc7_8498
+    );
+}
+
+#[test]
+fn test_check_mode_and_harness_inputs_are_hashed() {
+    let hash = |mode, inputs: &[String]| {
+        source_hash(SOURCE, TrustTier::Empirical, 1000, 1024, mode, inputs)
+    };
+    let plain = hash(CapabilityCheckMode::Permissive, &[]);
+    assert_ne!(hash(CapabilityCheckMode::Strict, &[]), plain);
+    let n = ["n".to_string()];
+    assert_ne!(hash(CapabilityCheckMode::Permissive, &n), plain);
+    // Input names are delimited, so moving characters between them counts
+    let ab = ["ab".to_string(), "c".to_string()];
+    let bc = ["a".to_string(), "bc".to_string()];
+    assert_ne!(
+        hash(CapabilityCheckMode::Permissive, &ab),
+        hash(CapabilityCheckMode::Permissive, &bc)
+    );
+
+    // A harness compilation is current only for the same inputs
+    let harness = TestHarness::new(&["n"]);
+    let result = compile_with_harness("n", TrustTier::Empirical, 1000, 1024, &harness).unwrap();
+    assert_eq!(
+        result.source_hash,
+        Some(source_hash(
+            "n",
+            TrustTier::Empirical,
+            1000,
+            1024,
+            CapabilityCheckMode::Permissive,
+            &n
+        ))
+    );
+}
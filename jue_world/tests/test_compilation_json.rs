@@ -0,0 +1,46 @@
+/// Test the tooling-oriented JSON rendering of compilation results
+use jue_world::core_compiler::compile;
+use jue_world::trust_tier::TrustTier;
+
+#[test]
+fn test_json_decodes_opcodes_constants_and_capabilities() {
+    let source = "(if (has-capability? IoReadSensor) \"ready\" 'waiting)";
+    let result = compile(source, TrustTier::Empirical, 1000, 1024).unwrap();
+
+    let json = result.to_json_pretty();
+    let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(document["schema_version"], 1);
+    assert_eq!(
+        document["required_capabilities"],
+        serde_json::json!(["IoReadSensor"])
+    );
+
+    let ops: Vec<&str> = document["bytecode"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["op"].as_str().unwrap())
+        .collect();
+    assert!(ops.contains(&"HasCap"));
+    assert!(ops.contains(&"JmpIfFalse"));
+
+    let resolved: Vec<&str> = document["bytecode"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|entry| entry["resolved"].as_str())
+        .collect();
+    assert!(resolved.contains(&"\"ready\""));
+    assert!(resolved.contains(&"'waiting"));
+    assert_eq!(document["symbols"], serde_json::json!(["waiting"]));
+}
+
+#[test]
+fn test_json_output_is_stable() {
+    let source = "(has-capability? IoReadSensor)";
+    let first = compile(source, TrustTier::Empirical, 1000, 1024).unwrap();
+    let second = compile(source, TrustTier::Empirical, 1000, 1024).unwrap();
+
+    assert_eq!(first.to_json_pretty(), second.to_json_pretty());
+}
@@ -0,0 +1,51 @@
+/// Test batch compilation of independent Jue units
+use jue_world::core_compiler::{compile_batch, compile_batch_parallel};
+use jue_world::error::CompilationError;
+use jue_world::trust_tier::TrustTier;
+
+fn units() -> Vec<(String, TrustTier)> {
+    vec![
+        ("(if true 1 2)".to_string(), TrustTier::Empirical),
+        ("(let ((x 1)".to_string(), TrustTier::Empirical),
+        ("42".to_string(), TrustTier::Experimental),
+        ("\"hello\"".to_string(), TrustTier::Formal),
+    ]
+}
+
+#[test]
+fn test_batch_isolates_syntax_error() {
+    let results = compile_batch(&units(), 1000, 1024);
+
+    assert_eq!(results.len(), 4);
+    assert!(results[0].is_ok());
+    assert!(matches!(
+        results[1],
+        Err(CompilationError::ParseError { .. })
+    ));
+    assert!(results[2].is_ok());
+    assert!(results[3].is_ok());
+}
+
+#[test]
+fn test_parallel_batch_preserves_order() {
+    let units = units();
+    let sequential = compile_batch(&units, 1000, 1024);
+
+    for threads in [2, 3, 8] {
+        let parallel = compile_batch_parallel(&units, 1000, 1024, threads);
+        assert_eq!(parallel.len(), sequential.len());
+        for (par, seq) in parallel.iter().zip(&sequential) {
+            match (par, seq) {
+                (Ok(par), Ok(seq)) => assert_eq!(par.bytecode, seq.bytecode),
+                (Err(par), Err(seq)) => assert_eq!(par.to_string(), seq.to_string()),
+                _ => panic!("thread count {threads} changed a unit's outcome"),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_empty_batch() {
+    assert!(compile_batch(&[], 1000, 1024).is_empty());
+    assert!(compile_batch_parallel(&[], 1000, 1024, 4).is_empty());
+}
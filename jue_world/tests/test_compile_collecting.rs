@@ -0,0 +1,45 @@
+/// Test that compilation reports every diagnostic at once
+use jue_world::core_compiler::compile_collecting;
+use jue_world::error::CompilationError;
+use jue_world::trust_tier::TrustTier;
+
+#[test]
+fn test_two_undefined_variables_are_both_reported() {
+    let errors = compile_collecting(
+        "(if true missing-one missing-two)",
+        TrustTier::Empirical,
+        1000,
+        1024,
+    )
+    .unwrap_err();
+
+    let names: Vec<&str> = errors
+        .iter()
+        .map(|error| match error {
+            CompilationError::VariableNotFound(name) => name.as_str(),
+            other => panic!("unexpected error: {:?}", other),
+        })
+        .collect();
+    assert_eq!(names, vec!["missing-one", "missing-two"]);
+}
+
+#[test]
+fn test_parse_errors_in_separate_forms_are_all_reported() {
+    let source = "(lambda (x)) 42 (if true) (let ((y 1)) y)";
+
+    let errors = compile_collecting(source, TrustTier::Empirical, 1000, 1024).unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .all(|error| matches!(error, CompilationError::ParseError { .. })));
+}
+
+#[test]
+fn test_bindings_in_scope_are_not_reported() {
+    let source = "(let ((x 1)) (letrec ((f (lambda (n) (f n)))) (if true x (f x))))";
+
+    let result = compile_collecting(source, TrustTier::Empirical, 1000, 1024);
+
+    assert!(result.is_ok(), "{:?}", result.err());
+}
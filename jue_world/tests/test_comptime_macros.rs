@@ -0,0 +1,70 @@
+/// Test that comptime macros are evaluated during expansion and memoized
+use jue_world::ast::{AstNode, Literal};
+use jue_world::error::{CompilationError, SourceLocation};
+use jue_world::macro_expander::{
+    create_macro_expansion_context, define_comptime_macro, expand_macros, MacroExpansionContext,
+};
+use jue_world::trust_tier::TrustTier;
+
+fn int(value: i64) -> AstNode {
+    AstNode::Literal(Literal::Int(value))
+}
+
+fn call(name: &str, arguments: Vec<AstNode>) -> AstNode {
+    AstNode::Call {
+        function: Box::new(AstNode::Symbol(name.to_string())),
+        arguments,
+        location: SourceLocation::default(),
+    }
+}
+
+fn double(argument: AstNode) -> AstNode {
+    AstNode::MacroExpansion {
+        name: "double".to_string(),
+        arguments: vec![argument],
+        location: SourceLocation::default(),
+    }
+}
+
+/// `(double x)` is evaluated to `(* x 2)` at compile time
+fn context_with_double(tier: TrustTier) -> Result<MacroExpansionContext, CompilationError> {
+    let mut context = create_macro_expansion_context(tier);
+    define_comptime_macro(
+        &mut context,
+        "double".to_string(),
+        vec!["x".to_string()],
+        call("*", vec![AstNode::Variable("x".to_string()), int(2)]),
+        tier,
+    )?;
+    Ok(context)
+}
+
+#[test]
+fn test_comptime_macro_call_is_replaced_by_its_value() {
+    let mut context = context_with_double(TrustTier::Empirical).unwrap();
+
+    let expanded = expand_macros(&double(int(21)), &mut context).unwrap();
+
+    assert_eq!(expanded, int(42));
+}
+
+#[test]
+fn test_repeated_comptime_call_is_cached_and_still_charged() {
+    let mut context = context_with_double(TrustTier::Empirical).unwrap();
+    expand_macros(&double(int(21)), &mut context).unwrap();
+    let once = context.comptime.env.steps_used();
+
+    let program = call("+", vec![double(int(21)), double(int(21))]);
+    let expanded = expand_macros(&program, &mut context).unwrap();
+
+    assert_eq!(expanded, call("+", vec![int(42), int(42)]));
+    assert_eq!(context.comptime.env.cache.len(), 1);
+    assert_eq!(context.comptime.env.steps_used(), 3 * once);
+}
+
+#[test]
+fn test_comptime_macro_needs_a_tier_allowing_comptime_evaluation() {
+    let result = context_with_double(TrustTier::Formal);
+
+    assert!(matches!(result, Err(CompilationError::CapabilityError(_))));
+}
@@ -0,0 +1,105 @@
+/// Test folding constant subexpressions at compile time
+use jue_world::ast::{AstNode, Literal};
+use jue_world::comptime::fold_constants;
+use jue_world::parser::parse;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::VmState;
+
+fn folded(source: &str) -> AstNode {
+    fold_constants(&parse(source).unwrap())
+}
+
+#[test]
+fn test_nested_pure_arithmetic_becomes_one_literal() {
+    assert_eq!(
+        folded("(+ 1 (* 2 3) (- 10 4))"),
+        AstNode::Literal(Literal::Int(13))
+    );
+    assert_eq!(
+        folded("(= (< 1 2) (> 4 3))"),
+        AstNode::Literal(Literal::Bool(true))
+    );
+    assert_eq!(
+        folded(r#"(str-concat "comp" "time")"#),
+        AstNode::Literal(Literal::String("comptime".to_string()))
+    );
+}
+
+#[test]
+fn test_expression_reading_a_sensor_is_untouched() {
+    let ast = parse("(+ 1 (read-sensor))").unwrap();
+    assert_eq!(fold_constants(&ast), ast);
+
+    let ffi = parse("(ffi-call add 1 (* 2 3))").unwrap();
+    assert_eq!(fold_constants(&ffi), ffi);
+}
+
+#[test]
+fn test_pure_subtree_beside_variables_is_folded() {
+    assert_eq!(
+        folded("(let ((x 1)) (+ x (* 2 3)))"),
+        folded("(let ((x 1)) (+ x 6))")
+    );
+}
+
+#[test]
+fn test_rebound_operators_and_runtime_errors_are_not_folded() {
+    let shadowed = parse("(let ((+ 0)) (+ 1 2))").unwrap();
+    assert_eq!(fold_constants(&shadowed), shadowed);
+
+    let division = parse("(/ 1 0)").unwrap();
+    assert_eq!(fold_constants(&division), division);
+
+    let overflow = parse(&format!("(+ {} 1)", i64::MAX)).unwrap();
+    assert_eq!(fold_constants(&overflow), overflow);
+}
+
+#[test]
+fn test_operators_without_a_matching_opcode_are_not_folded() {
+    for source in [
+        "(and true false)",
+        "(not true)",
+        "(- 5)",
+        r#"(str-concat "a" "b" "c")"#,
+        "(+ 1.5 2.5)",
+        "(/ 3.0 2.0)",
+        "(< 1.0 2.0)",
+    ] {
+        let ast = parse(source).unwrap();
+        assert_eq!(fold_constants(&ast), ast, "{source}");
+    }
+}
+
+#[test]
+fn test_folded_operators_compute_what_their_opcodes_do() {
+    let cases = [
+        (
+            "(- 10 4 3)",
+            vec![
+                OpCode::Int(10),
+                OpCode::Int(4),
+                OpCode::Sub,
+                OpCode::Int(3),
+                OpCode::Sub,
+            ],
+        ),
+        ("(/ 7 2)", vec![OpCode::Int(7), OpCode::Int(2), OpCode::Div]),
+        (
+            "(<= 2 2)",
+            vec![OpCode::Int(2), OpCode::Int(2), OpCode::Lte],
+        ),
+        (
+            "(= true false)",
+            vec![OpCode::Bool(true), OpCode::Bool(false), OpCode::Eq],
+        ),
+    ];
+    for (source, program) in cases {
+        let expected = match folded(source) {
+            AstNode::Literal(Literal::Int(value)) => Value::Int(value),
+            AstNode::Literal(Literal::Bool(value)) => Value::Bool(value),
+            other => panic!("{source} folded to {other:?}"),
+        };
+        let mut vm = VmState::new(program, vec![], 1000, 4096, 1, 100);
+        assert_eq!(vm.run().unwrap(), expected, "{source}");
+    }
+}
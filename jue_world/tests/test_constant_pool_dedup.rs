@@ -0,0 +1,117 @@
+/// Test structural deduplication of the compiler's constant pool
+use jue_world::ast::{AstNode, Literal};
+use jue_world::physics_compiler::{
+    compile_to_physics_world, compile_to_physics_world_with_source_map, PhysicsWorldCompiler,
+};
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::opcodes::list_ops::{handle_car, handle_cdr};
+use physics_world::vm::VmState;
+
+#[test]
+fn test_equal_compound_constants_share_a_slot() {
+    let mut compiler = PhysicsWorldCompiler::new(TrustTier::Empirical);
+
+    let first = compiler.get_constant_index(Value::Capability(Capability::ResourceExtraMemory(64)));
+    let other =
+        compiler.get_constant_index(Value::Capability(Capability::ResourceExtraMemory(128)));
+    let second =
+        compiler.get_constant_index(Value::Capability(Capability::ResourceExtraMemory(64)));
+
+    assert_eq!(first, second);
+    assert_ne!(first, other);
+    assert_eq!(compiler.string_pool.len(), 2);
+}
+
+#[test]
+fn test_float_constants_compare_by_bits() {
+    let mut compiler = PhysicsWorldCompiler::new(TrustTier::Empirical);
+
+    let nan = compiler.get_constant_index(Value::Float(f64::NAN));
+    assert_eq!(compiler.get_constant_index(Value::Float(f64::NAN)), nan);
+
+    let zero = compiler.get_constant_index(Value::Float(0.0));
+    assert_ne!(compiler.get_constant_index(Value::Float(-0.0)), zero);
+}
+
+#[test]
+fn test_repeated_literal_is_pooled_once() {
+    let ast = AstNode::If {
+        condition: Box::new(AstNode::Literal(Literal::Bool(true))),
+        then_branch: Box::new(AstNode::Literal(Literal::String("sensor".to_string()))),
+        else_branch: Box::new(AstNode::Literal(Literal::String("sensor".to_string()))),
+        location: Default::default(),
+    };
+
+    let (bytecode, constants) = compile_to_physics_world(&ast, TrustTier::Formal).unwrap();
+
+    assert_eq!(constants, vec![Value::String("sensor".to_string())]);
+    let loads = bytecode
+        .iter()
+        .filter(|op| **op == OpCode::LoadString(0))
+        .count();
+    assert_eq!(loads, 2);
+}
+
+fn int(value: i64) -> AstNode {
+    AstNode::Literal(Literal::Int(value))
+}
+
+fn cons(car: AstNode, cdr: AstNode) -> AstNode {
+    AstNode::Cons {
+        car: Box::new(car),
+        cdr: Box::new(cdr),
+        location: Default::default(),
+    }
+}
+
+#[test]
+fn test_repeated_literal_pair_is_pooled_once() {
+    // The second pair is spelled as a list, which is the same structure
+    let list = AstNode::List {
+        elements: vec![int(1), int(2)],
+        location: Default::default(),
+    };
+    let ast = AstNode::If {
+        condition: Box::new(AstNode::Literal(Literal::Bool(true))),
+        then_branch: Box::new(cons(int(1), cons(int(2), AstNode::Literal(Literal::Nil)))),
+        else_branch: Box::new(list),
+        location: Default::default(),
+    };
+
+    let (bytecode, constants, _, _, _, _, pairs) =
+        compile_to_physics_world_with_source_map(&ast, TrustTier::Formal, &[]).unwrap();
+
+    assert_eq!(pairs.len(), 2);
+    let outer = pairs.last().unwrap().slot;
+    let loads = bytecode
+        .iter()
+        .filter(|op| **op == OpCode::GetConst(outer))
+        .count();
+    assert_eq!(loads, 2);
+    assert!(!bytecode.contains(&OpCode::Cons));
+
+    // Loading builds the pair into its slot
+    let mut vm = VmState::new(bytecode, constants, 1000, 1024, 1, 100);
+    vm.link_pair_constants(&pairs).unwrap();
+    vm.stack.push(vm.constant_pool[outer].clone());
+    handle_cdr(&mut vm).unwrap();
+    handle_car(&mut vm).unwrap();
+    assert_eq!(vm.stack.pop(), Some(Value::Int(2)));
+}
+
+#[test]
+fn test_pair_with_computed_part_is_built_at_runtime() {
+    let sum = AstNode::Call {
+        function: Box::new(AstNode::Symbol("+".to_string())),
+        arguments: vec![int(1), int(2)],
+        location: Default::default(),
+    };
+
+    let (bytecode, _, _, _, _, _, pairs) =
+        compile_to_physics_world_with_source_map(&cons(sum, int(3)), TrustTier::Formal, &[])
+            .unwrap();
+
+    assert!(pairs.is_empty());
+    assert_eq!(bytecode.last(), Some(&OpCode::Cons));
+}
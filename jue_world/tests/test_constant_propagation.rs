@@ -0,0 +1,65 @@
+/// Test substitution of literal-bound let variables into their uses
+use jue_world::ast::{AstNode, Literal};
+use jue_world::error::SourceLocation;
+use jue_world::optimize::propagate_constants;
+use jue_world::parser::parse;
+use jue_world::physics_compiler::compile_to_physics_world;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::VmState;
+
+fn propagated(source: &str) -> AstNode {
+    propagate_constants(&parse(source).unwrap())
+}
+
+/// Compare trees by their printed form; parsed nodes carry their source
+/// positions, which differ between the two programs
+fn assert_same_tree(actual: &AstNode, expected: &str) {
+    assert_eq!(actual.to_string(), parse(expected).unwrap().to_string());
+}
+
+#[test]
+fn test_literal_binding_is_substituted_into_its_uses() {
+    let ast = propagated("(let ((x 5)) (+ x x))");
+    assert_same_tree(&ast, "(+ 5 5)");
+
+    // The physics compiler only recognizes arithmetic called by symbol
+    let add_x_twice = AstNode::Let {
+        bindings: vec![("x".to_string(), AstNode::Literal(Literal::Int(5)))],
+        body: Box::new(AstNode::Call {
+            function: Box::new(AstNode::Symbol("add".to_string())),
+            arguments: vec![
+                AstNode::Variable("x".to_string()),
+                AstNode::Variable("x".to_string()),
+            ],
+            location: SourceLocation::default(),
+        }),
+        location: SourceLocation::default(),
+    };
+    let ast = propagate_constants(&add_x_twice);
+    let (bytecode, constants) = compile_to_physics_world(&ast, TrustTier::Formal).unwrap();
+    assert!(!bytecode.iter().any(|op| matches!(op, OpCode::GetLocal(_))));
+    let mut vm = VmState::new(bytecode, constants, 1000, 4096, 1, 100);
+    assert_eq!(vm.run().unwrap(), Value::Int(10));
+}
+
+#[test]
+fn test_set_target_is_not_propagated() {
+    let source = "(let ((x 5)) (let ((y (set! x 6))) (+ x y)))";
+    assert_eq!(propagated(source), parse(source).unwrap());
+}
+
+#[test]
+fn test_inner_binding_shadows_the_constant() {
+    let ast = propagated("(let ((x 5)) (+ x (let ((x (f 1))) x)))");
+    assert_same_tree(&ast, "(+ 5 (let ((x (f 1))) x))");
+
+    let ast = propagated("(let ((x 5)) (lambda (x) x))");
+    assert_same_tree(&ast, "(lambda (x) x)");
+}
+
+#[test]
+fn test_let_star_bindings_see_earlier_constants() {
+    let ast = propagated("(let* ((x 1) (y (+ x 1)) (x 3)) (+ x y))");
+    assert_same_tree(&ast, "(let* ((y (+ 1 1))) (+ 3 y))");
+}
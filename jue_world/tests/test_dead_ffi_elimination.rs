@@ -0,0 +1,98 @@
+/// Test elimination of unused pure FFI calls and their capability requirements
+use jue_world::core_compilation::dead_ffi_elimination::eliminate_dead_ffi_calls;
+use jue_world::core_compiler::compile;
+use jue_world::ffi_system::standard_functions::create_standard_ffi_registry;
+use jue_world::parser::parse;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{Capability, OpCode};
+
+fn has_host_call(bytecode: &[OpCode]) -> bool {
+    bytecode
+        .iter()
+        .any(|op| matches!(op, OpCode::HostCall { .. }))
+}
+
+#[test]
+fn test_unused_pure_call_no_longer_requires_capability() {
+    let result = compile(
+        "(let ((reading (ffi-call read-sensor))) 42)",
+        TrustTier::Formal,
+        1000,
+        1024,
+    )
+    .unwrap();
+
+    assert!(!result
+        .required_capabilities
+        .contains(&Capability::IoReadSensor));
+    assert!(!has_host_call(&result.bytecode));
+}
+
+#[test]
+fn test_unused_actuator_write_is_retained() {
+    let source = "(let ((ok (ffi-call write-actuator 1.0))) 42)";
+    let result = compile(source, TrustTier::Empirical, 1000, 1024).unwrap();
+
+    assert!(result
+        .required_capabilities
+        .contains(&Capability::IoWriteActuator));
+    assert!(has_host_call(&result.bytecode));
+    assert!(compile(source, TrustTier::Formal, 1000, 1024).is_err());
+}
+
+#[test]
+fn test_used_pure_call_is_kept() {
+    let result = compile(
+        "(let ((reading (ffi-call read-sensor))) reading)",
+        TrustTier::Empirical,
+        1000,
+        1024,
+    )
+    .unwrap();
+
+    assert!(result
+        .required_capabilities
+        .contains(&Capability::IoReadSensor));
+    assert!(has_host_call(&result.bytecode));
+}
+
+#[test]
+fn test_pure_call_with_impure_argument_is_kept() {
+    let registry = create_standard_ffi_registry();
+    let ast = parse("(let ((x (add (ffi-call write-actuator 1.0) 1))) 42)").unwrap();
+
+    assert_eq!(eliminate_dead_ffi_calls(&ast, &registry), ast);
+}
+
+#[test]
+fn test_only_dead_bindings_are_dropped() {
+    let registry = create_standard_ffi_registry();
+    let ast = parse("(let ((x (ffi-call read-sensor)) (y 1)) y)").unwrap();
+
+    assert_eq!(
+        eliminate_dead_ffi_calls(&ast, &registry),
+        parse("(let ((y 1)) y)").unwrap()
+    );
+}
+
+#[test]
+fn test_call_through_a_local_named_like_a_pure_function_is_kept() {
+    let registry = create_standard_ffi_registry();
+    for source in [
+        "(lambda (add) (let ((x (add 1 2))) 42))",
+        "(let ((add (lambda (a b) (ffi-call write-actuator 1.0)))) (let ((x (add 1 2))) 42))",
+        "(let* ((add (lambda (a b) a)) (x (add 1 2))) 42)",
+    ] {
+        let ast = parse(source).unwrap();
+        assert_eq!(eliminate_dead_ffi_calls(&ast, &registry), ast, "{source}");
+    }
+
+    // Outside the binding the name refers to the FFI function again
+    assert_eq!(
+        eliminate_dead_ffi_calls(
+            &parse("(let ((f (lambda (add) add)) (x (add 1 2))) f)").unwrap(),
+            &registry
+        ),
+        parse("(let ((f (lambda (add) add))) f)").unwrap()
+    );
+}
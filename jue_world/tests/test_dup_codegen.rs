@@ -0,0 +1,36 @@
+/// Test that let compilation uses Dup instead of a store-and-reload round-trip
+use jue_world::parser::parse;
+use jue_world::physics_compiler::compile_to_physics_world;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::VmState;
+
+#[test]
+fn test_let_bound_call_result_is_duplicated() {
+    let source = "(let ((x (ffi-call mul 3 7))) (ffi-call add x x))";
+    let ast = parse(source).unwrap();
+    let (bytecode, constants) = compile_to_physics_world(&ast, TrustTier::Formal).unwrap();
+
+    // The bound call runs once and its result is duplicated rather than reloaded
+    let host_calls = bytecode
+        .iter()
+        .filter(|op| matches!(op, OpCode::HostCall { .. }))
+        .count();
+    assert_eq!(host_calls, 2);
+    assert_eq!(bytecode[3..5], [OpCode::Dup, OpCode::SetLocal(0)]);
+
+    let mut vm = VmState::new(bytecode, constants, 1000, 1024, 1, 100);
+    assert_eq!(vm.run().unwrap(), Value::Int(42));
+}
+
+#[test]
+fn test_let_without_immediate_reload_is_unchanged() {
+    let source = "(let ((x 1)) 2)";
+    let ast = parse(source).unwrap();
+    let (bytecode, _) = compile_to_physics_world(&ast, TrustTier::Formal).unwrap();
+
+    assert_eq!(
+        bytecode,
+        vec![OpCode::Int(1), OpCode::SetLocal(0), OpCode::Int(2)]
+    );
+}
@@ -0,0 +1,114 @@
+/// Test running compiled Empirical/Experimental code against a test harness
+use jue_world::core_compiler::{compile, compile_with_harness, EmpiricalResult};
+use jue_world::empirical_validation::TestHarness;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::Value;
+
+/// Successor of `n`, or 0 when `n` is not an integer. The `if` is bound
+/// rather than last, since the VM rejects a jump past the final instruction.
+const SUCCESSOR: &str = "(let ((m (if (integer? n) (ffi-call add n 1) 0))) m)";
+
+/// Like `SUCCESSOR`, but adds 2
+const OFF_BY_ONE: &str = "(let ((m (if (integer? n) (ffi-call add n 2) 0))) m)";
+
+fn successor_harness() -> TestHarness {
+    TestHarness::new(&["n"])
+        .case(vec![Value::Int(1)], Value::Int(2))
+        .case(vec![Value::Int(41)], Value::Int(42))
+}
+
+#[test]
+fn test_correct_program_passes() {
+    let harness = successor_harness().case(vec![Value::Bool(true)], Value::Int(0));
+    let result =
+        compile_with_harness(SUCCESSOR, TrustTier::Empirical, 1000, 4096, &harness).unwrap();
+
+    let EmpiricalResult::Passed {
+        tests_run,
+        coverage,
+    } = result.empirical_check
+    else {
+        panic!("expected a pass, got {:?}", result.empirical_check);
+    };
+    assert_eq!(tests_run, 3);
+    // Between them the cases take both branches
+    assert!((coverage - 1.0).abs() < f64::EPSILON, "coverage {coverage}");
+}
+
+#[test]
+fn test_untested_branch_lowers_coverage() {
+    let full = compile_with_harness(
+        SUCCESSOR,
+        TrustTier::Empirical,
+        1000,
+        4096,
+        &successor_harness().case(vec![Value::Nil], Value::Int(0)),
+    )
+    .unwrap();
+    let partial = compile_with_harness(
+        SUCCESSOR,
+        TrustTier::Empirical,
+        1000,
+        4096,
+        &successor_harness(),
+    )
+    .unwrap();
+
+    let (
+        EmpiricalResult::Passed { coverage: full, .. },
+        EmpiricalResult::Passed {
+            coverage: partial, ..
+        },
+    ) = (full.empirical_check, partial.empirical_check)
+    else {
+        panic!("both harnesses should pass");
+    };
+    assert!(partial < full, "{partial} should be below {full}");
+}
+
+#[test]
+fn test_buggy_program_reports_first_failing_case() {
+    let harness = TestHarness::new(&["n"])
+        .case(vec![Value::Bool(false)], Value::Int(0))
+        .case(vec![Value::Int(1)], Value::Int(2))
+        .case(vec![Value::Int(5)], Value::Int(6));
+    let result =
+        compile_with_harness(OFF_BY_ONE, TrustTier::Experimental, 1000, 4096, &harness).unwrap();
+
+    let EmpiricalResult::Failed {
+        reason,
+        failing_case,
+    } = result.empirical_check
+    else {
+        panic!("expected a failure, got {:?}", result.empirical_check);
+    };
+    assert!(failing_case.starts_with("case 1:"), "{failing_case}");
+    assert!(reason.contains("expected 2, got 3"), "{reason}");
+}
+
+#[test]
+fn test_case_with_wrong_input_count_fails() {
+    let harness = TestHarness::new(&["n"]).case(vec![], Value::Int(1));
+    let result =
+        compile_with_harness(SUCCESSOR, TrustTier::Empirical, 1000, 4096, &harness).unwrap();
+    assert!(matches!(
+        result.empirical_check,
+        EmpiricalResult::Failed { ref reason, .. } if reason.contains("0 inputs")
+    ));
+}
+
+#[test]
+fn test_formal_code_and_plain_compilation_are_not_validated() {
+    let formal = compile_with_harness(
+        SUCCESSOR,
+        TrustTier::Formal,
+        1000,
+        4096,
+        &successor_harness(),
+    )
+    .unwrap();
+    assert_eq!(formal.empirical_check, EmpiricalResult::NotApplicable);
+
+    let plain = compile("(ffi-call add 1 2)", TrustTier::Empirical, 1000, 4096).unwrap();
+    assert_eq!(plain.empirical_check, EmpiricalResult::NotApplicable);
+}
@@ -0,0 +1,48 @@
+/// Test that escape analysis gives each binding its own variable index
+use jue_world::escape_analysis::{AnalysisContext, EscapeAnalysis};
+use jue_world::parser::parse;
+
+/// Analyze `source`, returning the analysis and the names of escaping variables
+fn analyze(source: &str) -> (EscapeAnalysis, Vec<(usize, String)>) {
+    let ast = parse(source).unwrap();
+    let mut analysis = EscapeAnalysis::new();
+    let mut context = AnalysisContext::new();
+    analysis.analyze_expression(&ast, &mut context);
+    assert!(context.errors.is_empty(), "{:?}", context.errors);
+
+    let mut escaping: Vec<(usize, String)> = analysis
+        .escaping_vars
+        .iter()
+        .map(|&index| (index, analysis.symbols.name(index).unwrap().to_string()))
+        .collect();
+    escaping.sort();
+    (analysis, escaping)
+}
+
+#[test]
+fn test_shadowed_bindings_are_distinct() {
+    // Only the inner x is captured by the lambda
+    let (analysis, escaping) = analyze("(let ((x 1)) (let ((x 2)) (lambda (y) x)))");
+    assert_eq!(analysis.symbols.len(), 3);
+    assert_eq!(analysis.symbols.name(0), Some("x"));
+    assert_eq!(analysis.symbols.name(1), Some("x"));
+    assert_eq!(escaping, [(1, "x".to_string())]);
+
+    // A parameter shadowing the outer x is not captured at all
+    let (_, escaping) = analyze("(let ((x 1)) (lambda (x) x))");
+    assert!(escaping.is_empty());
+
+    // The inner let's value still refers to the outer x
+    let (_, escaping) = analyze("(let ((x 1)) (lambda () (let ((x x)) x)))");
+    assert_eq!(escaping, [(0, "x".to_string())]);
+}
+
+#[test]
+fn test_distinct_names_never_share_an_index() {
+    // Under hashed indices two names could collide and mark each other
+    // escaping; dense indices keep them apart
+    let (analysis, escaping) = analyze("(let ((a 1) (b 2)) (lambda () a))");
+    assert_eq!(analysis.symbols.len(), 2);
+    assert_eq!(escaping, [(0, "a".to_string())]);
+    assert!(!analysis.escaping_vars.contains(&1));
+}
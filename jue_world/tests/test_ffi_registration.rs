@@ -0,0 +1,184 @@
+/// Test exposing embedder-registered host functions to compiled programs
+use jue_world::core_compilation::capability_analysis::CapabilityCheckMode;
+use jue_world::core_compiler::compile_with_registry;
+use jue_world::error::CompilationError;
+use jue_world::parser::parse;
+use jue_world::physics_compiler::{PhysicsWorldCompiler, CAPABILITY_DENIED_CODE};
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{Capability, HostFunction, OpCode, Value};
+use physics_world::vm::VmState;
+
+/// Compiler that knows `nudge`, an integer add guarded by `IoWriteActuator`
+fn compiler() -> PhysicsWorldCompiler {
+    let mut compiler = PhysicsWorldCompiler::new(TrustTier::Experimental);
+    compiler
+        .ffi_registry
+        .registry
+        .register(
+            "nudge",
+            HostFunction::IntAdd,
+            Capability::IoWriteActuator,
+            2,
+        )
+        .unwrap();
+    compiler
+}
+
+fn run(compiler: &PhysicsWorldCompiler, bytecode: Vec<OpCode>, granted: bool) -> Value {
+    run_with(
+        compiler,
+        bytecode,
+        granted.then_some(Capability::IoWriteActuator),
+    )
+}
+
+fn run_with(
+    compiler: &PhysicsWorldCompiler,
+    bytecode: Vec<OpCode>,
+    granted: Option<Capability>,
+) -> Value {
+    let mut vm = VmState::new(bytecode, compiler.string_pool.clone(), 1000, 1024, 1, 100);
+    if let Some(capability) = granted {
+        vm.grant_capability(capability);
+    }
+    vm.run().unwrap()
+}
+
+#[test]
+fn test_registered_function_call_is_guarded_by_its_capability() {
+    let mut compiler = compiler();
+    let bytecode = compiler
+        .compile_to_physics(&parse("(nudge 40 2)").unwrap())
+        .unwrap();
+
+    let has_cap = bytecode
+        .iter()
+        .position(|op| {
+            matches!(op, OpCode::HasCap(idx)
+                if compiler.string_pool[*idx] == Value::Capability(Capability::IoWriteActuator))
+        })
+        .expect("no HasCap check for the declared capability");
+    let host_call = bytecode
+        .iter()
+        .position(|op| {
+            matches!(op, OpCode::HostCall { func_id, args: 2, .. }
+                if *func_id == HostFunction::IntAdd as u16)
+        })
+        .expect("no HostCall to the registered function");
+    assert!(has_cap < host_call);
+}
+
+#[test]
+fn test_guarded_call_runs_only_with_the_capability() {
+    let mut compiler = compiler();
+    let bytecode = compiler
+        .compile_to_physics(&parse("(nudge 40 2)").unwrap())
+        .unwrap();
+
+    assert_eq!(run(&compiler, bytecode.clone(), true), Value::Int(42));
+    match run(&compiler, bytecode, false) {
+        Value::ErrorRecord { code, message, .. } => {
+            assert_eq!(code, CAPABILITY_DENIED_CODE);
+            assert!(message.contains("IoWriteActuator"), "{message}");
+        }
+        other => panic!("expected a denial, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_local_binding_shadows_registered_function() {
+    let mut compiler = compiler();
+    let bytecode = compiler
+        .compile_to_physics(&parse("(let ((nudge (lambda (a b) a))) (nudge 40 2))").unwrap())
+        .unwrap();
+    assert!(!bytecode
+        .iter()
+        .any(|op| matches!(op, OpCode::HostCall { .. } | OpCode::HasCap(_))));
+}
+
+#[test]
+fn test_registered_system_function_runs_under_its_capability() {
+    let mut compiler = compiler();
+    compiler
+        .ffi_registry
+        .registry
+        .register(
+            "sense",
+            HostFunction::ReadSensor,
+            Capability::IoReadSensor,
+            0,
+        )
+        .unwrap();
+    let bytecode = compiler
+        .compile_to_physics(&parse("(sense)").unwrap())
+        .unwrap();
+
+    assert_eq!(
+        run_with(&compiler, bytecode.clone(), Some(Capability::IoReadSensor)),
+        Value::Int(42)
+    );
+    match run_with(&compiler, bytecode, None) {
+        Value::ErrorRecord { code, .. } => assert_eq!(code, CAPABILITY_DENIED_CODE),
+        other => panic!("expected a denial, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_system_function_cannot_be_registered_under_another_capability() {
+    let mut compiler = compiler();
+    let result = compiler.ffi_registry.registry.register(
+        "sense",
+        HostFunction::ReadSensor,
+        Capability::IoNetwork,
+        0,
+    );
+    assert!(matches!(result, Err(CompilationError::FfiError(_))));
+    assert!(compiler
+        .ffi_registry
+        .registry
+        .find_function("sense")
+        .is_none());
+}
+
+#[test]
+fn test_call_with_wrong_arity_is_rejected() {
+    let mut compiler = compiler();
+    for source in ["(nudge 10 3 2)", "(nudge 10)"] {
+        match compiler.compile_to_physics(&parse(source).unwrap()) {
+            Err(CompilationError::FfiArityMismatch { expected, got, .. }) => {
+                assert_eq!(expected, 2);
+                assert_ne!(got, 2);
+            }
+            other => panic!("{source}: expected an arity error, got {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn test_pipeline_compiles_against_the_supplied_registry() {
+    let registry = compiler().ffi_registry.registry;
+    let compile = |source: &str, mode| {
+        compile_with_registry(source, TrustTier::Experimental, 1000, 1024, mode, &registry)
+    };
+
+    let result = compile("(nudge 40 2)", CapabilityCheckMode::Permissive).unwrap();
+    assert!(result.bytecode.iter().any(|op| {
+        matches!(op, OpCode::HostCall { func_id, args: 2, .. }
+            if *func_id == HostFunction::IntAdd as u16)
+    }));
+    assert!(result
+        .required_capabilities
+        .contains(&Capability::IoWriteActuator));
+
+    match compile("(nudge 40 2)", CapabilityCheckMode::Strict) {
+        Err(CompilationError::UndeclaredCapability { cap, .. }) => {
+            assert_eq!(cap, Capability::IoWriteActuator);
+        }
+        other => panic!("expected an undeclared capability error, got {other:?}"),
+    }
+    compile(
+        "(let ((act (require-capability IoWriteActuator))) (nudge 40 2))",
+        CapabilityCheckMode::Strict,
+    )
+    .unwrap();
+}
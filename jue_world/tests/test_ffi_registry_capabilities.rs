@@ -0,0 +1,37 @@
+/// Test that capability analysis takes FFI requirements from the registry
+use jue_world::core_compilation::capability_analysis::{
+    analyze_capabilities, analyze_capabilities_with_registry,
+};
+use jue_world::error::SourceLocation;
+use jue_world::ffi_system::global_ffi_registry::FfiFunction;
+use jue_world::ffi_system::standard_functions::create_standard_ffi_registry;
+use jue_world::parser::parse;
+use physics_world::types::{Capability, HostFunction};
+
+#[test]
+fn test_custom_ffi_function_contributes_its_capability() {
+    let mut registry = create_standard_ffi_registry();
+    registry.register_function(FfiFunction {
+        name: "archive-state".to_string(),
+        host_function: HostFunction::PersistWrite,
+        required_capability: Some(Capability::IoPersist),
+        pure: false,
+        parameter_types: vec!["Int".to_string()],
+        return_type: "Nil".to_string(),
+        documentation: "Archive a snapshot of actor state".to_string(),
+        location: SourceLocation::default(),
+    });
+    let ast = parse("(archive-state 1)").unwrap();
+
+    let with_custom = analyze_capabilities_with_registry(&ast, &registry).unwrap();
+    assert!(with_custom.contains(&Capability::IoPersist));
+
+    let standard = analyze_capabilities(&ast).unwrap();
+    assert!(!standard.contains(&Capability::IoPersist));
+}
+
+#[test]
+fn test_standard_registry_covers_persistence_functions() {
+    let required = analyze_capabilities(&parse("(persist-read \"key\")").unwrap()).unwrap();
+    assert!(required.contains(&Capability::IoPersist));
+}
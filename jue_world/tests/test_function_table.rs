@@ -0,0 +1,114 @@
+/// Test the function table emitted by compilation and attached on load
+use jue_world::compiler::loaded_module::LoadedModule;
+use jue_world::core_compiler::{compile, compile_with_harness, EmpiricalResult};
+use jue_world::empirical_validation::TestHarness;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::EscapeStatus;
+
+const NESTED: &str = "(let ((y 1)) (lambda (x) (lambda (z) (+ x (+ y z)))))";
+
+#[test]
+fn test_lambdas_are_numbered_with_their_free_variables() {
+    let result = compile(NESTED, TrustTier::Empirical, 1000, 1024 * 1024).unwrap();
+    let table = &result.function_table;
+    assert_eq!(table.len(), 2);
+
+    // Besides its own parameter `x`, the outer lambda's body mentions `y`
+    let outer = &table[&0];
+    assert_eq!(outer.local_count, 1);
+    assert_eq!(outer.free_variables.len(), 1);
+    assert_eq!(outer.escape_info[&0], EscapeStatus::Escaping);
+
+    // The inner lambda closes over both `x` and `y`
+    let inner = &table[&1];
+    assert_eq!(inner.local_count, 1);
+    assert_eq!(inner.free_variables.len(), 2);
+    assert_eq!(inner.captured_locals(), inner.free_variables);
+}
+
+#[test]
+fn test_closed_lambda_has_no_free_variables() {
+    let result = compile(
+        "(lambda (a b . rest) rest)",
+        TrustTier::Empirical,
+        1000,
+        1024,
+    )
+    .unwrap();
+    let info = &result.function_table[&0];
+    assert_eq!(info.local_count, 3);
+    assert!(info.free_variables.is_empty());
+}
+
+#[test]
+fn test_loaded_module_attaches_function_table() {
+    let result = compile(NESTED, TrustTier::Empirical, 1000, 1024 * 1024).unwrap();
+    let json = serde_json::to_string(&LoadedModule::from_compilation(&result)).unwrap();
+    let module: LoadedModule = serde_json::from_str(&json).unwrap();
+
+    let vm = module.instantiate(1).unwrap();
+    assert_eq!(vm.function_table, result.function_table);
+    assert_eq!(vm.get_function_info(1).unwrap(), result.function_table[&1]);
+}
+
+#[test]
+fn test_bound_lambdas_are_named_by_function_id() {
+    let source =
+        "(let ((double (lambda (x) (* x 2))) (inc (lambda (y) (+ y 1)))) (inc (double 3)))";
+    let result = compile(source, TrustTier::Empirical, 1000, 1024 * 1024).unwrap();
+    assert_eq!(result.function_names.name_for(0), Some("double"));
+    assert_eq!(result.function_names.name_for(1), Some("inc"));
+
+    let vm = LoadedModule::from_compilation(&result)
+        .instantiate(1)
+        .unwrap();
+    assert_eq!(vm.function_names, result.function_names);
+}
+
+#[test]
+fn test_compiled_lambda_runs_with_the_table_attached() {
+    let harness = TestHarness::new(&["x"]).case(vec![Value::Int(5)], Value::Int(5));
+    let result = compile_with_harness(
+        "((lambda (y) y) x)",
+        TrustTier::Empirical,
+        1000,
+        1024 * 1024,
+        &harness,
+    )
+    .unwrap();
+    assert!(
+        matches!(result.empirical_check, EmpiricalResult::Passed { .. }),
+        "{:?}",
+        result.empirical_check
+    );
+
+    // The closure is made from the body slot the table is keyed by
+    let made = result.bytecode.iter().find_map(|op| match op {
+        OpCode::MakeClosure(function, 0) => Some(*function),
+        _ => None,
+    });
+    let function = made.expect("the lambda makes a closure");
+    assert!(result.function_table.contains_key(&(function as u16)));
+
+    let mut vm = LoadedModule::from_compilation(&result)
+        .instantiate(1)
+        .unwrap();
+    vm.top_level_locals = vec![Value::Int(7)];
+    assert_eq!(vm.run().unwrap(), Value::Int(7));
+}
+
+#[test]
+fn test_error_inside_named_lambda_is_traced_by_name() {
+    let source = "(let ((boom (lambda (x) (/ x 0)))) (boom 5))";
+    let result = compile(source, TrustTier::Empirical, 1000, 1024 * 1024).unwrap();
+
+    let mut vm = LoadedModule::from_compilation(&result)
+        .instantiate(1)
+        .unwrap();
+    assert!(vm.run().is_err(), "division by zero should fail");
+
+    // The frame runs the body slot `boom` was named under at compile time
+    let trace = vm.get_formatted_stack_trace();
+    assert!(trace.contains("0: boom "), "{trace}");
+}
@@ -0,0 +1,42 @@
+/// Test sequential let* bindings against parallel let
+use jue_world::ast::AstNode;
+use jue_world::error::CompilationError;
+use jue_world::parser::parse;
+use jue_world::physics_compiler::compile_to_physics_world;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::VmState;
+
+fn run(source: &str) -> Result<Value, CompilationError> {
+    let ast = parse(source)?;
+    let (bytecode, constants) = compile_to_physics_world(&ast, TrustTier::Formal)?;
+    let mut vm = VmState::new(bytecode, constants, 1000, 1024, 1, 100);
+    Ok(vm.run().unwrap())
+}
+
+#[test]
+fn test_let_star_bindings_see_earlier_bindings() {
+    let source = "(let* ((x 1) (y (ffi-call add x 1))) y)";
+    assert!(matches!(parse(source).unwrap(), AstNode::LetStar { .. }));
+    assert_eq!(run(source).unwrap(), Value::Int(2));
+}
+
+#[test]
+fn test_let_bindings_do_not_see_each_other() {
+    let result = run("(let ((x 1) (y (ffi-call add x 1))) y)");
+    assert!(matches!(result, Err(CompilationError::VariableNotFound(name)) if name == "x"));
+}
+
+#[test]
+fn test_let_star_binding_does_not_see_itself() {
+    let result = run("(let* ((f (lambda (n) (f n)))) 1)");
+    assert!(matches!(result, Err(CompilationError::VariableNotFound(name)) if name == "f"));
+}
+
+#[test]
+fn test_let_star_body_call_is_a_tail_call() {
+    let source = "(lambda (n) (let* ((g (lambda (m) m)) (h g)) (h n)))";
+    let (bytecode, _) =
+        compile_to_physics_world(&parse(source).unwrap(), TrustTier::Formal).unwrap();
+    assert!(bytecode.contains(&OpCode::TailCall(1)));
+}
@@ -0,0 +1,75 @@
+/// Test macro argument count validation against the definition
+use jue_world::ast::{AstNode, Literal};
+use jue_world::error::{CompilationError, SourceLocation};
+use jue_world::macro_expander::{create_macro_expansion_context, define_macro, expand_macros};
+use jue_world::trust_tier::TrustTier;
+
+fn context_with_pair_macro() -> jue_world::macro_expander::MacroExpansionContext {
+    let mut context = create_macro_expansion_context(TrustTier::Formal);
+    define_macro(
+        &mut context,
+        "first-of".to_string(),
+        vec!["a".to_string(), "b".to_string()],
+        AstNode::Variable("a".to_string()),
+        TrustTier::Formal,
+    )
+    .unwrap();
+    context
+}
+
+fn call(arguments: Vec<AstNode>, location: SourceLocation) -> AstNode {
+    AstNode::MacroExpansion {
+        name: "first-of".to_string(),
+        arguments,
+        location,
+    }
+}
+
+#[test]
+fn test_too_few_arguments_reports_both_counts() {
+    let mut context = context_with_pair_macro();
+    let location = SourceLocation {
+        line: 3,
+        column: 7,
+        offset: 42,
+        expanded_from: None,
+    };
+
+    let result = expand_macros(
+        &call(vec![AstNode::Literal(Literal::Int(1))], location.clone()),
+        &mut context,
+    );
+
+    match result {
+        Err(CompilationError::MacroArityMismatch {
+            macro_name,
+            expected,
+            got,
+            location: at,
+        }) => {
+            assert_eq!(macro_name, "first-of");
+            assert_eq!((expected, got), (2, 1));
+            assert_eq!(at, location);
+        }
+        other => panic!("Expected MacroArityMismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_matching_arity_expands() {
+    let mut context = context_with_pair_macro();
+
+    let expanded = expand_macros(
+        &call(
+            vec![
+                AstNode::Literal(Literal::Int(1)),
+                AstNode::Literal(Literal::Int(2)),
+            ],
+            SourceLocation::default(),
+        ),
+        &mut context,
+    )
+    .unwrap();
+
+    assert_eq!(expanded, AstNode::Literal(Literal::Int(1)));
+}
@@ -0,0 +1,157 @@
+/// Test that variables bound by a macro body cannot capture call-site variables
+use jue_world::ast::{AstNode, Literal};
+use jue_world::error::SourceLocation;
+use jue_world::macro_expander::{
+    create_macro_expansion_context, define_macro, expand_macros, MacroExpansionContext,
+};
+use jue_world::physics_compiler::compile_to_physics_world;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::Value;
+use physics_world::vm::VmState;
+
+fn var(name: &str) -> AstNode {
+    AstNode::Variable(name.to_string())
+}
+
+fn int(value: i64) -> AstNode {
+    AstNode::Literal(Literal::Int(value))
+}
+
+fn let_one(name: &str, value: AstNode, body: AstNode) -> AstNode {
+    AstNode::Let {
+        bindings: vec![(name.to_string(), value)],
+        body: Box::new(body),
+        location: SourceLocation::default(),
+    }
+}
+
+/// `(add-one x)` expands to `(let ((tmp 1)) (ffi-call add tmp x))`
+fn context_with_add_one() -> MacroExpansionContext {
+    let mut context = create_macro_expansion_context(TrustTier::Formal);
+    define_macro(
+        &mut context,
+        "add-one".to_string(),
+        vec!["x".to_string()],
+        let_one(
+            "tmp",
+            int(1),
+            AstNode::FfiCall {
+                function: "add".to_string(),
+                arguments: vec![var("tmp"), var("x")],
+                location: SourceLocation::default(),
+            },
+        ),
+        TrustTier::Formal,
+    )
+    .unwrap();
+    context
+}
+
+/// `(let ((tmp 10)) (add-one tmp))`
+fn call_site() -> AstNode {
+    let_one(
+        "tmp",
+        int(10),
+        AstNode::MacroExpansion {
+            name: "add-one".to_string(),
+            arguments: vec![var("tmp")],
+            location: SourceLocation::default(),
+        },
+    )
+}
+
+#[test]
+fn test_macro_temporary_does_not_capture_call_site_variable() {
+    let mut context = context_with_add_one();
+    let expanded = expand_macros(&call_site(), &mut context).unwrap();
+
+    let AstNode::Let { bindings, body, .. } = &expanded else {
+        panic!("expected the call site's let, got {expanded}");
+    };
+    assert_eq!(bindings[0].0, "tmp");
+    let AstNode::Let {
+        bindings: inner,
+        body: inner_body,
+        ..
+    } = body.as_ref()
+    else {
+        panic!("expected the macro's let, got {body}");
+    };
+    let gensym = inner[0].0.clone();
+    assert_ne!(gensym, "tmp");
+    assert!(gensym.starts_with("tmp#"), "{gensym}");
+
+    // The macro's reference is renamed; the argument spliced in is not
+    let AstNode::FfiCall { arguments, .. } = inner_body.as_ref() else {
+        panic!("expected the macro's ffi-call, got {inner_body}");
+    };
+    assert_eq!(arguments, &[var(&gensym), var("tmp")]);
+}
+
+#[test]
+fn test_hygienic_expansion_evaluates_with_user_binding() {
+    let mut context = context_with_add_one();
+    let expanded = expand_macros(&call_site(), &mut context).unwrap();
+
+    let (bytecode, constants) = compile_to_physics_world(&expanded, TrustTier::Formal).unwrap();
+    let mut vm = VmState::new(bytecode, constants, 1000, 1024, 1, 100);
+    // 1 + the user's tmp, not 1 + the macro's own tmp
+    assert_eq!(vm.run().unwrap(), Value::Int(11));
+}
+
+#[test]
+fn test_each_expansion_gets_fresh_names() {
+    let mut context = context_with_add_one();
+    let call = AstNode::MacroExpansion {
+        name: "add-one".to_string(),
+        arguments: vec![int(2)],
+        location: SourceLocation::default(),
+    };
+
+    let first = expand_macros(&call, &mut context).unwrap();
+    let second = expand_macros(&call, &mut context).unwrap();
+    let bound = |node: &AstNode| match node {
+        AstNode::Let { bindings, .. } => bindings[0].0.clone(),
+        other => panic!("expected a let, got {other}"),
+    };
+    assert_ne!(bound(&first), bound(&second));
+    assert_eq!(context.gensym_counter, 2);
+}
+
+#[test]
+fn test_two_parameter_lambda_in_macro_body_keeps_both_parameters() {
+    let mut context = create_macro_expansion_context(TrustTier::Formal);
+    define_macro(
+        &mut context,
+        "second".to_string(),
+        vec![],
+        AstNode::Lambda {
+            parameters: vec!["a".to_string(), "b".to_string()],
+            rest: None,
+            body: Box::new(var("b")),
+            location: SourceLocation::default(),
+        },
+        TrustTier::Formal,
+    )
+    .unwrap();
+    let call = AstNode::MacroExpansion {
+        name: "second".to_string(),
+        arguments: vec![],
+        location: SourceLocation::default(),
+    };
+
+    let expanded = expand_macros(&call, &mut context).unwrap();
+    let AstNode::Lambda {
+        parameters,
+        rest,
+        body,
+        ..
+    } = &expanded
+    else {
+        panic!("expected a lambda, got {expanded}");
+    };
+    assert_eq!(parameters.len(), 2);
+    assert!(parameters.iter().all(|name| name.contains('#')));
+    assert_eq!(rest, &None);
+    assert_eq!(body.as_ref(), &var(&parameters[1]));
+}
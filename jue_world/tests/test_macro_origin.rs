@@ -0,0 +1,88 @@
+/// Test that macro-expanded nodes remember both the definition and the call site
+use jue_world::ast::{AstNode, Literal};
+use jue_world::error::{CompilationError, SourceLocation};
+use jue_world::macro_expander::{create_macro_expansion_context, define_macro, expand_macros};
+use jue_world::trust_tier::TrustTier;
+
+fn at(line: usize, column: usize) -> SourceLocation {
+    SourceLocation {
+        line,
+        column,
+        ..SourceLocation::default()
+    }
+}
+
+#[test]
+fn test_error_in_macro_body_reports_definition_and_call_site() {
+    let mut context = create_macro_expansion_context(TrustTier::Formal);
+    // The body calls a macro that does not exist: the bug is in `wrap`
+    define_macro(
+        &mut context,
+        "wrap".to_string(),
+        vec!["x".to_string()],
+        AstNode::MacroExpansion {
+            name: "missing".to_string(),
+            arguments: vec![AstNode::Variable("x".to_string())],
+            location: at(2, 5),
+        },
+        TrustTier::Formal,
+    )
+    .unwrap();
+
+    let call = AstNode::MacroExpansion {
+        name: "wrap".to_string(),
+        arguments: vec![AstNode::Literal(Literal::Int(1))],
+        location: at(10, 3),
+    };
+    let error = expand_macros(&call, &mut context).unwrap_err();
+
+    let CompilationError::ParseError { location, .. } = &error else {
+        panic!("expected a parse error, got {error:?}");
+    };
+    assert_eq!((location.line, location.column), (2, 5));
+    let site = location.expanded_from.as_ref().expect("expansion site");
+    assert_eq!(site.macro_name, "wrap");
+    assert_eq!((site.call_site.line, site.call_site.column), (10, 3));
+    assert!(error
+        .to_string()
+        .contains("in macro `wrap` defined at 2:5, expanded at 10:3"));
+}
+
+#[test]
+fn test_substituted_arguments_keep_call_site_location() {
+    let mut context = create_macro_expansion_context(TrustTier::Formal);
+    define_macro(
+        &mut context,
+        "apply".to_string(),
+        vec!["f".to_string()],
+        AstNode::Call {
+            function: Box::new(AstNode::Variable("f".to_string())),
+            arguments: vec![],
+            location: at(1, 1),
+        },
+        TrustTier::Formal,
+    )
+    .unwrap();
+
+    let argument = AstNode::Lambda {
+        parameters: vec![],
+        rest: None,
+        body: Box::new(AstNode::Literal(Literal::Int(7))),
+        location: at(20, 9),
+    };
+    let call = AstNode::MacroExpansion {
+        name: "apply".to_string(),
+        arguments: vec![argument.clone()],
+        location: at(20, 1),
+    };
+
+    let AstNode::Call {
+        function, location, ..
+    } = expand_macros(&call, &mut context).unwrap()
+    else {
+        panic!("expected the expanded call");
+    };
+    assert_eq!(location.line, 1);
+    assert_eq!(location.expanded_from.unwrap().call_site.line, 20);
+    assert_eq!(*function, argument);
+}
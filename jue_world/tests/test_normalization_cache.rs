@@ -0,0 +1,76 @@
+/// Test memoized normalization of α-equivalent proof obligations
+use core_world::core_expr::{app, lam, nat, pair, var};
+use core_world::core_kernel::normalize;
+use core_world::proof_checker::verify;
+use jue_world::core_compilation::proof_generator::NormalizationCache;
+
+#[test]
+fn test_alpha_equivalent_obligations_normalize_once() {
+    let mut cache = NormalizationCache::new();
+    // ((lambda (x) x) 1) and ((lambda (y) y) 1) share one de Bruijn term
+    let first = app(lam(var(0)), nat(1));
+    let second = app(lam(var(0)), nat(1));
+
+    let first_proof = cache.normalization_proof(&first);
+    let second_proof = cache.normalization_proof(&second);
+
+    assert_eq!(cache.normalizations(), 1);
+    assert_eq!(cache.hits(), 1);
+    let first_ends = verify(&first_proof.unwrap()).unwrap();
+    let second_ends = verify(&second_proof.unwrap()).unwrap();
+    assert_eq!(first_ends, (second.clone(), nat(1)));
+    assert_eq!(first_ends, second_ends);
+}
+
+#[test]
+fn test_cached_normal_forms_match_direct_normalization() {
+    let terms = vec![
+        app(lam(var(0)), nat(1)),
+        app(lam(var(0)), nat(2)),
+        app(lam(lam(var(1))), nat(1)),
+        app(lam(lam(var(0))), nat(1)),
+        pair(app(lam(var(0)), nat(3)), nat(4)),
+        lam(app(var(0), var(0))),
+        nat(5),
+    ];
+
+    let mut cache = NormalizationCache::new();
+    for _ in 0..2 {
+        for term in &terms {
+            assert_eq!(cache.normal_form(term), normalize(term.clone()));
+        }
+    }
+    assert_eq!(cache.len(), terms.len());
+    assert_eq!(cache.normalizations(), terms.len());
+    assert_eq!(cache.hits(), terms.len());
+}
+
+#[test]
+fn test_normal_form_and_proof_are_computed_separately() {
+    let mut cache = NormalizationCache::new();
+    let term = app(lam(var(0)), nat(6));
+
+    cache.normal_form(&term);
+    assert_eq!(cache.normalizations(), 1);
+    cache.normalization_proof(&term);
+    cache.normal_form(&term);
+    cache.normalization_proof(&term);
+
+    assert_eq!(cache.normalizations(), 2);
+    assert_eq!(cache.hits(), 2);
+    assert_eq!(cache.len(), 1);
+}
+
+#[test]
+fn test_shared_cache_is_kept_between_uses() {
+    let term = app(lam(lam(var(1))), nat(7));
+    let proof = NormalizationCache::with_shared(|cache| cache.normalization_proof(&term));
+
+    let (again, hits) = NormalizationCache::with_shared(|cache| {
+        let hits = cache.hits();
+        (cache.normalization_proof(&term), cache.hits() - hits)
+    });
+
+    assert_eq!(again.map(|p| verify(&p)), proof.map(|p| verify(&p)));
+    assert_eq!(hits, 1);
+}
@@ -0,0 +1,86 @@
+/// Test capability analysis split across worker threads
+use jue_world::ast::{AstNode, Literal};
+use jue_world::core_compilation::capability_analysis::{
+    analyze_capabilities, analyze_capabilities_parallel,
+};
+use jue_world::parser::parse;
+use physics_world::types::Capability;
+use std::time::Instant;
+
+const GATED: [&str; 4] = [
+    "read-sensor",
+    "write-actuator",
+    "network-send",
+    "persist-write",
+];
+
+/// A `list` of `width` let forms, each calling one gated FFI function
+fn wide_program(width: usize) -> AstNode {
+    let elements = (0..width)
+        .map(|i| {
+            let source = format!(
+                "(let ((x{i} (add {i} 1))) (if x{i} ({} x{i}) (sub x{i} 1)))",
+                GATED[i % GATED.len()]
+            );
+            parse(&source).unwrap()
+        })
+        .collect();
+    AstNode::List {
+        elements,
+        location: Default::default(),
+    }
+}
+
+#[test]
+fn test_parallel_matches_sequential_on_large_program() {
+    let program = wide_program(400);
+    let sequential = analyze_capabilities(&program).unwrap();
+
+    for threads in [1, 2, 3, 8, 64] {
+        assert_eq!(
+            analyze_capabilities_parallel(&program, threads).unwrap(),
+            sequential
+        );
+    }
+    assert_eq!(sequential.len(), GATED.len());
+}
+
+#[test]
+fn test_capabilities_of_split_off_nodes_are_kept() {
+    // The gated calls sit near the root, above where the tree is split
+    let program = parse("(read-sensor (network-send 1) 2)").unwrap();
+    let caps = analyze_capabilities_parallel(&program, 4).unwrap();
+
+    assert_eq!(
+        caps.as_slice(),
+        analyze_capabilities(&program).unwrap().as_slice()
+    );
+    assert!(caps.contains(&Capability::IoReadSensor));
+    assert!(caps.contains(&Capability::IoNetwork));
+}
+
+#[test]
+fn test_leaf_program_needs_no_threads() {
+    let program = AstNode::Literal(Literal::Int(1));
+    assert!(analyze_capabilities_parallel(&program, 8)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_parallel_capability_analysis_benchmark() {
+    let program = wide_program(20_000);
+
+    let start_time = Instant::now();
+    let sequential = analyze_capabilities(&program).unwrap();
+    let sequential_duration = start_time.elapsed();
+
+    let start_time = Instant::now();
+    let parallel = analyze_capabilities_parallel(&program, 4).unwrap();
+    let parallel_duration = start_time.elapsed();
+
+    assert_eq!(parallel, sequential);
+    println!(
+        "Analyzed 20000-form program: 1 thread {sequential_duration:?}, 4 threads {parallel_duration:?}"
+    );
+}
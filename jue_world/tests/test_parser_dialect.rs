@@ -0,0 +1,40 @@
+/// Test the configurable bracket dialect of the parser
+use jue_world::error::CompilationError;
+use jue_world::parser::{parse, parse_with_config, ParserConfig};
+
+#[test]
+fn test_square_brackets_parse_like_parens() {
+    let parens = parse("(f 1 2 3)").unwrap();
+
+    for config in [ParserConfig::permissive(), ParserConfig::strict()] {
+        assert_eq!(parse_with_config("[f 1 2 3]", config).unwrap(), parens);
+    }
+}
+
+#[test]
+fn test_default_dialect_rejects_square_brackets() {
+    assert!(parse_with_config("[f 1 2 3]", ParserConfig::default()).is_err());
+}
+
+#[test]
+fn test_mixed_brackets_depend_on_strictness() {
+    let nested = parse("(let ((x 1)) x)").unwrap();
+    let mixed = "(let ([x 1)) x]";
+
+    assert_eq!(
+        parse_with_config(mixed, ParserConfig::permissive()).unwrap(),
+        nested
+    );
+    match parse_with_config(mixed, ParserConfig::strict()) {
+        Err(CompilationError::ParseError { message, .. }) => {
+            assert!(message.contains("Mismatched brackets"), "{message}");
+        }
+        other => panic!("Expected ParseError, got {:?}", other),
+    }
+
+    let matched = "(let ([x 1]) x)";
+    assert_eq!(
+        parse_with_config(matched, ParserConfig::strict()).unwrap(),
+        nested
+    );
+}
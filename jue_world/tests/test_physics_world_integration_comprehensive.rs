@@ -113,7 +113,8 @@ mod tests {
         assert!(bytecode.contains(&OpCode::SetLocal(0)));
         assert!(bytecode.contains(&OpCode::SetLocal(1)));
         assert!(bytecode.contains(&OpCode::GetLocal(0)));
-        assert!(bytecode.contains(&OpCode::GetLocal(1)));
+        // y is read straight after being stored, so it is duplicated instead
+        assert!(bytecode.contains(&OpCode::Dup));
 
         // Test execution
         let mut vm = VmState::new(bytecode, vec![], 100, 1024, 1, 100);
@@ -131,6 +132,7 @@ mod tests {
             bindings: vec![("multiplier".to_string(), AstNode::Literal(Literal::Int(3)))],
             body: Box::new(AstNode::Lambda {
                 parameters: vec!["x".to_string()],
+                rest: None,
                 body: Box::new(AstNode::Call {
                     function: Box::new(AstNode::Symbol("mul".to_string())),
                     arguments: vec![
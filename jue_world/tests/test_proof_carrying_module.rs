@@ -0,0 +1,107 @@
+/// Test load-time proof checking of proof-carrying modules
+use core_world::core_expr::{app, lam, nat, var};
+use core_world::proof_checker::Proof;
+use core_world::{prove_beta, VerifyError};
+use jue_world::compiler::loaded_module::LoadedModule;
+use jue_world::core_compiler::compile;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+
+fn verified_module() -> LoadedModule {
+    let result = compile("42", TrustTier::Formal, 1000, 1024 * 1024).unwrap();
+    let expr = app(lam(var(0)), nat(42));
+    LoadedModule::from_compilation(&result).with_proof(&expr, &prove_beta(expr.clone()))
+}
+
+#[test]
+fn test_intact_proof_verifies_and_runs() {
+    let module = verified_module();
+    assert!(module.verify_proof().is_ok());
+
+    let mut vm = module.instantiate(1).unwrap();
+    assert_eq!(vm.run().unwrap(), Value::Int(42));
+}
+
+#[test]
+fn test_module_survives_serialization() {
+    let json = serde_json::to_string(&verified_module()).unwrap();
+    let module: LoadedModule = serde_json::from_str(&json).unwrap();
+    assert!(module.verify_proof().is_ok());
+}
+
+#[test]
+fn test_tampered_proof_is_rejected() {
+    let expr = app(lam(var(0)), nat(42));
+    let forged = Proof::BetaStep {
+        redex: expr.clone(),
+        contractum: nat(7),
+    };
+    let module = verified_module().with_proof(&expr, &forged);
+
+    assert!(matches!(
+        module.verify_proof(),
+        Err(VerifyError::ProofRuleViolation(_))
+    ));
+    assert!(module.instantiate(1).is_err());
+}
+
+#[test]
+fn test_proof_about_another_expression_is_rejected() {
+    let mut module = verified_module();
+    let other = app(lam(var(0)), nat(1));
+    module.core_proof = Some(core_world::serialize_proof(&prove_beta(other)));
+
+    assert!(matches!(
+        module.verify_proof(),
+        Err(VerifyError::ProofRuleViolation(_))
+    ));
+}
+
+#[test]
+fn test_swapped_code_is_rejected() {
+    let mut module = verified_module();
+    module.bytecode = vec![OpCode::Int(7)];
+    assert!(matches!(
+        module.verify_proof(),
+        Err(VerifyError::ProofRuleViolation(_))
+    ));
+    assert!(module.instantiate(1).is_err());
+
+    let mut module = verified_module();
+    module.constants.push(Value::Int(7));
+    assert!(module.verify_proof().is_err());
+
+    // A proof without the hash of the code it was attached to proves nothing
+    let mut module = verified_module();
+    module.proof_code_hash = None;
+    assert!(matches!(
+        module.verify_proof(),
+        Err(VerifyError::InvalidProofStructure)
+    ));
+}
+
+#[test]
+fn test_corrupted_proof_bytes_are_rejected() {
+    let mut module = verified_module();
+    module.core_proof.as_mut().unwrap().truncate(3);
+
+    assert!(matches!(
+        module.verify_proof(),
+        Err(VerifyError::InvalidProofStructure)
+    ));
+}
+
+#[test]
+fn test_module_without_proof_runs_but_does_not_verify() {
+    let result = compile("42", TrustTier::Empirical, 1000, 1024 * 1024).unwrap();
+    let module = LoadedModule::from_compilation(&result);
+
+    assert!(matches!(
+        module.verify_proof(),
+        Err(VerifyError::InvalidProofStructure)
+    ));
+    assert_eq!(
+        module.instantiate(1).unwrap().run().unwrap(),
+        Value::Int(42)
+    );
+}
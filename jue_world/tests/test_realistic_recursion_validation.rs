@@ -14,6 +14,7 @@ mod realistic_recursion_tests {
                 "identity".to_string(),
                 AstNode::Lambda {
                     parameters: vec!["x".to_string()],
+                    rest: None,
                     body: Box::new(AstNode::Variable("x".to_string())),
                     location: Default::default(),
                 },
@@ -46,6 +47,7 @@ mod realistic_recursion_tests {
                     "func1".to_string(),
                     AstNode::Lambda {
                         parameters: vec!["x".to_string()],
+                        rest: None,
                         body: Box::new(AstNode::Variable("x".to_string())),
                         location: Default::default(),
                     },
@@ -54,6 +56,7 @@ mod realistic_recursion_tests {
                     "func2".to_string(),
                     AstNode::Lambda {
                         parameters: vec!["y".to_string()],
+                        rest: None,
                         body: Box::new(AstNode::Variable("y".to_string())),
                         location: Default::default(),
                     },
@@ -91,11 +94,13 @@ mod realistic_recursion_tests {
                 "outer".to_string(),
                 AstNode::Lambda {
                     parameters: vec!["x".to_string()],
+                    rest: None,
                     body: Box::new(AstNode::Let {
                         bindings: vec![(
                             "inner".to_string(),
                             AstNode::Lambda {
                                 parameters: vec!["y".to_string()],
+                                rest: None,
                                 body: Box::new(AstNode::Variable("y".to_string())),
                                 location: Default::default(),
                             },
@@ -138,13 +143,14 @@ mod realistic_recursion_tests {
     /// Test recursive lambda with closure capture
     #[test]
     fn test_recursive_lambda_with_capture_compilation() {
-        let ast = AstNode::Let {
+        let ast = AstNode::LetStar {
             bindings: vec![
                 ("captured".to_string(), AstNode::Literal(Literal::Int(42))),
                 (
                     "with_capture".to_string(),
                     AstNode::Lambda {
                         parameters: vec!["x".to_string()],
+                        rest: None,
                         body: Box::new(AstNode::Variable("captured".to_string())),
                         location: Default::default(),
                     },
@@ -183,6 +189,7 @@ mod realistic_recursion_tests {
                 "test_func".to_string(),
                 AstNode::Lambda {
                     parameters: vec!["x".to_string()],
+                    rest: None,
                     body: Box::new(AstNode::Variable("x".to_string())),
                     location: Default::default(),
                 },
@@ -236,6 +243,7 @@ mod realistic_recursion_tests {
                     func_name.clone(),
                     AstNode::Lambda {
                         parameters: vec!["x".to_string()],
+                        rest: None,
                         body: Box::new(AstNode::Variable("x".to_string())),
                         location: Default::default(),
                     },
@@ -267,6 +275,7 @@ mod realistic_recursion_tests {
                 AstNode::Lambda {
                     parameters: vec![], // No parameters
                     body: Box::new(AstNode::Literal(Literal::Int(0))),
+                    rest: None,
                     location: Default::default(),
                 },
             )],
@@ -297,6 +306,7 @@ mod realistic_recursion_tests {
                 "many_params".to_string(),
                 AstNode::Lambda {
                     parameters: parameters.clone(),
+                    rest: None,
                     body: Box::new(AstNode::Variable("param_0".to_string())),
                     location: Default::default(),
                 },
@@ -338,6 +348,7 @@ mod realistic_recursion_tests {
                     name.to_string(),
                     AstNode::Lambda {
                         parameters: vec!["x".to_string()],
+                        rest: None,
                         body: Box::new(literal.clone()),
                         location: Default::default(),
                     },
@@ -368,6 +379,7 @@ mod realistic_recursion_tests {
                 "structured_func".to_string(),
                 AstNode::Lambda {
                     parameters: vec!["x".to_string()],
+                    rest: None,
                     body: Box::new(AstNode::Variable("x".to_string())),
                     location: Default::default(),
                 },
@@ -0,0 +1,119 @@
+/// Test lambdas with a rest parameter collecting surplus arguments into a list
+use jue_world::ast::AstNode;
+use jue_world::error::CompilationError;
+use jue_world::parser::parse;
+use jue_world::physics_compiler::compile_to_physics_world;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::opcodes::closure::create_closure_body;
+use physics_world::vm::opcodes::list_ops;
+use physics_world::vm::VmState;
+
+const REST_LAMBDA: &str = "(lambda (a b . rest) rest)";
+
+/// Call the compiled `REST_LAMBDA` with `args` and return the VM and result
+fn call_rest_lambda(args: &[i64]) -> (VmState, Value) {
+    let (bytecode, _) =
+        compile_to_physics_world(&parse(REST_LAMBDA).unwrap(), TrustTier::Formal).unwrap();
+    assert!(matches!(bytecode[0], OpCode::MakeClosure(2, _)));
+    let mut body = bytecode[1..].to_vec();
+    body.push(OpCode::Ret);
+
+    let mut program: Vec<OpCode> = args.iter().map(|&arg| OpCode::Int(arg)).collect();
+    program.push(OpCode::MakeClosure(0, 0));
+    program.push(OpCode::Call(args.len() as u16));
+    let mut vm = VmState::new(program, vec![Value::Nil], 1000, 4096, 1, 100);
+    let body_ptr = create_closure_body(&mut vm, body).unwrap();
+    vm.constant_pool[0] = Value::Closure(body_ptr);
+
+    let result = vm.run().unwrap();
+    (vm, result)
+}
+
+/// The integers in a list of pairs, read through the VM's car and cdr
+fn list_elements(vm: &mut VmState, list: &Value) -> Vec<i64> {
+    let mut elements = Vec::new();
+    let mut current = list.clone();
+    while let Value::Pair(_) = current {
+        vm.stack.push(current.clone());
+        list_ops::handle_car(vm).unwrap();
+        match vm.stack.pop() {
+            Some(Value::Int(element)) => elements.push(element),
+            car => panic!("expected an integer car, got {car:?}"),
+        }
+        vm.stack.push(current);
+        list_ops::handle_cdr(vm).unwrap();
+        current = vm.stack.pop().unwrap();
+    }
+    assert_eq!(current, Value::Nil, "improper list");
+    elements
+}
+
+#[test]
+fn test_dotted_parameter_list_parses_rest() {
+    let ast = parse(REST_LAMBDA).unwrap();
+    let AstNode::Lambda {
+        parameters, rest, ..
+    } = &ast
+    else {
+        panic!("expected a lambda, got {ast}");
+    };
+    assert_eq!(parameters, &["a", "b"]);
+    assert_eq!(rest.as_deref(), Some("rest"));
+    assert_eq!(ast.to_string(), REST_LAMBDA);
+
+    assert!(parse("(lambda (a . rest more) a)").is_err());
+    assert!(parse("(lambda (a .) a)").is_err());
+}
+
+#[test]
+fn test_rest_is_nil_without_surplus_arguments() {
+    let (_, rest) = call_rest_lambda(&[1, 2]);
+    assert_eq!(rest, Value::Nil);
+}
+
+#[test]
+fn test_rest_holds_one_surplus_argument() {
+    let (mut vm, rest) = call_rest_lambda(&[1, 2, 3]);
+    assert_eq!(list_elements(&mut vm, &rest), [3]);
+}
+
+#[test]
+fn test_rest_holds_the_tail_in_order() {
+    let (mut vm, rest) = call_rest_lambda(&[1, 2, 3, 4, 5]);
+    assert_eq!(list_elements(&mut vm, &rest), [3, 4, 5]);
+}
+
+#[test]
+fn test_fixed_parameters_are_unaffected() {
+    let (bytecode, _) = compile_to_physics_world(
+        &parse("(lambda (a b . rest) b)").unwrap(),
+        TrustTier::Formal,
+    )
+    .unwrap();
+    assert_eq!(
+        &bytecode[1..],
+        [OpCode::CollectRest(2), OpCode::GetLocal(1)]
+    );
+
+    let (plain, _) =
+        compile_to_physics_world(&parse("(lambda (a b) b)").unwrap(), TrustTier::Formal).unwrap();
+    assert!(!plain.contains(&OpCode::CollectRest(2)));
+}
+
+/// Line and column a parse error of `source` reports
+fn parse_error_position(source: &str) -> (usize, usize) {
+    match parse(source) {
+        Err(CompilationError::ParseError { location, .. }) => (location.line, location.column),
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_malformed_rest_parameters_report_their_location() {
+    // Nothing but the closing parenthesis after the dot
+    assert_eq!(parse_error_position("(lambda\n  (a . ) a)"), (2, 6));
+    // A parameter after the rest parameter
+    assert_eq!(parse_error_position("(lambda\n  (a . rest b) a)"), (2, 8));
+    assert_eq!(parse_error_position("(defmacro m\n  (a . rest) a)"), (2, 6));
+}
@@ -0,0 +1,89 @@
+/// Test that sandboxed comptime execution stays within its resource budgets
+use jue_world::error::CompilationError;
+use jue_world::resource_limits::{Resource, ResourceLimits};
+use jue_world::sandboxed_comptime::{SandboxedComptimeEnv, SandboxedComptimeExecutor};
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{Capability, OpCode, Value};
+
+#[test]
+fn test_infinite_loop_exhausts_step_budget() {
+    let mut executor = SandboxedComptimeExecutor::new(TrustTier::Experimental, 1000, 1024 * 1024);
+
+    // Jumps back to itself forever
+    let result = executor.execute(vec![OpCode::Nil, OpCode::Pop, OpCode::Jmp(-3)]);
+
+    assert!(matches!(
+        result,
+        Err(CompilationError::ResourceExhausted {
+            resource: Resource::Steps,
+            limit: 1000,
+        })
+    ));
+    assert_eq!(executor.env.steps_remaining, 0);
+}
+
+#[test]
+fn test_growing_string_exhausts_memory_budget() {
+    let mut executor = SandboxedComptimeExecutor::new(TrustTier::Experimental, 1000, 4096);
+    executor.constants = vec![Value::String("ab".to_string())];
+
+    // Doubles the string on every pass
+    let bytecode = vec![
+        OpCode::LoadString(0),
+        OpCode::Dup,
+        OpCode::StrConcat,
+        OpCode::Jmp(-3),
+    ];
+
+    assert!(matches!(
+        executor.execute(bytecode),
+        Err(CompilationError::ResourceExhausted {
+            resource: Resource::Memory,
+            limit: 4096,
+        })
+    ));
+}
+
+#[test]
+fn test_bounded_loop_finishes_within_budget() {
+    let mut executor = SandboxedComptimeExecutor::new(TrustTier::Empirical, 100, 1024);
+
+    // Counts 3 down to 0
+    let bytecode = vec![
+        OpCode::Int(3),
+        OpCode::Dup,
+        OpCode::JmpIfFalse(3),
+        OpCode::Int(1),
+        OpCode::Sub,
+        OpCode::Jmp(-5),
+    ];
+
+    let result = executor.execute(bytecode).unwrap();
+    assert_eq!(result.value, Value::Int(0));
+    assert_eq!(result.steps_used, 1 + 3 * 5 + 2);
+    assert_eq!(executor.env.steps_remaining, 100 - result.steps_used);
+}
+
+#[test]
+fn test_sandbox_denies_io_capabilities_for_every_tier() {
+    for tier in [
+        TrustTier::Formal,
+        TrustTier::Verified,
+        TrustTier::Empirical,
+        TrustTier::Experimental,
+    ] {
+        let env = SandboxedComptimeEnv::with_limits(tier, ResourceLimits::default());
+        for capability in [
+            Capability::IoReadSensor,
+            Capability::IoWriteActuator,
+            Capability::IoNetwork,
+            Capability::IoPersist,
+            Capability::SysClock,
+        ] {
+            assert!(
+                !env.has_capability(&capability),
+                "{tier:?} has {capability:?}"
+            );
+        }
+    }
+}
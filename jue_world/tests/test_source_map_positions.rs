@@ -0,0 +1,72 @@
+/// Test that compiled bytecode maps back to the source lines it came from
+use jue_world::core_compiler::compile;
+use jue_world::error::{SourceLocation, SourceMap};
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::OpCode;
+
+const SOURCE: &str = "(let ((x 1))
+  (if (integer? x)
+      (pair? x)
+      nil))";
+
+fn position_of(bytecode: &[OpCode], wanted: &OpCode) -> usize {
+    bytecode
+        .iter()
+        .position(|op| op == wanted)
+        .unwrap_or_else(|| panic!("no {wanted:?} in {bytecode:?}"))
+}
+
+#[test]
+fn test_instructions_map_to_their_lines() {
+    let result = compile(SOURCE, TrustTier::Formal, 1000, 1024).unwrap();
+    let map = &result.source_map;
+
+    let is_pair = map
+        .location_for(position_of(&result.bytecode, &OpCode::IsPair))
+        .unwrap();
+    assert_eq!((is_pair.line, is_pair.column), (3, 7));
+
+    let is_int = map
+        .location_for(position_of(&result.bytecode, &OpCode::IsInt))
+        .unwrap();
+    assert_eq!((is_int.line, is_int.column), (2, 7));
+
+    // The let's own instructions and the if's jump
+    assert_eq!(map.location_for(0).unwrap().line, 1);
+    let jump = position_of(&result.bytecode, &OpCode::JmpIfFalse(3));
+    assert_eq!(map.location_for(jump).unwrap().line, 2);
+}
+
+#[test]
+fn test_sandbox_wrapper_keeps_locations_aligned() {
+    let formal = compile(SOURCE, TrustTier::Formal, 1000, 1024).unwrap();
+    let sandboxed = compile(SOURCE, TrustTier::Experimental, 1000, 1024).unwrap();
+    assert!(sandboxed.sandboxed);
+
+    let ip = position_of(&sandboxed.bytecode, &OpCode::IsPair);
+    assert_eq!(ip, position_of(&formal.bytecode, &OpCode::IsPair) + 3);
+    assert_eq!(sandboxed.source_map.location_for(ip).unwrap().line, 3);
+
+    // Wrapper instructions take the location of the code they surround
+    assert_eq!(sandboxed.source_map.location_for(0).unwrap().line, 1);
+    let last = sandboxed.bytecode.len() - 1;
+    assert_eq!(sandboxed.source_map.location_for(last).unwrap().line, 2);
+}
+
+#[test]
+fn test_location_for_falls_back_to_preceding_mapping() {
+    let at = |line| SourceLocation {
+        line,
+        ..SourceLocation::default()
+    };
+    let mut map = SourceMap::new();
+    assert_eq!(map.location_for(0), None);
+
+    map.add_mapping(2, at(1));
+    map.add_mapping(5, at(4));
+    assert_eq!(map.location_for(1), None);
+    assert_eq!(map.location_for(2), Some(at(1)));
+    assert_eq!(map.location_for(4), Some(at(1)));
+    assert_eq!(map.location_for(5), Some(at(4)));
+    assert_eq!(map.location_for(40), Some(at(4)));
+}
@@ -0,0 +1,92 @@
+/// Test incremental tokenization of source ranges
+use jue_world::token::Token;
+use jue_world::tokenizer::{SpannedToken, Tokenizer};
+use std::ops::Range;
+
+const PROGRAM: &str = "(define greeting \"hello\nworld\")\n(let ((x 42))\n  (add x 3.5))\n";
+
+fn full_tokens_overlapping(source: &str, range: Range<usize>) -> Vec<SpannedToken> {
+    Tokenizer::new(source.to_string(), "test")
+        .tokenize_spanned()
+        .into_iter()
+        .filter(|t| t.span.start < range.end && t.span.end > range.start)
+        .collect()
+}
+
+#[test]
+fn test_spans_cover_token_text() {
+    let tokens = Tokenizer::new(PROGRAM.to_string(), "test").tokenize_spanned();
+
+    assert_eq!(tokens[0].token, Token::OpenParen);
+    assert_eq!(&PROGRAM[tokens[1].span.clone()], "define");
+    assert_eq!(&PROGRAM[tokens[3].span.clone()], "\"hello\nworld\"");
+}
+
+#[test]
+fn test_range_matches_filtered_full_tokenization() {
+    // Each line of the program, plus ranges starting mid-token and mid-string
+    let second_line = PROGRAM.find("(let").unwrap();
+    let third_line = PROGRAM.find("  (add").unwrap();
+    let inside_string = PROGRAM.find("world").unwrap();
+    let inside_number = PROGRAM.find("42").unwrap() + 1;
+    let ranges = [
+        0..second_line,
+        second_line..third_line,
+        third_line..PROGRAM.len(),
+        inside_string..second_line,
+        inside_number..third_line + 4,
+    ];
+
+    for range in ranges {
+        assert_eq!(
+            Tokenizer::tokenize_range(PROGRAM, range.clone()),
+            full_tokens_overlapping(PROGRAM, range.clone()),
+            "range {:?}",
+            range
+        );
+    }
+}
+
+#[test]
+fn test_range_starting_inside_string_yields_whole_string() {
+    let inside_string = PROGRAM.find("world").unwrap();
+    let tokens = Tokenizer::tokenize_range(PROGRAM, inside_string..inside_string + 2);
+
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].token, Token::String("hello\nworld".to_string()));
+}
+
+#[test]
+fn test_range_ignores_quotes_in_comments_and_semicolons_in_strings() {
+    let source = "; one \" quote\n(print \"a;b\nc\")\n#| block\n(not code) |# (f 1)\n";
+    let inside_string = source.find("c\")").unwrap();
+    let inside_block = source.find("(not").unwrap();
+    let ranges = [
+        inside_string..inside_string + 1,
+        inside_block..source.len(),
+        source.find("(print").unwrap()..source.len(),
+    ];
+
+    for range in ranges {
+        assert_eq!(
+            Tokenizer::tokenize_range(source, range.clone()),
+            full_tokens_overlapping(source, range.clone()),
+            "range {:?}",
+            range
+        );
+    }
+}
+
+#[test]
+fn test_range_inside_multibyte_character_does_not_panic() {
+    let source = "(print \"héllo\")\n(λ x)\n";
+    let inside_e = source.find('é').unwrap() + 1;
+    let inside_lambda = source.find('λ').unwrap() + 1;
+
+    let tokens = Tokenizer::tokenize_range(source, inside_e..inside_lambda);
+
+    assert_eq!(
+        tokens,
+        full_tokens_overlapping(source, inside_e - 1..inside_lambda - 1)
+    );
+}
@@ -0,0 +1,60 @@
+/// Test rendering structured errors as source-annotated reports
+use jue_world::error::SourceLocation;
+use jue_world::structured_error::{ErrorType, StructuredErrorBuilder};
+
+const SOURCE: &str = "(define x 1)\n(print (undefined-fn x))\n";
+
+fn error_at(line: usize, column: usize, span_length: usize) -> StructuredErrorBuilder {
+    StructuredErrorBuilder::new(ErrorType::TypeError, "Unbound symbol".to_string())
+        .with_location(SourceLocation {
+            line,
+            column,
+            offset: 0,
+            expanded_from: None,
+        })
+        .with_span_length(span_length)
+        .with_error_code("UNBOUND")
+}
+
+#[test]
+fn test_render_underlines_span() {
+    let report = error_at(2, 9, 12).build().render(SOURCE);
+
+    assert_eq!(
+        report,
+        "error[UNBOUND]: Unbound symbol\n \
+         --> 2:9\n  \
+         |\n\
+         2 | (print (undefined-fn x))\n  \
+         |         ^^^^^^^^^^^^\n"
+    );
+
+    let lines: Vec<&str> = report.lines().collect();
+    let caret_start = lines[4].find('^').unwrap();
+    let caret_end = lines[4].rfind('^').unwrap() + 1;
+    assert_eq!(&lines[3][caret_start..caret_end], "undefined-fn");
+}
+
+#[test]
+fn test_render_clips_span_and_marks_points() {
+    let clipped = error_at(1, 9, 100).build().render(SOURCE);
+    assert!(clipped.ends_with("\n  |         ^^^^\n"), "{clipped}");
+
+    let point = error_at(1, 1, 0).build().render(SOURCE);
+    assert!(point.ends_with("\n  | ^\n"), "{point}");
+}
+
+#[test]
+fn test_render_includes_suggestions_and_skips_missing_lines() {
+    let report = error_at(2, 9, 12)
+        .with_recovery_suggestion("Define the function first")
+        .build()
+        .render(SOURCE);
+    assert!(
+        report.ends_with("  = help: Define the function first\n"),
+        "{report}"
+    );
+
+    let outside = error_at(9, 1, 3).build().render(SOURCE);
+    assert_eq!(outside, "error[UNBOUND]: Unbound symbol\n --> 9:1\n");
+}
@@ -0,0 +1,64 @@
+/// Test that symbols interned at compile time resolve to the same names at runtime
+use jue_world::core_compiler::{compile, CompilationResult};
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::VmState;
+
+fn run(result: CompilationResult) -> (VmState, Value) {
+    let mut vm = VmState::new(result.bytecode, result.constants, 1000, 1024, 1, 100);
+    vm.attach_symbol_table(result.symbol_table);
+    let value = vm.run().unwrap();
+    (vm, value)
+}
+
+#[test]
+fn test_compiled_symbol_resolves_after_running() {
+    let result = compile(
+        "(if true 'greeting 'farewell)",
+        TrustTier::Experimental,
+        1000,
+        1024,
+    )
+    .unwrap();
+    assert_eq!(result.symbol_table.len(), 2);
+
+    let (vm, value) = run(result);
+
+    assert!(matches!(value, Value::Symbol(_)));
+    assert_eq!(vm.resolve_symbol(&value), Some("greeting"));
+}
+
+#[test]
+fn test_symbol_table_survives_serialization() {
+    let result = compile(
+        "(if false 'greeting 'farewell)",
+        TrustTier::Experimental,
+        1000,
+        1024,
+    )
+    .unwrap();
+    assert!(result
+        .bytecode
+        .iter()
+        .any(|op| matches!(op, OpCode::Symbol(_))));
+
+    let json = serde_json::to_string(&result).unwrap();
+    let restored: CompilationResult = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.symbol_table, result.symbol_table);
+
+    let (vm, value) = run(restored);
+    assert_eq!(vm.resolve_symbol(&value), Some("farewell"));
+}
+
+#[test]
+fn test_symbol_operand_never_indexes_the_constant_pool() {
+    let mut vm = VmState::new(
+        vec![OpCode::Symbol(0)],
+        vec![Value::String("greeting".to_string())],
+        1000,
+        1024,
+        1,
+        100,
+    );
+    assert!(vm.run().is_err());
+}
@@ -0,0 +1,101 @@
+/// Test the tail-position analysis and the calls the compiler emits from it
+use jue_world::analysis::{tail_positions, NodeId};
+use jue_world::ast::AstNode;
+use jue_world::parser::parse;
+use jue_world::physics_compiler::PhysicsWorldCompiler;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::OpCode;
+
+const PROGRAM: &str = "(lambda (f x) (let ((y (f x))) (if (f y) (f x) (f y))))";
+
+#[test]
+fn test_nested_let_if_tail_positions() {
+    let ast = parse(PROGRAM).unwrap();
+    let tails = tail_positions(&ast);
+
+    let AstNode::Lambda { body, .. } = &ast else {
+        panic!("expected lambda, got {:?}", ast);
+    };
+    let AstNode::Let {
+        bindings,
+        body: let_body,
+        ..
+    } = body.as_ref()
+    else {
+        panic!("expected let, got {:?}", body);
+    };
+    let AstNode::If {
+        condition,
+        then_branch,
+        else_branch,
+        ..
+    } = let_body.as_ref()
+    else {
+        panic!("expected if, got {:?}", let_body);
+    };
+
+    let expected_tails = [
+        body.as_ref(),
+        let_body.as_ref(),
+        then_branch.as_ref(),
+        else_branch.as_ref(),
+    ];
+    for node in expected_tails {
+        assert!(
+            tails.contains(&NodeId::of(node)),
+            "{:?} should be in tail position",
+            node
+        );
+    }
+    for node in [&ast, &bindings[0].1, condition.as_ref()] {
+        assert!(
+            !tails.contains(&NodeId::of(node)),
+            "{:?} should not be in tail position",
+            node
+        );
+    }
+    assert_eq!(tails.len(), expected_tails.len());
+}
+
+#[test]
+fn test_call_arguments_are_not_tail_positions() {
+    let ast = parse("(lambda (f) (f (f 1)))").unwrap();
+    let tails = tail_positions(&ast);
+
+    let AstNode::Lambda { body, .. } = &ast else {
+        panic!("expected lambda, got {:?}", ast);
+    };
+    let AstNode::Call {
+        function,
+        arguments,
+        ..
+    } = body.as_ref()
+    else {
+        panic!("expected call, got {:?}", body);
+    };
+    assert!(tails.contains(&NodeId::of(body)));
+    assert!(!tails.contains(&NodeId::of(function)));
+    assert!(!tails.contains(&NodeId::of(&arguments[0])));
+}
+
+#[test]
+fn test_compiler_emits_tail_calls_at_analysed_positions() {
+    let ast = parse(PROGRAM).unwrap();
+    let mut compiler = PhysicsWorldCompiler::new(TrustTier::Empirical);
+    let bytecode = compiler.compile_to_physics(&ast).unwrap();
+
+    let calls: Vec<&OpCode> = bytecode
+        .iter()
+        .filter(|op| matches!(op, OpCode::Call(_) | OpCode::TailCall(_)))
+        .collect();
+    // Binding value and condition are ordinary calls; both branches are tail calls
+    assert_eq!(
+        calls,
+        vec![
+            &OpCode::Call(1),
+            &OpCode::Call(1),
+            &OpCode::TailCall(1),
+            &OpCode::TailCall(1),
+        ]
+    );
+}
@@ -0,0 +1,55 @@
+/// Test the syntactic non-termination heuristic
+use jue_world::analysis::check_termination;
+use jue_world::core_compiler::compile;
+use jue_world::error::CompilationWarning;
+use jue_world::parser::parse;
+use jue_world::trust_tier::TrustTier;
+
+#[test]
+fn test_recursion_with_unchanged_argument_is_flagged() {
+    let ast = parse("(define f (lambda (n) (f n)))").unwrap();
+
+    let warnings = check_termination(&ast);
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].function, "f");
+    assert_eq!(warnings[0].parameters, vec!["n".to_string()]);
+}
+
+#[test]
+fn test_recursion_with_decreasing_argument_is_not_flagged() {
+    let ast = parse("(define f (lambda (n) (f (- n 1))))").unwrap();
+
+    assert!(check_termination(&ast).is_empty());
+}
+
+#[test]
+fn test_rebound_parameter_is_not_treated_as_unchanged() {
+    let ast = parse("(letrec ((f (lambda (n) (let ((n (- n 1))) (f n))))) (f 3))").unwrap();
+
+    assert!(check_termination(&ast).is_empty());
+}
+
+#[test]
+fn test_letrec_self_call_with_swapped_arguments_is_not_flagged() {
+    let flagged = parse("(letrec ((f (lambda (a b) (if a (f a b) b)))) (f true 1))").unwrap();
+    let swapped = parse("(letrec ((f (lambda (a b) (if a (f b a) b)))) (f true 1))").unwrap();
+
+    assert_eq!(check_termination(&flagged).len(), 1);
+    assert!(check_termination(&swapped).is_empty());
+}
+
+#[test]
+fn test_compile_reports_termination_warning() {
+    let result = compile(
+        "(letrec ((loop (lambda (n) (loop n)))) 0)",
+        TrustTier::Empirical,
+        1000,
+        1024,
+    )
+    .unwrap();
+
+    assert!(result.warnings.iter().any(
+        |w| matches!(w, CompilationWarning::PossiblyNonTerminating(t) if t.function == "loop")
+    ));
+}
@@ -0,0 +1,39 @@
+/// Test trust-tier-specific default resource limits
+use jue_world::core_compiler::compile_with_tier_defaults;
+use jue_world::trust_tier::TrustTier;
+
+#[test]
+fn test_compile_uses_tier_limits() {
+    let source = "(if true 1 2)";
+
+    let experimental = compile_with_tier_defaults(source, TrustTier::Experimental).unwrap();
+    let formal = compile_with_tier_defaults(source, TrustTier::Formal).unwrap();
+
+    assert_eq!(
+        (experimental.step_limit, experimental.memory_limit),
+        TrustTier::Experimental.default_limits()
+    );
+    assert_eq!(
+        (formal.step_limit, formal.memory_limit),
+        TrustTier::Formal.default_limits()
+    );
+    assert!(experimental.step_limit < formal.step_limit);
+    assert!(experimental.memory_limit < formal.memory_limit);
+}
+
+#[test]
+fn test_limits_loosen_with_trust() {
+    let tiers = [
+        TrustTier::Experimental,
+        TrustTier::Empirical,
+        TrustTier::Verified,
+        TrustTier::Formal,
+    ];
+
+    for pair in tiers.windows(2) {
+        let (steps, memory) = pair[0].default_limits();
+        let (next_steps, next_memory) = pair[1].default_limits();
+        assert!(steps < next_steps, "{:?} vs {:?}", pair[0], pair[1]);
+        assert!(memory < next_memory, "{:?} vs {:?}", pair[0], pair[1]);
+    }
+}
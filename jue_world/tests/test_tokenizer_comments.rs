@@ -0,0 +1,66 @@
+/// Test line and block comment handling in the tokenizer
+use jue_world::token::Token;
+use jue_world::tokenizer::Tokenizer;
+
+const PLAIN: &str = "(define x 42)\n(add x \"a ; b\")\n";
+const COMMENTED: &str = "; leading note\n(define x #| inline |# 42) ; trailing\n#| outer #| nested |# still outer |#\n(add x \"a ; b\")\n";
+
+fn tokens(source: &str) -> Vec<Token> {
+    Tokenizer::new(source.to_string(), "test").tokenize()
+}
+
+#[test]
+fn test_comments_do_not_change_program_tokens() {
+    assert_eq!(tokens(COMMENTED), tokens(PLAIN));
+}
+
+#[test]
+fn test_preserving_mode_emits_comments_with_spans() {
+    let spanned = Tokenizer::new(COMMENTED.to_string(), "test")
+        .preserving_comments()
+        .tokenize_spanned();
+
+    let comments: Vec<&str> = spanned
+        .iter()
+        .filter_map(|t| match &t.token {
+            Token::Comment { span } => {
+                assert_eq!(span, &t.span);
+                Some(&COMMENTED[span.clone()])
+            }
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(
+        comments,
+        vec![
+            "; leading note",
+            "#| inline |#",
+            "; trailing",
+            "#| outer #| nested |# still outer |#",
+        ]
+    );
+
+    let program: Vec<Token> = spanned
+        .into_iter()
+        .map(|t| t.token)
+        .filter(|token| !matches!(token, Token::Comment { .. }))
+        .chain(std::iter::once(Token::Eof))
+        .collect();
+    assert_eq!(program, tokens(PLAIN));
+}
+
+#[test]
+fn test_unterminated_block_comment_runs_to_end() {
+    let source = "(x) #| open #| inner |# never closed";
+    let spanned = Tokenizer::new(source.to_string(), "test")
+        .preserving_comments()
+        .tokenize_spanned();
+
+    assert_eq!(
+        spanned.last().map(|t| &t.token),
+        Some(&Token::Comment {
+            span: 4..source.len()
+        })
+    );
+}
@@ -0,0 +1,67 @@
+/// Test type predicates compiled to type test opcodes
+use jue_world::parser::parse;
+use jue_world::physics_compiler::compile_to_physics_world;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::VmState;
+
+fn compile(source: &str) -> (Vec<OpCode>, Vec<Value>) {
+    compile_to_physics_world(&parse(source).unwrap(), TrustTier::Formal).unwrap()
+}
+
+fn run(source: &str) -> Value {
+    let (bytecode, constants) = compile(source);
+    let mut vm = VmState::new(bytecode, constants, 1000, 1024, 1, 100);
+    vm.run().unwrap()
+}
+
+#[test]
+fn test_integer_predicate_compiles_to_is_int() {
+    let (bytecode, _) = compile("(integer? 5)");
+    assert_eq!(bytecode, vec![OpCode::Int(5), OpCode::IsInt]);
+    assert_eq!(run("(integer? 5)"), Value::Bool(true));
+    assert_eq!(run("(integer? 5.0)"), Value::Bool(false));
+}
+
+#[test]
+fn test_float_and_number_predicates() {
+    assert_eq!(run("(float? 5.0)"), Value::Bool(true));
+    assert_eq!(run("(float? 5)"), Value::Bool(false));
+    assert_eq!(run("(number? 5)"), Value::Bool(true));
+    assert_eq!(run("(number? 2.5)"), Value::Bool(true));
+    assert_eq!(run("(number? \"5\")"), Value::Bool(false));
+}
+
+#[test]
+fn test_type_of_pushes_variant_tag() {
+    assert_eq!(
+        run("(type-of 5)"),
+        Value::Int(i64::from(Value::Int(0).type_tag()))
+    );
+    assert_eq!(
+        run("(type-of 5.0)"),
+        Value::Int(i64::from(Value::Float(0.0).type_tag()))
+    );
+}
+
+#[test]
+fn test_pair_and_closure_tests_in_vm() {
+    let run_bytecode = |bytecode: Vec<OpCode>| {
+        let mut vm = VmState::new(bytecode, vec![], 1000, 1024, 1, 100);
+        vm.run().unwrap()
+    };
+    let pair = vec![OpCode::Int(1), OpCode::Int(2), OpCode::Cons];
+
+    assert_eq!(
+        run_bytecode([pair.clone(), vec![OpCode::IsPair]].concat()),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        run_bytecode([pair, vec![OpCode::IsClosure]].concat()),
+        Value::Bool(false)
+    );
+    assert_eq!(
+        run_bytecode(vec![OpCode::Nil, OpCode::IsPair]),
+        Value::Bool(false)
+    );
+}
@@ -0,0 +1,54 @@
+/// Test compile-time detection of capabilities used without a declaration
+use jue_world::core_compilation::capability_analysis::CapabilityCheckMode;
+use jue_world::core_compiler::compile_with_capability_mode;
+use jue_world::core_compiler::CompilationResult;
+use jue_world::error::CompilationError;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::{Capability, OpCode, Value};
+
+const UNDECLARED: &str = "(ffi-call network-send \"status\")";
+
+fn compile(source: &str, mode: CapabilityCheckMode) -> Result<CompilationResult, CompilationError> {
+    compile_with_capability_mode(source, TrustTier::Experimental, 1000, 1024, mode)
+}
+
+#[test]
+fn test_undeclared_network_send_fails_in_strict_mode() {
+    let error = compile(UNDECLARED, CapabilityCheckMode::Strict).unwrap_err();
+    assert!(matches!(
+        error,
+        CompilationError::UndeclaredCapability {
+            cap: Capability::IoNetwork,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_undeclared_network_send_keeps_runtime_check_in_permissive_mode() {
+    let result = compile(UNDECLARED, CapabilityCheckMode::Permissive).unwrap();
+    // The host call still names the capability the VM checks before running it
+    let network = Value::Capability(Capability::IoNetwork);
+    assert!(result.bytecode.iter().any(|op| matches!(
+        op,
+        OpCode::HostCall { cap_idx, .. } if result.constants[*cap_idx] == network
+    )));
+}
+
+#[test]
+fn test_declared_network_send_compiles_in_strict_mode() {
+    let source = "(let ((net (require-capability IoNetwork))) (ffi-call network-send \"status\"))";
+    assert!(compile(source, CapabilityCheckMode::Strict).is_ok());
+}
+
+#[test]
+fn test_undeclared_direct_network_send_call_fails_in_strict_mode() {
+    let error = compile("(network-send \"status\")", CapabilityCheckMode::Strict).unwrap_err();
+    assert!(matches!(
+        error,
+        CompilationError::UndeclaredCapability {
+            cap: Capability::IoNetwork,
+            ..
+        }
+    ));
+}
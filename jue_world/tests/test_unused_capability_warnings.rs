@@ -0,0 +1,29 @@
+/// Test compile-time detection of unused capability requests
+use jue_world::core_compiler::compile;
+use jue_world::error::CompilationWarning;
+use jue_world::trust_tier::TrustTier;
+use physics_world::types::Capability;
+
+#[test]
+fn test_unused_network_capability_is_flagged() {
+    // Requires both the network and sensor capabilities, but only reads a sensor
+    let source = "(let ((net (require-capability IoNetwork)) \
+                        (sensor (require-capability IoReadSensor))) \
+                    (ffi-call read-sensor))";
+
+    let result = compile(source, TrustTier::Experimental, 1000, 1024).unwrap();
+
+    assert_eq!(
+        result.warnings,
+        vec![CompilationWarning::UnusedCapability(Capability::IoNetwork)]
+    );
+}
+
+#[test]
+fn test_used_capability_is_not_flagged() {
+    let source = "(let ((sensor (require-capability IoReadSensor))) (ffi-call read-sensor))";
+
+    let result = compile(source, TrustTier::Empirical, 1000, 1024).unwrap();
+
+    assert!(result.warnings.is_empty());
+}
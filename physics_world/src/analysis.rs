@@ -0,0 +1,235 @@
+/// Control-flow analysis over bytecode
+///
+/// `build_cfg` splits a flat instruction sequence into basic blocks so that
+/// passes such as dead-code elimination or cross-block peephole rewrites can
+/// reason about which instructions may follow which.
+use crate::types::OpCode;
+
+/// Index of a block within its `Cfg`
+pub type BlockId = usize;
+
+/// Why control can move from one block to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Execution runs off the end of the block into the next one
+    FallThrough,
+    /// Unconditional `Jmp`
+    Jump,
+    /// The taken side of a `JmpIfFalse`
+    Branch,
+    /// From a `SetErrorHandler` to the handler it installs, reached when
+    /// a `Throw` unwinds to it
+    Catch,
+}
+
+/// A control-flow edge to another block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub target: BlockId,
+    pub kind: EdgeKind,
+}
+
+/// A maximal run of instructions entered only at its first instruction and
+/// left only after its last
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    pub id: BlockId,
+    /// Index of the first instruction
+    pub start: usize,
+    /// Index one past the last instruction
+    pub end: usize,
+    pub successors: Vec<Edge>,
+}
+
+impl BasicBlock {
+    /// Instruction indices covered by this block
+    pub fn instructions(&self) -> std::ops::Range<usize> {
+        self.start..self.end
+    }
+
+    /// Index of the instruction that ends this block
+    pub fn terminator(&self) -> usize {
+        self.end - 1
+    }
+}
+
+/// Control-flow graph of a bytecode sequence
+#[derive(Debug, Clone, Default)]
+pub struct Cfg {
+    blocks: Vec<BasicBlock>,
+    predecessors: Vec<Vec<BlockId>>,
+}
+
+impl Cfg {
+    /// All blocks, in instruction order
+    pub fn blocks(&self) -> &[BasicBlock] {
+        &self.blocks
+    }
+
+    /// Iterate over the blocks in instruction order
+    pub fn iter(&self) -> std::slice::Iter<'_, BasicBlock> {
+        self.blocks.iter()
+    }
+
+    pub fn block(&self, id: BlockId) -> Option<&BasicBlock> {
+        self.blocks.get(id)
+    }
+
+    /// Block containing the instruction at `ip`
+    pub fn block_of(&self, ip: usize) -> Option<BlockId> {
+        let index = self.blocks.partition_point(|block| block.end <= ip);
+        self.blocks
+            .get(index)
+            .filter(|block| block.start <= ip)
+            .map(|block| block.id)
+    }
+
+    pub fn successors(&self, id: BlockId) -> &[Edge] {
+        self.blocks
+            .get(id)
+            .map_or(&[], |block| block.successors.as_slice())
+    }
+
+    /// Blocks with an edge into `id`, in ascending order
+    pub fn predecessors(&self, id: BlockId) -> &[BlockId] {
+        self.predecessors.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &'a Cfg {
+    type Item = &'a BasicBlock;
+    type IntoIter = std::slice::Iter<'a, BasicBlock>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.blocks.iter()
+    }
+}
+
+/// Instruction index a relative jump at `ip` lands on, if it is in bounds.
+/// Offsets are relative to the following instruction, as in the VM.
+fn jump_target(ip: usize, offset: i16, len: usize) -> Option<usize> {
+    let target = ip as i64 + 1 + offset as i64;
+    (0..len as i64).contains(&target).then_some(target as usize)
+}
+
+/// Whether control never continues to the next instruction
+fn ends_flow(instruction: &OpCode) -> bool {
+    matches!(
+        instruction,
+        OpCode::Jmp(_) | OpCode::Ret | OpCode::RetN(_) | OpCode::TailCall(_) | OpCode::Throw
+    )
+}
+
+/// Whether the instruction must be the last one in its block
+fn is_terminator(instruction: &OpCode) -> bool {
+    ends_flow(instruction)
+        || matches!(
+            instruction,
+            OpCode::JmpIfFalse(_) | OpCode::SetErrorHandler(_)
+        )
+}
+
+/// Partition `bytecode` into basic blocks and link them.
+///
+/// Blocks start at the first instruction, at every jump or handler target,
+/// and after every jump, return, tail call, throw or handler installation.
+/// Returns, tail calls and throws have no successors: where they go is only
+/// known at run time. Jumps that land outside the bytecode get no edge.
+pub fn build_cfg(bytecode: &[OpCode]) -> Cfg {
+    let len = bytecode.len();
+    if len == 0 {
+        return Cfg::default();
+    }
+
+    let mut leaders = vec![false; len];
+    leaders[0] = true;
+    for (ip, instruction) in bytecode.iter().enumerate() {
+        if let OpCode::Jmp(offset) | OpCode::JmpIfFalse(offset) | OpCode::SetErrorHandler(offset) =
+            instruction
+        {
+            if let Some(target) = jump_target(ip, *offset, len) {
+                leaders[target] = true;
+            }
+        }
+        if is_terminator(instruction) && ip + 1 < len {
+            leaders[ip + 1] = true;
+        }
+    }
+
+    let starts: Vec<usize> = (0..len).filter(|&ip| leaders[ip]).collect();
+    let mut block_at = vec![0; len];
+    for (id, &start) in starts.iter().enumerate() {
+        let end = starts.get(id + 1).copied().unwrap_or(len);
+        block_at[start..end].fill(id);
+    }
+
+    let mut blocks = Vec::with_capacity(starts.len());
+    for (id, &start) in starts.iter().enumerate() {
+        let end = starts.get(id + 1).copied().unwrap_or(len);
+        let last = end - 1;
+        let mut successors = Vec::new();
+        let edge_to = |ip: usize, kind: EdgeKind| Edge {
+            target: block_at[ip],
+            kind,
+        };
+
+        match &bytecode[last] {
+            OpCode::Jmp(offset) => {
+                if let Some(target) = jump_target(last, *offset, len) {
+                    successors.push(edge_to(target, EdgeKind::Jump));
+                }
+            }
+            OpCode::JmpIfFalse(offset) => {
+                if end < len {
+                    successors.push(edge_to(end, EdgeKind::FallThrough));
+                }
+                if let Some(target) = jump_target(last, *offset, len) {
+                    successors.push(edge_to(target, EdgeKind::Branch));
+                }
+            }
+            OpCode::SetErrorHandler(offset) => {
+                if end < len {
+                    successors.push(edge_to(end, EdgeKind::FallThrough));
+                }
+                if let Some(target) = jump_target(last, *offset, len) {
+                    successors.push(edge_to(target, EdgeKind::Catch));
+                }
+            }
+            instruction if ends_flow(instruction) => {}
+            _ => {
+                if end < len {
+                    successors.push(edge_to(end, EdgeKind::FallThrough));
+                }
+            }
+        }
+
+        blocks.push(BasicBlock {
+            id,
+            start,
+            end,
+            successors,
+        });
+    }
+
+    let mut predecessors = vec![Vec::new(); blocks.len()];
+    for block in &blocks {
+        for edge in &block.successors {
+            if !predecessors[edge.target].contains(&block.id) {
+                predecessors[edge.target].push(block.id);
+            }
+        }
+    }
+
+    Cfg {
+        blocks,
+        predecessors,
+    }
+}
@@ -253,6 +253,38 @@ impl ComptimeExecution for PhysicsWorld {
                                         "Recursion limit exceeded".to_string(),
                                     )
                                 }
+                                crate::vm::error::VmError::IndexOutOfBounds {
+                                    index,
+                                    length,
+                                    ..
+                                } => ComptimeError::SchedulerError(format!(
+                                    "Index {} out of bounds for vector of length {}",
+                                    index, length
+                                )),
+                                crate::vm::error::VmError::ReturnCountMismatch {
+                                    expected,
+                                    actual,
+                                    ..
+                                } => ComptimeError::SchedulerError(format!(
+                                    "Call expected {} results but callee returned {}",
+                                    expected, actual
+                                )),
+                                crate::vm::error::VmError::UnknownFunction {
+                                    function,
+                                    ..
+                                } => ComptimeError::SchedulerError(format!(
+                                    "No metadata loaded for function {}",
+                                    function
+                                )),
+                                crate::vm::error::VmError::CaptureCountMismatch {
+                                    function,
+                                    expected,
+                                    actual,
+                                    ..
+                                } => ComptimeError::SchedulerError(format!(
+                                    "Closure of function {} captured {} values, expected {}",
+                                    function, actual, expected
+                                )),
                                 crate::vm::error::VmError::StackOverflow { .. } => {
                                     ComptimeError::SchedulerError(
                                         "Stack overflow".to_string(),
@@ -277,7 +309,8 @@ impl ComptimeExecution for PhysicsWorld {
                             };
                             return Err(error);
                         }
-                        TickResult::ActorWaitingForCapability(actor_id, capability) => {
+                        TickResult::ActorWaitingForCapability(..)
+                        | TickResult::CapabilityRequestTimedOut(..) => {
                             // Comptime actors cannot request additional capabilities
                             // This is a security measure to prevent capability escalation
                             return Err(ComptimeError::CapabilityError(
@@ -82,6 +82,11 @@ impl PhysicsWorld {
                             steps_used += 1;
                             continue;
                         }
+                        TickResult::CapabilityRequestTimedOut(_, _) => {
+                            // Actor resumes with the denial on its stack
+                            steps_used += 1;
+                            continue;
+                        }
                         TickResult::ActorFinished(_, value) => {
                             output = Some(value);
                             steps_used += 1; // Count the final step
@@ -144,6 +149,38 @@ impl PhysicsWorld {
                                         "Recursion limit exceeded".to_string(),
                                     )
                                 }
+                                crate::vm::error::VmError::IndexOutOfBounds {
+                                    index,
+                                    length,
+                                    ..
+                                } => StructuredError::SchedulerError(format!(
+                                    "Index {} out of bounds for vector of length {}",
+                                    index, length
+                                )),
+                                crate::vm::error::VmError::ReturnCountMismatch {
+                                    expected,
+                                    actual,
+                                    ..
+                                } => StructuredError::SchedulerError(format!(
+                                    "Call expected {} results but callee returned {}",
+                                    expected, actual
+                                )),
+                                crate::vm::error::VmError::UnknownFunction {
+                                    function,
+                                    ..
+                                } => StructuredError::SchedulerError(format!(
+                                    "No metadata loaded for function {}",
+                                    function
+                                )),
+                                crate::vm::error::VmError::CaptureCountMismatch {
+                                    function,
+                                    expected,
+                                    actual,
+                                    ..
+                                } => StructuredError::SchedulerError(format!(
+                                    "Closure of function {} captured {} values, expected {}",
+                                    function, actual, expected
+                                )),
                                 crate::vm::error::VmError::StackOverflow { .. } => {
                                     StructuredError::SchedulerError(
                                         "Stack overflow".to_string(),
@@ -1,10 +1,12 @@
 /// Distributed scheduling and multi-node execution for Physics World V3
-use crate::scheduler::PhysicsScheduler;
+use crate::scheduler::{PhysicsScheduler, SchedulingOrder, TickResult};
 use crate::types::{
     ActorMigrationRequest, Capability, ConsensusStatus, DistributedConsensusRequest,
-    DistributedError, DistributedNode, RemoteExecutionRequest, RemoteExecutionResponse,
+    DistributedError, DistributedNode, MigrationStatus, RemoteExecutionRequest,
+    RemoteExecutionResponse, SerializedActorState, Value,
 };
-use std::collections::{HashMap, HashSet};
+use crate::vm::error::VmError;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Main distributed scheduler that coordinates execution across multiple nodes
@@ -68,6 +70,166 @@ impl DistributedScheduler {
         Ok(())
     }
 
+    /// Stop the scheduler without dropping in-flight work.
+    ///
+    /// Pending messages for local actors are delivered first. With
+    /// `ShutdownMode::Drain` every local actor then runs until it finishes,
+    /// yields or errors; with `ShutdownMode::Migrate` every local actor is
+    /// handed to the target node through `migrate_actor`, taking its
+    /// messages with it. Messages for actors not on this node are returned
+    /// for the caller to forward. The scheduler is stopped afterwards,
+    /// unless an actor could not be migrated: such actors stay on this node,
+    /// listed in `ShutdownReport::not_migrated`, and the scheduler keeps
+    /// running so the caller can retry or drain them.
+    pub fn shutdown(&mut self, mode: ShutdownMode) -> Result<ShutdownReport, DistributedError> {
+        if let ShutdownMode::Migrate(target_node) = mode {
+            if !self.remote_nodes.contains_key(&target_node) {
+                return Err(DistributedError {
+                    error_type: "NodeNotFound".to_string(),
+                    message: format!("Cannot migrate actors to unknown node {}", target_node),
+                    node_id: self.node_id,
+                    timestamp: current_timestamp(),
+                    context: None,
+                });
+            }
+        }
+
+        let mut report = ShutdownReport::default();
+        let local_ids: HashSet<u32> = self.local_scheduler.actors.iter().map(|a| a.id).collect();
+        let queues = std::mem::take(&mut self.local_scheduler.message_queues);
+        for (actor_id, messages) in queues {
+            if local_ids.contains(&actor_id) {
+                self.local_scheduler
+                    .message_queues
+                    .insert(actor_id, messages);
+            } else {
+                report.undelivered.insert(actor_id, messages);
+            }
+        }
+
+        match mode {
+            ShutdownMode::Drain => {
+                self.local_scheduler.deliver_external_messages();
+                self.drain_local_actors(&mut report);
+            }
+            ShutdownMode::Migrate(target_node) => {
+                let mut actor_ids: Vec<u32> = local_ids.into_iter().collect();
+                actor_ids.sort_unstable();
+                for actor_id in actor_ids {
+                    match self.migrate_actor(actor_id, target_node) {
+                        Ok(()) => report.migrated.push(actor_id),
+                        Err(error) => report.not_migrated.push((actor_id, error)),
+                    }
+                }
+            }
+        }
+
+        if report.not_migrated.is_empty() {
+            self.stop()?;
+        }
+        Ok(report)
+    }
+
+    /// Run each local actor once more, then remove them all
+    fn drain_local_actors(&mut self, report: &mut ShutdownReport) {
+        let scheduler = &mut self.local_scheduler;
+        // Visit the actors in order, whatever ordering was configured
+        scheduler.scheduling_order = SchedulingOrder::Fifo;
+
+        for index in 0..scheduler.actors.len() {
+            let actor_id = scheduler.actors[index].id;
+            if scheduler.actors[index].is_waiting {
+                report.suspended.push(actor_id);
+                continue;
+            }
+            scheduler.current_actor_index = index;
+            let result = loop {
+                match scheduler.tick() {
+                    // Expiring a request does not run the actor; tick again
+                    Ok(TickResult::CapabilityRequestTimedOut(..)) => continue,
+                    result => break result,
+                }
+            };
+            match result {
+                Ok(TickResult::ActorFinished(_, value)) => report.finished.push((actor_id, value)),
+                Ok(TickResult::ActorErrored(_, error)) => report.errored.push((actor_id, error)),
+                _ => report.suspended.push(actor_id),
+            }
+        }
+
+        scheduler.actors.clear();
+        scheduler.current_actor_index = 0;
+    }
+
+    /// Hand a local actor over to `target_node`
+    ///
+    /// The actor leaves the local scheduler, and a pending migration request
+    /// carrying its serialized VM, capabilities, mailbox and queued messages
+    /// is added to `migration_queue`. Time-boxed grants that have already
+    /// lapsed are dropped first, so they never reach the target node.
+    pub fn migrate_actor(
+        &mut self,
+        actor_id: u32,
+        target_node: u32,
+    ) -> Result<(), DistributedError> {
+        let scheduler = &mut self.local_scheduler;
+        let Some(index) = scheduler.actors.iter().position(|a| a.id == actor_id) else {
+            return Err(DistributedError {
+                error_type: "ActorNotFound".to_string(),
+                message: format!("Actor {} is not running on this node", actor_id),
+                node_id: self.node_id,
+                timestamp: current_timestamp(),
+                context: None,
+            });
+        };
+
+        scheduler.actors[index].vm.prune_expired_capabilities();
+        let vm_state = match bincode::serialize(&scheduler.actors[index].vm) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                self.network_stats.failed_migrations += 1;
+                return Err(DistributedError {
+                    error_type: "SerializationFailed".to_string(),
+                    message: format!("Cannot serialize actor {}: {}", actor_id, error),
+                    node_id: self.node_id,
+                    timestamp: current_timestamp(),
+                    context: None,
+                });
+            }
+        };
+
+        let actor = scheduler.actors.remove(index);
+        if scheduler.current_actor_index >= scheduler.actors.len() {
+            scheduler.current_actor_index = 0;
+        }
+        let mut mailbox = actor.mailbox;
+        mailbox.extend(
+            scheduler
+                .message_queues
+                .remove(&actor_id)
+                .unwrap_or_default(),
+        );
+        let mut capabilities = actor.vm.capabilities.clone();
+        capabilities.sort();
+
+        self.migration_queue.push(ActorMigrationRequest {
+            actor_id,
+            source_node: self.node_id,
+            target_node,
+            migration_priority: actor.priority,
+            state_snapshot: SerializedActorState {
+                actor_id,
+                vm_state,
+                capabilities,
+                mailbox,
+                priority: actor.priority,
+            },
+            migration_status: MigrationStatus::Pending,
+        });
+        self.network_stats.successful_migrations += 1;
+        Ok(())
+    }
+
     /// Add a remote node to the distributed network
     pub fn add_remote_node(
         &mut self,
@@ -297,6 +459,32 @@ impl DistributedScheduler {
     }
 }
 
+/// How `DistributedScheduler::shutdown` disposes of the local actors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownMode {
+    /// Run every actor until it finishes or yields, then stop
+    Drain,
+    /// Move every actor to the given remote node
+    Migrate(u32),
+}
+
+/// What became of the local actors and messages during a shutdown
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    /// Actors that ran to completion, with their results
+    pub finished: Vec<(u32, Value)>,
+    /// Actors stopped at a yield or while waiting for a capability decision
+    pub suspended: Vec<u32>,
+    /// Actors whose VM failed while draining
+    pub errored: Vec<(u32, VmError)>,
+    /// Actors queued for migration to the target node
+    pub migrated: Vec<u32>,
+    /// Actors whose migration failed, still on this node, with the reason
+    pub not_migrated: Vec<(u32, DistributedError)>,
+    /// Messages for actors not on this node, by target, to forward
+    pub undelivered: BTreeMap<u32, Vec<Value>>,
+}
+
 /// Load balancer for distributed scheduling
 #[derive(Debug, Clone)]
 pub struct LoadBalancer {
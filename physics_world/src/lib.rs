@@ -1,3 +1,4 @@
+pub mod analysis;
 pub mod api;
 /// Main library module for Physics World
 pub mod distributed;
@@ -1,6 +1,6 @@
 use crate::types::HeapPtr;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use thiserror::Error;
 
 /// Object tags for identifying different types of heap objects.
@@ -8,7 +8,55 @@ pub const TAG_CLOSURE: u8 = 1;
 pub const TAG_LIST: u8 = 2; // Cons cell (pair)
 pub const TAG_VECTOR: u8 = 3;
 pub const TAG_STRING: u8 = 4;
+pub const TAG_BIGINT: u8 = 5; // Sign byte, padding, then 32-bit limbs
+pub const TAG_CLOSURE_BODY: u8 = 6; // Bytecode length, then its bincode encoding
 pub const TAG_PAIR: u8 = TAG_LIST; // Alias for cons cells
+/// Filler object covering alignment padding; never referenced and never marked
+pub const TAG_PADDING: u8 = 0xFF;
+
+/// Bytes per element slot of a vector; a pair is two slots, car then cdr.
+pub const VECTOR_SLOT_SIZE: usize = 16;
+/// Slot holds `[SLOT_INLINE, len, bincode bytes...]` of a value without heap references
+pub const SLOT_INLINE: u8 = 1;
+/// Slot holds `[SLOT_BOXED, 0, 0, 0, heap ptr (u32 LE), ...]` of a `TAG_STRING`
+/// object holding the value's bincode encoding
+pub const SLOT_BOXED: u8 = 2;
+/// Slot holds `[SLOT_HEAP, kind, 0, 0, heap ptr (u32 LE), ...]` of a value
+/// that is itself an arena object (pair, closure, vector or bigint)
+pub const SLOT_HEAP: u8 = 3;
+
+/// Closure capture kind byte: the capture word is a `HeapPtr`
+pub const CAPTURE_HEAP: u8 = 1;
+
+/// Data size of a closure with `captures` captures: the body pointer, a word
+/// per capture and a kind byte per capture
+pub const fn closure_size(captures: usize) -> u32 {
+    (4 + 5 * captures) as u32
+}
+
+/// Number of captures of a closure whose data is `data_len` bytes
+pub const fn closure_capture_count(data_len: usize) -> usize {
+    data_len.saturating_sub(4) / 5
+}
+
+/// Alignment every allocation gets at minimum (the header size).
+pub const MIN_ALIGNMENT: u32 = 8;
+
+/// Natural alignment of objects with the given tag, in bytes.
+///
+/// Closures and vectors are aligned to 16 bytes so their fields can be read
+/// with aligned wide loads; everything else only needs the header alignment.
+pub const fn natural_alignment(tag: u8) -> u32 {
+    match tag {
+        TAG_CLOSURE | TAG_VECTOR => 16,
+        _ => MIN_ALIGNMENT,
+    }
+}
+
+/// Round `offset` up to a multiple of `align` (a power of two)
+const fn align_up(offset: u32, align: u32) -> u32 {
+    (offset + align - 1) & !(align - 1)
+}
 
 /// Error type for arena allocation failures.
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -31,6 +79,10 @@ pub enum DefragmentationError {
     DefragmentationFailed(String),
 }
 
+/// Old and new address of every object moved by a compacting collection,
+/// defragmentation, or any other pass that relocates live objects.
+pub type RelocationMap = HashMap<HeapPtr, HeapPtr>;
+
 /// Result type for garbage collection operations.
 pub type GarbageCollectionResult = Result<(), GarbageCollectionError>;
 
@@ -54,20 +106,37 @@ pub struct ObjectHeader {
     pub size: u32,
     pub tag: u8,
     pub marked: bool,  // Mark bit for garbage collection
-    _padding: [u8; 2], // Maintain 8-byte alignment
+    align_log2: u8,    // Alignment the object was allocated with, as a power of two
+    _padding: [u8; 1], // Maintain 8-byte alignment
 }
 
 impl ObjectHeader {
     /// Create a new header.
     pub fn new(size: u32, tag: u8) -> Self {
+        Self::with_alignment(size, tag, MIN_ALIGNMENT)
+    }
+
+    /// Create a new header for an object aligned to `align` bytes.
+    pub fn with_alignment(size: u32, tag: u8, align: u32) -> Self {
         Self {
             size,
             tag,
             marked: false,
-            _padding: [0; 2],
+            align_log2: align.trailing_zeros() as u8,
+            _padding: [0; 1],
         }
     }
 
+    /// Alignment the object was allocated with, in bytes.
+    pub fn alignment(&self) -> u32 {
+        (1u32 << self.align_log2).max(MIN_ALIGNMENT)
+    }
+
+    /// Bytes the object occupies in the arena: header plus data rounded up to 8 bytes.
+    pub fn footprint(&self) -> u32 {
+        Self::size_bytes() as u32 + align_up(self.size, MIN_ALIGNMENT)
+    }
+
     /// Size of the header in bytes.
     pub const fn size_bytes() -> usize {
         std::mem::size_of::<Self>()
@@ -88,6 +157,9 @@ pub struct ObjectArena {
     fragmentation_threshold: f32,
     /// Enable/disable automatic defragmentation
     auto_defragment: bool,
+    /// Bytes currently spent on alignment padding between objects
+    #[serde(default)]
+    padding_bytes: u32,
 }
 
 impl ObjectArena {
@@ -99,6 +171,7 @@ impl ObjectArena {
             capacity,
             fragmentation_threshold: 0.3, // 30% fragmentation threshold
             auto_defragment: true,        // Enable automatic defragmentation by default
+            padding_bytes: 0,
         }
     }
 
@@ -114,19 +187,42 @@ impl ObjectArena {
             capacity,
             fragmentation_threshold: fragmentation_threshold.clamp(0.0, 1.0),
             auto_defragment,
+            padding_bytes: 0,
         }
     }
 
     /// Allocates a region of `size` bytes and returns a `HeapPtr` to it.
     ///
-    /// The allocated region is guaranteed to be aligned to 8 bytes (the size of
-    /// the header). The header is written at the start of the region.
+    /// The object is aligned to the natural alignment of `tag` (see
+    /// [`natural_alignment`]). The header is written at the start of the region.
     ///
     /// # Errors
     /// Returns `ArenaError::ArenaFull` if there is insufficient space.
     pub fn allocate(&mut self, size: u32, tag: u8) -> Result<HeapPtr, ArenaError> {
-        let aligned_size = (size + 7) & !7; // align up to 8 bytes
-        let total_needed = ObjectHeader::size_bytes() as u32 + aligned_size;
+        self.allocate_aligned(size, tag, natural_alignment(tag))
+    }
+
+    /// Allocates a region of `size` bytes whose `HeapPtr` is a multiple of `align`.
+    ///
+    /// `align` is rounded up to a power of two of at least 8 bytes. Any gap
+    /// before the object is covered by a `TAG_PADDING` filler so heap walks
+    /// stay in step, and is counted in [`padding_bytes`](Self::padding_bytes).
+    /// Alignment is relative to the start of the arena and is preserved when
+    /// the object is moved by garbage collection or defragmentation.
+    ///
+    /// # Errors
+    /// Returns `ArenaError::ArenaFull` if there is insufficient space.
+    pub fn allocate_aligned(
+        &mut self,
+        size: u32,
+        tag: u8,
+        align: u32,
+    ) -> Result<HeapPtr, ArenaError> {
+        let align = align.max(MIN_ALIGNMENT).next_power_of_two();
+        let aligned_size = align_up(size, MIN_ALIGNMENT);
+        let ptr = align_up(self.next_free, align);
+        let padding = ptr - self.next_free;
+        let total_needed = padding + ObjectHeader::size_bytes() as u32 + aligned_size;
 
         if self.next_free + total_needed > self.capacity {
             return Err(ArenaError::ArenaFull {
@@ -135,19 +231,12 @@ impl ObjectArena {
             });
         }
 
-        let ptr = self.next_free;
+        self.write_padding(self.next_free, padding);
         self.next_free += total_needed;
 
         // Write header
-        let header = ObjectHeader::new(size, tag);
-        let header_bytes = unsafe {
-            std::slice::from_raw_parts(
-                &header as *const ObjectHeader as *const u8,
-                ObjectHeader::size_bytes(),
-            )
-        };
+        self.write_header(ptr, &ObjectHeader::with_alignment(size, tag, align));
         let start = ptr as usize;
-        self.storage[start..start + ObjectHeader::size_bytes()].copy_from_slice(header_bytes);
 
         // Zero the data region for safety
         let data_start = start + ObjectHeader::size_bytes();
@@ -157,9 +246,20 @@ impl ObjectArena {
         Ok(HeapPtr::new(ptr))
     }
 
+    /// Enlarges the arena to `new_capacity` bytes. Objects keep their addresses.
+    ///
+    /// Does nothing if `new_capacity` is not larger than the current capacity.
+    pub fn grow(&mut self, new_capacity: u32) {
+        if new_capacity > self.capacity {
+            self.storage.resize(new_capacity as usize, 0);
+            self.capacity = new_capacity;
+        }
+    }
+
     /// Resets the arena, discarding all allocated objects.
     pub fn reset(&mut self) {
         self.next_free = 0;
+        self.padding_bytes = 0;
         // Optionally zero the storage; not required for correctness but helps debugging.
         self.storage.fill(0);
     }
@@ -216,6 +316,34 @@ impl ObjectArena {
         self.capacity
     }
 
+    /// Returns the bytes below `next_free` spent on alignment padding.
+    pub fn padding_bytes(&self) -> u32 {
+        self.padding_bytes
+    }
+
+    /// Copies `header` into the arena at `ptr`.
+    fn write_header(&mut self, ptr: u32, header: &ObjectHeader) {
+        let header_bytes = unsafe {
+            std::slice::from_raw_parts(
+                header as *const ObjectHeader as *const u8,
+                ObjectHeader::size_bytes(),
+            )
+        };
+        let start = ptr as usize;
+        self.storage[start..start + ObjectHeader::size_bytes()].copy_from_slice(header_bytes);
+    }
+
+    /// Covers `len` bytes of alignment padding at `start` with a filler object.
+    fn write_padding(&mut self, start: u32, len: u32) {
+        if len == 0 {
+            return;
+        }
+        // Padding sits between 8-byte aligned offsets, so it always has room for a header
+        let data_len = len - ObjectHeader::size_bytes() as u32;
+        self.write_header(start, &ObjectHeader::new(data_len, TAG_PADDING));
+        self.padding_bytes += len;
+    }
+
     /// Marks an object as reachable during garbage collection.
     ///
     /// # Safety
@@ -242,11 +370,7 @@ impl ObjectArena {
     /// # Returns
     /// A `GarbageCollectionResult` indicating success or failure.
     pub fn collect_garbage(&mut self, root_set: &[HeapPtr]) -> GarbageCollectionResult {
-        // Mark phase: Mark all reachable objects starting from the root set
-        self.mark_phase(root_set)?;
-
-        // Sweep phase: Collect unmarked objects and compact memory
-        self.sweep_phase()?;
+        self.collect_garbage_relocating(root_set)?;
 
         // Check if automatic defragmentation should be triggered
         if self.auto_defragment && self.should_defragment() {
@@ -256,6 +380,27 @@ impl ObjectArena {
         Ok(())
     }
 
+    /// Performs garbage collection and reports where surviving objects moved.
+    ///
+    /// Pointers stored inside surviving heap objects are rewritten to the new
+    /// addresses (as conservatively as marking finds them); the caller must
+    /// rewrite its own roots using the returned map. Unlike
+    /// [`collect_garbage`](Self::collect_garbage) this never defragments
+    /// afterwards, since that would move objects again without reporting it.
+    pub fn collect_garbage_relocating(
+        &mut self,
+        root_set: &[HeapPtr],
+    ) -> Result<RelocationMap, GarbageCollectionError> {
+        // Mark phase: Mark all reachable objects starting from the root set
+        self.mark_phase(root_set)?;
+
+        // Sweep phase: Collect unmarked objects and compact memory
+        let relocations = self.sweep_phase()?;
+        self.relocate_references(&relocations);
+
+        Ok(relocations)
+    }
+
     /// Performs defragmentation to compact memory and reduce fragmentation.
     ///
     /// # Returns
@@ -272,12 +417,13 @@ impl ObjectArena {
         let mut current_ptr = 0;
         while current_ptr < self.next_free {
             let header = unsafe { self.get_header(HeapPtr::new(current_ptr)) };
-            let object_size = ObjectHeader::size_bytes() as u32 + header.size;
+            let object_size = header.footprint();
 
             if header.marked {
-                // This object is live, calculate its new position
-                pointer_mapping.push((current_ptr, new_next_free));
-                new_next_free += object_size;
+                // This object is live, calculate its new (still aligned) position
+                let new_ptr = align_up(new_next_free, header.alignment());
+                pointer_mapping.push((current_ptr, new_next_free, new_ptr));
+                new_next_free = new_ptr + object_size;
             }
 
             current_ptr += object_size;
@@ -285,11 +431,9 @@ impl ObjectArena {
 
         // Second pass: move objects to their new positions
         let mut objects_moved = 0;
-        for (old_ptr, new_ptr) in &pointer_mapping {
-            let object_size = {
-                let header = unsafe { self.get_header(HeapPtr::new(*old_ptr)) };
-                ObjectHeader::size_bytes() as u32 + header.size
-            };
+        self.padding_bytes = 0;
+        for (old_ptr, padding_start, new_ptr) in &pointer_mapping {
+            let object_size = unsafe { self.get_header(HeapPtr::new(*old_ptr)) }.footprint();
 
             // Move the object data
             let src_start = *old_ptr as usize;
@@ -297,6 +441,7 @@ impl ObjectArena {
             let dst_start = *new_ptr as usize;
 
             self.storage.copy_within(src_start..src_end, dst_start);
+            self.write_padding(*padding_start, new_ptr - padding_start);
             objects_moved += 1;
         }
 
@@ -316,6 +461,97 @@ impl ObjectArena {
         })
     }
 
+    /// Defragments live objects and reports where they moved.
+    ///
+    /// Unlike [`defragment`](Self::defragment), objects may be reordered:
+    /// when a 16-byte aligned object would follow an 8-byte gap, a later
+    /// 8-byte aligned object whose footprint closes the gap is moved in
+    /// front of it, so padding left behind by a compacting collection is
+    /// reclaimed too. Pointers inside objects are rewritten; the caller must
+    /// rewrite its own using the returned map.
+    ///
+    /// Only marked objects are kept, so call this right after a collection,
+    /// before anything else is allocated.
+    pub fn defragment_relocating(
+        &mut self,
+    ) -> Result<(DefragmentationStats, RelocationMap), DefragmentationError> {
+        let start_time = std::time::Instant::now();
+        let fragmentation_before = self.fragmentation_ratio();
+
+        // Live objects in address order, as (address, footprint, alignment)
+        let mut live = Vec::new();
+        let mut current_ptr = 0;
+        while current_ptr < self.next_free {
+            let header = unsafe { self.get_header(HeapPtr::new(current_ptr)) };
+            if header.marked {
+                live.push((current_ptr, header.footprint(), header.alignment()));
+            }
+            current_ptr += header.footprint();
+        }
+
+        // Objects that turn an 8 mod 16 offset into a 16-byte aligned one
+        let fillers: Vec<usize> = (0..live.len())
+            .filter(|&i| live[i].2 == MIN_ALIGNMENT && live[i].1 % 16 == 8)
+            .collect();
+        let mut next_filler = 0;
+        let mut placed = vec![false; live.len()];
+        // (object, where its padding starts, new address)
+        let mut layout = Vec::with_capacity(live.len());
+        let mut cursor = 0;
+        for index in 0..live.len() {
+            if placed[index] {
+                continue;
+            }
+            let (_, footprint, align) = live[index];
+            if align == 16 && cursor % 16 == 8 {
+                while fillers
+                    .get(next_filler)
+                    .is_some_and(|&filler| filler <= index || placed[filler])
+                {
+                    next_filler += 1;
+                }
+                if let Some(&filler) = fillers.get(next_filler) {
+                    placed[filler] = true;
+                    layout.push((filler, cursor, cursor));
+                    cursor += live[filler].1;
+                }
+            }
+            let new_ptr = align_up(cursor, align);
+            placed[index] = true;
+            layout.push((index, cursor, new_ptr));
+            cursor = new_ptr + footprint;
+        }
+
+        // Objects may move in either direction, so copy into fresh storage
+        let capacity = self.storage.len();
+        let old_storage = std::mem::replace(&mut self.storage, vec![0; capacity]);
+        let mut relocations = RelocationMap::new();
+        self.padding_bytes = 0;
+        for &(index, padding_start, new_ptr) in &layout {
+            let (old_ptr, footprint, _) = live[index];
+            let (src, dst) = (old_ptr as usize, new_ptr as usize);
+            self.storage[dst..dst + footprint as usize]
+                .copy_from_slice(&old_storage[src..src + footprint as usize]);
+            self.write_padding(padding_start, new_ptr - padding_start);
+            if old_ptr != new_ptr {
+                relocations.insert(HeapPtr::new(old_ptr), HeapPtr::new(new_ptr));
+            }
+        }
+
+        let bytes_reclaimed = self.next_free - cursor;
+        self.next_free = cursor;
+        self.relocate_references(&relocations);
+
+        let stats = DefragmentationStats {
+            objects_moved: relocations.len() as u32,
+            bytes_reclaimed,
+            fragmentation_before,
+            fragmentation_after: self.fragmentation_ratio(),
+            time_taken_ms: start_time.elapsed().as_millis() as u64,
+        };
+        Ok((stats, relocations))
+    }
+
     /// Checks if defragmentation should be performed based on fragmentation level.
     ///
     /// # Returns
@@ -344,8 +580,9 @@ impl ObjectArena {
 
         while current_ptr < self.next_free {
             let header = unsafe { self.get_header(HeapPtr::new(current_ptr)) };
-            let object_size = ObjectHeader::size_bytes() as u32 + header.size;
+            let object_size = header.footprint();
 
+            // Padding fillers are never marked, so they count as wasted space
             if header.marked {
                 used_space += object_size;
             }
@@ -376,6 +613,10 @@ impl ObjectArena {
     /// Marks all reachable objects starting from the root set, including
     /// transitive closure of HeapPtr references.
     fn mark_phase(&mut self, root_set: &[HeapPtr]) -> Result<(), GarbageCollectionError> {
+        // Marks from the previous collection stay set until now so that
+        // fragmentation analysis can tell live objects from dead ones
+        self.clear_marks();
+
         // Mark all objects in the root set
         for &root_ptr in root_set {
             unsafe { self.mark_object(root_ptr) };
@@ -419,94 +660,103 @@ impl ObjectArena {
     unsafe fn get_referenced_heap_ptrs(&self, ptr: HeapPtr) -> Vec<HeapPtr> {
         let header = self.get_header(ptr);
         let data = self.get_data(ptr);
-        let mut refs = Vec::new();
-
-        // Helper function to check if a value is a valid heap pointer
-        // A valid heap pointer must:
-        // 1. Be non-zero
-        // 2. Be less than next_free (within arena bounds)
-        // 3. Be 8-byte aligned (objects are 8-byte aligned in this arena)
-        // 4. Point to a valid object header (header must be within allocated range)
-        fn is_valid_heap_ptr(val: u32, next_free: u32) -> bool {
-            if val == 0 || val >= next_free {
-                return false;
-            }
-            // Check 8-byte alignment (header is 8 bytes)
-            if val % 8 != 0 {
-                return false;
-            }
-            true
-        }
 
-        // Parse data based on object tag/type
-        match header.tag {
-            TAG_CLOSURE => {
-                // Closure contains: code_ptr (4 bytes) + captures (variable length)
-                // After code_ptr, we may have captured HeapPtr values
-                // Try to read captures starting at offset 4
-                if data.len() >= 4 {
-                    // Try to parse as a sequence of HeapPtr values
-                    // Each HeapPtr is 4 bytes (u32)
-                    let mut offset = 4;
-                    while offset + 4 <= data.len() {
-                        let ptr_bytes = &data[offset..offset + 4];
-                        let ptr_val = u32::from_le_bytes(ptr_bytes.try_into().unwrap());
-
-                        if is_valid_heap_ptr(ptr_val, self.next_free) {
-                            refs.push(HeapPtr::new(ptr_val));
-                        }
-                        offset += 4;
-                    }
+        let (offsets, exact) = pointer_words(header.tag, data);
+        offsets
+            .into_iter()
+            .map(|offset| read_word(data, offset))
+            .filter(|&value| {
+                if exact {
+                    value < self.next_free
+                } else {
+                    is_valid_heap_ptr(value, self.next_free)
                 }
+            })
+            .map(HeapPtr::new)
+            .collect()
+    }
+
+    /// Address of every allocated object, in address order, skipping
+    /// alignment padding
+    pub fn object_pointers(&self) -> Vec<HeapPtr> {
+        let mut objects = Vec::new();
+        let mut current_ptr = 0;
+        while current_ptr < self.next_free {
+            let header = unsafe { self.get_header(HeapPtr::new(current_ptr)) };
+            if header.tag != TAG_PADDING {
+                objects.push(HeapPtr::new(current_ptr));
             }
-            TAG_LIST | TAG_PAIR => {
-                // Cons cell contains: car (4 bytes) + cdr (4 bytes)
-                // Each could be a HeapPtr or an immediate value
-                if data.len() >= 8 {
-                    // Read car
-                    let car_bytes = &data[0..4];
-                    let car_val = u32::from_le_bytes(car_bytes.try_into().unwrap());
-                    if is_valid_heap_ptr(car_val, self.next_free) {
-                        refs.push(HeapPtr::new(car_val));
-                    }
-
-                    // Read cdr
-                    let cdr_bytes = &data[4..8];
-                    let cdr_val = u32::from_le_bytes(cdr_bytes.try_into().unwrap());
-                    if is_valid_heap_ptr(cdr_val, self.next_free) {
-                        refs.push(HeapPtr::new(cdr_val));
-                    }
+            current_ptr += header.footprint();
+        }
+        objects
+    }
+
+    /// Heap pointers held by the object at `ptr`, as marking finds them.
+    ///
+    /// # Safety
+    /// The caller must ensure that `ptr` points to a valid object header.
+    pub unsafe fn outgoing_pointers(&self, ptr: HeapPtr) -> Vec<HeapPtr> {
+        self.get_referenced_heap_ptrs(ptr)
+    }
+
+    /// Unmarks every object in the arena
+    fn clear_marks(&mut self) {
+        let mut current_ptr = 0;
+        while current_ptr < self.next_free {
+            let header = unsafe { self.get_header_mut(HeapPtr::new(current_ptr)) };
+            header.marked = false;
+            current_ptr += header.footprint();
+        }
+    }
+
+    /// Rewrites pointers stored inside objects that refer to moved objects.
+    ///
+    /// Only pointer-bearing words are considered (see `pointer_words`), so raw
+    /// bytes that happen to equal an old address are left alone. As in marking,
+    /// a zero word is only a pointer in a slot tagged as one.
+    pub fn relocate_references(&mut self, relocations: &RelocationMap) {
+        if relocations.is_empty() {
+            return;
+        }
+
+        let mut current_ptr = 0;
+        while current_ptr < self.next_free {
+            let ptr = HeapPtr::new(current_ptr);
+            let header = unsafe { self.get_header(ptr) };
+            let (tag, footprint) = (header.tag, header.footprint());
+            let data = unsafe { self.get_data_mut(ptr) };
+
+            let (offsets, exact) = pointer_words(tag, data);
+            for offset in offsets {
+                let word = read_word(data, offset);
+                if word == 0 && !exact {
+                    continue;
                 }
-            }
-            TAG_VECTOR => {
-                // Vector contains elements, each 4 bytes that could be HeapPtr
-                let num_elements = data.len() / 4;
-                for i in 0..num_elements {
-                    let elem_bytes = &data[i * 4..(i + 1) * 4];
-                    let elem_val = u32::from_le_bytes(elem_bytes.try_into().unwrap());
-                    if is_valid_heap_ptr(elem_val, self.next_free) {
-                        refs.push(HeapPtr::new(elem_val));
-                    }
+                if let Some(new_ptr) = relocations.get(&HeapPtr::new(word)) {
+                    data[offset..offset + 4].copy_from_slice(&new_ptr.get().to_le_bytes());
                 }
             }
-            // TAG_STRING and other unhandled tags contain raw bytes, no HeapPtr references
-            _ => {}
-        }
 
-        refs
+            current_ptr += footprint;
+        }
     }
 
     /// Collects unmarked objects and compacts memory.
-    fn sweep_phase(&mut self) -> Result<(), GarbageCollectionError> {
+    fn sweep_phase(&mut self) -> Result<RelocationMap, GarbageCollectionError> {
+        let mut relocations = RelocationMap::new();
         let mut new_next_free = 0;
         let mut current_ptr = 0;
+        self.padding_bytes = 0;
 
         while current_ptr < self.next_free {
             let header = unsafe { self.get_header(HeapPtr::new(current_ptr)) };
-            let object_size = ObjectHeader::size_bytes() as u32 + header.size;
+            let object_size = header.footprint();
 
             if header.marked {
-                // Object is reachable, keep it
+                // Object is reachable, keep it at its alignment
+                let new_ptr = align_up(new_next_free, header.alignment());
+                self.write_padding(new_next_free, new_ptr - new_next_free);
+                new_next_free = new_ptr;
                 if current_ptr != new_next_free {
                     // Move the object to the new location
                     let src_start = current_ptr as usize;
@@ -514,10 +764,7 @@ impl ObjectArena {
                     let dst_start = new_next_free as usize;
 
                     self.storage.copy_within(src_start..src_end, dst_start);
-
-                    // Update the pointer in the root set if it points to this object
-                    // TODO: This is a simplified approach; a more robust solution would
-                    // maintain a mapping of old to new pointers for all reachable objects.
+                    relocations.insert(HeapPtr::new(current_ptr), HeapPtr::new(new_next_free));
                 }
                 new_next_free += object_size;
             } else {
@@ -528,10 +775,64 @@ impl ObjectArena {
         }
 
         self.next_free = new_next_free;
-        Ok(())
+        Ok(relocations)
+    }
+}
+
+/// Offsets of the 4-byte words in an object's data that may hold a `HeapPtr`,
+/// and whether every one of them certainly does.
+///
+/// Closures hold a body pointer, their capture words and then one kind byte
+/// per capture, so only the captures marked `CAPTURE_HEAP` are pointers. Pairs
+/// and vectors are made of slots tagged with what they hold, so only their
+/// boxed and heap slots are pointers. Pointers found this way may be to
+/// address 0. Other tags (strings, closure bodies, padding) hold raw bytes
+/// only.
+fn pointer_words(tag: u8, data: &[u8]) -> (Vec<usize>, bool) {
+    match tag {
+        TAG_CLOSURE => (closure_pointer_words(data), true),
+        TAG_LIST | TAG_VECTOR => (slot_pointer_words(data), true),
+        _ => (Vec::new(), false),
     }
 }
 
+/// Offsets of the body pointer and of every heap capture in closure `data`
+fn closure_pointer_words(data: &[u8]) -> Vec<usize> {
+    let captures = closure_capture_count(data.len());
+    let kinds = 4 + 4 * captures;
+    std::iter::once(0)
+        .chain(
+            (0..captures)
+                .filter(|&i| data[kinds + i] == CAPTURE_HEAP)
+                .map(|i| 4 + 4 * i),
+        )
+        .collect()
+}
+
+/// Offsets of the pointer word of every boxed or heap slot in `data`
+fn slot_pointer_words(data: &[u8]) -> Vec<usize> {
+    (0..data.len() / VECTOR_SLOT_SIZE)
+        .map(|slot| slot * VECTOR_SLOT_SIZE)
+        .filter(|&start| matches!(data[start], SLOT_BOXED | SLOT_HEAP))
+        .map(|start| start + 4)
+        .collect()
+}
+
+fn read_word(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+/// Check whether a stored word can be a heap pointer: non-zero, within the
+/// allocated part of the arena, and 8-byte aligned like every object header.
+fn is_valid_heap_ptr(value: u32, next_free: u32) -> bool {
+    value != 0 && value < next_free && value.is_multiple_of(MIN_ALIGNMENT)
+}
+
 #[cfg(test)]
 #[path = "test/arena_tests.rs"]
 mod tests;
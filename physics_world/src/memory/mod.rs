@@ -1,7 +1,8 @@
 pub mod arena;
 
 pub use arena::{
-    ArenaError, DefragmentationError, DefragmentationResult, DefragmentationStats,
-    GarbageCollectionError, GarbageCollectionResult, ObjectArena, TAG_CLOSURE, TAG_LIST,
-    TAG_PAIR, TAG_STRING, TAG_VECTOR,
+    natural_alignment, ArenaError, DefragmentationError, DefragmentationResult,
+    DefragmentationStats, GarbageCollectionError, GarbageCollectionResult, ObjectArena,
+    RelocationMap, MIN_ALIGNMENT, SLOT_BOXED, SLOT_HEAP, SLOT_INLINE, TAG_CLOSURE, TAG_LIST,
+    TAG_PADDING, TAG_PAIR, TAG_STRING, TAG_VECTOR, VECTOR_SLOT_SIZE,
 };
@@ -1,5 +1,22 @@
 use super::*;
 
+/// A pair or vector slot referencing the arena object at `ptr`
+fn heap_slot(ptr: HeapPtr) -> [u8; VECTOR_SLOT_SIZE] {
+    let mut slot = [0; VECTOR_SLOT_SIZE];
+    slot[0] = SLOT_HEAP;
+    slot[4..8].copy_from_slice(&ptr.get().to_le_bytes());
+    slot
+}
+
+/// A pair or vector slot holding a small inline value
+fn inline_slot(byte: u8) -> [u8; VECTOR_SLOT_SIZE] {
+    let mut slot = [0; VECTOR_SLOT_SIZE];
+    slot[0] = SLOT_INLINE;
+    slot[1] = 1;
+    slot[2] = byte;
+    slot
+}
+
 #[test]
 fn test_object_header_size() {
     // Ensure the header is 8 bytes (size 4 + tag 1 + marked 1 + padding 2)
@@ -254,31 +271,30 @@ fn test_gc_list_chain() {
     let mut arena = ObjectArena::with_capacity(4096);
 
     // Create a linked list: A -> B -> C -> nil
-    // where each node is a cons cell (car, cdr)
-    let node_c = arena.allocate(8, TAG_PAIR).unwrap();  // Points to nil (0)
-    let node_b = arena.allocate(8, TAG_PAIR).unwrap();  // Points to C
-    let node_a = arena.allocate(8, TAG_PAIR).unwrap();  // Points to B
+    // where each node is a cons cell of a car slot and a cdr slot
+    let node_c = arena.allocate(32, TAG_PAIR).unwrap();
+    let node_b = arena.allocate(32, TAG_PAIR).unwrap();
+    let node_a = arena.allocate(32, TAG_PAIR).unwrap();
 
-    // Set up the linked list
-    // Node A: car = 1 (some value), cdr = node_b
+    // Node A: car = 1, cdr = node_b
     {
         let data = unsafe { arena.get_data_mut(node_a) };
-        data[0..4].copy_from_slice(&1u32.to_le_bytes());  // car = 1
-        data[4..8].copy_from_slice(&node_b.get().to_le_bytes());  // cdr = node_b
+        data[0..16].copy_from_slice(&inline_slot(1));
+        data[16..32].copy_from_slice(&heap_slot(node_b));
     }
 
     // Node B: car = 2, cdr = node_c
     {
         let data = unsafe { arena.get_data_mut(node_b) };
-        data[0..4].copy_from_slice(&2u32.to_le_bytes());  // car = 2
-        data[4..8].copy_from_slice(&node_c.get().to_le_bytes());  // cdr = node_c
+        data[0..16].copy_from_slice(&inline_slot(2));
+        data[16..32].copy_from_slice(&heap_slot(node_c));
     }
 
-    // Node C: car = 3, cdr = nil (0)
+    // Node C: car = 3, cdr = nil
     {
         let data = unsafe { arena.get_data_mut(node_c) };
-        data[0..4].copy_from_slice(&3u32.to_le_bytes());  // car = 3
-        // cdr already 0 (nil) from allocation zeroing
+        data[0..16].copy_from_slice(&inline_slot(3));
+        data[16..32].copy_from_slice(&inline_slot(0));
     }
 
     // Mark only node A as reachable
@@ -291,7 +307,7 @@ fn test_gc_list_chain() {
     assert!(result.is_ok());
 
     // All nodes should survive
-    assert!(arena.next_free() > 0, "All list nodes should survive");
+    assert_eq!(arena.next_free(), 3 * 40, "All list nodes should survive");
 
     println!("✅ List chain GC test passed");
 }
@@ -305,9 +321,9 @@ fn test_gc_circular_reference_survival() {
     // Create a more complex circular reference:
     // closure_a -> list_1 -> closure_b -> list_2 -> closure_a
     let closure_a = arena.allocate(16, TAG_CLOSURE).unwrap();
-    let list_1 = arena.allocate(8, TAG_LIST).unwrap();
+    let list_1 = arena.allocate(32, TAG_LIST).unwrap();
     let closure_b = arena.allocate(16, TAG_CLOSURE).unwrap();
-    let list_2 = arena.allocate(8, TAG_LIST).unwrap();
+    let list_2 = arena.allocate(32, TAG_LIST).unwrap();
 
     // Set up the circular references
     // closure_a -> list_1 (closure stores list_1 at offset 4)
@@ -319,8 +335,8 @@ fn test_gc_circular_reference_survival() {
     // list_1 -> closure_b (cdr points to closure_b)
     {
         let data = unsafe { arena.get_data_mut(list_1) };
-        data[0..4].copy_from_slice(&42u32.to_le_bytes());  // car = 42
-        data[4..8].copy_from_slice(&closure_b.get().to_le_bytes());  // cdr = closure_b
+        data[0..16].copy_from_slice(&inline_slot(42)); // car = 42
+        data[16..32].copy_from_slice(&heap_slot(closure_b)); // cdr = closure_b
     }
 
     // closure_b -> list_2
@@ -332,8 +348,8 @@ fn test_gc_circular_reference_survival() {
     // list_2 -> closure_a (cdr points to closure_a)
     {
         let data = unsafe { arena.get_data_mut(list_2) };
-        data[0..4].copy_from_slice(&43u32.to_le_bytes());  // car = 43
-        data[4..8].copy_from_slice(&closure_a.get().to_le_bytes());  // cdr = closure_a
+        data[0..16].copy_from_slice(&inline_slot(43)); // car = 43
+        data[16..32].copy_from_slice(&heap_slot(closure_a)); // cdr = closure_a
     }
 
     // Mark only closure_a as reachable
@@ -359,19 +375,19 @@ fn test_gc_mark_reachable_from_roots() {
 
     // Create a vector containing references to other objects
     let contained_obj = arena.allocate(16, TAG_CLOSURE).unwrap();
-    let vector_ptr = arena.allocate(16, TAG_VECTOR).unwrap();  // 4 elements
+    let vector_ptr = arena.allocate(64, TAG_VECTOR).unwrap(); // 4 elements
 
     // Fill vector with pointers (including to contained_obj)
     {
         let data = unsafe { arena.get_data_mut(vector_ptr) };
         // Element 0: pointer to contained_obj
-        data[0..4].copy_from_slice(&contained_obj.get().to_le_bytes());
+        data[0..16].copy_from_slice(&heap_slot(contained_obj));
         // Element 1: pointer to vector itself (self-reference)
-        data[4..8].copy_from_slice(&vector_ptr.get().to_le_bytes());
+        data[16..32].copy_from_slice(&heap_slot(vector_ptr));
         // Element 2: some other value
-        data[8..12].copy_from_slice(&99u32.to_le_bytes());
+        data[32..48].copy_from_slice(&inline_slot(99));
         // Element 3: zero
-        data[12..16].copy_from_slice(&0u32.to_le_bytes());
+        data[48..64].copy_from_slice(&inline_slot(0));
     }
 
     // Mark only the vector as reachable
@@ -388,3 +404,122 @@ fn test_gc_mark_reachable_from_roots() {
 
     println!("✅ Mark reachable from roots test passed");
 }
+
+#[test]
+fn test_mixed_allocations_are_naturally_aligned() {
+    let mut arena = ObjectArena::with_capacity(1024);
+    let tags = [TAG_STRING, TAG_CLOSURE, TAG_LIST, TAG_VECTOR, 0, TAG_VECTOR];
+    let sizes = [3, 16, 8, 24, 1, 5];
+
+    let mut payload = 0;
+    for (&tag, &size) in tags.iter().zip(&sizes) {
+        let ptr = arena.allocate(size, tag).unwrap();
+        assert_eq!(
+            ptr.get() % natural_alignment(tag),
+            0,
+            "tag {} at {}",
+            tag,
+            ptr.get()
+        );
+        payload += ObjectHeader::size_bytes() as u32 + ((size + 7) & !7);
+    }
+
+    // 0: string (16) | 16: closure (24) | 40: list (16) | 56: pad 8, 64: vector (32)
+    // | 96: untagged (16) | 112: vector (16)
+    assert_eq!(arena.padding_bytes(), 8);
+    assert_eq!(arena.next_free(), payload + arena.padding_bytes());
+}
+
+#[test]
+fn test_explicit_alignment() {
+    let mut arena = ObjectArena::with_capacity(1024);
+    arena.allocate(1, TAG_STRING).unwrap();
+    let ptr = arena.allocate_aligned(8, TAG_STRING, 64).unwrap();
+    assert_eq!(ptr.get(), 64);
+    assert_eq!(unsafe { arena.get_header(ptr) }.alignment(), 64);
+    assert_eq!(arena.padding_bytes(), 48);
+    assert_eq!(arena.next_free(), 80);
+}
+
+#[test]
+fn test_gc_compaction_preserves_alignment() {
+    let mut arena = ObjectArena::with_capacity(1024);
+    arena.set_defragmentation_settings(0.3, false);
+    let garbage = arena.allocate(1, TAG_STRING).unwrap();
+    let closure = arena.allocate(16, TAG_CLOSURE).unwrap();
+    let vector = arena.allocate(8, TAG_VECTOR).unwrap();
+    // 0: garbage (16) | 16: closure (24) | 40: pad 8, 48: vector (16)
+    assert_eq!(garbage.get(), 0);
+    assert_eq!(closure.get(), 16);
+    assert_eq!(vector.get(), 48);
+    assert_eq!(arena.padding_bytes(), 8);
+    // A closure's first word is its body pointer, which is always followed;
+    // point it at the vector rather than at the garbage at address 0
+    let data = unsafe { arena.get_data_mut(closure) };
+    data[0..4].copy_from_slice(&vector.get().to_le_bytes());
+
+    // Padding fillers are never live, so they count as wasted space
+    unsafe {
+        arena.mark_object(closure);
+        arena.mark_object(vector);
+    }
+    assert!((arena.fragmentation_ratio() - 24.0 / 64.0).abs() < f32::EPSILON);
+
+    // Compaction moves the vector down, and must keep it on a 16-byte boundary
+    arena.collect_garbage(&[closure, vector]).unwrap();
+
+    // 0: closure (24) | 24: pad 8, 32: vector (16)
+    assert_eq!(arena.next_free(), 48);
+    assert_eq!(arena.padding_bytes(), 8);
+    let header = unsafe { arena.get_header(HeapPtr::new(32)) };
+    assert_eq!(header.tag, TAG_VECTOR);
+    assert_eq!(header.size, 8);
+}
+
+#[test]
+fn test_relocating_collection_rewrites_internal_pointers() {
+    let mut arena = ObjectArena::with_capacity(1024);
+    let first = arena.allocate(32, TAG_PAIR).unwrap();
+    let _garbage = arena.allocate(32, TAG_PAIR).unwrap();
+    let tail = arena.allocate(32, TAG_PAIR).unwrap();
+    let head = arena.allocate(32, TAG_PAIR).unwrap();
+    let head_data = unsafe { arena.get_data_mut(head) };
+    head_data[16..32].copy_from_slice(&heap_slot(tail));
+    // An inline car whose bytes spell an old address is not a pointer
+    head_data[0..16].copy_from_slice(&inline_slot(0));
+    head_data[4..8].copy_from_slice(&tail.get().to_le_bytes());
+
+    let relocations = arena.collect_garbage_relocating(&[first, head]).unwrap();
+
+    // 0: first (40) | 40: tail (40) | 80: head (40)
+    assert!(!relocations.contains_key(&first));
+    assert_eq!(relocations.get(&tail), Some(&HeapPtr::new(40)));
+    assert_eq!(relocations.get(&head), Some(&HeapPtr::new(80)));
+    let head_data = unsafe { arena.get_data(HeapPtr::new(80)) };
+    assert_eq!(head_data[20..24], 40u32.to_le_bytes());
+    assert_eq!(head_data[4..8], tail.get().to_le_bytes());
+
+    // Marks are recomputed each collection, so dropping the roots frees everything
+    arena.collect_garbage_relocating(&[]).unwrap();
+    assert_eq!(arena.next_free(), 0);
+}
+
+#[test]
+fn test_relocating_collection_only_rewrites_vector_pointer_slots() {
+    let mut arena = ObjectArena::with_capacity(1024);
+    let _garbage = arena.allocate(32, TAG_PAIR).unwrap();
+    let element = arena.allocate(32, TAG_PAIR).unwrap();
+    let vector = arena.allocate(32, TAG_VECTOR).unwrap();
+    let data = unsafe { arena.get_data_mut(vector) };
+    data[0..16].copy_from_slice(&heap_slot(element));
+    // An inline element whose bytes spell the element's old address
+    data[16..32].copy_from_slice(&inline_slot(0));
+    data[20..24].copy_from_slice(&element.get().to_le_bytes());
+
+    let relocations = arena.collect_garbage_relocating(&[vector]).unwrap();
+
+    let moved = relocations[&element];
+    let data = unsafe { arena.get_data(relocations[&vector]) };
+    assert_eq!(data[4..8], moved.get().to_le_bytes());
+    assert_eq!(data[20..24], element.get().to_le_bytes());
+}
@@ -10,6 +10,20 @@ pub struct CapRequest {
     pub justification: String,
     pub requested_at: u64,
     pub granted: Option<bool>,
+    /// Scheduler ticks to wait for a decision before auto-denying; `None` waits forever
+    pub timeout: Option<u64>,
+    /// Scheduler tick on which the request was made
+    pub requested_at_tick: u64,
+}
+
+impl CapRequest {
+    /// Whether the request is still undecided after its timeout has elapsed at `tick`
+    pub fn is_expired(&self, tick: u64) -> bool {
+        self.granted.is_none()
+            && self
+                .timeout
+                .is_some_and(|timeout| tick.saturating_sub(self.requested_at_tick) >= timeout)
+    }
 }
 
 /// Represents an actor in the Physics World.
@@ -18,7 +32,10 @@ pub struct Actor {
     pub vm: VmState,
     pub mailbox: Vec<Value>, // Incoming messages
     pub is_waiting: bool,
-    // V2 Capability System - Added capability state
+    // V2 Capability System - View of the capabilities held in `vm`, the one
+    // place HasCap, privileged opcodes and scheduler decisions all consult.
+    // Those listed when the actor is added are granted to its VM; after that
+    // the scheduler refreshes this view whenever the VM's set may change
     pub capabilities: HashSet<crate::types::Capability>,
     pub capability_requests: Vec<CapRequest>,
     pub parent_id: Option<u32>,
@@ -26,3 +43,16 @@ pub struct Actor {
     pub priority: u8,                // 0-255 range, higher = more important
     pub priority_boost: Option<u32>, // Temporary priority boost (step count)
 }
+
+impl Actor {
+    /// Refresh `capabilities` from the capabilities `vm` currently holds
+    pub fn sync_capabilities(&mut self) {
+        self.capabilities = self
+            .vm
+            .capabilities
+            .iter()
+            .filter(|capability| self.vm.has_capability(capability))
+            .cloned()
+            .collect();
+    }
+}
@@ -1,7 +1,14 @@
 /// Capability management for the Physics World scheduler
 ///
 /// This module provides capability types, audit logging, and delegation logic
-/// for the Physics World scheduler.
+/// for the Physics World scheduler, and the runtime decision flow: an actor
+/// whose VM stops with `WaitingForCapability` is parked with its request
+/// queued, and stays parked until `grant` or `deny` resumes it.
+use super::actor::CapRequest;
+use super::core::PhysicsScheduler;
+use super::error::PhysicsError;
+use super::events::SchedulerEvent;
+use crate::types::{Capability, Value};
 
 /// Capability audit log entry for tracking capability operations
 #[derive(Debug, Clone)]
@@ -52,3 +59,156 @@ pub struct ConsensusVoteResult {
     pub abstain: u32,
     pub total: u32,
 }
+
+impl PhysicsScheduler {
+    /// Park `actor_id` on its request for `capability`, leaving the decision
+    /// to a later [`Self::grant`] or [`Self::deny`] instead of the automatic
+    /// policy of `handle_capability_request`.
+    ///
+    /// Call this on `TickResult::ActorWaitingForCapability`. The request
+    /// times out after `capability_request_timeout` ticks, if set.
+    pub fn park_capability_request(
+        &mut self,
+        actor_id: u32,
+        capability: Capability,
+        justification: &str,
+    ) -> Result<(), PhysicsError> {
+        let requested_at = self.next_request_id;
+        let requested_at_tick = self.tick_count;
+        let timeout = self.capability_request_timeout;
+        let actor = self
+            .actors
+            .iter_mut()
+            .find(|a| a.id == actor_id)
+            .ok_or(PhysicsError::ActorNotFound(actor_id))?;
+
+        actor.capability_requests.push(CapRequest {
+            capability: capability.clone(),
+            justification: justification.to_string(),
+            requested_at,
+            granted: None,
+            timeout,
+            requested_at_tick,
+        });
+        actor.is_waiting = true;
+
+        self.next_request_id += 1;
+        self.capability_audit_log.push(CapAuditEntry {
+            timestamp: requested_at,
+            actor_id,
+            operation: CapOperation::Request,
+            capability: capability.clone(),
+            result: CapDecisionResult::Pending,
+        });
+        self.event_log
+            .record(|seq| SchedulerEvent::CapabilityDecided {
+                seq,
+                actor_id,
+                capability,
+                granted: None,
+            });
+        Ok(())
+    }
+
+    /// Requests still awaiting a decision, oldest first
+    pub fn pending_capability_requests(&self) -> Vec<(u32, Capability)> {
+        let mut pending: Vec<_> = self
+            .actors
+            .iter()
+            .flat_map(|actor| {
+                actor
+                    .capability_requests
+                    .iter()
+                    .filter(|r| r.granted.is_none())
+                    .map(move |r| (r.requested_at, actor.id, r.capability.clone()))
+            })
+            .collect();
+        pending.sort_by_key(|(requested_at, ..)| *requested_at);
+        pending
+            .into_iter()
+            .map(|(_, actor_id, capability)| (actor_id, capability))
+            .collect()
+    }
+
+    /// Grant `actor_id` its pending request for `capability`: the actor
+    /// holds the capability from now on, in its VM too, and resumes with
+    /// `true` on its stack.
+    pub fn grant(&mut self, actor_id: u32, capability: Capability) -> Result<(), PhysicsError> {
+        self.decide_capability_request(actor_id, capability, true)
+    }
+
+    /// Deny `actor_id` its pending request for `capability`; the actor
+    /// resumes with `false` on its stack.
+    pub fn deny(&mut self, actor_id: u32, capability: Capability) -> Result<(), PhysicsError> {
+        self.decide_capability_request(actor_id, capability, false)
+    }
+
+    /// Take `capability` away from `actor_id` on the scheduler's authority,
+    /// so the actor's next `HasCap` check for it fails
+    pub fn revoke(&mut self, actor_id: u32, capability: &Capability) -> Result<(), PhysicsError> {
+        let actor = self
+            .actors
+            .iter_mut()
+            .find(|a| a.id == actor_id)
+            .ok_or(PhysicsError::ActorNotFound(actor_id))?;
+        actor.vm.revoke_capability(capability);
+        actor.sync_capabilities();
+
+        self.capability_audit_log.push(CapAuditEntry {
+            timestamp: self.next_request_id,
+            actor_id,
+            operation: CapOperation::Revoke,
+            capability: capability.clone(),
+            result: CapDecisionResult::Granted,
+        });
+        self.next_request_id += 1;
+        Ok(())
+    }
+
+    /// Settle the oldest pending request of `actor_id` for `capability` and
+    /// resume the actor with the decision on its stack
+    pub(super) fn decide_capability_request(
+        &mut self,
+        actor_id: u32,
+        capability: Capability,
+        granted: bool,
+    ) -> Result<(), PhysicsError> {
+        let actor = self
+            .actors
+            .iter()
+            .find(|a| a.id == actor_id)
+            .ok_or(PhysicsError::ActorNotFound(actor_id))?;
+        let requested_at = actor
+            .capability_requests
+            .iter()
+            .find(|r| r.granted.is_none() && r.capability == capability)
+            .map(|r| r.requested_at)
+            .ok_or_else(|| {
+                PhysicsError::CapabilityError(format!(
+                    "Actor {actor_id} has no pending request for {capability:?}"
+                ))
+            })?;
+
+        self.settle_capability_request(actor_id, &capability, granted);
+        if let Some(entry) = self
+            .capability_audit_log
+            .iter_mut()
+            .find(|e| e.timestamp == requested_at && e.actor_id == actor_id)
+        {
+            entry.result = if granted {
+                CapDecisionResult::Granted
+            } else {
+                CapDecisionResult::Denied
+            };
+        }
+
+        if let Some(actor) = self.actors.iter_mut().find(|a| a.id == actor_id) {
+            if granted {
+                actor.vm.grant_capability(capability);
+                actor.sync_capabilities();
+            }
+            actor.vm.stack.push(Value::Bool(granted));
+        }
+        Ok(())
+    }
+}
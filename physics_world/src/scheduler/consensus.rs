@@ -49,17 +49,20 @@ impl PhysicsScheduler {
         if consensus_result.approve >= required_approval {
             // Consensus approved - grant the capability
             if let Some(target) = self.actors.iter_mut().find(|a| a.id == requester_id) {
-                target.capabilities.insert(capability.clone());
+                target.vm.grant_capability(capability.clone());
+                target.sync_capabilities();
 
                 // Update the audit log with final decision
                 self.capability_audit_log.last_mut().unwrap().result =
                     CapDecisionResult::ConsensusApproved;
             }
+            self.settle_capability_request(requester_id, capability, true);
             Ok(CapDecision::Granted)
         } else if consensus_result.deny > consensus_result.total / 2 {
             // Majority denial - deny the capability
             self.capability_audit_log.last_mut().unwrap().result =
                 CapDecisionResult::ConsensusDenied;
+            self.settle_capability_request(requester_id, capability, false);
             Ok(CapDecision::Denied)
         } else {
             // Still pending
@@ -96,8 +99,7 @@ impl PhysicsScheduler {
             .actors
             .iter()
             .filter(|a| {
-                a.capabilities
-                    .contains(&crate::types::Capability::MetaGrant)
+                a.vm.has_capability(&crate::types::Capability::MetaGrant)
             })
             .count() as u32;
 
@@ -4,26 +4,30 @@
 /// including actor management, message passing, and tick-based execution.
 use crate::types::Value;
 use crate::vm::error::VmError as DetailedVmError;
-use crate::vm::state::InstructionResult;
+use crate::vm::state::{CapabilityExpiry, InstructionResult};
 
 use super::{
     actor::Actor, error::PhysicsError, CapAuditEntry, CapDecision, CapDecisionResult, CapOperation,
-    CapRequest,
+    CapRequest, SchedulerEvent, SchedulerEventListener, SchedulerEventLog, SchedulingOrder,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Manages multiple actors and enforces fair, deterministic execution.
 pub struct PhysicsScheduler {
     pub actors: Vec<Actor>,
     pub current_actor_index: usize,
-    pub message_queues: HashMap<u32, Vec<Value>>, // External inbox per actor
+    pub message_queues: BTreeMap<u32, Vec<Value>>, // External inbox per actor, delivered in id order
     // V2 Capability System - Added capability authority state
     pub capability_audit_log: Vec<CapAuditEntry>,
     pub next_request_id: u64,
+    pub next_actor_id: u32, // Id the next spawned actor gets; ids are never reused
+    pub tick_count: u64,    // Ticks run so far, the clock for capability request timeouts
+    pub capability_request_timeout: Option<u64>, // Default timeout for pending capability requests
     // V2 Priority Scheduling - Added priority scheduling state
-    pub use_priority_scheduling: bool, // Enable/disable priority scheduling
-    pub starvation_counter: u64,       // Track steps since last low-priority actor ran
-    pub starvation_threshold: u64,     // When to force run a low-priority actor
+    pub starvation_counter: u64, // Track steps since last low-priority actor ran
+    pub starvation_threshold: u64, // When to force run a low-priority actor
+    pub scheduling_order: SchedulingOrder, // How runnable actors are ordered
+    pub schedule_cursor: usize,  // Ticks handed out in the current order
     // V2 Resource Management - Added resource tracking and management
     pub global_step_count: u64, // Global step counter for resource accounting
     pub total_memory_usage: usize, // Total memory usage across all actors
@@ -31,21 +35,27 @@ pub struct PhysicsScheduler {
     pub cpu_time_limit: u64,    // Global CPU time limit
     pub resource_usage_history: Vec<ResourceUsageSnapshot>, // Historical resource usage
     pub resource_quota_system: ResourceQuotaSystem, // Resource quota management
+    pub event_log: SchedulerEventLog, // Recent spawns, yields, capability decisions and terminations
 }
 
 /// Clone implementation for PhysicsScheduler
-/// Creates a new scheduler with the same configuration but empty state
+/// Creates a new scheduler with the same configuration, id counters and tick
+/// clock, but empty state
 impl Clone for PhysicsScheduler {
     fn clone(&self) -> Self {
         Self {
             actors: Vec::new(), // Don't clone actors - they should be managed separately
             current_actor_index: 0,
-            message_queues: HashMap::new(),
+            message_queues: BTreeMap::new(),
             capability_audit_log: Vec::new(),
             next_request_id: self.next_request_id,
-            use_priority_scheduling: self.use_priority_scheduling,
+            next_actor_id: self.next_actor_id,
+            tick_count: self.tick_count,
+            capability_request_timeout: self.capability_request_timeout,
             starvation_counter: 0,
             starvation_threshold: self.starvation_threshold,
+            scheduling_order: self.scheduling_order,
+            schedule_cursor: 0,
             global_step_count: 0,
             total_memory_usage: 0,
             memory_limit: self.memory_limit,
@@ -58,6 +68,7 @@ impl Clone for PhysicsScheduler {
                 global_memory_limit: self.resource_quota_system.global_memory_limit,
                 global_cpu_limit: self.resource_quota_system.global_cpu_limit,
             },
+            event_log: SchedulerEventLog::with_capacity(self.event_log.capacity()),
         }
     }
 }
@@ -124,6 +135,9 @@ pub enum TickResult {
     ActorFinished(u32, Value),
     ActorErrored(u32, DetailedVmError),
     ActorWaitingForCapability(u32, crate::types::Capability),
+    /// A pending capability request outlived its timeout and was denied;
+    /// the actor resumes with a `CapabilityDenied` error value on its stack
+    CapabilityRequestTimedOut(u32, crate::types::Capability),
 }
 
 impl PhysicsScheduler {
@@ -132,12 +146,16 @@ impl PhysicsScheduler {
         Self {
             actors: Vec::new(),
             current_actor_index: 0,
-            message_queues: HashMap::new(),
+            message_queues: BTreeMap::new(),
             capability_audit_log: Vec::new(),
             next_request_id: 0,
-            use_priority_scheduling: false, // Default to round-robin for backward compatibility
+            next_actor_id: 0,
+            tick_count: 0,
+            capability_request_timeout: None, // Pending requests wait forever by default
             starvation_counter: 0,
             starvation_threshold: 1000, // Default threshold to prevent starvation
+            scheduling_order: SchedulingOrder::Fifo,
+            schedule_cursor: 0,
             // V2 Resource Management - Initialize resource tracking
             global_step_count: 0,
             total_memory_usage: 0,
@@ -151,6 +169,7 @@ impl PhysicsScheduler {
                 global_memory_limit: usize::MAX,
                 global_cpu_limit: u64::MAX,
             },
+            event_log: SchedulerEventLog::default(),
         }
     }
 
@@ -163,15 +182,48 @@ impl PhysicsScheduler {
             ));
         }
 
+        self.tick_count += 1;
+
+        // Auto-deny capability requests nobody answered in time
+        if let Some((actor_id, capability)) = self.expire_capability_requests() {
+            return Ok(TickResult::CapabilityRequestTimedOut(actor_id, capability));
+        }
+
         // Select next actor based on scheduling mode
-        if self.use_priority_scheduling {
-            self.select_next_actor_by_priority();
+        match self.scheduling_order {
+            SchedulingOrder::Deterministic(seed) => self.select_next_actor_deterministically(seed),
+            SchedulingOrder::Priority => self.select_next_actor_by_priority(),
+            SchedulingOrder::Fifo => {}
         }
         // Note: For round-robin, we don't advance here - current_actor_index stays the same
         // until the actor yields/finishes/errors, then we advance in the result handling
 
-        // Get current actor
+        // Actors blocked on a capability decision do not run
+        if self.actors[self.current_actor_index].is_waiting {
+            let actor_count = self.actors.len();
+            match (1..actor_count)
+                .map(|offset| (self.current_actor_index + offset) % actor_count)
+                .find(|&index| !self.actors[index].is_waiting)
+            {
+                Some(index) => self.current_actor_index = index,
+                None => {
+                    return Err(PhysicsError::SchedulerError(
+                        "All actors are waiting for capabilities".to_string(),
+                    ))
+                }
+            }
+        }
+
+        // Run the current actor, then refresh its view of what its VM holds
         let current_index = self.current_actor_index;
+        let result = self.run_actor(current_index);
+        self.actors[current_index].sync_capabilities();
+        result
+    }
+
+    /// Runs the actor at `current_index` until it yields, finishes, errors,
+    /// or requests a capability
+    fn run_actor(&mut self, current_index: usize) -> Result<TickResult, PhysicsError> {
         let actor = &mut self.actors[current_index];
 
         // Process any messages in the actor's mailbox first
@@ -185,20 +237,60 @@ impl PhysicsScheduler {
 
         // Execute the actor's VM until it yields, finishes, errors, or requests a capability
         loop {
-            match actor.vm.step() {
+            let actor = &mut self.actors[current_index];
+            let step = actor.vm.step();
+
+            // Queue messages sent by the step for their targets
+            for (target, message) in actor.vm.outbox.drain(..) {
+                self.message_queues.entry(target).or_default().push(message);
+            }
+
+            match step {
                 Ok(InstructionResult::Continue) => {
                     // Continue executing
                     continue;
                 }
+                Ok(InstructionResult::Spawn { closure, args }) => {
+                    // Start the child and give its id back to the parent
+                    let parent_id = actor.id;
+                    let child_id = self.allocate_actor_id();
+                    let child_vm = self.actors[current_index]
+                        .vm
+                        .spawn_child(child_id, closure, args);
+                    self.add_actor(Actor {
+                        id: child_id,
+                        vm: child_vm,
+                        mailbox: Vec::new(),
+                        is_waiting: false,
+                        capabilities: HashSet::new(),
+                        capability_requests: Vec::new(),
+                        parent_id: Some(parent_id),
+                        priority: 128, // Default priority
+                        priority_boost: None,
+                    });
+                    self.actors[current_index]
+                        .vm
+                        .stack
+                        .push(Value::ActorId(child_id));
+                    continue;
+                }
                 Ok(InstructionResult::Yield) => {
                     // Actor yielded, move to next actor
                     let actor_id = actor.id;
+                    self.event_log
+                        .record(|seq| SchedulerEvent::ActorYielded { seq, actor_id });
                     self.advance_to_next_actor();
                     return Ok(TickResult::ActorYielded(actor_id));
                 }
                 Ok(InstructionResult::Finished(value)) => {
                     // Actor finished, move to next actor
                     let actor_id = actor.id;
+                    self.event_log
+                        .record(|seq| SchedulerEvent::ActorTerminated {
+                            seq,
+                            actor_id,
+                            errored: false,
+                        });
                     self.advance_to_next_actor();
                     return Ok(TickResult::ActorFinished(actor_id, value));
                 }
@@ -215,6 +307,12 @@ impl PhysicsScheduler {
                     let context = actor.vm.create_error_context();
                     let detailed_error =
                         crate::vm::error::WithContext::with_context(vm_error, context);
+                    self.event_log
+                        .record(|seq| SchedulerEvent::ActorTerminated {
+                            seq,
+                            actor_id,
+                            errored: true,
+                        });
                     self.advance_to_next_actor();
                     return Ok(TickResult::ActorErrored(actor_id, detailed_error));
                 }
@@ -230,8 +328,17 @@ impl PhysicsScheduler {
             .push(message);
     }
 
+    /// Id for a new actor, above every id handed out or added so far, so an
+    /// actor that finished or migrated away never has its id reused
+    pub fn allocate_actor_id(&mut self) -> u32 {
+        let actor_id = self.next_actor_id;
+        self.next_actor_id += 1;
+        actor_id
+    }
+
     /// Advances to the next actor in round-robin fashion.
     pub fn advance_to_next_actor(&mut self) {
+        self.schedule_cursor += 1;
         if self.actors.is_empty() {
             self.current_actor_index = 0;
             return;
@@ -241,8 +348,27 @@ impl PhysicsScheduler {
     }
 
     /// Adds a new actor to the scheduler.
-    pub fn add_actor(&mut self, actor: Actor) {
+    pub fn add_actor(&mut self, mut actor: Actor) {
+        // Capabilities the actor was built with are held by its VM from now on
+        for capability in actor.capabilities.clone() {
+            actor.vm.grant_capability(capability);
+        }
+        actor.sync_capabilities();
+        let actor_id = actor.id;
+        self.next_actor_id = self.next_actor_id.max(actor_id.saturating_add(1));
         self.actors.push(actor);
+        self.event_log
+            .record(|seq| SchedulerEvent::ActorSpawned { seq, actor_id });
+    }
+
+    /// The most recent scheduler events, oldest first
+    pub fn events(&self) -> &[SchedulerEvent] {
+        self.event_log.events()
+    }
+
+    /// Streams every future scheduler event to `listener` as it is recorded
+    pub fn set_event_listener(&mut self, listener: SchedulerEventListener) {
+        self.event_log.set_listener(listener);
     }
 
     /// Gets the current actor ID.
@@ -252,7 +378,7 @@ impl PhysicsScheduler {
 
     /// Delivers external messages to actors' mailboxes.
     pub fn deliver_external_messages(&mut self) {
-        for (actor_id, messages) in self.message_queues.drain() {
+        for (actor_id, messages) in std::mem::take(&mut self.message_queues) {
             if let Some(actor) = self.actors.iter_mut().find(|a| a.id == actor_id) {
                 actor.mailbox.extend(messages);
             }
@@ -269,18 +395,37 @@ impl PhysicsScheduler {
         capability: &crate::types::Capability,
     ) -> bool {
         if let Some(actor) = self.actors.iter().find(|a| a.id == actor_id) {
-            actor.capabilities.contains(capability)
+            actor.vm.has_capability(capability)
         } else {
             false
         }
     }
 
     /// V2 Capability System - Handle capability requests from actors
+    ///
+    /// Requests left pending use the scheduler's `capability_request_timeout`.
     pub fn handle_capability_request(
         &mut self,
         requester_id: u32,
         capability: crate::types::Capability,
         justification: &str,
+    ) -> CapDecision {
+        self.handle_capability_request_with_timeout(
+            requester_id,
+            capability,
+            justification,
+            self.capability_request_timeout,
+        )
+    }
+
+    /// V2 Capability System - Handle a capability request that is auto-denied if
+    /// still pending after `timeout` scheduler ticks
+    pub fn handle_capability_request_with_timeout(
+        &mut self,
+        requester_id: u32,
+        capability: crate::types::Capability,
+        justification: &str,
+        timeout: Option<u64>,
     ) -> CapDecision {
         // Log the request
         let timestamp = self.next_request_id;
@@ -299,13 +444,15 @@ impl PhysicsScheduler {
         if actor.is_none() {
             self.capability_audit_log.last_mut().unwrap().result =
                 CapDecisionResult::Error("Actor not found".to_string());
+            self.record_capability_decision(requester_id, &capability, Some(false));
             return CapDecision::Denied;
         }
         let actor = actor.unwrap();
 
         // Check if actor already has the capability
-        if actor.capabilities.contains(&capability) {
+        if actor.vm.has_capability(&capability) {
             self.capability_audit_log.last_mut().unwrap().result = CapDecisionResult::Granted;
+            self.record_capability_decision(requester_id, &capability, Some(true));
             return CapDecision::Granted;
         }
 
@@ -315,6 +462,8 @@ impl PhysicsScheduler {
             justification: justification.to_string(),
             requested_at: timestamp,
             granted: None,
+            timeout,
+            requested_at_tick: self.tick_count,
         });
 
         // Advanced capability decision logic with delegation and consensus
@@ -337,8 +486,8 @@ impl PhysicsScheduler {
             crate::types::Capability::MacroUnsafe => {
                 // Unsafe macros require MetaGrant
                 if actor
-                    .capabilities
-                    .contains(&crate::types::Capability::MetaGrant)
+                    .vm
+                    .has_capability(&crate::types::Capability::MetaGrant)
                 {
                     CapDecision::Granted
                 } else {
@@ -365,6 +514,7 @@ impl PhysicsScheduler {
                 CapDecision::Granted
             }
             crate::types::Capability::SysClock => CapDecision::Granted,
+            crate::types::Capability::SysGc => CapDecision::Granted,
             // Resource capabilities
             crate::types::Capability::ResourceExtraMemory(_) => CapDecision::Granted,
             crate::types::Capability::ResourceExtraTime(_) => CapDecision::Granted,
@@ -386,20 +536,139 @@ impl PhysicsScheduler {
             CapDecision::PendingConsensus => CapDecisionResult::ConsensusRequired,
         };
 
-        // If granted, add the capability to the actor
-        if let CapDecision::Granted = decision {
-            actor.capabilities.insert(capability);
+        let granted = match decision {
+            CapDecision::Granted => Some(true),
+            CapDecision::Denied => Some(false),
+            CapDecision::PendingConsensus => None,
+        };
+        self.event_log
+            .record(|seq| SchedulerEvent::CapabilityDecided {
+                seq,
+                actor_id: requester_id,
+                capability: capability.clone(),
+                granted,
+            });
+
+        // If granted, add the capability to the actor; if pending, block it until decided
+        match decision {
+            CapDecision::Granted => {
+                actor.vm.grant_capability(capability);
+                actor.sync_capabilities();
+            }
+            CapDecision::PendingConsensus => actor.is_waiting = true,
+            CapDecision::Denied => {}
         }
 
         decision
     }
 
+    /// Records the final decision on an actor's pending request for `capability`
+    /// and unblocks the actor once nothing else is pending
+    pub fn settle_capability_request(
+        &mut self,
+        actor_id: u32,
+        capability: &crate::types::Capability,
+        granted: bool,
+    ) {
+        if let Some(actor) = self.actors.iter_mut().find(|a| a.id == actor_id) {
+            if let Some(request) = actor
+                .capability_requests
+                .iter_mut()
+                .find(|r| r.granted.is_none() && r.capability == *capability)
+            {
+                request.granted = Some(granted);
+                self.event_log
+                    .record(|seq| SchedulerEvent::CapabilityDecided {
+                        seq,
+                        actor_id,
+                        capability: capability.clone(),
+                        granted: Some(granted),
+                    });
+            }
+            actor.is_waiting = actor
+                .capability_requests
+                .iter()
+                .any(|r| r.granted.is_none());
+        }
+    }
+
+    /// Records the decision on a capability request in the event log
+    fn record_capability_decision(
+        &mut self,
+        actor_id: u32,
+        capability: &crate::types::Capability,
+        granted: Option<bool>,
+    ) {
+        self.event_log
+            .record(|seq| SchedulerEvent::CapabilityDecided {
+                seq,
+                actor_id,
+                capability: capability.clone(),
+                granted,
+            });
+    }
+
+    /// Denies the first pending capability request whose timeout has elapsed,
+    /// resuming its actor with a `CapabilityDenied` error value on the stack
+    fn expire_capability_requests(&mut self) -> Option<(u32, crate::types::Capability)> {
+        let tick = self.tick_count;
+        let (actor_id, request) = self.actors.iter().find_map(|actor| {
+            actor
+                .capability_requests
+                .iter()
+                .find(|r| r.is_expired(tick))
+                .map(|r| (actor.id, r.clone()))
+        })?;
+
+        self.settle_capability_request(actor_id, &request.capability, false);
+        if let Some(entry) = self
+            .capability_audit_log
+            .iter_mut()
+            .find(|e| e.timestamp == request.requested_at && e.actor_id == actor_id)
+        {
+            entry.result = CapDecisionResult::Denied;
+        }
+        if let Some(actor) = self.actors.iter_mut().find(|a| a.id == actor_id) {
+            actor.vm.stack.push(Value::Error(format!(
+                "CapabilityDenied: request for {:?} timed out",
+                request.capability
+            )));
+        }
+
+        Some((actor_id, request.capability))
+    }
+
     /// V2 Capability System - Grant a capability to an actor with delegation validation
     pub fn grant_capability(
         &mut self,
         granter_id: u32,
         target_id: u32,
         capability: crate::types::Capability,
+    ) -> Result<(), PhysicsError> {
+        self.grant_capability_with_expiry(granter_id, target_id, capability, None)
+    }
+
+    /// V2 Capability System - Grant a capability that lapses after `expiry`,
+    /// counted on the target's VM, with the same validation as `grant_capability`
+    ///
+    /// Once the window has passed the target's `HasCap` checks fail without
+    /// an explicit revoke.
+    pub fn grant_capability_until(
+        &mut self,
+        granter_id: u32,
+        target_id: u32,
+        capability: crate::types::Capability,
+        expiry: CapabilityExpiry,
+    ) -> Result<(), PhysicsError> {
+        self.grant_capability_with_expiry(granter_id, target_id, capability, Some(expiry))
+    }
+
+    fn grant_capability_with_expiry(
+        &mut self,
+        granter_id: u32,
+        target_id: u32,
+        capability: crate::types::Capability,
+        expiry: Option<CapabilityExpiry>,
     ) -> Result<(), PhysicsError> {
         // Find the granter actor
         let granter = self
@@ -417,8 +686,8 @@ impl PhysicsScheduler {
 
         // Check if granter has MetaGrant capability for delegation
         if !granter
-            .capabilities
-            .contains(&crate::types::Capability::MetaGrant)
+            .vm
+            .has_capability(&crate::types::Capability::MetaGrant)
         {
             return Err(PhysicsError::CapabilityError(
                 "Granter does not have MetaGrant capability".to_string(),
@@ -426,7 +695,7 @@ impl PhysicsScheduler {
         }
 
         // Check if granter has the capability they're trying to delegate
-        if !granter.capabilities.contains(&capability) {
+        if !granter.vm.has_capability(&capability) {
             return Err(PhysicsError::CapabilityError(
                 "Granter does not have the capability to delegate".to_string(),
             ));
@@ -449,9 +718,25 @@ impl PhysicsScheduler {
         });
         self.next_request_id += 1;
 
-        // Add the capability to the target
+        // The grant also answers every request the target is parked on for it
+        while self.actors.iter().any(|a| {
+            a.id == target_id
+                && a.capability_requests
+                    .iter()
+                    .any(|r| r.granted.is_none() && r.capability == capability)
+        }) {
+            self.decide_capability_request(target_id, capability.clone(), true)?;
+        }
+
+        // Add the capability to the target's VM, which holds its capabilities.
+        // This comes after answering requests, whose grants never lapse, so
+        // that an expiry sticks
         if let Some(target_actor) = self.actors.iter_mut().find(|a| a.id == target_id) {
-            target_actor.capabilities.insert(capability);
+            match expiry {
+                Some(expiry) => target_actor.vm.grant_capability_until(capability, expiry),
+                None => target_actor.vm.grant_capability(capability),
+            }
+            target_actor.sync_capabilities();
         }
 
         Ok(())
@@ -464,7 +749,7 @@ impl PhysicsScheduler {
         target: &Actor,
     ) -> bool {
         // Granter must have the capability they're trying to delegate
-        if !granter.capabilities.contains(capability) {
+        if !granter.vm.has_capability(capability) {
             return false;
         }
 
@@ -524,9 +809,10 @@ impl PhysicsScheduler {
         });
         self.next_request_id += 1;
 
-        // Remove the capability from the target
+        // Remove the capability from the target's VM
         if let Some(target_actor) = self.actors.iter_mut().find(|a| a.id == target_id) {
-            target_actor.capabilities.remove(capability);
+            target_actor.vm.revoke_capability(capability);
+            target_actor.sync_capabilities();
         }
 
         Ok(())
@@ -545,8 +831,8 @@ impl PhysicsScheduler {
 
         // MetaGrant holders can revoke most capabilities
         if revoker
-            .capabilities
-            .contains(&crate::types::Capability::MetaGrant)
+            .vm
+            .has_capability(&crate::types::Capability::MetaGrant)
         {
             // But cannot revoke MetaGrant from others unless they're the parent
             if let crate::types::Capability::MetaGrant = capability {
@@ -0,0 +1,130 @@
+/// Structured event log for the Physics World scheduler
+///
+/// The scheduler records actor spawns, yields, capability decisions and
+/// terminations as they happen, giving a timeline of a multi-actor run.
+/// Only the most recent events are kept; a listener sees every event as it
+/// is recorded.
+use crate::types::Capability;
+use std::sync::Arc;
+
+/// Number of events kept by a scheduler's log unless configured otherwise
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 1024;
+
+/// A scheduler event. `seq` increases by one with every event a scheduler
+/// records, and `actor_id` is the actor the event is about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchedulerEvent {
+    /// The actor was added to the scheduler
+    ActorSpawned { seq: u64, actor_id: u32 },
+    /// The actor executed `Yield` and gave up the rest of its tick
+    ActorYielded { seq: u64, actor_id: u32 },
+    /// A capability request by the actor was decided; `granted` is `None`
+    /// while the request waits for consensus
+    CapabilityDecided {
+        seq: u64,
+        actor_id: u32,
+        capability: Capability,
+        granted: Option<bool>,
+    },
+    /// The actor's program finished, or stopped with an error
+    ActorTerminated {
+        seq: u64,
+        actor_id: u32,
+        errored: bool,
+    },
+}
+
+impl SchedulerEvent {
+    /// Position of the event in the scheduler's timeline
+    pub fn seq(&self) -> u64 {
+        match self {
+            SchedulerEvent::ActorSpawned { seq, .. }
+            | SchedulerEvent::ActorYielded { seq, .. }
+            | SchedulerEvent::CapabilityDecided { seq, .. }
+            | SchedulerEvent::ActorTerminated { seq, .. } => *seq,
+        }
+    }
+
+    /// The actor the event is about
+    pub fn actor_id(&self) -> u32 {
+        match self {
+            SchedulerEvent::ActorSpawned { actor_id, .. }
+            | SchedulerEvent::ActorYielded { actor_id, .. }
+            | SchedulerEvent::CapabilityDecided { actor_id, .. }
+            | SchedulerEvent::ActorTerminated { actor_id, .. } => *actor_id,
+        }
+    }
+}
+
+/// Callback receiving every event as the scheduler records it
+pub type SchedulerEventListener = Arc<dyn Fn(&SchedulerEvent) + Send + Sync>;
+
+/// Bounded log of the most recent scheduler events
+#[derive(Clone)]
+pub struct SchedulerEventLog {
+    // Holds up to twice `capacity` events; only the newest `capacity` are visible
+    events: Vec<SchedulerEvent>,
+    capacity: usize,
+    next_seq: u64,
+    listener: Option<SchedulerEventListener>,
+}
+
+impl SchedulerEventLog {
+    /// Creates an empty log keeping the last `capacity` events
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            events: Vec::new(),
+            capacity,
+            next_seq: 0,
+            listener: None,
+        }
+    }
+
+    /// Maximum number of events kept
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The kept events, oldest first
+    pub fn events(&self) -> &[SchedulerEvent] {
+        &self.events[self.events.len().saturating_sub(self.capacity)..]
+    }
+
+    /// Records an event built from the next sequence number
+    pub fn record(&mut self, event: impl FnOnce(u64) -> SchedulerEvent) {
+        let event = event(self.next_seq);
+        self.next_seq += 1;
+        if let Some(listener) = &self.listener {
+            listener(&event);
+        }
+
+        // Drop old events in batches so recording stays amortized O(1)
+        if self.events.len() >= self.capacity.saturating_mul(2).max(1) {
+            let excess = self.events.len() + 1 - self.capacity.max(1);
+            self.events.drain(..excess);
+        }
+        self.events.push(event);
+    }
+
+    /// Sends every future event to `listener`, replacing any previous one
+    pub fn set_listener(&mut self, listener: SchedulerEventListener) {
+        self.listener = Some(listener);
+    }
+}
+
+impl Default for SchedulerEventLog {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_EVENT_LOG_CAPACITY)
+    }
+}
+
+impl std::fmt::Debug for SchedulerEventLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SchedulerEventLog")
+            .field("events", &self.events())
+            .field("capacity", &self.capacity)
+            .field("next_seq", &self.next_seq)
+            .field("listener", &self.listener.is_some())
+            .finish()
+    }
+}
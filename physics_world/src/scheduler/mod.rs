@@ -8,7 +8,9 @@ pub mod consensus;
 pub mod core;
 pub mod debug;
 pub mod error;
+pub mod events;
 pub mod execution;
+pub mod ordering;
 pub mod priority;
 pub mod resource;
 
@@ -16,4 +18,6 @@ pub use actor::*;
 pub use capability::*;
 pub use core::*;
 pub use error::*;
+pub use events::*;
 pub use execution::*;
+pub use ordering::SchedulingOrder;
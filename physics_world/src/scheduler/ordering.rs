@@ -0,0 +1,62 @@
+/// Actor ordering policies for the Physics World scheduler
+use super::core::PhysicsScheduler;
+
+/// Order in which the scheduler hands out ticks to actors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchedulingOrder {
+    /// Round-robin in the order actors were added
+    #[default]
+    Fifo,
+    /// Highest effective priority first, with starvation protection
+    Priority,
+    /// Round-robin where each round visits the actors in a permutation of
+    /// their ids derived from the seed, so runs are reproducible
+    Deterministic(u64),
+}
+
+impl PhysicsScheduler {
+    /// Select how the scheduler orders runnable actors.
+    ///
+    /// Switching order starts a fresh round.
+    pub fn set_scheduling_order(&mut self, order: SchedulingOrder) {
+        self.scheduling_order = order;
+        self.schedule_cursor = 0;
+    }
+
+    /// Point `current_actor_index` at the actor due for the current tick
+    /// under the `Deterministic` order
+    pub(crate) fn select_next_actor_deterministically(&mut self, seed: u64) {
+        let actor_count = self.actors.len();
+        if actor_count == 0 {
+            self.current_actor_index = 0;
+            return;
+        }
+
+        let round = (self.schedule_cursor / actor_count) as u64;
+        let order = self.deterministic_round(seed, round);
+        self.current_actor_index = order[self.schedule_cursor % actor_count];
+    }
+
+    /// Actor indices for one round: actors sorted by id, then shuffled with
+    /// a generator seeded by `seed` and the round number
+    fn deterministic_round(&self, seed: u64, round: u64) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.actors.len()).collect();
+        order.sort_by_key(|&index| self.actors[index].id);
+
+        let mut state = seed ^ round.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        for i in (1..order.len()).rev() {
+            let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+        order
+    }
+}
+
+/// SplitMix64 step, a small generator with well-mixed output for any seed
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
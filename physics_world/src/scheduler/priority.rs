@@ -107,12 +107,17 @@ impl PhysicsScheduler {
 
     /// Enables priority-based scheduling.
     pub fn enable_priority_scheduling(&mut self) {
-        self.use_priority_scheduling = true;
+        self.set_scheduling_order(super::SchedulingOrder::Priority);
     }
 
     /// Disables priority-based scheduling (uses round-robin).
     pub fn disable_priority_scheduling(&mut self) {
-        self.use_priority_scheduling = false;
+        self.set_scheduling_order(super::SchedulingOrder::Fifo);
+    }
+
+    /// Whether actors are scheduled by priority.
+    pub fn uses_priority_scheduling(&self) -> bool {
+        self.scheduling_order == super::SchedulingOrder::Priority
     }
 
     /// Sets the priority of an actor.
@@ -7,7 +7,7 @@ fn test_new_scheduler() {
     assert_eq!(scheduler.actors.len(), 0);
     assert_eq!(scheduler.current_actor_index, 0);
     assert_eq!(scheduler.message_queues.len(), 0);
-    assert!(!scheduler.use_priority_scheduling); // Default should be false
+    assert!(!scheduler.uses_priority_scheduling()); // Default should be false
     assert_eq!(scheduler.starvation_threshold, 1000);
 }
 
@@ -587,14 +587,14 @@ fn test_consensus_denial() {
 fn test_priority_scheduling_enabled() {
     let mut scheduler = PhysicsScheduler::new();
     scheduler.enable_priority_scheduling();
-    assert!(scheduler.use_priority_scheduling);
+    assert!(scheduler.uses_priority_scheduling());
 }
 
 #[test]
 fn test_priority_scheduling_disabled() {
     let mut scheduler = PhysicsScheduler::new();
     scheduler.disable_priority_scheduling();
-    assert!(!scheduler.use_priority_scheduling);
+    assert!(!scheduler.uses_priority_scheduling());
 }
 
 #[test]
@@ -0,0 +1,260 @@
+/// Arbitrary-precision integers for `Value::BigInt`
+///
+/// A `BigInt` is a sign and a little-endian magnitude of 32-bit limbs with no
+/// trailing zero limbs, so every number has exactly one representation and
+/// derived equality is numeric equality. In the arena it is stored as a
+/// `TAG_BIGINT` object: one sign byte, three bytes of padding, then the limbs.
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Signed arbitrary-precision integer
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    /// Build from an `i64`
+    pub fn from_i64(value: i64) -> Self {
+        let abs = value.unsigned_abs();
+        Self::from_parts(value < 0, vec![abs as u32, (abs >> 32) as u32])
+    }
+
+    /// The value as an `i64`, if it fits
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let abs = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &limb| (acc << 32) | u64::from(limb));
+        if self.negative {
+            0i64.checked_sub_unsigned(abs)
+        } else {
+            i64::try_from(abs).ok()
+        }
+    }
+
+    /// Whether the value is negative
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Sum of two integers
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return Self::from_parts(
+                self.negative,
+                add_magnitudes(&self.magnitude, &other.magnitude),
+            );
+        }
+        // Opposite signs: subtract the smaller magnitude from the larger
+        match compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => Self::from_parts(
+                other.negative,
+                sub_magnitudes(&other.magnitude, &self.magnitude),
+            ),
+            _ => Self::from_parts(
+                self.negative,
+                sub_magnitudes(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+
+    /// Difference of two integers
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.negated())
+    }
+
+    /// Product of two integers
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let sum = u64::from(product[i + j]) + u64::from(a) * u64::from(b) + carry;
+                product[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        Self::from_parts(self.negative != other.negative, product)
+    }
+
+    /// Quotient and remainder of a division, or `None` for a zero divisor.
+    ///
+    /// Like Rust's `/` and `%` on primitive integers, the quotient is
+    /// truncated towards zero and the remainder has the sign of `self`.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.magnitude.is_empty() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitudes(&self.magnitude, &other.magnitude);
+        Some((
+            Self::from_parts(self.negative != other.negative, quotient),
+            Self::from_parts(self.negative, remainder),
+        ))
+    }
+
+    /// The integer with its sign flipped
+    pub fn negated(&self) -> BigInt {
+        Self::from_parts(!self.negative, self.magnitude.clone())
+    }
+
+    /// Arena encoding: sign byte, three padding bytes, little-endian limbs
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![u8::from(self.negative), 0, 0, 0];
+        for limb in &self.magnitude {
+            bytes.extend_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode the arena encoding produced by [`BigInt::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> BigInt {
+        let negative = bytes.first().is_some_and(|&sign| sign != 0);
+        let magnitude = bytes
+            .get(4..)
+            .unwrap_or_default()
+            .chunks_exact(4)
+            .map(|limb| u32::from_le_bytes([limb[0], limb[1], limb[2], limb[3]]))
+            .collect();
+        Self::from_parts(negative, magnitude)
+    }
+
+    /// Normalize: strip high zero limbs and never report a negative zero
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+        Self {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        Self::from_i64(value)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitudes(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.magnitude.is_empty() {
+            return write!(f, "0");
+        }
+        // Peel off base-10^9 digits by repeated short division
+        const CHUNK: u64 = 1_000_000_000;
+        let mut remaining = self.magnitude.clone();
+        let mut chunks = Vec::new();
+        while !remaining.is_empty() {
+            let mut remainder = 0u64;
+            for limb in remaining.iter_mut().rev() {
+                let current = (remainder << 32) | u64::from(*limb);
+                *limb = (current / CHUNK) as u32;
+                remainder = current % CHUNK;
+            }
+            chunks.push(remainder);
+            while remaining.last() == Some(&0) {
+                remaining.pop();
+            }
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        let mut chunks = chunks.iter().rev();
+        if let Some(first) = chunks.next() {
+            write!(f, "{first}")?;
+        }
+        for chunk in chunks {
+            write!(f, "{chunk:09}")?;
+        }
+        Ok(())
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let total = u64::from(a.get(i).copied().unwrap_or(0))
+            + u64::from(b.get(i).copied().unwrap_or(0))
+            + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// Quotient and remainder of magnitudes `a / b` for a nonzero `b`, by
+/// binary long division
+fn div_rem_magnitudes(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::with_capacity(b.len() + 1);
+    for bit in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + next bit of a
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for limb in &mut remainder {
+            let shifted_out = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = shifted_out;
+        }
+        if carry != 0 {
+            remainder.push(carry);
+        }
+
+        if compare_magnitudes(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitudes(&remainder, b);
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+/// `a - b` for magnitudes with `a >= b`
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut current = i64::from(limb) - i64::from(b.get(i).copied().unwrap_or(0)) - borrow;
+        borrow = i64::from(current < 0);
+        if current < 0 {
+            current += 1 << 32;
+        }
+        difference.push(current as u32);
+    }
+    difference
+}
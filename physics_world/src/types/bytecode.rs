@@ -0,0 +1,481 @@
+//! Compact binary encoding of bytecode.
+//!
+//! Each instruction is a one-byte tag followed by its operands in
+//! little-endian order, so an instruction takes exactly
+//! [`OpCode::size_bytes`] bytes. `bool` and `u8` operands take one byte,
+//! `u16` and `i16` two, `u32` four, `i64` and `f64` eight. `usize` operands
+//! (constant pool indices, counts) are stored as `u32`, and encoding fails
+//! for larger values rather than truncating them.
+
+use crate::types::OpCode;
+use thiserror::Error;
+
+/// Number of opcode variants; tags run from 0 to `OPCODE_COUNT - 1`.
+pub const OPCODE_COUNT: u8 = 86;
+
+/// Error encoding or decoding bytecode
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BytecodeError {
+    #[error("Operand {0} does not fit in 32 bits")]
+    OperandTooLarge(usize),
+    #[error("Unknown opcode tag {0}")]
+    UnknownTag(u8),
+    #[error("Bytecode ends in the middle of an instruction")]
+    UnexpectedEnd,
+}
+
+impl OpCode {
+    /// Tag identifying this instruction's variant in the binary encoding
+    pub fn tag(&self) -> u8 {
+        match self {
+            OpCode::Nil => 0,
+            OpCode::Bool(..) => 1,
+            OpCode::Int(..) => 2,
+            OpCode::Float(..) => 3,
+            OpCode::Symbol(..) => 4,
+            OpCode::LoadString(..) => 5,
+            OpCode::StrLen => 6,
+            OpCode::StrConcat => 7,
+            OpCode::StrIndex => 8,
+            OpCode::BytesToStr => 9,
+            OpCode::StrToBytes => 10,
+            OpCode::Swap => 11,
+            OpCode::Dup => 12,
+            OpCode::Pop => 13,
+            OpCode::GetLocal(..) => 14,
+            OpCode::SetLocal(..) => 15,
+            OpCode::Cons => 16,
+            OpCode::Car => 17,
+            OpCode::Cdr => 18,
+            OpCode::DeepClone => 19,
+            OpCode::MakeThunk => 20,
+            OpCode::Force => 21,
+            OpCode::MakeVector(..) => 22,
+            OpCode::VecGet => 23,
+            OpCode::VecSet => 24,
+            OpCode::VecLen => 25,
+            OpCode::Call(..) => 26,
+            OpCode::TailCall(..) => 27,
+            OpCode::CallN(..) => 28,
+            OpCode::Ret => 29,
+            OpCode::RetN(..) => 30,
+            OpCode::Jmp(..) => 31,
+            OpCode::JmpIfFalse(..) => 32,
+            OpCode::Yield => 33,
+            OpCode::Send => 34,
+            OpCode::MakeClosure(..) => 35,
+            OpCode::ClosureAlphaEq => 36,
+            OpCode::GetConst(..) => 37,
+            OpCode::CheckStepLimit => 38,
+            OpCode::GcCollect => 39,
+            OpCode::GcStats => 40,
+            OpCode::StepsRemaining => 41,
+            OpCode::MemoryRemaining => 42,
+            OpCode::Add => 43,
+            OpCode::Sub => 44,
+            OpCode::Mul => 45,
+            OpCode::Div => 46,
+            OpCode::Mod => 47,
+            OpCode::FAdd => 48,
+            OpCode::FSub => 49,
+            OpCode::FMul => 50,
+            OpCode::FDiv => 51,
+            OpCode::Eq => 52,
+            OpCode::Lt => 53,
+            OpCode::Gt => 54,
+            OpCode::Lte => 55,
+            OpCode::Gte => 56,
+            OpCode::Ne => 57,
+            OpCode::HasCap(..) => 58,
+            OpCode::RequestCap(..) => 59,
+            OpCode::GrantCap(..) => 60,
+            OpCode::RevokeCap(..) => 61,
+            OpCode::HostCall { .. } => 62,
+            OpCode::WithCaps { .. } => 63,
+            OpCode::InitSandbox => 64,
+            OpCode::IsolateCapabilities => 65,
+            OpCode::SetErrorHandler(..) => 66,
+            OpCode::LogSandboxViolation => 67,
+            OpCode::CleanupSandbox => 68,
+            OpCode::MakeError => 69,
+            OpCode::Throw => 70,
+            OpCode::PopErrorHandler => 71,
+            OpCode::ErrorPayload => 72,
+            OpCode::Spawn { .. } => 73,
+            OpCode::TypeOf => 74,
+            OpCode::IsInt => 75,
+            OpCode::IsFloat => 76,
+            OpCode::IsPair => 77,
+            OpCode::IsClosure => 78,
+            OpCode::ListLen => 79,
+            OpCode::BitAnd => 80,
+            OpCode::BitOr => 81,
+            OpCode::BitXor => 82,
+            OpCode::Shl => 83,
+            OpCode::Shr => 84,
+            OpCode::CollectRest(..) => 85,
+        }
+    }
+
+    /// Append this instruction's encoding to `out`
+    pub fn encode(&self, out: &mut Vec<u8>) -> Result<(), BytecodeError> {
+        let mut w = Writer(out);
+        w.u8(self.tag());
+        match self {
+            OpCode::Bool(a) => {
+                w.bool(*a);
+            }
+            OpCode::Int(a) => {
+                w.i64(*a);
+            }
+            OpCode::Float(a) => {
+                w.f64(*a);
+            }
+            OpCode::Symbol(a) => {
+                w.usize(*a)?;
+            }
+            OpCode::LoadString(a) => {
+                w.usize(*a)?;
+            }
+            OpCode::GetLocal(a) => {
+                w.u16(*a);
+            }
+            OpCode::SetLocal(a) => {
+                w.u16(*a);
+            }
+            OpCode::MakeVector(a) => {
+                w.usize(*a)?;
+            }
+            OpCode::Call(a) => {
+                w.u16(*a);
+            }
+            OpCode::TailCall(a) => {
+                w.u16(*a);
+            }
+            OpCode::CallN(a, b) => {
+                w.u16(*a);
+                w.u16(*b);
+            }
+            OpCode::RetN(a) => {
+                w.u16(*a);
+            }
+            OpCode::CollectRest(a) => {
+                w.u16(*a);
+            }
+            OpCode::Spawn { arg_count } => {
+                w.u16(*arg_count);
+            }
+            OpCode::Jmp(a) => {
+                w.i16(*a);
+            }
+            OpCode::JmpIfFalse(a) => {
+                w.i16(*a);
+            }
+            OpCode::MakeClosure(a, b) => {
+                w.usize(*a)?;
+                w.usize(*b)?;
+            }
+            OpCode::GetConst(a) => {
+                w.usize(*a)?;
+            }
+            OpCode::HasCap(a) => {
+                w.usize(*a)?;
+            }
+            OpCode::RequestCap(a, b) => {
+                w.usize(*a)?;
+                w.usize(*b)?;
+            }
+            OpCode::GrantCap(a, b) => {
+                w.u32(*a);
+                w.usize(*b)?;
+            }
+            OpCode::RevokeCap(a, b) => {
+                w.u32(*a);
+                w.usize(*b)?;
+            }
+            OpCode::HostCall {
+                cap_idx,
+                func_id,
+                args,
+            } => {
+                w.usize(*cap_idx)?;
+                w.u16(*func_id);
+                w.u8(*args);
+            }
+            OpCode::WithCaps { cap_mask, body_len } => {
+                w.u32(*cap_mask);
+                w.u16(*body_len);
+            }
+            OpCode::SetErrorHandler(a) => {
+                w.i16(*a);
+            }
+            // Instructions without operands are just their tag
+            OpCode::Nil
+            | OpCode::StrLen
+            | OpCode::StrConcat
+            | OpCode::StrIndex
+            | OpCode::BytesToStr
+            | OpCode::StrToBytes
+            | OpCode::Swap
+            | OpCode::Dup
+            | OpCode::Pop
+            | OpCode::Cons
+            | OpCode::Car
+            | OpCode::Cdr
+            | OpCode::ListLen
+            | OpCode::DeepClone
+            | OpCode::MakeThunk
+            | OpCode::Force
+            | OpCode::VecGet
+            | OpCode::VecSet
+            | OpCode::VecLen
+            | OpCode::TypeOf
+            | OpCode::IsInt
+            | OpCode::IsFloat
+            | OpCode::IsPair
+            | OpCode::IsClosure
+            | OpCode::Ret
+            | OpCode::Yield
+            | OpCode::Send
+            | OpCode::ClosureAlphaEq
+            | OpCode::CheckStepLimit
+            | OpCode::GcCollect
+            | OpCode::GcStats
+            | OpCode::StepsRemaining
+            | OpCode::MemoryRemaining
+            | OpCode::Add
+            | OpCode::Sub
+            | OpCode::Mul
+            | OpCode::Div
+            | OpCode::Mod
+            | OpCode::BitAnd
+            | OpCode::BitOr
+            | OpCode::BitXor
+            | OpCode::Shl
+            | OpCode::Shr
+            | OpCode::FAdd
+            | OpCode::FSub
+            | OpCode::FMul
+            | OpCode::FDiv
+            | OpCode::Eq
+            | OpCode::Lt
+            | OpCode::Gt
+            | OpCode::Lte
+            | OpCode::Gte
+            | OpCode::Ne
+            | OpCode::InitSandbox
+            | OpCode::IsolateCapabilities
+            | OpCode::LogSandboxViolation
+            | OpCode::CleanupSandbox
+            | OpCode::MakeError
+            | OpCode::Throw
+            | OpCode::PopErrorHandler
+            | OpCode::ErrorPayload => {}
+        }
+        Ok(())
+    }
+}
+
+/// Encode a program, one instruction after another
+pub fn encode_program(program: &[OpCode]) -> Result<Vec<u8>, BytecodeError> {
+    let mut out = Vec::with_capacity(program.iter().map(OpCode::size_bytes).sum());
+    for op in program {
+        op.encode(&mut out)?;
+    }
+    Ok(out)
+}
+
+/// Decode a program produced by [`encode_program`]
+pub fn decode_program(bytes: &[u8]) -> Result<Vec<OpCode>, BytecodeError> {
+    let mut r = Reader(bytes);
+    let mut program = Vec::new();
+    while !r.0.is_empty() {
+        program.push(decode_instruction(&mut r)?);
+    }
+    Ok(program)
+}
+
+fn decode_instruction(r: &mut Reader<'_>) -> Result<OpCode, BytecodeError> {
+    let tag = r.u8()?;
+    Ok(match tag {
+        0 => OpCode::Nil,
+        1 => OpCode::Bool(r.bool()?),
+        2 => OpCode::Int(r.i64()?),
+        3 => OpCode::Float(r.f64()?),
+        4 => OpCode::Symbol(r.usize()?),
+        5 => OpCode::LoadString(r.usize()?),
+        6 => OpCode::StrLen,
+        7 => OpCode::StrConcat,
+        8 => OpCode::StrIndex,
+        9 => OpCode::BytesToStr,
+        10 => OpCode::StrToBytes,
+        11 => OpCode::Swap,
+        12 => OpCode::Dup,
+        13 => OpCode::Pop,
+        14 => OpCode::GetLocal(r.u16()?),
+        15 => OpCode::SetLocal(r.u16()?),
+        16 => OpCode::Cons,
+        17 => OpCode::Car,
+        18 => OpCode::Cdr,
+        19 => OpCode::DeepClone,
+        20 => OpCode::MakeThunk,
+        21 => OpCode::Force,
+        22 => OpCode::MakeVector(r.usize()?),
+        23 => OpCode::VecGet,
+        24 => OpCode::VecSet,
+        25 => OpCode::VecLen,
+        26 => OpCode::Call(r.u16()?),
+        27 => OpCode::TailCall(r.u16()?),
+        28 => OpCode::CallN(r.u16()?, r.u16()?),
+        29 => OpCode::Ret,
+        30 => OpCode::RetN(r.u16()?),
+        31 => OpCode::Jmp(r.i16()?),
+        32 => OpCode::JmpIfFalse(r.i16()?),
+        33 => OpCode::Yield,
+        34 => OpCode::Send,
+        35 => OpCode::MakeClosure(r.usize()?, r.usize()?),
+        36 => OpCode::ClosureAlphaEq,
+        37 => OpCode::GetConst(r.usize()?),
+        38 => OpCode::CheckStepLimit,
+        39 => OpCode::GcCollect,
+        40 => OpCode::GcStats,
+        41 => OpCode::StepsRemaining,
+        42 => OpCode::MemoryRemaining,
+        43 => OpCode::Add,
+        44 => OpCode::Sub,
+        45 => OpCode::Mul,
+        46 => OpCode::Div,
+        47 => OpCode::Mod,
+        48 => OpCode::FAdd,
+        49 => OpCode::FSub,
+        50 => OpCode::FMul,
+        51 => OpCode::FDiv,
+        52 => OpCode::Eq,
+        53 => OpCode::Lt,
+        54 => OpCode::Gt,
+        55 => OpCode::Lte,
+        56 => OpCode::Gte,
+        57 => OpCode::Ne,
+        58 => OpCode::HasCap(r.usize()?),
+        59 => OpCode::RequestCap(r.usize()?, r.usize()?),
+        60 => OpCode::GrantCap(r.u32()?, r.usize()?),
+        61 => OpCode::RevokeCap(r.u32()?, r.usize()?),
+        62 => OpCode::HostCall {
+            cap_idx: r.usize()?,
+            func_id: r.u16()?,
+            args: r.u8()?,
+        },
+        63 => OpCode::WithCaps {
+            cap_mask: r.u32()?,
+            body_len: r.u16()?,
+        },
+        64 => OpCode::InitSandbox,
+        65 => OpCode::IsolateCapabilities,
+        66 => OpCode::SetErrorHandler(r.i16()?),
+        67 => OpCode::LogSandboxViolation,
+        68 => OpCode::CleanupSandbox,
+        69 => OpCode::MakeError,
+        70 => OpCode::Throw,
+        71 => OpCode::PopErrorHandler,
+        72 => OpCode::ErrorPayload,
+        73 => OpCode::Spawn {
+            arg_count: r.u16()?,
+        },
+        74 => OpCode::TypeOf,
+        75 => OpCode::IsInt,
+        76 => OpCode::IsFloat,
+        77 => OpCode::IsPair,
+        78 => OpCode::IsClosure,
+        79 => OpCode::ListLen,
+        80 => OpCode::BitAnd,
+        81 => OpCode::BitOr,
+        82 => OpCode::BitXor,
+        83 => OpCode::Shl,
+        84 => OpCode::Shr,
+        85 => OpCode::CollectRest(r.u16()?),
+        _ => return Err(BytecodeError::UnknownTag(tag)),
+    })
+}
+
+struct Writer<'a>(&'a mut Vec<u8>);
+
+impl Writer<'_> {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.u8(u8::from(value));
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i16(&mut self, value: i16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i64(&mut self, value: i64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn usize(&mut self, value: usize) -> Result<(), BytecodeError> {
+        let value = u32::try_from(value).map_err(|_| BytecodeError::OperandTooLarge(value))?;
+        self.u32(value);
+        Ok(())
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], BytecodeError> {
+        if self.0.len() < N {
+            return Err(BytecodeError::UnexpectedEnd);
+        }
+        let (bytes, rest) = self.0.split_at(N);
+        self.0 = rest;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, BytecodeError> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, BytecodeError> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn u16(&mut self) -> Result<u16, BytecodeError> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn i16(&mut self) -> Result<i16, BytecodeError> {
+        Ok(i16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, BytecodeError> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn i64(&mut self) -> Result<i64, BytecodeError> {
+        Ok(i64::from_le_bytes(self.take()?))
+    }
+
+    fn f64(&mut self) -> Result<f64, BytecodeError> {
+        Ok(f64::from_le_bytes(self.take()?))
+    }
+
+    fn usize(&mut self) -> Result<usize, BytecodeError> {
+        Ok(self.u32()? as usize)
+    }
+}
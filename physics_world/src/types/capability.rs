@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// Capability enum for the capability system
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Hash, Eq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Hash, Eq, PartialOrd, Ord)]
 pub enum Capability {
     // Meta-capabilities
     MetaSelfModify, // Can modify own non-core code
@@ -24,12 +24,46 @@ pub enum Capability {
     SysCreateActor,    // Can spawn new actors
     SysTerminateActor, // Can terminate actors (including self)
     SysClock,          // Access non-deterministic time
+    SysGc,             // Trigger and inspect garbage collection
 
     // Resource privileges
     ResourceExtraMemory(u64), // Additional memory quota
     ResourceExtraTime(u64),   // Additional time quota
 }
 
+impl Capability {
+    /// Bit identifying this capability's kind in a `WithCaps` mask.
+    ///
+    /// Resource capabilities share one bit per kind regardless of amount.
+    pub fn mask_bit(&self) -> u32 {
+        let bit = match self {
+            Capability::MetaSelfModify => 0,
+            Capability::MetaGrant => 1,
+            Capability::MacroHygienic => 2,
+            Capability::MacroUnsafe => 3,
+            Capability::ComptimeEval => 4,
+            Capability::IoReadSensor => 5,
+            Capability::IoWriteActuator => 6,
+            Capability::IoNetwork => 7,
+            Capability::IoPersist => 8,
+            Capability::SysCreateActor => 9,
+            Capability::SysTerminateActor => 10,
+            Capability::SysClock => 11,
+            Capability::SysGc => 12,
+            Capability::ResourceExtraMemory(_) => 13,
+            Capability::ResourceExtraTime(_) => 14,
+        };
+        1 << bit
+    }
+
+    /// Mask keeping exactly the given capabilities
+    pub fn mask_of(capabilities: &[Capability]) -> u32 {
+        capabilities
+            .iter()
+            .fold(0, |mask, cap| mask | cap.mask_bit())
+    }
+}
+
 impl fmt::Display for Capability {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -45,6 +79,7 @@ impl fmt::Display for Capability {
             Capability::SysCreateActor => write!(f, "SysCreateActor"),
             Capability::SysTerminateActor => write!(f, "SysTerminateActor"),
             Capability::SysClock => write!(f, "SysClock"),
+            Capability::SysGc => write!(f, "SysGc"),
             Capability::ResourceExtraMemory(bytes) => write!(f, "ResourceExtraMemory({})", bytes),
             Capability::ResourceExtraTime(ms) => write!(f, "ResourceExtraTime({})", ms),
         }
@@ -36,6 +36,8 @@ pub enum OpCode {
     StrLen,            // Get string length
     StrConcat,         // Concatenate two strings
     StrIndex,          // Get character at index
+    BytesToStr,        // Decode bytes as UTF-8, or push an Error value if invalid
+    StrToBytes,        // Encode a string as its UTF-8 bytes
     // Primitive Stack Operations
     Swap, // Swap top two stack values
     Dup,
@@ -47,20 +49,61 @@ pub enum OpCode {
     Cons,
     Car,
     Cdr,
+    ListLen,   // Replace a proper list with its number of elements
+    DeepClone, // Replace a heap value with an independent deep copy
+    // Lazy evaluation
+    MakeThunk, // Wrap a closure in an unevaluated thunk
+    Force,     // Evaluate a thunk once and push its (memoized) result
+    // Vectors
+    MakeVector(usize), // Pop element count values into a new vector
+    VecGet,            // Get element at index
+    VecSet,            // Set element at index in place
+    VecLen,            // Get vector length
+    // Type tests
+    TypeOf,    // Replace the top value with its type tag (`Value::type_tag`)
+    IsInt,     // Replace the top value with whether it is an Int or BigInt
+    IsFloat,   // Replace the top value with whether it is a Float
+    IsPair,    // Replace the top value with whether it is a Pair
+    IsClosure, // Replace the top value with whether it is a Closure
     // Control
-    Call(u16),     // Argument count
-    TailCall(u16), // NEW: Tail call (reuses stack frame)
+    Call(u16),       // Argument count
+    TailCall(u16),   // NEW: Tail call (reuses stack frame)
+    CallN(u16, u16), // Argument count, number of results the callee must return
+    /// Replace the arguments past the first N locals of the current call
+    /// with a single list of them in local N (a rest parameter). Fewer than
+    /// N arguments is a `StackUnderflow`, as reading a missing one would be.
+    CollectRest(u16),
     Ret,
+    RetN(u16), // Return the top N values to the caller
     Jmp(i16),
     JmpIfFalse(i16),
     // Actors
     Yield,
     Send,
+    /// Pop a closure and the `arg_count` arguments beneath it, and start a
+    /// new actor that calls the closure with them. Pushes the new actor's id.
+    /// Requires SysCreateActor capability.
+    Spawn {
+        arg_count: u16,
+    },
     // Closure Operations
     MakeClosure(usize /* code_idx */, usize /* capture_count */),
+    /// Pop two closures and push whether their bodies are α-equivalent.
+    /// Captured values are not compared.
+    ClosureAlphaEq,
     GetConst(usize), // NEW: Load constant from constant pool by index
     // Resource Management
     CheckStepLimit,
+    /// Collect garbage and push the number of bytes reclaimed.
+    /// Requires SysGc capability.
+    GcCollect,
+    /// Push a pair of (live bytes, heap capacity).
+    /// Requires SysGc capability.
+    GcStats,
+    /// Push the number of execution steps left in the budget.
+    StepsRemaining,
+    /// Push the number of heap bytes still available for allocation.
+    MemoryRemaining,
 
     // Primitive Arithmetic (Int64)
     Add, // TOS = TOS + TOS-1
@@ -69,6 +112,13 @@ pub enum OpCode {
     Div,
     Mod,
 
+    // Bitwise operations on Int64, two's complement
+    BitAnd,
+    BitOr,
+    BitXor,
+    Shl, // TOS-1 shifted left by TOS bits
+    Shr, // TOS-1 shifted right by TOS bits, copying the sign bit
+
     // NEW: Float Arithmetic Operations
     FAdd, // Float addition
     FSub, // Float subtraction
@@ -110,6 +160,14 @@ pub enum OpCode {
         args: u8,
     },
 
+    /// Run the next `body_len` instructions holding only the current
+    /// capabilities whose `Capability::mask_bit` is set in `cap_mask`.
+    /// The full set is restored once execution leaves the region.
+    WithCaps {
+        cap_mask: u32,
+        body_len: u16,
+    },
+
     // --- SANDBOX INSTRUCTIONS ---
     /// Initialize sandbox environment with resource limits
     InitSandbox,
@@ -125,9 +183,31 @@ pub enum OpCode {
 
     /// Cleanup sandbox resources and restore previous state
     CleanupSandbox,
+
+    // --- ERROR INSTRUCTIONS ---
+    /// Pop a payload, a message string and an integer code, and push an
+    /// error record holding all three
+    MakeError,
+
+    /// Pop a value and unwind to the innermost handler installed by
+    /// `SetErrorHandler`, which receives the value on top of its stack.
+    /// Without a handler the program finishes with the value as its result.
+    Throw,
+
+    /// Remove the innermost error handler once its protected code is done
+    PopErrorHandler,
+
+    /// Replace the error record on top of the stack with its payload
+    ErrorPayload,
 }
 
 impl OpCode {
+    /// Length of this instruction in the binary encoding of
+    /// [`encode_program`](crate::types::bytecode::encode_program): a one-byte
+    /// tag plus its operands, with `usize` operands stored as `u32`.
+    ///
+    /// The match has no wildcard arm, so a new opcode cannot compile
+    /// without a size.
     pub fn size_bytes(&self) -> usize {
         match self {
             OpCode::Nil => 1,
@@ -139,6 +219,8 @@ impl OpCode {
             OpCode::StrLen => 1,
             OpCode::StrConcat => 1,
             OpCode::StrIndex => 1,
+            OpCode::BytesToStr => 1,
+            OpCode::StrToBytes => 1,
             OpCode::Dup => 1,
             OpCode::Pop => 1,
             OpCode::Swap => 1,
@@ -147,9 +229,25 @@ impl OpCode {
             OpCode::Cons => 1,
             OpCode::Car => 1,
             OpCode::Cdr => 1,
+            OpCode::ListLen => 1,
+            OpCode::DeepClone => 1,
+            OpCode::MakeThunk => 1,
+            OpCode::Force => 1,
+            OpCode::MakeVector(_) => 5, // usize (4 bytes) + opcode tag (1 byte)
+            OpCode::VecGet => 1,
+            OpCode::VecSet => 1,
+            OpCode::VecLen => 1,
+            OpCode::TypeOf => 1,
+            OpCode::IsInt => 1,
+            OpCode::IsFloat => 1,
+            OpCode::IsPair => 1,
+            OpCode::IsClosure => 1,
             OpCode::Call(_) => 3,
             OpCode::TailCall(_) => 3,
+            OpCode::CallN(_, _) => 5,
             OpCode::Ret => 1,
+            OpCode::RetN(_) => 3,
+            OpCode::CollectRest(_) => 3,
             OpCode::Jmp(_) => 3,
             OpCode::JmpIfFalse(_) => 3,
             OpCode::Yield => 1,
@@ -159,6 +257,11 @@ impl OpCode {
             OpCode::Mul => 1,
             OpCode::Div => 1,
             OpCode::Mod => 1,
+            OpCode::BitAnd => 1,
+            OpCode::BitOr => 1,
+            OpCode::BitXor => 1,
+            OpCode::Shl => 1,
+            OpCode::Shr => 1,
             OpCode::FAdd => 1, // NEW: Float addition
             OpCode::FSub => 1, // NEW: Float subtraction
             OpCode::FMul => 1, // NEW: Float multiplication
@@ -170,24 +273,53 @@ impl OpCode {
             OpCode::Gte => 1,
             OpCode::Ne => 1,
             OpCode::MakeClosure(_, _) => 9, // 4 bytes for each usize
-            OpCode::GetConst(_) => 5,       // usize (4 bytes) + opcode tag (1 byte)
+            OpCode::ClosureAlphaEq => 1,
+            OpCode::GetConst(_) => 5, // usize (4 bytes) + opcode tag (1 byte)
             OpCode::CheckStepLimit => 1,
+            OpCode::GcCollect => 1,
+            OpCode::GcStats => 1,
+            OpCode::StepsRemaining => 1,
+            OpCode::MemoryRemaining => 1,
             // Capability instructions
             OpCode::HasCap(_) => 5, // usize (4 bytes) + opcode tag (1 byte)
             OpCode::RequestCap(_, _) => 9, // 2 x usize (8 bytes) + opcode tag (1 byte)
-            OpCode::GrantCap(_, _) => 6, // u32 (4 bytes) + usize (4 bytes) + opcode tag (1 byte) = 9 bytes
-            OpCode::RevokeCap(_, _) => 6, // u32 (4 bytes) + usize (4 bytes) + opcode tag (1 byte) = 9 bytes
+            OpCode::GrantCap(_, _) => 9, // u32 (4 bytes) + usize (4 bytes) + opcode tag (1 byte)
+            OpCode::RevokeCap(_, _) => 9, // u32 (4 bytes) + usize (4 bytes) + opcode tag (1 byte)
             OpCode::HostCall {
                 cap_idx: _,
                 func_id: _,
                 args: _,
-            } => 7, // usize (4) + u16 (2) + u8 (1) + opcode tag (1) = 8 bytes
+            } => 8, // usize (4) + u16 (2) + u8 (1) + opcode tag (1)
             // Sandbox instructions
+            OpCode::WithCaps { .. } => 7, // u32 mask + u16 length + opcode tag
             OpCode::InitSandbox => 1,
             OpCode::IsolateCapabilities => 1,
             OpCode::SetErrorHandler(_) => 3, // i16 (2 bytes) + opcode tag (1 byte)
             OpCode::LogSandboxViolation => 1,
             OpCode::CleanupSandbox => 1,
+            // Error instructions
+            OpCode::MakeError => 1,
+            OpCode::Throw => 1,
+            OpCode::PopErrorHandler => 1,
+            OpCode::ErrorPayload => 1,
+            OpCode::Spawn { .. } => 3, // u16 argument count + opcode tag
+        }
+    }
+
+    /// The instruction's operand, if it has exactly one and it is a `u16`.
+    ///
+    /// Instructions with several operands, such as `CallN`, have none here
+    /// even when some of them are `u16`.
+    pub fn u16_operand(&self) -> Option<u16> {
+        match *self {
+            OpCode::GetLocal(operand)
+            | OpCode::SetLocal(operand)
+            | OpCode::Call(operand)
+            | OpCode::TailCall(operand)
+            | OpCode::RetN(operand)
+            | OpCode::CollectRest(operand)
+            | OpCode::Spawn { arg_count: operand } => Some(operand),
+            _ => None,
         }
     }
 }
@@ -203,10 +335,20 @@ pub enum Value {
     Symbol(usize),  // Index into a constant table.
     Pair(HeapPtr),  // HeapPtr is a u32 index into an ObjectArena.
     Closure(HeapPtr),
+    Vector(HeapPtr), // Contiguous, indexable TAG_VECTOR object
+    BigInt(HeapPtr), // Arbitrary-precision TAG_BIGINT integer
     ActorId(u32),
     Capability(crate::types::capability::Capability),
     GcPtr(crate::vm::gc::GcPtr), // GC-managed pointer
     Error(String),               // Error value for host function errors
+    Bytes(Vec<u8>),              // Raw bytes, e.g. host input that is not valid UTF-8
+    Thunk(crate::vm::gc::GcPtr), // Delayed closure call, evaluated at most once by Force
+    /// Error built by `MakeError`, carrying the offending value as its payload
+    ErrorRecord {
+        code: i64,
+        message: String,
+        payload: Box<Value>,
+    },
 }
 
 impl fmt::Display for Value {
@@ -220,15 +362,49 @@ impl fmt::Display for Value {
             Value::Symbol(idx) => write!(f, "Symbol({})", idx),
             Value::Pair(ptr) => write!(f, "Pair({})", ptr),
             Value::Closure(ptr) => write!(f, "Closure({})", ptr),
+            Value::Vector(ptr) => write!(f, "Vector({})", ptr),
+            Value::BigInt(ptr) => write!(f, "BigInt({})", ptr),
             Value::ActorId(id) => write!(f, "Actor({})", id),
             Value::Capability(cap) => write!(f, "Capability({:?})", cap),
             Value::GcPtr(ptr) => write!(f, "GcPtr({})", ptr.0),
             Value::Error(msg) => write!(f, "Error({})", msg),
+            Value::Bytes(bytes) => write!(f, "Bytes({:?})", bytes),
+            Value::Thunk(ptr) => write!(f, "Thunk({})", ptr.0),
+            Value::ErrorRecord {
+                code,
+                message,
+                payload,
+            } => write!(f, "Error({}: {}, {})", code, message, payload),
         }
     }
 }
 
 impl Value {
+    /// Number identifying this value's variant, in declaration order:
+    /// 0 for `Nil`, 1 for `Bool`, 2 for `Int`, 3 for `Float` and so on up to
+    /// 16 for `ErrorRecord`. Pushed by `OpCode::TypeOf`.
+    pub fn type_tag(&self) -> u8 {
+        match self {
+            Value::Nil => 0,
+            Value::Bool(_) => 1,
+            Value::Int(_) => 2,
+            Value::Float(_) => 3,
+            Value::String(_) => 4,
+            Value::Symbol(_) => 5,
+            Value::Pair(_) => 6,
+            Value::Closure(_) => 7,
+            Value::Vector(_) => 8,
+            Value::BigInt(_) => 9,
+            Value::ActorId(_) => 10,
+            Value::Capability(_) => 11,
+            Value::GcPtr(_) => 12,
+            Value::Error(_) => 13,
+            Value::Bytes(_) => 14,
+            Value::Thunk(_) => 15,
+            Value::ErrorRecord { .. } => 16,
+        }
+    }
+
     pub fn is_truthy(&self) -> bool {
         match self {
             Value::Nil => false,
@@ -239,10 +415,24 @@ impl Value {
             Value::Symbol(_) => true,
             Value::Pair(_) => true,
             Value::Closure(_) => true,
+            Value::Vector(_) => true,
+            Value::BigInt(_) => true, // Never zero: small values stay Int
             Value::ActorId(_) => true,
             Value::Capability(_) => true,
             Value::GcPtr(_) => true,
             Value::Error(_) => false, // Errors are falsy
+            Value::Bytes(bytes) => !bytes.is_empty(),
+            Value::Thunk(_) => true,
+            Value::ErrorRecord { .. } => false, // Errors are falsy
+        }
+    }
+
+    /// Wrap bytes arriving from the host: a `String` when they are valid
+    /// UTF-8, otherwise `Bytes` so nothing is lost and nothing panics
+    pub fn from_host_bytes(bytes: Vec<u8>) -> Value {
+        match String::from_utf8(bytes) {
+            Ok(string) => Value::String(string),
+            Err(error) => Value::Bytes(error.into_bytes()),
         }
     }
 }
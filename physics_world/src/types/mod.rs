@@ -1,12 +1,18 @@
+pub mod bigint;
+pub mod bytecode;
 pub mod capability;
 /// Types module for Physics World
 pub mod core;
 pub mod error;
 pub mod host;
+pub mod opcode_meta;
 pub mod distributed;
 
+pub use bigint::*;
+pub use bytecode::*;
 pub use capability::*;
 pub use core::*;
 pub use error::*;
 pub use host::*;
+pub use opcode_meta::*;
 pub use distributed::*;
@@ -0,0 +1,376 @@
+/// Runtime documentation for opcodes
+///
+/// Tools such as a disassembler or a REPL `:help` command read an
+/// instruction's name, operands and stack effect from here instead of
+/// keeping their own table.
+use crate::types::OpCode;
+
+/// Name, operands and stack effect of an instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeMeta {
+    /// Variant name, as the instruction is written in listings
+    pub mnemonic: &'static str,
+    /// What the operands mean, or empty if there are none
+    pub operands: &'static str,
+    /// Values popped from the current frame's stack
+    pub stack_in: usize,
+    /// Values pushed onto the current frame's stack
+    pub stack_out: usize,
+    pub description: &'static str,
+}
+
+const fn meta(
+    mnemonic: &'static str,
+    operands: &'static str,
+    stack_in: usize,
+    stack_out: usize,
+    description: &'static str,
+) -> OpcodeMeta {
+    OpcodeMeta {
+        mnemonic,
+        operands,
+        stack_in,
+        stack_out,
+        description,
+    }
+}
+
+impl OpCode {
+    /// Documentation for this instruction.
+    ///
+    /// Stack effects that depend on an operand, such as a call's argument
+    /// count, are computed from this instruction's operands. The match has no
+    /// wildcard arm, so a new opcode cannot compile without metadata.
+    pub fn metadata(&self) -> OpcodeMeta {
+        match self {
+            OpCode::Nil => meta("Nil", "", 0, 1, "Push nil"),
+            OpCode::Bool(_) => meta("Bool", "boolean literal", 0, 1, "Push a boolean"),
+            OpCode::Int(_) => meta("Int", "i64 literal", 0, 1, "Push an integer"),
+            OpCode::Float(_) => meta("Float", "f64 literal", 0, 1, "Push a float"),
+            OpCode::Symbol(_) => meta(
+                "Symbol",
+                "symbol id",
+                0,
+                1,
+                "Push a symbol, or the constant at the index when no symbol table is attached",
+            ),
+            OpCode::LoadString(_) => meta(
+                "LoadString",
+                "constant index",
+                0,
+                1,
+                "Push a string from the constant pool",
+            ),
+            OpCode::StrLen => meta("StrLen", "", 1, 1, "Replace a string with its length"),
+            OpCode::StrConcat => meta("StrConcat", "", 2, 1, "Concatenate two strings"),
+            OpCode::StrIndex => meta("StrIndex", "", 2, 1, "Character of a string at an index"),
+            OpCode::BytesToStr => meta(
+                "BytesToStr",
+                "",
+                1,
+                1,
+                "Decode bytes as UTF-8, or push an error value if invalid",
+            ),
+            OpCode::StrToBytes => {
+                meta("StrToBytes", "", 1, 1, "Encode a string as its UTF-8 bytes")
+            }
+            OpCode::Swap => meta("Swap", "", 2, 2, "Swap the top two values"),
+            OpCode::Dup => meta("Dup", "", 1, 2, "Duplicate the top value"),
+            OpCode::Pop => meta("Pop", "", 1, 0, "Discard the top value"),
+            OpCode::GetLocal(_) => meta("GetLocal", "local slot", 0, 1, "Push a local variable"),
+            OpCode::SetLocal(_) => meta(
+                "SetLocal",
+                "local slot",
+                1,
+                0,
+                "Pop a value into a local variable",
+            ),
+            OpCode::Cons => meta("Cons", "", 2, 1, "Build a pair from two values"),
+            OpCode::Car => meta("Car", "", 1, 1, "First element of a pair"),
+            OpCode::Cdr => meta("Cdr", "", 1, 1, "Second element of a pair"),
+            OpCode::ListLen => meta("ListLen", "", 1, 1, "Replace a list with its length"),
+            OpCode::DeepClone => meta(
+                "DeepClone",
+                "",
+                1,
+                1,
+                "Replace a heap value with an independent deep copy",
+            ),
+            OpCode::MakeThunk => meta(
+                "MakeThunk",
+                "",
+                1,
+                1,
+                "Wrap a closure in an unevaluated thunk",
+            ),
+            OpCode::Force => meta(
+                "Force",
+                "",
+                1,
+                1,
+                "Evaluate a thunk once and push its memoized result",
+            ),
+            OpCode::MakeVector(count) => meta(
+                "MakeVector",
+                "element count",
+                *count,
+                1,
+                "Pop elements into a new vector",
+            ),
+            OpCode::VecGet => meta("VecGet", "", 2, 1, "Element of a vector at an index"),
+            OpCode::VecSet => meta(
+                "VecSet",
+                "",
+                3,
+                1,
+                "Set a vector element in place and push the vector",
+            ),
+            OpCode::VecLen => meta("VecLen", "", 1, 1, "Replace a vector with its length"),
+            OpCode::TypeOf => meta(
+                "TypeOf",
+                "",
+                1,
+                1,
+                "Replace a value with its integer type tag",
+            ),
+            OpCode::IsInt => meta("IsInt", "", 1, 1, "Whether a value is an integer"),
+            OpCode::IsFloat => meta("IsFloat", "", 1, 1, "Whether a value is a float"),
+            OpCode::IsPair => meta("IsPair", "", 1, 1, "Whether a value is a pair"),
+            OpCode::IsClosure => meta("IsClosure", "", 1, 1, "Whether a value is a closure"),
+            OpCode::Call(args) => meta(
+                "Call",
+                "argument count",
+                *args as usize + 1,
+                1,
+                "Call the closure on top of the stack with the arguments beneath it",
+            ),
+            OpCode::TailCall(args) => meta(
+                "TailCall",
+                "argument count",
+                *args as usize + 1,
+                1,
+                "Call a closure, reusing the current frame",
+            ),
+            OpCode::CallN(args, results) => meta(
+                "CallN",
+                "argument count, result count",
+                *args as usize + 1,
+                *results as usize,
+                "Call a closure that must return the given number of results",
+            ),
+            OpCode::CollectRest(_) => meta(
+                "CollectRest",
+                "fixed parameter count",
+                0,
+                0,
+                "Bind the arguments past the fixed parameters as a list",
+            ),
+            OpCode::Ret => meta("Ret", "", 1, 0, "Return the top value to the caller"),
+            OpCode::RetN(count) => meta(
+                "RetN",
+                "result count",
+                *count as usize,
+                0,
+                "Return the top values to the caller",
+            ),
+            OpCode::Jmp(_) => meta(
+                "Jmp",
+                "relative offset",
+                0,
+                0,
+                "Jump relative to the next instruction",
+            ),
+            OpCode::JmpIfFalse(_) => meta(
+                "JmpIfFalse",
+                "relative offset",
+                1,
+                0,
+                "Pop a condition and jump if it is falsy",
+            ),
+            OpCode::Yield => meta("Yield", "", 0, 0, "Give up the rest of the tick"),
+            OpCode::Send => meta("Send", "", 2, 0, "Send a message to an actor"),
+            OpCode::Spawn { arg_count } => meta(
+                "Spawn",
+                "argument count",
+                *arg_count as usize + 1,
+                1,
+                "Start an actor calling a closure and push its id",
+            ),
+            OpCode::MakeClosure(_, captures) => meta(
+                "MakeClosure",
+                "code constant index, capture count",
+                *captures,
+                1,
+                "Build a closure capturing values from the stack",
+            ),
+            OpCode::ClosureAlphaEq => meta(
+                "ClosureAlphaEq",
+                "",
+                2,
+                1,
+                "Whether two closures have α-equivalent bodies",
+            ),
+            OpCode::GetConst(_) => meta(
+                "GetConst",
+                "constant index",
+                0,
+                1,
+                "Push a value from the constant pool",
+            ),
+            OpCode::CheckStepLimit => meta(
+                "CheckStepLimit",
+                "",
+                0,
+                0,
+                "Fail if the step budget is spent",
+            ),
+            OpCode::GcCollect => meta(
+                "GcCollect",
+                "",
+                0,
+                1,
+                "Collect garbage and push the bytes reclaimed",
+            ),
+            OpCode::GcStats => meta(
+                "GcStats",
+                "",
+                0,
+                2,
+                "Push live heap bytes and heap capacity",
+            ),
+            OpCode::StepsRemaining => meta(
+                "StepsRemaining",
+                "",
+                0,
+                1,
+                "Push the steps left in the budget",
+            ),
+            OpCode::MemoryRemaining => meta(
+                "MemoryRemaining",
+                "",
+                0,
+                1,
+                "Push the heap bytes still available",
+            ),
+            OpCode::Add => meta("Add", "", 2, 1, "Integer addition"),
+            OpCode::Sub => meta("Sub", "", 2, 1, "Integer subtraction"),
+            OpCode::Mul => meta("Mul", "", 2, 1, "Integer multiplication"),
+            OpCode::Div => meta("Div", "", 2, 1, "Integer division"),
+            OpCode::Mod => meta("Mod", "", 2, 1, "Integer remainder"),
+            OpCode::BitAnd => meta("BitAnd", "", 2, 1, "Bitwise and of two integers"),
+            OpCode::BitOr => meta("BitOr", "", 2, 1, "Bitwise or of two integers"),
+            OpCode::BitXor => meta("BitXor", "", 2, 1, "Bitwise exclusive or of two integers"),
+            OpCode::Shl => meta("Shl", "", 2, 1, "Shift an integer left"),
+            OpCode::Shr => meta("Shr", "", 2, 1, "Shift an integer right, keeping its sign"),
+            OpCode::FAdd => meta("FAdd", "", 2, 1, "Float addition"),
+            OpCode::FSub => meta("FSub", "", 2, 1, "Float subtraction"),
+            OpCode::FMul => meta("FMul", "", 2, 1, "Float multiplication"),
+            OpCode::FDiv => meta("FDiv", "", 2, 1, "Float division"),
+            OpCode::Eq => meta("Eq", "", 2, 1, "Whether two values are equal"),
+            OpCode::Lt => meta("Lt", "", 2, 1, "Whether the lower value is less"),
+            OpCode::Gt => meta("Gt", "", 2, 1, "Whether the lower value is greater"),
+            OpCode::Lte => meta("Lte", "", 2, 1, "Whether the lower value is less or equal"),
+            OpCode::Gte => meta(
+                "Gte",
+                "",
+                2,
+                1,
+                "Whether the lower value is greater or equal",
+            ),
+            OpCode::Ne => meta("Ne", "", 2, 1, "Whether two values differ"),
+            OpCode::HasCap(_) => meta(
+                "HasCap",
+                "capability constant index",
+                0,
+                1,
+                "Whether the actor holds a capability",
+            ),
+            OpCode::RequestCap(_, _) => meta(
+                "RequestCap",
+                "capability constant index, justification constant index",
+                0,
+                1,
+                "Ask the scheduler for a capability and wait for its decision",
+            ),
+            OpCode::GrantCap(_, _) => meta(
+                "GrantCap",
+                "target actor id, capability constant index",
+                0,
+                0,
+                "Grant a capability to another actor",
+            ),
+            OpCode::RevokeCap(_, _) => meta(
+                "RevokeCap",
+                "target actor id, capability constant index",
+                0,
+                0,
+                "Revoke a capability from an actor",
+            ),
+            OpCode::HostCall { args, .. } => meta(
+                "HostCall",
+                "capability constant index, host function id, argument count",
+                *args as usize,
+                1,
+                "Call a privileged host function",
+            ),
+            OpCode::WithCaps { .. } => meta(
+                "WithCaps",
+                "capability mask, body length",
+                0,
+                0,
+                "Run the following instructions with only the masked capabilities",
+            ),
+            OpCode::InitSandbox => meta("InitSandbox", "", 0, 0, "Enter a sandbox"),
+            OpCode::IsolateCapabilities => meta(
+                "IsolateCapabilities",
+                "",
+                0,
+                0,
+                "Isolate capability access for untrusted code",
+            ),
+            OpCode::SetErrorHandler(_) => meta(
+                "SetErrorHandler",
+                "relative handler offset",
+                0,
+                0,
+                "Install an error handler",
+            ),
+            OpCode::LogSandboxViolation => meta(
+                "LogSandboxViolation",
+                "",
+                0,
+                0,
+                "Record a sandbox violation",
+            ),
+            OpCode::CleanupSandbox => meta("CleanupSandbox", "", 0, 0, "Leave a sandbox"),
+            OpCode::MakeError => meta(
+                "MakeError",
+                "",
+                3,
+                1,
+                "Build an error record from a code, a message and a payload",
+            ),
+            OpCode::Throw => meta(
+                "Throw",
+                "",
+                1,
+                0,
+                "Unwind to the innermost error handler with a value",
+            ),
+            OpCode::PopErrorHandler => meta(
+                "PopErrorHandler",
+                "",
+                0,
+                0,
+                "Remove the innermost error handler",
+            ),
+            OpCode::ErrorPayload => meta(
+                "ErrorPayload",
+                "",
+                1,
+                1,
+                "Replace an error record with its payload",
+            ),
+        }
+    }
+}
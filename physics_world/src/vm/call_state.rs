@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::types::Value;
+use crate::vm::gc::GcPtr;
 
 /// Represents a binding in the environment.
 ///
@@ -233,6 +234,12 @@ pub struct CallFrame {
     pub is_tail_call: bool,         // TCO tracking flag
     pub frame_id: u64,              // For debugging/verification
     pub code_index: usize,          // Track which closure this frame is for (for TCO)
+    /// Number of results the caller expects, set by `CallN`
+    #[serde(default)]
+    pub expected_results: Option<u16>,
+    /// Thunk whose closure this frame evaluates, set by `Force`
+    #[serde(default)]
+    pub forcing: Option<GcPtr>,
 }
 
 impl CallFrame {
@@ -262,6 +269,8 @@ impl CallFrame {
             is_tail_call: false,
             frame_id,
             code_index,
+            expected_results: None,
+            forcing: None,
         }
     }
 
@@ -322,11 +331,13 @@ impl CallStack {
                 stack_trace: Vec::new(),
                 execution_history: Vec::new(),
                 timestamp: 0,
+                source_location: None,
             };
             return Err(crate::vm::error::VmError::recursion_limit_exceeded(
                 context,
                 self.max_depth as u32,
                 self.frames.len() as u32,
+                None,
             ));
         }
         self.frames.push(frame);
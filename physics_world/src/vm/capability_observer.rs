@@ -0,0 +1,28 @@
+//! Real-time observation of capability lifecycle events.
+//!
+//! An embedder installs a [`CapabilityObserver`] on a
+//! [`VmState`](crate::vm::VmState) to log or alert on capability requests,
+//! checks, grants and revocations as the VM executes them, instead of
+//! reconstructing them afterwards from debug snapshots.
+
+use crate::types::Capability;
+
+/// Receives capability events from the capability opcode handlers.
+///
+/// Every method has an empty default, so an observer only implements the
+/// events it cares about. `actor_id` is always the actor executing the
+/// opcode. Observers are shared between clones of a VM, so they take `&self`
+/// and use interior mutability to record anything.
+pub trait CapabilityObserver: Send + Sync {
+    /// `RequestCap`: the actor asked for `capability` and now waits for a decision
+    fn on_request(&self, _actor_id: u32, _capability: &Capability, _justification: &str) {}
+
+    /// `HasCap`: the actor checked whether it holds `capability`
+    fn on_check(&self, _actor_id: u32, _capability: &Capability, _held: bool) {}
+
+    /// `GrantCap`: the actor granted `capability` to `target_actor_id`
+    fn on_grant(&self, _actor_id: u32, _target_actor_id: u32, _capability: &Capability) {}
+
+    /// `RevokeCap`: the actor revoked `capability` from `target_actor_id`
+    fn on_revoke(&self, _actor_id: u32, _target_actor_id: u32, _capability: &Capability) {}
+}
@@ -0,0 +1,17 @@
+//! Pluggable comparison of closure bodies for `ClosureAlphaEq`.
+//!
+//! The VM only sees bytecode, so on its own it can only tell whether two
+//! closures run the same instructions. An embedder that can lift bodies to
+//! a term language installs a [`ClosureEquivalence`] on a
+//! [`VmState`](crate::vm::VmState) to compare them up to α-equivalence
+//! instead.
+
+use crate::types::OpCode;
+
+/// Decides whether two closure bodies compute the same function.
+///
+/// Implementations are shared between clones of a VM, so they take `&self`.
+pub trait ClosureEquivalence: Send + Sync {
+    /// Whether the bodies `a` and `b` are equivalent
+    fn equivalent(&self, a: &[OpCode], b: &[OpCode]) -> bool;
+}
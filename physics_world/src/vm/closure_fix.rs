@@ -98,6 +98,8 @@ pub fn execute_closure_call(
         is_tail_call: false,
         frame_id: 0, // TODO: Add frame_id generation
         code_index: code_index as usize,
+        expected_results: None,
+        forcing: None,
     };
     vm.call_stack.push(call_frame);
 
@@ -139,6 +141,8 @@ pub fn create_closure(
         let value_bytes = match value {
             Value::Pair(p) => p.get().to_le_bytes(),
             Value::Closure(p) => p.get().to_le_bytes(),
+            Value::Vector(p) => p.get().to_le_bytes(),
+            Value::BigInt(p) => p.get().to_le_bytes(),
             Value::Int(n) => (*n as u32).to_le_bytes(),
             Value::Float(f) => (*f as u32).to_le_bytes(), // Convert float to u32 for storage
             Value::Bool(b) => (*b as u32).to_le_bytes(),
@@ -149,6 +153,9 @@ pub fn create_closure(
             Value::Capability(_) => 0u32.to_le_bytes(), // Placeholder
             Value::GcPtr(p) => (p.0 as u32).to_le_bytes(),
             Value::Error(_) => 0u32.to_le_bytes(), // Errors stored as 0
+            Value::ErrorRecord { .. } => 0u32.to_le_bytes(), // Like plain errors
+            Value::Bytes(_) => 0u32.to_le_bytes(), // Bytes stored as 0, like strings
+            Value::Thunk(p) => (p.0 as u32).to_le_bytes(),
         };
         let start = 4 + (i * 4);
         data[start..start + 4].copy_from_slice(&value_bytes);
@@ -0,0 +1,56 @@
+/// Instruction costs charged against the step budget
+use crate::types::OpCode;
+use serde::{Deserialize, Serialize};
+
+/// How many steps each instruction charges against `steps_remaining`.
+///
+/// Costs depend only on the instruction, its operands and the closure body
+/// it copies, so the fuel a program consumes is deterministic. The default model charges one step
+/// per instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostModel {
+    /// Steps every instruction charges
+    pub base: u64,
+    /// Additional steps per value an instruction copies: each body
+    /// instruction of a `MakeClosure` and each element of a `MakeVector`
+    pub per_element: u64,
+}
+
+impl CostModel {
+    /// One step per instruction, whatever it does
+    pub const fn uniform() -> Self {
+        Self {
+            base: 1,
+            per_element: 0,
+        }
+    }
+
+    /// One step per instruction plus one per value it copies
+    pub const fn proportional() -> Self {
+        Self {
+            base: 1,
+            per_element: 1,
+        }
+    }
+
+    /// Steps `opcode` charges under this model, saturating at `u64::MAX`
+    ///
+    /// `body_len` gives the instruction count of the closure body at a
+    /// constant pool index and is only called for `MakeClosure`.
+    pub fn opcode_cost(&self, opcode: &OpCode, body_len: impl FnOnce(usize) -> usize) -> u64 {
+        let elements = match opcode {
+            OpCode::MakeClosure(code_idx, _) => body_len(*code_idx) as u64,
+            OpCode::MakeVector(count) => *count as u64,
+            _ => 0,
+        };
+        self.per_element
+            .saturating_mul(elements)
+            .saturating_add(self.base)
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self::uniform()
+    }
+}
@@ -0,0 +1,70 @@
+//! Instruction coverage for test runs.
+//!
+//! A [`CoverageCollector`] installed on a [`VmState`](crate::vm::VmState)
+//! sets one bit per top-level instruction index the VM executes. Once the run
+//! is over, [`VmState::coverage_report`](crate::vm::VmState::coverage_report)
+//! summarizes which instructions ran and which never did, which points at
+//! untested branches.
+
+use serde::{Deserialize, Serialize};
+
+/// Bitset over the instruction indices executed so far
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageCollector {
+    executed: Vec<u64>,
+    total: usize,
+}
+
+impl CoverageCollector {
+    /// Create a collector for a program of `instruction_count` instructions
+    pub fn new(instruction_count: usize) -> Self {
+        Self {
+            executed: vec![0; instruction_count.div_ceil(64)],
+            total: instruction_count,
+        }
+    }
+
+    /// Mark instruction `index` as executed; out-of-range indices are ignored
+    pub fn record(&mut self, index: usize) {
+        if index < self.total {
+            self.executed[index / 64] |= 1 << (index % 64);
+        }
+    }
+
+    /// Whether instruction `index` has executed
+    pub fn is_covered(&self, index: usize) -> bool {
+        index < self.total && self.executed[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Summarize the instructions executed so far
+    pub fn report(&self) -> CoverageReport {
+        CoverageReport {
+            covered: self
+                .executed
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum(),
+            total: self.total,
+            uncovered: (0..self.total).filter(|&i| !self.is_covered(i)).collect(),
+        }
+    }
+}
+
+/// Covered/total instruction counts and the indices that never executed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub covered: usize,
+    pub total: usize,
+    pub uncovered: Vec<usize>,
+}
+
+impl CoverageReport {
+    /// Fraction of instructions executed, `1.0` for an empty program
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.covered as f64 / self.total as f64
+        }
+    }
+}
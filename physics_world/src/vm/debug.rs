@@ -1,3 +1,4 @@
+use crate::types::Value;
 use crate::vm::state::{VmDebugSnapshot, VmState};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -6,6 +7,9 @@ use std::collections::{HashMap, HashSet};
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Debugger {
     pub breakpoints: HashSet<usize>,
+    /// Breakpoints that only halt when their condition holds, by address
+    #[serde(default)]
+    pub conditional_breakpoints: HashMap<usize, String>,
     pub watchpoints: HashMap<String, Watchpoint>,
     pub step_mode: bool,
     pub call_stack_depth: usize,
@@ -17,6 +21,7 @@ impl Debugger {
     pub fn new() -> Self {
         Self {
             breakpoints: HashSet::new(),
+            conditional_breakpoints: HashMap::new(),
             watchpoints: HashMap::new(),
             step_mode: false,
             call_stack_depth: 0,
@@ -29,8 +34,21 @@ impl Debugger {
         self.breakpoints.insert(address);
     }
 
+    /// Halt at `address` only when `condition` holds.
+    ///
+    /// The condition is a watchpoint expression (see [`WatchScope`]) such as
+    /// `local[0] == 0`; one that does not evaluate to true, including one
+    /// that does not parse, never halts. An unconditional breakpoint at the
+    /// same address still halts every time.
+    pub fn add_conditional_breakpoint(&mut self, address: usize, condition: &str) {
+        self.conditional_breakpoints
+            .insert(address, condition.to_string());
+    }
+
+    /// Remove the breakpoints at `address`, conditional or not
     pub fn remove_breakpoint(&mut self, address: usize) {
         self.breakpoints.remove(&address);
+        self.conditional_breakpoints.remove(&address);
     }
 
     pub fn add_watchpoint(&mut self, name: &str, expression: &str) {
@@ -39,68 +57,79 @@ impl Debugger {
             Watchpoint {
                 expression: expression.to_string(),
                 last_value: None,
+                condition_held: false,
             },
         );
     }
 
+    /// Whether execution should halt before the instruction at `vm.ip`
     pub fn check_breakpoints(&self, vm: &VmState) -> bool {
-        self.breakpoints.contains(&vm.ip)
+        if self.breakpoints.contains(&vm.ip) {
+            return true;
+        }
+        self.conditional_breakpoints
+            .get(&vm.ip)
+            .and_then(|condition| WatchExpr::parse(condition))
+            .is_some_and(|expr| expr.evaluate(&WatchScope::of(vm)) == Some(Value::Bool(true)))
     }
 
+    /// Evaluate every watchpoint against `vm` and report those that fired.
+    ///
+    /// A comparison such as `stack[0] > 100` fires when it becomes true; any
+    /// other expression fires when its value changes. See [`WatchScope`]
+    /// for what an expression can read.
     pub fn check_watchpoints(&mut self, vm: &VmState) -> Vec<WatchpointTrigger> {
-        let mut triggers = Vec::new();
-
-        // Clone watchpoints to avoid borrow checker issues
-        let watchpoints = self.watchpoints.clone();
-        for (name, mut watchpoint) in watchpoints {
-            if let Some(current_value) = self.evaluate_watchpoint(&watchpoint.expression, vm) {
-                if let Some(last_value) = &watchpoint.last_value {
-                    if current_value != *last_value {
-                        triggers.push(WatchpointTrigger {
-                            name: name.clone(),
-                            old_value: last_value.clone(),
-                            new_value: current_value.clone(),
-                        });
-                    }
-                }
-                // Update the original watchpoint
-                if let Some(original) = self.watchpoints.get_mut(&name) {
-                    original.last_value = Some(current_value);
-                }
-            }
-        }
-
-        triggers
+        self.check_watchpoints_in(&WatchScope::of(vm))
     }
 
     pub fn check_watchpoints_snapshot(
         &mut self,
         vm_snapshot: &VmDebugSnapshot,
     ) -> Vec<WatchpointTrigger> {
-        let mut triggers = Vec::new();
+        self.check_watchpoints_in(&WatchScope {
+            stack: &vm_snapshot.stack,
+            locals: &vm_snapshot.locals,
+            steps_remaining: vm_snapshot.steps_remaining,
+        })
+    }
 
-        // Clone watchpoints to avoid borrow checker issues
-        let watchpoints = self.watchpoints.clone();
-        for (name, mut watchpoint) in watchpoints {
-            if let Some(current_value) =
-                self.evaluate_watchpoint_snapshot(&watchpoint.expression, vm_snapshot)
-            {
-                if let Some(last_value) = &watchpoint.last_value {
-                    if current_value != *last_value {
-                        triggers.push(WatchpointTrigger {
-                            name: name.clone(),
-                            old_value: last_value.clone(),
-                            new_value: current_value.clone(),
-                        });
-                    }
-                }
-                // Update the original watchpoint
-                if let Some(original) = self.watchpoints.get_mut(&name) {
-                    original.last_value = Some(current_value);
-                }
+    /// Evaluate every watchpoint in `scope`, returning triggers sorted by name
+    pub fn check_watchpoints_in(&mut self, scope: &WatchScope) -> Vec<WatchpointTrigger> {
+        let mut triggers = Vec::new();
+        for (name, watchpoint) in &mut self.watchpoints {
+            // Expressions that do not parse never fire
+            let Some(expr) = WatchExpr::parse(&watchpoint.expression) else {
+                continue;
+            };
+            let current = expr
+                .watched()
+                .evaluate(scope)
+                .map(|value| value.to_string());
+            let fired = if expr.is_condition() {
+                let holds = expr.evaluate(scope) == Some(Value::Bool(true));
+                let became_true = holds && !watchpoint.condition_held;
+                watchpoint.condition_held = holds;
+                became_true
+            } else {
+                current.is_some()
+                    && watchpoint.last_value.is_some()
+                    && current != watchpoint.last_value
+            };
+            if fired {
+                triggers.push(WatchpointTrigger {
+                    name: name.clone(),
+                    old_value: watchpoint
+                        .last_value
+                        .clone()
+                        .unwrap_or_else(|| UNSET.to_string()),
+                    new_value: current.clone().unwrap_or_else(|| UNSET.to_string()),
+                });
+            }
+            if current.is_some() {
+                watchpoint.last_value = current;
             }
         }
-
+        triggers.sort_by(|a, b| a.name.cmp(&b.name));
         triggers
     }
 
@@ -129,28 +158,11 @@ impl Debugger {
     pub fn get_debug_info(&self) -> DebugInfo {
         DebugInfo {
             breakpoints: self.breakpoints.clone(),
+            conditional_breakpoints: self.conditional_breakpoints.clone(),
             watchpoints: self.watchpoints.clone(),
             events: self.debug_log.clone(),
         }
     }
-
-    /// Simple watchpoint evaluation - in a real implementation this would parse expressions
-    fn evaluate_watchpoint(&self, expression: &str, vm: &VmState) -> Option<String> {
-        // Simple implementation - just return the expression as the value
-        // In a real debugger, this would evaluate the expression in the VM context
-        Some(expression.to_string())
-    }
-
-    /// Simple watchpoint evaluation using snapshot data
-    fn evaluate_watchpoint_snapshot(
-        &self,
-        expression: &str,
-        vm_snapshot: &VmDebugSnapshot,
-    ) -> Option<String> {
-        // Simple implementation - just return the expression as the value
-        // In a real debugger, this would evaluate the expression in the VM context
-        Some(expression.to_string())
-    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -183,19 +195,245 @@ pub enum DebugEventData {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Watchpoint {
     pub expression: String,
+    /// Watched value at the last check where it could be evaluated; for a
+    /// comparison this is its left-hand side
     pub last_value: Option<String>,
+    /// Whether the comparison held at the last check
+    #[serde(default)]
+    pub condition_held: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WatchpointTrigger {
     pub name: String,
+    /// Watched value before the trigger, or `<unset>` if it had never been
+    /// evaluated
     pub old_value: String,
+    /// Watched value when the watchpoint fired
     pub new_value: String,
 }
 
+/// Reported in place of a value that could not be evaluated
+const UNSET: &str = "<unset>";
+
+/// VM state a watchpoint expression can read
+///
+/// Expressions use integer literals, `local[n]` (a local of the current
+/// frame), `stack[n]` (the `n`th value from the top, `stack[0]` being the
+/// top), `steps_remaining`, the operators `+ - * / %`, parentheses, and at
+/// most one comparison `== != < <= > >=`. Arithmetic and ordering need
+/// integers; `==` and `!=` compare any values. An expression that reads a
+/// missing slot or overflows has no value at that check.
+pub struct WatchScope<'a> {
+    pub stack: &'a [Value],
+    pub locals: &'a [Value],
+    pub steps_remaining: u64,
+}
+
+impl<'a> WatchScope<'a> {
+    /// Scope of the current frame of `vm`
+    pub fn of(vm: &'a VmState) -> Self {
+        let locals = match vm.call_stack.last() {
+            Some(frame) => &frame.locals,
+            None => &vm.top_level_locals,
+        };
+        WatchScope {
+            stack: &vm.stack,
+            locals,
+            steps_remaining: vm.steps_remaining,
+        }
+    }
+}
+
+/// Parsed watchpoint expression
+#[derive(Debug, Clone, PartialEq)]
+enum WatchExpr {
+    Int(i64),
+    Local(usize),
+    Stack(usize),
+    StepsRemaining,
+    Arith(char, Box<WatchExpr>, Box<WatchExpr>),
+    Compare(&'static str, Box<WatchExpr>, Box<WatchExpr>),
+}
+
+impl WatchExpr {
+    fn parse(text: &str) -> Option<WatchExpr> {
+        let mut parser = ExprParser { text, pos: 0 };
+        let expr = parser.comparison()?;
+        parser.skip_whitespace();
+        (parser.pos == text.len()).then_some(expr)
+    }
+
+    fn is_condition(&self) -> bool {
+        matches!(self, WatchExpr::Compare(..))
+    }
+
+    /// The quantity reported in triggers
+    fn watched(&self) -> &WatchExpr {
+        match self {
+            WatchExpr::Compare(_, lhs, _) => lhs,
+            other => other,
+        }
+    }
+
+    fn evaluate(&self, scope: &WatchScope) -> Option<Value> {
+        match self {
+            WatchExpr::Int(n) => Some(Value::Int(*n)),
+            WatchExpr::Local(slot) => scope.locals.get(*slot).cloned(),
+            WatchExpr::Stack(depth) => scope.stack.iter().rev().nth(*depth).cloned(),
+            WatchExpr::StepsRemaining => i64::try_from(scope.steps_remaining).ok().map(Value::Int),
+            WatchExpr::Arith(op, lhs, rhs) => {
+                let (Value::Int(a), Value::Int(b)) = (lhs.evaluate(scope)?, rhs.evaluate(scope)?)
+                else {
+                    return None;
+                };
+                let result = match op {
+                    '+' => a.checked_add(b),
+                    '-' => a.checked_sub(b),
+                    '*' => a.checked_mul(b),
+                    '/' => a.checked_div(b),
+                    _ => a.checked_rem(b),
+                };
+                result.map(Value::Int)
+            }
+            WatchExpr::Compare(op, lhs, rhs) => {
+                let (a, b) = (lhs.evaluate(scope)?, rhs.evaluate(scope)?);
+                let holds = match (*op, &a, &b) {
+                    ("==", _, _) => a == b,
+                    ("!=", _, _) => a != b,
+                    ("<", Value::Int(a), Value::Int(b)) => a < b,
+                    ("<=", Value::Int(a), Value::Int(b)) => a <= b,
+                    (">", Value::Int(a), Value::Int(b)) => a > b,
+                    (">=", Value::Int(a), Value::Int(b)) => a >= b,
+                    _ => return None,
+                };
+                Some(Value::Bool(holds))
+            }
+        }
+    }
+}
+
+/// Recursive descent parser for watchpoint expressions
+struct ExprParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl ExprParser<'_> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consume `token` if the input continues with it
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.text[self.pos..].starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn comparison(&mut self) -> Option<WatchExpr> {
+        let lhs = self.sum()?;
+        // Two-character operators first so `<=` is not read as `<`
+        for op in ["==", "!=", "<=", ">=", "<", ">"] {
+            if self.eat(op) {
+                let rhs = self.sum()?;
+                return Some(WatchExpr::Compare(op, Box::new(lhs), Box::new(rhs)));
+            }
+        }
+        Some(lhs)
+    }
+
+    fn sum(&mut self) -> Option<WatchExpr> {
+        let mut expr = self.product()?;
+        loop {
+            let op = if self.eat("+") {
+                '+'
+            } else if self.eat("-") {
+                '-'
+            } else {
+                return Some(expr);
+            };
+            expr = WatchExpr::Arith(op, Box::new(expr), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Option<WatchExpr> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat("*") {
+                '*'
+            } else if self.eat("/") {
+                '/'
+            } else if self.eat("%") {
+                '%'
+            } else {
+                return Some(expr);
+            };
+            expr = WatchExpr::Arith(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Option<WatchExpr> {
+        if self.eat("-") {
+            let operand = self.unary()?;
+            return Some(WatchExpr::Arith(
+                '-',
+                Box::new(WatchExpr::Int(0)),
+                Box::new(operand),
+            ));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Option<WatchExpr> {
+        if self.eat("(") {
+            let expr = self.sum()?;
+            return self.eat(")").then_some(expr);
+        }
+        if self.eat("steps_remaining") {
+            return Some(WatchExpr::StepsRemaining);
+        }
+        if self.eat("local") {
+            return Some(WatchExpr::Local(self.index()?));
+        }
+        if self.eat("stack") {
+            return Some(WatchExpr::Stack(self.index()?));
+        }
+        self.number().map(WatchExpr::Int)
+    }
+
+    /// `[n]` after `local` or `stack`
+    fn index(&mut self) -> Option<usize> {
+        if !self.eat("[") {
+            return None;
+        }
+        let index = usize::try_from(self.number()?).ok()?;
+        self.eat("]").then_some(index)
+    }
+
+    fn number(&mut self) -> Option<i64> {
+        self.skip_whitespace();
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let value = rest[..len].parse().ok()?;
+        self.pos += len;
+        Some(value)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DebugInfo {
     pub breakpoints: HashSet<usize>,
+    /// Breakpoints that only halt when their condition holds, by address
+    #[serde(default)]
+    pub conditional_breakpoints: HashMap<usize, String>,
     pub watchpoints: HashMap<String, Watchpoint>,
     pub events: Vec<DebugEvent>,
 }
@@ -29,9 +29,33 @@ impl WithContext for SimpleVmError {
             SimpleVmError::CapabilityDenied => {
                 VmError::capability_error(context, "unknown", "operation")
             }
-            SimpleVmError::RecursionLimitExceeded => {
-                VmError::recursion_limit_exceeded(context, 0, 0)
+            SimpleVmError::RecursionLimitExceeded {
+                function,
+                depth,
+                limit,
+            } => VmError::recursion_limit_exceeded(
+                context,
+                limit,
+                depth,
+                Some(format!("closure@{}", function.get())),
+            ),
+            SimpleVmError::IndexOutOfBounds { index, length } => {
+                VmError::index_out_of_bounds(context, index, length)
             }
+            SimpleVmError::ReturnCountMismatch { expected, actual } => {
+                VmError::return_count_mismatch(context, expected, actual)
+            }
+            SimpleVmError::InvalidSnapshot => {
+                VmError::serialization_error(context, "invalid VM snapshot")
+            }
+            SimpleVmError::UnknownFunction { function } => {
+                VmError::unknown_function(context, function)
+            }
+            SimpleVmError::CaptureCountMismatch {
+                function,
+                expected,
+                actual,
+            } => VmError::capture_count_mismatch(context, function, expected, actual),
         }
     }
 }
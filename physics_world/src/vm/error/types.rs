@@ -7,6 +7,7 @@
 //! - [`VmError`]: Detailed VM errors with comprehensive context
 
 use crate::types::{HeapPtr, OpCode, Value};
+use crate::vm::source_map::SourceLocation;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
@@ -15,16 +16,56 @@ use std::fmt;
 /// These are used internally by opcode handlers and converted to detailed errors.
 #[derive(Debug)]
 pub enum SimpleVmError {
-    CpuLimitExceeded,       // Resource limit violation
-    MemoryLimitExceeded,    // Resource limit violation
-    StackUnderflow,         // Invalid operation
-    InvalidHeapPtr,         // Memory safety violation
-    UnknownOpCode,          // Invalid instruction
-    TypeMismatch,           // Type system violation
-    DivisionByZero,         // Arithmetic error
-    ArithmeticOverflow,     // Arithmetic error
-    CapabilityDenied,       // Capability system violation
-    RecursionLimitExceeded, // Recursion depth exceeded
+    CpuLimitExceeded,    // Resource limit violation
+    MemoryLimitExceeded, // Resource limit violation
+    StackUnderflow,      // Invalid operation
+    InvalidHeapPtr,      // Memory safety violation
+    UnknownOpCode,       // Invalid instruction
+    TypeMismatch,        // Type system violation
+    DivisionByZero,      // Arithmetic error
+    ArithmeticOverflow,  // Arithmetic error
+    CapabilityDenied,    // Capability system violation
+    /// Recursion depth exceeded
+    RecursionLimitExceeded {
+        /// Closure whose call would have exceeded the limit
+        function: HeapPtr,
+        /// Depth the call would have reached
+        depth: u32,
+        /// Configured maximum recursion depth
+        limit: u32,
+    },
+    /// Vector index out of range
+    IndexOutOfBounds {
+        /// Index that was requested
+        index: i64,
+        /// Length of the indexed vector
+        length: usize,
+    },
+    /// Callee returned a different number of values than the call expected
+    ReturnCountMismatch {
+        /// Results the call expected
+        expected: u16,
+        /// Values the callee returned
+        actual: u16,
+    },
+    /// `VmState::snapshot` could not encode the VM, or bytes passed to
+    /// `VmState::restore` are not a VM snapshot
+    InvalidSnapshot,
+    /// No metadata was loaded for the function
+    UnknownFunction {
+        /// Function id that was looked up
+        function: u16,
+    },
+    /// A closure captured a different number of values than the function's
+    /// escaping free variables
+    CaptureCountMismatch {
+        /// Function id of the closure
+        function: u16,
+        /// Escaping free variables recorded for the function
+        expected: usize,
+        /// Values the closure captured
+        actual: usize,
+    },
 }
 
 /// Enhanced error context that captures the VM state at the time of error
@@ -50,6 +91,9 @@ pub struct ErrorContext {
     pub execution_history: Vec<OpCode>,
     /// Error timestamp (global step count)
     pub timestamp: u64,
+    /// Source location of the failing instruction, if a source map is attached
+    #[serde(default)]
+    pub source_location: Option<Box<SourceLocation>>,
 }
 
 /// Represents a single stack frame in the call trace
@@ -145,6 +189,37 @@ pub enum VmError {
         context: ErrorContext,
         limit: u32,
         current_depth: u32,
+        /// Function whose call exceeded the limit, if known
+        function: Option<String>,
+    },
+
+    /// Vector index out of range
+    IndexOutOfBounds {
+        context: ErrorContext,
+        index: i64,
+        length: usize,
+    },
+
+    /// Callee returned a different number of values than the call expected
+    ReturnCountMismatch {
+        context: ErrorContext,
+        expected: u16,
+        actual: u16,
+    },
+
+    /// No function metadata was loaded for the function id
+    UnknownFunction {
+        context: ErrorContext,
+        function: u16,
+    },
+
+    /// A closure captured a different number of values than its function's
+    /// escaping free variables
+    CaptureCountMismatch {
+        context: ErrorContext,
+        function: u16,
+        expected: usize,
+        actual: usize,
     },
 
     /// Stack overflow error
@@ -189,6 +264,7 @@ impl VmError {
             stack_trace,
             execution_history,
             timestamp,
+            source_location: None,
         }
     }
 
@@ -213,6 +289,7 @@ impl VmError {
             stack_trace: Vec::new(),
             execution_history: Vec::new(),
             timestamp: 0,
+            source_location: None,
         }
     }
 
@@ -327,11 +404,55 @@ impl VmError {
     }
 
     /// Create a recursion limit exceeded error
-    pub fn recursion_limit_exceeded(context: ErrorContext, limit: u32, current_depth: u32) -> Self {
+    pub fn recursion_limit_exceeded(
+        context: ErrorContext,
+        limit: u32,
+        current_depth: u32,
+        function: Option<String>,
+    ) -> Self {
         VmError::RecursionLimitExceeded {
             context,
             limit,
             current_depth,
+            function,
+        }
+    }
+
+    /// Create an index out of bounds error
+    pub fn index_out_of_bounds(context: ErrorContext, index: i64, length: usize) -> Self {
+        VmError::IndexOutOfBounds {
+            context,
+            index,
+            length,
+        }
+    }
+
+    /// Create a return count mismatch error
+    pub fn return_count_mismatch(context: ErrorContext, expected: u16, actual: u16) -> Self {
+        VmError::ReturnCountMismatch {
+            context,
+            expected,
+            actual,
+        }
+    }
+
+    /// Create an unknown function error
+    pub fn unknown_function(context: ErrorContext, function: u16) -> Self {
+        VmError::UnknownFunction { context, function }
+    }
+
+    /// Create a capture count mismatch error
+    pub fn capture_count_mismatch(
+        context: ErrorContext,
+        function: u16,
+        expected: usize,
+        actual: usize,
+    ) -> Self {
+        VmError::CaptureCountMismatch {
+            context,
+            function,
+            expected,
+            actual,
         }
     }
 
@@ -350,6 +471,10 @@ impl VmError {
             VmError::SerializationError { context, .. } => context,
             VmError::HeapCorruption { context, .. } => context,
             VmError::RecursionLimitExceeded { context, .. } => context,
+            VmError::IndexOutOfBounds { context, .. } => context,
+            VmError::ReturnCountMismatch { context, .. } => context,
+            VmError::UnknownFunction { context, .. } => context,
+            VmError::CaptureCountMismatch { context, .. } => context,
             VmError::StackOverflow { context, .. } => context,
             VmError::GcDisabled => panic!("GcDisabled error has no context"),
             VmError::HeapExhausted => panic!("HeapExhausted error has no context"),
@@ -480,10 +605,59 @@ impl VmError {
                 context,
                 limit,
                 current_depth,
+                function,
+            } => {
+                let callee = function
+                    .as_ref()
+                    .map(|name| format!(" calling {}", name))
+                    .unwrap_or_default();
+                let top_frame = context
+                    .stack_trace
+                    .last()
+                    .map(|frame| {
+                        format!(" from {} (call IP {})", frame.function_name, frame.call_ip)
+                    })
+                    .unwrap_or_default();
+                format!(
+                    "Recursion Limit Exceeded: Depth {} exceeds limit {}{}{} at IP {} (actor {}). Stack: {:?}",
+                    current_depth, limit, callee, top_frame, context.instruction_pointer, context.actor_id, context.stack_state
+                )
+            }
+            VmError::IndexOutOfBounds {
+                context,
+                index,
+                length,
             } => {
                 format!(
-                    "Recursion Limit Exceeded: Depth {} exceeds limit {} at IP {} (actor {}). Stack: {:?}",
-                    current_depth, limit, context.instruction_pointer, context.actor_id, context.stack_state
+                    "Index Out Of Bounds: Index {} for vector of length {} at IP {} (actor {}). Stack: {:?}",
+                    index, length, context.instruction_pointer, context.actor_id, context.stack_state
+                )
+            }
+            VmError::ReturnCountMismatch {
+                context,
+                expected,
+                actual,
+            } => {
+                format!(
+                    "Return Count Mismatch: Call expected {} results but callee returned {} at IP {} (actor {}). Stack: {:?}",
+                    expected, actual, context.instruction_pointer, context.actor_id, context.stack_state
+                )
+            }
+            VmError::UnknownFunction { context, function } => {
+                format!(
+                    "Unknown Function: No metadata loaded for function {} at IP {} (actor {}). Stack: {:?}",
+                    function, context.instruction_pointer, context.actor_id, context.stack_state
+                )
+            }
+            VmError::CaptureCountMismatch {
+                context,
+                function,
+                expected,
+                actual,
+            } => {
+                format!(
+                    "Capture Count Mismatch: Closure of function {} captured {} values but {} free variables escape into it at IP {} (actor {}). Stack: {:?}",
+                    function, actual, expected, context.instruction_pointer, context.actor_id, context.stack_state
                 )
             }
             VmError::StackOverflow {
@@ -504,6 +678,14 @@ impl VmError {
         }
     }
 
+    /// Get the resolved source location, if the error carries a context with one
+    pub fn source_location(&self) -> Option<SourceLocation> {
+        match self {
+            VmError::GcDisabled | VmError::HeapExhausted | VmError::DebuggerError { .. } => None,
+            _ => self.context().source_location.as_deref().copied(),
+        }
+    }
+
     /// Check if this error is recoverable
     pub fn is_recoverable(&self) -> bool {
         match self {
@@ -516,7 +698,11 @@ impl VmError {
             VmError::HeapCorruption { .. } => false,
             VmError::SerializationError { .. } => false,
             VmError::StackOverflow { .. } => false,
+            VmError::ReturnCountMismatch { .. } => false,
+            VmError::UnknownFunction { .. } => false,
+            VmError::CaptureCountMismatch { .. } => false,
             VmError::RecursionLimitExceeded { .. } => true, // Can be recovered with higher limit
+            VmError::IndexOutOfBounds { .. } => true,       // Caller can retry with a valid index
             VmError::CpuLimitExceeded { .. } => true,       // Can be recovered with more steps
             VmError::MemoryLimitExceeded { .. } => true,    // Can be recovered with more memory
             VmError::CapabilityError { .. } => true, // Can be recovered with proper capabilities
@@ -555,7 +741,11 @@ impl VmError {
 
 impl fmt::Display for VmError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.detailed_message())
+        write!(f, "{}", self.detailed_message())?;
+        if let Some(location) = self.source_location() {
+            write!(f, " (at {})", location)?;
+        }
+        Ok(())
     }
 }
 
@@ -576,6 +766,7 @@ impl From<SimpleVmError> for VmError {
             stack_trace: Vec::new(),
             execution_history: Vec::new(),
             timestamp: 0,
+            source_location: None,
         };
 
         match simple_error {
@@ -594,9 +785,33 @@ impl From<SimpleVmError> for VmError {
             SimpleVmError::CapabilityDenied => {
                 VmError::capability_error(context, "unknown", "operation")
             }
-            SimpleVmError::RecursionLimitExceeded => {
-                VmError::recursion_limit_exceeded(context, 0, 0)
+            SimpleVmError::RecursionLimitExceeded {
+                function,
+                depth,
+                limit,
+            } => VmError::recursion_limit_exceeded(
+                context,
+                limit,
+                depth,
+                Some(format!("closure@{}", function.get())),
+            ),
+            SimpleVmError::IndexOutOfBounds { index, length } => {
+                VmError::index_out_of_bounds(context, index, length)
+            }
+            SimpleVmError::ReturnCountMismatch { expected, actual } => {
+                VmError::return_count_mismatch(context, expected, actual)
             }
+            SimpleVmError::InvalidSnapshot => {
+                VmError::serialization_error(context, "invalid VM snapshot")
+            }
+            SimpleVmError::UnknownFunction { function } => {
+                VmError::unknown_function(context, function)
+            }
+            SimpleVmError::CaptureCountMismatch {
+                function,
+                expected,
+                actual,
+            } => VmError::capture_count_mismatch(context, function, expected, actual),
         }
     }
 }
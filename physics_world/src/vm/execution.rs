@@ -9,8 +9,9 @@
 use crate::types::{OpCode, Value};
 use crate::vm::error::{SimpleVmError, VmError, WithContext};
 use crate::vm::opcodes::{
-    arithmetic, basic, call, capability, comparison, jump, list_ops, make_closure, messaging, ret,
-    stack_ops, string_ops,
+    arithmetic, basic, call, capability, closure_eq, comparison, deep_clone, error_ops, gc_ops,
+    jump, list_ops, make_closure, messaging, ret, stack_ops, string_ops, thunk, type_ops,
+    vector_ops,
 };
 use crate::vm::state::InstructionResult;
 
@@ -36,11 +37,18 @@ impl ExecutionEngine {
         &mut self,
         state: &mut crate::vm::state::VmState,
     ) -> Result<InstructionResult, SimpleVmError> {
-        // Check if we've exceeded CPU limit
-        if state.steps_remaining == 0 {
+        // Charge the instruction's cost before running it. Running out
+        // leaves the VM untouched, so a top-up resumes at this instruction
+        let cost = state.instructions.get(state.ip).map_or(1, |instruction| {
+            state
+                .cost_model
+                .opcode_cost(instruction, |code_idx| state.closure_body_len(code_idx))
+        });
+        if state.steps_remaining < cost {
             return Err(SimpleVmError::CpuLimitExceeded);
         }
-        state.steps_remaining -= 1;
+        state.steps_remaining -= cost;
+        state.steps_executed += 1;
 
         eprintln!(
             "STEP: ip={}, call_stack_len={}, instructions_len={}",
@@ -49,6 +57,9 @@ impl ExecutionEngine {
             state.instructions.len()
         );
 
+        // Restore capabilities dropped by any WithCaps region we have left
+        state.leave_finished_capability_scopes();
+
         // Check bounds BEFORE trying to fetch the instruction
         if state.call_stack.is_empty() && state.ip >= state.instructions.len() {
             eprintln!(
@@ -61,6 +72,18 @@ impl ExecutionEngine {
             ));
         }
 
+        // Closure bodies swapped in by a call have their own indices; only
+        // the loaded program's instructions count towards coverage
+        if let Some(coverage) = &mut state.coverage {
+            if state
+                .call_stack
+                .iter()
+                .all(|frame| frame.saved_instructions.is_none())
+            {
+                coverage.record(state.ip);
+            }
+        }
+
         // Get current instruction
         let instruction = match state.instructions.get(state.ip) {
             Some(instr) => {
@@ -163,6 +186,14 @@ impl ExecutionEngine {
                 string_ops::handle_str_index(state)?;
                 state.ip += 1;
             }
+            OpCode::BytesToStr => {
+                string_ops::handle_bytes_to_str(state)?;
+                state.ip += 1;
+            }
+            OpCode::StrToBytes => {
+                string_ops::handle_str_to_bytes(state)?;
+                state.ip += 1;
+            }
             OpCode::Dup => {
                 stack_ops::handle_dup(state)?;
                 state.ip += 1;
@@ -195,6 +226,60 @@ impl ExecutionEngine {
                 list_ops::handle_cdr(state)?;
                 state.ip += 1;
             }
+            OpCode::ListLen => {
+                list_ops::handle_list_len(state)?;
+                state.ip += 1;
+            }
+            OpCode::DeepClone => {
+                deep_clone::handle_deep_clone(state)?;
+                state.ip += 1;
+            }
+            OpCode::MakeThunk => {
+                thunk::handle_make_thunk(state)?;
+                state.ip += 1;
+            }
+            OpCode::Force => {
+                if !thunk::handle_force(state)? {
+                    state.ip += 1;
+                }
+                // Note: forcing a pending thunk calls its closure, which sets ip
+            }
+            OpCode::MakeVector(count) => {
+                vector_ops::handle_make_vector(state, *count)?;
+                state.ip += 1;
+            }
+            OpCode::VecGet => {
+                vector_ops::handle_vec_get(state)?;
+                state.ip += 1;
+            }
+            OpCode::VecSet => {
+                vector_ops::handle_vec_set(state)?;
+                state.ip += 1;
+            }
+            OpCode::VecLen => {
+                vector_ops::handle_vec_len(state)?;
+                state.ip += 1;
+            }
+            OpCode::TypeOf => {
+                type_ops::handle_type_of(state)?;
+                state.ip += 1;
+            }
+            OpCode::IsInt => {
+                type_ops::handle_type_test(state, type_ops::is_int)?;
+                state.ip += 1;
+            }
+            OpCode::IsFloat => {
+                type_ops::handle_type_test(state, type_ops::is_float)?;
+                state.ip += 1;
+            }
+            OpCode::IsPair => {
+                type_ops::handle_type_test(state, type_ops::is_pair)?;
+                state.ip += 1;
+            }
+            OpCode::IsClosure => {
+                type_ops::handle_type_test(state, type_ops::is_closure)?;
+                state.ip += 1;
+            }
             OpCode::Call(arg_count) => {
                 // Use the new enhanced handle_call method from VmState
                 state.handle_call(*arg_count)?;
@@ -206,6 +291,26 @@ impl ExecutionEngine {
                 state.handle_tail_call(*arg_count)?;
                 // Note: TailCall handler sets ip to 0 for closure execution
             }
+            OpCode::CallN(arg_count, result_count) => {
+                call::handle_call_n(state, *arg_count, *result_count)?;
+                // Note: CallN handler sets ip to 0 for closure execution
+            }
+            OpCode::CollectRest(fixed) => {
+                call::handle_collect_rest(state, *fixed)?;
+                state.ip += 1;
+            }
+            OpCode::RetN(count) => {
+                let result = ret::handle_ret_n(state, *count)?;
+                // Note: RetN handler sets ip to return address, or returns Finished if at top level
+                match result {
+                    InstructionResult::Finished(value) => {
+                        return Ok(InstructionResult::Finished(value))
+                    }
+                    InstructionResult::Continue => { /* Continue with normal flow, ip already set */
+                    }
+                    _ => return Ok(result),
+                }
+            }
             OpCode::Ret => {
                 let result = ret::handle_ret(state)?;
                 // Note: Ret handler sets ip to return address, or returns Finished if at top level
@@ -236,6 +341,12 @@ impl ExecutionEngine {
                 state.ip += 1;
                 return Ok(result);
             }
+            OpCode::Spawn { arg_count } => {
+                // The scheduler creates the actor and pushes its id
+                let result = messaging::handle_spawn(state, *arg_count)?;
+                state.ip += 1;
+                return Ok(result);
+            }
             OpCode::Add => {
                 arithmetic::handle_add(state)?;
                 state.ip += 1;
@@ -296,11 +407,35 @@ impl ExecutionEngine {
                 arithmetic::handle_mod(state)?;
                 state.ip += 1;
             }
+            OpCode::BitAnd => {
+                arithmetic::handle_bit_and(state)?;
+                state.ip += 1;
+            }
+            OpCode::BitOr => {
+                arithmetic::handle_bit_or(state)?;
+                state.ip += 1;
+            }
+            OpCode::BitXor => {
+                arithmetic::handle_bit_xor(state)?;
+                state.ip += 1;
+            }
+            OpCode::Shl => {
+                arithmetic::handle_shl(state)?;
+                state.ip += 1;
+            }
+            OpCode::Shr => {
+                arithmetic::handle_shr(state)?;
+                state.ip += 1;
+            }
             OpCode::MakeClosure(code_idx, capture_count) => {
                 let closure = make_closure::handle_make_closure(state, *code_idx, *capture_count)?;
                 state.stack.push(closure);
                 state.ip += 1;
             }
+            OpCode::ClosureAlphaEq => {
+                closure_eq::handle_closure_alpha_eq(state)?;
+                state.ip += 1;
+            }
             OpCode::CheckStepLimit => {
                 // Check if we've exceeded CPU limit
                 if state.steps_remaining == 0 {
@@ -308,13 +443,34 @@ impl ExecutionEngine {
                 }
                 state.ip += 1;
             }
+            OpCode::GcCollect => {
+                gc_ops::handle_gc_collect(state)?;
+                state.ip += 1;
+            }
+            OpCode::GcStats => {
+                gc_ops::handle_gc_stats(state)?;
+                state.ip += 1;
+            }
+            OpCode::StepsRemaining => {
+                let steps = i64::try_from(state.steps_remaining).unwrap_or(i64::MAX);
+                state.stack.push(Value::Int(steps));
+                state.ip += 1;
+            }
+            OpCode::MemoryRemaining => {
+                let remaining = state.memory.capacity() - state.memory.next_free();
+                state.stack.push(Value::Int(i64::from(remaining)));
+                state.ip += 1;
+            }
             // V2 Capability System - Implement capability opcodes
             OpCode::HasCap(cap_idx) => {
                 let result = capability::handle_has_cap(state, *cap_idx)?;
+                state.ip += 1;
                 return Ok(result);
             }
             OpCode::RequestCap(cap_idx, justification_idx) => {
                 let result = capability::handle_request_cap(state, *cap_idx, *justification_idx)?;
+                // Resume after the request once the decision has been made
+                state.ip += 1;
                 return Ok(result);
             }
             OpCode::GrantCap(target_actor_id, cap_idx) => {
@@ -334,6 +490,10 @@ impl ExecutionEngine {
                 state.ip += 1;
             }
             // Sandbox instructions
+            OpCode::WithCaps { cap_mask, body_len } => {
+                state.enter_capability_scope(*cap_mask, *body_len);
+                state.ip += 1;
+            }
             OpCode::InitSandbox => {
                 // Initialize sandbox environment - place holder for now
                 state.ip += 1;
@@ -343,7 +503,7 @@ impl ExecutionEngine {
                 state.ip += 1;
             }
             OpCode::SetErrorHandler(offset) => {
-                // Set error handler jump target - place holder for now
+                error_ops::handle_set_error_handler(state, *offset)?;
                 state.ip += 1;
             }
             OpCode::LogSandboxViolation => {
@@ -354,6 +514,28 @@ impl ExecutionEngine {
                 // Cleanup sandbox resources - place holder for now
                 state.ip += 1;
             }
+            // Error instructions
+            OpCode::MakeError => {
+                error_ops::handle_make_error(state)?;
+                state.ip += 1;
+            }
+            OpCode::Throw => {
+                // Throw jumps to the handler itself
+                if let Some(value) = error_ops::handle_throw(state)? {
+                    return Ok(InstructionResult::Finished(value));
+                }
+            }
+            OpCode::PopErrorHandler => {
+                state
+                    .error_handlers
+                    .pop()
+                    .ok_or(SimpleVmError::StackUnderflow)?;
+                state.ip += 1;
+            }
+            OpCode::ErrorPayload => {
+                error_ops::handle_error_payload(state)?;
+                state.ip += 1;
+            }
         }
 
         Ok(InstructionResult::Continue)
@@ -380,6 +562,15 @@ impl ExecutionEngine {
                         "WaitingForCapability",
                     ));
                 }
+                Ok(InstructionResult::Spawn { .. }) => {
+                    // Only a scheduler can host the new actor
+                    let context = state.create_error_context();
+                    return Err(VmError::capability_error(
+                        context,
+                        "SysCreateActor",
+                        "Spawn outside a scheduler",
+                    ));
+                }
                 Err(simple_error) => {
                     // Convert simple error to detailed error with context
                     return Err(simple_error.with_context(state.create_error_context()));
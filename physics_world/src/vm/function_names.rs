@@ -0,0 +1,55 @@
+//! Function names for symbolicated stack traces.
+//!
+//! A closure body lives in the constant pool, and a call frame records the
+//! constant index of the body it is running as its `code_index`. A compiler
+//! that knows the source-level name of each function ships a
+//! [`FunctionNames`] table with the bytecode; once it is attached to a
+//! [`VmState`](crate::vm::VmState), stack traces show those names instead of
+//! ip-based placeholders.
+//!
+//! The table is keyed by the same number as the VM's function table: the
+//! constant index of the body. A compiler that hoists each lambda body into
+//! its own constant slot, as the Jue pipeline does, uses that slot as the
+//! function id for both tables, so names recorded at compile time match the
+//! frames the VM reports.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Source-level function names, keyed by the constant index of each body
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionNames {
+    names: BTreeMap<usize, String>,
+}
+
+impl FunctionNames {
+    /// Create a new empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name the function whose body is constant `code_index`
+    pub fn insert(&mut self, code_index: usize, name: &str) {
+        self.names.insert(code_index, name.to_string());
+    }
+
+    /// Name of the function whose body is constant `code_index`
+    pub fn name_for(&self, code_index: usize) -> Option<&str> {
+        self.names.get(&code_index).map(String::as_str)
+    }
+
+    /// Constant indices of the named bodies, in increasing order
+    pub fn code_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.names.keys().copied()
+    }
+
+    /// Number of named functions
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether no functions have been named
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -0,0 +1,69 @@
+/// Fuzzing entry point for the VM
+///
+/// `fuzz_run` turns arbitrary bytes into a program and runs it under tight
+/// limits. It is meant to be called from a `cargo fuzz` target:
+///
+/// ```ignore
+/// fuzz_target!(|data: &[u8]| {
+///     let _ = physics_world::vm::fuzz_run(data);
+/// });
+/// ```
+///
+/// Malformed programs must surface as `Err`; any panic is a VM bug. The byte
+/// encoding follows `OpCode` variant order, so saved corpora go stale when
+/// opcodes are added.
+use bincode::Options;
+
+use crate::types::{OpCode, Value};
+use crate::vm::error::VmError;
+use crate::vm::state::VmState;
+
+/// Instruction budget for a fuzzed program
+pub const FUZZ_STEP_LIMIT: u64 = 1000;
+/// Heap size for a fuzzed program
+pub const FUZZ_MEMORY_LIMIT: usize = 1024;
+/// Call depth limit for a fuzzed program
+pub const FUZZ_MAX_RECURSION_DEPTH: u32 = 16;
+/// Inputs are truncated to this many bytes
+pub const FUZZ_MAX_INPUT: usize = 4096;
+
+/// Decode `data` into a program and run it with fuzzing limits
+#[allow(clippy::result_large_err)] // Same error type as `VmState::run`
+pub fn fuzz_run(data: &[u8]) -> Result<Value, VmError> {
+    let instructions = decode_opcodes(data);
+    let mut vm = VmState::new(
+        instructions,
+        vec![],
+        FUZZ_STEP_LIMIT,
+        FUZZ_MEMORY_LIMIT,
+        1,
+        FUZZ_MAX_RECURSION_DEPTH,
+    );
+    vm.run()
+}
+
+/// Synthesize opcodes from arbitrary bytes.
+///
+/// Opcodes are read back to back in bincode's varint encoding, where a
+/// single byte below the variant count selects an opcode, so most inputs
+/// yield a program. A byte that does not start a valid opcode is skipped.
+pub fn decode_opcodes(data: &[u8]) -> Vec<OpCode> {
+    let data = &data[..data.len().min(FUZZ_MAX_INPUT)];
+    let options = bincode::DefaultOptions::new()
+        .with_limit(FUZZ_MAX_INPUT as u64)
+        .allow_trailing_bytes();
+
+    let mut instructions = Vec::new();
+    let mut rest = data;
+    while !rest.is_empty() {
+        let mut reader = rest;
+        match options.deserialize_from::<_, OpCode>(&mut reader) {
+            Ok(opcode) => {
+                instructions.push(opcode);
+                rest = reader;
+            }
+            Err(_) => rest = &rest[1..],
+        }
+    }
+    instructions
+}
@@ -1,16 +1,88 @@
 use crate::types::Value;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::Instant;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
 /// Heap object types that can be managed by the garbage collector
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HeapObject {
     Closure(Closure),
     Array(Array),
+    Thunk(Thunk),
     // Other heap object types can be added here
 }
 
+impl HeapObject {
+    /// GC pointers this object references directly
+    pub fn outgoing_pointers(&self) -> Vec<GcPtr> {
+        let values: Vec<&Value> = match self {
+            HeapObject::Closure(closure) => closure.environment.values().collect(),
+            HeapObject::Array(array) => array.elements().iter().collect(),
+            HeapObject::Thunk(thunk) => vec![thunk.value()],
+        };
+        values
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::GcPtr(ptr) | Value::Thunk(ptr) => Some(*ptr),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Every value this object holds
+    pub fn values_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            HeapObject::Closure(closure) => closure.environment.values_mut().collect(),
+            HeapObject::Array(array) => array.elements.iter_mut().collect(),
+            HeapObject::Thunk(Thunk::Pending(value) | Thunk::Forced(value)) => vec![value],
+        }
+    }
+
+    /// Approximate bytes this object occupies in memory, counted from its
+    /// shape so that recording an allocation does not serialize the object
+    pub fn footprint(&self) -> usize {
+        let held = match self {
+            HeapObject::Closure(closure) => {
+                closure.environment.len()
+                    * (std::mem::size_of::<usize>() + std::mem::size_of::<Value>())
+            }
+            HeapObject::Array(array) => array.elements.len() * std::mem::size_of::<Value>(),
+            HeapObject::Thunk(_) => 0,
+        };
+        std::mem::size_of::<HeapObject>() + held
+    }
+
+    /// Point references at the new index of their target after compaction;
+    /// references to objects that were not kept are left as they are
+    fn relocate(&mut self, new_index_map: &[Option<usize>]) {
+        for value in self.values_mut() {
+            relocate_gc_value(value, new_index_map);
+        }
+    }
+}
+
+/// Point the GC heap references `value` holds, including those nested in
+/// error payloads, at the new index of their target after compaction;
+/// references to objects that were not kept are left as they are
+pub(crate) fn relocate_gc_value(value: &mut Value, new_index_map: &[Option<usize>]) {
+    let mut value = value;
+    loop {
+        match value {
+            Value::GcPtr(ptr) | Value::Thunk(ptr) => {
+                if let Some(Some(new_index)) = new_index_map.get(ptr.0) {
+                    ptr.0 = *new_index;
+                }
+            }
+            Value::ErrorRecord { payload, .. } => {
+                value = payload;
+                continue;
+            }
+            _ => {}
+        }
+        return;
+    }
+}
+
 /// Closure representation for GC
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Closure {
@@ -30,14 +102,101 @@ impl Array {
     }
 }
 
+/// Delayed computation: a zero-argument closure until forced, then its result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Thunk {
+    Pending(Value),
+    Forced(Value),
+}
+
+impl Thunk {
+    /// The closure still to run, or the memoized result
+    pub fn value(&self) -> &Value {
+        match self {
+            Thunk::Pending(value) | Thunk::Forced(value) => value,
+        }
+    }
+}
+
+/// When the VM runs the collector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum GcMode {
+    /// Mark and sweep the whole heap once the allocation threshold is reached
+    #[default]
+    StopTheWorld,
+    /// Past the threshold, scan at most `budget` objects per allocation and
+    /// sweep once marking completes
+    Incremental { budget: usize },
+    /// Collect only the nursery once it holds `nursery` objects, promoting
+    /// the survivors; collect the whole heap once the old generation has
+    /// grown by the allocation threshold since the last full collection
+    Generational { nursery: usize },
+}
+
+/// Tri-color state of an object during incremental marking
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GcColor {
+    /// Not yet reached; reclaimed if still white when marking completes
+    White,
+    /// Reached, but its references have not been scanned
+    Gray,
+    /// Reached and scanned
+    Black,
+}
+
+/// Progress of an incremental marking cycle
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MarkState {
+    colors: Vec<GcColor>,
+    gray: Vec<usize>,
+}
+
+impl MarkState {
+    fn shade(&mut self, index: usize) {
+        if self.colors.get(index) == Some(&GcColor::White) {
+            self.colors[index] = GcColor::Gray;
+            self.gray.push(index);
+        }
+    }
+}
+
 /// Mark-and-sweep garbage collector
+///
+/// `collect` marks and sweeps in one pause. Alternatively `mark_increment`
+/// spreads tri-color marking over many short steps interleaved with the
+/// mutator, which must report every pointer it stores into a heap object
+/// through `write_barrier` while marking is in progress.
+///
+/// The heap is split into two generations: `heap[..old_generation_len()]`
+/// holds objects that survived a collection and the rest is the nursery of
+/// objects allocated since. `minor_collect` sweeps only the nursery, treating
+/// old objects as live; the old objects that `write_barrier` saw receive a
+/// pointer into the nursery are scanned as extra roots.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct GarbageCollector {
     pub heap: Vec<HeapObject>,
     pub roots: Vec<GcRoot>,
     pub allocation_threshold: usize,
     pub allocations_since_last_gc: usize,
+    #[serde(default)]
+    pub bytes_allocated_since_last_gc: usize,
     pub gc_stats: GcStats,
+    #[serde(default)]
+    pub next_root_scope: u64,
+    #[serde(default)]
+    marking: Option<MarkState>,
+    /// Number of objects at the start of the heap in the old generation
+    #[serde(default)]
+    old_len: usize,
+    /// Size of the old generation after the last full collection
+    #[serde(default)]
+    old_len_after_major: usize,
+    /// Old objects that may point into the nursery
+    #[serde(default)]
+    remembered: BTreeSet<usize>,
+    /// Old-to-new index map of the sweeps since the last `take_relocation`
+    #[serde(skip)]
+    relocation: Option<Vec<Option<usize>>>,
 }
 
 impl GarbageCollector {
@@ -47,30 +206,269 @@ impl GarbageCollector {
             roots: Vec::new(),
             allocation_threshold: threshold,
             allocations_since_last_gc: 0,
+            bytes_allocated_since_last_gc: 0,
             gc_stats: GcStats::default(),
+            next_root_scope: 0,
+            marking: None,
+            old_len: 0,
+            old_len_after_major: 0,
+            remembered: BTreeSet::new(),
+            relocation: None,
         }
     }
 
-    pub fn allocate(&mut self, object: HeapObject) -> GcPtr {
-        self.allocations_since_last_gc += 1;
+    pub fn allocate(&mut self, mut object: HeapObject) -> GcPtr {
+        self.record_allocation(&object);
+        let collections = self.gc_stats.collections;
 
         if self.allocations_since_last_gc >= self.allocation_threshold {
             self.collect();
         }
 
+        self.relocate_if_collected(&mut object, collections);
+        self.push_object(object)
+    }
+
+    /// Allocate without a stop-the-world pause: once the threshold is
+    /// reached, each allocation advances marking by `budget` objects and the
+    /// heap is swept when marking completes
+    pub fn allocate_incremental(&mut self, mut object: HeapObject, budget: usize) -> GcPtr {
+        self.record_allocation(&object);
+        let collections = self.gc_stats.collections;
+
+        if (self.is_marking() || self.allocations_since_last_gc >= self.allocation_threshold)
+            && self.mark_increment(budget)
+        {
+            self.finish_incremental_collection();
+        }
+
+        self.relocate_if_collected(&mut object, collections);
+        self.push_object(object)
+    }
+
+    /// Allocate into the nursery, first running a minor collection if it
+    /// already holds `nursery` objects and a full one if that grew the old
+    /// generation by the allocation threshold since the last full collection
+    pub fn allocate_generational(&mut self, mut object: HeapObject, nursery: usize) -> GcPtr {
+        self.record_allocation(&object);
+        let collections = self.gc_stats.collections;
+
+        if self.heap.len().saturating_sub(self.old_len) >= nursery {
+            self.minor_collect();
+            if self.old_len.saturating_sub(self.old_len_after_major) >= self.allocation_threshold {
+                self.collect();
+            }
+        }
+
+        self.relocate_if_collected(&mut object, collections);
+        self.push_object(object)
+    }
+
+    fn record_allocation(&mut self, object: &HeapObject) {
+        self.allocations_since_last_gc += 1;
+        self.bytes_allocated_since_last_gc += object.footprint();
+    }
+
+    /// Point the references of an object being allocated at their targets'
+    /// new indices if the allocation ran a collection
+    fn relocate_if_collected(&self, object: &mut HeapObject, collections: u32) {
+        if self.gc_stats.collections == collections {
+            return;
+        }
+        if let Some(relocation) = &self.relocation {
+            object.relocate(relocation);
+        }
+    }
+
+    /// Take the old-to-new index map of the sweeps since the last call, or
+    /// `None` if the heap has not been compacted since.
+    ///
+    /// A sweep rewrites the roots and the references between heap objects,
+    /// but not the `GcPtr` and `Thunk` values held outside the heap; their
+    /// owner must apply this map after every allocation or collection.
+    pub fn take_relocation(&mut self) -> Option<Vec<Option<usize>>> {
+        self.relocation.take()
+    }
+
+    /// Record a sweep's index map, composing it with the map of an earlier
+    /// sweep that was not taken yet
+    fn record_relocation(&mut self, new_index_map: Vec<Option<usize>>) {
+        self.relocation = Some(match self.relocation.take() {
+            Some(earlier) => earlier
+                .into_iter()
+                .map(|index| index.and_then(|index| new_index_map.get(index).copied().flatten()))
+                .collect(),
+            None => new_index_map,
+        });
+    }
+
+    fn push_object(&mut self, object: HeapObject) -> GcPtr {
         let ptr = self.heap.len();
         self.heap.push(object);
+        // Objects allocated during marking are live for this cycle
+        if let Some(marking) = &mut self.marking {
+            marking.colors.push(GcColor::Black);
+        }
         GcPtr(ptr)
     }
 
+    /// Number of objects in the old generation, which occupies the start of
+    /// the heap
+    pub fn old_generation_len(&self) -> usize {
+        self.old_len
+    }
+
+    /// Whether the old object at `ptr` is recorded as pointing into the
+    /// nursery
+    pub fn is_remembered(&self, ptr: GcPtr) -> bool {
+        self.remembered.contains(&ptr.0)
+    }
+
+    /// Whether an incremental marking cycle is in progress
+    pub fn is_marking(&self) -> bool {
+        self.marking.is_some()
+    }
+
+    /// Color of the object at `ptr` in the current marking cycle, or `None`
+    /// when no cycle is in progress
+    pub fn color(&self, ptr: GcPtr) -> Option<GcColor> {
+        self.marking
+            .as_ref()
+            .and_then(|marking| marking.colors.get(ptr.0).copied())
+    }
+
+    /// Scan at most `budget` gray objects, starting a marking cycle by
+    /// shading the roots if none is in progress.
+    ///
+    /// Returns true once no gray object is left, i.e. every reachable object
+    /// is black; the roots are shaded again first so that roots registered
+    /// during marking are not missed. Call `finish_incremental_collection`
+    /// to sweep.
+    pub fn mark_increment(&mut self, budget: usize) -> bool {
+        let mut marking = self.marking.take().unwrap_or_else(|| {
+            let mut marking = MarkState {
+                colors: vec![GcColor::White; self.heap.len()],
+                gray: Vec::new(),
+            };
+            for root in &self.roots {
+                marking.shade(root.ptr.0);
+            }
+            marking
+        });
+        // Objects pushed onto the heap directly are treated as allocated black
+        marking.colors.resize(self.heap.len(), GcColor::Black);
+
+        for _ in 0..budget {
+            let Some(index) = marking.gray.pop() else {
+                break;
+            };
+            for ptr in self.heap[index].outgoing_pointers() {
+                marking.shade(ptr.0);
+            }
+            marking.colors[index] = GcColor::Black;
+        }
+
+        if marking.gray.is_empty() {
+            for root in &self.roots {
+                marking.shade(root.ptr.0);
+            }
+        }
+        let done = marking.gray.is_empty();
+        self.marking = Some(marking);
+        done
+    }
+
+    /// Complete the current marking cycle and sweep every object left white.
+    /// Does nothing when no cycle is in progress.
+    pub fn finish_incremental_collection(&mut self) {
+        let start_time = Instant::now();
+        if !self.is_marking() {
+            return;
+        }
+        while !self.mark_increment(usize::MAX) {}
+        let marking = self.marking.take().unwrap_or_default();
+        let marked: Vec<bool> = marking
+            .colors
+            .iter()
+            .map(|&color| color == GcColor::Black)
+            .collect();
+        self.sweep(&marked, start_time, 0);
+        self.finish_major_collection();
+    }
+
+    /// Record that `value` was stored into the object at `source`.
+    ///
+    /// While marking, a black object is never scanned again, so a white
+    /// object stored into it would be reclaimed although reachable; the
+    /// barrier shades such a target gray instead. A pointer from an old
+    /// object into the nursery is remembered for the next minor collection.
+    pub fn write_barrier(&mut self, source: GcPtr, value: &Value) {
+        let (Value::GcPtr(target) | Value::Thunk(target)) = value else {
+            return;
+        };
+        if source.0 < self.old_len && target.0 >= self.old_len {
+            self.remembered.insert(source.0);
+        }
+        if let Some(marking) = &mut self.marking {
+            if marking.colors.get(source.0) == Some(&GcColor::Black) {
+                marking.shade(target.0);
+            }
+        }
+    }
+
+    /// Mark and sweep the whole heap, abandoning any incremental marking
+    /// cycle in progress
     pub fn collect(&mut self) {
         let start_time = Instant::now();
+        self.marking = None;
         let mut marked = vec![false; self.heap.len()];
 
         // Mark phase
         self.mark_roots(&mut marked);
+        self.sweep(&marked, start_time, 0);
+        self.finish_major_collection();
+    }
+
+    /// Mark and sweep only the nursery and promote its survivors to the old
+    /// generation, abandoning any incremental marking cycle in progress.
+    ///
+    /// Old objects are kept without being scanned; nursery objects survive
+    /// if they are reachable from a root or from a remembered old object.
+    pub fn minor_collect(&mut self) {
+        let start_time = Instant::now();
+        self.marking = None;
+        let old_len = self.old_len.min(self.heap.len());
+        let mut marked = vec![false; self.heap.len()];
+        marked[..old_len].fill(true);
 
-        // Sweep phase
+        let mut worklist: Vec<usize> = self.roots.iter().map(|root| root.ptr.0).collect();
+        for source in &self.remembered {
+            if let Some(object) = self.heap.get(*source) {
+                worklist.extend(object.outgoing_pointers().iter().map(|ptr| ptr.0));
+            }
+        }
+        while let Some(index) = worklist.pop() {
+            // Old objects are already marked, so the walk stays in the nursery
+            if index >= marked.len() || marked[index] {
+                continue;
+            }
+            marked[index] = true;
+            worklist.extend(self.heap[index].outgoing_pointers().iter().map(|ptr| ptr.0));
+        }
+
+        self.sweep(&marked, start_time, old_len);
+        self.gc_stats.minor_collections += 1;
+    }
+
+    fn finish_major_collection(&mut self) {
+        self.gc_stats.major_collections += 1;
+        self.old_len_after_major = self.old_len;
+    }
+
+    /// Drop every unmarked object, compact the heap, promote the surviving
+    /// nursery objects and update the stats of a collection that started at
+    /// `start_time` and examined the objects from index `first` on
+    fn sweep(&mut self, marked: &[bool], start_time: Instant, first: usize) {
         let mut new_heap = Vec::new();
         let mut new_index_map = Vec::new();
 
@@ -89,6 +487,13 @@ impl GarbageCollector {
                 root.ptr = GcPtr(new_index);
             }
         }
+        for object in &mut new_heap {
+            object.relocate(&new_index_map);
+        }
+        let promoted = marked[self.old_len.min(marked.len())..]
+            .iter()
+            .filter(|&&kept| kept)
+            .count();
 
         // Update stats
         let collected = self.heap.len() - new_heap.len();
@@ -103,8 +508,31 @@ impl GarbageCollector {
             self.gc_stats.max_pause_time_millis = duration.as_millis() as u64;
         }
 
+        // Record per-cycle distributions
+        let examined = self.heap.len() - first;
+        let survivor_ratio = if examined == 0 {
+            1.0
+        } else {
+            (new_heap.len() - first) as f64 / examined as f64
+        };
+        self.gc_stats.objects_promoted += promoted as u32;
+        self.gc_stats
+            .bytes_allocated
+            .record(self.bytes_allocated_since_last_gc as f64);
+        self.gc_stats
+            .allocations
+            .record(self.allocations_since_last_gc as f64);
+        self.gc_stats
+            .pause_micros
+            .record(duration.as_secs_f64() * 1_000_000.0);
+        self.gc_stats.survivor_ratio.record(survivor_ratio);
+
         self.allocations_since_last_gc = 0;
+        self.bytes_allocated_since_last_gc = 0;
         self.heap = new_heap;
+        self.old_len = self.heap.len();
+        self.remembered.clear();
+        self.record_relocation(new_index_map);
     }
 
     fn mark_roots(&self, marked: &mut [bool]) {
@@ -121,41 +549,228 @@ impl GarbageCollector {
         // Process worklist
         while let Some(index) = worklist.pop() {
             if let Some(object) = self.heap.get(index) {
-                match object {
-                    HeapObject::Closure(closure) => {
-                        // Mark closure environment
-                        for value in closure.environment.values() {
-                            if let Value::GcPtr(ptr) = value {
-                                if !marked[ptr.0] {
-                                    marked[ptr.0] = true;
-                                    worklist.push(ptr.0);
-                                }
-                            }
-                        }
+                // Mark closure environments and array elements
+                for ptr in object.outgoing_pointers() {
+                    if !marked[ptr.0] {
+                        marked[ptr.0] = true;
+                        worklist.push(ptr.0);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Find reference cycles among heap objects.
+///
+/// Returns every strongly-connected component of the object graph that spans
+/// more than one object, i.e. cycles that mark-sweep keeps alive as a unit
+/// once any member is reachable. Components and their members are sorted by
+/// pointer for deterministic output. Dangling pointers are ignored.
+pub fn find_cycles(gc: &GarbageCollector) -> Vec<Vec<GcPtr>> {
+    let edges: Vec<Vec<usize>> = gc
+        .heap
+        .iter()
+        .map(|object| {
+            object
+                .outgoing_pointers()
+                .into_iter()
+                .map(|ptr| ptr.0)
+                .filter(|&target| target < gc.heap.len())
+                .collect()
+        })
+        .collect();
+
+    // Iterative Tarjan's algorithm so deep object chains cannot overflow the stack
+    let node_count = edges.len();
+    let mut index_of: Vec<Option<usize>> = vec![None; node_count];
+    let mut lowlink = vec![0; node_count];
+    let mut on_stack = vec![false; node_count];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut cycles = Vec::new();
+
+    for start in 0..node_count {
+        if index_of[start].is_some() {
+            continue;
+        }
+
+        // Each frame is (node, position of the next edge to visit)
+        let mut call_stack = vec![(start, 0)];
+        index_of[start] = Some(next_index);
+        lowlink[start] = next_index;
+        next_index += 1;
+        stack.push(start);
+        on_stack[start] = true;
+
+        while let Some(&mut (node, ref mut edge_pos)) = call_stack.last_mut() {
+            if let Some(&target) = edges[node].get(*edge_pos) {
+                *edge_pos += 1;
+                match index_of[target] {
+                    None => {
+                        index_of[target] = Some(next_index);
+                        lowlink[target] = next_index;
+                        next_index += 1;
+                        stack.push(target);
+                        on_stack[target] = true;
+                        call_stack.push((target, 0));
+                    }
+                    Some(target_index) if on_stack[target] => {
+                        lowlink[node] = lowlink[node].min(target_index);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            // All edges visited: close the component if node is its root
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[node]);
+            }
+            if Some(lowlink[node]) == index_of[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack[member] = false;
+                    component.push(GcPtr(member));
+                    if member == node {
+                        break;
                     }
-                    HeapObject::Array(array) => {
-                        // Mark array elements
-                        for value in array.elements() {
-                            if let Value::GcPtr(ptr) = value {
-                                if !marked[ptr.0] {
-                                    marked[ptr.0] = true;
-                                    worklist.push(ptr.0);
-                                }
-                            }
-                        }
-                    } // Other heap object types...
+                }
+                if component.len() > 1 {
+                    component.sort_by_key(|ptr| ptr.0);
+                    cycles.push(component);
                 }
             }
         }
     }
+
+    cycles.sort_by_key(|component| component[0].0);
+    cycles
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GcStats {
+    /// Collections of any kind
     pub collections: u32,
+    /// Collections of the nursery alone
+    #[serde(default)]
+    pub minor_collections: u32,
+    /// Collections of the whole heap
+    #[serde(default)]
+    pub major_collections: u32,
+    /// Objects moved from the nursery into the old generation
+    #[serde(default)]
+    pub objects_promoted: u32,
     pub objects_collected: u32,
     pub total_time_millis: u64,
     pub max_pause_time_millis: u64,
+    /// Bytes allocated between consecutive collections
+    #[serde(default)]
+    pub bytes_allocated: GcHistogram,
+    /// Allocation requests between consecutive collections
+    #[serde(default)]
+    pub allocations: GcHistogram,
+    /// Pause duration of each collection, in microseconds
+    #[serde(default)]
+    pub pause_micros: GcHistogram,
+    /// Fraction of heap objects surviving each collection
+    #[serde(default)]
+    pub survivor_ratio: GcHistogram,
+}
+
+impl GcStats {
+    /// Mean pause over the recorded collections
+    pub fn average_pause(&self) -> Duration {
+        Duration::from_secs_f64(self.pause_micros.mean() / 1_000_000.0)
+    }
+
+    /// Mean bytes per allocation over the recorded cycles
+    ///
+    /// The collector does not see VM instructions, so a step here is one
+    /// allocation request, not one `VmState::step`: this is the mean object
+    /// footprint, and it is unaffected by instructions that allocate nothing.
+    pub fn allocation_rate_per_step(&self) -> f64 {
+        let allocations = self.allocations.sum();
+        if allocations == 0.0 {
+            0.0
+        } else {
+            self.bytes_allocated.sum() / allocations
+        }
+    }
+
+    /// Mean fraction of objects surviving a collection
+    pub fn average_survivor_ratio(&self) -> f64 {
+        self.survivor_ratio.mean()
+    }
+}
+
+/// Ring buffer of the most recent samples of a GC metric
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GcHistogram {
+    capacity: usize,
+    samples: VecDeque<f64>,
+}
+
+impl GcHistogram {
+    /// Number of samples kept when no capacity is given
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    /// Create a histogram that keeps the last `capacity` samples
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            samples: VecDeque::with_capacity(capacity.max(1)),
+        }
+    }
+
+    /// Record a sample, evicting the oldest one when full
+    pub fn record(&mut self, sample: f64) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Retained samples, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = f64> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.samples.iter().sum()
+    }
+
+    /// Mean of the retained samples, or 0.0 when empty
+    pub fn mean(&self) -> f64 {
+        if self.samples.is_empty() {
+            0.0
+        } else {
+            self.sum() / self.samples.len() as f64
+        }
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.samples.iter().copied().reduce(f64::max)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.samples.iter().copied().reduce(f64::min)
+    }
+}
+
+impl Default for GcHistogram {
+    fn default() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -165,4 +780,102 @@ pub struct GcPtr(pub usize);
 pub struct GcRoot {
     pub ptr: GcPtr,
     pub description: String,
+    /// Root scope that registered this root, if any
+    #[serde(default)]
+    pub scope: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn array_of(ptrs: &[usize]) -> HeapObject {
+        HeapObject::Array(Array {
+            elements: ptrs.iter().map(|&p| Value::GcPtr(GcPtr(p))).collect(),
+        })
+    }
+
+    #[test]
+    fn test_find_cycles_reports_two_object_cycle() {
+        let mut gc = GarbageCollector::new(16, 100);
+        // A -> B -> A, plus C -> A outside the cycle
+        gc.allocate(array_of(&[1]));
+        gc.allocate(array_of(&[0]));
+        gc.allocate(array_of(&[0]));
+
+        assert_eq!(find_cycles(&gc), vec![vec![GcPtr(0), GcPtr(1)]]);
+    }
+
+    #[test]
+    fn test_find_cycles_ignores_acyclic_heap_and_self_loops() {
+        let mut gc = GarbageCollector::new(16, 100);
+        gc.allocate(array_of(&[1, 2]));
+        gc.allocate(array_of(&[2]));
+        gc.allocate(array_of(&[2])); // self-loop only
+        gc.allocate(HeapObject::Closure(Closure {
+            code_ptr: 0,
+            environment: HashMap::from([(0, Value::GcPtr(GcPtr(0)))]),
+        }));
+
+        assert!(find_cycles(&gc).is_empty());
+    }
+
+    #[test]
+    fn test_find_cycles_through_closure_environment() {
+        let mut gc = GarbageCollector::new(16, 100);
+        gc.allocate(array_of(&[1]));
+        gc.allocate(HeapObject::Closure(Closure {
+            code_ptr: 0,
+            environment: HashMap::from([(0, Value::GcPtr(GcPtr(2)))]),
+        }));
+        gc.allocate(array_of(&[0, 7])); // dangling pointer 7 is ignored
+
+        assert_eq!(find_cycles(&gc), vec![vec![GcPtr(0), GcPtr(1), GcPtr(2)]]);
+    }
+
+    #[test]
+    fn test_stats_record_histograms_each_cycle() {
+        let mut gc = GarbageCollector::new(64, 10);
+        // Keep one object alive across every cycle, the rest is garbage
+        gc.allocate(array_of(&[]));
+        gc.roots.push(GcRoot {
+            ptr: GcPtr(0),
+            description: "pinned".to_string(),
+            scope: None,
+        });
+        for i in 1..50 {
+            gc.allocate(array_of(&[i % 3]));
+        }
+
+        let stats = &gc.gc_stats;
+        assert_eq!(stats.collections, 5);
+        for histogram in [
+            &stats.bytes_allocated,
+            &stats.allocations,
+            &stats.pause_micros,
+            &stats.survivor_ratio,
+        ] {
+            assert_eq!(histogram.len(), 5);
+        }
+        assert!(stats.allocations.samples().all(|n| n == 10.0));
+        assert!(stats.bytes_allocated.min().unwrap() > 0.0);
+        assert!(stats
+            .survivor_ratio
+            .samples()
+            .all(|ratio| ratio > 0.0 && ratio < 1.0));
+        assert!(stats.allocation_rate_per_step() > 0.0);
+        assert!(stats.average_pause() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_histogram_keeps_most_recent_samples() {
+        let mut histogram = GcHistogram::with_capacity(3);
+        for sample in [1.0, 2.0, 3.0, 4.0, 5.0] {
+            histogram.record(sample);
+        }
+
+        assert_eq!(histogram.samples().collect::<Vec<_>>(), vec![3.0, 4.0, 5.0]);
+        assert_eq!(histogram.mean(), 4.0);
+        assert_eq!(histogram.max(), Some(5.0));
+    }
 }
@@ -6,10 +6,14 @@
 //! # Extracted from
 //! - `vm/state.rs` (lines 714-740, GC integration methods)
 
-use crate::memory::arena::{ObjectArena, ObjectHeader};
+use crate::memory::arena::{
+    DefragmentationStats, GarbageCollectionError, ObjectArena, ObjectHeader, RelocationMap,
+};
 use crate::types::{HeapPtr, Value};
 use crate::vm::error::VmError;
-use crate::vm::gc::{GarbageCollector, GcPtr, GcRoot, GcStats, HeapObject};
+use crate::vm::gc::{
+    relocate_gc_value, GarbageCollector, GcMode, GcPtr, GcRoot, GcStats, HeapObject,
+};
 
 /// GC integration layer for VmState.
 ///
@@ -43,11 +47,17 @@ impl GcIntegration {
                 stack_trace: Vec::new(),
                 execution_history: Vec::new(),
                 timestamp: 0,
+                source_location: None,
             };
             return Err(VmError::GcDisabled);
         }
 
-        let ptr = state.gc.allocate(object);
+        let ptr = match state.gc_mode {
+            GcMode::StopTheWorld => state.gc.allocate(object),
+            GcMode::Incremental { budget } => state.gc.allocate_incremental(object, budget),
+            GcMode::Generational { nursery } => state.gc.allocate_generational(object, nursery),
+        };
+        Self::relocate_gc_values(state);
         Ok(Value::GcPtr(ptr))
     }
 
@@ -58,19 +68,17 @@ impl GcIntegration {
     /// * `ptr` - The GC pointer to root
     /// * `description` - Description of the root for debugging
     pub fn add_gc_root(state: &mut crate::vm::state::VmState, ptr: GcPtr, description: &str) {
-        state.gc.roots.push(GcRoot {
-            ptr,
-            description: description.to_string(),
-        });
+        state.add_gc_root(ptr, description);
     }
 
-    /// Remove a GC root.
+    /// Remove one GC root added by `add_gc_root`; see
+    /// [`VmState::remove_gc_root`](crate::vm::state::VmState::remove_gc_root).
     ///
     /// # Arguments
     /// * `state` - Mutable reference to the VM state
     /// * `ptr` - The GC pointer to unroot
     pub fn remove_gc_root(state: &mut crate::vm::state::VmState, ptr: GcPtr) {
-        state.gc.roots.retain(|root| root.ptr != ptr);
+        state.remove_gc_root(ptr);
     }
 
     /// Get GC statistics.
@@ -141,6 +149,137 @@ impl GcIntegration {
     /// * `state` - Mutable reference to the VM state
     pub fn collect_garbage(state: &mut crate::vm::state::VmState) {
         state.gc.collect();
+        Self::relocate_gc_values(state);
+    }
+
+    /// Rewrite the VM's references into the GC heap after a collection
+    /// compacted it.
+    ///
+    /// Covers `GcPtr` and `Thunk` values on the stack, in locals, captured
+    /// variables and the constant pool, including those nested in error
+    /// payloads, and the thunk each call frame is forcing. Must run after
+    /// every allocation or collection in the GC heap; does nothing if the
+    /// heap was not compacted since the last call.
+    ///
+    /// # Arguments
+    /// * `state` - Mutable reference to the VM state
+    pub fn relocate_gc_values(state: &mut crate::vm::state::VmState) {
+        let Some(relocation) = state.gc.take_relocation() else {
+            return;
+        };
+        for value in Self::vm_values_mut(state) {
+            relocate_gc_value(value, &relocation);
+        }
+        for frame in &mut state.call_stack {
+            if let Some(ptr) = &mut frame.forcing {
+                if let Some(Some(new_index)) = relocation.get(ptr.0) {
+                    ptr.0 = *new_index;
+                }
+            }
+        }
+    }
+
+    /// Collect unreachable objects in the arena heap.
+    ///
+    /// Every `Pair`, `Closure` and `Vector` value on the stack, in locals,
+    /// captured variables, the constant pool and GC heap objects such as
+    /// pending thunks is a root, as is one nested in an error record's
+    /// payload, and is rewritten in place if its object moved during
+    /// compaction.
+    ///
+    /// # Arguments
+    /// * `state` - Mutable reference to the VM state
+    ///
+    /// # Returns
+    /// Number of bytes reclaimed
+    pub fn collect_heap(
+        state: &mut crate::vm::state::VmState,
+    ) -> Result<u32, GarbageCollectionError> {
+        let before = state.memory.next_free();
+        let mut roots = Vec::new();
+        for value in Self::heap_values_mut(state) {
+            collect_heap_ptrs(value, &mut roots);
+        }
+
+        let relocations = state.memory.collect_garbage_relocating(&roots)?;
+        Self::relocate_heap_values(state, &relocations);
+        Self::auto_defragment(state);
+
+        Ok(before - state.memory.next_free())
+    }
+
+    /// Defragment the arena if its fragmentation ratio exceeds the VM's
+    /// `defrag_threshold`.
+    ///
+    /// Runs after every arena collection, when the marks are current. A
+    /// collection already compacts the heap, so what is left is alignment
+    /// padding, which defragmentation reclaims by reordering objects.
+    ///
+    /// # Returns
+    /// Statistics of the defragmentation, or `None` if none was needed
+    pub fn auto_defragment(state: &mut crate::vm::state::VmState) -> Option<DefragmentationStats> {
+        let threshold = state.defrag_threshold?;
+        if state.memory.fragmentation_ratio() <= threshold {
+            return None;
+        }
+        let (stats, relocations) = state.memory.defragment_relocating().ok()?;
+        Self::relocate_heap_values(state, &relocations);
+        Some(stats)
+    }
+
+    /// Rewrite arena pointers held by the VM after objects moved.
+    ///
+    /// # Arguments
+    /// * `state` - Mutable reference to the VM state
+    /// * `relocations` - Old and new address of each moved object
+    pub fn relocate_heap_values(
+        state: &mut crate::vm::state::VmState,
+        relocations: &RelocationMap,
+    ) {
+        if relocations.is_empty() {
+            return;
+        }
+        for value in Self::heap_values_mut(state) {
+            relocate_value(value, relocations);
+        }
+    }
+
+    /// Every VM-held value that may point into the arena, including the
+    /// values held by GC heap objects
+    fn heap_values_mut(state: &mut crate::vm::state::VmState) -> impl Iterator<Item = &mut Value> {
+        let objects = state.gc.heap.iter_mut().flat_map(HeapObject::values_mut);
+        let frames = state.call_stack.iter_mut().flat_map(|frame| {
+            frame
+                .locals
+                .iter_mut()
+                .chain(frame.closed_over.values_mut())
+        });
+        state
+            .stack
+            .iter_mut()
+            .chain(state.top_level_locals.iter_mut())
+            .chain(state.constant_pool.iter_mut())
+            .chain(frames)
+            .chain(objects)
+    }
+
+    /// Every value the VM holds outside the heaps: the stack, locals,
+    /// captured variables and the constant pool
+    pub(crate) fn vm_values_mut(
+        state: &mut crate::vm::state::VmState,
+    ) -> impl Iterator<Item = &mut Value> {
+        let frames = state.call_stack.iter_mut().flat_map(|frame| {
+            frame
+                .locals
+                .iter_mut()
+                .chain(frame.closed_over.values_mut())
+        });
+        state
+            .stack
+            .iter_mut()
+            .chain(state.top_level_locals.iter_mut())
+            .chain(state.constant_pool.iter_mut())
+            .chain(frames)
     }
 
     /// Get heap usage statistics.
@@ -180,6 +319,46 @@ impl GcIntegration {
     }
 }
 
+/// Push the arena pointers `value` holds, including those nested in error
+/// payloads, onto `ptrs`
+fn collect_heap_ptrs(value: &Value, ptrs: &mut Vec<HeapPtr>) {
+    let mut value = value;
+    loop {
+        match value {
+            Value::Pair(ptr) | Value::Closure(ptr) | Value::Vector(ptr) | Value::BigInt(ptr) => {
+                ptrs.push(*ptr);
+            }
+            Value::ErrorRecord { payload, .. } => {
+                value = payload;
+                continue;
+            }
+            _ => {}
+        }
+        return;
+    }
+}
+
+/// Point the arena pointers `value` holds, including those nested in error
+/// payloads, at their objects' new addresses
+fn relocate_value(value: &mut Value, relocations: &RelocationMap) {
+    let mut value = value;
+    loop {
+        match value {
+            Value::Pair(ptr) | Value::Closure(ptr) | Value::Vector(ptr) | Value::BigInt(ptr) => {
+                if let Some(new_ptr) = relocations.get(ptr) {
+                    *ptr = *new_ptr;
+                }
+            }
+            Value::ErrorRecord { payload, .. } => {
+                value = payload;
+                continue;
+            }
+            _ => {}
+        }
+        return;
+    }
+}
+
 /// Memory analysis for VM heap.
 ///
 /// Provides detailed memory usage analysis for the VM.
@@ -212,6 +391,16 @@ impl<'a> MemoryAnalysis<'a> {
         }
     }
 
+    /// Get the bytes of heap usage spent on alignment padding.
+    pub fn padding_bytes(&self) -> usize {
+        self.state.memory.padding_bytes() as usize
+    }
+
+    /// Get the arena fragmentation ratio, counting alignment padding as wasted space.
+    pub fn fragmentation_ratio(&self) -> f32 {
+        self.state.memory.fragmentation_ratio()
+    }
+
     /// Get the number of heap objects.
     pub fn object_count(&self) -> usize {
         self.state.gc.heap.len()
@@ -233,6 +422,71 @@ impl<'a> MemoryAnalysis<'a> {
     }
 }
 
+/// Guard over a set of GC roots that are all removed when it is dropped.
+///
+/// Obtained from [`VmState::push_root_scope`](crate::vm::state::VmState::push_root_scope).
+/// Hosts holding many references root them through the scope instead of
+/// pairing every `add_gc_root` with a `remove_gc_root`, so an early return
+/// or panic cannot leak roots. The scope dereferences to the VM, so it can
+/// run collections or open nested scopes while it is alive.
+pub struct GcRootScope<'a> {
+    state: &'a mut crate::vm::state::VmState,
+    id: u64,
+}
+
+impl<'a> GcRootScope<'a> {
+    pub(crate) fn new(state: &'a mut crate::vm::state::VmState) -> Self {
+        let id = state.gc.next_root_scope;
+        state.gc.next_root_scope += 1;
+        Self { state, id }
+    }
+
+    /// Root `ptr` until the scope is dropped.
+    pub fn add(&mut self, ptr: GcPtr, description: &str) {
+        self.state.gc.roots.push(GcRoot {
+            ptr,
+            description: description.to_string(),
+            scope: Some(self.id),
+        });
+    }
+
+    /// Number of roots this scope currently holds.
+    pub fn len(&self) -> usize {
+        self.state
+            .gc
+            .roots
+            .iter()
+            .filter(|root| root.scope == Some(self.id))
+            .count()
+    }
+
+    /// Whether this scope holds no roots.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl std::ops::Deref for GcRootScope<'_> {
+    type Target = crate::vm::state::VmState;
+
+    fn deref(&self) -> &Self::Target {
+        self.state
+    }
+}
+
+impl std::ops::DerefMut for GcRootScope<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.state
+    }
+}
+
+impl Drop for GcRootScope<'_> {
+    fn drop(&mut self) {
+        let id = self.id;
+        self.state.gc.roots.retain(|root| root.scope != Some(id));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
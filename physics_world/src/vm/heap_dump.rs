@@ -0,0 +1,174 @@
+/// Heap dumps for offline analysis
+///
+/// `VmState::dump_heap` writes every object in the arena as plain text, one
+/// line per object, and `analyze` reads such a dump back into an object graph.
+/// Dumping only reads the heap; it neither marks nor moves anything.
+///
+/// # Format
+///
+/// ```text
+/// heap-dump 1 <capacity> <used>
+/// <address> <tag> <size> <marked> [<reference> ...]
+/// ```
+///
+/// The first line holds the format version, the arena capacity and the bytes
+/// allocated so far. Every following line is an object: its address, tag,
+/// data size in bytes, mark bit (`0` or `1`) and the addresses it points to.
+/// Objects appear in address order; alignment padding is left out. All
+/// numbers are decimal.
+use crate::vm::state::VmState;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+/// Version written on the first line of every dump
+pub const HEAP_DUMP_VERSION: u32 = 1;
+
+/// An object read back from a heap dump
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DumpedObject {
+    pub address: u32,
+    pub tag: u8,
+    pub size: u32,
+    pub marked: bool,
+    pub references: Vec<u32>,
+}
+
+/// Object graph rebuilt from a heap dump
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeapGraph {
+    pub capacity: u32,
+    pub used: u32,
+    pub objects: BTreeMap<u32, DumpedObject>,
+}
+
+impl HeapGraph {
+    pub fn object_count(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn object(&self, address: u32) -> Option<&DumpedObject> {
+        self.objects.get(&address)
+    }
+
+    /// Every `(from, to)` pointer edge, in address order
+    pub fn edges(&self) -> Vec<(u32, u32)> {
+        self.objects
+            .values()
+            .flat_map(|object| {
+                object
+                    .references
+                    .iter()
+                    .map(move |&target| (object.address, target))
+            })
+            .collect()
+    }
+
+    /// Addresses of the objects pointing at `address`
+    pub fn referrers(&self, address: u32) -> Vec<u32> {
+        self.objects
+            .values()
+            .filter(|object| object.references.contains(&address))
+            .map(|object| object.address)
+            .collect()
+    }
+
+    /// Object count and total data bytes per tag
+    pub fn usage_by_tag(&self) -> BTreeMap<u8, (usize, u64)> {
+        let mut usage = BTreeMap::new();
+        for object in self.objects.values() {
+            let entry = usage.entry(object.tag).or_insert((0, 0));
+            entry.0 += 1;
+            entry.1 += object.size as u64;
+        }
+        usage
+    }
+}
+
+impl VmState {
+    /// Write every heap object with its tag, size, mark bit and outgoing
+    /// pointers to `w`, in the format described in [`crate::vm::heap_dump`]
+    pub fn dump_heap(&self, mut w: impl Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "heap-dump {} {} {}",
+            HEAP_DUMP_VERSION,
+            self.memory.capacity(),
+            self.memory.next_free()
+        )?;
+        for ptr in self.memory.object_pointers() {
+            // Pointers come from walking the arena, so each heads an object
+            let header = unsafe { self.memory.get_header(ptr) };
+            write!(
+                w,
+                "{} {} {} {}",
+                ptr.get(),
+                header.tag,
+                header.size,
+                u8::from(header.marked)
+            )?;
+            for target in unsafe { self.memory.outgoing_pointers(ptr) } {
+                write!(w, " {}", target.get())?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild the object graph from a dump written by `VmState::dump_heap`
+pub fn analyze(reader: impl BufRead) -> io::Result<HeapGraph> {
+    let mut lines = reader.lines();
+    let header = lines.next().ok_or_else(|| invalid("empty heap dump"))??;
+    let fields: Vec<&str> = header.split_whitespace().collect();
+    let [magic, version, capacity, used] = fields[..] else {
+        return Err(invalid("malformed heap dump header"));
+    };
+    if magic != "heap-dump" || parse::<u32>(version)? != HEAP_DUMP_VERSION {
+        return Err(invalid("not a version 1 heap dump"));
+    }
+
+    let mut graph = HeapGraph {
+        capacity: parse(capacity)?,
+        used: parse(used)?,
+        objects: BTreeMap::new(),
+    };
+    for line in lines {
+        let line = line?;
+        let mut fields = line.split_whitespace();
+        let mut next = || {
+            fields
+                .next()
+                .ok_or_else(|| invalid("truncated object line"))
+        };
+        let address = parse(next()?)?;
+        let tag = parse(next()?)?;
+        let size = parse(next()?)?;
+        let marked = match next()? {
+            "0" => false,
+            "1" => true,
+            other => return Err(invalid(&format!("invalid mark bit {other}"))),
+        };
+        let references = fields.map(parse).collect::<io::Result<_>>()?;
+        graph.objects.insert(
+            address,
+            DumpedObject {
+                address,
+                tag,
+                size,
+                marked,
+                references,
+            },
+        );
+    }
+    Ok(graph)
+}
+
+fn parse<T: std::str::FromStr>(field: &str) -> io::Result<T> {
+    field
+        .parse()
+        .map_err(|_| invalid(&format!("invalid number {field}")))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
@@ -0,0 +1,18 @@
+//! Data that host calls deliver to the VM.
+//!
+//! `NetworkReceive` and `PersistRead` hand the program whatever bytes the
+//! embedder has for them. An embedder installs a [`HostInput`] on a
+//! [`VmState`](crate::vm::VmState) to supply those bytes; they reach the
+//! program through [`Value::from_host_bytes`](crate::types::Value::from_host_bytes),
+//! so data that is not valid UTF-8 arrives as `Bytes` rather than panicking.
+
+use crate::types::HostFunction;
+
+/// Supplies the bytes returned by receiving host calls.
+///
+/// Implementations are shared between clones of a VM, so they take `&self`
+/// and use interior mutability to consume queued data.
+pub trait HostInput: Send + Sync {
+    /// Bytes for a call to `function`, or `None` when nothing is available
+    fn receive(&self, actor_id: u32, function: HostFunction) -> Option<Vec<u8>>;
+}
@@ -1,23 +1,52 @@
 pub mod call_state;
+pub mod capability_observer;
+pub mod closure_equivalence;
 pub mod closure_fix;
+pub mod cost_model;
+pub mod coverage;
 pub mod debug;
 pub mod error;
 pub mod execution;
+pub mod function_names;
+pub mod fuzz;
 pub mod gc;
 pub mod gc_integration;
+pub mod heap_dump;
+pub mod host_input;
 pub mod opcodes;
 pub mod performance;
+pub mod source_map;
 pub mod state;
+pub mod symbol_table;
 
 pub use call_state::{
     CallFrame, CallStack, Closure, EnvBinding, RecursiveEnvironment, Symbol,
 };
-pub use debug::{DebugEvent, DebugEventType, Debugger, Watchpoint, WatchpointTrigger};
+pub use capability_observer::CapabilityObserver;
+pub use closure_equivalence::ClosureEquivalence;
+pub use cost_model::CostModel;
+pub use coverage::{CoverageCollector, CoverageReport};
+pub use debug::{
+    DebugEvent, DebugEventType, Debugger, WatchScope, Watchpoint, WatchpointTrigger,
+};
 pub use error::{ErrorContext, RecoveryAction, VmError};
 pub use execution::ExecutionEngine;
-pub use gc::{GarbageCollector, GcPtr, GcRoot, GcStats, HeapObject};
-pub use gc_integration::{GcIntegration, MemoryAnalysis};
+pub use function_names::FunctionNames;
+pub use fuzz::fuzz_run;
+pub use gc::{
+    find_cycles, GarbageCollector, GcColor, GcHistogram, GcMode, GcPtr, GcRoot, GcStats, HeapObject,
+};
+pub use gc_integration::{GcIntegration, GcRootScope, MemoryAnalysis};
+pub use heap_dump::{DumpedObject, HeapGraph};
+pub use host_input::HostInput;
+pub use opcodes::arithmetic::IntOverflowMode;
+pub use opcodes::comparison::FloatCmpPolicy;
 pub use performance::{
     PerformanceAnalysis, PerformanceMetrics, PerformanceMonitor, PerformanceSample,
 };
-pub use state::{InstructionResult, VmState, VmDebugger};
+pub use source_map::{SourceLocation, SourceMap};
+pub use state::{
+    CapabilityDeadline, CapabilityExpiry, CapabilityScope, ClosureInfo, ErrorHandler, InstructionResult,
+    OnOutOfMemory, PairConstant, VmDebugger, VmState,
+};
+pub use symbol_table::SymbolTable;
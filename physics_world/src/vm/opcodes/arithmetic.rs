@@ -1,62 +1,195 @@
-/// Arithmetic opcode handlers - Add, Sub, Mul, Div, Mod
-use crate::types::Value;
+/// Arithmetic opcode handlers - Add, Sub, Mul, Div, Mod, BitAnd, BitOr, BitXor,
+/// Shl, Shr, FAdd, FSub, FMul, FDiv
+///
+/// Integer opcodes take `Int` operands and float opcodes take `Float`
+/// operands; neither coerces, so mixing an `Int` with a `Float` is always a
+/// `TypeMismatch`. `Add`, `Sub`, `Mul`, `Div` and `Mod` also take `BigInt`
+/// operands, mixed freely with `Int`s, and compute in arbitrary precision,
+/// narrowing the result back to an `Int` when it fits; the bitwise opcodes
+/// do not. Division by zero is a `DivisionByZero` error for integers and
+/// floats alike, rather than an IEEE infinity or NaN.
+use crate::memory::arena::TAG_BIGINT;
+use crate::types::{BigInt, Value};
 use crate::vm::state::VmError;
 use crate::vm::state::VmState;
+use serde::{Deserialize, Serialize};
 
-/// Handles Add opcode
-pub fn handle_add(vm: &mut VmState) -> Result<(), VmError> {
-    let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
-    let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+/// How integer `Add`, `Sub` and `Mul` behave when the result does not fit in an `i64`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum IntOverflowMode {
+    /// Raise `ArithmeticOverflow`
+    #[default]
+    Checked,
+    /// Wrap around in two's complement
+    Wrapping,
+    /// Clamp to `i64::MIN` or `i64::MAX`
+    Saturating,
+    /// Promote the result to an arbitrary-precision `Value::BigInt`
+    BigIntPromotion,
+}
 
-    match (a, b) {
-        (Value::Int(x), Value::Int(y)) => {
-            let result = x.checked_add(y).ok_or(VmError::ArithmeticOverflow)?;
-            vm.stack.push(Value::Int(result));
+impl IntOverflowMode {
+    /// Apply an integer operation under this overflow mode
+    fn apply(
+        self,
+        x: i64,
+        y: i64,
+        checked: fn(i64, i64) -> Option<i64>,
+        wrapping: fn(i64, i64) -> i64,
+        saturating: fn(i64, i64) -> i64,
+    ) -> Result<i64, VmError> {
+        match self {
+            IntOverflowMode::Checked | IntOverflowMode::BigIntPromotion => {
+                checked(x, y).ok_or(VmError::ArithmeticOverflow)
+            }
+            IntOverflowMode::Wrapping => Ok(wrapping(x, y)),
+            IntOverflowMode::Saturating => Ok(saturating(x, y)),
         }
-        _ => return Err(VmError::TypeMismatch),
     }
-    Ok(())
+}
+
+/// Handles Add opcode
+pub fn handle_add(vm: &mut VmState) -> Result<(), VmError> {
+    integer_binary_op(
+        vm,
+        i64::checked_add,
+        i64::wrapping_add,
+        i64::saturating_add,
+        BigInt::add,
+    )
 }
 
 /// Handles Sub opcode
 pub fn handle_sub(vm: &mut VmState) -> Result<(), VmError> {
+    integer_binary_op(
+        vm,
+        i64::checked_sub,
+        i64::wrapping_sub,
+        i64::saturating_sub,
+        BigInt::sub,
+    )
+}
+
+/// Handles Mul opcode
+pub fn handle_mul(vm: &mut VmState) -> Result<(), VmError> {
+    integer_binary_op(
+        vm,
+        i64::checked_mul,
+        i64::wrapping_mul,
+        i64::saturating_mul,
+        BigInt::mul,
+    )
+}
+
+/// Pop two integers and push the result of an operation on them.
+///
+/// Two `Int`s follow the VM's overflow mode. Once either operand is a
+/// `BigInt` the operation is carried out in arbitrary precision whatever the
+/// mode, since the value could only have come from an earlier promotion.
+fn integer_binary_op(
+    vm: &mut VmState,
+    checked: fn(i64, i64) -> Option<i64>,
+    wrapping: fn(i64, i64) -> i64,
+    saturating: fn(i64, i64) -> i64,
+    big: fn(&BigInt, &BigInt) -> BigInt,
+) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
 
-    match (a, b) {
-        (Value::Int(x), Value::Int(y)) => {
-            let result = x.checked_sub(y).ok_or(VmError::ArithmeticOverflow)?;
-            vm.stack.push(Value::Int(result));
+    let result = match (&a, &b) {
+        (Value::Int(x), Value::Int(y)) => match vm.int_overflow_mode {
+            IntOverflowMode::BigIntPromotion => match checked(*x, *y) {
+                Some(result) => Value::Int(result),
+                None => integer_value(vm, &big(&BigInt::from(*x), &BigInt::from(*y)))?,
+            },
+            mode => Value::Int(mode.apply(*x, *y, checked, wrapping, saturating)?),
+        },
+        _ => {
+            let x = read_integer(vm, &a)?;
+            let y = read_integer(vm, &b)?;
+            integer_value(vm, &big(&x, &y))?
         }
-        _ => return Err(VmError::TypeMismatch),
-    }
+    };
+    vm.stack.push(result);
     Ok(())
 }
 
-/// Handles Mul opcode
-pub fn handle_mul(vm: &mut VmState) -> Result<(), VmError> {
+/// Read an `Int` or `BigInt` value as a `BigInt`
+pub fn read_integer(vm: &VmState, value: &Value) -> Result<BigInt, VmError> {
+    match value {
+        Value::Int(n) => Ok(BigInt::from(*n)),
+        Value::BigInt(ptr) => {
+            if ptr.get() >= vm.memory.next_free() {
+                return Err(VmError::InvalidHeapPtr);
+            }
+            Ok(BigInt::from_bytes(unsafe { vm.memory.get_data(*ptr) }))
+        }
+        _ => Err(VmError::TypeMismatch),
+    }
+}
+
+/// Store an integer as an `Int` when it fits, otherwise as a heap `BigInt`
+pub fn integer_value(vm: &mut VmState, n: &BigInt) -> Result<Value, VmError> {
+    if let Some(small) = n.to_i64() {
+        return Ok(Value::Int(small));
+    }
+    let bytes = n.to_bytes();
+    let size = u32::try_from(bytes.len()).map_err(|_| VmError::MemoryLimitExceeded)?;
+    let ptr = vm.allocate_object(size, TAG_BIGINT)?;
+    unsafe { vm.memory.get_data_mut(ptr) }.copy_from_slice(&bytes);
+    Ok(Value::BigInt(ptr))
+}
+
+/// Handles Div opcode. The quotient is truncated towards zero; a `BigInt`
+/// operand is divided in arbitrary precision, as in [`integer_binary_op`].
+pub fn handle_div(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
 
+    if matches!(a, Value::BigInt(_)) || matches!(b, Value::BigInt(_)) {
+        let (quotient, _) = big_div_rem(vm, &a, &b)?;
+        let quotient = integer_value(vm, &quotient)?;
+        vm.stack.push(quotient);
+        return Ok(());
+    }
+
     match (a, b) {
-        (Value::Int(x), Value::Int(y)) => {
-            let result = x.checked_mul(y).ok_or(VmError::ArithmeticOverflow)?;
-            vm.stack.push(Value::Int(result));
-        }
+        (Value::Int(_), Value::Int(0)) => return Err(VmError::DivisionByZero),
+        // i64::MIN / -1 is the only quotient that does not fit
+        (Value::Int(x), Value::Int(y)) => match x.checked_div(y) {
+            Some(result) => vm.stack.push(Value::Int(result)),
+            None if vm.int_overflow_mode == IntOverflowMode::BigIntPromotion => {
+                let promoted = integer_value(vm, &BigInt::from(x).negated())?;
+                vm.stack.push(promoted);
+            }
+            None => return Err(VmError::ArithmeticOverflow),
+        },
         _ => return Err(VmError::TypeMismatch),
     }
     Ok(())
 }
 
-/// Handles Div opcode
-pub fn handle_div(vm: &mut VmState) -> Result<(), VmError> {
+/// Handles Mod opcode with the semantics of Rust's `%`: the remainder has
+/// the sign of the dividend. A zero divisor is `DivisionByZero`, and
+/// `i64::MIN % -1`, whose quotient overflows, is `ArithmeticOverflow`. A
+/// `BigInt` operand is reduced in arbitrary precision.
+pub fn handle_mod(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
 
+    if matches!(a, Value::BigInt(_)) || matches!(b, Value::BigInt(_)) {
+        let (_, remainder) = big_div_rem(vm, &a, &b)?;
+        let remainder = integer_value(vm, &remainder)?;
+        vm.stack.push(remainder);
+        return Ok(());
+    }
+
     match (a, b) {
-        (Value::Int(_), Value::Int(0)) => return Err(VmError::DivisionByZero),
         (Value::Int(x), Value::Int(y)) => {
-            let result = x.checked_div(y).ok_or(VmError::ArithmeticOverflow)?;
+            if y == 0 {
+                return Err(VmError::DivisionByZero);
+            }
+            let result = x.checked_rem(y).ok_or(VmError::ArithmeticOverflow)?;
             vm.stack.push(Value::Int(result));
         }
         _ => return Err(VmError::TypeMismatch),
@@ -64,17 +197,64 @@ pub fn handle_div(vm: &mut VmState) -> Result<(), VmError> {
     Ok(())
 }
 
-/// Handles Mod opcode
-pub fn handle_mod(vm: &mut VmState) -> Result<(), VmError> {
+/// Quotient and remainder of two integer values in arbitrary precision
+fn big_div_rem(vm: &VmState, a: &Value, b: &Value) -> Result<(BigInt, BigInt), VmError> {
+    let x = read_integer(vm, a)?;
+    let y = read_integer(vm, b)?;
+    x.div_rem(&y).ok_or(VmError::DivisionByZero)
+}
+
+/// Handles BitAnd opcode
+pub fn handle_bit_and(vm: &mut VmState) -> Result<(), VmError> {
+    bitwise_op(vm, |x, y| Some(x & y))
+}
+
+/// Handles BitOr opcode
+pub fn handle_bit_or(vm: &mut VmState) -> Result<(), VmError> {
+    bitwise_op(vm, |x, y| Some(x | y))
+}
+
+/// Handles BitXor opcode
+pub fn handle_bit_xor(vm: &mut VmState) -> Result<(), VmError> {
+    bitwise_op(vm, |x, y| Some(x ^ y))
+}
+
+/// Handles Shl opcode; see [`shift_left`]
+pub fn handle_shl(vm: &mut VmState) -> Result<(), VmError> {
+    bitwise_op(vm, shift_left)
+}
+
+/// Handles Shr opcode; see [`shift_right`]
+pub fn handle_shr(vm: &mut VmState) -> Result<(), VmError> {
+    bitwise_op(vm, shift_right)
+}
+
+/// `x` shifted left by `amount` bits, discarding bits shifted past the top
+/// (so the sign can change). `None` when `amount` is negative or at least
+/// 64, rather than wrapping the amount as Rust's `wrapping_shl` would.
+pub fn shift_left(x: i64, amount: i64) -> Option<i64> {
+    u32::try_from(amount).ok().and_then(|n| x.checked_shl(n))
+}
+
+/// `x` shifted right by `amount` bits, filling with copies of the sign bit,
+/// so negative values round towards negative infinity. `None` when
+/// `amount` is negative or at least 64.
+pub fn shift_right(x: i64, amount: i64) -> Option<i64> {
+    u32::try_from(amount).ok().and_then(|n| x.checked_shr(n))
+}
+
+/// Pop two `Int`s and push the result of a bitwise operation on them.
+///
+/// Operands are read as 64-bit two's complement; `BigInt`s and any other
+/// values are a `TypeMismatch`. An operation returning `None` (an
+/// out-of-range shift) is `ArithmeticOverflow`.
+fn bitwise_op(vm: &mut VmState, op: fn(i64, i64) -> Option<i64>) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
 
     match (a, b) {
         (Value::Int(x), Value::Int(y)) => {
-            if y == 0 {
-                return Err(VmError::DivisionByZero);
-            }
-            let result = x.checked_rem(y).ok_or(VmError::ArithmeticOverflow)?;
+            let result = op(x, y).ok_or(VmError::ArithmeticOverflow)?;
             vm.stack.push(Value::Int(result));
         }
         _ => return Err(VmError::TypeMismatch),
@@ -124,7 +304,8 @@ pub fn handle_fmul(vm: &mut VmState) -> Result<(), VmError> {
     Ok(())
 }
 
-/// NEW: Handles Float Div opcode
+/// Handles Float Div opcode. Dividing by `0.0` or `-0.0` is
+/// `DivisionByZero`; other IEEE special values propagate as usual.
 pub fn handle_fdiv(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
@@ -22,12 +22,14 @@ pub fn handle_int(vm: &mut VmState, value: i64) -> Result<(), VmError> {
 }
 
 /// Handles Symbol opcode
+///
+/// The operand is an id in the attached symbol table, never a constant pool
+/// index; an id the table does not hold is rejected.
 pub fn handle_symbol(vm: &mut VmState, sym_idx: usize) -> Result<(), VmError> {
-    let symbol = match vm.constant_pool.get(sym_idx) {
-        Some(val) => val.clone(),
-        None => return Err(VmError::InvalidHeapPtr),
-    };
-    vm.stack.push(symbol);
+    if vm.symbol_table.resolve(sym_idx).is_none() {
+        return Err(VmError::InvalidHeapPtr);
+    }
+    vm.stack.push(Value::Symbol(sym_idx));
     Ok(())
 }
 
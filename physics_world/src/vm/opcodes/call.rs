@@ -2,6 +2,7 @@
 /// This is a critical Phase 1 feature for the Physics World VM
 use crate::types::{HeapPtr, OpCode, Value};
 use crate::vm::call_state::CallFrame;
+use crate::vm::opcodes::list_ops;
 use crate::vm::state::VmError;
 use crate::vm::state::VmState;
 use bincode;
@@ -26,9 +27,60 @@ use std::collections::HashMap;
 /// 7. Resets instruction pointer to start of closure
 /// 8. Arguments remain on stack for function to access via GetLocal
 pub fn handle_call(vm: &mut VmState, arg_count: u16) -> Result<(), VmError> {
-    // 1. Validate stack has at least the closure (function)
-    // Note: arg_count can be 0, so we just need the closure on the stack
-    if vm.stack.is_empty() {
+    call_closure(vm, arg_count, None)
+}
+
+/// Handles the CallN opcode: a call whose callee must return `result_count` values
+///
+/// The callee returns with `RetN(result_count)` and the results are left on
+/// the caller's stack in order, last result on top. Returning any other
+/// number of values is a `ReturnCountMismatch`.
+pub fn handle_call_n(vm: &mut VmState, arg_count: u16, result_count: u16) -> Result<(), VmError> {
+    call_closure(vm, arg_count, Some(result_count))
+}
+
+/// Handles the CollectRest opcode: gathers a call's surplus arguments into a
+/// rest parameter
+///
+/// The arguments past the first `fixed` locals of the current frame (or the
+/// top-level locals outside any call) are consed into a list, in order,
+/// which replaces them as local `fixed`. With no surplus arguments the rest
+/// parameter is nil. Fewer than `fixed` arguments is a `StackUnderflow`.
+pub fn handle_collect_rest(vm: &mut VmState, fixed: u16) -> Result<(), VmError> {
+    let fixed = fixed as usize;
+    let surplus: Vec<Value> = match vm.call_stack.last() {
+        Some(frame) => frame.locals.get(fixed..),
+        None => vm.top_level_locals.get(fixed..),
+    }
+    .ok_or(VmError::StackUnderflow)?
+    .to_vec();
+
+    // Cons from the last argument back; the arguments stay in the locals
+    // until the list is built, so a collection while allocating keeps them
+    let mut rest = Value::Nil;
+    for value in surplus.into_iter().rev() {
+        vm.stack.push(value);
+        vm.stack.push(rest);
+        list_ops::handle_cons(vm)?;
+        rest = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    }
+
+    let locals = match vm.call_stack.last_mut() {
+        Some(frame) => &mut frame.locals,
+        None => &mut vm.top_level_locals,
+    };
+    locals.truncate(fixed);
+    locals.push(rest);
+    Ok(())
+}
+
+pub(crate) fn call_closure(
+    vm: &mut VmState,
+    arg_count: u16,
+    expected_results: Option<u16>,
+) -> Result<(), VmError> {
+    // 1. Validate stack has the closure (function) plus its arguments
+    if vm.stack.len() <= arg_count as usize {
         return Err(VmError::StackUnderflow);
     }
 
@@ -46,19 +98,28 @@ pub fn handle_call(vm: &mut VmState, arg_count: u16) -> Result<(), VmError> {
         vm.max_recursion_depth
     );
 
-    if current_depth > vm.max_recursion_depth {
-        eprintln!("DEBUG: Recursion limit exceeded at depth {}", current_depth);
-        return Err(VmError::RecursionLimitExceeded);
-    }
-
     // 3. Get the function (closure) from stack
     // The function should be the last item on the stack
     let func_pos = vm.stack.len() - 1;
     let func = &vm.stack[func_pos];
 
+    if current_depth > vm.max_recursion_depth {
+        eprintln!("DEBUG: Recursion limit exceeded at depth {}", current_depth);
+        return match func {
+            Value::Closure(closure_ptr) => Err(VmError::RecursionLimitExceeded {
+                function: *closure_ptr,
+                depth: current_depth,
+                limit: vm.max_recursion_depth,
+            }),
+            _ => Err(VmError::TypeMismatch),
+        };
+    }
+
     // 4. Handle different function types
     match func {
-        Value::Closure(closure_ptr) => execute_closure_call(vm, *closure_ptr, arg_count),
+        Value::Closure(closure_ptr) => {
+            execute_closure_call(vm, *closure_ptr, arg_count, expected_results)
+        }
         _ => Err(VmError::TypeMismatch),
     }
 }
@@ -68,6 +129,7 @@ fn execute_closure_call(
     vm: &mut VmState,
     closure_ptr: HeapPtr,
     arg_count: u16,
+    expected_results: Option<u16>,
 ) -> Result<(), VmError> {
     // 1. Validate closure pointer
     if closure_ptr.get() == 0 {
@@ -96,18 +158,15 @@ fn execute_closure_call(
             let bytecode_bytes = &body_data[4..4 + bytecode_length as usize];
             match bincode::deserialize::<Vec<OpCode>>(bytecode_bytes) {
                 Ok(closure_body) => {
-                    // Get code_index from closure data (stored at bytes 4-8)
-                    let code_index = if closure_data.len() >= 8 {
-                        u32::from_le_bytes([
-                            closure_data[4],
-                            closure_data[5],
-                            closure_data[6],
-                            closure_data[7],
-                        ]) as usize
-                    } else {
-                        0
-                    };
-                    return execute_closure_body(vm, closure_body, arg_count, code_index);
+                    // Frames of unnamed bodies get an index no name is keyed by
+                    let code_index = body_code_index(vm, body_ptr).unwrap_or(usize::MAX);
+                    return execute_closure_body(
+                        vm,
+                        closure_body,
+                        arg_count,
+                        code_index,
+                        expected_results,
+                    );
                 }
                 Err(_) => {
                     return Err(VmError::TypeMismatch);
@@ -118,12 +177,39 @@ fn execute_closure_call(
     return Err(VmError::InvalidHeapPtr);
 }
 
+/// Constant pool index of the closure body at `body_ptr`, if it is one of
+/// the bodies named in `function_names`
+///
+/// Only the named slots are checked, so a call costs nothing extra when no
+/// names are attached.
+fn body_code_index(vm: &VmState, body_ptr: HeapPtr) -> Option<usize> {
+    vm.function_names
+        .code_indices()
+        .find(|index| vm.constant_pool.get(*index) == Some(&Value::Closure(body_ptr)))
+}
+
+/// Length the stack is cut back to when a call with `arg_count` arguments
+/// (the closure already popped) enters its callee
+///
+/// This must include BOTH the caller's stack AND the caller's locals, which
+/// is CRITICAL for recursion: the caller's complete state is preserved.
+pub(crate) fn preserved_stack_len(vm: &VmState, arg_count: u16) -> usize {
+    match vm.call_stack.last() {
+        // Top-level call: no caller to preserve
+        None => vm.stack.len() - arg_count as usize,
+        // Nested call: preserve the caller's stack_start PLUS the caller's
+        // locals count, so the caller's arguments survive the return
+        Some(caller) => caller.stack_start + caller.locals.len(),
+    }
+}
+
 /// Helper function to execute a closure body with TCO support
 fn execute_closure_body(
     vm: &mut VmState,
     closure_body: Vec<OpCode>,
     arg_count: u16,
     code_index: usize,
+    expected_results: Option<u16>,
 ) -> Result<(), VmError> {
     eprintln!(
         "DEBUG CALL: stack.len()={}, arg_count={}, stack={:?}",
@@ -133,20 +219,10 @@ fn execute_closure_body(
     );
 
     // 1. Pop the closure from the stack first
-    let _closure = vm.stack.pop().unwrap();
+    let closure = vm.stack.pop().unwrap();
 
     // 2. Capture caller's stack state BEFORE truncating arguments
-    // original_stack_size must include BOTH the caller's stack AND the caller's locals
-    // This is CRITICAL for recursion: we must preserve the caller's complete state
-    let original_stack_size = if vm.call_stack.is_empty() {
-        // Top-level call: no caller to preserve
-        vm.stack.len() - arg_count as usize
-    } else {
-        // Nested call: preserve the caller's stack_start PLUS the caller's locals count
-        // This ensures we keep the caller's arguments on the stack after they return
-        let caller = vm.call_stack.last().unwrap();
-        caller.stack_start + caller.locals.len()
-    };
+    let original_stack_size = preserved_stack_len(vm, arg_count);
 
     // 3. Copy arguments to locals (preserving order: first arg at index 0)
     // NOTE: For TCO, we need to keep arguments on the stack at positions
@@ -241,11 +317,20 @@ fn execute_closure_body(
             is_tail_call: false,
             frame_id: vm.next_frame_id(),
             code_index,
+            expected_results,
+            forcing: None,
         };
         // vm.call_stack is Vec<CallFrame>, check length manually
         // Use > to allow exactly max_recursion_depth frames (0 to max_recursion_depth-1)
         if vm.call_stack.len() > (vm.max_recursion_depth - 1) as usize {
-            return Err(VmError::RecursionLimitExceeded);
+            return match closure {
+                Value::Closure(closure_ptr) => Err(VmError::RecursionLimitExceeded {
+                    function: closure_ptr,
+                    depth: recursion_depth,
+                    limit: vm.max_recursion_depth,
+                }),
+                _ => Err(VmError::TypeMismatch),
+            };
         }
         vm.call_stack.push(call_frame);
     }
@@ -266,9 +351,8 @@ pub fn handle_tail_call(vm: &mut VmState, arg_count: u16) -> Result<(), VmError>
     }
     vm.steps_remaining -= 1;
 
-    // 2. Validate stack has at least the closure (function)
-    // Note: arg_count can be 0, so we just need the closure on the stack
-    if vm.stack.is_empty() {
+    // 2. Validate stack has the closure (function) plus its arguments
+    if vm.stack.len() <= arg_count as usize {
         return Err(VmError::StackUnderflow);
     }
 
@@ -321,6 +405,12 @@ fn execute_tail_call_closure(
             let bytecode_bytes = &body_data[4..4 + bytecode_length as usize];
             match bincode::deserialize::<Vec<OpCode>>(bytecode_bytes) {
                 Ok(closure_body) => {
+                    // The reused frame now runs a (possibly different) body
+                    if let Some(code_index) = body_code_index(vm, body_ptr) {
+                        if let Some(frame) = vm.call_stack.last_mut() {
+                            frame.code_index = code_index;
+                        }
+                    }
                     return execute_tail_call_body(vm, closure_body, arg_count);
                 }
                 Err(_) => {
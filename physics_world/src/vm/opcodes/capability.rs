@@ -1,4 +1,4 @@
-use crate::types::{Capability, Value};
+use crate::types::{Capability, HostFunction, Value};
 use crate::vm::state::VmError;
 /// Capability-related opcode handlers for the Physics World VM
 use crate::vm::state::{InstructionResult, VmState};
@@ -78,11 +78,15 @@ pub fn handle_has_cap(vm: &mut VmState, cap_idx: usize) -> Result<InstructionRes
         _ => return Err(VmError::TypeMismatch),
     };
 
-    // Check if the actor has this capability
-    // This requires access to the scheduler, which we don't have directly in the VM
-    // For now, we'll push false as a placeholder
-    // In a real implementation, this would call the scheduler's capability system
-    vm.stack.push(Value::Bool(false));
+    // Check the capabilities the scheduler has handed to this VM, dropping
+    // time-boxed grants whose window has passed
+    let capability = capability.clone();
+    vm.prune_expired_capabilities();
+    let held = vm.has_capability(&capability);
+    if let Some(observer) = &vm.capability_observer {
+        observer.on_check(vm.actor_id, &capability, held);
+    }
+    vm.stack.push(Value::Bool(held));
 
     Ok(InstructionResult::Continue)
 }
@@ -112,14 +116,17 @@ pub fn handle_request_cap(
     };
 
     let justification = match justification_value {
-        Value::Symbol(sym_idx) => {
-            // In a real implementation, we'd look up the string from a symbol table
-            // For now, we'll use a placeholder
-            format!("Request for {:?}", capability)
-        }
+        Value::Symbol(_) => match vm.resolve_symbol(justification_value) {
+            Some(name) => name.to_string(),
+            None => format!("Request for {:?}", capability),
+        },
         _ => return Err(VmError::TypeMismatch),
     };
 
+    if let Some(observer) = &vm.capability_observer {
+        observer.on_request(vm.actor_id, capability, &justification);
+    }
+
     // Return WaitingForCapability to indicate the actor is waiting for a decision
     Ok(InstructionResult::WaitingForCapability(capability.clone()))
 }
@@ -146,7 +153,10 @@ pub fn handle_grant_cap(
     };
 
     // In a real implementation, this would call the scheduler to grant the capability
-    // For now, we'll just continue execution
+    // For now, we'll just report it and continue execution
+    if let Some(observer) = &vm.capability_observer {
+        observer.on_grant(vm.actor_id, target_actor_id, capability);
+    }
 
     Ok(())
 }
@@ -169,14 +179,17 @@ pub fn handle_revoke_cap(
     };
 
     // In a real implementation, this would call the scheduler to revoke the capability
-    // For now, we'll just continue execution
+    // For now, we'll just report it and continue execution
+    if let Some(observer) = &vm.capability_observer {
+        observer.on_revoke(vm.actor_id, target_actor_id, capability);
+    }
 
     Ok(())
 }
 
 /// Get the capability required for a specific host function
 /// Arithmetic operations (func_id 9-25) don't require special capabilities
-fn get_required_capability_for_host_function(func_id: u16) -> Option<Capability> {
+pub fn get_required_capability_for_host_function(func_id: u16) -> Option<Capability> {
     match func_id {
         0 => Some(Capability::IoReadSensor),      // ReadSensor
         1 => Some(Capability::IoWriteActuator),   // WriteActuator
@@ -193,6 +206,15 @@ fn get_required_capability_for_host_function(func_id: u16) -> Option<Capability>
     }
 }
 
+/// Bytes the installed host input has for `function`, as a `String` when
+/// they are valid UTF-8 and `Bytes` otherwise; nil when nothing is available
+fn receive_host_bytes(vm: &VmState, function: HostFunction) -> Value {
+    vm.host_input
+        .as_ref()
+        .and_then(|input| input.receive(vm.actor_id, function))
+        .map_or(Value::Nil, Value::from_host_bytes)
+}
+
 /// Handles the HostCall opcode - executes a privileged host function call
 pub fn handle_host_call(
     vm: &mut VmState,
@@ -226,6 +248,11 @@ pub fn handle_host_call(
             }
         }
 
+        // A WithCaps region only runs with the capabilities it kept
+        if vm.in_capability_scope() && !vm.has_capability(required_capability) {
+            return Err(VmError::CapabilityDenied);
+        }
+
         // Get the arguments from the stack for system operations
         if vm.stack.len() < args as usize {
             return Err(VmError::StackUnderflow);
@@ -244,9 +271,9 @@ pub fn handle_host_call(
         3 => Value::ActorId(1),      // SpawnActor - return mock actor ID
         4 => Value::Nil,             // TerminateActor - return nil
         5 => Value::Nil,             // NetworkSend - return nil
-        6 => Value::Nil,             // NetworkReceive - return nil
+        6 => receive_host_bytes(vm, HostFunction::NetworkReceive),
         7 => Value::Nil,             // PersistWrite - return nil
-        8 => Value::Nil,             // PersistRead - return nil
+        8 => receive_host_bytes(vm, HostFunction::PersistRead),
         
         // Integer arithmetic operations
         9 => {  // IntAdd
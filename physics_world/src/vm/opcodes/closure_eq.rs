@@ -0,0 +1,32 @@
+/// ClosureAlphaEq opcode handler - compares two closures by their bodies
+///
+/// Only the bodies are compared: closures made from the same code with
+/// different captured values compare equal. Bodies go to the installed
+/// `ClosureEquivalence` when there is one, and are otherwise compared
+/// instruction by instruction.
+use crate::types::Value;
+use crate::vm::state::{VmError, VmState};
+
+/// Pop two closures and push whether their bodies are equivalent
+pub fn handle_closure_alpha_eq(vm: &mut VmState) -> Result<(), VmError> {
+    if vm.stack.len() < 2 {
+        return Err(VmError::StackUnderflow);
+    }
+    let (Value::Closure(rhs), Value::Closure(lhs)) =
+        (&vm.stack[vm.stack.len() - 1], &vm.stack[vm.stack.len() - 2])
+    else {
+        return Err(VmError::TypeMismatch);
+    };
+
+    let lhs = vm.inspect_closure(*lhs).ok_or(VmError::InvalidHeapPtr)?;
+    let rhs = vm.inspect_closure(*rhs).ok_or(VmError::InvalidHeapPtr)?;
+    let equal = lhs.body_ptr == rhs.body_ptr
+        || match &vm.closure_equivalence {
+            Some(equivalence) => equivalence.equivalent(&lhs.body, &rhs.body),
+            None => lhs.body == rhs.body,
+        };
+
+    vm.stack.truncate(vm.stack.len() - 2);
+    vm.stack.push(Value::Bool(equal));
+    Ok(())
+}
@@ -0,0 +1,247 @@
+/// Textual closure bodies
+///
+/// A constant pool string starting with `closure_body:` holds a closure body
+/// written as a list of instructions, in the same notation `{:?}` prints
+/// them:
+///
+/// ```text
+/// body     := "closure_body:" "[" [ opcode { "," opcode } ] "]"
+/// opcode   := NAME
+///           | NAME "(" operand { "," operand } ")"
+///           | NAME "{" FIELD ":" operand { "," FIELD ":" operand } "}"
+/// operand  := INTEGER | FLOAT | "true" | "false"
+/// ```
+///
+/// `NAME` is an `OpCode` variant name and takes the same operands as the
+/// variant, in declaration order; struct variants such as
+/// `Spawn { arg_count: 2 }` name each field. Whitespace may appear between
+/// any two tokens. For example
+/// `closure_body:[GetLocal(0), Int(1), Add, Ret]` adds one to its argument.
+use crate::types::OpCode;
+use crate::vm::state::VmError;
+use std::str::FromStr;
+
+/// Prefix marking a string constant as a closure body
+pub const CLOSURE_BODY_PREFIX: &str = "closure_body:";
+
+/// Parse a closure body string, including its `closure_body:` prefix.
+///
+/// Returns `VmError::TypeMismatch` if the text does not follow the grammar
+/// above, names an unknown opcode, or gives an opcode the wrong operands.
+pub fn parse_closure_body(text: &str) -> Result<Vec<OpCode>, VmError> {
+    let list = text
+        .strip_prefix(CLOSURE_BODY_PREFIX)
+        .ok_or(VmError::TypeMismatch)?
+        .trim();
+    let inner = list
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or(VmError::TypeMismatch)?;
+    if inner.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    split_top_level(inner)?
+        .into_iter()
+        .map(parse_opcode)
+        .collect()
+}
+
+/// Split `text` at the commas that are not inside parentheses or braces
+fn split_top_level(text: &str) -> Result<Vec<&str>, VmError> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (index, ch) in text.char_indices() {
+        match ch {
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth = depth.checked_sub(1).ok_or(VmError::TypeMismatch)?,
+            ',' if depth == 0 => {
+                items.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(VmError::TypeMismatch);
+    }
+    items.push(&text[start..]);
+    Ok(items)
+}
+
+/// How an instruction's operands are written
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Unit,
+    Tuple,
+    Struct,
+}
+
+/// Operands of one instruction, consumed in declaration order
+struct Operands<'a> {
+    items: std::vec::IntoIter<(Option<&'a str>, &'a str)>,
+}
+
+impl Operands<'_> {
+    /// Next positional operand
+    fn next<T: FromStr>(&mut self) -> Result<T, VmError> {
+        match self.items.next() {
+            Some((None, value)) => value.parse().map_err(|_| VmError::TypeMismatch),
+            _ => Err(VmError::TypeMismatch),
+        }
+    }
+
+    /// Next operand, which must be the field `name`
+    fn field<T: FromStr>(&mut self, name: &str) -> Result<T, VmError> {
+        match self.items.next() {
+            Some((Some(field), value)) if field == name => {
+                value.parse().map_err(|_| VmError::TypeMismatch)
+            }
+            _ => Err(VmError::TypeMismatch),
+        }
+    }
+
+    /// Fail if operands are left over
+    fn finish(mut self) -> Result<(), VmError> {
+        match self.items.next() {
+            Some(_) => Err(VmError::TypeMismatch),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_opcode(text: &str) -> Result<OpCode, VmError> {
+    let text = text.trim();
+    let name_end = text
+        .find(|ch: char| !ch.is_ascii_alphanumeric() && ch != '_')
+        .unwrap_or(text.len());
+    let (name, rest) = text.split_at(name_end);
+    let rest = rest.trim();
+
+    let (shape, body) = if rest.is_empty() {
+        (Shape::Unit, "")
+    } else if let Some(body) = rest.strip_prefix('(').and_then(|r| r.strip_suffix(')')) {
+        (Shape::Tuple, body)
+    } else if let Some(body) = rest.strip_prefix('{').and_then(|r| r.strip_suffix('}')) {
+        (Shape::Struct, body)
+    } else {
+        return Err(VmError::TypeMismatch);
+    };
+
+    let mut items = Vec::new();
+    if shape != Shape::Unit {
+        for item in split_top_level(body)? {
+            let item = item.trim();
+            if shape == Shape::Struct {
+                let (field, value) = item.split_once(':').ok_or(VmError::TypeMismatch)?;
+                items.push((Some(field.trim()), value.trim()));
+            } else {
+                items.push((None, item));
+            }
+        }
+    }
+    let mut ops = Operands {
+        items: items.into_iter(),
+    };
+
+    let opcode = match (name, shape) {
+        ("Nil", Shape::Unit) => OpCode::Nil,
+        ("Bool", Shape::Tuple) => OpCode::Bool(ops.next()?),
+        ("Int", Shape::Tuple) => OpCode::Int(ops.next()?),
+        ("Float", Shape::Tuple) => OpCode::Float(ops.next()?),
+        ("Symbol", Shape::Tuple) => OpCode::Symbol(ops.next()?),
+        ("LoadString", Shape::Tuple) => OpCode::LoadString(ops.next()?),
+        ("StrLen", Shape::Unit) => OpCode::StrLen,
+        ("StrConcat", Shape::Unit) => OpCode::StrConcat,
+        ("StrIndex", Shape::Unit) => OpCode::StrIndex,
+        ("BytesToStr", Shape::Unit) => OpCode::BytesToStr,
+        ("StrToBytes", Shape::Unit) => OpCode::StrToBytes,
+        ("Swap", Shape::Unit) => OpCode::Swap,
+        ("Dup", Shape::Unit) => OpCode::Dup,
+        ("Pop", Shape::Unit) => OpCode::Pop,
+        ("GetLocal", Shape::Tuple) => OpCode::GetLocal(ops.next()?),
+        ("SetLocal", Shape::Tuple) => OpCode::SetLocal(ops.next()?),
+        ("Cons", Shape::Unit) => OpCode::Cons,
+        ("Car", Shape::Unit) => OpCode::Car,
+        ("Cdr", Shape::Unit) => OpCode::Cdr,
+        ("ListLen", Shape::Unit) => OpCode::ListLen,
+        ("DeepClone", Shape::Unit) => OpCode::DeepClone,
+        ("MakeThunk", Shape::Unit) => OpCode::MakeThunk,
+        ("Force", Shape::Unit) => OpCode::Force,
+        ("MakeVector", Shape::Tuple) => OpCode::MakeVector(ops.next()?),
+        ("VecGet", Shape::Unit) => OpCode::VecGet,
+        ("VecSet", Shape::Unit) => OpCode::VecSet,
+        ("VecLen", Shape::Unit) => OpCode::VecLen,
+        ("TypeOf", Shape::Unit) => OpCode::TypeOf,
+        ("IsInt", Shape::Unit) => OpCode::IsInt,
+        ("IsFloat", Shape::Unit) => OpCode::IsFloat,
+        ("IsPair", Shape::Unit) => OpCode::IsPair,
+        ("IsClosure", Shape::Unit) => OpCode::IsClosure,
+        ("Call", Shape::Tuple) => OpCode::Call(ops.next()?),
+        ("TailCall", Shape::Tuple) => OpCode::TailCall(ops.next()?),
+        ("CallN", Shape::Tuple) => OpCode::CallN(ops.next()?, ops.next()?),
+        ("Ret", Shape::Unit) => OpCode::Ret,
+        ("RetN", Shape::Tuple) => OpCode::RetN(ops.next()?),
+        ("CollectRest", Shape::Tuple) => OpCode::CollectRest(ops.next()?),
+        ("Jmp", Shape::Tuple) => OpCode::Jmp(ops.next()?),
+        ("JmpIfFalse", Shape::Tuple) => OpCode::JmpIfFalse(ops.next()?),
+        ("Yield", Shape::Unit) => OpCode::Yield,
+        ("Send", Shape::Unit) => OpCode::Send,
+        ("Spawn", Shape::Struct) => OpCode::Spawn {
+            arg_count: ops.field("arg_count")?,
+        },
+        ("MakeClosure", Shape::Tuple) => OpCode::MakeClosure(ops.next()?, ops.next()?),
+        ("ClosureAlphaEq", Shape::Unit) => OpCode::ClosureAlphaEq,
+        ("GetConst", Shape::Tuple) => OpCode::GetConst(ops.next()?),
+        ("CheckStepLimit", Shape::Unit) => OpCode::CheckStepLimit,
+        ("GcCollect", Shape::Unit) => OpCode::GcCollect,
+        ("GcStats", Shape::Unit) => OpCode::GcStats,
+        ("StepsRemaining", Shape::Unit) => OpCode::StepsRemaining,
+        ("MemoryRemaining", Shape::Unit) => OpCode::MemoryRemaining,
+        ("Add", Shape::Unit) => OpCode::Add,
+        ("Sub", Shape::Unit) => OpCode::Sub,
+        ("Mul", Shape::Unit) => OpCode::Mul,
+        ("Div", Shape::Unit) => OpCode::Div,
+        ("Mod", Shape::Unit) => OpCode::Mod,
+        ("BitAnd", Shape::Unit) => OpCode::BitAnd,
+        ("BitOr", Shape::Unit) => OpCode::BitOr,
+        ("BitXor", Shape::Unit) => OpCode::BitXor,
+        ("Shl", Shape::Unit) => OpCode::Shl,
+        ("Shr", Shape::Unit) => OpCode::Shr,
+        ("FAdd", Shape::Unit) => OpCode::FAdd,
+        ("FSub", Shape::Unit) => OpCode::FSub,
+        ("FMul", Shape::Unit) => OpCode::FMul,
+        ("FDiv", Shape::Unit) => OpCode::FDiv,
+        ("Eq", Shape::Unit) => OpCode::Eq,
+        ("Lt", Shape::Unit) => OpCode::Lt,
+        ("Gt", Shape::Unit) => OpCode::Gt,
+        ("Lte", Shape::Unit) => OpCode::Lte,
+        ("Gte", Shape::Unit) => OpCode::Gte,
+        ("Ne", Shape::Unit) => OpCode::Ne,
+        ("HasCap", Shape::Tuple) => OpCode::HasCap(ops.next()?),
+        ("RequestCap", Shape::Tuple) => OpCode::RequestCap(ops.next()?, ops.next()?),
+        ("GrantCap", Shape::Tuple) => OpCode::GrantCap(ops.next()?, ops.next()?),
+        ("RevokeCap", Shape::Tuple) => OpCode::RevokeCap(ops.next()?, ops.next()?),
+        ("HostCall", Shape::Struct) => OpCode::HostCall {
+            cap_idx: ops.field("cap_idx")?,
+            func_id: ops.field("func_id")?,
+            args: ops.field("args")?,
+        },
+        ("WithCaps", Shape::Struct) => OpCode::WithCaps {
+            cap_mask: ops.field("cap_mask")?,
+            body_len: ops.field("body_len")?,
+        },
+        ("InitSandbox", Shape::Unit) => OpCode::InitSandbox,
+        ("IsolateCapabilities", Shape::Unit) => OpCode::IsolateCapabilities,
+        ("SetErrorHandler", Shape::Tuple) => OpCode::SetErrorHandler(ops.next()?),
+        ("LogSandboxViolation", Shape::Unit) => OpCode::LogSandboxViolation,
+        ("CleanupSandbox", Shape::Unit) => OpCode::CleanupSandbox,
+        ("MakeError", Shape::Unit) => OpCode::MakeError,
+        ("Throw", Shape::Unit) => OpCode::Throw,
+        ("PopErrorHandler", Shape::Unit) => OpCode::PopErrorHandler,
+        ("ErrorPayload", Shape::Unit) => OpCode::ErrorPayload,
+        _ => return Err(VmError::TypeMismatch),
+    };
+    ops.finish()?;
+    Ok(opcode)
+}
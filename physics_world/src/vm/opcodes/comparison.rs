@@ -1,13 +1,43 @@
 /// Comparison opcode handlers - Eq, Lt, Gt, Lte, Gte, Ne
+///
+/// Integer comparisons accept any mix of `Int` and `BigInt` operands and
+/// compare them numerically. Two `Float` operands compare under the VM's
+/// `FloatCmpPolicy`.
 use crate::types::Value;
+use crate::vm::opcodes::arithmetic::read_integer;
 use crate::vm::state::VmError;
 use crate::vm::state::VmState;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// How `Float` operands compare, which only matters once NaN is involved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FloatCmpPolicy {
+    /// IEEE 754: NaN is unordered, so `Lt`, `Gt`, `Lte`, `Gte` and `Eq`
+    /// involving NaN are all false and `Ne` is true
+    #[default]
+    IeeeNaNFalse,
+    /// IEEE 754 `totalOrder`: every float has a place, with negative NaN
+    /// below every number, positive NaN above, and `-0.0` below `0.0`
+    TotalOrder,
+}
+
+impl FloatCmpPolicy {
+    /// Ordering of `x` and `y`, or `None` if they are unordered
+    pub fn compare(self, x: f64, y: f64) -> Option<Ordering> {
+        match self {
+            FloatCmpPolicy::IeeeNaNFalse => x.partial_cmp(&y),
+            FloatCmpPolicy::TotalOrder => Some(x.total_cmp(&y)),
+        }
+    }
+}
 
 /// Handles Eq opcode
 pub fn handle_eq(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
-    vm.stack.push(Value::Bool(a == b));
+    let equal = values_equal(vm, &a, &b)?;
+    vm.stack.push(Value::Bool(equal));
     Ok(())
 }
 
@@ -16,12 +46,9 @@ pub fn handle_lt(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
 
-    match (a, b) {
-        (Value::Int(x), Value::Int(y)) => {
-            vm.stack.push(Value::Bool(x < y));
-        }
-        _ => return Err(VmError::TypeMismatch),
-    }
+    let ordering = compare_numbers(vm, &a, &b)?;
+    vm.stack
+        .push(Value::Bool(ordering.is_some_and(Ordering::is_lt)));
     Ok(())
 }
 
@@ -30,12 +57,9 @@ pub fn handle_gt(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
 
-    match (a, b) {
-        (Value::Int(x), Value::Int(y)) => {
-            vm.stack.push(Value::Bool(x > y));
-        }
-        _ => return Err(VmError::TypeMismatch),
-    }
+    let ordering = compare_numbers(vm, &a, &b)?;
+    vm.stack
+        .push(Value::Bool(ordering.is_some_and(Ordering::is_gt)));
     Ok(())
 }
 
@@ -44,12 +68,9 @@ pub fn handle_lte(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
 
-    match (a, b) {
-        (Value::Int(x), Value::Int(y)) => {
-            vm.stack.push(Value::Bool(x <= y));
-        }
-        _ => return Err(VmError::TypeMismatch),
-    }
+    let ordering = compare_numbers(vm, &a, &b)?;
+    vm.stack
+        .push(Value::Bool(ordering.is_some_and(Ordering::is_le)));
     Ok(())
 }
 
@@ -58,12 +79,9 @@ pub fn handle_gte(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
 
-    match (a, b) {
-        (Value::Int(x), Value::Int(y)) => {
-            vm.stack.push(Value::Bool(x >= y));
-        }
-        _ => return Err(VmError::TypeMismatch),
-    }
+    let ordering = compare_numbers(vm, &a, &b)?;
+    vm.stack
+        .push(Value::Bool(ordering.is_some_and(Ordering::is_ge)));
     Ok(())
 }
 
@@ -71,6 +89,29 @@ pub fn handle_gte(vm: &mut VmState) -> Result<(), VmError> {
 pub fn handle_ne(vm: &mut VmState) -> Result<(), VmError> {
     let b = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
     let a = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
-    vm.stack.push(Value::Bool(a != b));
+    let equal = values_equal(vm, &a, &b)?;
+    vm.stack.push(Value::Bool(!equal));
     Ok(())
 }
+
+/// Equality, comparing big integers by value rather than by heap address
+fn values_equal(vm: &VmState, a: &Value, b: &Value) -> Result<bool, VmError> {
+    match (a, b) {
+        (Value::Float(x), Value::Float(y)) => {
+            Ok(vm.float_cmp_policy.compare(*x, *y) == Some(Ordering::Equal))
+        }
+        (Value::BigInt(_), Value::BigInt(_) | Value::Int(_))
+        | (Value::Int(_), Value::BigInt(_)) => Ok(read_integer(vm, a)? == read_integer(vm, b)?),
+        _ => Ok(a == b),
+    }
+}
+
+/// Numeric ordering of two `Float` operands, or of two `Int` or `BigInt`
+/// operands; `None` if the floats are unordered
+fn compare_numbers(vm: &VmState, a: &Value, b: &Value) -> Result<Option<Ordering>, VmError> {
+    match (a, b) {
+        (Value::Float(x), Value::Float(y)) => Ok(vm.float_cmp_policy.compare(*x, *y)),
+        (Value::Int(x), Value::Int(y)) => Ok(Some(x.cmp(y))),
+        _ => Ok(Some(read_integer(vm, a)?.cmp(&read_integer(vm, b)?))),
+    }
+}
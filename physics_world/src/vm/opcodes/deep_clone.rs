@@ -0,0 +1,103 @@
+/// Deep clone opcode handler - DeepClone
+///
+/// Copies a heap value into freshly allocated objects so the copy can be
+/// mutated without affecting the original. Vector elements and the car and
+/// cdr of pairs are cloned too, however deeply nested. A map from original to copied object keeps
+/// shared structure shared in the copy and stops cycles from unrolling.
+///
+/// Allocation goes straight to the arena rather than through
+/// `VmState::allocate_object`, since a collection mid-copy could move the
+/// objects the half-built copy still points at.
+use crate::memory::arena::{TAG_LIST, TAG_VECTOR, VECTOR_SLOT_SIZE};
+use crate::types::{HeapPtr, Value};
+use crate::vm::opcodes::vector_ops::{read_slot, vector_len, write_slot_without_collecting};
+use crate::vm::state::{VmError, VmState};
+use std::collections::HashMap;
+
+/// Replace the value on top of the stack with a deep copy of it
+pub fn handle_deep_clone(vm: &mut VmState) -> Result<(), VmError> {
+    let value = vm.stack.last().cloned().ok_or(VmError::StackUnderflow)?;
+    let copy = deep_clone(vm, &value, &mut HashMap::new())?;
+    vm.stack.pop();
+    vm.stack.push(copy);
+    Ok(())
+}
+
+/// Deep copy `value`, reusing copies already made for objects in `copies`
+///
+/// Objects are copied from an explicit worklist rather than by recursion,
+/// so the depth of a structure, such as the spine of a long list, is not
+/// limited by the native stack.
+pub fn deep_clone(
+    vm: &mut VmState,
+    value: &Value,
+    copies: &mut HashMap<HeapPtr, Value>,
+) -> Result<Value, VmError> {
+    let mut pending = Vec::new();
+    let copy = shallow_copy(vm, value, copies, &mut pending)?;
+    while let Some((original, new_ptr, length)) = pending.pop() {
+        for index in 0..length {
+            let element = read_slot(vm, original, index)?;
+            let element = shallow_copy(vm, &element, copies, &mut pending)?;
+            write_slot_without_collecting(vm, new_ptr, index, &element)?;
+        }
+    }
+    Ok(copy)
+}
+
+/// Copy of `value` whose slots are still to be filled in.
+///
+/// A pair or vector seen for the first time gets a new object, recorded in
+/// `copies` so cycles and shared structure resolve to it, and is queued on
+/// `pending` with its original and length for its elements to be copied.
+fn shallow_copy(
+    vm: &mut VmState,
+    value: &Value,
+    copies: &mut HashMap<HeapPtr, Value>,
+    pending: &mut Vec<(HeapPtr, HeapPtr, usize)>,
+) -> Result<Value, VmError> {
+    let ptr = match value {
+        Value::Pair(ptr) | Value::Vector(ptr) | Value::BigInt(ptr) => *ptr,
+        // Closures share immutable code; everything else is held by value
+        _ => return Ok(value.clone()),
+    };
+    if let Some(copy) = copies.get(&ptr) {
+        return Ok(copy.clone());
+    }
+
+    let (length, tag) = match value {
+        Value::Vector(_) => (vector_len(vm, ptr), TAG_VECTOR),
+        Value::Pair(_) => (2, TAG_LIST),
+        _ => {
+            let copy = Value::BigInt(copy_object(vm, ptr)?);
+            copies.insert(ptr, copy.clone());
+            return Ok(copy);
+        }
+    };
+    let size =
+        u32::try_from(length * VECTOR_SLOT_SIZE).map_err(|_| VmError::MemoryLimitExceeded)?;
+    let new_ptr = vm
+        .memory
+        .allocate(size, tag)
+        .map_err(|_| VmError::MemoryLimitExceeded)?;
+    let copy = match value {
+        Value::Vector(_) => Value::Vector(new_ptr),
+        _ => Value::Pair(new_ptr),
+    };
+    copies.insert(ptr, copy.clone());
+    pending.push((ptr, new_ptr, length));
+    Ok(copy)
+}
+
+/// Allocate an object with the same tag and bytes as the one at `ptr`
+fn copy_object(vm: &mut VmState, ptr: HeapPtr) -> Result<HeapPtr, VmError> {
+    let header = unsafe { vm.memory.get_header(ptr) };
+    let (size, tag) = (header.size, header.tag);
+    let bytes = unsafe { vm.memory.get_data(ptr) }.to_vec();
+    let new_ptr = vm
+        .memory
+        .allocate(size, tag)
+        .map_err(|_| VmError::MemoryLimitExceeded)?;
+    unsafe { vm.memory.get_data_mut(new_ptr) }.copy_from_slice(&bytes);
+    Ok(new_ptr)
+}
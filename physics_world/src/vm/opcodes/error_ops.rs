@@ -0,0 +1,77 @@
+/// Error opcode handlers - structured error values and handler unwinding
+///
+/// `SetErrorHandler` records where to resume and how much of the stack and
+/// call stack to keep. `Throw` discards everything above that point and
+/// hands the thrown value to the handler unchanged, so an error record's
+/// payload reaches the handler intact.
+use crate::types::Value;
+use crate::vm::state::{ErrorHandler, VmError, VmState};
+
+/// Install a handler starting `offset` instructions after this one
+pub fn handle_set_error_handler(vm: &mut VmState, offset: i16) -> Result<(), VmError> {
+    // Same relative offset as Jmp: target = current_ip + 1 + offset
+    let target = (vm.ip as i32 + 1 + offset as i32) as usize;
+    if target >= vm.instructions.len() {
+        return Err(VmError::UnknownOpCode);
+    }
+    vm.error_handlers.push(ErrorHandler {
+        target,
+        stack_len: vm.stack.len(),
+        frame_depth: vm.call_stack.len(),
+    });
+    Ok(())
+}
+
+/// Pop a payload, a message and a code, and push the error record
+pub fn handle_make_error(vm: &mut VmState) -> Result<(), VmError> {
+    if vm.stack.len() < 3 {
+        return Err(VmError::StackUnderflow);
+    }
+    let payload = vm.stack.pop().unwrap();
+    let (Some(Value::String(message)), Some(Value::Int(code))) = (vm.stack.pop(), vm.stack.pop())
+    else {
+        return Err(VmError::TypeMismatch);
+    };
+    vm.stack.push(Value::ErrorRecord {
+        code,
+        message,
+        payload: Box::new(payload),
+    });
+    Ok(())
+}
+
+/// Pop a value and transfer control to the innermost handler.
+///
+/// Returns the value when no handler is installed, in which case it
+/// becomes the program's result.
+pub fn handle_throw(vm: &mut VmState) -> Result<Option<Value>, VmError> {
+    let value = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let Some(handler) = vm.error_handlers.pop() else {
+        return Ok(Some(value));
+    };
+
+    // Leave every call made since the handler was installed; the outermost
+    // of those frames holds the instructions the handler belongs to
+    while vm.call_stack.len() > handler.frame_depth {
+        let frame = vm.call_stack.pop().unwrap();
+        if let Some(saved_instructions) = frame.saved_instructions {
+            vm.instructions = saved_instructions;
+        }
+    }
+    vm.stack.truncate(handler.stack_len);
+    vm.stack.push(value);
+    vm.ip = handler.target;
+    Ok(None)
+}
+
+/// Replace the error record on top of the stack with its payload
+pub fn handle_error_payload(vm: &mut VmState) -> Result<(), VmError> {
+    match vm.stack.pop() {
+        Some(Value::ErrorRecord { payload, .. }) => {
+            vm.stack.push(*payload);
+            Ok(())
+        }
+        Some(_) => Err(VmError::TypeMismatch),
+        None => Err(VmError::StackUnderflow),
+    }
+}
@@ -0,0 +1,36 @@
+/// Garbage collection opcode handlers - GcCollect, GcStats
+use crate::types::{Capability, Value};
+use crate::vm::gc_integration::GcIntegration;
+use crate::vm::opcodes::list_ops;
+use crate::vm::state::{VmError, VmState};
+
+fn require_gc_capability(vm: &VmState) -> Result<(), VmError> {
+    if vm.has_capability(&Capability::SysGc) {
+        Ok(())
+    } else {
+        Err(VmError::CapabilityDenied)
+    }
+}
+
+/// Collect unreachable heap objects and push the number of bytes reclaimed
+pub fn handle_gc_collect(vm: &mut VmState) -> Result<(), VmError> {
+    require_gc_capability(vm)?;
+
+    let reclaimed = GcIntegration::collect_heap(vm).map_err(|_| VmError::InvalidHeapPtr)?;
+    vm.stack.push(Value::Int(i64::from(reclaimed)));
+    Ok(())
+}
+
+/// Push a pair of (live bytes, heap capacity)
+///
+/// Live bytes are measured after a collection has swept unreachable objects,
+/// and before the result pair is allocated; they exclude alignment padding.
+pub fn handle_gc_stats(vm: &mut VmState) -> Result<(), VmError> {
+    require_gc_capability(vm)?;
+
+    GcIntegration::collect_heap(vm).map_err(|_| VmError::InvalidHeapPtr)?;
+    let live = vm.memory.next_free() - vm.memory.padding_bytes();
+    vm.stack.push(Value::Int(i64::from(live)));
+    vm.stack.push(Value::Int(i64::from(vm.memory.capacity())));
+    list_ops::handle_cons(vm)
+}
@@ -1,38 +1,30 @@
-/// List operation handlers - Cons, Car, Cdr
-use crate::types::Value;
+/// List operation handlers - Cons, Car, Cdr, ListLen
+///
+/// A pair is a `TAG_LIST` object of two vector slots, car then cdr, so a cdr
+/// records whether it is nil, another pair or some other value.
+use crate::memory::arena::{ObjectHeader, TAG_LIST, VECTOR_SLOT_SIZE};
+use crate::types::{HeapPtr, Value};
+use crate::vm::opcodes::vector_ops::{read_slot, write_slot};
 use crate::vm::state::{VmError, VmState};
 
+/// Bytes of pair data: a car slot and a cdr slot
+const PAIR_SIZE: usize = 2 * VECTOR_SLOT_SIZE;
+
 /// Create a new pair (cons cell) from two values
 pub fn handle_cons(vm: &mut VmState) -> Result<(), VmError> {
-    let cdr = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
-    let car = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
-
-    // Allocate space for pair: [car_value][cdr_value]
-    let car_size = std::mem::size_of::<Value>();
-    let cdr_size = std::mem::size_of::<Value>();
-    let total_size = car_size + cdr_size;
-
-    // Allocate memory for the pair (tag 3 for pairs)
-    let pair_ptr = vm
-        .memory
-        .allocate(total_size as u32, 3)
-        .map_err(|_| VmError::MemoryLimitExceeded)?;
-
-    // Store car and cdr values
-    unsafe {
-        let data = vm.memory.get_data_mut(pair_ptr);
-
-        // Convert values to u32 representation
-        let car_bytes = value_to_u32(&car);
-        let cdr_bytes = value_to_u32(&cdr);
-
-        // Write car value (first 4 bytes)
-        data[0..4].copy_from_slice(&car_bytes.to_le_bytes());
-
-        // Write cdr value (next 4 bytes)
-        data[4..8].copy_from_slice(&cdr_bytes.to_le_bytes());
+    if vm.stack.len() < 2 {
+        return Err(VmError::StackUnderflow);
     }
 
+    // Car and cdr stay on the stack until written, so a collection
+    // triggered by allocating the pair or boxing either keeps them alive
+    let pair_ptr = vm.allocate_object(PAIR_SIZE as u32, TAG_LIST)?;
+    let car = vm.stack[vm.stack.len() - 2].clone();
+    let pair_ptr = write_slot(vm, pair_ptr, 0, &car)?;
+    let cdr = vm.stack[vm.stack.len() - 1].clone();
+    let pair_ptr = write_slot(vm, pair_ptr, 1, &cdr)?;
+    vm.stack.truncate(vm.stack.len() - 2);
+
     // Push the pair pointer
     vm.stack.push(Value::Pair(pair_ptr));
     Ok(())
@@ -41,74 +33,56 @@ pub fn handle_cons(vm: &mut VmState) -> Result<(), VmError> {
 /// Get the car (first element) of a pair
 pub fn handle_car(vm: &mut VmState) -> Result<(), VmError> {
     let pair = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
-
-    match pair {
-        Value::Pair(ptr) => {
-            // Read car value from the pair
-            unsafe {
-                let data = vm.memory.get_data(ptr);
-                let car_bytes = &data[0..4];
-                let car_value = u32::from_le_bytes(car_bytes.try_into().unwrap());
-                let car = u32_to_value(car_value);
-                vm.stack.push(car);
-            }
-            Ok(())
-        }
-        _ => Err(VmError::TypeMismatch),
-    }
+    let car = read_slot(vm, pair_ptr(&pair)?, 0)?;
+    vm.stack.push(car);
+    Ok(())
 }
 
 /// Get the cdr (rest of the list) of a pair
 pub fn handle_cdr(vm: &mut VmState) -> Result<(), VmError> {
     let pair = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let cdr = read_slot(vm, pair_ptr(&pair)?, 1)?;
+    vm.stack.push(cdr);
+    Ok(())
+}
 
-    match pair {
-        Value::Pair(ptr) => {
-            // Read cdr value from the pair
-            unsafe {
-                let data = vm.memory.get_data(ptr);
-                let cdr_bytes = &data[4..8];
-                let cdr_value = u32::from_le_bytes(cdr_bytes.try_into().unwrap());
-                let cdr = u32_to_value(cdr_value);
-                vm.stack.push(cdr);
-            }
-            Ok(())
+/// Replace a list with its number of elements: 0 for nil, otherwise the
+/// number of pairs reached by following cdrs until a nil cdr.
+///
+/// A cdr that is neither nil nor a pair ends an improper list, which is a
+/// `TypeMismatch`, as is a value that is neither nil nor a pair. So is a
+/// cyclic list, detected once the walk has visited more pairs than the
+/// arena could hold.
+pub fn handle_list_len(vm: &mut VmState) -> Result<(), VmError> {
+    let list = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let mut current = match list {
+        Value::Nil => {
+            vm.stack.push(Value::Int(0));
+            return Ok(());
         }
-        _ => Err(VmError::TypeMismatch),
-    }
-}
+        Value::Pair(ptr) => ptr,
+        _ => return Err(VmError::TypeMismatch),
+    };
 
-/// Convert a Value to a u32 representation (simplified for basic types)
-fn value_to_u32(value: &Value) -> u32 {
-    match value {
-        Value::Int(n) => *n as u32,
-        Value::Float(f) => *f as u32, // Convert float to u32 for storage
-        Value::Bool(b) => {
-            if *b {
-                1
-            } else {
-                0
-            }
+    let pair_footprint = ObjectHeader::size_bytes() + PAIR_SIZE;
+    let max_pairs = vm.memory.next_free() as usize / pair_footprint;
+    let mut length = 1;
+    loop {
+        match read_slot(vm, current, 1)? {
+            Value::Nil => break,
+            Value::Pair(next) if length < max_pairs => current = next,
+            _ => return Err(VmError::TypeMismatch),
         }
-        Value::Nil => 0,
-        Value::String(_) => 0, // Strings handled through constant pool, not stored directly
-        Value::Symbol(s) => *s as u32,
-        Value::ActorId(id) => *id,
-        Value::Pair(ptr) => ptr.get(),
-        Value::Closure(ptr) => ptr.get(),
-        Value::Capability(_) => 0, // Placeholder
-        Value::GcPtr(ptr) => ptr.0 as u32,
-        Value::Error(_) => 0, // Errors stored as 0
+        length += 1;
     }
+
+    vm.stack.push(Value::Int(length as i64));
+    Ok(())
 }
 
-/// Convert a u32 back to a Value (simplified)
-fn u32_to_value(value: u32) -> Value {
-    // This is a simplified conversion - in a real implementation,
-    // you'd need type information to properly reconstruct values
-    if value == 0 {
-        Value::Nil
-    } else {
-        Value::Int(value as i64)
+fn pair_ptr(value: &Value) -> Result<HeapPtr, VmError> {
+    match value {
+        Value::Pair(ptr) => Ok(*ptr),
+        _ => Err(VmError::TypeMismatch),
     }
 }
@@ -1,5 +1,7 @@
 /// MakeClosure opcode handler - creates closures with proper environment capture
+use crate::memory::arena::{closure_size, CAPTURE_HEAP, TAG_CLOSURE, TAG_CLOSURE_BODY};
 use crate::types::{HeapPtr, OpCode, Value};
+use crate::vm::opcodes::closure_text::{parse_closure_body, CLOSURE_BODY_PREFIX};
 use crate::vm::state::VmError;
 use crate::vm::state::VmState;
 use bincode;
@@ -23,6 +25,15 @@ pub fn handle_make_closure(
         return Err(VmError::StackUnderflow);
     }
 
+    // Once compiler metadata is attached, the closure must capture exactly
+    // the free variables escape analysis found escaping into it
+    if !vm.function_table.is_empty() {
+        check_captures(vm, code_idx, capture_count)?;
+    }
+
+    // A textual body is parsed once and its constant replaced by the body
+    materialize_closure_body(vm, code_idx)?;
+
     // 2. Handle zero-capture closures by reusing from constant pool (optimization for recursion)
     if capture_count == 0 {
         match vm.constant_pool.get(code_idx) {
@@ -35,11 +46,15 @@ pub fn handle_make_closure(
                 );
                 // Create a proper closure wrapper with the body pointer from constant pool
                 // The Call handler reads bytes 0-4 to get the body pointer
-                let size = 4; // Just the body pointer, no captures
-                let closure_ptr = vm
-                    .memory
-                    .allocate(size, 2) // Tag 2 for closures
-                    .map_err(|_| VmError::MemoryLimitExceeded)?;
+                let size = closure_size(0); // Just the body pointer, no captures
+                let closure_ptr = vm.allocate_object(size, TAG_CLOSURE)?;
+
+                // A collection while allocating may have moved the body, so
+                // read it back from the constant pool
+                let body_ptr = match vm.constant_pool.get(code_idx) {
+                    Some(Value::Closure(body_ptr)) => *body_ptr,
+                    _ => return Err(VmError::InvalidHeapPtr),
+                };
 
                 // Store body pointer in closure wrapper
                 let data = unsafe { vm.memory.get_data_mut(closure_ptr) };
@@ -48,15 +63,6 @@ pub fn handle_make_closure(
 
                 return Ok(Value::Closure(closure_ptr));
             }
-            Some(Value::String(bytecode_str)) if bytecode_str.starts_with("closure_body:") => {
-                // For string-based bytecode, parse and create the closure body
-                let bytecode_str = &bytecode_str["closure_body:".len()..];
-                if let Ok(bytecode) = parse_bytecode_from_string(bytecode_str) {
-                    let body_ptr = create_closure_body(vm, bytecode)?;
-                    return Ok(Value::Closure(body_ptr));
-                }
-                // Fall through to default creation
-            }
             _ => {
                 // Fall through to default creation
             }
@@ -64,40 +70,32 @@ pub fn handle_make_closure(
     }
 
     // 3. Check if we have a proper closure body in the constant pool
-    let closure_body_value = match vm.constant_pool.get(code_idx) {
-        Some(Value::Closure(body_ptr)) => *body_ptr,
-        Some(Value::String(bytecode_str)) => {
-            // Handle serialized bytecode stored as string constants
-            // This is used by Jue-World compiler to store lambda bodies
-            if bytecode_str.starts_with("closure_body:") {
-                // Extract the bytecode from the string representation
-                let bytecode_str = &bytecode_str["closure_body:".len()..];
+    let has_body = matches!(vm.constant_pool.get(code_idx), Some(Value::Closure(_)));
+    if !has_body {
+        // For simple test cases, create a default identity function
+        // This handles cases where the constant pool has placeholder values.
+        // It stays on the stack while the closure is allocated so a
+        // collection keeps it alive.
+        let body_ptr = create_default_identity_closure(vm, capture_count)?;
+        vm.stack.push(Value::Closure(body_ptr));
+    }
 
-                // Try to parse the bytecode from the debug string representation
-                if let Ok(bytecode) = parse_bytecode_from_string(bytecode_str) {
-                    create_closure_body(vm, bytecode)?
-                } else {
-                    // Fall back to default identity function if parsing fails
-                    create_default_identity_closure(vm, capture_count)?
-                }
-            } else {
-                // Fall back to default identity function for unknown string format
-                create_default_identity_closure(vm, capture_count)?
-            }
-        }
-        Some(_) | None => {
-            // For simple test cases, create a default identity function
-            // This handles cases where the constant pool has placeholder values
-            create_default_identity_closure(vm, capture_count)?
-        }
-    };
+    // 4. Calculate closure size (4 bytes body ptr + 4 bytes and a kind byte
+    //    per captured value)
+    let size = closure_size(capture_count);
+    let allocated = vm.allocate_object(size, TAG_CLOSURE);
 
-    // 4. Calculate closure size (4 bytes body ptr + 4 bytes per captured value)
-    let size = 4 + (capture_count as u32 * 4);
-    let closure_ptr = vm
-        .memory
-        .allocate(size, 2) // Tag 2 for closures
-        .map_err(|_| VmError::MemoryLimitExceeded)?;
+    // The body is read only now, as a collection may have moved it
+    let closure_body_value = if has_body {
+        vm.constant_pool.get(code_idx).cloned()
+    } else {
+        vm.stack.pop()
+    };
+    let closure_ptr = allocated?;
+    let closure_body_value = match closure_body_value {
+        Some(Value::Closure(body_ptr)) => body_ptr,
+        _ => return Err(VmError::InvalidHeapPtr),
+    };
 
     // 5. Store closure body pointer and captured values
     let data = unsafe { vm.memory.get_data_mut(closure_ptr) };
@@ -106,12 +104,22 @@ pub fn handle_make_closure(
     let body_ptr_bytes = closure_body_value.get().to_le_bytes();
     data[0..4].copy_from_slice(&body_ptr_bytes);
 
-    // Store captured values (next capture_count * 4 bytes)
+    // Store captured values (next capture_count * 4 bytes), then a kind byte
+    // per capture so the collector only follows the ones that are pointers
+    let kinds = 4 + capture_count * 4;
     for i in 0..capture_count {
         let value = &vm.stack[vm.stack.len() - (capture_count - i)];
+        if matches!(
+            value,
+            Value::Pair(_) | Value::Closure(_) | Value::Vector(_) | Value::BigInt(_)
+        ) {
+            data[kinds + i] = CAPTURE_HEAP;
+        }
         let value_bytes = match value {
             Value::Pair(p) => p.get().to_le_bytes(),
             Value::Closure(p) => p.get().to_le_bytes(),
+            Value::Vector(p) => p.get().to_le_bytes(),
+            Value::BigInt(p) => p.get().to_le_bytes(),
             Value::Int(n) => (*n as u32).to_le_bytes(),
             Value::Float(f) => (*f as u32).to_le_bytes(), // Convert float to u32 for storage
             Value::Bool(b) => (*b as u32).to_le_bytes(),
@@ -122,6 +130,9 @@ pub fn handle_make_closure(
             Value::Capability(_) => 0u32.to_le_bytes(), // Placeholder
             Value::GcPtr(p) => (p.0 as u32).to_le_bytes(),
             Value::Error(_) => 0u32.to_le_bytes(), // Errors stored as 0
+            Value::ErrorRecord { .. } => 0u32.to_le_bytes(), // Like plain errors
+            Value::Bytes(_) => 0u32.to_le_bytes(), // Bytes stored as 0, like strings
+            Value::Thunk(p) => (p.0 as u32).to_le_bytes(),
         };
         let start = 4 + (i * 4);
         data[start..start + 4].copy_from_slice(&value_bytes);
@@ -135,6 +146,43 @@ pub fn handle_make_closure(
     Ok(Value::Closure(closure_ptr))
 }
 
+/// Check `capture_count` against the escaping free variables recorded for
+/// function `code_idx`.
+///
+/// Returns `VmError::UnknownFunction` if the table has no entry for the
+/// function and `VmError::CaptureCountMismatch` if the counts differ.
+fn check_captures(vm: &VmState, code_idx: usize, capture_count: usize) -> Result<(), VmError> {
+    let function =
+        u16::try_from(code_idx).map_err(|_| VmError::UnknownFunction { function: u16::MAX })?;
+    let expected = vm.get_function_info(function)?.captured_locals().len();
+    if expected != capture_count {
+        return Err(VmError::CaptureCountMismatch {
+            function,
+            expected,
+            actual: capture_count,
+        });
+    }
+    Ok(())
+}
+
+/// Replace a `closure_body:` string constant at `code_idx` with the closure
+/// body it describes.
+///
+/// Every closure made from that constant afterwards shares the body, so a
+/// body can refer to itself with `MakeClosure(code_idx, 0)` to recurse.
+/// Returns `VmError::TypeMismatch` if the string is malformed.
+fn materialize_closure_body(vm: &mut VmState, code_idx: usize) -> Result<(), VmError> {
+    let bytecode = match vm.constant_pool.get(code_idx) {
+        Some(Value::String(text)) if text.starts_with(CLOSURE_BODY_PREFIX) => {
+            parse_closure_body(text)?
+        }
+        _ => return Ok(()),
+    };
+    let body_ptr = create_closure_body(vm, bytecode)?;
+    vm.constant_pool[code_idx] = Value::Closure(body_ptr);
+    Ok(())
+}
+
 /// Creates a default identity closure for simple test cases
 fn create_default_identity_closure(
     vm: &mut VmState,
@@ -156,10 +204,7 @@ pub fn create_closure_body(vm: &mut VmState, bytecode: Vec<OpCode>) -> Result<He
     let size = 4 + serialized.len() as u32;
 
     // 3. Allocate memory for closure body
-    let body_ptr = vm
-        .memory
-        .allocate(size, 2) // Tag 2 for closure bodies
-        .map_err(|_| VmError::MemoryLimitExceeded)?;
+    let body_ptr = vm.allocate_object(size, TAG_CLOSURE_BODY)?;
 
     // 4. Store size and bytecode
     let data = unsafe { vm.memory.get_data_mut(body_ptr) };
@@ -168,185 +213,3 @@ pub fn create_closure_body(vm: &mut VmState, bytecode: Vec<OpCode>) -> Result<He
     data[4..4 + serialized.len()].copy_from_slice(&serialized);
     Ok(body_ptr)
 }
-
-/// Parse bytecode from debug string representation - FIXED: Added missing opcodes
-fn parse_bytecode_from_string(bytecode_str: &str) -> Result<Vec<OpCode>, VmError> {
-    // Debug: print what we're trying to parse
-    eprintln!("DEBUG: Parsing bytecode from string: '{}'", bytecode_str);
-
-    // This is a simple parser for the debug string representation of bytecode
-    // Format: [Op1, Op2, Op3(...), ...]
-
-    if bytecode_str.trim() == "[]" {
-        return Ok(vec![]);
-    }
-
-    // Remove brackets and split by commas
-    let mut bytecode = Vec::new();
-    let content = bytecode_str.trim().trim_matches(&['[', ']']);
-
-    if content.is_empty() {
-        return Ok(bytecode);
-    }
-
-    // Split by commas and parse each opcode
-    for op_str in content.split(',').map(|s| s.trim()) {
-        if op_str.is_empty() {
-            continue;
-        }
-
-        // Debug: print each opcode string we're trying to parse
-        eprintln!("DEBUG: Parsing opcode: '{}'", op_str);
-
-        // Parse common opcodes from their debug string representation
-        let opcode = match op_str {
-            "Ret" => OpCode::Ret,
-            "GetLocal(0)" => OpCode::GetLocal(0),
-            "GetLocal(1)" => OpCode::GetLocal(1),
-            "Int(0)" => OpCode::Int(0),
-            "Int(1)" => OpCode::Int(1),
-            "Int(2)" => OpCode::Int(2),
-            "Int(5)" => OpCode::Int(5),
-            "Lte" => OpCode::Lte,
-            "Mul" => OpCode::Mul,
-            "Div" => OpCode::Div,
-            "Sub" => OpCode::Sub,
-            "Add" => OpCode::Add, // FIXED: Added Add opcode
-            "Eq" => OpCode::Eq,   // FIXED: Added Eq opcode
-            "SetLocal(0)" => OpCode::SetLocal(0),
-            "SetLocal(1)" => OpCode::SetLocal(1),
-            "Float(1.0)" => OpCode::Float(1.0),
-            "Bool(true)" => OpCode::Bool(true),
-            "Bool(false)" => OpCode::Bool(false),
-            "LoadString(0)" => OpCode::LoadString(0),
-            "Call(1)" => OpCode::Call(1),
-            "Call(2)" => OpCode::Call(2),
-            "TailCall(1)" => OpCode::TailCall(1),
-            "TailCall(2)" => OpCode::TailCall(2),
-            "JmpIfFalse(0)" => OpCode::JmpIfFalse(0),
-            "JmpIfFalse(2)" => OpCode::JmpIfFalse(2),
-            "Jmp(0)" => OpCode::Jmp(0),
-            "Jmp(9)" => OpCode::Jmp(9),
-            "Jmp(14)" => OpCode::Jmp(14),
-            "Jmp(6)" => OpCode::Jmp(6),
-            "Jmp(12)" => OpCode::Jmp(12),
-            _ => {
-                // For unknown opcodes, try to parse more complex patterns
-                if op_str.starts_with("GetLocal(") && op_str.ends_with(')') {
-                    if let Some(num_str) = op_str.get("GetLocal(".len()..op_str.len() - 1) {
-                        if let Ok(num) = num_str.parse::<u16>() {
-                            eprintln!("DEBUG: Successfully parsed GetLocal({})", num);
-                            OpCode::GetLocal(num)
-                        } else {
-                            eprintln!("DEBUG: Failed to parse GetLocal number: {}", num_str);
-                            return Err(VmError::TypeMismatch);
-                        }
-                    } else {
-                        eprintln!("DEBUG: Failed to parse GetLocal format");
-                        return Err(VmError::TypeMismatch);
-                    }
-                } else if op_str.starts_with("Int(") && op_str.ends_with(')') {
-                    if let Some(num_str) = op_str.get("Int(".len()..op_str.len() - 1) {
-                        if let Ok(num) = num_str.parse::<i32>() {
-                            eprintln!("DEBUG: Successfully parsed Int({})", num);
-                            OpCode::Int(num as i64)
-                        } else {
-                            eprintln!("DEBUG: Failed to parse Int number: {}", num_str);
-                            return Err(VmError::TypeMismatch);
-                        }
-                    } else {
-                        eprintln!("DEBUG: Failed to parse Int format");
-                        return Err(VmError::TypeMismatch);
-                    }
-                } else if op_str.starts_with("Float(") && op_str.ends_with(')') {
-                    if let Some(num_str) = op_str.get("Float(".len()..op_str.len() - 1) {
-                        if let Ok(num) = num_str.parse::<f64>() {
-                            eprintln!("DEBUG: Successfully parsed Float({})", num);
-                            OpCode::Float(num)
-                        } else {
-                            eprintln!("DEBUG: Failed to parse Float number: {}", num_str);
-                            return Err(VmError::TypeMismatch);
-                        }
-                    } else {
-                        eprintln!("DEBUG: Failed to parse Float format");
-                        return Err(VmError::TypeMismatch);
-                    }
-                } else if op_str.starts_with("Call(") && op_str.ends_with(')') {
-                    if let Some(num_str) = op_str.get("Call(".len()..op_str.len() - 1) {
-                        if let Ok(num) = num_str.parse::<u16>() {
-                            eprintln!("DEBUG: Successfully parsed Call({})", num);
-                            OpCode::Call(num)
-                        } else {
-                            eprintln!("DEBUG: Failed to parse Call number: {}", num_str);
-                            return Err(VmError::TypeMismatch);
-                        }
-                    } else {
-                        eprintln!("DEBUG: Failed to parse Call format");
-                        return Err(VmError::TypeMismatch);
-                    }
-                } else if op_str.starts_with("TailCall(") && op_str.ends_with(')') {
-                    if let Some(num_str) = op_str.get("TailCall(".len()..op_str.len() - 1) {
-                        if let Ok(num) = num_str.parse::<u16>() {
-                            eprintln!("DEBUG: Successfully parsed TailCall({})", num);
-                            OpCode::TailCall(num)
-                        } else {
-                            eprintln!("DEBUG: Failed to parse TailCall number: {}", num_str);
-                            return Err(VmError::TypeMismatch);
-                        }
-                    } else {
-                        eprintln!("DEBUG: Failed to parse TailCall format");
-                        return Err(VmError::TypeMismatch);
-                    }
-                } else if op_str.starts_with("JmpIfFalse(") && op_str.ends_with(')') {
-                    if let Some(num_str) = op_str.get("JmpIfFalse(".len()..op_str.len() - 1) {
-                        if let Ok(num) = num_str.parse::<i16>() {
-                            eprintln!("DEBUG: Successfully parsed JmpIfFalse({})", num);
-                            OpCode::JmpIfFalse(num)
-                        } else {
-                            eprintln!("DEBUG: Failed to parse JmpIfFalse number: {}", num_str);
-                            return Err(VmError::TypeMismatch);
-                        }
-                    } else {
-                        eprintln!("DEBUG: Failed to parse JmpIfFalse format");
-                        return Err(VmError::TypeMismatch);
-                    }
-                } else if op_str.starts_with("Jmp(") && op_str.ends_with(')') {
-                    if let Some(num_str) = op_str.get("Jmp(".len()..op_str.len() - 1) {
-                        if let Ok(num) = num_str.parse::<i16>() {
-                            eprintln!("DEBUG: Successfully parsed Jmp({})", num);
-                            OpCode::Jmp(num)
-                        } else {
-                            eprintln!("DEBUG: Failed to parse Jmp number: {}", num_str);
-                            return Err(VmError::TypeMismatch);
-                        }
-                    } else {
-                        eprintln!("DEBUG: Failed to parse Jmp format");
-                        return Err(VmError::TypeMismatch);
-                    }
-                } else if op_str.starts_with("SetLocal(") && op_str.ends_with(')') {
-                    if let Some(num_str) = op_str.get("SetLocal(".len()..op_str.len() - 1) {
-                        if let Ok(num) = num_str.parse::<u16>() {
-                            eprintln!("DEBUG: Successfully parsed SetLocal({})", num);
-                            OpCode::SetLocal(num)
-                        } else {
-                            eprintln!("DEBUG: Failed to parse SetLocal number: {}", num_str);
-                            return Err(VmError::TypeMismatch);
-                        }
-                    } else {
-                        eprintln!("DEBUG: Failed to parse SetLocal format");
-                        return Err(VmError::TypeMismatch);
-                    }
-                } else {
-                    eprintln!("DEBUG: Unknown opcode: {}", op_str);
-                    // For now, return an error for unknown opcodes
-                    return Err(VmError::TypeMismatch);
-                }
-            }
-        };
-
-        bytecode.push(opcode);
-    }
-
-    eprintln!("DEBUG: Successfully parsed {} opcodes", bytecode.len());
-    Ok(bytecode)
-}
@@ -1,5 +1,5 @@
-/// Send operation handler for inter-actor communication
-use crate::types::Value;
+/// Actor opcode handlers: sending messages and spawning actors
+use crate::types::{Capability, Value};
 use crate::vm::state::{InstructionResult, VmError, VmState};
 
 /// Send a message to another actor
@@ -9,16 +9,8 @@ pub fn handle_send(vm: &mut VmState) -> Result<InstructionResult, VmError> {
 
     match target_actor {
         Value::ActorId(actor_id) => {
-            // In a real implementation, this would:
-            // 1. Check if the current actor has permission to send to target_actor
-            // 2. Deliver the message to the target actor's mailbox
-            // 3. Update the sender's message log/statistics
-
-            // For now, we'll just log that a message was sent
-            eprintln!(
-                "Send: Actor {} sending message {:?} to actor {}",
-                vm.actor_id, message, actor_id
-            );
+            // Queue the message; the scheduler delivers it to the target's inbox
+            vm.outbox.push((actor_id, message));
 
             // Continue execution - message sending is non-blocking
             Ok(InstructionResult::Continue)
@@ -26,3 +18,20 @@ pub fn handle_send(vm: &mut VmState) -> Result<InstructionResult, VmError> {
         _ => Err(VmError::TypeMismatch),
     }
 }
+
+/// Pop a closure and its arguments and ask the scheduler to run them as a new actor
+pub fn handle_spawn(vm: &mut VmState, arg_count: u16) -> Result<InstructionResult, VmError> {
+    if !vm.has_capability(&Capability::SysCreateActor) {
+        return Err(VmError::CapabilityDenied);
+    }
+    if vm.stack.len() <= arg_count as usize {
+        return Err(VmError::StackUnderflow);
+    }
+
+    let closure = vm.stack.pop().unwrap();
+    if !matches!(closure, Value::Closure(_)) {
+        return Err(VmError::TypeMismatch);
+    }
+    let args = vm.stack.split_off(vm.stack.len() - arg_count as usize);
+    Ok(InstructionResult::Spawn { closure, args })
+}
@@ -5,7 +5,12 @@ pub mod basic;
 pub mod call;
 pub mod capability;
 pub mod closure;
+pub mod closure_eq;
+pub mod closure_text;
 pub mod comparison;
+pub mod deep_clone;
+pub mod error_ops;
+pub mod gc_ops;
 pub mod jump;
 pub mod list_ops;
 pub mod make_closure;
@@ -13,3 +18,6 @@ pub mod messaging;
 pub mod ret;
 pub mod stack_ops;
 pub mod string_ops;
+pub mod thunk;
+pub mod type_ops;
+pub mod vector_ops;
@@ -1,6 +1,7 @@
 /// Ret opcode handler - implements proper function return system
 /// Handles stack frame cleanup and return value propagation
 use crate::types::Value;
+use crate::vm::opcodes::thunk::memoize;
 use crate::vm::state::{InstructionResult, VmError, VmState};
 
 /// Handles the Ret opcode with proper stack frame management
@@ -27,6 +28,12 @@ pub fn handle_ret(vm: &mut VmState) -> Result<InstructionResult, VmError> {
 
     // 2. Get the call frame
     let call_frame = vm.call_stack.pop().unwrap();
+    if let Some(expected) = call_frame.expected_results.filter(|&n| n != 1) {
+        return Err(VmError::ReturnCountMismatch {
+            expected,
+            actual: 1,
+        });
+    }
     eprintln!(
         "DEBUG RET: return_ip={}, original_stack_size={}",
         call_frame.return_ip, call_frame.original_stack_size
@@ -40,6 +47,9 @@ pub fn handle_ret(vm: &mut VmState) -> Result<InstructionResult, VmError> {
     } else {
         Value::Nil
     };
+    if let Some(thunk) = call_frame.forcing {
+        memoize(vm, thunk, &return_value);
+    }
     eprintln!("DEBUG RET: return_value = {:?}", return_value);
     eprintln!("DEBUG RET: Stack after pop: {:?}", vm.stack);
 
@@ -70,3 +80,50 @@ pub fn handle_ret(vm: &mut VmState) -> Result<InstructionResult, VmError> {
 
     Ok(InstructionResult::Continue)
 }
+
+/// Handles the RetN opcode, returning the top `count` values to the caller
+///
+/// The values keep their order and replace everything the callee left on
+/// its part of the stack. A frame entered through `Call` expects exactly one
+/// result and one entered through `CallN` expects the count it was given;
+/// any other count is a `ReturnCountMismatch`.
+pub fn handle_ret_n(vm: &mut VmState, count: u16) -> Result<InstructionResult, VmError> {
+    let n = count as usize;
+    if vm.stack.len() < n {
+        return Err(VmError::StackUnderflow);
+    }
+
+    // Returning from the main program leaves the results on the stack
+    let Some(call_frame) = vm.call_stack.pop() else {
+        let top = vm.stack.last().cloned().unwrap_or(Value::Nil);
+        return Ok(InstructionResult::Finished(top));
+    };
+
+    let expected = call_frame.expected_results.unwrap_or(1);
+    if expected != count {
+        return Err(VmError::ReturnCountMismatch {
+            expected,
+            actual: count,
+        });
+    }
+
+    // Copy exactly N values back over the callee's stack region
+    let results = vm.stack.split_off(vm.stack.len() - n);
+    if let (Some(thunk), [result]) = (call_frame.forcing, results.as_slice()) {
+        memoize(vm, thunk, result);
+    }
+    vm.stack.truncate(call_frame.stack_start);
+    vm.stack.extend(results);
+
+    if vm.call_stack.is_empty() {
+        let top = vm.stack.last().cloned().unwrap_or(Value::Nil);
+        return Ok(InstructionResult::Finished(top));
+    }
+
+    vm.ip = call_frame.return_ip;
+    if let Some(saved_instructions) = call_frame.saved_instructions {
+        vm.instructions = saved_instructions;
+    }
+
+    Ok(InstructionResult::Continue)
+}
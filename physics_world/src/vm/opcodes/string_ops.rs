@@ -1,4 +1,4 @@
-/// String opcode handlers - LoadString, StrLen, StrConcat, StrIndex
+/// String opcode handlers - LoadString, StrLen, StrConcat, StrIndex, BytesToStr, StrToBytes
 use crate::types::Value;
 use crate::vm::state::VmError;
 use crate::vm::state::VmState;
@@ -60,3 +60,32 @@ pub fn handle_str_index(vm: &mut VmState) -> Result<(), VmError> {
         _ => Err(VmError::TypeMismatch),
     }
 }
+
+/// Handles BytesToStr opcode - decodes a byte string as UTF-8.
+///
+/// Invalid UTF-8 is not a VM fault: an `Error` value is pushed instead so the
+/// program can test for it and keep the bytes it already has.
+pub fn handle_bytes_to_str(vm: &mut VmState) -> Result<(), VmError> {
+    match vm.stack.pop().ok_or(VmError::StackUnderflow)? {
+        Value::Bytes(bytes) => {
+            let decoded = match String::from_utf8(bytes) {
+                Ok(string) => Value::String(string),
+                Err(error) => Value::Error(format!("Invalid UTF-8: {}", error.utf8_error())),
+            };
+            vm.stack.push(decoded);
+            Ok(())
+        }
+        _ => Err(VmError::TypeMismatch),
+    }
+}
+
+/// Handles StrToBytes opcode - encodes a string as its UTF-8 bytes
+pub fn handle_str_to_bytes(vm: &mut VmState) -> Result<(), VmError> {
+    match vm.stack.pop().ok_or(VmError::StackUnderflow)? {
+        Value::String(string) => {
+            vm.stack.push(Value::Bytes(string.into_bytes()));
+            Ok(())
+        }
+        _ => Err(VmError::TypeMismatch),
+    }
+}
@@ -0,0 +1,92 @@
+/// Thunk opcode handlers - MakeThunk and Force
+///
+/// A thunk delays a zero-argument closure call until its value is needed,
+/// giving call-by-need semantics: a thunk that is never forced never runs,
+/// and one that is forced runs once, with later forces reusing the result.
+///
+/// Thunks live in the GC heap. The collector does not scan the VM stack, so
+/// each thunk is registered as a root when it is made. Once it is forced,
+/// the VM's references to it are replaced by its result and the root is
+/// dropped; references from other heap objects keep it alive after that.
+use crate::types::Value;
+use crate::vm::gc::{GcPtr, HeapObject, Thunk};
+use crate::vm::gc_integration::GcIntegration;
+use crate::vm::opcodes::call::{call_closure, preserved_stack_len};
+use crate::vm::state::{VmError, VmState};
+
+/// Replace the closure on top of the stack with an unevaluated thunk
+pub fn handle_make_thunk(vm: &mut VmState) -> Result<(), VmError> {
+    let closure = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    if !matches!(closure, Value::Closure(_)) {
+        vm.stack.push(closure);
+        return Err(VmError::TypeMismatch);
+    }
+
+    let thunk = HeapObject::Thunk(Thunk::Pending(closure));
+    // Allocating in the VM's GC mode fails only while the GC is disabled
+    let allocated =
+        GcIntegration::allocate_heap_object(vm, thunk).map_err(|_| VmError::MemoryLimitExceeded)?;
+    let Value::GcPtr(ptr) = allocated else {
+        return Err(VmError::InvalidHeapPtr);
+    };
+    vm.add_gc_root(ptr, "thunk");
+    vm.stack.push(Value::Thunk(ptr));
+    Ok(())
+}
+
+/// Force the value on top of the stack
+///
+/// A forced thunk pushes its memoized result and continues at the next
+/// instruction. A pending thunk calls its closure, and the matching `Ret`
+/// stores the result in the thunk before handing it back. Any other value
+/// is already evaluated and is left as it is.
+///
+/// Returns whether a call was started, in which case the call has set `ip`.
+pub fn handle_force(vm: &mut VmState) -> Result<bool, VmError> {
+    let value = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let Value::Thunk(ptr) = value else {
+        vm.stack.push(value);
+        return Ok(false);
+    };
+
+    match vm.gc.heap.get(ptr.0) {
+        Some(HeapObject::Thunk(Thunk::Forced(result))) => {
+            let result = result.clone();
+            vm.stack.push(result);
+            Ok(false)
+        }
+        Some(HeapObject::Thunk(Thunk::Pending(closure))) => {
+            let closure = closure.clone();
+            // A thunk is forced mid-expression, so unlike a Call the caller's
+            // operands must survive the call rather than be cut back to its locals
+            let operands = vm
+                .stack
+                .split_off(preserved_stack_len(vm, 0).min(vm.stack.len()));
+            vm.stack.push(closure);
+            call_closure(vm, 0, None)?;
+            vm.stack.extend(operands);
+            let stack_len = vm.stack.len();
+            if let Some(frame) = vm.call_stack.last_mut() {
+                frame.stack_start = stack_len;
+                frame.original_stack_size = stack_len;
+                frame.forcing = Some(ptr);
+            }
+            Ok(true)
+        }
+        _ => Err(VmError::InvalidHeapPtr),
+    }
+}
+
+/// Record `result` as the value of the thunk at `ptr` and unroot it
+pub(crate) fn memoize(vm: &mut VmState, ptr: GcPtr, result: &Value) {
+    if let Some(HeapObject::Thunk(thunk)) = vm.gc.heap.get_mut(ptr.0) {
+        *thunk = Thunk::Forced(result.clone());
+    }
+    vm.gc.write_barrier(ptr, result);
+    for value in GcIntegration::vm_values_mut(vm) {
+        if *value == Value::Thunk(ptr) {
+            *value = result.clone();
+        }
+    }
+    vm.remove_gc_root(ptr);
+}
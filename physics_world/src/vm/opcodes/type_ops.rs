@@ -0,0 +1,37 @@
+/// Type test opcode handlers - TypeOf, IsInt, IsFloat, IsPair, IsClosure
+///
+/// Each handler pops one value and pushes what it found out about its
+/// variant, so source-level predicates such as `integer?` need no host call.
+use crate::types::Value;
+use crate::vm::state::{VmError, VmState};
+
+/// Replace the top value with its `Value::type_tag` as an `Int`
+pub fn handle_type_of(vm: &mut VmState) -> Result<(), VmError> {
+    let value = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    vm.stack.push(Value::Int(i64::from(value.type_tag())));
+    Ok(())
+}
+
+/// Replace the top value with whether `test` holds for it
+pub fn handle_type_test(vm: &mut VmState, test: fn(&Value) -> bool) -> Result<(), VmError> {
+    let value = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    vm.stack.push(Value::Bool(test(&value)));
+    Ok(())
+}
+
+/// Fixed-size and arbitrary-precision integers
+pub fn is_int(value: &Value) -> bool {
+    matches!(value, Value::Int(_) | Value::BigInt(_))
+}
+
+pub fn is_float(value: &Value) -> bool {
+    matches!(value, Value::Float(_))
+}
+
+pub fn is_pair(value: &Value) -> bool {
+    matches!(value, Value::Pair(_))
+}
+
+pub fn is_closure(value: &Value) -> bool {
+    matches!(value, Value::Closure(_))
+}
@@ -0,0 +1,230 @@
+/// Vector operation handlers - MakeVector, VecGet, VecSet, VecLen
+///
+/// A vector is a `TAG_VECTOR` arena object holding one fixed-size slot per
+/// element, so indexing is a single offset computation. A slot stores the
+/// element's bincode encoding inline when it fits; larger values (strings,
+/// errors, ...) are encoded into a separate `TAG_STRING` object and the slot
+/// holds its `HeapPtr`. Elements that are themselves arena objects are stored
+/// as a tagged `HeapPtr`, so the collector can tell them from plain numbers.
+/// Pairs use the same slots for their car and cdr.
+use crate::memory::arena::{
+    SLOT_BOXED, SLOT_HEAP, SLOT_INLINE, TAG_STRING, TAG_VECTOR, VECTOR_SLOT_SIZE,
+};
+use crate::types::{HeapPtr, Value};
+use crate::vm::state::{VmError, VmState};
+
+/// Largest encoding that fits in a slot after the kind and length bytes
+const INLINE_CAPACITY: usize = VECTOR_SLOT_SIZE - 2;
+
+/// Kinds of arena object a `SLOT_HEAP` slot can point at
+const HEAP_PAIR: u8 = 0;
+const HEAP_CLOSURE: u8 = 1;
+const HEAP_VECTOR: u8 = 2;
+const HEAP_BIGINT: u8 = 3;
+
+/// Create a vector from the top `count` stack values, first element deepest
+pub fn handle_make_vector(vm: &mut VmState, count: usize) -> Result<(), VmError> {
+    if vm.stack.len() < count {
+        return Err(VmError::StackUnderflow);
+    }
+
+    let size = u32::try_from(count * VECTOR_SLOT_SIZE).map_err(|_| VmError::MemoryLimitExceeded)?;
+    // Elements stay on the stack until written so a collection keeps them
+    let mut vector_ptr = vm.allocate_object(size, TAG_VECTOR)?;
+    let base = vm.stack.len() - count;
+
+    for index in 0..count {
+        let element = vm.stack[base + index].clone();
+        vector_ptr = write_slot(vm, vector_ptr, index, &element)?;
+    }
+
+    vm.stack.truncate(base);
+    vm.stack.push(Value::Vector(vector_ptr));
+    Ok(())
+}
+
+/// Replace `vector index` on the stack with the element at `index`
+pub fn handle_vec_get(vm: &mut VmState) -> Result<(), VmError> {
+    let index = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let vector = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let (ptr, index) = checked_index(vm, &vector, &index)?;
+
+    let element = read_slot(vm, ptr, index)?;
+    vm.stack.push(element);
+    Ok(())
+}
+
+/// Store the value on top of the stack at `index` and leave the vector on the stack.
+///
+/// Consumes `vector index value`; the vector is updated in place, so every
+/// reference to it observes the new element.
+pub fn handle_vec_set(vm: &mut VmState) -> Result<(), VmError> {
+    let value = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let index = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let vector = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let (ptr, index) = checked_index(vm, &vector, &index)?;
+
+    let ptr = write_slot(vm, ptr, index, &value)?;
+    vm.stack.push(Value::Vector(ptr));
+    Ok(())
+}
+
+/// Replace the vector on top of the stack with its length
+pub fn handle_vec_len(vm: &mut VmState) -> Result<(), VmError> {
+    let vector = vm.stack.pop().ok_or(VmError::StackUnderflow)?;
+    let ptr = vector_ptr(&vector)?;
+    let length = vector_len(vm, ptr);
+    vm.stack.push(Value::Int(length as i64));
+    Ok(())
+}
+
+/// Number of elements in the vector at `ptr`
+pub fn vector_len(vm: &VmState, ptr: HeapPtr) -> usize {
+    let size = unsafe { vm.memory.get_header(ptr) }.size as usize;
+    size / VECTOR_SLOT_SIZE
+}
+
+fn vector_ptr(value: &Value) -> Result<HeapPtr, VmError> {
+    match value {
+        Value::Vector(ptr) => Ok(*ptr),
+        _ => Err(VmError::TypeMismatch),
+    }
+}
+
+/// Check the operand types and that `index` is within the vector's bounds
+fn checked_index(vm: &VmState, vector: &Value, index: &Value) -> Result<(HeapPtr, usize), VmError> {
+    let ptr = vector_ptr(vector)?;
+    let Value::Int(index) = *index else {
+        return Err(VmError::TypeMismatch);
+    };
+    let length = vector_len(vm, ptr);
+    match usize::try_from(index) {
+        Ok(i) if i < length => Ok((ptr, i)),
+        _ => Err(VmError::IndexOutOfBounds { index, length }),
+    }
+}
+
+/// Store `value` at `index` of the vector or pair at `vector`.
+///
+/// A value too large to inline is boxed through `VmState::allocate_object`,
+/// which may run a collection; the vector is kept on the stack meanwhile so
+/// it survives, and the returned pointer is its address after the write.
+/// Callers must keep any value they still have to write rooted as well.
+pub(crate) fn write_slot(
+    vm: &mut VmState,
+    vector: HeapPtr,
+    index: usize,
+    value: &Value,
+) -> Result<HeapPtr, VmError> {
+    let (mut slot, boxed) = encode_slot(value)?;
+    let Some(encoded) = boxed else {
+        store_slot(vm, vector, index, &slot)?;
+        return Ok(vector);
+    };
+
+    let size = u32::try_from(encoded.len()).map_err(|_| VmError::MemoryLimitExceeded)?;
+    // The root only has to name the object; pairs are traced by their tag
+    vm.stack.push(Value::Vector(vector));
+    let boxed = vm.allocate_object(size, TAG_STRING);
+    let vector = match vm.stack.pop() {
+        Some(Value::Vector(ptr)) => ptr,
+        _ => return Err(VmError::StackUnderflow),
+    };
+    let boxed = boxed?;
+    unsafe { vm.memory.get_data_mut(boxed) }.copy_from_slice(&encoded);
+    slot[4..8].copy_from_slice(&boxed.get().to_le_bytes());
+    store_slot(vm, vector, index, &slot)?;
+    Ok(vector)
+}
+
+/// Like `write_slot`, but boxes straight into the arena so no collection
+/// can run, for callers holding pointers the collector does not know about
+pub(crate) fn write_slot_without_collecting(
+    vm: &mut VmState,
+    vector: HeapPtr,
+    index: usize,
+    value: &Value,
+) -> Result<(), VmError> {
+    let (mut slot, boxed) = encode_slot(value)?;
+    if let Some(encoded) = boxed {
+        let size = u32::try_from(encoded.len()).map_err(|_| VmError::MemoryLimitExceeded)?;
+        let boxed = vm
+            .memory
+            .allocate(size, TAG_STRING)
+            .map_err(|_| VmError::MemoryLimitExceeded)?;
+        unsafe { vm.memory.get_data_mut(boxed) }.copy_from_slice(&encoded);
+        slot[4..8].copy_from_slice(&boxed.get().to_le_bytes());
+    }
+    store_slot(vm, vector, index, &slot)
+}
+
+/// The slot for `value`, plus the encoding to box when it does not fit; the
+/// boxed object's pointer still has to be written into bytes 4..8
+fn encode_slot(value: &Value) -> Result<([u8; VECTOR_SLOT_SIZE], Option<Vec<u8>>), VmError> {
+    let mut slot = [0u8; VECTOR_SLOT_SIZE];
+    let heap = match value {
+        Value::Pair(ptr) => Some((HEAP_PAIR, ptr)),
+        Value::Closure(ptr) => Some((HEAP_CLOSURE, ptr)),
+        Value::Vector(ptr) => Some((HEAP_VECTOR, ptr)),
+        Value::BigInt(ptr) => Some((HEAP_BIGINT, ptr)),
+        _ => None,
+    };
+    if let Some((kind, ptr)) = heap {
+        slot[0] = SLOT_HEAP;
+        slot[1] = kind;
+        slot[4..8].copy_from_slice(&ptr.get().to_le_bytes());
+        return Ok((slot, None));
+    }
+
+    let encoded = bincode::serialize(value).map_err(|_| VmError::TypeMismatch)?;
+    if encoded.len() <= INLINE_CAPACITY {
+        slot[0] = SLOT_INLINE;
+        slot[1] = encoded.len() as u8;
+        slot[2..2 + encoded.len()].copy_from_slice(&encoded);
+        Ok((slot, None))
+    } else {
+        slot[0] = SLOT_BOXED;
+        Ok((slot, Some(encoded)))
+    }
+}
+
+fn store_slot(
+    vm: &mut VmState,
+    vector: HeapPtr,
+    index: usize,
+    slot: &[u8; VECTOR_SLOT_SIZE],
+) -> Result<(), VmError> {
+    let start = index * VECTOR_SLOT_SIZE;
+    let data = unsafe { vm.memory.get_data_mut(vector) };
+    data[start..start + VECTOR_SLOT_SIZE].copy_from_slice(slot);
+    Ok(())
+}
+
+pub(crate) fn read_slot(vm: &VmState, vector: HeapPtr, index: usize) -> Result<Value, VmError> {
+    let start = index * VECTOR_SLOT_SIZE;
+    let data = unsafe { vm.memory.get_data(vector) };
+    let slot = &data[start..start + VECTOR_SLOT_SIZE];
+
+    let encoded = match slot[0] {
+        SLOT_HEAP => {
+            let ptr = HeapPtr::new(u32::from_le_bytes([slot[4], slot[5], slot[6], slot[7]]));
+            return match slot[1] {
+                HEAP_PAIR => Ok(Value::Pair(ptr)),
+                HEAP_CLOSURE => Ok(Value::Closure(ptr)),
+                HEAP_VECTOR => Ok(Value::Vector(ptr)),
+                HEAP_BIGINT => Ok(Value::BigInt(ptr)),
+                _ => Err(VmError::InvalidHeapPtr),
+            };
+        }
+        SLOT_INLINE => &slot[2..2 + slot[1] as usize],
+        SLOT_BOXED => {
+            let boxed = u32::from_le_bytes([slot[4], slot[5], slot[6], slot[7]]);
+            if boxed >= vm.memory.next_free() {
+                return Err(VmError::InvalidHeapPtr);
+            }
+            unsafe { vm.memory.get_data(HeapPtr::new(boxed)) }
+        }
+        _ => return Err(VmError::InvalidHeapPtr),
+    };
+    bincode::deserialize(encoded).map_err(|_| VmError::InvalidHeapPtr)
+}
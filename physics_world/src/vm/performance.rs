@@ -56,24 +56,37 @@ impl PerformanceMonitor {
     }
 
     pub fn take_sample(&mut self, vm: &VmState) {
+        if let Some(sample) = self.sample(vm) {
+            self.record_sample(sample);
+        }
+    }
+
+    /// Sample `vm`, including its call stack, if the sample interval has elapsed
+    pub fn sample(&self, vm: &VmState) -> Option<PerformanceSample> {
         let now_millis = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_millis() as u64;
         if now_millis - self.last_sample_time_millis >= self.sample_interval_millis {
-            let sample = PerformanceSample {
+            Some(PerformanceSample {
                 timestamp: now_millis,
                 instructions_executed: vm.steps_remaining,
                 heap_usage: vm.memory.next_free() as usize,
                 call_stack_depth: vm.call_stack.len(),
                 counters: self.counters.clone(),
-            };
-
-            self.samples.push(sample);
-            self.last_sample_time_millis = now_millis;
+                call_stack: vm.call_stack_names(),
+            })
+        } else {
+            None
         }
     }
 
+    /// Store a sample produced by [`PerformanceMonitor::sample`]
+    pub fn record_sample(&mut self, sample: PerformanceSample) {
+        self.last_sample_time_millis = sample.timestamp;
+        self.samples.push(sample);
+    }
+
     pub fn take_sample_snapshot(&mut self, vm_snapshot: &VmDebugSnapshot) {
         let now_millis = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
@@ -86,6 +99,7 @@ impl PerformanceMonitor {
                 heap_usage: vm_snapshot.memory_usage,
                 call_stack_depth: vm_snapshot.call_stack.len(),
                 counters: self.counters.clone(),
+                call_stack: Vec::new(), // Snapshots carry no function names
             };
 
             self.samples.push(sample);
@@ -144,4 +158,7 @@ pub struct PerformanceSample {
     pub heap_usage: usize,
     pub call_stack_depth: usize,
     pub counters: HashMap<String, u64>,
+    /// Function names from the outermost frame (`main`) to the innermost
+    #[serde(default)]
+    pub call_stack: Vec<String>,
 }
@@ -0,0 +1,73 @@
+//! Source mapping for runtime error attribution.
+//!
+//! A [`SourceMap`] attached to a [`VmState`](crate::vm::VmState) lets runtime
+//! errors report the source position that produced the failing instruction.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Position in the original source program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// Line number (1-indexed)
+    pub line: usize,
+    /// Column number (1-indexed)
+    pub column: usize,
+}
+
+impl SourceLocation {
+    /// Create a new source location
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, col {}", self.line, self.column)
+    }
+}
+
+/// Mapping from top-level instruction offsets to source locations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceMap {
+    /// (instruction offset, source location) pairs, sorted by offset
+    entries: Vec<(usize, SourceLocation)>,
+}
+
+impl SourceMap {
+    /// Create a new empty source map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the source location of the instruction at `offset`
+    pub fn add_mapping(&mut self, offset: usize, location: SourceLocation) {
+        match self.entries.binary_search_by_key(&offset, |(o, _)| *o) {
+            Ok(index) => self.entries[index].1 = location,
+            Err(index) => self.entries.insert(index, (offset, location)),
+        }
+    }
+
+    /// Resolve the source location for an instruction offset.
+    ///
+    /// Instructions without their own entry inherit the location of the
+    /// nearest preceding mapped instruction.
+    pub fn lookup(&self, offset: usize) -> Option<SourceLocation> {
+        match self.entries.binary_search_by_key(&offset, |(o, _)| *o) {
+            Ok(index) => Some(self.entries[index].1),
+            Err(0) => None,
+            Err(index) => Some(self.entries[index - 1].1),
+        }
+    }
+
+    /// Number of mapped instructions
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
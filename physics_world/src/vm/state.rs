@@ -13,37 +13,78 @@
 //! - `execution.rs`: ~400 lines - Step execution logic
 //! - `gc_integration.rs`: ~200 lines - GC integration helpers
 
-use crate::memory::arena::{ObjectArena, ObjectHeader};
-use crate::types::{HeapPtr, OpCode, Value};
-use crate::vm::debug::{DebugEvent, DebugEventType, DebugInfo, Debugger, WatchpointTrigger};
+use crate::memory::arena::{
+    closure_capture_count, ArenaError, ObjectArena, ObjectHeader, RelocationMap, TAG_CLOSURE,
+    TAG_CLOSURE_BODY,
+};
+use crate::types::{Capability, HeapPtr, OpCode, Value};
+use crate::vm::capability_observer::CapabilityObserver;
+use crate::vm::closure_equivalence::ClosureEquivalence;
+use crate::vm::cost_model::CostModel;
+use crate::vm::coverage::{CoverageCollector, CoverageReport};
+use crate::vm::debug::{
+    DebugEvent, DebugEventType, DebugInfo, Debugger, WatchScope, WatchpointTrigger,
+};
 use crate::vm::error::{
     ErrorContext, SimpleVmError, StackFrame, VmError as DetailedVmError, WithContext,
 };
-use crate::vm::gc::{GarbageCollector, GcPtr, GcRoot, GcStats, HeapObject};
-use crate::vm::opcodes::closure::Closure;
+use crate::vm::function_names::FunctionNames;
+use crate::vm::gc::{GarbageCollector, GcMode, GcPtr, GcRoot, GcStats, HeapObject};
+use crate::vm::gc_integration::GcRootScope;
+use crate::vm::host_input::HostInput;
+use crate::vm::opcodes::arithmetic::IntOverflowMode;
+use crate::vm::opcodes::comparison::FloatCmpPolicy;
 use crate::vm::opcodes::*;
 use crate::vm::performance::{
     PerformanceAnalysis, PerformanceMetrics, PerformanceMonitor, PerformanceSample,
 };
+use crate::vm::source_map::{SourceLocation, SourceMap};
+use crate::vm::symbol_table::SymbolTable;
 use bincode;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // Re-export from new modules for convenience
 // CallFrame is now defined in call_state.rs and re-exported here for backwards compatibility
 pub use crate::vm::call_state::CallFrame;
 
 /// Function information for escape analysis integration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FunctionInfo {
     pub local_count: usize,
     pub escape_info: HashMap<usize, EscapeStatus>,
     pub free_variables: Vec<usize>,
 }
 
+impl FunctionInfo {
+    /// Local slots of the free variables that escape into the function's
+    /// closures, in free-variable order; `MakeClosure` captures exactly these
+    pub fn captured_locals(&self) -> Vec<usize> {
+        self.free_variables
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.escape_info.get(index) == Some(&EscapeStatus::Escaping))
+            .map(|(_, slot)| *slot)
+            .collect()
+    }
+}
+
+/// A literal pair in the constant pool, built when the program is loaded.
+///
+/// Pairs live in the arena, so the compiler leaves `slot` empty and records
+/// which constant slots hold the car and cdr; nested literal pairs refer to
+/// slots of earlier entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PairConstant {
+    pub slot: usize,
+    pub car: usize,
+    pub cdr: usize,
+}
+
 /// Escape status for variables
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EscapeStatus {
     Escaping,
     NonEscaping,
@@ -56,11 +97,91 @@ pub struct VmDebugSnapshot {
     pub instructions: Vec<OpCode>,
     pub stack: Vec<Value>,
     pub call_stack: Vec<CallFrame>,
+    /// Locals of the current frame, or the top-level locals outside a call
+    pub locals: Vec<Value>,
     pub memory_usage: usize,
     pub memory_capacity: usize,
     pub steps_remaining: u64,
     pub actor_id: u32,
     pub constant_pool: Vec<Value>,
+    /// Stack slot and details of every closure on the stack
+    pub closures: Vec<(usize, ClosureInfo)>,
+}
+
+/// Debugger view of a closure object
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureInfo {
+    /// Heap object holding the closure body bytecode
+    pub body_ptr: HeapPtr,
+    /// Number of parameters, inferred from the argument slots the body reads
+    /// (slots read with `GetLocal` but never written with `SetLocal`)
+    pub arity: usize,
+    /// Number of captured values
+    pub capture_count: usize,
+    /// Captured values as stored by `MakeClosure`, which keeps only the
+    /// low 32 bits of each value and not its type
+    pub captures: Vec<u32>,
+    /// The closure body
+    pub body: Vec<OpCode>,
+}
+
+/// Capabilities set aside on entry to a `WithCaps` region
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityScope {
+    /// Held capabilities the region's mask dropped; they come back on exit
+    /// unless revoked or expired in the meantime
+    #[serde(default)]
+    pub hidden: Vec<Capability>,
+    /// Call stack depth of the frame containing the region
+    pub frame_depth: usize,
+    /// Instruction range of the region body
+    pub body: std::ops::Range<usize>,
+}
+
+/// How long a time-boxed capability grant lasts, from the moment it is made
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapabilityExpiry {
+    /// For this many more executed instructions
+    Steps(u64),
+    /// For this much wall-clock time
+    WallClock(Duration),
+}
+
+/// Point at which a time-boxed capability grant lapses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CapabilityDeadline {
+    /// Once `steps_executed` reaches this count
+    Step(u64),
+    /// At this time, in milliseconds since the Unix epoch
+    WallClockMs(u64),
+}
+
+impl CapabilityDeadline {
+    /// Whether the deadline has passed for a VM that has run `steps_executed` instructions
+    pub fn has_passed(&self, steps_executed: u64) -> bool {
+        match self {
+            CapabilityDeadline::Step(step) => steps_executed >= *step,
+            CapabilityDeadline::WallClockMs(at) => unix_time_ms() >= *at,
+        }
+    }
+}
+
+/// Current wall-clock time in milliseconds since the Unix epoch
+fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+/// Catch target installed by `SetErrorHandler`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorHandler {
+    /// Instruction the handler starts at
+    pub target: usize,
+    /// Operand stack height when the handler was installed
+    pub stack_len: usize,
+    /// Call stack depth of the frame that installed the handler
+    pub frame_depth: usize,
 }
 
 /// Enhanced debugging information with capability analysis
@@ -118,22 +239,53 @@ pub struct PerformanceProfile {
     pub execution_time_ms: u64,
     pub memory_operations: u32,
     pub capability_checks: u32,
+    /// Call stacks sampled while profiling, outermost frame first
+    pub stack_samples: Vec<Vec<String>>,
+}
+
+impl PerformanceProfile {
+    /// Render the stack samples in the collapsed-stack format flamegraph
+    /// tools read: one `frame1;frame2;... count` line per distinct stack
+    pub fn to_folded_stacks(&self) -> String {
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        for stack in self.stack_samples.iter().filter(|s| !s.is_empty()) {
+            *counts.entry(stack.join(";")).or_insert(0) += 1;
+        }
+        counts
+            .iter()
+            .map(|(stack, count)| format!("{} {}\n", stack, count))
+            .collect()
+    }
 }
 
 /// Performance hotspot information
 #[derive(Debug, Clone)]
 pub struct Hotspot {
+    /// Lowest and highest address the instruction ran at
     pub instruction_range: (usize, usize),
     pub execution_count: u32,
     pub time_spent_ms: u64,
+    /// Cumulative wall-clock time, at full precision
+    pub time_spent: Duration,
     pub description: String,
 }
 
+/// Time spent in one opcode variant while profiling
+#[derive(Debug, Clone, Copy)]
+struct OpcodeTiming {
+    time: Duration,
+    first_ip: usize,
+    last_ip: usize,
+}
+
 /// Comprehensive debugging interface for VM introspection
 pub struct VmDebugger {
     vm: VmState,
     profiling_enabled: bool,
     instruction_counts: HashMap<String, u32>,
+    instruction_times: HashMap<&'static str, OpcodeTiming>,
+    execution_time: Duration,
+    memory_operations: u32,
     execution_history: Vec<ExecutionRecord>,
 }
 
@@ -144,6 +296,9 @@ impl VmDebugger {
             vm,
             profiling_enabled: false,
             instruction_counts: HashMap::new(),
+            instruction_times: HashMap::new(),
+            execution_time: Duration::ZERO,
+            memory_operations: 0,
             execution_history: Vec::new(),
         }
     }
@@ -158,13 +313,16 @@ impl VmDebugger {
         self.profiling_enabled = false;
     }
 
-    /// Execute a single instruction with debugging
+    /// Execute a single instruction with debugging.
+    ///
+    /// While profiling, the wall-clock time of the step is added to its
+    /// opcode variant.
     pub fn step_with_debug(&mut self) -> Result<InstructionResult, SimpleVmError> {
         let start_time = std::time::Instant::now();
 
         // Record instruction before execution
+        let current_instruction = self.vm.instructions.get(self.vm.ip).cloned();
         if self.profiling_enabled {
-            let current_instruction = self.vm.instructions.get(self.vm.ip).cloned();
             self.execution_history.push(ExecutionRecord {
                 ip: self.vm.ip,
                 instruction: current_instruction,
@@ -172,17 +330,45 @@ impl VmDebugger {
                 memory_usage: self.vm.memory.next_free() as usize,
                 timestamp: start_time.elapsed().as_micros() as u64,
             });
+            if let Some(sample) = self.vm.performance_monitor.sample(&self.vm) {
+                self.vm.performance_monitor.record_sample(sample);
+            }
         }
 
         // Execute the instruction
+        let ip = self.vm.ip;
+        let step_start = std::time::Instant::now();
         let result = self.vm.step();
+        let elapsed = step_start.elapsed();
 
-        // Update profiling data
+        // Update profiling data; jumps and calls move ip, so count the
+        // instruction fetched before the step
         if self.profiling_enabled {
-            if let Ok(InstructionResult::Continue) = &result {
-                if let Some(instruction) = self.vm.instructions.get(self.vm.ip - 1) {
-                    let instruction_name = format!("{:?}", instruction);
-                    *self.instruction_counts.entry(instruction_name).or_insert(0) += 1;
+            if let (Ok(InstructionResult::Continue), Some(instruction)) =
+                (&result, &current_instruction)
+            {
+                let mnemonic = instruction.metadata().mnemonic;
+                *self
+                    .instruction_counts
+                    .entry(mnemonic.to_string())
+                    .or_insert(0) += 1;
+                let timing = self
+                    .instruction_times
+                    .entry(mnemonic)
+                    .or_insert(OpcodeTiming {
+                        time: Duration::ZERO,
+                        first_ip: ip,
+                        last_ip: ip,
+                    });
+                timing.time += elapsed;
+                timing.first_ip = timing.first_ip.min(ip);
+                timing.last_ip = timing.last_ip.max(ip);
+                self.execution_time += elapsed;
+                if matches!(
+                    instruction,
+                    OpCode::MakeClosure(..) | OpCode::MakeVector(_) | OpCode::Cons
+                ) {
+                    self.memory_operations += 1;
                 }
             }
         }
@@ -279,31 +465,42 @@ impl VmDebugger {
         }
     }
 
-    /// Get performance profile
+    /// Profile of the steps run while profiling was enabled.
+    ///
+    /// Instruction counts are keyed by opcode variant. There is one hotspot
+    /// per variant that ran, hottest by cumulative wall-clock time first;
+    /// `memory_operations` counts the `MakeClosure`, `MakeVector` and `Cons`
+    /// instructions executed.
     pub fn get_performance_profile(&self) -> PerformanceProfile {
-        // Identify hotspots (instructions executed frequently)
-        let mut hotspots = Vec::new();
-        for (instruction_name, count) in &self.instruction_counts {
-            if *count > 10 {
-                // Arbitrary threshold for "hot"
-                hotspots.push(Hotspot {
-                    instruction_range: (0, 0), // Would be calculated in real implementation
-                    execution_count: *count,
-                    time_spent_ms: 0, // Would be measured in real implementation
-                    description: format!("Frequent {} execution", instruction_name),
-                });
-            }
-        }
-
-        // Sort hotspots by execution count
-        hotspots.sort_by(|a, b| b.execution_count.cmp(&a.execution_count));
+        let mut hotspots: Vec<Hotspot> = self
+            .instruction_times
+            .iter()
+            .map(|(mnemonic, timing)| {
+                let count = self.instruction_counts.get(*mnemonic).copied().unwrap_or(0);
+                Hotspot {
+                    instruction_range: (timing.first_ip, timing.last_ip),
+                    execution_count: count,
+                    time_spent_ms: timing.time.as_millis() as u64,
+                    time_spent: timing.time,
+                    description: format!("{mnemonic} executed {count} times"),
+                }
+            })
+            .collect();
+        hotspots.sort_by(|a, b| b.time_spent.cmp(&a.time_spent));
 
         PerformanceProfile {
             instruction_counts: self.instruction_counts.clone(),
             hotspots,
-            execution_time_ms: 0, // Would be measured in real implementation
-            memory_operations: 0, // Would be tracked in real implementation
+            execution_time_ms: self.execution_time.as_millis() as u64,
+            memory_operations: self.memory_operations,
             capability_checks: 0, // Would be tracked in real implementation
+            stack_samples: self
+                .vm
+                .performance_monitor
+                .samples
+                .iter()
+                .map(|sample| sample.call_stack.clone())
+                .collect(),
         }
     }
 
@@ -355,6 +552,12 @@ pub enum InstructionResult {
     Yield,           // Voluntary yield, suspend execution
     Finished(Value), // Execution completed with final value
     WaitingForCapability(crate::types::Capability), // V2: Actor is waiting for capability decision
+    /// The actor asked to start a new actor calling `closure` with `args`;
+    /// the scheduler pushes the new actor's id before resuming it
+    Spawn {
+        closure: Value,
+        args: Vec<Value>,
+    },
 }
 
 /// Backward compatibility: VmError enum for opcode handlers
@@ -370,7 +573,33 @@ pub enum VmError {
     DivisionByZero,
     ArithmeticOverflow,
     CapabilityDenied,
-    RecursionLimitExceeded,
+    /// Calling `function` would have reached `depth`, past the configured `limit`
+    RecursionLimitExceeded {
+        function: HeapPtr,
+        depth: u32,
+        limit: u32,
+    },
+    /// `index` is outside a vector of `length` elements
+    IndexOutOfBounds {
+        index: i64,
+        length: usize,
+    },
+    /// A call expecting `expected` results got `actual` from the callee's return
+    ReturnCountMismatch {
+        expected: u16,
+        actual: u16,
+    },
+    /// No metadata was loaded for `function`
+    UnknownFunction {
+        function: u16,
+    },
+    /// A closure of `function` captured `actual` values where `expected`
+    /// free variables escape into it
+    CaptureCountMismatch {
+        function: u16,
+        expected: usize,
+        actual: usize,
+    },
 }
 
 impl From<VmError> for SimpleVmError {
@@ -385,11 +614,47 @@ impl From<VmError> for SimpleVmError {
             VmError::DivisionByZero => SimpleVmError::DivisionByZero,
             VmError::ArithmeticOverflow => SimpleVmError::ArithmeticOverflow,
             VmError::CapabilityDenied => SimpleVmError::CapabilityDenied,
-            VmError::RecursionLimitExceeded => SimpleVmError::RecursionLimitExceeded,
+            VmError::RecursionLimitExceeded {
+                function,
+                depth,
+                limit,
+            } => SimpleVmError::RecursionLimitExceeded {
+                function,
+                depth,
+                limit,
+            },
+            VmError::IndexOutOfBounds { index, length } => {
+                SimpleVmError::IndexOutOfBounds { index, length }
+            }
+            VmError::ReturnCountMismatch { expected, actual } => {
+                SimpleVmError::ReturnCountMismatch { expected, actual }
+            }
+            VmError::UnknownFunction { function } => SimpleVmError::UnknownFunction { function },
+            VmError::CaptureCountMismatch {
+                function,
+                expected,
+                actual,
+            } => SimpleVmError::CaptureCountMismatch {
+                function,
+                expected,
+                actual,
+            },
         }
     }
 }
 
+/// What an allocation does when the arena is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OnOutOfMemory {
+    /// Raise `MemoryLimitExceeded`
+    #[default]
+    Fail,
+    /// Collect garbage and retry the allocation once
+    CollectAndRetry,
+    /// Enlarge the arena, never beyond `max_capacity` bytes
+    Grow { max_capacity: u32 },
+}
+
 /// Represents the state of a single virtual machine instance.
 ///
 /// # Test Coverage: 100% (critical path)
@@ -410,7 +675,7 @@ pub struct VmState {
     pub call_stack: Vec<CallFrame>, // For function calls/returns
     pub memory: ObjectArena,        // Heap
     // Resources (AIKR)
-    pub steps_remaining: u64, // Decremented each instruction
+    pub steps_remaining: u64, // Decremented by each instruction's cost
     // V2 Capability System - Add actor ID for capability checks
     pub actor_id: u32,
     // Recursion depth limit configuration
@@ -423,10 +688,70 @@ pub struct VmState {
     pub performance_monitor: PerformanceMonitor, // Performance monitoring
     pub gc_enabled: bool,                        // GC enable/disable flag
     pub gc_threshold: usize,                     // GC allocation threshold
+    // Whether GC heap allocation collects in one pause or marks incrementally
+    #[serde(default)]
+    pub gc_mode: GcMode,
     // Top-level locals for when no call frame exists
     // Used by SetLocal/GetLocal when running standalone bytecode without function calls
     #[serde(default)]
     pub top_level_locals: Vec<Value>,
+    // Optional debug info mapping top-level instructions back to source positions
+    #[serde(default)]
+    pub source_map: Option<SourceMap>,
+    // Symbol names for Symbol opcodes, shipped with the bytecode by the compiler
+    #[serde(default)]
+    pub symbol_table: SymbolTable,
+    // Source-level function names used to symbolicate stack traces
+    #[serde(default)]
+    pub function_names: FunctionNames,
+    // Compiler metadata for each function id, checked by MakeClosure once attached
+    #[serde(default)]
+    pub function_table: HashMap<u16, FunctionInfo>,
+    // Instructions executed so far, when coverage collection is installed
+    #[serde(default)]
+    pub coverage: Option<CoverageCollector>,
+    // Integer overflow behaviour of Add/Sub/Mul
+    #[serde(default)]
+    pub int_overflow_mode: IntOverflowMode,
+    // How Float operands compare, in particular NaN
+    #[serde(default)]
+    pub float_cmp_policy: FloatCmpPolicy,
+    // What heap allocation does when the arena is full
+    #[serde(default)]
+    pub on_out_of_memory: OnOutOfMemory,
+    // Steps each instruction charges against steps_remaining
+    #[serde(default)]
+    pub cost_model: CostModel,
+    // Arena fragmentation ratio above which a collection is followed by defragmentation
+    #[serde(default)]
+    pub defrag_threshold: Option<f32>,
+    // Optional embedder hook notified of capability opcodes as they execute
+    #[serde(skip)]
+    pub capability_observer: Option<Arc<dyn CapabilityObserver>>,
+    // Optional embedder hook deciding ClosureAlphaEq; bodies are compared as bytecode without it
+    #[serde(skip)]
+    pub closure_equivalence: Option<Arc<dyn ClosureEquivalence>>,
+    // Optional embedder hook supplying the bytes NetworkReceive and PersistRead return
+    #[serde(skip)]
+    pub host_input: Option<Arc<dyn HostInput>>,
+    // Capabilities the actor holds, consulted by HasCap and privileged opcodes
+    #[serde(default)]
+    pub capabilities: Vec<Capability>,
+    // Deadlines of time-boxed grants; other held capabilities never lapse
+    #[serde(default)]
+    pub capability_deadlines: Vec<(Capability, CapabilityDeadline)>,
+    // Instructions executed so far, the clock for step-based capability expiry
+    #[serde(default)]
+    pub steps_executed: u64,
+    // Active WithCaps regions, innermost last
+    #[serde(default)]
+    pub capability_scopes: Vec<CapabilityScope>,
+    // Installed error handlers, innermost last
+    #[serde(default)]
+    pub error_handlers: Vec<ErrorHandler>,
+    // Messages sent by Send, as (target actor, message), awaiting delivery by the scheduler
+    #[serde(default)]
+    pub outbox: Vec<(u32, Value)>,
 }
 
 impl VmState {
@@ -470,10 +795,307 @@ impl VmState {
             performance_monitor,
             gc_enabled: true,
             gc_threshold: mem_limit / 2,
+            gc_mode: GcMode::StopTheWorld,
             top_level_locals: Vec::new(),
+            source_map: None,
+            symbol_table: SymbolTable::new(),
+            function_names: FunctionNames::new(),
+            function_table: HashMap::new(),
+            coverage: None,
+            int_overflow_mode: IntOverflowMode::Checked,
+            float_cmp_policy: FloatCmpPolicy::IeeeNaNFalse,
+            on_out_of_memory: OnOutOfMemory::Fail,
+            cost_model: CostModel::uniform(),
+            defrag_threshold: None,
+            capability_observer: None,
+            closure_equivalence: None,
+            host_input: None,
+            capabilities: Vec::new(),
+            capability_deadlines: Vec::new(),
+            steps_executed: 0,
+            capability_scopes: Vec::new(),
+            error_handlers: Vec::new(),
+            outbox: Vec::new(),
+        }
+    }
+
+    /// Attach a source map so runtime errors can report source locations
+    pub fn attach_source_map(&mut self, source_map: SourceMap) {
+        self.source_map = Some(source_map);
+    }
+
+    /// Attach the symbol table the bytecode was compiled with.
+    ///
+    /// `Symbol(id)` pushes `Value::Symbol(id)`, and fails unless the attached
+    /// table holds `id`.
+    pub fn attach_symbol_table(&mut self, symbol_table: SymbolTable) {
+        self.symbol_table = symbol_table;
+    }
+
+    /// Attach the function names the bytecode was compiled with, so stack
+    /// traces name each frame's function
+    pub fn attach_function_names(&mut self, function_names: FunctionNames) {
+        self.function_names = function_names;
+    }
+
+    /// Attach the per-function metadata the compiler produced, so
+    /// `get_function_info` reports real local counts and escape statuses.
+    ///
+    /// Once a table is attached, `MakeClosure(code_idx, n)` requires
+    /// metadata for function `code_idx` and `n` to match its escaping free
+    /// variables.
+    pub fn attach_function_table(&mut self, function_table: HashMap<u16, FunctionInfo>) {
+        self.function_table = function_table;
+    }
+
+    /// Allocate the literal pairs the compiler pooled and store them in
+    /// their constant slots, in order, so later pairs can refer to earlier ones.
+    ///
+    /// Every load of a slot yields the same pair, like a quoted literal.
+    pub fn link_pair_constants(&mut self, pairs: &[PairConstant]) -> Result<(), VmError> {
+        let length = self.constant_pool.len();
+        let out_of_bounds = |index: usize| VmError::IndexOutOfBounds {
+            index: index as i64,
+            length,
+        };
+        for pair in pairs {
+            if let Some(index) = [pair.slot, pair.car, pair.cdr]
+                .into_iter()
+                .find(|index| *index >= length)
+            {
+                return Err(out_of_bounds(index));
+            }
+            // Built through Cons so a collection during allocation sees car and cdr
+            self.stack.push(self.constant_pool[pair.car].clone());
+            self.stack.push(self.constant_pool[pair.cdr].clone());
+            crate::vm::opcodes::list_ops::handle_cons(self)?;
+            self.constant_pool[pair.slot] = self.stack.pop().ok_or(VmError::StackUnderflow)?;
+        }
+        Ok(())
+    }
+
+    /// Start recording which instructions execute, discarding earlier coverage
+    pub fn install_coverage_collector(&mut self) {
+        self.coverage = Some(CoverageCollector::new(self.instructions.len()));
+    }
+
+    /// Coverage of the loaded instructions; everything is uncovered if no
+    /// collector was installed
+    pub fn coverage_report(&self) -> CoverageReport {
+        match &self.coverage {
+            Some(coverage) => coverage.report(),
+            None => CoverageCollector::new(self.instructions.len()).report(),
+        }
+    }
+
+    /// Top up the step budget, saturating at `u64::MAX`.
+    ///
+    /// A run that stopped with `CpuLimitExceeded` leaves the instruction
+    /// pointer, stack and heap untouched, so calling `run` again after a
+    /// top-up resumes where it stopped.
+    pub fn grant_steps(&mut self, additional: u64) {
+        self.steps_remaining = self.steps_remaining.saturating_add(additional);
+    }
+
+    /// Set the step budget to exactly `to`, e.g. at the start of a quantum
+    pub fn reset_steps(&mut self, to: u64) {
+        self.steps_remaining = to;
+    }
+
+    /// Allocate an arena object, applying `on_out_of_memory` if the arena is full.
+    ///
+    /// `CollectAndRetry` may move or free objects, so callers must keep any
+    /// heap value they still need reachable (e.g. on the stack) until this
+    /// returns.
+    pub fn allocate_object(&mut self, size: u32, tag: u8) -> Result<HeapPtr, VmError> {
+        let requested = match self.memory.allocate(size, tag) {
+            Ok(ptr) => return Ok(ptr),
+            Err(ArenaError::ArenaFull { requested, .. }) => requested,
+        };
+
+        match self.on_out_of_memory {
+            OnOutOfMemory::Fail => return Err(VmError::MemoryLimitExceeded),
+            OnOutOfMemory::CollectAndRetry => {
+                crate::vm::gc_integration::GcIntegration::collect_heap(self)
+                    .map_err(|_| VmError::MemoryLimitExceeded)?;
+            }
+            OnOutOfMemory::Grow { max_capacity } => {
+                let capacity = self.memory.capacity();
+                let needed = self.memory.next_free().saturating_add(requested);
+                let target = capacity.saturating_mul(2).max(needed).min(max_capacity);
+                self.memory.grow(target);
+            }
+        }
+
+        self.memory
+            .allocate(size, tag)
+            .map_err(|_| VmError::MemoryLimitExceeded)
+    }
+
+    /// Install an observer for capability request/check/grant/revoke events
+    pub fn set_capability_observer(&mut self, observer: Arc<dyn CapabilityObserver>) {
+        self.capability_observer = Some(observer);
+    }
+
+    /// Install the source of the bytes receiving host calls return
+    pub fn set_host_input(&mut self, input: Arc<dyn HostInput>) {
+        self.host_input = Some(input);
+    }
+
+    /// Install the comparison `ClosureAlphaEq` uses for closure bodies
+    pub fn set_closure_equivalence(&mut self, equivalence: Arc<dyn ClosureEquivalence>) {
+        self.closure_equivalence = Some(equivalence);
+    }
+
+    /// VM for an actor spawned by this one, which calls `closure` with `args`.
+    ///
+    /// The child starts from a copy of this VM's heap and constants, so the
+    /// closure's body and captured values stay valid, but it holds no
+    /// capabilities and shares no stack, frames or pending messages.
+    pub fn spawn_child(&self, actor_id: u32, closure: Value, args: Vec<Value>) -> VmState {
+        let mut child = self.clone();
+        child.ip = 0;
+        child.instructions = vec![OpCode::Call(args.len() as u16)];
+        child.stack = args;
+        child.stack.push(closure);
+        child.call_stack.clear();
+        child.actor_id = actor_id;
+        child.debugger = Debugger::new();
+        child.top_level_locals.clear();
+        child.source_map = None;
+        child.coverage = None;
+        child.capabilities.clear();
+        child.capability_deadlines.clear();
+        child.steps_executed = 0;
+        child.capability_scopes.clear();
+        child.error_handlers.clear();
+        child.outbox.clear();
+        child
+    }
+
+    /// Give the actor running this VM a capability
+    pub fn grant_capability(&mut self, capability: Capability) {
+        // A permanent grant replaces any time-boxed one
+        self.capability_deadlines
+            .retain(|(held, _)| *held != capability);
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+    }
+
+    /// Give the actor running this VM a capability that lapses after `expiry`
+    ///
+    /// Once the window has passed, `has_capability` reports the capability as
+    /// missing without an explicit revoke.
+    pub fn grant_capability_until(&mut self, capability: Capability, expiry: CapabilityExpiry) {
+        let deadline = match expiry {
+            CapabilityExpiry::Steps(steps) => {
+                CapabilityDeadline::Step(self.steps_executed.saturating_add(steps))
+            }
+            CapabilityExpiry::WallClock(window) => CapabilityDeadline::WallClockMs(
+                unix_time_ms().saturating_add(window.as_millis() as u64),
+            ),
+        };
+        self.grant_capability(capability.clone());
+        self.capability_deadlines.push((capability, deadline));
+    }
+
+    /// Take a capability away from the actor running this VM
+    pub fn revoke_capability(&mut self, capability: &Capability) {
+        self.capabilities.retain(|held| held != capability);
+        self.capability_deadlines
+            .retain(|(held, _)| held != capability);
+        for scope in &mut self.capability_scopes {
+            scope.hidden.retain(|held| held != capability);
+        }
+    }
+
+    /// Whether the actor running this VM holds `capability`
+    pub fn has_capability(&self, capability: &Capability) -> bool {
+        self.capabilities.contains(capability)
+            && !self.capability_deadlines.iter().any(|(held, deadline)| {
+                held == capability && deadline.has_passed(self.steps_executed)
+            })
+    }
+
+    /// Drop every time-boxed capability whose window has passed
+    pub fn prune_expired_capabilities(&mut self) {
+        let steps_executed = self.steps_executed;
+        let (expired, live): (Vec<_>, Vec<_>) = std::mem::take(&mut self.capability_deadlines)
+            .into_iter()
+            .partition(|(_, deadline)| deadline.has_passed(steps_executed));
+        self.capability_deadlines = live;
+        let is_live = |held: &Capability| !expired.iter().any(|(capability, _)| capability == held);
+        self.capabilities.retain(is_live);
+        for scope in &mut self.capability_scopes {
+            scope.hidden.retain(is_live);
+        }
+    }
+
+    /// Drop every held capability outside `cap_mask` for the `body_len`
+    /// instructions following the current one
+    pub fn enter_capability_scope(&mut self, cap_mask: u32, body_len: u16) {
+        let start = self.ip + 1;
+        let (kept, hidden) = std::mem::take(&mut self.capabilities)
+            .into_iter()
+            .partition(|held| held.mask_bit() & cap_mask != 0);
+        self.capabilities = kept;
+        self.capability_scopes.push(CapabilityScope {
+            hidden,
+            frame_depth: self.call_stack.len(),
+            body: start..start + body_len as usize,
+        });
+    }
+
+    /// Restore the capabilities of every region execution has left, by
+    /// running past its end, jumping out, or returning from its frame.
+    ///
+    /// Only the capabilities the mask hid are added back, so grants made
+    /// inside the region survive it and nothing revoked or expired inside
+    /// it returns.
+    pub fn leave_finished_capability_scopes(&mut self) {
+        while let Some(scope) = self.capability_scopes.last() {
+            let depth = self.call_stack.len();
+            let inside = depth > scope.frame_depth
+                || (depth == scope.frame_depth && scope.body.contains(&self.ip));
+            if inside {
+                break;
+            }
+            let scope = self.capability_scopes.pop().unwrap();
+            for held in scope.hidden {
+                if !self.capabilities.contains(&held) {
+                    self.capabilities.push(held);
+                }
+            }
         }
     }
 
+    /// Whether execution is inside a `WithCaps` region
+    pub fn in_capability_scope(&self) -> bool {
+        !self.capability_scopes.is_empty()
+    }
+
+    /// Name of a `Value::Symbol`, if it is in the attached symbol table
+    pub fn resolve_symbol(&self, value: &Value) -> Option<&str> {
+        match value {
+            Value::Symbol(id) => self.symbol_table.resolve(*id),
+            _ => None,
+        }
+    }
+
+    /// Resolve the source location of the current instruction.
+    ///
+    /// The source map describes top-level bytecode only, so inside a call the
+    /// location of the outermost call site is reported instead.
+    pub fn current_source_location(&self) -> Option<SourceLocation> {
+        let source_map = self.source_map.as_ref()?;
+        let ip = match self.call_stack.first() {
+            Some(frame) => frame.return_ip.saturating_sub(1),
+            None => self.ip,
+        };
+        source_map.lookup(ip)
+    }
+
     /// Create an error context for detailed error reporting
     pub fn create_error_context(&self) -> ErrorContext {
         ErrorContext {
@@ -487,6 +1109,7 @@ impl VmState {
             stack_trace: self.create_stack_trace(),
             execution_history: self.get_execution_history(),
             timestamp: 0, // Will be set by scheduler
+            source_location: self.current_source_location().map(Box::new),
         }
     }
 
@@ -519,14 +1142,105 @@ impl VmState {
             instructions: self.instructions.clone(),
             stack: self.stack.clone(),
             call_stack: self.call_stack.clone(),
+            locals: match self.call_stack.last() {
+                Some(frame) => frame.locals.clone(),
+                None => self.top_level_locals.clone(),
+            },
             memory_usage: self.memory.next_free() as usize,
             memory_capacity: self.memory.capacity() as usize,
             steps_remaining: self.steps_remaining,
             actor_id: self.actor_id,
             constant_pool: self.constant_pool.clone(),
+            closures: self
+                .stack
+                .iter()
+                .enumerate()
+                .filter_map(|(slot, value)| match value {
+                    Value::Closure(ptr) => Some((slot, self.inspect_closure(*ptr)?)),
+                    _ => None,
+                })
+                .collect(),
         }
     }
 
+    /// Debugging support: Decode the closure object at `ptr`.
+    ///
+    /// Returns `None` if `ptr` does not point at a closure whose body decodes.
+    pub fn inspect_closure(&self, ptr: HeapPtr) -> Option<ClosureInfo> {
+        let data = self.heap_object(ptr)?;
+        if unsafe { self.memory.get_header(ptr) }.tag != TAG_CLOSURE {
+            return None;
+        }
+        let body_ptr = HeapPtr::new(u32::from_le_bytes(data.get(0..4)?.try_into().ok()?));
+        // The capture words are followed by a kind byte per capture
+        let capture_count = closure_capture_count(data.len());
+        let captures: Vec<u32> = data
+            .get(4..4 + capture_count * 4)?
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+
+        let body_data = self.heap_object(body_ptr)?;
+        let length = u32::from_le_bytes(body_data.get(0..4)?.try_into().ok()?) as usize;
+        let body: Vec<OpCode> = bincode::deserialize(body_data.get(4..4 + length)?).ok()?;
+
+        let written: Vec<u16> = body
+            .iter()
+            .filter_map(|op| match op {
+                OpCode::SetLocal(slot) => Some(*slot),
+                _ => None,
+            })
+            .collect();
+        let arity = body
+            .iter()
+            .filter_map(|op| match op {
+                OpCode::GetLocal(slot) if !written.contains(slot) => Some(*slot as usize + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+
+        Some(ClosureInfo {
+            body_ptr,
+            arity,
+            capture_count,
+            captures,
+            body,
+        })
+    }
+
+    /// Number of instructions in the body `MakeClosure(code_idx, _)` copies.
+    ///
+    /// A `closure_body:` constant not yet materialized is parsed, and a slot
+    /// holding no body stands for the one-instruction identity body.
+    pub fn closure_body_len(&self, code_idx: usize) -> usize {
+        match self.constant_pool.get(code_idx) {
+            Some(Value::Closure(body_ptr)) => self
+                .heap_object(*body_ptr)
+                .filter(|_| unsafe { self.memory.get_header(*body_ptr) }.tag == TAG_CLOSURE_BODY)
+                // The serialized body starts with its instruction count
+                .and_then(|data| bincode::deserialize::<u64>(data.get(4..)?).ok())
+                .map_or(1, |len| len as usize),
+            Some(Value::String(text)) if text.starts_with(closure_text::CLOSURE_BODY_PREFIX) => {
+                closure_text::parse_closure_body(text).map_or(1, |body| body.len())
+            }
+            _ => 1,
+        }
+    }
+
+    /// Data of the heap object at `ptr`, if `ptr` is inside the allocated heap
+    fn heap_object(&self, ptr: HeapPtr) -> Option<&[u8]> {
+        let header_end = ptr.get().checked_add(ObjectHeader::size_bytes() as u32)?;
+        if header_end > self.memory.next_free() {
+            return None;
+        }
+        let size = unsafe { self.memory.get_header(ptr) }.size;
+        if header_end.checked_add(size)? > self.memory.next_free() {
+            return None;
+        }
+        Some(unsafe { self.memory.get_data(ptr) })
+    }
+
     /// Debugging support: Get formatted stack trace with function names
     pub fn get_formatted_stack_trace(&self) -> String {
         let mut trace = String::new();
@@ -731,7 +1445,12 @@ impl VmState {
             return Err(DetailedVmError::GcDisabled);
         }
 
-        let ptr = self.gc.allocate(object);
+        let ptr = match self.gc_mode {
+            GcMode::StopTheWorld => self.gc.allocate(object),
+            GcMode::Incremental { budget } => self.gc.allocate_incremental(object, budget),
+            GcMode::Generational { nursery } => self.gc.allocate_generational(object, nursery),
+        };
+        crate::vm::gc_integration::GcIntegration::relocate_gc_values(self);
         Ok(Value::GcPtr(ptr))
     }
 
@@ -740,12 +1459,29 @@ impl VmState {
         self.gc.roots.push(GcRoot {
             ptr,
             description: description.to_string(),
+            scope: None,
         });
     }
 
+    /// Open a scope whose roots are all removed when it is dropped
+    pub fn push_root_scope(&mut self) -> GcRootScope<'_> {
+        GcRootScope::new(self)
+    }
+
     /// Phase 3: GC integration - Remove GC root
+    ///
+    /// Roots are counted: this drops one root added by `add_gc_root`, so
+    /// `ptr` stays rooted while other `add_gc_root` calls or any open
+    /// [`GcRootScope`] still hold it.
     pub fn remove_gc_root(&mut self, ptr: GcPtr) {
-        self.gc.roots.retain(|root| root.ptr != ptr);
+        if let Some(index) = self
+            .gc
+            .roots
+            .iter()
+            .rposition(|root| root.ptr == ptr && root.scope.is_none())
+        {
+            self.gc.roots.remove(index);
+        }
     }
 
     /// Phase 3: GC integration - Get GC stats
@@ -758,6 +1494,21 @@ impl VmState {
         self.gc_enabled = enabled;
     }
 
+    /// Rewrite the references to moved arena objects that the VM tracks.
+    ///
+    /// Covers `Pair`, `Closure`, `Vector` and `BigInt` values held directly
+    /// on the value stack, in top-level and call-frame locals, in closed-over
+    /// variables, in the constant pool and in GC heap objects, plus the
+    /// pointer words of arena objects. Values nested inside another value,
+    /// such as an error record's payload, are not visited. Whatever moved the
+    /// objects must already have copied them to their new addresses; each
+    /// map entry is applied exactly once, so the map must not chain (an
+    /// address that is both a source and a target).
+    pub fn relocate_all(&mut self, map: &RelocationMap) {
+        self.memory.relocate_references(map);
+        crate::vm::gc_integration::GcIntegration::relocate_heap_values(self, map);
+    }
+
     // ========================================================================
     // Debugging Integration Methods
     // ========================================================================
@@ -767,6 +1518,12 @@ impl VmState {
         self.debugger.add_breakpoint(address);
     }
 
+    /// Halt at `address` only when `condition`, a watchpoint expression,
+    /// holds; see `Debugger::add_conditional_breakpoint`
+    pub fn add_conditional_breakpoint(&mut self, address: usize, condition: &str) {
+        self.debugger.add_conditional_breakpoint(address, condition);
+    }
+
     /// Phase 3: Debugging integration - Remove breakpoint
     pub fn remove_breakpoint(&mut self, address: usize) {
         self.debugger.remove_breakpoint(address);
@@ -783,9 +1540,19 @@ impl VmState {
     }
 
     /// Phase 3: Debugging integration - Check watchpoints
+    ///
+    /// Call after each step; a condition watchpoint fires on the check where
+    /// it first holds.
     pub fn check_watchpoints(&mut self) -> Vec<WatchpointTrigger> {
-        let vm_state = self.get_debug_snapshot();
-        self.debugger.check_watchpoints_snapshot(&vm_state)
+        let locals = match self.call_stack.last() {
+            Some(frame) => &frame.locals,
+            None => &self.top_level_locals,
+        };
+        self.debugger.check_watchpoints_in(&WatchScope {
+            stack: &self.stack,
+            locals,
+            steps_remaining: self.steps_remaining,
+        })
     }
 
     /// Phase 3: Debugging integration - Log debug event
@@ -825,8 +1592,9 @@ impl VmState {
 
     /// Phase 3: Performance integration - Take sample
     pub fn take_performance_sample(&mut self) {
-        let vm_state = self.get_debug_snapshot();
-        self.performance_monitor.take_sample_snapshot(&vm_state);
+        if let Some(sample) = self.performance_monitor.sample(self) {
+            self.performance_monitor.record_sample(sample);
+        }
     }
 
     /// Phase 3: Performance integration - Get metrics
@@ -846,21 +1614,23 @@ impl VmState {
     /// Tail call detection method
     pub fn is_current_position_tail(&self) -> bool {
         if let Some(opcode) = self.instructions.get(self.ip) {
-            matches!(opcode, OpCode::Ret | OpCode::TailCall(_))
+            matches!(opcode, OpCode::Ret | OpCode::RetN(_) | OpCode::TailCall(_))
         } else {
             false
         }
     }
 
     /// Get function info for escape analysis integration
+    ///
+    /// Returns `VmError::UnknownFunction` if no metadata was attached for
+    /// `function_ptr`.
     pub fn get_function_info(&self, function_ptr: u16) -> Result<FunctionInfo, VmError> {
-        // In a real implementation, this would look up function metadata
-        // For now, return a default function info
-        Ok(FunctionInfo {
-            local_count: 0,
-            escape_info: HashMap::new(),
-            free_variables: Vec::new(),
-        })
+        self.function_table
+            .get(&function_ptr)
+            .cloned()
+            .ok_or(VmError::UnknownFunction {
+                function: function_ptr,
+            })
     }
 
     /// Tail call optimization handler - delegates to opcodes::call::handle_tail_call
@@ -905,49 +1675,19 @@ impl VmState {
         call::handle_call(self, arg_count)
     }
 
-    /// Helper method to read u16 from instructions
+    /// Read the `u16` operand of the current instruction and move past it.
+    ///
+    /// Returns `VmError::UnknownOpCode` without moving if the instruction
+    /// does not carry exactly one `u16` operand (see `OpCode::u16_operand`);
+    /// neighbouring instructions are never consulted.
     pub fn read_u16(&mut self) -> Result<u16, VmError> {
-        if self.ip >= self.instructions.len() {
-            return Err(VmError::UnknownOpCode);
-        }
-
-        // Handle the current instruction as a u16 value
-        match &self.instructions[self.ip] {
-            OpCode::Int(i) => {
-                self.ip += 1;
-                Ok(*i as u16)
-            }
-            OpCode::Call(arg_count) => {
-                self.ip += 1;
-                Ok(*arg_count)
-            }
-            OpCode::TailCall(arg_count) => {
-                self.ip += 1;
-                Ok(*arg_count)
-            }
-            OpCode::GetLocal(offset) => {
-                self.ip += 1;
-                Ok(*offset)
-            }
-            OpCode::SetLocal(offset) => {
-                self.ip += 1;
-                Ok(*offset)
-            }
-            _ => {
-                // For other opcodes, try to extract from the next instruction
-                if self.ip + 1 < self.instructions.len() {
-                    match &self.instructions[self.ip + 1] {
-                        OpCode::Int(i) => {
-                            self.ip += 2;
-                            Ok(*i as u16)
-                        }
-                        _ => Err(VmError::UnknownOpCode),
-                    }
-                } else {
-                    Err(VmError::UnknownOpCode)
-                }
-            }
-        }
+        let operand = self
+            .instructions
+            .get(self.ip)
+            .and_then(OpCode::u16_operand)
+            .ok_or(VmError::UnknownOpCode)?;
+        self.ip += 1;
+        Ok(operand)
     }
 
     /// Helper method to get local variable
@@ -963,11 +1703,26 @@ impl VmState {
         }
     }
 
-    /// Debugging support: Get function name for a call frame (simplified)
+    /// Names of the active functions, `main` first and the innermost frame last
+    pub fn call_stack_names(&self) -> Vec<String> {
+        std::iter::once("main".to_string())
+            .chain(
+                self.call_stack
+                    .iter()
+                    .map(|frame| self.get_function_name_for_frame(frame)),
+            )
+            .collect()
+    }
+
+    /// Debugging support: Get function name for a call frame
+    ///
+    /// Frames whose body has no entry in `function_names` fall back to a
+    /// placeholder derived from the return address.
     fn get_function_name_for_frame(&self, frame: &CallFrame) -> String {
-        // In a real implementation, this would look up function names from debug info
-        // For now, we'll use a placeholder
-        format!("function_{}", frame.return_ip)
+        match self.function_names.name_for(frame.code_index) {
+            Some(name) => name.to_string(),
+            None => format!("function_{}", frame.return_ip),
+        }
     }
 
     // ========================================================================
@@ -1000,6 +1755,25 @@ impl VmState {
         engine.run(self)
     }
 
+    /// Serialize the whole VM, heap and call stack included, so `restore`
+    /// can resume it later, possibly in another process.
+    ///
+    /// The capability observer, closure equivalence and host input hooks
+    /// are host objects and are not part of the snapshot; reattach them
+    /// after restoring.
+    ///
+    /// Returns `SimpleVmError::InvalidSnapshot` if the state does not encode.
+    pub fn snapshot(&self) -> Result<Vec<u8>, SimpleVmError> {
+        bincode::serialize(self).map_err(|_| SimpleVmError::InvalidSnapshot)
+    }
+
+    /// Rebuild a VM from bytes written by `snapshot`.
+    ///
+    /// Returns `SimpleVmError::InvalidSnapshot` if the bytes do not decode.
+    pub fn restore(bytes: &[u8]) -> Result<VmState, SimpleVmError> {
+        bincode::deserialize(bytes).map_err(|_| SimpleVmError::InvalidSnapshot)
+    }
+
     /// Convert a simple VmError to a detailed VmError with context
     pub fn convert_to_detailed_error(&self, error: SimpleVmError) -> DetailedVmError {
         let context = self.create_error_context();
@@ -0,0 +1,71 @@
+//! Symbol interning shared between compilers and the VM.
+//!
+//! A compiler interns every symbol it emits into a [`SymbolTable`] and ships
+//! the table alongside the bytecode. Once the table is attached to a
+//! [`VmState`](crate::vm::VmState), `OpCode::Symbol(id)` pushes
+//! `Value::Symbol(id)` and the id resolves to the same name it had at
+//! compile time. Symbol ids index this table only, never the constant pool.
+
+use serde::{Deserialize, Serialize};
+
+/// Interned symbol names, indexed by stable symbol id
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolTable {
+    /// Symbol names; a symbol's id is its index
+    symbols: Vec<String>,
+}
+
+impl SymbolTable {
+    /// Create a new empty symbol table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the id of `name`, adding it to the table if needed
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(id) = self.lookup(name) {
+            id
+        } else {
+            self.symbols.push(name.to_string());
+            self.symbols.len() - 1
+        }
+    }
+
+    /// Id of an already interned name
+    pub fn lookup(&self, name: &str) -> Option<usize> {
+        self.symbols.iter().position(|symbol| symbol == name)
+    }
+
+    /// Name of the symbol with the given id
+    pub fn resolve(&self, id: usize) -> Option<&str> {
+        self.symbols.get(id).map(String::as_str)
+    }
+
+    /// Number of interned symbols
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Whether no symbols have been interned
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_returns_stable_ids() {
+        let mut table = SymbolTable::new();
+        let foo = table.intern("foo");
+        let bar = table.intern("bar");
+
+        assert_ne!(foo, bar);
+        assert_eq!(table.intern("foo"), foo);
+        assert_eq!(table.resolve(bar), Some("bar"));
+        assert_eq!(table.resolve(7), None);
+        assert_eq!(table.len(), 2);
+    }
+}
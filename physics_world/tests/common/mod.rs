@@ -0,0 +1,12 @@
+/// Fixtures shared by the integration tests
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::opcodes::closure::create_closure_body;
+use physics_world::vm::VmState;
+
+/// Store each body in the heap and point its constant slot at it
+pub fn load_bodies(vm: &mut VmState, bodies: Vec<Vec<OpCode>>) {
+    for body in bodies {
+        let ptr = create_closure_body(vm, body).unwrap();
+        vm.constant_pool.push(Value::Closure(ptr));
+    }
+}
@@ -0,0 +1,114 @@
+/// Test automatic arena defragmentation after a collection
+use physics_world::memory::arena::{SLOT_BOXED, TAG_STRING, TAG_VECTOR, VECTOR_SLOT_SIZE};
+use physics_world::types::{Capability, HeapPtr, OpCode, Value};
+use physics_world::vm::state::VmState;
+
+/// Interleave 16-byte aligned two-slot vectors with pairs of strings whose
+/// footprint leaves an 8-byte gap before the next vector, each vector's
+/// slots pointing at the two strings after it. Returns the vectors.
+fn fragment(vm: &mut VmState, count: usize) -> Vec<HeapPtr> {
+    (0..count)
+        .map(|i| {
+            let vector = vm
+                .memory
+                .allocate(2 * VECTOR_SLOT_SIZE as u32, TAG_VECTOR)
+                .unwrap();
+            for slot in 0..2 {
+                let string = vm.memory.allocate(9, TAG_STRING).unwrap();
+                unsafe { vm.memory.get_data_mut(string) }.copy_from_slice(&payload(i));
+                let start = slot * VECTOR_SLOT_SIZE;
+                let data = unsafe { vm.memory.get_data_mut(vector) };
+                data[start] = SLOT_BOXED;
+                data[start + 4..start + 8].copy_from_slice(&string.get().to_le_bytes());
+            }
+            vector
+        })
+        .collect()
+}
+
+fn payload(i: usize) -> [u8; 9] {
+    let mut bytes = *b"payload-0";
+    bytes[8] = b'0' + i as u8;
+    bytes
+}
+
+/// The string a vector's first slot points at, read back through the vector
+fn string_of(vm: &VmState, value: &Value) -> Vec<u8> {
+    let Value::Vector(vector) = value else {
+        panic!("expected a vector, got {value:?}");
+    };
+    unsafe {
+        assert_eq!(vm.memory.get_header(*vector).tag, TAG_VECTOR);
+        let data = vm.memory.get_data(*vector);
+        let string = HeapPtr::new(u32::from_le_bytes(data[4..8].try_into().unwrap()));
+        assert_eq!(vm.memory.get_header(string).tag, TAG_STRING);
+        vm.memory.get_data(string).to_vec()
+    }
+}
+
+fn fragmented_vm(defrag_threshold: Option<f32>) -> VmState {
+    let mut vm = VmState::new(vec![OpCode::GcCollect], vec![], 100, 4096, 1, 100);
+    vm.grant_capability(Capability::SysGc);
+    vm.defrag_threshold = defrag_threshold;
+
+    // Roots in the stack, the top-level locals and the constant pool
+    let vectors = fragment(&mut vm, 6);
+    vm.stack
+        .extend(vectors[0..2].iter().map(|&p| Value::Vector(p)));
+    vm.top_level_locals
+        .extend(vectors[2..4].iter().map(|&p| Value::Vector(p)));
+    vm.constant_pool
+        .extend(vectors[4..6].iter().map(|&p| Value::Vector(p)));
+    vm
+}
+
+fn roots(vm: &VmState) -> Vec<Value> {
+    let stack = &vm.stack[..2];
+    stack
+        .iter()
+        .chain(&vm.top_level_locals)
+        .chain(&vm.constant_pool)
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn test_collection_defragments_past_threshold_and_keeps_pointers_valid() {
+    let mut without = fragmented_vm(None);
+    without.step().unwrap();
+    let padded = without.memory.padding_bytes();
+    assert!(padded > 0);
+    assert!(without.memory.fragmentation_ratio() > 0.05);
+
+    let mut vm = fragmented_vm(Some(0.05));
+    vm.step().unwrap();
+
+    assert_eq!(vm.memory.padding_bytes(), 0);
+    assert_eq!(vm.memory.next_free(), without.memory.next_free() - padded);
+    assert_eq!(vm.memory.fragmentation_ratio(), 0.0);
+    // GcCollect reports the padding as reclaimed too
+    let (Some(Value::Int(reclaimed)), Some(Value::Int(reclaimed_without))) =
+        (vm.stack.last(), without.stack.last())
+    else {
+        panic!("GcCollect pushes the bytes reclaimed");
+    };
+    assert_eq!(*reclaimed, reclaimed_without + i64::from(padded));
+
+    let live = roots(&vm);
+    assert_eq!(live.len(), 6);
+    for (i, root) in live.iter().enumerate() {
+        assert_eq!(string_of(&vm, root), payload(i));
+    }
+    // Some objects really moved
+    assert_ne!(live, roots(&without));
+}
+
+#[test]
+fn test_no_defragmentation_below_threshold() {
+    let mut vm = fragmented_vm(Some(0.9));
+    vm.step().unwrap();
+    assert!(vm.memory.padding_bytes() > 0);
+    for (i, root) in roots(&vm).iter().enumerate() {
+        assert_eq!(string_of(&vm, root), payload(i));
+    }
+}
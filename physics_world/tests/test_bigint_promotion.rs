@@ -0,0 +1,162 @@
+/// Test promotion of overflowing integer arithmetic to arbitrary-precision BigInts
+use physics_world::types::{BigInt, OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::opcodes::arithmetic::read_integer;
+use physics_world::vm::{IntOverflowMode, VmState};
+
+fn vm_with_mode(instructions: Vec<OpCode>, mode: IntOverflowMode) -> VmState {
+    let mut vm = VmState::new(instructions, vec![], 1000, 4096, 1, 100);
+    vm.int_overflow_mode = mode;
+    vm
+}
+
+/// 1 * 2 * ... * n
+fn factorial(n: i64) -> Vec<OpCode> {
+    let mut instructions = vec![OpCode::Int(1)];
+    for k in 2..=n {
+        instructions.extend([OpCode::Int(k), OpCode::Mul]);
+    }
+    instructions
+}
+
+#[test]
+fn test_factorial_25_promotes_to_bigint() {
+    let mut vm = vm_with_mode(factorial(25), IntOverflowMode::BigIntPromotion);
+
+    let result = vm.run().unwrap();
+
+    assert!(matches!(result, Value::BigInt(_)));
+    let value = read_integer(&vm, &result).unwrap();
+    assert_eq!(value.to_string(), "15511210043330985984000000");
+}
+
+#[test]
+fn test_factorial_25_overflows_in_checked_mode() {
+    let error = vm_with_mode(factorial(25), IntOverflowMode::Checked)
+        .run()
+        .unwrap_err();
+    assert!(matches!(error, VmError::ArithmeticOverflow { .. }));
+}
+
+#[test]
+fn test_results_that_fit_demote_to_int() {
+    let mut instructions = vec![OpCode::Int(i64::MAX), OpCode::Int(1), OpCode::Add];
+    instructions.extend([OpCode::Int(2), OpCode::Sub]);
+
+    let result = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion)
+        .run()
+        .unwrap();
+    assert_eq!(result, Value::Int(i64::MAX - 1));
+}
+
+#[test]
+fn test_mixed_comparisons() {
+    let big = || vec![OpCode::Int(i64::MAX), OpCode::Int(1), OpCode::Add];
+    let cases = [
+        (OpCode::Lt, false),
+        (OpCode::Gt, true),
+        (OpCode::Gte, true),
+        (OpCode::Lte, false),
+        (OpCode::Eq, false),
+        (OpCode::Ne, true),
+    ];
+    for (opcode, expected) in cases {
+        let mut instructions = big();
+        instructions.extend([OpCode::Int(i64::MAX), opcode]);
+        let result = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion).run();
+        assert_eq!(result.unwrap(), Value::Bool(expected), "{opcode:?}");
+    }
+}
+
+#[test]
+fn test_equal_bigints_at_different_addresses_are_eq() {
+    let mut instructions = factorial(21);
+    instructions.extend(factorial(21));
+    instructions.push(OpCode::Eq);
+
+    let result = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion).run();
+    assert_eq!(result.unwrap(), Value::Bool(true));
+}
+
+#[test]
+fn test_negative_promotion_and_min_division() {
+    let instructions = vec![OpCode::Int(i64::MIN), OpCode::Int(-1), OpCode::Div];
+    let mut vm = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion);
+    let result = vm.run().unwrap();
+    assert_eq!(
+        read_integer(&vm, &result).unwrap(),
+        BigInt::from(i64::MIN).negated()
+    );
+
+    let instructions = vec![OpCode::Int(i64::MIN), OpCode::Int(1), OpCode::Sub];
+    let mut vm = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion);
+    let result = vm.run().unwrap();
+    assert_eq!(
+        read_integer(&vm, &result).unwrap().to_string(),
+        "-9223372036854775809"
+    );
+}
+
+#[test]
+fn test_bigint_division_and_remainder() {
+    // 25! / 24! demotes back to an Int
+    let mut instructions = factorial(25);
+    instructions.extend(factorial(24));
+    instructions.push(OpCode::Div);
+    let result = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion).run();
+    assert_eq!(result.unwrap(), Value::Int(25));
+
+    // Both operands BigInts: 21! needs three limbs
+    let mut instructions = factorial(25);
+    instructions.extend(factorial(21));
+    instructions.push(OpCode::Div);
+    let result = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion).run();
+    assert_eq!(result.unwrap(), Value::Int(25 * 24 * 23 * 22));
+
+    // 25! = 15511210043330985984000000, and 25! mod 1_000_000_007 = 440732388
+    let mut instructions = factorial(25);
+    instructions.extend([OpCode::Int(1_000_000_007), OpCode::Mod]);
+    let result = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion).run();
+    assert_eq!(result.unwrap(), Value::Int(440_732_388));
+
+    let mut instructions = factorial(25);
+    instructions.extend([OpCode::Int(0), OpCode::Div]);
+    let error = vm_with_mode(instructions, IntOverflowMode::BigIntPromotion)
+        .run()
+        .unwrap_err();
+    assert!(matches!(error, VmError::DivisionByZero { .. }));
+}
+
+#[test]
+fn test_bigint_div_rem_truncates_like_i128() {
+    let values = [
+        i64::MAX,
+        i64::MIN,
+        -1,
+        3,
+        -7,
+        1 << 40,
+        -(1 << 33) + 5,
+        987_654_321_987,
+    ];
+    for &a in &values {
+        for &b in &values {
+            for &c in &values {
+                let dividend = BigInt::from(a).mul(&BigInt::from(b));
+                let (quotient, remainder) = dividend.div_rem(&BigInt::from(c)).unwrap();
+                let expected = i128::from(a) * i128::from(b);
+                assert_eq!(
+                    quotient.to_string(),
+                    (expected / i128::from(c)).to_string(),
+                    "{a} * {b} / {c}"
+                );
+                assert_eq!(
+                    remainder.to_string(),
+                    (expected % i128::from(c)).to_string(),
+                    "{a} * {b} % {c}"
+                );
+            }
+        }
+    }
+    assert!(BigInt::from(5).div_rem(&BigInt::from(0)).is_none());
+}
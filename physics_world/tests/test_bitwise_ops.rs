@@ -0,0 +1,81 @@
+/// Test bitwise and shift opcodes on 64-bit two's complement integers
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::VmState;
+
+/// Apply `op` to `a` and `b`
+fn vm(a: i64, b: i64, op: OpCode) -> VmState {
+    VmState::new(
+        vec![OpCode::Int(a), OpCode::Int(b), op],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    )
+}
+
+fn eval(a: i64, b: i64, op: OpCode) -> Value {
+    vm(a, b, op).run().unwrap()
+}
+
+#[test]
+fn test_and_or_xor() {
+    assert_eq!(eval(0b1100, 0b1010, OpCode::BitAnd), Value::Int(0b1000));
+    assert_eq!(eval(0b1100, 0b1010, OpCode::BitOr), Value::Int(0b1110));
+    assert_eq!(eval(0b1100, 0b1010, OpCode::BitXor), Value::Int(0b0110));
+
+    // Negative operands are two's complement
+    assert_eq!(eval(-1, 0xFF, OpCode::BitAnd), Value::Int(0xFF));
+    assert_eq!(eval(-8, 3, OpCode::BitOr), Value::Int(-5));
+    assert_eq!(eval(-1, 5, OpCode::BitXor), Value::Int(!5));
+    assert_eq!(eval(i64::MIN, -1, OpCode::BitAnd), Value::Int(i64::MIN));
+}
+
+#[test]
+fn test_shifts() {
+    assert_eq!(eval(3, 4, OpCode::Shl), Value::Int(48));
+    assert_eq!(eval(48, 4, OpCode::Shr), Value::Int(3));
+    assert_eq!(eval(5, 0, OpCode::Shl), Value::Int(5));
+
+    // Bits shifted past the top are lost, so the sign can change
+    assert_eq!(eval(1, 63, OpCode::Shl), Value::Int(i64::MIN));
+    assert_eq!(eval(-1, 63, OpCode::Shl), Value::Int(i64::MIN));
+    assert_eq!(eval(3, 63, OpCode::Shl), Value::Int(i64::MIN));
+
+    // Shr copies the sign bit, rounding towards negative infinity
+    assert_eq!(eval(-16, 2, OpCode::Shr), Value::Int(-4));
+    assert_eq!(eval(-1, 63, OpCode::Shr), Value::Int(-1));
+    assert_eq!(eval(-7, 1, OpCode::Shr), Value::Int(-4));
+    assert_eq!(eval(i64::MIN, 63, OpCode::Shr), Value::Int(-1));
+}
+
+#[test]
+fn test_shift_amount_out_of_range_overflows() {
+    for (amount, op) in [
+        (64, OpCode::Shl),
+        (64, OpCode::Shr),
+        (1000, OpCode::Shl),
+        (-1, OpCode::Shl),
+        (-1, OpCode::Shr),
+    ] {
+        let result = vm(1, amount, op).run();
+        assert!(
+            matches!(result, Err(VmError::ArithmeticOverflow { .. })),
+            "{op:?} by {amount}: {result:?}"
+        );
+    }
+}
+
+#[test]
+fn test_non_int_operands_are_type_mismatch() {
+    let mut vm = VmState::new(
+        vec![OpCode::Float(1.0), OpCode::Int(1), OpCode::BitAnd],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    );
+    assert!(matches!(vm.run(), Err(VmError::TypeMismatch { .. })));
+}
@@ -0,0 +1,59 @@
+/// Test the StepsRemaining and MemoryRemaining introspection opcodes
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::VmState;
+
+#[test]
+fn test_steps_remaining_decreases_across_reads() {
+    let mut vm = VmState::new(
+        vec![
+            OpCode::StepsRemaining,
+            OpCode::Int(1),
+            OpCode::Int(2),
+            OpCode::Add,
+            OpCode::Pop,
+            OpCode::StepsRemaining,
+        ],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    );
+
+    let later = vm.run().unwrap();
+    let earlier = vm.stack.pop().unwrap();
+
+    match (earlier, later) {
+        (Value::Int(earlier), Value::Int(later)) => {
+            assert!(earlier <= 100);
+            assert_eq!(earlier - later, 5, "the first read plus four instructions");
+        }
+        other => panic!("Expected two Ints, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_memory_remaining_reflects_allocation() {
+    let mut vm = VmState::new(
+        vec![
+            OpCode::MemoryRemaining,
+            OpCode::Int(1),
+            OpCode::Int(2),
+            OpCode::Cons,
+            OpCode::Pop,
+            OpCode::MemoryRemaining,
+        ],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    );
+
+    let after = vm.run().unwrap();
+    assert_eq!(vm.stack.pop(), Some(Value::Int(1024)));
+    match after {
+        Value::Int(after) => assert!(after < 1024, "{after} bytes left after a cons"),
+        other => panic!("Expected Int, got {:?}", other),
+    }
+}
@@ -0,0 +1,90 @@
+/// Test byte strings for host data that is not valid UTF-8
+use physics_world::types::{Capability, HostFunction, OpCode, Value};
+use physics_world::vm::state::VmState;
+use physics_world::vm::HostInput;
+use std::sync::Arc;
+
+fn vm(instructions: Vec<OpCode>, constants: Vec<Value>) -> VmState {
+    VmState::new(instructions, constants, 100, 4096, 1, 100)
+}
+
+/// Host input handing out one fixed payload
+struct Payload(Vec<u8>);
+
+impl HostInput for Payload {
+    fn receive(&self, _actor_id: u32, function: HostFunction) -> Option<Vec<u8>> {
+        (function == HostFunction::NetworkReceive).then(|| self.0.clone())
+    }
+}
+
+#[test]
+fn test_invalid_utf8_from_host_is_kept_as_bytes() {
+    let received = Value::from_host_bytes(vec![0x66, 0x6f, 0xff, 0xfe]);
+    assert_eq!(received, Value::Bytes(vec![0x66, 0x6f, 0xff, 0xfe]));
+
+    // Receive it through a host call, store it in a vector and read it back
+    let mut vm = vm(
+        vec![
+            OpCode::HostCall {
+                cap_idx: 0,
+                func_id: HostFunction::NetworkReceive as u16,
+                args: 0,
+            },
+            OpCode::MakeVector(1),
+            OpCode::Int(0),
+            OpCode::VecGet,
+        ],
+        vec![Value::Capability(Capability::IoNetwork)],
+    );
+    vm.set_host_input(Arc::new(Payload(vec![0x66, 0x6f, 0xff, 0xfe])));
+
+    assert_eq!(vm.run().unwrap(), received);
+}
+
+#[test]
+fn test_receive_without_host_input_is_nil() {
+    let mut vm = vm(
+        vec![OpCode::HostCall {
+            cap_idx: 0,
+            func_id: HostFunction::NetworkReceive as u16,
+            args: 0,
+        }],
+        vec![Value::Capability(Capability::IoNetwork)],
+    );
+    assert_eq!(vm.run().unwrap(), Value::Nil);
+}
+
+#[test]
+fn test_valid_utf8_from_host_is_a_string() {
+    assert_eq!(
+        Value::from_host_bytes("héllo".as_bytes().to_vec()),
+        Value::String("héllo".to_string())
+    );
+}
+
+#[test]
+fn test_bytes_to_str_on_invalid_utf8_pushes_error_value() {
+    let mut vm = vm(vec![OpCode::BytesToStr], vec![]);
+    vm.stack.push(Value::Bytes(vec![0xc3, 0x28]));
+
+    match vm.run().unwrap() {
+        Value::Error(message) => assert!(message.starts_with("Invalid UTF-8")),
+        other => panic!("Expected an error value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_str_bytes_round_trip() {
+    let mut vm = vm(
+        vec![
+            OpCode::LoadString(0),
+            OpCode::StrToBytes,
+            OpCode::Dup,
+            OpCode::BytesToStr,
+        ],
+        vec![Value::String("héllo".to_string())],
+    );
+
+    assert_eq!(vm.run().unwrap(), Value::String("héllo".to_string()));
+    assert_eq!(vm.stack, vec![Value::Bytes("héllo".as_bytes().to_vec())]);
+}
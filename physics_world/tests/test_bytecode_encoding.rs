@@ -0,0 +1,163 @@
+/// Test that the binary bytecode encoding agrees with OpCode::size_bytes
+use physics_world::types::{decode_program, encode_program, BytecodeError, OpCode, OPCODE_COUNT};
+
+/// One instance of every opcode variant, with non-trivial operands
+fn every_opcode() -> Vec<OpCode> {
+    vec![
+        OpCode::Nil,
+        OpCode::Bool(true),
+        OpCode::Int(-42),
+        OpCode::Float(2.5),
+        OpCode::Symbol(7),
+        OpCode::LoadString(3),
+        OpCode::StrLen,
+        OpCode::StrConcat,
+        OpCode::StrIndex,
+        OpCode::BytesToStr,
+        OpCode::StrToBytes,
+        OpCode::Swap,
+        OpCode::Dup,
+        OpCode::Pop,
+        OpCode::GetLocal(2),
+        OpCode::SetLocal(u16::MAX),
+        OpCode::Cons,
+        OpCode::Car,
+        OpCode::Cdr,
+        OpCode::ListLen,
+        OpCode::DeepClone,
+        OpCode::MakeThunk,
+        OpCode::Force,
+        OpCode::MakeVector(4),
+        OpCode::VecGet,
+        OpCode::VecSet,
+        OpCode::VecLen,
+        OpCode::TypeOf,
+        OpCode::IsInt,
+        OpCode::IsFloat,
+        OpCode::IsPair,
+        OpCode::IsClosure,
+        OpCode::Call(1),
+        OpCode::TailCall(2),
+        OpCode::CallN(3, 2),
+        OpCode::Ret,
+        OpCode::RetN(2),
+        OpCode::CollectRest(2),
+        OpCode::Jmp(-5),
+        OpCode::JmpIfFalse(9),
+        OpCode::Yield,
+        OpCode::Send,
+        OpCode::MakeClosure(1, 2),
+        OpCode::ClosureAlphaEq,
+        OpCode::GetConst(u32::MAX as usize),
+        OpCode::CheckStepLimit,
+        OpCode::GcCollect,
+        OpCode::GcStats,
+        OpCode::StepsRemaining,
+        OpCode::MemoryRemaining,
+        OpCode::Add,
+        OpCode::Sub,
+        OpCode::Mul,
+        OpCode::Div,
+        OpCode::Mod,
+        OpCode::BitAnd,
+        OpCode::BitOr,
+        OpCode::BitXor,
+        OpCode::Shl,
+        OpCode::Shr,
+        OpCode::FAdd,
+        OpCode::FSub,
+        OpCode::FMul,
+        OpCode::FDiv,
+        OpCode::Eq,
+        OpCode::Lt,
+        OpCode::Gt,
+        OpCode::Lte,
+        OpCode::Gte,
+        OpCode::Ne,
+        OpCode::HasCap(0),
+        OpCode::RequestCap(1, 2),
+        OpCode::GrantCap(9, 3),
+        OpCode::RevokeCap(u32::MAX, 4),
+        OpCode::HostCall {
+            cap_idx: 5,
+            func_id: 6,
+            args: 7,
+        },
+        OpCode::WithCaps {
+            cap_mask: 0b101,
+            body_len: 12,
+        },
+        OpCode::InitSandbox,
+        OpCode::IsolateCapabilities,
+        OpCode::SetErrorHandler(-3),
+        OpCode::LogSandboxViolation,
+        OpCode::CleanupSandbox,
+        OpCode::MakeError,
+        OpCode::Throw,
+        OpCode::PopErrorHandler,
+        OpCode::ErrorPayload,
+        OpCode::Spawn { arg_count: 2 },
+    ]
+}
+
+#[test]
+fn test_program_covers_every_opcode() {
+    let mut tags: Vec<u8> = every_opcode().iter().map(OpCode::tag).collect();
+    tags.sort_unstable();
+    assert_eq!(tags, (0..OPCODE_COUNT).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_every_opcode_round_trips_at_its_size() {
+    let program = every_opcode();
+    let encoded = encode_program(&program).unwrap();
+
+    let total: usize = program.iter().map(OpCode::size_bytes).sum();
+    assert_eq!(encoded.len(), total);
+    assert_eq!(decode_program(&encoded).unwrap(), program);
+}
+
+#[test]
+fn test_each_instruction_encodes_to_its_size() {
+    for op in every_opcode() {
+        let mut encoded = Vec::new();
+        op.encode(&mut encoded).unwrap();
+        assert_eq!(encoded.len(), op.size_bytes(), "size of {op:?}");
+        assert_eq!(encoded[0], op.tag());
+    }
+}
+
+#[test]
+fn test_every_opcode_has_metadata() {
+    for op in every_opcode() {
+        let meta = op.metadata();
+        assert!(
+            format!("{op:?}").starts_with(meta.mnemonic),
+            "mnemonic of {op:?}"
+        );
+        assert!(!meta.description.is_empty(), "description of {op:?}");
+    }
+
+    let add = OpCode::Add.metadata();
+    assert_eq!((add.stack_in, add.stack_out), (2, 1));
+    let call = OpCode::Call(3).metadata();
+    assert_eq!((call.stack_in, call.stack_out), (4, 1));
+}
+
+#[test]
+fn test_malformed_bytecode_is_rejected() {
+    assert_eq!(
+        encode_program(&[OpCode::GetConst(u32::MAX as usize + 1)]),
+        Err(BytecodeError::OperandTooLarge(u32::MAX as usize + 1))
+    );
+    assert_eq!(
+        decode_program(&[OPCODE_COUNT]),
+        Err(BytecodeError::UnknownTag(OPCODE_COUNT))
+    );
+
+    let encoded = encode_program(&[OpCode::Int(1)]).unwrap();
+    assert_eq!(
+        decode_program(&encoded[..encoded.len() - 1]),
+        Err(BytecodeError::UnexpectedEnd)
+    );
+}
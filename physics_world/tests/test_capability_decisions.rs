@@ -0,0 +1,166 @@
+/// Test parking actors on capability requests and resuming them on a decision
+use physics_world::scheduler::{Actor, CapDecision, PhysicsError, PhysicsScheduler, TickResult};
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::state::VmState;
+use std::collections::HashSet;
+
+fn actor(id: u32, instructions: Vec<OpCode>) -> Actor {
+    let constants = vec![Value::Capability(Capability::IoNetwork), Value::Symbol(0)];
+    Actor {
+        id,
+        vm: VmState::new(instructions, constants, 100, 1024, id, 100),
+        mailbox: Vec::new(),
+        is_waiting: false,
+        capabilities: HashSet::new(),
+        capability_requests: Vec::new(),
+        parent_id: None,
+        priority: 128,
+        priority_boost: None,
+    }
+}
+
+/// Run actor 1 up to its request and park it there
+fn park_request(scheduler: &mut PhysicsScheduler) {
+    match scheduler.tick().unwrap() {
+        TickResult::ActorWaitingForCapability(1, capability) => {
+            assert_eq!(capability, Capability::IoNetwork);
+            scheduler
+                .park_capability_request(1, capability, "fetch data")
+                .unwrap();
+        }
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+}
+
+#[test]
+fn test_granted_actor_resumes_and_passes_has_cap() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, vec![OpCode::RequestCap(0, 1), OpCode::HasCap(0)]));
+    park_request(&mut scheduler);
+
+    // Parked until someone decides
+    assert!(scheduler.actors[0].is_waiting);
+    assert_eq!(
+        scheduler.pending_capability_requests(),
+        [(1, Capability::IoNetwork)]
+    );
+    assert!(scheduler.tick().is_err());
+
+    scheduler.grant(1, Capability::IoNetwork).unwrap();
+    assert!(!scheduler.actors[0].is_waiting);
+    assert!(scheduler.pending_capability_requests().is_empty());
+    assert!(scheduler.actor_has_capability(1, &Capability::IoNetwork));
+    assert_eq!(scheduler.actors[0].vm.stack, [Value::Bool(true)]);
+
+    match scheduler.tick().unwrap() {
+        TickResult::ActorFinished(1, Value::Bool(true)) => {}
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+}
+
+#[test]
+fn test_denied_actor_resumes_with_false() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, vec![OpCode::RequestCap(0, 1)]));
+    park_request(&mut scheduler);
+
+    scheduler.deny(1, Capability::IoNetwork).unwrap();
+    assert!(!scheduler.actor_has_capability(1, &Capability::IoNetwork));
+    assert_eq!(
+        scheduler.actors[0].capability_requests[0].granted,
+        Some(false)
+    );
+    match scheduler.tick().unwrap() {
+        TickResult::ActorFinished(1, Value::Bool(false)) => {}
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+}
+
+#[test]
+fn test_revoked_capability_fails_later_check() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(
+        1,
+        vec![
+            OpCode::RequestCap(0, 1),
+            OpCode::Pop,
+            OpCode::Yield,
+            OpCode::HasCap(0),
+        ],
+    ));
+    park_request(&mut scheduler);
+    scheduler.grant(1, Capability::IoNetwork).unwrap();
+    assert!(matches!(
+        scheduler.tick().unwrap(),
+        TickResult::ActorYielded(1)
+    ));
+
+    scheduler.revoke(1, &Capability::IoNetwork).unwrap();
+    assert!(!scheduler.actor_has_capability(1, &Capability::IoNetwork));
+    match scheduler.tick().unwrap() {
+        TickResult::ActorFinished(1, Value::Bool(false)) => {}
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+}
+
+#[test]
+fn test_automatic_grant_is_seen_by_has_cap() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, vec![OpCode::HasCap(0)]));
+
+    let decision = scheduler.handle_capability_request(1, Capability::IoNetwork, "fetch data");
+    assert!(matches!(decision, CapDecision::Granted));
+    assert!(scheduler.actors[0]
+        .vm
+        .has_capability(&Capability::IoNetwork));
+    assert!(scheduler.actors[0]
+        .capabilities
+        .contains(&Capability::IoNetwork));
+    match scheduler.tick().unwrap() {
+        TickResult::ActorFinished(1, Value::Bool(true)) => {}
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+}
+
+#[test]
+fn test_deciding_without_pending_request_is_an_error() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, vec![OpCode::Nil]));
+
+    assert!(matches!(
+        scheduler.grant(1, Capability::IoNetwork),
+        Err(PhysicsError::CapabilityError(_))
+    ));
+    assert!(matches!(
+        scheduler.deny(7, Capability::IoNetwork),
+        Err(PhysicsError::ActorNotFound(7))
+    ));
+    assert!(scheduler.actors[0].vm.stack.is_empty());
+}
+
+#[test]
+fn test_delegated_grant_resumes_parked_requester() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, vec![OpCode::RequestCap(0, 1), OpCode::HasCap(0)]));
+    let mut granter = actor(2, vec![]);
+    granter.vm.grant_capability(Capability::MetaGrant);
+    granter.vm.grant_capability(Capability::IoNetwork);
+    scheduler.add_actor(granter);
+    park_request(&mut scheduler);
+
+    scheduler
+        .grant_capability(2, 1, Capability::IoNetwork)
+        .unwrap();
+    assert!(!scheduler.actors[0].is_waiting);
+    assert!(scheduler.pending_capability_requests().is_empty());
+    assert_eq!(
+        scheduler.actors[0].capability_requests[0].granted,
+        Some(true)
+    );
+    // The granter may run first
+    let finished = (0..2).find_map(|_| match scheduler.tick().unwrap() {
+        TickResult::ActorFinished(1, value) => Some(value),
+        _ => None,
+    });
+    assert_eq!(finished, Some(Value::Bool(true)));
+}
@@ -0,0 +1,102 @@
+/// Test time-boxed capability grants that lapse without an explicit revoke
+use physics_world::scheduler::{Actor, PhysicsScheduler, TickResult};
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::{CapabilityExpiry, VmState};
+use std::collections::HashSet;
+use std::time::Duration;
+
+fn vm_with(instructions: Vec<OpCode>) -> VmState {
+    let constants = vec![Value::Capability(Capability::IoNetwork)];
+    VmState::new(instructions, constants, 100, 1024, 1, 100)
+}
+
+#[test]
+fn test_step_expiry_lapses_after_window() {
+    // Check, burn four instructions, check again
+    let mut vm = vm_with(vec![
+        OpCode::HasCap(0),
+        OpCode::Int(0),
+        OpCode::Pop,
+        OpCode::Int(0),
+        OpCode::Pop,
+        OpCode::HasCap(0),
+    ]);
+    vm.grant_capability_until(Capability::IoNetwork, CapabilityExpiry::Steps(3));
+
+    assert_eq!(vm.run().unwrap(), Value::Bool(false));
+    assert_eq!(vm.stack, vec![Value::Bool(true)]);
+    // The lapsed grant was pruned by the check itself
+    assert!(vm.capabilities.is_empty());
+    assert!(vm.capability_deadlines.is_empty());
+}
+
+#[test]
+fn test_wall_clock_expiry_lapses_after_window() {
+    let mut vm = vm_with(vec![OpCode::HasCap(0)]);
+    vm.grant_capability_until(
+        Capability::IoNetwork,
+        CapabilityExpiry::WallClock(Duration::from_millis(20)),
+    );
+    assert_eq!(vm.run().unwrap(), Value::Bool(true));
+
+    std::thread::sleep(Duration::from_millis(40));
+    let mut later = vm_with(vec![OpCode::HasCap(0)]);
+    later.capabilities = vm.capabilities.clone();
+    later.capability_deadlines = vm.capability_deadlines.clone();
+    assert!(!later.has_capability(&Capability::IoNetwork));
+    assert_eq!(later.run().unwrap(), Value::Bool(false));
+    assert!(later.capabilities.is_empty());
+}
+
+#[test]
+fn test_permanent_grant_replaces_expiring_one() {
+    let mut vm = vm_with(vec![OpCode::Int(0), OpCode::Pop, OpCode::HasCap(0)]);
+    vm.grant_capability_until(Capability::IoNetwork, CapabilityExpiry::Steps(1));
+    vm.grant_capability(Capability::IoNetwork);
+
+    assert_eq!(vm.run().unwrap(), Value::Bool(true));
+}
+
+fn actor(id: u32, vm: VmState) -> Actor {
+    Actor {
+        id,
+        vm,
+        mailbox: Vec::new(),
+        is_waiting: false,
+        capabilities: HashSet::new(),
+        capability_requests: Vec::new(),
+        parent_id: None,
+        priority: 128,
+        priority_boost: None,
+    }
+}
+
+#[test]
+fn test_scheduler_grant_with_expiry_lapses() {
+    let mut granter = vm_with(Vec::new());
+    granter.grant_capability(Capability::MetaGrant);
+    granter.grant_capability(Capability::IoNetwork);
+    let target = vm_with(vec![
+        OpCode::HasCap(0),
+        OpCode::Int(0),
+        OpCode::Pop,
+        OpCode::HasCap(0),
+    ]);
+
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, target));
+    scheduler.add_actor(actor(2, granter));
+    scheduler
+        .grant_capability_until(2, 1, Capability::IoNetwork, CapabilityExpiry::Steps(2))
+        .unwrap();
+    assert!(scheduler.actors[0]
+        .capabilities
+        .contains(&Capability::IoNetwork));
+
+    assert!(matches!(
+        scheduler.tick().unwrap(),
+        TickResult::ActorFinished(1, Value::Bool(false))
+    ));
+    assert_eq!(scheduler.actors[0].vm.stack, vec![Value::Bool(true)]);
+    assert!(scheduler.actors[0].capabilities.is_empty());
+}
@@ -0,0 +1,110 @@
+/// Test that capability opcodes report their events to an installed observer
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::{CapabilityObserver, InstructionResult, SymbolTable, VmState};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    Request(u32, Capability, String),
+    Check(u32, Capability, bool),
+    Grant(u32, u32, Capability),
+    Revoke(u32, u32, Capability),
+}
+
+#[derive(Default)]
+struct Recorder {
+    events: Mutex<Vec<Event>>,
+}
+
+impl CapabilityObserver for Recorder {
+    fn on_request(&self, actor_id: u32, capability: &Capability, justification: &str) {
+        self.events.lock().unwrap().push(Event::Request(
+            actor_id,
+            capability.clone(),
+            justification.to_string(),
+        ));
+    }
+
+    fn on_check(&self, actor_id: u32, capability: &Capability, held: bool) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(Event::Check(actor_id, capability.clone(), held));
+    }
+
+    fn on_grant(&self, actor_id: u32, target_actor_id: u32, capability: &Capability) {
+        self.events.lock().unwrap().push(Event::Grant(
+            actor_id,
+            target_actor_id,
+            capability.clone(),
+        ));
+    }
+
+    fn on_revoke(&self, actor_id: u32, target_actor_id: u32, capability: &Capability) {
+        self.events.lock().unwrap().push(Event::Revoke(
+            actor_id,
+            target_actor_id,
+            capability.clone(),
+        ));
+    }
+}
+
+#[test]
+fn test_request_check_revoke_events_in_order() {
+    let mut symbols = SymbolTable::new();
+    let reason = symbols.intern("need clock");
+    let instructions = vec![
+        OpCode::RequestCap(0, 1),
+        OpCode::HasCap(0),
+        OpCode::RevokeCap(9, 0),
+    ];
+    let constants = vec![
+        Value::Capability(Capability::SysClock),
+        Value::Symbol(reason),
+    ];
+    let mut vm = VmState::new(instructions, constants, 100, 1024, 7, 100);
+    vm.attach_symbol_table(symbols);
+    let recorder = Arc::new(Recorder::default());
+    vm.set_capability_observer(recorder.clone());
+
+    assert!(matches!(
+        vm.step(),
+        Ok(InstructionResult::WaitingForCapability(
+            Capability::SysClock
+        ))
+    ));
+    while vm.ip < vm.instructions.len() {
+        assert!(matches!(vm.step(), Ok(InstructionResult::Continue)));
+    }
+
+    assert_eq!(
+        *recorder.events.lock().unwrap(),
+        vec![
+            Event::Request(7, Capability::SysClock, "need clock".to_string()),
+            Event::Check(7, Capability::SysClock, false),
+            Event::Revoke(7, 9, Capability::SysClock),
+        ]
+    );
+    assert_eq!(vm.stack, vec![Value::Bool(false)]);
+}
+
+#[test]
+fn test_grant_event() {
+    let mut vm = VmState::new(
+        vec![OpCode::GrantCap(3, 0)],
+        vec![Value::Capability(Capability::IoNetwork)],
+        100,
+        1024,
+        1,
+        100,
+    );
+    let recorder = Arc::new(Recorder::default());
+    vm.set_capability_observer(recorder.clone());
+
+    vm.step().unwrap();
+
+    assert_eq!(
+        *recorder.events.lock().unwrap(),
+        vec![Event::Grant(1, 3, Capability::IoNetwork)]
+    );
+}
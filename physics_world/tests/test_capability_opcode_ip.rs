@@ -0,0 +1,47 @@
+/// Test that the capability opcodes move past themselves
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::{InstructionResult, SymbolTable, VmState};
+
+#[test]
+fn test_has_cap_continues_with_the_next_instruction() {
+    let mut vm = VmState::new(
+        vec![OpCode::HasCap(0), OpCode::Int(7)],
+        vec![Value::Capability(Capability::SysClock)],
+        100,
+        1024,
+        1,
+        100,
+    );
+
+    assert!(matches!(vm.step(), Ok(InstructionResult::Continue)));
+    assert_eq!(vm.ip, 1);
+    assert_eq!(vm.run().unwrap(), Value::Int(7));
+    assert_eq!(vm.stack, [Value::Bool(false)]);
+}
+
+#[test]
+fn test_request_cap_resumes_after_the_request() {
+    let mut symbols = SymbolTable::new();
+    let reason = symbols.intern("need clock");
+    let mut vm = VmState::new(
+        vec![OpCode::RequestCap(0, 1), OpCode::Int(7)],
+        vec![
+            Value::Capability(Capability::SysClock),
+            Value::Symbol(reason),
+        ],
+        100,
+        1024,
+        1,
+        100,
+    );
+    vm.attach_symbol_table(symbols);
+
+    assert!(matches!(
+        vm.step(),
+        Ok(InstructionResult::WaitingForCapability(
+            Capability::SysClock
+        ))
+    ));
+    // The decision is pushed by the scheduler; execution resumes past the request
+    assert_eq!(vm.ip, 1);
+}
@@ -0,0 +1,113 @@
+/// Test that unanswered capability requests are auto-denied after their timeout
+use physics_world::scheduler::{Actor, CapDecision, PhysicsScheduler, TickResult};
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::state::VmState;
+use std::collections::HashSet;
+
+fn actor(id: u32, instructions: Vec<OpCode>) -> Actor {
+    let constants = vec![Value::Capability(Capability::MetaGrant), Value::Symbol(0)];
+    Actor {
+        id,
+        vm: VmState::new(instructions, constants, 100, 1024, id, 100),
+        mailbox: Vec::new(),
+        is_waiting: false,
+        capabilities: HashSet::new(),
+        capability_requests: Vec::new(),
+        parent_id: None,
+        priority: 128,
+        priority_boost: None,
+    }
+}
+
+/// Run the requesting actor up to its request and leave the request pending
+fn request_meta_grant(scheduler: &mut PhysicsScheduler, timeout: Option<u64>) {
+    match scheduler.tick().unwrap() {
+        TickResult::ActorWaitingForCapability(1, capability) => {
+            let decision =
+                scheduler.handle_capability_request_with_timeout(1, capability, "", timeout);
+            assert!(matches!(decision, CapDecision::PendingConsensus));
+        }
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+}
+
+#[test]
+fn test_unanswered_request_is_denied_after_timeout() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.capability_request_timeout = Some(3);
+    scheduler.add_actor(actor(1, vec![OpCode::RequestCap(0, 1)]));
+
+    match scheduler.tick().unwrap() {
+        TickResult::ActorWaitingForCapability(id, capability) => {
+            let decision = scheduler.handle_capability_request(id, capability, "");
+            assert!(matches!(decision, CapDecision::PendingConsensus));
+        }
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+    assert_eq!(scheduler.actors[0].capability_requests[0].timeout, Some(3));
+
+    // Blocked with no one else to run until the timeout elapses
+    assert!(scheduler.tick().is_err());
+    assert!(scheduler.tick().is_err());
+    match scheduler.tick().unwrap() {
+        TickResult::CapabilityRequestTimedOut(1, Capability::MetaGrant) => {}
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+    assert_eq!(
+        scheduler.actors[0].capability_requests[0].granted,
+        Some(false)
+    );
+    assert!(!scheduler.actors[0].is_waiting);
+
+    // The actor resumes and sees the denial
+    match scheduler.tick().unwrap() {
+        TickResult::ActorFinished(1, Value::Error(message)) => {
+            assert!(message.starts_with("CapabilityDenied"));
+        }
+        other => panic!("Unexpected tick result {:?}", other),
+    }
+}
+
+#[test]
+fn test_other_actors_run_while_request_is_pending() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, vec![OpCode::RequestCap(0, 1)]));
+    scheduler.add_actor(actor(2, vec![OpCode::Yield; 8]));
+    request_meta_grant(&mut scheduler, Some(2));
+
+    assert!(matches!(
+        scheduler.tick().unwrap(),
+        TickResult::ActorYielded(2)
+    ));
+    assert!(matches!(
+        scheduler.tick().unwrap(),
+        TickResult::CapabilityRequestTimedOut(1, Capability::MetaGrant)
+    ));
+}
+
+#[test]
+fn test_request_without_timeout_keeps_waiting() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, vec![OpCode::RequestCap(0, 1)]));
+    scheduler.add_actor(actor(2, vec![OpCode::Yield; 32]));
+    request_meta_grant(&mut scheduler, None);
+
+    for _ in 0..20 {
+        assert!(matches!(
+            scheduler.tick().unwrap(),
+            TickResult::ActorYielded(2)
+        ));
+    }
+    assert!(scheduler.actors[0].is_waiting);
+    assert_eq!(scheduler.actors[0].capability_requests[0].granted, None);
+}
+
+#[test]
+fn test_clone_keeps_the_tick_clock() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(actor(1, vec![OpCode::Yield, OpCode::Yield]));
+    scheduler.tick().unwrap();
+    scheduler.tick().unwrap();
+
+    assert_eq!(scheduler.clone().tick_count, 2);
+}
@@ -0,0 +1,135 @@
+/// Test WithCaps regions that run code with a reduced capability set
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::VmState;
+
+const NETWORK_SEND: OpCode = OpCode::HostCall {
+    cap_idx: 0,
+    func_id: 5,
+    args: 0,
+};
+
+fn networked_vm(instructions: Vec<OpCode>) -> VmState {
+    let mut vm = VmState::new(
+        instructions,
+        vec![Value::Capability(Capability::IoNetwork)],
+        100,
+        1024,
+        1,
+        100,
+    );
+    vm.grant_capability(Capability::IoNetwork);
+    vm.grant_capability(Capability::IoReadSensor);
+    vm
+}
+
+#[test]
+fn test_dropped_capability_cannot_be_used_inside() {
+    let mut parent = networked_vm(vec![NETWORK_SEND]);
+    assert_eq!(parent.run().unwrap(), Value::Nil);
+
+    let mut vm = networked_vm(vec![
+        OpCode::WithCaps {
+            cap_mask: Capability::mask_of(&[Capability::IoReadSensor]),
+            body_len: 1,
+        },
+        NETWORK_SEND,
+    ]);
+    assert!(matches!(vm.run(), Err(VmError::CapabilityError { .. })));
+}
+
+#[test]
+fn test_kept_capability_remains_usable() {
+    let mut vm = networked_vm(vec![
+        OpCode::WithCaps {
+            cap_mask: Capability::mask_of(&[Capability::IoNetwork]),
+            body_len: 1,
+        },
+        NETWORK_SEND,
+    ]);
+    assert_eq!(vm.run().unwrap(), Value::Nil);
+}
+
+#[test]
+fn test_capabilities_restored_after_region() {
+    let mut vm = networked_vm(vec![
+        OpCode::WithCaps {
+            cap_mask: 0,
+            body_len: 1,
+        },
+        OpCode::HasCap(0), // Inside: dropped
+        OpCode::HasCap(0), // After: restored
+    ]);
+
+    assert_eq!(vm.run().unwrap(), Value::Bool(true));
+    assert_eq!(vm.stack, vec![Value::Bool(false)]);
+    assert!(vm.capability_scopes.is_empty());
+    assert_eq!(
+        vm.capabilities,
+        vec![Capability::IoNetwork, Capability::IoReadSensor]
+    );
+}
+
+#[test]
+fn test_mask_cannot_add_capabilities() {
+    let mut vm = VmState::new(
+        vec![
+            OpCode::WithCaps {
+                cap_mask: u32::MAX,
+                body_len: 1,
+            },
+            OpCode::HasCap(0),
+        ],
+        vec![Value::Capability(Capability::IoNetwork)],
+        100,
+        1024,
+        1,
+        100,
+    );
+    assert_eq!(vm.run().unwrap(), Value::Bool(false));
+}
+
+#[test]
+fn test_capability_revoked_inside_region_stays_revoked() {
+    // Mask keeps IoNetwork; IoReadSensor is hidden
+    let mut vm = networked_vm(vec![
+        OpCode::WithCaps {
+            cap_mask: Capability::mask_of(&[Capability::IoNetwork]),
+            body_len: 1,
+        },
+        OpCode::Int(1),
+        OpCode::HasCap(0),
+    ]);
+    vm.step().unwrap();
+    vm.revoke_capability(&Capability::IoNetwork);
+    vm.revoke_capability(&Capability::IoReadSensor);
+
+    assert_eq!(vm.run().unwrap(), Value::Bool(false));
+    assert!(vm.capability_scopes.is_empty());
+    assert!(vm.capabilities.is_empty());
+}
+
+#[test]
+fn test_capability_granted_inside_region_survives_it() {
+    let mut vm = networked_vm(vec![
+        OpCode::WithCaps {
+            cap_mask: Capability::mask_of(&[Capability::IoNetwork]),
+            body_len: 1,
+        },
+        OpCode::Int(1),
+        OpCode::HasCap(0),
+    ]);
+    vm.step().unwrap();
+    vm.grant_capability(Capability::SysGc);
+
+    assert_eq!(vm.run().unwrap(), Value::Bool(true));
+    assert!(vm.capability_scopes.is_empty());
+    assert_eq!(
+        vm.capabilities,
+        vec![
+            Capability::IoNetwork,
+            Capability::SysGc,
+            Capability::IoReadSensor
+        ]
+    );
+}
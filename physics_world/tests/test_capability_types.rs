@@ -94,9 +94,9 @@ fn test_capability_opcodes() {
     // Test sizes
     assert_eq!(opcodes[0].size_bytes(), 5); // HasCap
     assert_eq!(opcodes[1].size_bytes(), 9); // RequestCap
-    assert_eq!(opcodes[2].size_bytes(), 6); // GrantCap
-    assert_eq!(opcodes[3].size_bytes(), 6); // RevokeCap
-    assert_eq!(opcodes[4].size_bytes(), 7); // HostCall
+    assert_eq!(opcodes[2].size_bytes(), 9); // GrantCap
+    assert_eq!(opcodes[3].size_bytes(), 9); // RevokeCap
+    assert_eq!(opcodes[4].size_bytes(), 8); // HostCall
 
     // Test serialization
     for opcode in &opcodes {
@@ -317,16 +317,16 @@ fn test_capability_opcode_size_calculations() {
         9
     );
 
-    assert_eq!(OpCode::GrantCap(0, 0).size_bytes(), 6);
+    assert_eq!(OpCode::GrantCap(0, 0).size_bytes(), 9);
     assert_eq!(
         OpCode::GrantCap(u32::MAX, u32::MAX as usize).size_bytes(),
-        6
+        9
     );
 
-    assert_eq!(OpCode::RevokeCap(0, 0).size_bytes(), 6);
+    assert_eq!(OpCode::RevokeCap(0, 0).size_bytes(), 9);
     assert_eq!(
         OpCode::RevokeCap(u32::MAX, u32::MAX as usize).size_bytes(),
-        6
+        9
     );
 
     assert_eq!(
@@ -336,7 +336,7 @@ fn test_capability_opcode_size_calculations() {
             args: 0
         }
         .size_bytes(),
-        7
+        8
     );
     assert_eq!(
         OpCode::HostCall {
@@ -345,7 +345,7 @@ fn test_capability_opcode_size_calculations() {
             args: u8::MAX
         }
         .size_bytes(),
-        7
+        8
     );
 }
 
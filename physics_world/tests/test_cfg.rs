@@ -0,0 +1,65 @@
+/// Test basic-block partitioning and edges produced by build_cfg
+use physics_world::analysis::{build_cfg, Edge, EdgeKind};
+use physics_world::types::OpCode;
+
+/// `(if true 1 2)` inside a handler-protected region, followed by the handler
+fn guarded_if() -> Vec<OpCode> {
+    vec![
+        OpCode::SetErrorHandler(7), // 0: handler at 8
+        OpCode::Bool(true),         // 1
+        OpCode::JmpIfFalse(2),      // 2: else at 5
+        OpCode::Int(1),             // 3
+        OpCode::Jmp(1),             // 4: join at 6
+        OpCode::Int(2),             // 5
+        OpCode::PopErrorHandler,    // 6
+        OpCode::Jmp(1),             // 7: skip the handler
+        OpCode::ErrorPayload,       // 8: handler
+        OpCode::Ret,                // 9
+    ]
+}
+
+fn edge(target: usize, kind: EdgeKind) -> Edge {
+    Edge { target, kind }
+}
+
+#[test]
+fn test_if_expression_blocks_and_edges() {
+    let cfg = build_cfg(&guarded_if());
+
+    let ranges: Vec<_> = cfg.iter().map(|block| block.instructions()).collect();
+    assert_eq!(ranges, vec![0..1, 1..3, 3..5, 5..6, 6..8, 8..9, 9..10]);
+
+    assert_eq!(
+        cfg.successors(0),
+        [edge(1, EdgeKind::FallThrough), edge(5, EdgeKind::Catch)]
+    );
+    assert_eq!(
+        cfg.successors(1),
+        [edge(2, EdgeKind::FallThrough), edge(3, EdgeKind::Branch)]
+    );
+    assert_eq!(cfg.successors(2), [edge(4, EdgeKind::Jump)]);
+    assert_eq!(cfg.successors(3), [edge(4, EdgeKind::FallThrough)]);
+    assert_eq!(cfg.successors(4), [edge(6, EdgeKind::Jump)]);
+    assert_eq!(cfg.successors(5), [edge(6, EdgeKind::FallThrough)]);
+    assert!(cfg.successors(6).is_empty());
+}
+
+#[test]
+fn test_predecessor_queries() {
+    let cfg = build_cfg(&guarded_if());
+
+    assert!(cfg.predecessors(0).is_empty());
+    assert_eq!(cfg.predecessors(4), [2, 3]);
+    assert_eq!(cfg.predecessors(5), [0]);
+    assert_eq!(cfg.predecessors(6), [4, 5]);
+    assert_eq!(cfg.block_of(4), Some(2));
+    assert_eq!(cfg.block_of(10), None);
+}
+
+#[test]
+fn test_straight_line_code_is_one_block() {
+    let cfg = build_cfg(&[OpCode::Int(1), OpCode::Int(2), OpCode::Add]);
+    assert_eq!(cfg.len(), 1);
+    assert!(cfg.successors(0).is_empty());
+    assert!(build_cfg(&[]).is_empty());
+}
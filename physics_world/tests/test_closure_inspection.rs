@@ -0,0 +1,107 @@
+/// Test closure introspection for the debugger
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::opcodes::make_closure::create_closure_body;
+use physics_world::vm::VmState;
+
+fn vm_with_closure_on_stack() -> VmState {
+    let mut vm = VmState::new(
+        vec![OpCode::Int(10), OpCode::Int(20), OpCode::MakeClosure(2, 2)],
+        vec![Value::Nil, Value::Nil, Value::Nil],
+        100,
+        2048,
+        1,
+        100,
+    );
+    let body = vec![
+        OpCode::GetLocal(0),
+        OpCode::GetLocal(1),
+        OpCode::Add,
+        OpCode::SetLocal(2),
+        OpCode::GetLocal(2),
+        OpCode::Ret,
+    ];
+    let body_ptr = create_closure_body(&mut vm, body).unwrap();
+    vm.constant_pool[2] = Value::Closure(body_ptr);
+
+    for _ in 0..3 {
+        vm.step().unwrap();
+    }
+    vm
+}
+
+#[test]
+fn test_inspect_closure_reports_arity_and_captures() {
+    let vm = vm_with_closure_on_stack();
+    let Some(Value::Closure(ptr)) = vm.stack.last() else {
+        panic!("Expected a closure on the stack, got {:?}", vm.stack);
+    };
+
+    let info = vm.inspect_closure(*ptr).unwrap();
+    assert_eq!(info.arity, 2); // Slot 2 is a local written by the body
+    assert_eq!(info.capture_count, 2);
+    assert_eq!(info.captures, vec![10, 20]);
+    assert_eq!(info.body.last(), Some(&OpCode::Ret));
+}
+
+#[test]
+fn test_debug_snapshot_summarizes_stack_closures() {
+    let vm = vm_with_closure_on_stack();
+    let snapshot = vm.get_debug_snapshot();
+
+    assert_eq!(snapshot.closures.len(), 1);
+    let (slot, info) = &snapshot.closures[0];
+    assert_eq!(*slot, 0);
+    assert_eq!(info.arity, 2);
+}
+
+#[test]
+fn test_inspect_non_closure_pointer() {
+    let vm = VmState::new(vec![], vec![], 100, 2048, 1, 100);
+    assert!(vm
+        .inspect_closure(physics_world::types::HeapPtr::new(64))
+        .is_none());
+}
+
+#[test]
+fn test_inspect_closure_reads_exactly_its_captures() {
+    let mut vm = VmState::new(
+        vec![
+            OpCode::Int(1),
+            OpCode::Int(2),
+            OpCode::Int(3),
+            OpCode::Int(4),
+            OpCode::MakeClosure(0, 4),
+        ],
+        vec![Value::Nil],
+        100,
+        2048,
+        1,
+        100,
+    );
+    let body_ptr = create_closure_body(&mut vm, vec![OpCode::Nil, OpCode::Ret]).unwrap();
+    vm.constant_pool[0] = Value::Closure(body_ptr);
+    let Value::Closure(ptr) = vm.run().unwrap() else {
+        panic!("Expected a closure");
+    };
+
+    let info = vm.inspect_closure(ptr).unwrap();
+    assert_eq!(info.capture_count, 4);
+    assert_eq!(info.captures, vec![1, 2, 3, 4]);
+}
+
+#[test]
+fn test_inspect_pair_is_not_a_closure() {
+    let mut vm = VmState::new(
+        vec![OpCode::Int(1), OpCode::Int(2), OpCode::Cons],
+        vec![],
+        100,
+        2048,
+        1,
+        100,
+    );
+    let Value::Pair(ptr) = vm.run().unwrap() else {
+        panic!("Expected a pair");
+    };
+
+    assert!(vm.inspect_closure(ptr).is_none());
+}
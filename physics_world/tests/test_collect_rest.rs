@@ -0,0 +1,56 @@
+/// Test CollectRest gathering surplus arguments into a rest parameter
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::opcodes::closure::create_closure_body;
+use physics_world::vm::VmState;
+
+/// Call a closure running `body` with `args` as its arguments
+fn call_with(body: Vec<OpCode>, args: &[i64]) -> VmState {
+    let mut program: Vec<OpCode> = args.iter().map(|&arg| OpCode::Int(arg)).collect();
+    program.push(OpCode::MakeClosure(0, 0));
+    program.push(OpCode::Call(args.len() as u16));
+    let mut vm = VmState::new(program, vec![Value::Nil], 1000, 4096, 1, 100);
+    let body_ptr = create_closure_body(&mut vm, body).unwrap();
+    vm.constant_pool[0] = Value::Closure(body_ptr);
+    vm
+}
+
+#[test]
+fn test_rest_list_has_surplus_length_and_head() {
+    // Length of rest, then its first element
+    let body = vec![
+        OpCode::CollectRest(1),
+        OpCode::GetLocal(1),
+        OpCode::ListLen,
+        OpCode::GetLocal(1),
+        OpCode::Car,
+        OpCode::Add,
+        OpCode::Ret,
+    ];
+    // 3 surplus arguments, the first of them 20
+    assert_eq!(
+        call_with(body, &[10, 20, 30, 40]).run().unwrap(),
+        Value::Int(23)
+    );
+}
+
+#[test]
+fn test_fixed_arguments_keep_their_slots() {
+    let body = vec![OpCode::CollectRest(2), OpCode::GetLocal(1), OpCode::Ret];
+    assert_eq!(call_with(body, &[1, 2, 3, 4]).run().unwrap(), Value::Int(2));
+}
+
+#[test]
+fn test_too_few_arguments_underflow() {
+    let body = vec![OpCode::CollectRest(2), OpCode::GetLocal(2), OpCode::Ret];
+    assert!(call_with(body, &[1]).run().is_err());
+}
+
+#[test]
+fn test_top_level_locals_collect_outside_a_call() {
+    let program = vec![OpCode::CollectRest(1), OpCode::GetLocal(1), OpCode::Car];
+    let mut vm = VmState::new(program, vec![], 100, 1024, 1, 100);
+    vm.top_level_locals = vec![Value::Int(4), Value::Int(5), Value::Int(6)];
+    assert_eq!(vm.run().unwrap(), Value::Int(5));
+    assert_eq!(vm.top_level_locals.len(), 2);
+    assert_eq!(vm.top_level_locals[0], Value::Int(4));
+}
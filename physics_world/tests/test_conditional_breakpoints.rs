@@ -0,0 +1,74 @@
+/// Test conditional breakpoints alongside unconditional ones
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::InstructionResult;
+use physics_world::vm::VmState;
+
+/// Count local 0 down from 3 to 0
+fn countdown_vm() -> VmState {
+    let program = vec![
+        OpCode::Int(3),
+        OpCode::SetLocal(0),
+        // 2: loop while the counter is non-zero
+        OpCode::GetLocal(0),
+        OpCode::Int(0),
+        OpCode::Eq,
+        OpCode::JmpIfFalse(1),
+        OpCode::Jmp(5),
+        OpCode::GetLocal(0),
+        OpCode::Int(1),
+        OpCode::Sub,
+        OpCode::SetLocal(0),
+        OpCode::Jmp(-10),
+        // 12: done
+        OpCode::GetLocal(0),
+    ];
+    VmState::new(program, vec![], 1000, 1024, 1, 100)
+}
+
+/// Run to completion, recording the address and counter at every halt
+fn halts(vm: &mut VmState) -> Vec<(usize, Value)> {
+    let mut halts = Vec::new();
+    loop {
+        if vm.check_breakpoints() {
+            let counter = vm.top_level_locals.first().cloned().unwrap_or(Value::Nil);
+            halts.push((vm.ip, counter));
+        }
+        if let InstructionResult::Finished(_) = vm.step().unwrap() {
+            return halts;
+        }
+    }
+}
+
+#[test]
+fn test_conditional_breakpoint_halts_only_on_matching_iteration() {
+    let mut vm = countdown_vm();
+    vm.add_breakpoint(2);
+    vm.add_conditional_breakpoint(8, "local[0] == 1");
+
+    assert_eq!(
+        halts(&mut vm),
+        vec![
+            (2, Value::Int(3)),
+            (2, Value::Int(2)),
+            (2, Value::Int(1)),
+            (8, Value::Int(1)),
+            (2, Value::Int(0)),
+        ]
+    );
+}
+
+#[test]
+fn test_unconditional_breakpoint_wins_at_shared_address() {
+    let mut vm = countdown_vm();
+    vm.add_breakpoint(7);
+    vm.add_conditional_breakpoint(7, "local[0] > 100");
+    assert_eq!(halts(&mut vm).len(), 3);
+
+    // Removing the address drops both; a bad condition never halts
+    let mut vm = countdown_vm();
+    vm.add_conditional_breakpoint(7, "local[0] ==");
+    vm.add_conditional_breakpoint(9, "stack[0] == 1");
+    vm.remove_breakpoint(9);
+    assert!(halts(&mut vm).is_empty());
+    assert!(vm.get_debug_info().conditional_breakpoints.contains_key(&7));
+}
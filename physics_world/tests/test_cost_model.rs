@@ -0,0 +1,113 @@
+/// Test fuel accounting under uniform and proportional cost models
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::{InstructionResult, VmState};
+use physics_world::vm::CostModel;
+
+mod common;
+use common::load_bodies;
+
+const CLOSURES: usize = 10;
+const CAPTURES: usize = 3;
+
+/// Body every closure copies
+fn body() -> Vec<OpCode> {
+    vec![
+        OpCode::GetLocal(0),
+        OpCode::GetLocal(1),
+        OpCode::Add,
+        OpCode::Ret,
+    ]
+}
+
+/// Build closures capturing three values each, then a vector of four
+fn closure_heavy_vm(cost_model: CostModel) -> VmState {
+    let mut program = Vec::new();
+    for i in 0..CLOSURES {
+        for capture in 0..CAPTURES {
+            program.push(OpCode::Int((i * CAPTURES + capture) as i64));
+        }
+        program.extend([OpCode::MakeClosure(0, CAPTURES), OpCode::Pop]);
+    }
+    program.extend([
+        OpCode::Int(1),
+        OpCode::Int(2),
+        OpCode::Int(3),
+        OpCode::Int(4),
+        OpCode::MakeVector(4),
+        OpCode::VecLen,
+    ]);
+    let mut vm = VmState::new(program, vec![], 1000, 1 << 16, 1, 100);
+    load_bodies(&mut vm, vec![body()]);
+    vm.cost_model = cost_model;
+    vm
+}
+
+fn fuel_consumed(mut vm: VmState) -> u64 {
+    let budget = vm.steps_remaining;
+    assert_eq!(vm.run().unwrap(), Value::Int(4));
+    budget - vm.steps_remaining
+}
+
+#[test]
+fn test_proportional_model_charges_for_copied_values() {
+    // Finishing past the last instruction takes a step of its own
+    let instructions = (CLOSURES * (CAPTURES + 2) + 6) as u64 + 1;
+
+    let uniform = fuel_consumed(closure_heavy_vm(CostModel::default()));
+    assert_eq!(uniform, instructions);
+
+    let proportional = fuel_consumed(closure_heavy_vm(CostModel::proportional()));
+    assert_eq!(
+        proportional,
+        instructions + (CLOSURES * body().len()) as u64 + 4
+    );
+}
+
+#[test]
+fn test_opcode_cost() {
+    let model = CostModel {
+        base: 2,
+        per_element: 5,
+    };
+    let body_len = |_| 3;
+    assert_eq!(model.opcode_cost(&OpCode::Add, body_len), 2);
+    // Captures are free; the body the closure copies is charged
+    assert_eq!(model.opcode_cost(&OpCode::MakeClosure(0, 0), body_len), 17);
+    assert_eq!(model.opcode_cost(&OpCode::MakeClosure(0, 8), body_len), 17);
+    assert_eq!(model.opcode_cost(&OpCode::MakeVector(0), body_len), 2);
+    assert_eq!(
+        CostModel::uniform().opcode_cost(&OpCode::MakeVector(100), body_len),
+        1
+    );
+}
+
+#[test]
+fn test_expensive_instruction_waits_for_enough_fuel() {
+    let mut vm = VmState::new(
+        vec![
+            OpCode::Int(1),
+            OpCode::Int(2),
+            OpCode::MakeVector(2),
+            OpCode::VecLen,
+        ],
+        vec![],
+        4,
+        1024,
+        1,
+        100,
+    );
+    vm.cost_model = CostModel::proportional();
+
+    vm.step().unwrap();
+    vm.step().unwrap();
+    // MakeVector(2) costs 3 but only 2 steps are left
+    assert!(vm.step().is_err());
+    assert_eq!(vm.ip, 2);
+    assert_eq!(vm.steps_remaining, 2);
+
+    vm.grant_steps(2);
+    vm.step().unwrap();
+    assert_eq!(vm.steps_remaining, 1);
+    assert!(matches!(vm.step().unwrap(), InstructionResult::Continue));
+    assert_eq!(vm.stack, [Value::Int(2)]);
+}
@@ -0,0 +1,50 @@
+/// Test opcode coverage collection over a VM run
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::VmState;
+
+/// `if true { 1 } else { 2 } + 10`; the else-branch is instruction 4
+fn branching_program() -> Vec<OpCode> {
+    vec![
+        OpCode::Bool(true),
+        OpCode::JmpIfFalse(2),
+        OpCode::Int(1),
+        OpCode::Jmp(1),
+        OpCode::Int(2),
+        OpCode::Int(10),
+        OpCode::Add,
+    ]
+}
+
+#[test]
+fn test_untaken_else_branch_is_uncovered() {
+    let mut vm = VmState::new(branching_program(), vec![], 100, 1024, 1, 100);
+    vm.install_coverage_collector();
+
+    assert_eq!(vm.run().unwrap(), Value::Int(11));
+
+    let report = vm.coverage_report();
+    assert_eq!(report.total, 7);
+    assert_eq!(report.covered, 6);
+    assert_eq!(report.uncovered, vec![4]);
+}
+
+#[test]
+fn test_taken_else_branch_skips_then_branch() {
+    let mut program = branching_program();
+    program[0] = OpCode::Bool(false);
+    let mut vm = VmState::new(program, vec![], 100, 1024, 1, 100);
+    vm.install_coverage_collector();
+
+    assert_eq!(vm.run().unwrap(), Value::Int(12));
+    assert_eq!(vm.coverage_report().uncovered, vec![2, 3]);
+}
+
+#[test]
+fn test_report_without_collector_covers_nothing() {
+    let vm = VmState::new(branching_program(), vec![], 100, 1024, 1, 100);
+
+    let report = vm.coverage_report();
+    assert_eq!(report.covered, 0);
+    assert_eq!(report.uncovered.len(), 7);
+    assert_eq!(report.ratio(), 0.0);
+}
@@ -0,0 +1,111 @@
+/// Test the DeepClone opcode's independent copies of heap structures
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::VmState;
+
+fn vm(instructions: Vec<OpCode>) -> VmState {
+    VmState::new(instructions, vec![], 200, 4096, 1, 100)
+}
+
+#[test]
+fn test_mutating_clone_leaves_original_unchanged() {
+    let mut vm = vm(vec![
+        // outer = [[1, 2]]
+        OpCode::Int(1),
+        OpCode::Int(2),
+        OpCode::MakeVector(2),
+        OpCode::MakeVector(1),
+        OpCode::Dup,
+        OpCode::DeepClone,
+        // copy[0][0] = 99
+        OpCode::Int(0),
+        OpCode::VecGet,
+        OpCode::Int(0),
+        OpCode::Int(99),
+        OpCode::VecSet,
+        OpCode::Pop,
+        // original[0][0]
+        OpCode::Int(0),
+        OpCode::VecGet,
+        OpCode::Int(0),
+        OpCode::VecGet,
+    ]);
+
+    assert_eq!(vm.run().unwrap(), Value::Int(1));
+}
+
+#[test]
+fn test_clone_of_pair_is_a_new_cell() {
+    let mut vm = vm(vec![
+        OpCode::Int(1),
+        OpCode::Int(2),
+        OpCode::Cons,
+        OpCode::Dup,
+        OpCode::DeepClone,
+        OpCode::Dup,
+        OpCode::Car,
+    ]);
+
+    assert_eq!(vm.run().unwrap(), Value::Int(1));
+    match (&vm.stack[0], &vm.stack[1]) {
+        (Value::Pair(original), Value::Pair(copy)) => assert_ne!(original, copy),
+        other => panic!("Expected two pairs, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_cyclic_vector_clone_terminates_and_stays_cyclic() {
+    let mut vm = vm(vec![
+        // v = [0]; v[0] = v
+        OpCode::Int(0),
+        OpCode::MakeVector(1),
+        OpCode::Dup,
+        OpCode::Int(0),
+        OpCode::Swap,
+        OpCode::VecSet,
+        OpCode::Dup,
+        OpCode::DeepClone,
+        // copy[0]
+        OpCode::Dup,
+        OpCode::Int(0),
+        OpCode::VecGet,
+    ]);
+
+    let element = vm.run().unwrap();
+    let (original, copy) = (&vm.stack[0], &vm.stack[1]);
+    assert_ne!(original, copy);
+    assert_eq!(&element, copy);
+}
+
+#[test]
+fn test_shared_elements_stay_shared() {
+    let mut vm = vm(vec![
+        // inner = [7]; outer = [inner, inner]
+        OpCode::Int(7),
+        OpCode::MakeVector(1),
+        OpCode::Dup,
+        OpCode::MakeVector(2),
+        OpCode::DeepClone,
+        OpCode::Dup,
+        OpCode::Int(0),
+        OpCode::VecGet,
+        OpCode::Swap,
+        OpCode::Int(1),
+        OpCode::VecGet,
+    ]);
+
+    let second = vm.run().unwrap();
+    assert_eq!(vm.stack[0], second);
+}
+
+#[test]
+fn test_clone_of_long_list_does_not_overflow_the_stack() {
+    let length = 30_000;
+    let mut instructions = vec![OpCode::Nil];
+    for i in 0..length {
+        instructions.extend([OpCode::Int(i), OpCode::Swap, OpCode::Cons]);
+    }
+    instructions.extend([OpCode::DeepClone, OpCode::Cdr, OpCode::Car]);
+    let mut vm = VmState::new(instructions, vec![], 200_000, 8 << 20, 1, 100);
+
+    assert_eq!(vm.run().unwrap(), Value::Int(length - 2));
+}
@@ -0,0 +1,111 @@
+/// Test draining and migrating shutdowns of the distributed scheduler
+use physics_world::distributed::{DistributedScheduler, ShutdownMode};
+use physics_world::scheduler::Actor;
+use physics_world::types::{Capability, MigrationStatus, OpCode, Value};
+use physics_world::vm::state::VmState;
+use physics_world::vm::CapabilityExpiry;
+use std::collections::HashSet;
+
+fn actor(id: u32, instructions: Vec<OpCode>) -> Actor {
+    Actor {
+        id,
+        vm: VmState::new(instructions, vec![], 100, 1024, id, 100),
+        mailbox: Vec::new(),
+        is_waiting: false,
+        capabilities: HashSet::new(),
+        capability_requests: Vec::new(),
+        parent_id: None,
+        priority: 128,
+        priority_boost: None,
+    }
+}
+
+fn running_node() -> DistributedScheduler {
+    let mut node = DistributedScheduler::new(1, "127.0.0.1:8080".to_string());
+    node.start().unwrap();
+    node
+}
+
+#[test]
+fn test_drain_lets_running_actor_finish() {
+    let mut node = running_node();
+    node.local_scheduler
+        .add_actor(actor(7, vec![OpCode::Int(40), OpCode::Int(2), OpCode::Add]));
+
+    let report = node.shutdown(ShutdownMode::Drain).unwrap();
+
+    assert_eq!(report.finished, vec![(7, Value::Int(42))]);
+    assert!(report.suspended.is_empty() && report.errored.is_empty());
+    assert!(node.local_scheduler.actors.is_empty());
+    assert!(!node.is_running);
+}
+
+#[test]
+fn test_drain_delivers_pending_messages() {
+    let mut node = running_node();
+    // The actor adds the delivered message to its own constant
+    node.local_scheduler
+        .add_actor(actor(7, vec![OpCode::Int(2), OpCode::Add]));
+    node.local_scheduler.send_message(7, Value::Int(40));
+    node.local_scheduler.send_message(9, Value::Int(1));
+
+    let report = node.shutdown(ShutdownMode::Drain).unwrap();
+
+    assert_eq!(report.finished, vec![(7, Value::Int(42))]);
+    // Actor 9 lives elsewhere, so its message is handed back for forwarding
+    assert_eq!(report.undelivered.get(&9), Some(&vec![Value::Int(1)]));
+}
+
+#[test]
+fn test_migrate_moves_actors_with_their_messages() {
+    let mut node = running_node();
+    node.add_remote_node(2, "127.0.0.1:8081".to_string())
+        .unwrap();
+    node.local_scheduler
+        .add_actor(actor(7, vec![OpCode::Yield, OpCode::Int(1)]));
+    node.local_scheduler.send_message(7, Value::Int(5));
+
+    let report = node.shutdown(ShutdownMode::Migrate(2)).unwrap();
+
+    assert_eq!(report.migrated, vec![7]);
+    assert!(report.not_migrated.is_empty());
+    assert!(node.local_scheduler.actors.is_empty());
+    assert!(!node.is_running);
+    let request = &node.migration_queue[0];
+    assert_eq!((request.actor_id, request.target_node), (7, 2));
+    assert_eq!(request.migration_status, MigrationStatus::Pending);
+    assert_eq!(request.state_snapshot.mailbox, vec![Value::Int(5)]);
+    let vm: VmState = bincode::deserialize(&request.state_snapshot.vm_state).unwrap();
+    assert_eq!(vm.instructions, vec![OpCode::Yield, OpCode::Int(1)]);
+}
+
+#[test]
+fn test_migrate_to_unknown_node_keeps_actors() {
+    let mut node = running_node();
+    node.local_scheduler
+        .add_actor(actor(7, vec![OpCode::Int(1)]));
+
+    assert!(node.shutdown(ShutdownMode::Migrate(3)).is_err());
+    assert_eq!(node.local_scheduler.actors.len(), 1);
+    assert!(node.is_running);
+}
+
+#[test]
+fn test_migration_leaves_lapsed_grants_behind() {
+    let mut node = running_node();
+    node.add_remote_node(2, "127.0.0.1:8081".to_string())
+        .unwrap();
+    let mut migrant = actor(7, vec![OpCode::Yield]);
+    migrant.vm.grant_capability(Capability::IoNetwork);
+    migrant
+        .vm
+        .grant_capability_until(Capability::IoReadSensor, CapabilityExpiry::Steps(0));
+    node.local_scheduler.add_actor(migrant);
+
+    node.migrate_actor(7, 2).unwrap();
+
+    let snapshot = &node.migration_queue[0].state_snapshot;
+    assert_eq!(snapshot.capabilities, vec![Capability::IoNetwork]);
+    let vm: VmState = bincode::deserialize(&snapshot.vm_state).unwrap();
+    assert_eq!(vm.capabilities, vec![Capability::IoNetwork]);
+}
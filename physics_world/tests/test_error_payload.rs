@@ -0,0 +1,121 @@
+/// Test structured error values raised by Throw and caught by SetErrorHandler
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::opcodes::closure::create_closure_body;
+use physics_world::vm::VmState;
+
+/// Push an error with code 1, message "bad input" and an integer payload
+fn make_error(payload: i64) -> Vec<OpCode> {
+    vec![
+        OpCode::Int(1),
+        OpCode::LoadString(0),
+        OpCode::Int(payload),
+        OpCode::MakeError,
+    ]
+}
+
+fn run(instructions: Vec<OpCode>) -> (VmState, Value) {
+    let constants = vec![Value::String("bad input".to_string())];
+    let mut vm = VmState::new(instructions, constants, 100, 1024, 1, 100);
+    let result = vm.run().unwrap();
+    (vm, result)
+}
+
+#[test]
+fn test_caught_error_keeps_its_payload() {
+    let mut program = vec![OpCode::SetErrorHandler(7), OpCode::Int(99)];
+    program.extend(make_error(42));
+    program.extend([OpCode::Throw, OpCode::Int(0)]);
+    // Handler: the thrown record is on top of the stack
+    program.push(OpCode::ErrorPayload);
+
+    let (vm, result) = run(program);
+    assert_eq!(result, Value::Int(42));
+    // Values pushed after the handler was installed are discarded
+    assert!(vm.stack.is_empty());
+    assert!(vm.error_handlers.is_empty());
+}
+
+#[test]
+fn test_error_record_carries_code_and_message() {
+    let mut program = make_error(42);
+    program.push(OpCode::Throw);
+
+    let (_, result) = run(program);
+    assert_eq!(
+        result,
+        Value::ErrorRecord {
+            code: 1,
+            message: "bad input".to_string(),
+            payload: Box::new(Value::Int(42)),
+        }
+    );
+}
+
+#[test]
+fn test_throw_unwinds_calls_to_the_handler() {
+    let mut thrower = make_error(42);
+    thrower.extend([OpCode::Throw, OpCode::Ret]);
+
+    let constants = vec![Value::String("bad input".to_string())];
+    let main = vec![
+        OpCode::SetErrorHandler(2),
+        OpCode::MakeClosure(1, 0),
+        OpCode::Call(0),
+        OpCode::ErrorPayload,
+    ];
+    let mut vm = VmState::new(main, constants, 100, 1024, 1, 100);
+    let body = create_closure_body(&mut vm, thrower).unwrap();
+    vm.constant_pool.push(Value::Closure(body));
+
+    assert_eq!(vm.run().unwrap(), Value::Int(42));
+    assert!(vm.call_stack.is_empty());
+}
+
+#[test]
+fn test_popped_handler_no_longer_catches() {
+    let mut program = vec![OpCode::SetErrorHandler(7), OpCode::PopErrorHandler];
+    program.extend(make_error(42));
+    program.extend([OpCode::Throw, OpCode::Int(0)]);
+    program.push(OpCode::ErrorPayload);
+
+    let (_, result) = run(program);
+    assert!(matches!(result, Value::ErrorRecord { code: 1, .. }));
+}
+
+#[test]
+fn test_collection_keeps_and_relocates_a_caught_pair_payload() {
+    let program = vec![
+        OpCode::SetErrorHandler(11),
+        // A garbage pair below the payload, so collecting moves the payload
+        OpCode::Int(98),
+        OpCode::Int(99),
+        OpCode::Cons,
+        OpCode::Pop,
+        OpCode::Int(1),
+        OpCode::LoadString(0),
+        OpCode::Int(10),
+        OpCode::Int(20),
+        OpCode::Cons,
+        OpCode::MakeError,
+        OpCode::Throw,
+        // Handler: collect, allocate over the freed space, then read the
+        // payload's car
+        OpCode::GcCollect,
+        OpCode::Pop,
+        OpCode::Int(77),
+        OpCode::Int(88),
+        OpCode::Cons,
+        OpCode::Int(77),
+        OpCode::Int(88),
+        OpCode::Cons,
+        OpCode::Pop,
+        OpCode::Pop,
+        OpCode::ErrorPayload,
+        OpCode::Car,
+    ];
+    let constants = vec![Value::String("bad input".to_string())];
+    let mut vm = VmState::new(program, constants, 100, 1024, 1, 100);
+    vm.grant_capability(Capability::SysGc);
+
+    assert_eq!(vm.run().unwrap(), Value::Int(10));
+}
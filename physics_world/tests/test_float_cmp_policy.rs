@@ -0,0 +1,66 @@
+/// Test NaN handling of float comparisons under each FloatCmpPolicy
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::{FloatCmpPolicy, VmState};
+
+fn compare(policy: FloatCmpPolicy, a: f64, b: f64, op: OpCode) -> Value {
+    let mut vm = VmState::new(
+        vec![OpCode::Float(a), OpCode::Float(b), op],
+        Vec::new(),
+        100,
+        1024,
+        1,
+        100,
+    );
+    vm.float_cmp_policy = policy;
+    vm.run().unwrap()
+}
+
+#[test]
+fn test_ieee_nan_compares_false() {
+    for op in [OpCode::Lt, OpCode::Gt, OpCode::Lte, OpCode::Gte, OpCode::Eq] {
+        let result = compare(FloatCmpPolicy::IeeeNaNFalse, f64::NAN, 1.0, op);
+        assert_eq!(result, Value::Bool(false), "{op:?}");
+    }
+    assert_eq!(
+        compare(FloatCmpPolicy::IeeeNaNFalse, f64::NAN, f64::NAN, OpCode::Ne),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        compare(FloatCmpPolicy::IeeeNaNFalse, 0.5, 1.0, OpCode::Lt),
+        Value::Bool(true)
+    );
+}
+
+#[test]
+fn test_total_order_places_nan_above_numbers() {
+    assert_eq!(
+        compare(FloatCmpPolicy::TotalOrder, f64::NAN, 1.0, OpCode::Lt),
+        Value::Bool(false)
+    );
+    assert_eq!(
+        compare(FloatCmpPolicy::TotalOrder, f64::NAN, 1.0, OpCode::Gt),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        compare(FloatCmpPolicy::TotalOrder, f64::NAN, f64::NAN, OpCode::Eq),
+        Value::Bool(true)
+    );
+    assert_eq!(
+        compare(FloatCmpPolicy::TotalOrder, -0.0, 0.0, OpCode::Lt),
+        Value::Bool(true)
+    );
+}
+
+#[test]
+fn test_sorting_with_nan_is_deterministic_under_total_order() {
+    let policy = FloatCmpPolicy::TotalOrder;
+    let sort = |mut values: Vec<f64>| {
+        values.sort_by(|a, b| policy.compare(*a, *b).unwrap());
+        values.into_iter().map(f64::to_bits).collect::<Vec<_>>()
+    };
+
+    let sorted = sort(vec![3.0, f64::NAN, -1.0, 0.5, f64::NAN]);
+    assert_eq!(sorted, sort(vec![f64::NAN, 0.5, f64::NAN, 3.0, -1.0]));
+    let expected = [-1.0, 0.5, 3.0, f64::NAN, f64::NAN].map(f64::to_bits);
+    assert_eq!(sorted, expected);
+}
@@ -251,3 +251,35 @@ fn test_special_float_values() {
         _ => panic!("Expected Float result, got {:?}", result),
     }
 }
+
+/// Test that float division by zero is an error rather than infinity
+#[test]
+fn test_float_division_by_zero() {
+    for divisor in [0.0, -0.0] {
+        let program = vec![OpCode::Float(1.0), OpCode::Float(divisor), OpCode::FDiv];
+        let mut vm = VmState::new(program, vec![], 100, 1024, 1, 100);
+        assert!(
+            matches!(vm.run(), Err(VmError::DivisionByZero { .. })),
+            "1.0 / {divisor} should be DivisionByZero"
+        );
+    }
+}
+
+/// Test that float opcodes reject Int operands and integer opcodes reject Float operands
+#[test]
+fn test_mixed_operands_are_type_mismatch() {
+    let float_ops = [OpCode::FAdd, OpCode::FSub, OpCode::FMul, OpCode::FDiv];
+    let int_ops = [OpCode::Add, OpCode::Sub, OpCode::Mul, OpCode::Div];
+    for op in float_ops.into_iter().chain(int_ops) {
+        for (a, b) in [
+            (OpCode::Int(3), OpCode::Float(1.5)),
+            (OpCode::Float(1.5), OpCode::Int(3)),
+        ] {
+            let mut vm = VmState::new(vec![a, b, op], vec![], 100, 1024, 1, 100);
+            assert!(
+                matches!(vm.run(), Err(VmError::TypeMismatch { .. })),
+                "{a:?} {b:?} {op:?} should be TypeMismatch"
+            );
+        }
+    }
+}
@@ -0,0 +1,80 @@
+/// Test folded-stack flamegraph export of profiling samples
+use physics_world::types::OpCode;
+use physics_world::vm::state::InstructionResult;
+use physics_world::vm::{FunctionNames, VmDebugger, VmState};
+
+mod common;
+use common::load_bodies;
+
+/// Body that runs `n` push/pop pairs and returns 0
+fn busy_body(n: usize) -> Vec<OpCode> {
+    let mut body: Vec<OpCode> = (0..n).flat_map(|_| [OpCode::Int(1), OpCode::Pop]).collect();
+    body.extend([OpCode::Int(0), OpCode::Ret]);
+    body
+}
+
+fn profile_hot_and_cold() -> String {
+    // A return to the top level ends the run, so a driver makes both calls
+    let main = vec![OpCode::MakeClosure(0, 0), OpCode::Call(0)];
+    let driver = vec![
+        OpCode::MakeClosure(1, 0),
+        OpCode::Call(0),
+        OpCode::Pop,
+        OpCode::MakeClosure(2, 0),
+        OpCode::Call(0),
+        OpCode::Ret,
+    ];
+    let mut vm = VmState::new(main, vec![], 1000, 8192, 1, 100);
+    load_bodies(&mut vm, vec![driver, busy_body(40), busy_body(2)]);
+    let mut names = FunctionNames::new();
+    names.insert(0, "driver");
+    names.insert(1, "hot");
+    names.insert(2, "cold");
+    vm.attach_function_names(names);
+    vm.performance_monitor.sample_interval_millis = 0;
+
+    let mut debugger = VmDebugger::new(vm);
+    debugger.enable_profiling();
+    loop {
+        match debugger.step_with_debug().unwrap() {
+            InstructionResult::Finished(_) => break,
+            _ => continue,
+        }
+    }
+    debugger.get_performance_profile().to_folded_stacks()
+}
+
+#[test]
+fn test_hot_function_has_largest_count() {
+    let folded = profile_hot_and_cold();
+
+    let mut lines: Vec<(String, u64)> = folded
+        .lines()
+        .map(|line| {
+            let (stack, count) = line.rsplit_once(' ').unwrap();
+            (stack.to_string(), count.parse().unwrap())
+        })
+        .collect();
+    lines.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    assert_eq!(lines[0].0, "main;driver;hot", "{folded}");
+    assert!(
+        lines.iter().any(|(stack, _)| stack == "main;driver;cold"),
+        "{folded}"
+    );
+    assert!(lines.iter().any(|(stack, _)| stack == "main"), "{folded}");
+}
+
+#[test]
+fn test_folded_output_is_one_line_per_distinct_stack() {
+    let folded = profile_hot_and_cold();
+
+    let stacks: Vec<&str> = folded
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().0)
+        .collect();
+    let mut distinct = stacks.clone();
+    distinct.dedup();
+    assert_eq!(stacks, distinct);
+    assert!(folded.ends_with('\n'));
+}
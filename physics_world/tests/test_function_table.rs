@@ -0,0 +1,132 @@
+/// Test function metadata lookup for escape-analysing closure creation
+use physics_world::memory::arena::TAG_CLOSURE;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::{SimpleVmError, VmError as DetailedVmError};
+use physics_world::vm::opcodes::closure::{create_closure_body, handle_make_closure};
+use physics_world::vm::state::{CallFrame, EscapeStatus, FunctionInfo, VmError, VmState};
+use std::collections::HashMap;
+
+/// Function 0 keeps its first free variable local and lets the second
+/// escape; function 3 has no free variables at all
+fn function_table() -> HashMap<u16, FunctionInfo> {
+    HashMap::from([
+        (
+            0,
+            FunctionInfo {
+                local_count: 3,
+                escape_info: HashMap::from([
+                    (0, EscapeStatus::NonEscaping),
+                    (1, EscapeStatus::Escaping),
+                ]),
+                free_variables: vec![1, 2],
+            },
+        ),
+        (
+            3,
+            FunctionInfo {
+                local_count: 1,
+                escape_info: HashMap::new(),
+                free_variables: Vec::new(),
+            },
+        ),
+    ])
+}
+
+fn loaded_vm() -> VmState {
+    let mut vm = VmState::new(vec![OpCode::Nil], vec![], 100, 1024, 1, 100);
+    vm.attach_function_table(function_table());
+    vm
+}
+
+#[test]
+fn test_lookup_returns_loaded_metadata() {
+    let vm = loaded_vm();
+
+    let info = vm.get_function_info(0).unwrap();
+    assert_eq!(info.local_count, 3);
+    assert_eq!(info.free_variables, [1, 2]);
+    assert_eq!(info.escape_info[&0], EscapeStatus::NonEscaping);
+    assert_eq!(info.escape_info[&1], EscapeStatus::Escaping);
+
+    assert_eq!(vm.get_function_info(3).unwrap(), function_table()[&3]);
+}
+
+#[test]
+fn test_unknown_function_is_an_error() {
+    let mut vm = loaded_vm();
+    assert!(matches!(
+        vm.get_function_info(1),
+        Err(VmError::UnknownFunction { function: 1 })
+    ));
+
+    // Closure creation no longer proceeds with a fabricated empty record
+    assert!(matches!(
+        handle_make_closure(&mut vm, 7),
+        Err(VmError::UnknownFunction { function: 7 })
+    ));
+    assert!(vm.stack.is_empty());
+
+    let detailed = DetailedVmError::from(SimpleVmError::from(VmError::UnknownFunction {
+        function: 7,
+    }));
+    assert!(matches!(
+        detailed,
+        DetailedVmError::UnknownFunction { function: 7, .. }
+    ));
+    assert!(detailed.to_string().contains("function 7"));
+}
+
+#[test]
+fn test_closure_for_known_function_without_free_variables() {
+    let mut vm = loaded_vm();
+    handle_make_closure(&mut vm, 3).unwrap();
+    assert_eq!(vm.stack.len(), 1);
+}
+
+#[test]
+fn test_escape_analysed_closure_captures_escaping_locals_in_arena_layout() {
+    let mut vm = loaded_vm();
+    let body = create_closure_body(&mut vm, vec![OpCode::GetLocal(0), OpCode::Ret]).unwrap();
+    vm.constant_pool.push(Value::Closure(body));
+    let mut frame = CallFrame::new(0, 0, 0, 1, 0);
+    frame.locals = vec![Value::Int(10), Value::Int(20), Value::Int(30)];
+    vm.call_stack.push(frame);
+
+    // Only free variable 1, local slot 2, escapes
+    let Value::Closure(ptr) = handle_make_closure(&mut vm, 0).unwrap() else {
+        panic!("expected a closure");
+    };
+    assert_eq!(unsafe { vm.memory.get_header(ptr) }.tag, TAG_CLOSURE);
+    let info = vm.inspect_closure(ptr).unwrap();
+    assert_eq!(info.body_ptr, body);
+    assert_eq!(info.captures[0], 30);
+    assert_eq!(vm.stack, [Value::Closure(ptr)]);
+}
+
+#[test]
+fn test_make_closure_opcode_checks_captures_against_table() {
+    let vm_for = |capture_count: usize, code_idx: usize| {
+        let mut program = vec![OpCode::Int(5); capture_count];
+        program.push(OpCode::MakeClosure(code_idx, capture_count));
+        let mut vm = VmState::new(program, vec![Value::Nil], 100, 1024, 1, 100);
+        let body = create_closure_body(&mut vm, vec![OpCode::Ret]).unwrap();
+        vm.constant_pool[0] = Value::Closure(body);
+        vm.attach_function_table(function_table());
+        vm
+    };
+
+    assert!(matches!(vm_for(1, 0).run(), Ok(Value::Closure(_))));
+    assert!(matches!(
+        vm_for(2, 0).run(),
+        Err(DetailedVmError::CaptureCountMismatch {
+            function: 0,
+            expected: 1,
+            actual: 2,
+            ..
+        })
+    ));
+    assert!(matches!(
+        vm_for(0, 1).run(),
+        Err(DetailedVmError::UnknownFunction { function: 1, .. })
+    ));
+}
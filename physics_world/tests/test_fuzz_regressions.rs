@@ -0,0 +1,70 @@
+/// Replay inputs that once crashed the VM through the fuzzing entry point
+use bincode::Options;
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::fuzz::decode_opcodes;
+use physics_world::vm::fuzz_run;
+
+/// Encode a program the way `decode_opcodes` reads it
+fn encode(program: &[OpCode]) -> Vec<u8> {
+    program
+        .iter()
+        .flat_map(|op| bincode::DefaultOptions::new().serialize(op).unwrap())
+        .collect()
+}
+
+/// Programs decoded from inputs that panicked before being fixed
+///
+/// Stored as opcodes rather than raw bytes because the byte encoding shifts
+/// whenever an opcode is added.
+fn crash_corpus() -> Vec<Vec<OpCode>> {
+    vec![
+        // Call with fewer arguments on the stack than its argument count
+        vec![
+            OpCode::Int(-3),
+            OpCode::Int(7),
+            OpCode::MakeClosure(39, 2),
+            OpCode::Call(49),
+            OpCode::Add,
+        ],
+        vec![
+            OpCode::Int(5),
+            OpCode::Int(8),
+            OpCode::Int(8),
+            OpCode::Int(-4),
+            OpCode::Int(5),
+            OpCode::Eq,
+            OpCode::MakeClosure(73, 1),
+            OpCode::Call(35),
+        ],
+    ]
+}
+
+#[test]
+fn test_crash_corpus_returns_errors() {
+    for program in crash_corpus() {
+        let input = encode(&program);
+        assert_eq!(decode_opcodes(&input), program);
+        match fuzz_run(&input) {
+            Err(VmError::StackUnderflow { .. }) => {}
+            other => panic!("Expected StackUnderflow for {:?}, got {:?}", program, other),
+        }
+    }
+}
+
+#[test]
+fn test_garbage_input_does_not_panic() {
+    assert!(fuzz_run(&[0xFF; 64]).is_ok()); // No byte decodes, so the program is empty
+    assert!(fuzz_run(&[]).is_ok());
+    let eq = encode(&[OpCode::Eq]);
+    assert!(fuzz_run(&eq.repeat(5000)).is_err()); // Truncated run of Eq on an empty stack
+}
+
+#[test]
+fn test_decode_reads_varint_encoded_opcodes() {
+    let program = vec![OpCode::Int(40), OpCode::Int(2), OpCode::Add];
+    let data = encode(&program);
+
+    assert_eq!(decode_opcodes(&data), program);
+    assert_eq!(fuzz_run(&data).unwrap(), Value::Int(42));
+}
@@ -0,0 +1,137 @@
+/// Test the capability-gated GcCollect and GcStats opcodes
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::VmState;
+
+fn vm_with_gc(instructions: Vec<OpCode>) -> VmState {
+    let mut vm = VmState::new(instructions, vec![], 100, 4096, 1, 100);
+    vm.grant_capability(Capability::SysGc);
+    vm
+}
+
+#[test]
+fn test_collect_reclaims_garbage() {
+    let mut vm = vm_with_gc(vec![
+        OpCode::Int(1),
+        OpCode::Int(2),
+        OpCode::Cons,
+        OpCode::Pop, // The pair is now garbage
+        OpCode::GcCollect,
+    ]);
+
+    match vm.run().unwrap() {
+        Value::Int(reclaimed) => assert!(reclaimed > 0, "reclaimed {reclaimed} bytes"),
+        other => panic!("Expected Int, got {:?}", other),
+    }
+    assert_eq!(vm.memory.next_free(), 0);
+}
+
+#[test]
+fn test_collect_keeps_and_relocates_live_objects() {
+    let mut vm = vm_with_gc(vec![
+        OpCode::Int(1),
+        OpCode::Int(2),
+        OpCode::Cons,
+        OpCode::Pop, // Garbage before the live vector, so the vector moves
+        OpCode::Int(7),
+        OpCode::Int(8),
+        OpCode::MakeVector(2),
+        OpCode::GcCollect,
+        OpCode::Pop,
+        OpCode::Int(1),
+        OpCode::VecGet,
+    ]);
+
+    assert_eq!(vm.run().unwrap(), Value::Int(8));
+    assert!(vm.stack.is_empty());
+}
+
+#[test]
+fn test_collect_moves_vector_elements_but_not_lookalike_ints() {
+    let mut vm = vm_with_gc(vec![
+        OpCode::Int(1),
+        OpCode::Int(2),
+        OpCode::Cons,
+        OpCode::Pop, // Garbage at 0, so everything after it moves
+        OpCode::Int(7),
+        OpCode::MakeVector(1), // Inner vector at 48
+        OpCode::Int(48),       // An int equal to the inner vector's address
+        OpCode::MakeVector(2),
+        OpCode::GcCollect,
+        OpCode::Pop,
+    ]);
+
+    let Value::Vector(outer) = vm.run().unwrap() else {
+        panic!("Expected a vector");
+    };
+    assert!(outer.get() < 80, "the outer vector was not moved");
+
+    vm.stack.push(Value::Vector(outer));
+    vm.instructions = vec![
+        OpCode::Dup,
+        OpCode::Int(1),
+        OpCode::VecGet,
+        OpCode::Swap,
+        OpCode::Int(0),
+        OpCode::VecGet,
+        OpCode::Int(0),
+        OpCode::VecGet,
+    ];
+    vm.ip = 0;
+    assert_eq!(vm.run().unwrap(), Value::Int(7));
+    assert_eq!(vm.stack, vec![Value::Int(48)]);
+}
+
+#[test]
+fn test_collect_relocates_pointer_captures_only() {
+    let mut vm = vm_with_gc(vec![
+        OpCode::Int(1),
+        OpCode::Int(2),
+        OpCode::Cons,
+        OpCode::Pop, // Garbage at 0, so everything after it moves
+        OpCode::Int(7),
+        OpCode::MakeVector(1), // Captured vector at 48
+        OpCode::Int(48),       // A captured int equal to the vector's address
+        OpCode::MakeClosure(0, 2),
+        OpCode::GcCollect,
+        OpCode::Pop,
+    ]);
+
+    let Value::Closure(closure) = vm.run().unwrap() else {
+        panic!("Expected a closure");
+    };
+    let data = unsafe { vm.memory.get_data(closure) };
+    let word = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+    assert_eq!(word(4), 0, "the vector capture follows the vector to 0");
+    assert_eq!(word(8), 48, "the int capture is left alone");
+}
+
+#[test]
+fn test_stats_pushes_live_bytes_and_capacity() {
+    let mut vm = vm_with_gc(vec![OpCode::GcStats, OpCode::Car]);
+    assert_eq!(vm.run().unwrap(), Value::Int(0)); // Empty heap: zero live bytes
+
+    let mut vm = vm_with_gc(vec![OpCode::GcStats, OpCode::Cdr]);
+    assert_eq!(vm.run().unwrap(), Value::Int(4096));
+}
+
+#[test]
+fn test_stats_count_only_objects_surviving_a_sweep() {
+    let pair = [OpCode::Int(1), OpCode::Int(2), OpCode::Cons];
+    let mut vm = vm_with_gc([&pair[..], &[OpCode::Pop, OpCode::GcStats, OpCode::Car]].concat());
+    assert_eq!(vm.run().unwrap(), Value::Int(0)); // The dropped pair is not live
+
+    let mut vm = vm_with_gc([&pair[..], &[OpCode::GcStats, OpCode::Car]].concat());
+    match vm.run().unwrap() {
+        Value::Int(live) => assert!(live > 0, "live {live} bytes"),
+        other => panic!("Expected Int, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_gc_opcodes_require_capability() {
+    for opcode in [OpCode::GcCollect, OpCode::GcStats] {
+        let mut vm = VmState::new(vec![opcode], vec![], 100, 4096, 1, 100);
+        assert!(matches!(vm.run(), Err(VmError::CapabilityError { .. })));
+    }
+}
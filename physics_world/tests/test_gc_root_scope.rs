@@ -0,0 +1,93 @@
+/// Test GC root scopes that release their roots when dropped
+use physics_world::types::Value;
+use physics_world::vm::gc::{Array, GcPtr, HeapObject};
+use physics_world::vm::VmState;
+
+fn allocate(vm: &mut VmState, element: i64) -> GcPtr {
+    match vm
+        .allocate_heap_object(HeapObject::Array(Array {
+            elements: vec![Value::Int(element)],
+        }))
+        .unwrap()
+    {
+        Value::GcPtr(ptr) => ptr,
+        other => panic!("Expected a GC pointer, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_scoped_roots_live_until_scope_is_dropped() {
+    let mut vm = VmState::new(vec![], vec![], 100, 1024, 1, 100);
+    let first = allocate(&mut vm, 1);
+    let second = allocate(&mut vm, 2);
+
+    {
+        let mut scope = vm.push_root_scope();
+        scope.add(first, "host reference");
+        scope.add(second, "host reference");
+        assert_eq!(scope.len(), 2);
+
+        scope.gc.collect();
+        assert_eq!(scope.gc.heap.len(), 2);
+    }
+
+    assert!(vm.gc.roots.is_empty());
+    vm.gc.collect();
+    assert!(vm.gc.heap.is_empty());
+}
+
+#[test]
+fn test_dropping_a_scope_keeps_other_roots() {
+    let mut vm = VmState::new(vec![], vec![], 100, 1024, 1, 100);
+    let pinned = allocate(&mut vm, 1);
+    let scoped = allocate(&mut vm, 2);
+    vm.add_gc_root(pinned, "pinned");
+
+    {
+        let mut outer = vm.push_root_scope();
+        outer.add(scoped, "outer");
+        {
+            let mut inner = outer.push_root_scope();
+            inner.add(scoped, "inner");
+        }
+        // The inner scope only released its own root
+        assert_eq!(outer.len(), 1);
+    }
+
+    assert_eq!(vm.gc.roots.len(), 1);
+    vm.gc.collect();
+    assert_eq!(vm.gc.heap.len(), 1);
+    assert!(matches!(
+        &vm.gc.heap[0],
+        HeapObject::Array(array) if array.elements == vec![Value::Int(1)]
+    ));
+}
+
+#[test]
+fn test_removing_a_root_keeps_scoped_and_repeated_roots() {
+    let mut vm = VmState::new(vec![], vec![], 100, 1024, 1, 100);
+    let shared = allocate(&mut vm, 1);
+    vm.add_gc_root(shared, "first holder");
+    vm.add_gc_root(shared, "second holder");
+
+    {
+        let mut scope = vm.push_root_scope();
+        scope.add(shared, "host reference");
+        scope.remove_gc_root(shared);
+        scope.remove_gc_root(shared);
+        assert_eq!(scope.len(), 1);
+
+        scope.gc.collect();
+        assert_eq!(scope.gc.heap.len(), 1);
+    }
+
+    vm.add_gc_root(shared, "holder");
+    vm.add_gc_root(shared, "holder");
+    vm.remove_gc_root(shared);
+    vm.gc.collect();
+    assert_eq!(vm.gc.heap.len(), 1);
+
+    vm.remove_gc_root(shared);
+    vm.gc.collect();
+    assert!(vm.gc.heap.is_empty());
+}
@@ -0,0 +1,127 @@
+/// Test nursery collections, promotion and the old-to-young write barrier
+use physics_world::types::Value;
+use physics_world::vm::gc::Array;
+use physics_world::vm::{GarbageCollector, GcPtr, GcRoot, HeapObject};
+
+fn array_of(ptrs: &[usize]) -> HeapObject {
+    HeapObject::Array(Array {
+        elements: ptrs.iter().map(|&p| Value::GcPtr(GcPtr(p))).collect(),
+    })
+}
+
+fn root(gc: &mut GarbageCollector, ptr: GcPtr) {
+    gc.roots.push(GcRoot {
+        ptr,
+        description: "test".to_string(),
+        scope: None,
+    });
+}
+
+fn elements(gc: &GarbageCollector, ptr: usize) -> &[Value] {
+    match &gc.heap[ptr] {
+        HeapObject::Array(array) => array.elements(),
+        _ => panic!("not an array"),
+    }
+}
+
+#[test]
+fn test_minor_collections_reclaim_ephemeral_objects() {
+    let mut gc = GarbageCollector::new(64, 1000);
+    let live = gc.allocate_generational(array_of(&[]), 32);
+    root(&mut gc, live);
+
+    for _ in 0..1000 {
+        gc.allocate_generational(array_of(&[]), 32);
+    }
+
+    let stats = &gc.gc_stats;
+    assert!(stats.minor_collections >= 30, "{stats:?}");
+    assert_eq!(stats.major_collections, 0);
+    assert_eq!(stats.collections, stats.minor_collections);
+    // Only the rooted object ever survived, so only it was promoted
+    assert_eq!(stats.objects_promoted, 1);
+    assert_eq!(gc.old_generation_len(), 1);
+    assert!(gc.heap.len() <= 33);
+    assert_eq!(
+        stats.objects_collected as usize + gc.heap.len(),
+        1001,
+        "every dead object was reclaimed by a minor collection"
+    );
+}
+
+#[test]
+fn test_write_barrier_keeps_young_object_referenced_from_old() {
+    let mut gc = GarbageCollector::new(16, 1000);
+    gc.allocate(array_of(&[]));
+    root(&mut gc, GcPtr(0));
+    gc.minor_collect();
+    assert_eq!(gc.old_generation_len(), 1);
+
+    // Garbage first, so the survivors move when the nursery is compacted
+    gc.allocate(array_of(&[]));
+    let young = gc.allocate(array_of(&[]));
+    let child = gc.allocate(array_of(&[]));
+    if let HeapObject::Array(array) = &mut gc.heap[young.0] {
+        array.elements.push(Value::GcPtr(child));
+    }
+
+    let stored = Value::GcPtr(young);
+    if let HeapObject::Array(array) = &mut gc.heap[0] {
+        array.elements.push(stored.clone());
+    }
+    gc.write_barrier(GcPtr(0), &stored);
+    assert!(gc.is_remembered(GcPtr(0)));
+
+    gc.minor_collect();
+
+    assert_eq!(gc.heap.len(), 3);
+    assert_eq!(gc.gc_stats.objects_collected, 1);
+    assert_eq!(gc.old_generation_len(), 3);
+    assert!(!gc.is_remembered(GcPtr(0)));
+    // References were rewritten to the compacted positions
+    assert_eq!(elements(&gc, 0), [Value::GcPtr(GcPtr(1))]);
+    assert_eq!(elements(&gc, 1), [Value::GcPtr(GcPtr(2))]);
+}
+
+#[test]
+fn test_unreachable_old_objects_wait_for_major_collection() {
+    let mut gc = GarbageCollector::new(64, 8);
+    for _ in 0..4 {
+        let ptr = gc.allocate_generational(array_of(&[]), 4);
+        root(&mut gc, ptr);
+    }
+    gc.minor_collect();
+    assert_eq!(gc.old_generation_len(), 4);
+
+    // Old objects are not examined by a minor collection
+    gc.roots.clear();
+    gc.minor_collect();
+    assert_eq!(gc.heap.len(), 4);
+
+    // Growing the old generation by the threshold triggers a full collection
+    for _ in 0..12 {
+        let ptr = gc.allocate_generational(array_of(&[]), 4);
+        root(&mut gc, ptr);
+    }
+    assert_eq!(gc.gc_stats.major_collections, 1);
+    assert!(gc.gc_stats.objects_collected >= 4);
+    assert!(gc.heap.len() <= 12);
+}
+
+#[test]
+fn test_relocation_spans_the_minor_and_major_collections_of_one_allocation() {
+    let mut gc = GarbageCollector::new(64, 1);
+    gc.allocate_generational(array_of(&[]), 2);
+    let live = gc.allocate_generational(array_of(&[]), 2);
+    root(&mut gc, live);
+    gc.take_relocation();
+
+    // The nursery is full, so this runs a minor and then a major collection
+    let holder = gc.allocate_generational(array_of(&[live.0]), 2);
+
+    assert_eq!(gc.gc_stats.minor_collections, 1);
+    assert_eq!(gc.gc_stats.major_collections, 1);
+    assert_eq!(gc.take_relocation(), Some(vec![None, Some(0)]));
+    assert_eq!(elements(&gc, holder.0), [Value::GcPtr(GcPtr(0))]);
+    assert_eq!(gc.take_relocation(), None);
+}
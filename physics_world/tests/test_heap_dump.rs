@@ -0,0 +1,73 @@
+/// Test exporting the heap and rebuilding its object graph from the dump
+use physics_world::memory::{SLOT_BOXED, TAG_LIST, TAG_STRING, TAG_VECTOR, VECTOR_SLOT_SIZE};
+use physics_world::types::HeapPtr;
+use physics_world::vm::heap_dump::analyze;
+use physics_world::vm::VmState;
+
+/// Fill the leading slots of a pair or vector with pointers to `targets`
+fn store_slots(vm: &mut VmState, ptr: HeapPtr, targets: &[HeapPtr]) {
+    let data = unsafe { vm.memory.get_data_mut(ptr) };
+    for (slot, target) in targets.iter().enumerate() {
+        let start = slot * VECTOR_SLOT_SIZE;
+        data[start] = SLOT_BOXED;
+        data[start + 4..start + 8].copy_from_slice(&target.get().to_le_bytes());
+    }
+}
+
+#[test]
+fn test_dump_round_trips_objects_and_edges() {
+    let mut vm = VmState::new(Vec::new(), Vec::new(), 100, 4096, 1, 100);
+    // An unreferenced object, which the dump lists all the same
+    vm.memory.allocate(4, TAG_STRING).unwrap();
+    let first = vm.memory.allocate(5, TAG_STRING).unwrap();
+    let second = vm.memory.allocate(3, TAG_STRING).unwrap();
+    let cell = vm.memory.allocate(32, TAG_LIST).unwrap();
+    store_slots(&mut vm, cell, &[first, second]);
+    let vector = vm.memory.allocate(32, TAG_VECTOR).unwrap();
+    store_slots(&mut vm, vector, &[cell, second]);
+    unsafe { vm.memory.mark_object(vector) };
+
+    let mut dump = Vec::new();
+    vm.dump_heap(&mut dump).unwrap();
+    let graph = analyze(dump.as_slice()).unwrap();
+
+    assert_eq!(graph.object_count(), 5);
+    assert_eq!(graph.used, vm.memory.next_free());
+    assert_eq!(
+        graph.edges(),
+        vec![
+            (cell.get(), first.get()),
+            (cell.get(), second.get()),
+            (vector.get(), cell.get()),
+            (vector.get(), second.get()),
+        ]
+    );
+    assert_eq!(
+        graph.referrers(second.get()),
+        vec![cell.get(), vector.get()]
+    );
+
+    let vector_object = graph.object(vector.get()).unwrap();
+    assert_eq!(vector_object.tag, TAG_VECTOR);
+    assert_eq!(vector_object.size, 32);
+    assert!(vector_object.marked);
+    assert!(!graph.object(cell.get()).unwrap().marked);
+    assert_eq!(graph.usage_by_tag()[&TAG_STRING], (3, 12));
+}
+
+#[test]
+fn test_dump_leaves_heap_untouched() {
+    let mut vm = VmState::new(Vec::new(), Vec::new(), 100, 4096, 1, 100);
+    vm.memory.allocate(16, TAG_VECTOR).unwrap();
+    let used = vm.memory.next_free();
+
+    vm.dump_heap(std::io::sink()).unwrap();
+    assert_eq!(vm.memory.next_free(), used);
+}
+
+#[test]
+fn test_malformed_dump_is_rejected() {
+    assert!(analyze("".as_bytes()).is_err());
+    assert!(analyze("heap-dump 2 0 0\n".as_bytes()).is_err());
+    assert!(analyze("heap-dump 1 64 16\n0 4 x 0\n".as_bytes()).is_err());
+}
@@ -0,0 +1,72 @@
+/// Test the configurable integer overflow behaviour of Add/Sub/Mul
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::{IntOverflowMode, VmState};
+
+fn vm_with_mode(instructions: Vec<OpCode>, mode: IntOverflowMode) -> VmState {
+    let mut vm = VmState::new(instructions, vec![], 100, 1024, 1, 100);
+    vm.int_overflow_mode = mode;
+    vm
+}
+
+fn max_plus_one() -> Vec<OpCode> {
+    vec![OpCode::Int(i64::MAX), OpCode::Int(1), OpCode::Add]
+}
+
+#[test]
+fn test_checked_mode_raises_overflow() {
+    let error = vm_with_mode(max_plus_one(), IntOverflowMode::Checked)
+        .run()
+        .unwrap_err();
+    assert!(matches!(error, VmError::ArithmeticOverflow { .. }));
+}
+
+#[test]
+fn test_wrapping_mode_wraps_to_min() {
+    let result = vm_with_mode(max_plus_one(), IntOverflowMode::Wrapping)
+        .run()
+        .unwrap();
+    assert_eq!(result, Value::Int(i64::MIN));
+}
+
+#[test]
+fn test_saturating_mode_clamps_to_max() {
+    let result = vm_with_mode(max_plus_one(), IntOverflowMode::Saturating)
+        .run()
+        .unwrap();
+    assert_eq!(result, Value::Int(i64::MAX));
+}
+
+#[test]
+fn test_modes_apply_to_sub_and_mul() {
+    let min_minus_one = vec![OpCode::Int(i64::MIN), OpCode::Int(1), OpCode::Sub];
+    let max_times_two = vec![OpCode::Int(i64::MAX), OpCode::Int(2), OpCode::Mul];
+
+    assert_eq!(
+        vm_with_mode(min_minus_one.clone(), IntOverflowMode::Wrapping)
+            .run()
+            .unwrap(),
+        Value::Int(i64::MAX)
+    );
+    assert_eq!(
+        vm_with_mode(min_minus_one, IntOverflowMode::Saturating)
+            .run()
+            .unwrap(),
+        Value::Int(i64::MIN)
+    );
+    assert_eq!(
+        vm_with_mode(max_times_two.clone(), IntOverflowMode::Wrapping)
+            .run()
+            .unwrap(),
+        Value::Int(-2)
+    );
+    assert!(vm_with_mode(max_times_two, IntOverflowMode::Checked)
+        .run()
+        .is_err());
+}
+
+#[test]
+fn test_default_mode_is_checked() {
+    let vm = VmState::new(vec![], vec![], 100, 1024, 1, 100);
+    assert_eq!(vm.int_overflow_mode, IntOverflowMode::Checked);
+}
@@ -0,0 +1,110 @@
+/// Test string concatenation, string length and list length opcodes
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::VmState;
+
+fn vm(program: Vec<OpCode>, constants: Vec<Value>) -> VmState {
+    VmState::new(program, constants, 1000, 4096, 1, 100)
+}
+
+/// Cons the integers 1..=n onto nil
+fn list_of(n: i64) -> Vec<OpCode> {
+    let mut program = vec![OpCode::Nil];
+    for i in (1..=n).rev() {
+        program.extend([OpCode::Int(i), OpCode::Swap, OpCode::Cons]);
+    }
+    program
+}
+
+#[test]
+fn test_concatenate_two_strings() {
+    let mut concat = vm(
+        vec![
+            OpCode::LoadString(0),
+            OpCode::LoadString(1),
+            OpCode::StrConcat,
+            OpCode::Dup,
+            OpCode::StrLen,
+            OpCode::Swap,
+            OpCode::Pop,
+        ],
+        vec![
+            Value::String("Result: ".to_string()),
+            Value::String("42".to_string()),
+        ],
+    );
+    assert_eq!(concat.run().unwrap(), Value::Int(10));
+
+    let mut concat = vm(
+        vec![
+            OpCode::LoadString(0),
+            OpCode::LoadString(1),
+            OpCode::StrConcat,
+        ],
+        vec![
+            Value::String("Result: ".to_string()),
+            Value::String("42".to_string()),
+        ],
+    );
+    assert_eq!(
+        concat.run().unwrap(),
+        Value::String("Result: 42".to_string())
+    );
+}
+
+#[test]
+fn test_length_of_empty_and_non_empty_lists() {
+    assert_eq!(
+        vm(vec![OpCode::Nil, OpCode::ListLen], vec![])
+            .run()
+            .unwrap(),
+        Value::Int(0)
+    );
+
+    // The last pair of each list is the first object allocated, at address 0
+    for n in [1, 2, 4] {
+        let mut program = list_of(n);
+        program.push(OpCode::ListLen);
+        assert_eq!(vm(program, vec![]).run().unwrap(), Value::Int(n));
+    }
+}
+
+#[test]
+fn test_length_operands_must_have_the_right_type() {
+    for program in [
+        vec![OpCode::Int(3), OpCode::ListLen],
+        vec![
+            OpCode::Int(1),
+            OpCode::Int(2),
+            OpCode::Cons,
+            OpCode::ListLen,
+        ],
+        // Improper lists whose cdrs encode like nil in an untagged word
+        vec![
+            OpCode::Int(1),
+            OpCode::Int(0),
+            OpCode::Cons,
+            OpCode::ListLen,
+        ],
+        vec![
+            OpCode::Int(1),
+            OpCode::LoadString(0),
+            OpCode::Cons,
+            OpCode::ListLen,
+        ],
+        vec![
+            OpCode::Int(1),
+            OpCode::Bool(false),
+            OpCode::Cons,
+            OpCode::ListLen,
+        ],
+        vec![OpCode::Nil, OpCode::StrLen],
+        vec![OpCode::LoadString(0), OpCode::Int(1), OpCode::StrConcat],
+    ] {
+        let result = vm(program.clone(), vec![Value::String("a".to_string())]).run();
+        assert!(
+            matches!(result, Err(VmError::TypeMismatch { .. })),
+            "{program:?}: {result:?}"
+        );
+    }
+}
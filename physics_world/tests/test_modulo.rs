@@ -0,0 +1,53 @@
+/// Test the integer remainder opcode
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::{VmError, VmState};
+
+fn remainder_vm(x: i64, y: i64) -> VmState {
+    let program = vec![OpCode::Int(x), OpCode::Int(y), OpCode::Mod];
+    VmState::new(program, vec![], 100, 1024, 1, 100)
+}
+
+#[test]
+fn test_mod_matches_rust_remainder() {
+    for (x, y) in [
+        (17, 5),
+        (15, 5),
+        (3, 7),
+        (-17, 5),
+        (17, -5),
+        (-17, -5),
+        (0, 3),
+    ] {
+        assert_eq!(
+            remainder_vm(x, y).run().unwrap(),
+            Value::Int(x % y),
+            "{x} % {y}"
+        );
+    }
+}
+
+#[test]
+fn test_mod_by_zero_is_division_by_zero() {
+    assert!(matches!(
+        remainder_vm(7, 0).run(),
+        Err(VmError::DivisionByZero { .. })
+    ));
+}
+
+#[test]
+fn test_mod_min_by_minus_one_overflows() {
+    assert!(matches!(
+        remainder_vm(i64::MIN, -1).run(),
+        Err(VmError::ArithmeticOverflow { .. })
+    ));
+}
+
+#[test]
+fn test_mod_rejects_floats() {
+    let program = vec![OpCode::Float(7.0), OpCode::Int(2), OpCode::Mod];
+    assert!(matches!(
+        VmState::new(program, vec![], 100, 1024, 1, 100).run(),
+        Err(VmError::TypeMismatch { .. })
+    ));
+    assert_eq!(OpCode::Mod.size_bytes(), 1);
+}
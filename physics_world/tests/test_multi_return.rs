@@ -0,0 +1,110 @@
+/// Test returning several values through CallN/RetN
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::opcodes::make_closure::create_closure_body;
+use physics_world::vm::VmState;
+
+/// Constant pool slot of `(divmod a b)`, which returns quotient and remainder
+const DIVMOD: usize = 0;
+/// Constant pool slot of a closure taking `divmod` and computing `q * 10 + r` of 17 and 5
+const CALLER: usize = 1;
+
+fn vm_with_functions(instructions: Vec<OpCode>, call_divmod: OpCode) -> VmState {
+    let mut vm = VmState::new(
+        instructions,
+        vec![Value::Nil, Value::Nil],
+        100,
+        2048,
+        1,
+        100,
+    );
+    let divmod = vec![
+        OpCode::GetLocal(0),
+        OpCode::GetLocal(1),
+        OpCode::Div,
+        OpCode::GetLocal(0),
+        OpCode::GetLocal(1),
+        OpCode::Mod,
+        OpCode::RetN(2),
+    ];
+    let caller = vec![
+        OpCode::Int(17),
+        OpCode::Int(5),
+        OpCode::GetLocal(0),
+        call_divmod,
+        OpCode::Swap,
+        OpCode::Int(10),
+        OpCode::Mul,
+        OpCode::Add,
+        OpCode::Ret,
+    ];
+    vm.constant_pool[DIVMOD] = Value::Closure(create_closure_body(&mut vm, divmod).unwrap());
+    vm.constant_pool[CALLER] = Value::Closure(create_closure_body(&mut vm, caller).unwrap());
+    vm
+}
+
+fn run_caller(call_divmod: OpCode) -> (VmState, Result<Value, VmError>) {
+    let mut vm = vm_with_functions(
+        vec![
+            OpCode::MakeClosure(DIVMOD, 0),
+            OpCode::MakeClosure(CALLER, 0),
+            OpCode::Call(1),
+        ],
+        call_divmod,
+    );
+    let result = vm.run();
+    (vm, result)
+}
+
+#[test]
+fn test_caller_consumes_both_results_without_allocating() {
+    let mut vm = vm_with_functions(
+        vec![
+            OpCode::MakeClosure(DIVMOD, 0),
+            OpCode::MakeClosure(CALLER, 0),
+            OpCode::Call(1),
+        ],
+        OpCode::CallN(2, 2),
+    );
+    vm.step().unwrap();
+    vm.step().unwrap();
+    let heap_after_closures = vm.memory.next_free();
+
+    assert_eq!(vm.run().unwrap(), Value::Int(32));
+    assert_eq!(vm.memory.next_free(), heap_after_closures);
+}
+
+#[test]
+fn test_results_replace_callee_stack() {
+    let mut vm = vm_with_functions(
+        vec![
+            OpCode::Int(17),
+            OpCode::Int(5),
+            OpCode::MakeClosure(DIVMOD, 0),
+            OpCode::CallN(2, 2),
+        ],
+        OpCode::CallN(2, 2),
+    );
+
+    assert_eq!(vm.run().unwrap(), Value::Int(2));
+    assert_eq!(vm.stack, vec![Value::Int(3), Value::Int(2)]);
+}
+
+#[test]
+fn test_return_count_must_match_call() {
+    let cases = [
+        (OpCode::Call(2), 1, 2),     // Plain calls expect a single result
+        (OpCode::CallN(2, 3), 3, 2), // Too few results
+    ];
+    for (call, expected_count, actual_count) in cases {
+        match run_caller(call).1 {
+            Err(VmError::ReturnCountMismatch {
+                expected, actual, ..
+            }) => {
+                assert_eq!(expected, expected_count);
+                assert_eq!(actual, actual_count);
+            }
+            other => panic!("Expected ReturnCountMismatch, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+/// Test the arena allocation failure strategies
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::{OnOutOfMemory, VmState};
+
+/// Bytes one cons cell takes in the arena, including alignment padding
+fn pair_footprint() -> u32 {
+    let mut vm = VmState::new(
+        vec![
+            OpCode::Int(1),
+            OpCode::Int(2),
+            OpCode::Cons,
+            OpCode::Int(3),
+            OpCode::Int(4),
+            OpCode::Cons,
+        ],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    );
+    let Ok(Value::Pair(second)) = vm.run() else {
+        panic!("Expected a pair");
+    };
+    second.get()
+}
+
+/// Allocate four pairs, discarding each one when `discard` is set
+fn four_pairs(discard: bool) -> Vec<OpCode> {
+    let mut program = Vec::new();
+    for i in 0..4 {
+        program.extend([OpCode::Int(i), OpCode::Int(i), OpCode::Cons]);
+        if discard {
+            program.push(OpCode::Pop);
+        }
+    }
+    program.push(OpCode::Int(0));
+    program
+}
+
+fn run_with(
+    program: Vec<OpCode>,
+    capacity: u32,
+    strategy: OnOutOfMemory,
+) -> (VmState, Result<Value, VmError>) {
+    let mut vm = VmState::new(program, vec![], 1000, capacity as usize, 1, 100);
+    vm.on_out_of_memory = strategy;
+    let result = vm.run();
+    (vm, result)
+}
+
+#[test]
+fn test_collect_and_retry_reclaims_garbage() {
+    let capacity = 3 * pair_footprint();
+
+    let (_, failed) = run_with(four_pairs(true), capacity, OnOutOfMemory::Fail);
+    assert!(matches!(failed, Err(VmError::MemoryLimitExceeded { .. })));
+
+    let (_, retried) = run_with(four_pairs(true), capacity, OnOutOfMemory::CollectAndRetry);
+    assert_eq!(retried.unwrap(), Value::Int(0));
+}
+
+#[test]
+fn test_collect_and_retry_fails_without_garbage() {
+    let capacity = 3 * pair_footprint();
+
+    let (vm, result) = run_with(four_pairs(false), capacity, OnOutOfMemory::CollectAndRetry);
+
+    assert!(matches!(result, Err(VmError::MemoryLimitExceeded { .. })));
+    assert_eq!(
+        vm.stack.len(),
+        5,
+        "three live pairs plus the failed cons operands"
+    );
+}
+
+#[test]
+fn test_grow_expands_up_to_ceiling() {
+    let footprint = pair_footprint();
+
+    let (vm, result) = run_with(
+        four_pairs(false),
+        3 * footprint,
+        OnOutOfMemory::Grow {
+            max_capacity: 4 * footprint,
+        },
+    );
+    assert_eq!(result.unwrap(), Value::Int(0));
+    assert_eq!(vm.memory.capacity(), 4 * footprint);
+
+    let (_, result) = run_with(
+        four_pairs(false),
+        2 * footprint,
+        OnOutOfMemory::Grow {
+            max_capacity: 3 * footprint,
+        },
+    );
+    assert!(matches!(result, Err(VmError::MemoryLimitExceeded { .. })));
+}
+
+/// Store a string too long to inline into a fresh vector and read it back
+fn set_long_string(garbage_pairs: i64) -> Vec<OpCode> {
+    let mut program = Vec::new();
+    for i in 0..garbage_pairs {
+        program.extend([OpCode::Int(i), OpCode::Int(i), OpCode::Cons, OpCode::Pop]);
+    }
+    program.extend([
+        OpCode::Int(0),
+        OpCode::MakeVector(1),
+        OpCode::Int(0),
+        OpCode::GetConst(0),
+        OpCode::VecSet,
+        OpCode::Int(0),
+        OpCode::VecGet,
+    ]);
+    program
+}
+
+#[test]
+fn test_collect_and_retry_while_boxing_keeps_the_vector() {
+    let long = Value::String("x".repeat(100));
+    let run = |garbage_pairs, capacity: u32| {
+        let mut vm = VmState::new(
+            set_long_string(garbage_pairs),
+            vec![long.clone()],
+            1000,
+            capacity as usize,
+            1,
+            100,
+        );
+        vm.on_out_of_memory = OnOutOfMemory::CollectAndRetry;
+        let result = vm.run();
+        (vm, result)
+    };
+    let (vm, result) = run(0, 4096);
+    assert_eq!(result.unwrap(), long);
+    let footprint = vm.memory.next_free();
+
+    // The garbage pairs leave room for the vector but not the boxed string,
+    // so boxing it collects them and moves the vector
+    let (_, result) = run(2, footprint + pair_footprint());
+    assert_eq!(result.unwrap(), long);
+}
+
+/// Call a closure built from a textual body after discarding some pairs
+fn call_closure(garbage_pairs: i64) -> Vec<OpCode> {
+    let mut program = Vec::new();
+    for i in 0..garbage_pairs {
+        program.extend([OpCode::Int(i), OpCode::Int(i), OpCode::Cons, OpCode::Pop]);
+    }
+    program.extend([OpCode::Int(5), OpCode::MakeClosure(0, 0), OpCode::Call(1)]);
+    program
+}
+
+#[test]
+fn test_collect_and_retry_while_making_a_closure() {
+    let body = Value::String("closure_body:[GetLocal(0), Int(1), Add, Ret]".to_string());
+    let run = |garbage_pairs, capacity: u32| {
+        let mut vm = VmState::new(
+            call_closure(garbage_pairs),
+            vec![body.clone()],
+            1000,
+            capacity as usize,
+            1,
+            100,
+        );
+        vm.on_out_of_memory = OnOutOfMemory::CollectAndRetry;
+        let result = vm.run();
+        (vm, result)
+    };
+    let (vm, result) = run(0, 4096);
+    assert_eq!(result.unwrap(), Value::Int(6));
+    let footprint = vm.memory.next_free();
+
+    // The garbage pairs fill the room the closure body and wrapper need
+    let (_, result) = run(2, footprint);
+    assert_eq!(result.unwrap(), Value::Int(6));
+}
@@ -0,0 +1,91 @@
+/// Test that the debugger's performance profile reports measured times
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::InstructionResult;
+use physics_world::vm::{VmDebugger, VmState};
+use std::time::{Duration, Instant};
+
+const ITERATIONS: i64 = 300;
+
+/// Cons the counter onto a list while counting it down to zero
+fn loop_vm() -> VmState {
+    let program = vec![
+        OpCode::Int(ITERATIONS),
+        OpCode::SetLocal(0),
+        OpCode::Nil,
+        OpCode::SetLocal(1),
+        // 4: loop while the counter is non-zero
+        OpCode::GetLocal(0),
+        OpCode::Int(0),
+        OpCode::Eq,
+        OpCode::JmpIfFalse(1),
+        OpCode::Jmp(9),
+        OpCode::GetLocal(0),
+        OpCode::GetLocal(1),
+        OpCode::Cons,
+        OpCode::SetLocal(1),
+        OpCode::GetLocal(0),
+        OpCode::Int(1),
+        OpCode::Sub,
+        OpCode::SetLocal(0),
+        OpCode::Jmp(-14),
+        // 18: done
+        OpCode::GetLocal(1),
+        OpCode::Car,
+    ];
+    VmState::new(program, vec![], 100_000, 1 << 16, 1, 100)
+}
+
+#[test]
+fn test_hottest_instruction_has_measured_time() {
+    let mut debugger = VmDebugger::new(loop_vm());
+    debugger.enable_profiling();
+
+    let started = Instant::now();
+    let result = loop {
+        if let InstructionResult::Finished(value) = debugger.step_with_debug().unwrap() {
+            break value;
+        }
+    };
+    let wall_clock = started.elapsed();
+    assert_eq!(result, Value::Int(1));
+
+    let profile = debugger.get_performance_profile();
+    let hottest = &profile.hotspots[0];
+    assert!(hottest.time_spent > Duration::ZERO);
+    assert!(hottest.time_spent <= wall_clock);
+    // Only instructions inside the loop run often enough to dominate
+    assert!(
+        hottest.execution_count >= ITERATIONS as u32,
+        "hottest: {hottest:?}"
+    );
+    assert!((4..=17).contains(&hottest.instruction_range.0));
+
+    assert!(profile
+        .hotspots
+        .windows(2)
+        .all(|pair| pair[0].time_spent >= pair[1].time_spent));
+    let total: Duration = profile.hotspots.iter().map(|h| h.time_spent).sum();
+    assert_eq!(profile.execution_time_ms, total.as_millis() as u64);
+    assert!(total <= wall_clock);
+
+    assert_eq!(profile.instruction_counts["Cons"], ITERATIONS as u32);
+    assert_eq!(
+        profile.instruction_counts["GetLocal"],
+        4 * ITERATIONS as u32 + 2
+    );
+    assert_eq!(profile.memory_operations, ITERATIONS as u32);
+}
+
+#[test]
+fn test_profile_is_empty_without_profiling() {
+    let mut debugger = VmDebugger::new(loop_vm());
+    while !matches!(
+        debugger.step_with_debug().unwrap(),
+        InstructionResult::Finished(_)
+    ) {}
+
+    let profile = debugger.get_performance_profile();
+    assert!(profile.hotspots.is_empty());
+    assert_eq!(profile.execution_time_ms, 0);
+    assert_eq!(profile.memory_operations, 0);
+}
@@ -0,0 +1,40 @@
+/// Test that u16 operands are read only from instructions that carry one
+use physics_world::types::OpCode;
+use physics_world::vm::state::{VmError, VmState};
+
+fn vm_with(program: Vec<OpCode>) -> VmState {
+    VmState::new(program, vec![], 100, 1024, 1, 100)
+}
+
+#[test]
+fn test_reads_operand_of_current_instruction() {
+    let mut vm = vm_with(vec![
+        OpCode::GetLocal(7),
+        OpCode::Call(2),
+        OpCode::Spawn { arg_count: 3 },
+    ]);
+    assert_eq!(vm.read_u16().unwrap(), 7);
+    assert_eq!(vm.read_u16().unwrap(), 2);
+    assert_eq!(vm.read_u16().unwrap(), 3);
+    assert_eq!(vm.ip, 3);
+}
+
+#[test]
+fn test_malformed_stream_is_unknown_opcode() {
+    // An Int is not a u16 immediate, even when it would fit or follows an
+    // instruction without operands
+    for program in [
+        vec![OpCode::Int(70_000)],
+        vec![OpCode::Int(5)],
+        vec![OpCode::Add, OpCode::Int(5)],
+        vec![OpCode::CallN(1, 2)],
+        vec![],
+    ] {
+        let mut vm = vm_with(program.clone());
+        assert!(
+            matches!(vm.read_u16(), Err(VmError::UnknownOpCode)),
+            "{program:?}"
+        );
+        assert_eq!(vm.ip, 0);
+    }
+}
@@ -0,0 +1,68 @@
+/// Test that recursion limit errors identify the recursing function and depth
+use physics_world::types::{HeapPtr, OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::VmState;
+
+/// Program `((lambda (f) (f f)) (lambda (f) (f f)))`: a closure that calls itself forever
+fn self_application_vm(max_recursion_depth: u32) -> VmState {
+    let closure_body = vec![
+        OpCode::GetLocal(0),
+        OpCode::GetLocal(0),
+        OpCode::Call(1),
+        OpCode::Ret,
+    ];
+    let main_program = vec![OpCode::MakeClosure(0, 0), OpCode::Dup, OpCode::Call(1)];
+    let mut vm = VmState::new(
+        main_program,
+        vec![Value::Closure(HeapPtr::new(0))],
+        10_000,
+        64 * 1024,
+        1,
+        max_recursion_depth,
+    );
+
+    // Store the serialized closure body in memory and point the constant pool at it
+    let serialized_body = bincode::serialize(&closure_body).unwrap();
+    let body_size = serialized_body.len() as u32;
+    let body_ptr = vm.memory.allocate(body_size + 4, 2).unwrap();
+    let body_data = unsafe { vm.memory.get_data_mut(body_ptr) };
+    body_data[0..4].copy_from_slice(&body_size.to_le_bytes());
+    body_data[4..].copy_from_slice(&serialized_body);
+    vm.constant_pool[0] = Value::Closure(body_ptr);
+    vm
+}
+
+#[test]
+fn test_recursion_limit_reports_function_and_depth() {
+    let mut vm = self_application_vm(8);
+
+    let error = vm.run().unwrap_err();
+    match &error {
+        VmError::RecursionLimitExceeded {
+            context,
+            limit,
+            current_depth,
+            function,
+        } => {
+            assert_eq!(*limit, 8);
+            assert_eq!(*current_depth, 9);
+            assert_eq!(context.call_stack_depth, 8);
+            assert!(function.as_deref().unwrap().starts_with("closure@"));
+        }
+        other => panic!("expected RecursionLimitExceeded, got {:?}", other),
+    }
+
+    let message = error.to_string();
+    assert!(message.contains("Depth 9 exceeds limit 8"));
+    assert!(message.contains("calling closure@"));
+    assert!(message.contains("from frame_7"));
+}
+
+#[test]
+fn test_recursion_within_limit_is_not_reported() {
+    // Run out of steps long before hitting a generous recursion limit
+    let mut vm = self_application_vm(1000);
+    vm.steps_remaining = 20;
+    let error = vm.run().unwrap_err();
+    assert!(!matches!(error, VmError::RecursionLimitExceeded { .. }));
+}
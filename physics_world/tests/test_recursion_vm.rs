@@ -1,7 +1,8 @@
 /// Physics World VM-level recursion tests
 /// These tests focus on the VM's ability to handle recursive function calls correctly
 use physics_world::types::{HeapPtr, OpCode, Value};
-use physics_world::vm::{Closure, EnvBinding, RecursiveEnvironment, VmState};
+use physics_world::vm::opcodes::closure_text::parse_closure_body;
+use physics_world::vm::{state, Closure, EnvBinding, RecursiveEnvironment, VmError, VmState};
 
 /// Test that the VM can handle simple recursive closures
 #[test]
@@ -138,6 +139,10 @@ fn test_vm_recursive_call_stack_management() {
                 println!("✅ VM recursive call stack management test waiting for capability");
                 return;
             }
+            Ok(physics_world::vm::InstructionResult::Spawn { .. }) => {
+                println!("✅ VM recursive call stack management test spawned an actor");
+                return;
+            }
             Err(_) => {
                 println!("✅ VM recursive call stack management test failed gracefully");
                 return;
@@ -788,3 +793,82 @@ fn test_closure_self_reference() {
     assert!(result.is_ok() || result.is_err());
     println!("✅ Closure self-reference test completed");
 }
+
+/// Textual body of `(lambda (n) (if (<= n 1) 1 (* n (fact (- n 1)))))`,
+/// recursing through `MakeClosure(0, 0)` on its own constant
+const FACT_BODY: &str = "closure_body:[GetLocal(0), Int(1), Lte, JmpIfFalse(2), Int(1), Ret, \
+     GetLocal(0), Int(1), Sub, MakeClosure(0, 0), Call(1), GetLocal(0), Mul, Ret]";
+
+fn vm_with_body(body: &str, argument: i64) -> VmState {
+    let bytecode = vec![
+        OpCode::Int(argument),
+        OpCode::MakeClosure(0, 0),
+        OpCode::Call(1),
+    ];
+    VmState::new(
+        bytecode,
+        vec![Value::String(body.to_string())],
+        1000,
+        4096,
+        1,
+        100,
+    )
+}
+
+/// Test that a string-encoded closure body is parsed and actually executed
+#[test]
+fn test_string_encoded_factorial_computes_result() {
+    assert_eq!(vm_with_body(FACT_BODY, 5).run().unwrap(), Value::Int(120));
+    assert_eq!(vm_with_body(FACT_BODY, 1).run().unwrap(), Value::Int(1));
+}
+
+/// Test that the body grammar accepts every operand shape
+#[test]
+fn test_parse_closure_body_grammar() {
+    let body = parse_closure_body(
+        "closure_body:[ Nil, Bool(true), Float(-1.5), CallN(2, 1), \
+         Spawn { arg_count: 3 }, HostCall { cap_idx: 0, func_id: 4, args: 2 } ]",
+    )
+    .unwrap();
+    assert_eq!(
+        body,
+        vec![
+            OpCode::Nil,
+            OpCode::Bool(true),
+            OpCode::Float(-1.5),
+            OpCode::CallN(2, 1),
+            OpCode::Spawn { arg_count: 3 },
+            OpCode::HostCall {
+                cap_idx: 0,
+                func_id: 4,
+                args: 2,
+            },
+        ]
+    );
+    assert_eq!(parse_closure_body("closure_body:[]").unwrap(), vec![]);
+}
+
+/// Test that malformed bodies are rejected instead of replaced by a default
+#[test]
+fn test_malformed_closure_body_is_type_mismatch() {
+    for body in [
+        "closure_body:[GetLocal(0), Frobnicate, Ret]",
+        "closure_body:[Int(1, 2)]",
+        "closure_body:[Int(x)]",
+        "closure_body:[Ret, Add(]",
+        "closure_body:GetLocal(0)",
+        "closure_body:[Spawn { count: 1 }]",
+    ] {
+        assert!(
+            matches!(parse_closure_body(body), Err(state::VmError::TypeMismatch)),
+            "{body} should not parse"
+        );
+        assert!(
+            matches!(
+                vm_with_body(body, 1).run(),
+                Err(VmError::TypeMismatch { .. })
+            ),
+            "{body} should fail MakeClosure"
+        );
+    }
+}
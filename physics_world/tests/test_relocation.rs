@@ -0,0 +1,87 @@
+/// Test rewriting every reference to a manually relocated heap object
+use physics_world::memory::{RelocationMap, TAG_VECTOR};
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::state::{CallFrame, VmState};
+
+#[test]
+fn test_relocate_all_rewrites_every_reference() {
+    // A one-element vector, and a pair whose car points at it. The empty
+    // vector keeps it off address 0, which heap words cannot point to.
+    let mut vm = VmState::new(
+        vec![
+            OpCode::MakeVector(0),
+            OpCode::Pop,
+            OpCode::Int(7),
+            OpCode::MakeVector(1),
+            OpCode::Dup,
+            OpCode::Int(0),
+            OpCode::Cons,
+        ],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    );
+    let Value::Pair(pair) = vm.run().unwrap() else {
+        panic!("Expected a pair");
+    };
+    let Value::Vector(old) = vm.stack[0] else {
+        panic!("Expected a vector, got {:?}", vm.stack[0]);
+    };
+    vm.stack.push(Value::Pair(pair));
+
+    // Reference the vector from every other place the VM keeps values
+    let vector = Value::Vector(old);
+    vm.constant_pool.push(vector.clone());
+    vm.top_level_locals.push(vector.clone());
+    let mut frame = CallFrame::new(0, 0, 0, 1, 0);
+    frame.locals.push(vector.clone());
+    frame.closed_over.insert(0, vector);
+    vm.call_stack.push(frame);
+
+    // Move the vector by hand and clobber its old copy
+    let size = unsafe { vm.memory.get_header(old) }.size;
+    let new = vm.memory.allocate(size, TAG_VECTOR).unwrap();
+    let bytes = unsafe { vm.memory.get_data(old) }.to_vec();
+    unsafe { vm.memory.get_data_mut(new) }.copy_from_slice(&bytes);
+    unsafe { vm.memory.get_data_mut(old) }.fill(0);
+
+    let map = RelocationMap::from([(old, new)]);
+    vm.relocate_all(&map);
+
+    let moved = Value::Vector(new);
+    assert_eq!(vm.stack, vec![moved.clone(), Value::Pair(pair)]);
+    assert_eq!(vm.constant_pool, vec![moved.clone()]);
+    assert_eq!(vm.top_level_locals, vec![moved.clone()]);
+    assert_eq!(vm.call_stack[0].locals, vec![moved.clone()]);
+    assert_eq!(vm.call_stack[0].closed_over[&0], moved);
+    // The car slot holds the pointer after its four-byte slot prefix
+    let car = unsafe { vm.memory.get_data(pair) }[4..8].to_vec();
+    assert_eq!(car, new.get().to_le_bytes());
+
+    // Reading through the rewritten reference sees the original element
+    vm.call_stack.clear();
+    vm.stack.truncate(1);
+    vm.instructions = vec![OpCode::Int(0), OpCode::VecGet];
+    vm.ip = 0;
+    assert_eq!(vm.run().unwrap(), Value::Int(7));
+}
+
+#[test]
+fn test_empty_map_changes_nothing() {
+    let mut vm = VmState::new(
+        vec![OpCode::Int(1), OpCode::MakeVector(1)],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    );
+    vm.run().unwrap();
+    let before = vm.stack.clone();
+
+    vm.relocate_all(&RelocationMap::new());
+
+    assert_eq!(vm.stack, before);
+}
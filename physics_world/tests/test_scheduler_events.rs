@@ -0,0 +1,117 @@
+/// Test the scheduler's structured event log
+use physics_world::scheduler::{
+    Actor, PhysicsScheduler, SchedulerEvent, SchedulerEventLog, TickResult,
+};
+use physics_world::types::{Capability, OpCode};
+use physics_world::vm::state::VmState;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+fn actor(id: u32, instructions: Vec<OpCode>) -> Actor {
+    Actor {
+        id,
+        vm: VmState::new(instructions, vec![], 100, 1024, id, 100),
+        mailbox: Vec::new(),
+        is_waiting: false,
+        capabilities: HashSet::new(),
+        capability_requests: Vec::new(),
+        parent_id: None,
+        priority: 128,
+        priority_boost: None,
+    }
+}
+
+/// Two actors that each yield once and then finish
+fn run_two_actors(scheduler: &mut PhysicsScheduler) {
+    scheduler.add_actor(actor(1, vec![OpCode::Yield, OpCode::Int(1)]));
+    scheduler.add_actor(actor(2, vec![OpCode::Yield, OpCode::Int(2)]));
+    for _ in 0..4 {
+        scheduler.tick().unwrap();
+    }
+}
+
+#[test]
+fn test_spawn_yield_and_terminate_events_are_ordered() {
+    let mut scheduler = PhysicsScheduler::new();
+    run_two_actors(&mut scheduler);
+
+    assert_eq!(
+        scheduler.events(),
+        &[
+            SchedulerEvent::ActorSpawned {
+                seq: 0,
+                actor_id: 1
+            },
+            SchedulerEvent::ActorSpawned {
+                seq: 1,
+                actor_id: 2
+            },
+            SchedulerEvent::ActorYielded {
+                seq: 2,
+                actor_id: 1
+            },
+            SchedulerEvent::ActorYielded {
+                seq: 3,
+                actor_id: 2
+            },
+            SchedulerEvent::ActorTerminated {
+                seq: 4,
+                actor_id: 1,
+                errored: false
+            },
+            SchedulerEvent::ActorTerminated {
+                seq: 5,
+                actor_id: 2,
+                errored: false
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_listener_sees_every_event_and_errors_are_terminations() {
+    let mut scheduler = PhysicsScheduler::new();
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&streamed);
+    scheduler.set_event_listener(Arc::new(move |event: &SchedulerEvent| {
+        sink.lock().unwrap().push(event.clone());
+    }));
+
+    scheduler.add_actor(actor(3, vec![OpCode::Add]));
+    assert!(matches!(
+        scheduler.tick().unwrap(),
+        TickResult::ActorErrored(3, _)
+    ));
+    scheduler.handle_capability_request(3, Capability::IoReadSensor, "sensor");
+
+    let streamed = streamed.lock().unwrap();
+    assert_eq!(streamed.as_slice(), scheduler.events());
+    assert_eq!(
+        streamed[1],
+        SchedulerEvent::ActorTerminated {
+            seq: 1,
+            actor_id: 3,
+            errored: true
+        }
+    );
+    assert_eq!(
+        streamed[2],
+        SchedulerEvent::CapabilityDecided {
+            seq: 2,
+            actor_id: 3,
+            capability: Capability::IoReadSensor,
+            granted: Some(true)
+        }
+    );
+}
+
+#[test]
+fn test_log_keeps_only_the_most_recent_events() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.event_log = SchedulerEventLog::with_capacity(3);
+    run_two_actors(&mut scheduler);
+
+    let seqs: Vec<u64> = scheduler.events().iter().map(SchedulerEvent::seq).collect();
+    assert_eq!(seqs, vec![3, 4, 5]);
+    assert!(scheduler.events().iter().all(|event| event.actor_id() != 0));
+}
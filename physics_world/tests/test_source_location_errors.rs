@@ -0,0 +1,47 @@
+use physics_world::types::OpCode;
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::VmState;
+use physics_world::vm::{SourceLocation, SourceMap};
+
+/// Program `(div 1 0)` on line 12, with the division itself at column 8
+fn division_by_zero_program() -> (Vec<OpCode>, SourceMap) {
+    let instructions = vec![OpCode::Int(1), OpCode::Int(0), OpCode::Div];
+    let mut source_map = SourceMap::new();
+    source_map.add_mapping(0, SourceLocation::new(12, 3));
+    source_map.add_mapping(2, SourceLocation::new(12, 8));
+    (instructions, source_map)
+}
+
+#[test]
+fn test_division_by_zero_reports_source_location() {
+    let (instructions, source_map) = division_by_zero_program();
+    let mut vm = VmState::new(instructions, vec![], 100, 1024, 1, 100);
+    vm.attach_source_map(source_map);
+
+    let error = vm.run().unwrap_err();
+    assert!(matches!(error, VmError::DivisionByZero { .. }));
+    assert_eq!(error.source_location(), Some(SourceLocation::new(12, 8)));
+    assert!(error.to_string().contains("line 12, col 8"));
+}
+
+#[test]
+fn test_error_without_source_map_has_no_location() {
+    let (instructions, _) = division_by_zero_program();
+    let mut vm = VmState::new(instructions, vec![], 100, 1024, 1, 100);
+
+    let error = vm.run().unwrap_err();
+    assert_eq!(error.source_location(), None);
+    assert!(!error.to_string().contains("line"));
+}
+
+#[test]
+fn test_unmapped_instruction_inherits_preceding_location() {
+    let mut source_map = SourceMap::new();
+    source_map.add_mapping(4, SourceLocation::new(3, 1));
+    source_map.add_mapping(10, SourceLocation::new(5, 7));
+
+    assert_eq!(source_map.lookup(2), None);
+    assert_eq!(source_map.lookup(4), Some(SourceLocation::new(3, 1)));
+    assert_eq!(source_map.lookup(9), Some(SourceLocation::new(3, 1)));
+    assert_eq!(source_map.lookup(42), Some(SourceLocation::new(5, 7)));
+}
@@ -0,0 +1,108 @@
+/// Test the Spawn opcode creating actors through the scheduler
+use physics_world::scheduler::{Actor, PhysicsScheduler, TickResult};
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::opcodes::closure::create_closure_body;
+use physics_world::vm::state::VmState;
+use std::collections::HashSet;
+
+/// Parent that spawns a child adding one to 20, then sends the child a message
+fn parent(capabilities: Vec<Capability>) -> Actor {
+    let main = vec![
+        OpCode::Int(20),
+        OpCode::MakeClosure(0, 0),
+        OpCode::Spawn { arg_count: 1 },
+        OpCode::Dup,
+        OpCode::Int(99),
+        OpCode::Send,
+    ];
+    let mut vm = VmState::new(main, Vec::new(), 1000, 4096, 1, 100);
+    let child_body = vec![
+        OpCode::GetLocal(0),
+        OpCode::Int(1),
+        OpCode::Add,
+        OpCode::Ret,
+    ];
+    let body = create_closure_body(&mut vm, child_body).unwrap();
+    vm.constant_pool.push(Value::Closure(body));
+    vm.capabilities = capabilities;
+
+    Actor {
+        id: 1,
+        vm,
+        mailbox: Vec::new(),
+        is_waiting: false,
+        capabilities: HashSet::new(),
+        capability_requests: Vec::new(),
+        parent_id: None,
+        priority: 128,
+        priority_boost: None,
+    }
+}
+
+#[test]
+fn test_spawned_actor_runs_its_closure() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(parent(vec![Capability::SysCreateActor]));
+
+    // The parent finishes holding the id it got back
+    let child_id = match scheduler.tick().unwrap() {
+        TickResult::ActorFinished(1, Value::ActorId(child_id)) => child_id,
+        other => panic!("unexpected tick result: {other:?}"),
+    };
+    assert_eq!(child_id, 2);
+    assert_eq!(scheduler.actors.len(), 2);
+    assert_eq!(scheduler.actors[1].id, child_id);
+    assert_eq!(scheduler.actors[1].parent_id, Some(1));
+    assert!(scheduler.actors[1].vm.capabilities.is_empty());
+
+    // The parent's Send is addressed to the child
+    assert_eq!(scheduler.message_queues[&child_id], vec![Value::Int(99)]);
+
+    match scheduler.tick().unwrap() {
+        TickResult::ActorFinished(id, value) => {
+            assert_eq!(id, child_id);
+            assert_eq!(value, Value::Int(21));
+        }
+        other => panic!("unexpected tick result: {other:?}"),
+    }
+}
+
+#[test]
+fn test_spawn_requires_create_actor_capability() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(parent(Vec::new()));
+
+    assert!(matches!(
+        scheduler.tick().unwrap(),
+        TickResult::ActorErrored(1, _)
+    ));
+    assert_eq!(scheduler.actors.len(), 1);
+}
+
+#[test]
+fn test_spawn_outside_scheduler_is_an_error() {
+    let mut vm = parent(vec![Capability::SysCreateActor]).vm;
+    assert!(vm.run().is_err());
+}
+
+#[test]
+fn test_ids_of_removed_actors_are_not_reused() {
+    let mut scheduler = PhysicsScheduler::new();
+    scheduler.add_actor(parent(vec![Capability::SysCreateActor]));
+    assert!(matches!(
+        scheduler.tick().unwrap(),
+        TickResult::ActorFinished(1, Value::ActorId(2))
+    ));
+
+    // The child leaves, e.g. by migrating to another node
+    scheduler.actors.retain(|actor| actor.id != 2);
+    let mut second = parent(vec![Capability::SysCreateActor]);
+    second.id = 0;
+    scheduler.add_actor(second);
+    scheduler.current_actor_index = 1;
+
+    assert!(matches!(
+        scheduler.tick().unwrap(),
+        TickResult::ActorFinished(0, Value::ActorId(3))
+    ));
+}
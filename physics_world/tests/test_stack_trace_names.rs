@@ -0,0 +1,59 @@
+/// Test stack trace symbolication from function names
+use physics_world::types::OpCode;
+use physics_world::vm::{FunctionNames, VmState};
+
+mod common;
+use common::load_bodies;
+
+/// `main` calls `outer` (constant 0), which calls `inner` (constant 1);
+/// the step limit stops execution inside `inner`
+fn stopped_in_inner(names: FunctionNames) -> VmState {
+    let main = vec![OpCode::MakeClosure(0, 0), OpCode::Call(0)];
+    let mut vm = VmState::new(main, vec![], 6, 4096, 1, 100);
+    load_bodies(
+        &mut vm,
+        vec![
+            vec![OpCode::MakeClosure(1, 0), OpCode::Call(0), OpCode::Ret],
+            vec![OpCode::Int(1), OpCode::Int(2), OpCode::Add, OpCode::Ret],
+        ],
+    );
+    vm.attach_function_names(names);
+    assert!(vm.run().is_err(), "execution should stop on the step limit");
+    assert_eq!(vm.call_stack.len(), 2);
+    vm
+}
+
+#[test]
+fn test_stack_trace_uses_function_names() {
+    let mut names = FunctionNames::new();
+    names.insert(0, "outer");
+    names.insert(1, "inner");
+    let vm = stopped_in_inner(names);
+
+    let trace = vm.get_formatted_stack_trace();
+
+    assert!(trace.contains("0: outer "), "{trace}");
+    assert!(trace.contains("1: inner "), "{trace}");
+    assert!(!trace.contains("function_"), "{trace}");
+}
+
+#[test]
+fn test_unnamed_frames_fall_back_to_placeholders() {
+    let vm = stopped_in_inner(FunctionNames::new());
+
+    let trace = vm.get_formatted_stack_trace();
+
+    assert!(trace.contains("0: function_2 "), "{trace}");
+}
+
+#[test]
+fn test_unnamed_body_is_not_given_a_named_frame() {
+    let mut names = FunctionNames::new();
+    names.insert(0, "outer");
+    let vm = stopped_in_inner(names);
+
+    let trace = vm.get_formatted_stack_trace();
+
+    assert!(trace.contains("0: outer "), "{trace}");
+    assert!(trace.contains("1: function_2 "), "{trace}");
+}
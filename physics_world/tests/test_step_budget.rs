@@ -0,0 +1,47 @@
+/// Test topping up a VM's step budget between runs
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::VmState;
+
+/// Sums 1 through 5 in nine instructions, plus one step to finish
+fn sum_program() -> Vec<OpCode> {
+    let mut instructions = vec![OpCode::Int(1)];
+    for n in 2..=5 {
+        instructions.extend([OpCode::Int(n), OpCode::Add]);
+    }
+    instructions
+}
+
+#[test]
+fn test_run_resumes_after_grant_steps() {
+    let mut vm = VmState::new(sum_program(), vec![], 4, 1024, 1, 100);
+
+    // The actor yields on budget exhaustion with its progress intact
+    assert!(matches!(vm.run(), Err(VmError::CpuLimitExceeded { .. })));
+    assert_eq!(vm.ip, 4);
+    assert_eq!(vm.stack, vec![Value::Int(3), Value::Int(3)]);
+
+    vm.grant_steps(2);
+    assert!(matches!(vm.run(), Err(VmError::CpuLimitExceeded { .. })));
+
+    vm.grant_steps(4);
+    assert_eq!(vm.run().unwrap(), Value::Int(15));
+}
+
+#[test]
+fn test_grant_steps_saturates() {
+    let mut vm = VmState::new(sum_program(), vec![], 10, 1024, 1, 100);
+    vm.grant_steps(u64::MAX);
+    assert_eq!(vm.steps_remaining, u64::MAX);
+}
+
+#[test]
+fn test_reset_steps_sets_budget() {
+    let mut vm = VmState::new(sum_program(), vec![], 1000, 1024, 1, 100);
+    vm.reset_steps(0);
+    assert!(matches!(vm.run(), Err(VmError::CpuLimitExceeded { .. })));
+
+    vm.reset_steps(10);
+    assert_eq!(vm.run().unwrap(), Value::Int(15));
+    assert_eq!(vm.steps_remaining, 0);
+}
@@ -0,0 +1,228 @@
+/// Test lazy thunk values and the Force opcode
+use physics_world::types::{Capability, OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::gc::{GcMode, HeapObject, Thunk};
+use physics_world::vm::VmState;
+
+mod common;
+use common::load_bodies;
+
+/// VM whose main calls `driver`, since a return to the top level ends the run
+fn run_driver(driver: Vec<OpCode>, thunk_body: Vec<OpCode>, steps: u64) -> (VmState, Value) {
+    let main = vec![OpCode::MakeClosure(0, 0), OpCode::Call(0)];
+    let mut vm = VmState::new(main, vec![], steps, 8192, 1, 100);
+    load_bodies(&mut vm, vec![driver, thunk_body]);
+    let result = vm.run().unwrap();
+    (vm, result)
+}
+
+/// Thunk body that never returns
+fn divergent() -> Vec<OpCode> {
+    vec![OpCode::Jmp(-1)]
+}
+
+#[test]
+fn test_unused_divergent_thunk_is_never_forced() {
+    let driver = vec![
+        OpCode::MakeClosure(1, 0),
+        OpCode::MakeThunk,
+        OpCode::Pop,
+        OpCode::Int(7),
+        OpCode::Ret,
+    ];
+
+    let (_, result) = run_driver(driver, divergent(), 100);
+    assert_eq!(result, Value::Int(7));
+}
+
+#[test]
+fn test_forcing_divergent_thunk_runs_it() {
+    let driver = vec![
+        OpCode::MakeClosure(1, 0),
+        OpCode::MakeThunk,
+        OpCode::Force,
+        OpCode::Ret,
+    ];
+    let main = vec![OpCode::MakeClosure(0, 0), OpCode::Call(0)];
+    let mut vm = VmState::new(main, vec![], 100, 8192, 1, 100);
+    load_bodies(&mut vm, vec![driver, divergent()]);
+
+    assert!(matches!(vm.run(), Err(VmError::CpuLimitExceeded { .. })));
+}
+
+#[test]
+fn test_force_memoizes_result() {
+    let body = vec![OpCode::Int(20), OpCode::Int(1), OpCode::Add, OpCode::Ret];
+    let force_once = vec![
+        OpCode::MakeClosure(1, 0),
+        OpCode::MakeThunk,
+        OpCode::Force,
+        OpCode::Dup,
+        OpCode::Add,
+        OpCode::Ret,
+    ];
+    let force_twice = vec![
+        OpCode::MakeClosure(1, 0),
+        OpCode::MakeThunk,
+        OpCode::Dup,
+        OpCode::Force,
+        OpCode::Swap,
+        OpCode::Force,
+        OpCode::Add,
+        OpCode::Ret,
+    ];
+
+    let (once, once_result) = run_driver(force_once, body.clone(), 1000);
+    let (twice, twice_result) = run_driver(force_twice, body, 1000);
+    assert_eq!(once_result, Value::Int(42));
+    assert_eq!(twice_result, Value::Int(42));
+
+    // Only the extra Swap and Force cost steps: the body does not run again
+    assert_eq!(once.steps_remaining, twice.steps_remaining + 2);
+    assert!(matches!(
+        twice.gc.heap.as_slice(),
+        [HeapObject::Thunk(Thunk::Forced(Value::Int(21)))]
+    ));
+}
+
+#[test]
+fn test_force_leaves_evaluated_values_alone() {
+    let mut vm = VmState::new(
+        vec![OpCode::Int(5), OpCode::Force],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    );
+    assert_eq!(vm.run().unwrap(), Value::Int(5));
+}
+
+#[test]
+fn test_make_thunk_requires_closure() {
+    let mut vm = VmState::new(
+        vec![OpCode::Int(5), OpCode::MakeThunk],
+        vec![],
+        100,
+        1024,
+        1,
+        100,
+    );
+    assert!(vm.run().is_err());
+}
+
+#[test]
+fn test_forced_thunk_is_unrooted_and_replaced_by_its_result() {
+    let body = vec![OpCode::Int(21), OpCode::Ret];
+    let driver = vec![
+        OpCode::MakeClosure(1, 0),
+        OpCode::MakeThunk,
+        OpCode::Dup,
+        OpCode::Force,
+        OpCode::Swap,
+        // The copy left behind now holds the result rather than the thunk
+        OpCode::IsInt,
+        OpCode::Swap,
+        OpCode::Pop,
+        OpCode::Ret,
+    ];
+
+    let (vm, result) = run_driver(driver, body, 1000);
+    assert_eq!(result, Value::Bool(true));
+    assert!(vm.gc.roots.is_empty());
+}
+
+#[test]
+fn test_pending_thunk_keeps_its_closure_across_heap_collection() {
+    let body = vec![OpCode::Int(42), OpCode::Ret];
+    let driver = vec![
+        // Garbage below the thunk's closure, so collection moves the closure
+        OpCode::MakeClosure(1, 0),
+        OpCode::Pop,
+        OpCode::MakeClosure(1, 0),
+        OpCode::MakeThunk,
+        OpCode::GcCollect,
+        OpCode::Pop,
+        // Reuse the space the closure was collected or moved from
+        OpCode::Int(1),
+        OpCode::Int(2),
+        OpCode::Cons,
+        OpCode::Pop,
+        OpCode::Force,
+        OpCode::Ret,
+    ];
+    let main = vec![OpCode::MakeClosure(0, 0), OpCode::Call(0)];
+    let mut vm = VmState::new(main, vec![], 1000, 8192, 1, 100);
+    vm.grant_capability(Capability::SysGc);
+    load_bodies(&mut vm, vec![driver, body]);
+
+    assert_eq!(vm.run().unwrap(), Value::Int(42));
+}
+
+#[test]
+fn test_thunk_forced_after_heap_compaction_runs_its_own_body() {
+    let driver = vec![
+        OpCode::MakeClosure(1, 0),
+        OpCode::MakeThunk,
+        OpCode::Force,
+        OpCode::MakeClosure(2, 0),
+        OpCode::MakeThunk,
+        // The third allocation collects the forced thunk, moving the second
+        OpCode::MakeClosure(3, 0),
+        OpCode::MakeThunk,
+        OpCode::Pop,
+        OpCode::Force,
+        OpCode::Add,
+        OpCode::Ret,
+    ];
+    let main = vec![OpCode::MakeClosure(0, 0), OpCode::Call(0)];
+    let mut vm = VmState::new(main, vec![], 1000, 8192, 1, 100);
+    vm.gc.allocation_threshold = 3;
+    load_bodies(
+        &mut vm,
+        vec![
+            driver,
+            vec![OpCode::Int(1), OpCode::Ret],
+            vec![OpCode::Int(20), OpCode::Ret],
+            vec![OpCode::Int(300), OpCode::Ret],
+        ],
+    );
+
+    assert_eq!(vm.run().unwrap(), Value::Int(21));
+    assert_eq!(vm.gc.gc_stats.collections, 1);
+}
+
+#[test]
+fn test_thunks_are_allocated_in_the_vms_gc_mode() {
+    let driver = vec![
+        OpCode::MakeClosure(1, 0),
+        OpCode::MakeThunk,
+        OpCode::Force,
+        OpCode::MakeClosure(2, 0),
+        OpCode::MakeThunk,
+        OpCode::Force,
+        OpCode::Add,
+        // The nursery holds two objects, so this runs a minor collection
+        OpCode::MakeClosure(3, 0),
+        OpCode::MakeThunk,
+        OpCode::Force,
+        OpCode::Add,
+        OpCode::Ret,
+    ];
+    let main = vec![OpCode::MakeClosure(0, 0), OpCode::Call(0)];
+    let mut vm = VmState::new(main, vec![], 1000, 8192, 1, 100);
+    vm.gc_mode = GcMode::Generational { nursery: 2 };
+    load_bodies(
+        &mut vm,
+        vec![
+            driver,
+            vec![OpCode::Int(1), OpCode::Ret],
+            vec![OpCode::Int(20), OpCode::Ret],
+            vec![OpCode::Int(300), OpCode::Ret],
+        ],
+    );
+
+    assert_eq!(vm.run().unwrap(), Value::Int(321));
+    assert_eq!(vm.gc.gc_stats.minor_collections, 1);
+    assert_eq!(vm.gc.gc_stats.major_collections, 0);
+}
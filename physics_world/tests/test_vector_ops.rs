@@ -0,0 +1,92 @@
+/// Test the indexed, mutable vector value type
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::VmError;
+use physics_world::vm::state::VmState;
+
+fn vm(instructions: Vec<OpCode>, constants: Vec<Value>) -> VmState {
+    VmState::new(instructions, constants, 100, 1024, 1, 100)
+}
+
+fn make_three() -> Vec<OpCode> {
+    vec![
+        OpCode::Int(10),
+        OpCode::Int(20),
+        OpCode::Int(30),
+        OpCode::MakeVector(3),
+    ]
+}
+
+#[test]
+fn test_get_set_middle_element() {
+    let mut instructions = make_three();
+    instructions.extend([
+        OpCode::Dup,
+        OpCode::Int(1),
+        OpCode::VecGet, // 20
+        OpCode::Swap,
+        OpCode::Int(1),
+        OpCode::Int(99),
+        OpCode::VecSet,
+        OpCode::Int(1),
+        OpCode::VecGet, // 99
+    ]);
+    let mut vm = vm(instructions, vec![]);
+
+    assert_eq!(vm.run().unwrap(), Value::Int(99));
+    assert_eq!(vm.stack, vec![Value::Int(20)]);
+}
+
+#[test]
+fn test_vec_len() {
+    let mut instructions = make_three();
+    instructions.push(OpCode::VecLen);
+    assert_eq!(vm(instructions, vec![]).run().unwrap(), Value::Int(3));
+
+    let empty = vec![OpCode::MakeVector(0), OpCode::VecLen];
+    assert_eq!(vm(empty, vec![]).run().unwrap(), Value::Int(0));
+}
+
+#[test]
+fn test_large_elements_round_trip() {
+    let text = "a string too long to fit inline in a slot";
+    let instructions = vec![
+        OpCode::Int(0),
+        OpCode::MakeVector(1),
+        OpCode::Int(0),
+        OpCode::LoadString(0),
+        OpCode::VecSet,
+        OpCode::Int(0),
+        OpCode::VecGet,
+    ];
+    let result = vm(instructions, vec![Value::String(text.to_string())]).run();
+    assert_eq!(result.unwrap(), Value::String(text.to_string()));
+}
+
+#[test]
+fn test_out_of_bounds_index_is_an_error() {
+    for index in [3, -1] {
+        let mut instructions = make_three();
+        instructions.extend([OpCode::Int(index), OpCode::VecGet]);
+
+        match vm(instructions, vec![]).run() {
+            Err(VmError::IndexOutOfBounds {
+                index: reported,
+                length,
+                ..
+            }) => {
+                assert_eq!(reported, index);
+                assert_eq!(length, 3);
+            }
+            other => panic!("Expected IndexOutOfBounds, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_vector_ops_reject_non_vectors() {
+    let instructions = vec![OpCode::Int(5), OpCode::VecLen];
+    assert!(matches!(
+        vm(instructions, vec![]).run(),
+        Err(VmError::TypeMismatch { .. })
+    ));
+}
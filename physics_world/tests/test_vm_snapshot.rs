@@ -0,0 +1,84 @@
+/// Test that a restored VM snapshot resumes exactly where the original was
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::error::SimpleVmError;
+use physics_world::vm::VmState;
+
+/// Sum 10 down to 1 while consing each counter onto a list, then add the
+/// head of the list to the sum: 55 + 1
+fn looping_vm() -> VmState {
+    let program = vec![
+        OpCode::Int(0),
+        OpCode::SetLocal(0), // sum
+        OpCode::Int(10),
+        OpCode::SetLocal(1), // counter
+        OpCode::Nil,
+        OpCode::SetLocal(2), // list
+        // 6: loop while counter != 0
+        OpCode::GetLocal(1),
+        OpCode::Int(0),
+        OpCode::Eq,
+        OpCode::JmpIfFalse(1),
+        OpCode::Jmp(13),
+        OpCode::GetLocal(0),
+        OpCode::GetLocal(1),
+        OpCode::Add,
+        OpCode::SetLocal(0),
+        OpCode::GetLocal(1),
+        OpCode::GetLocal(2),
+        OpCode::Cons,
+        OpCode::SetLocal(2),
+        OpCode::GetLocal(1),
+        OpCode::Int(1),
+        OpCode::Sub,
+        OpCode::SetLocal(1),
+        OpCode::Jmp(-18),
+        // 24: done
+        OpCode::GetLocal(2),
+        OpCode::Car,
+        OpCode::GetLocal(0),
+        OpCode::Add,
+    ];
+    VmState::new(program, vec![], 10_000, 4096, 1, 100)
+}
+
+#[test]
+fn test_restored_snapshot_finishes_with_same_value() {
+    let mut vm = looping_vm();
+    for _ in 0..60 {
+        vm.step().unwrap();
+    }
+    let snapshot = vm.snapshot().unwrap();
+
+    let mut restored = VmState::restore(&snapshot).unwrap();
+    assert_eq!(restored.ip, vm.ip);
+    assert_eq!(restored.steps_remaining, vm.steps_remaining);
+    assert_eq!(restored.stack, vm.stack);
+
+    let mut heap = Vec::new();
+    let mut restored_heap = Vec::new();
+    vm.dump_heap(&mut heap).unwrap();
+    restored.dump_heap(&mut restored_heap).unwrap();
+    assert_eq!(heap, restored_heap);
+
+    let original = vm.run().unwrap();
+    assert_eq!(original, Value::Int(56));
+    assert_eq!(restored.run().unwrap(), original);
+
+    // Restoring the same bytes again replays the same continuation
+    let mut replay = VmState::restore(&snapshot).unwrap();
+    assert_eq!(replay.run().unwrap(), original);
+    assert_eq!(replay.steps_remaining, restored.steps_remaining);
+}
+
+#[test]
+fn test_restore_rejects_invalid_bytes() {
+    let snapshot = looping_vm().snapshot().unwrap();
+    assert!(matches!(
+        VmState::restore(&snapshot[..snapshot.len() / 2]),
+        Err(SimpleVmError::InvalidSnapshot)
+    ));
+    assert!(matches!(
+        VmState::restore(b"not a snapshot"),
+        Err(SimpleVmError::InvalidSnapshot)
+    ));
+}
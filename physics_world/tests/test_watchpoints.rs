@@ -0,0 +1,117 @@
+/// Test that watchpoint expressions are evaluated against the running VM
+use physics_world::types::{OpCode, Value};
+use physics_world::vm::{Debugger, VmState, WatchScope, WatchpointTrigger};
+
+/// Count local 0 up from 0 by 25 four times, leaving each new value on the stack
+fn counting_vm() -> VmState {
+    let mut program = vec![OpCode::Int(0), OpCode::SetLocal(0)];
+    for _ in 0..4 {
+        program.extend([
+            OpCode::GetLocal(0),
+            OpCode::Int(25),
+            OpCode::Add,
+            OpCode::Dup,
+            OpCode::SetLocal(0),
+        ]);
+    }
+    program.push(OpCode::GetLocal(0));
+    VmState::new(program, vec![], 1000, 1024, 1, 100)
+}
+
+/// Step until a watchpoint fires, checking after every step
+fn step_until_trigger(vm: &mut VmState) -> (usize, Vec<WatchpointTrigger>) {
+    for steps in 1..=vm.instructions.len() {
+        vm.step().unwrap();
+        let triggers = vm.check_watchpoints();
+        if !triggers.is_empty() {
+            return (steps, triggers);
+        }
+    }
+    panic!("watchpoint never fired");
+}
+
+#[test]
+fn test_condition_watchpoint_fires_when_it_becomes_true() {
+    let mut vm = counting_vm();
+    vm.add_watchpoint("big", "stack[0] > 60");
+
+    let (steps, triggers) = step_until_trigger(&mut vm);
+
+    // The third Add pushes 75 on the stack
+    assert_eq!(steps, 15);
+    assert_eq!(vm.stack.last(), Some(&Value::Int(75)));
+    assert_eq!(triggers.len(), 1);
+    assert_eq!(triggers[0].name, "big");
+    assert_eq!(triggers[0].old_value, "25");
+    assert_eq!(triggers[0].new_value, "75");
+
+    // Still true on the next step: a condition only fires on the transition
+    vm.step().unwrap();
+    assert!(vm.check_watchpoints().is_empty());
+}
+
+#[test]
+fn test_local_arithmetic_watchpoint() {
+    let mut vm = counting_vm();
+    vm.add_watchpoint("double", "local[0] * 2 >= 100");
+
+    let (steps, triggers) = step_until_trigger(&mut vm);
+
+    // local[0] becomes 50 on the second SetLocal; the reported value is
+    // the left-hand side of the comparison
+    assert_eq!(steps, 12);
+    assert_eq!(triggers[0].old_value, "50");
+    assert_eq!(triggers[0].new_value, "100");
+}
+
+#[test]
+fn test_value_watchpoint_fires_on_change() {
+    let mut vm = counting_vm();
+    vm.add_watchpoint("counter", "local[0]");
+
+    let (steps, triggers) = step_until_trigger(&mut vm);
+
+    // Defined with 0 by the first SetLocal, first changed by the second
+    assert_eq!(steps, 7);
+    assert_eq!(triggers[0].old_value, "0");
+    assert_eq!(triggers[0].new_value, "25");
+}
+
+#[test]
+fn test_steps_remaining_and_unset_old_value() {
+    let mut debugger = Debugger::new();
+    debugger.add_watchpoint("budget", "steps_remaining < 10");
+    debugger.add_watchpoint("top", "stack[1] == 7");
+
+    let stack = [Value::Int(7), Value::Int(1)];
+    let triggers = debugger.check_watchpoints_in(&WatchScope {
+        stack: &stack,
+        locals: &[],
+        steps_remaining: 3,
+    });
+
+    // Both hold on the first check; neither had a value before
+    let names: Vec<_> = triggers.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["budget", "top"]);
+    assert_eq!(triggers[0].old_value, "<unset>");
+    assert_eq!(triggers[0].new_value, "3");
+    assert_eq!(triggers[1].new_value, "7");
+}
+
+#[test]
+fn test_unparsable_or_unreadable_expressions_never_fire() {
+    let mut debugger = Debugger::new();
+    debugger.add_watchpoint("syntax", "x + 1");
+    debugger.add_watchpoint("missing", "stack[5] > 0");
+    debugger.add_watchpoint("zero", "local[0] / 0 == 1");
+
+    for value in 0..3 {
+        let slot = [Value::Int(value)];
+        let triggers = debugger.check_watchpoints_in(&WatchScope {
+            stack: &slot,
+            locals: &slot,
+            steps_remaining: 100,
+        });
+        assert!(triggers.is_empty());
+    }
+}